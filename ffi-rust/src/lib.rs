@@ -12,11 +12,10 @@ mod direct_api;
 
 use smartnoise_validator::utilities::serial::{
     serialize_error, parse_release, serialize_release, parse_argument_properties,
-    serialize_value_properties, parse_indexmap_release_node, serialize_component_expansion
+    serialize_graph_properties, parse_indexmap_release_node, serialize_component_expansion
 };
 use crate::utilities::{ptr_to_buffer, buffer_to_ptr};
 use smartnoise_validator::base::Release;
-use std::collections::HashMap;
 use indexmap::map::IndexMap;
 
 /// FFI wrapper for [validate_analysis](../fn.validate_analysis.html)
@@ -315,12 +314,7 @@ pub extern "C" fn get_properties(
                     let (properties, warnings) = smartnoise_validator::get_properties(
                         privacy_definition, computation_graph, release, node_ids)?;
 
-                    Ok(proto::GraphProperties {
-                        properties: properties.into_iter()
-                            .map(|(node_id, properties)| (node_id, serialize_value_properties(properties)))
-                            .collect::<HashMap<u32, proto::ValueProperties>>(),
-                        warnings: warnings.into_iter().map(serialize_error).collect(),
-                    })
+                    Ok(serialize_graph_properties(properties, warnings))
                 };
 
                 match run() {
@@ -1,12 +1,14 @@
 use smartnoise_validator::errors::*;
 
 use crate::NodeArguments;
-use smartnoise_validator::base::{Value, Array, ReleaseNode};
+use smartnoise_validator::base::{Value, Array, Jagged, ReleaseNode, IndexKey};
 use crate::components::Evaluable;
 use ndarray::ArrayD;
 use smartnoise_validator::{proto, Float, Integer};
 use crate::utilities::noise;
-use smartnoise_validator::utilities::take_argument;
+use smartnoise_validator::utilities::{standardize_categorical_argument, take_argument};
+use crate::utilities::get_num_columns;
+use std::hash::Hash;
 
 
 impl Evaluable for proto::Cast {
@@ -16,14 +18,36 @@ impl Evaluable for proto::Cast {
             // if casting to bool, identify what value should map to true, then cast
             "bool" => {
                 let true_label = take_argument(&mut arguments, "true_label")?.array()?;
-                Ok(cast_bool(&data, &true_label)?.into())
+                if arguments.contains_key::<IndexKey>(&"false_label".into()) {
+                    let false_label = take_argument(&mut arguments, "false_label")?.array()?;
+                    // bool arrays cannot represent nulls, so the labeled data is released as float indicators,
+                    //    with NAN where neither label matched
+                    Ok(parse_bool(&data, &true_label, &false_label)?
+                        .mapv(|v| match v {
+                            Some(true) => 1.,
+                            Some(false) => 0.,
+                            None => Float::NAN
+                        }).into())
+                } else {
+                    Ok(cast_bool(&data, &true_label)?.into())
+                }
             },
             "float" | "real" => Ok(Value::Array(Array::Float(cast_float(&data)?))),
+            // label-encode each value as its position in the categories
+            "int" | "integer" if self.index => {
+                let categories = take_argument(&mut arguments, "categories")?.jagged()?;
+                Ok(match (data, categories) {
+                    (Array::Str(data), Jagged::Str(categories)) => cast_index(data, categories)?,
+                    (Array::Int(data), Jagged::Int(categories)) => cast_index(data, categories)?,
+                    (Array::Bool(data), Jagged::Bool(categories)) => cast_index(data, categories)?,
+                    _ => return Err("data and categories must share the same type, and must not be float".into())
+                }.into())
+            },
             "int" | "integer" => {
                 // TODO: handle different bounds on each column
                 let lower = take_argument(&mut arguments, "lower")?.array()?.first_int()?;
                 let upper = take_argument(&mut arguments, "upper")?.array()?.first_int()?;
-                Ok(cast_int(&data, lower, upper)?.into())
+                Ok(cast_int(&data, lower, upper, &self.overflow)?.into())
             },
             "string" | "str" =>
                 Ok(cast_str(&data)?.into()),
@@ -58,6 +82,40 @@ pub fn cast_bool(data: &Array, positive: &Array) -> Result<ArrayD<bool>> {
     }
 }
 
+/// Parse data as `bool` against explicit positive and negative classes.
+///
+/// Elements equal to `true_label` are mapped to `Some(true)`, elements equal to `false_label` to `Some(false)`,
+/// and all other elements to `None`.
+///
+/// # Arguments
+/// * `data` - Data to be parsed as `bool`.
+/// * `true_label` - Positive class (class to be mapped to `true`).
+/// * `false_label` - Negative class (class to be mapped to `false`).
+///
+/// # Return
+/// Data parsed as `bool`, with nulls where neither label matched.
+pub fn parse_bool(data: &Array, true_label: &Array, false_label: &Array) -> Result<ArrayD<Option<bool>>> {
+    fn compare<T: PartialEq + Clone>(data: &ArrayD<T>, true_label: &ArrayD<T>, false_label: &ArrayD<T>) -> Result<ArrayD<Option<bool>>> {
+        let true_label = true_label.first()
+            .ok_or_else(|| Error::from("true_label cannot be empty"))?;
+        let false_label = false_label.first()
+            .ok_or_else(|| Error::from("false_label cannot be empty"))?;
+        Ok(data.mapv(|v| if v == *true_label {
+            Some(true)
+        } else if v == *false_label {
+            Some(false)
+        } else { None }))
+    }
+
+    match (data, true_label, false_label) {
+        (Array::Str(data), Array::Str(t), Array::Str(f)) => compare(data, t, f),
+        (Array::Bool(data), Array::Bool(t), Array::Bool(f)) => compare(data, t, f),
+        (Array::Int(data), Array::Int(t), Array::Int(f)) => compare(data, t, f),
+        (Array::Float(data), Array::Float(t), Array::Float(f)) => compare(data, t, f),
+        _ => Err("data and labels must share the same type".into())
+    }
+}
+
 /// Cast data to type `f64`.
 ///
 /// If data are `bool`, map `true => 1.` and `false => 0.`
@@ -90,25 +148,107 @@ pub fn cast_float(data: &Array) -> Result<ArrayD<Float>> {
 ///
 /// If data are `f64`, round non-`NAN` values to their `i64` representation,
 /// impute uniform `i64` between `lower` and `upper` for values that are `NAN`.
+/// Values beyond the range of `i64` are handled by the `overflow` policy.
 ///
 /// # Arguments
 /// * `data` - Data to be cast to `i64`.
 /// * `lower` - Minimum allowable imputation value.
 /// * `upper` - Maximum allowable imputation value.
+/// * `overflow` - One of `error`, `saturate` or `wrap`.
 ///
 /// # Return
 /// Data cast to `i64`.
-pub fn cast_int(data: &Array, lower: Integer, upper: Integer) -> Result<ArrayD<Integer>> {
+///
+/// # Example
+/// ```
+/// use ndarray::arr1;
+/// use smartnoise_validator::base::Array;
+/// use smartnoise_runtime::components::cast::cast_int;
+/// let data = Array::Float(arr1(&[1e19]).into_dyn());
+///
+/// assert!(cast_int(&data, 0, 10, "error").is_err());
+/// assert_eq!(cast_int(&data, 0, 10, "saturate").unwrap(), arr1(&[i64::MAX]).into_dyn());
+/// assert_eq!(cast_int(&data, 0, 10, "wrap").unwrap(), arr1(&[(1e19 as u64) as i64]).into_dyn());
+/// ```
+pub fn cast_int(data: &Array, lower: Integer, upper: Integer, overflow: &str) -> Result<ArrayD<Integer>> {
     Ok(match data {
         Array::Str(data) => data
             .mapv(|v| v.parse::<Integer>().unwrap_or_else(|_| noise::sample_uniform_int(lower, upper).unwrap())),
-        Array::Float(data) => data
-            .mapv(|v| if !v.is_nan() {v.round() as Integer} else {noise::sample_uniform_int(lower, upper).unwrap()}),
+        Array::Float(data) => {
+            let overflow = overflow.to_lowercase();
+            ArrayD::from_shape_vec(data.shape(), data.iter()
+                .map(|v| if !v.is_nan() {round_int(*v, &overflow)} else {noise::sample_uniform_int(lower, upper)})
+                .collect::<Result<Vec<Integer>>>()?)?
+        },
         Array::Bool(data) => data.mapv(|v| if v {1} else {0}),
         Array::Int(data) => data.clone()
     })
 }
 
+/// Round a float to an `i64`, handling values beyond the range of `i64` by the `overflow` policy.
+fn round_int(value: Float, overflow: &str) -> Result<Integer> {
+    // 2^63, the smallest float beyond the range of i64
+    const LIMIT: Float = 9_223_372_036_854_775_808.;
+    let value = value.round();
+    if (-LIMIT..LIMIT).contains(&value) {
+        return Ok(value as Integer)
+    }
+
+    match overflow {
+        "error" => Err(format!("{} is beyond the range of i64", value).into()),
+        // float to int casts saturate at the limits of the range
+        "saturate" => Ok(value as Integer),
+        // floats beyond 2^63 are multiples of 2^11, so the remainder is exact
+        "wrap" => if value.is_finite() {
+            Ok(value.rem_euclid(2. * LIMIT) as u64 as Integer)
+        } else {
+            Err("infinite values cannot be wrapped into the range of i64".into())
+        },
+        _ => Err("overflow policy is not recognized. Must be one of \"error\", \"saturate\" or \"wrap\"".into())
+    }
+}
+
+/// Label-encode each column of data as the index of each value in the categories of the column.
+///
+/// # Arguments
+/// * `data` - Data to be encoded.
+/// * `categories` - For each column, the categories in the order they are encoded.
+///
+/// # Return
+/// Data cast to `i64`, where each value is in `[0, k - 1]` for a column with `k` categories.
+///
+/// # Example
+/// ```
+/// use ndarray::arr1;
+/// use smartnoise_runtime::components::cast::cast_index;
+/// let data = arr1(&["low", "high", "med"]).mapv(|v| v.to_string()).into_dyn();
+/// let categories = vec![vec!["low".to_string(), "med".to_string(), "high".to_string()]];
+///
+/// let encoded = cast_index(data, categories).unwrap();
+/// assert_eq!(encoded, arr1(&[0, 2, 1]).into_dyn());
+/// ```
+pub fn cast_index<T: Clone + Eq + Hash + Ord>(
+    data: ArrayD<T>, categories: Vec<Vec<T>>
+) -> Result<ArrayD<Integer>> {
+    let num_columns = get_num_columns(&data)?;
+    let mut encoded = ArrayD::<Integer>::zeros(data.shape());
+
+    // iterate over the generalized columns
+    encoded.gencolumns_mut().into_iter()
+        // pair generalized columns with the data and categories
+        .zip(data.gencolumns().into_iter())
+        .zip(standardize_categorical_argument(categories, num_columns)?)
+        .try_for_each(|((mut encoded, column), categories)| encoded.iter_mut()
+            .zip(column.iter())
+            .try_for_each(|(encoded, v)| {
+                *encoded = categories.iter().position(|category| category == v)
+                    .ok_or_else(|| Error::from("data contains values outside of the categories"))? as Integer;
+                Ok::<_, Error>(())
+            }))?;
+
+    Ok(encoded)
+}
+
 /// Cast data to type `String`.
 ///
 /// Regardless of data type, simply convert to `String`.
@@ -125,4 +265,23 @@ pub fn cast_str(data: &Array) -> Result<ArrayD<String>> {
         Array::Bool(data) => data.mapv(|v| v.to_string()),
         Array::Int(data) => data.mapv(|v| v.to_string())
     })
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod test_cast {
+    use ndarray::arr1;
+    use smartnoise_validator::base::Array;
+
+    use crate::components::cast::parse_bool;
+
+    #[test]
+    fn test_parse_bool() {
+        let data = Array::Str(arr1(&["yes", "no", "maybe"]).mapv(|v| v.to_string()).into_dyn());
+        let true_label = Array::Str(arr1(&["yes".to_string()]).into_dyn());
+        let false_label = Array::Str(arr1(&["no".to_string()]).into_dyn());
+
+        let parsed = parse_bool(&data, &true_label, &false_label).unwrap();
+        assert_eq!(parsed.into_raw_vec(), vec![Some(true), Some(false), None]);
+    }
+}
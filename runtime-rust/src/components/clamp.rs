@@ -13,7 +13,13 @@ impl Evaluable for proto::Clamp {
     fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
         // if categories argument was provided, clamp data as if they are categorical (regardless of atomic type)
         if arguments.contains_key::<IndexKey>(&"categories".into()) {
-            match (take_argument(&mut arguments, "data")?, take_argument(&mut arguments, "categories")?, take_argument(&mut arguments, "null_value")?) {
+            // elements outside of the category set are mapped to otherwise, falling back to null_value
+            let otherwise = if arguments.contains_key::<IndexKey>(&"otherwise".into()) {
+                take_argument(&mut arguments, "otherwise")?
+            } else {
+                take_argument(&mut arguments, "null_value")?
+            };
+            match (take_argument(&mut arguments, "data")?, take_argument(&mut arguments, "categories")?, otherwise) {
                 (Value::Array(data), Value::Jagged(categories), Value::Array(nulls)) => Ok(match (data, categories, nulls) {
                     (Array::Bool(data), Jagged::Bool(categories), Array::Bool(nulls)) =>
                         clamp_categorical(data, categories, nulls)?.into(),
@@ -33,8 +39,13 @@ impl Evaluable for proto::Clamp {
         else {
             match (take_argument(&mut arguments, "data")?, take_argument(&mut arguments, "lower")?, take_argument(&mut arguments, "upper")?) {
                 (Value::Array(data), Value::Array(lower), Value::Array(upper)) => Ok(match (data, lower, upper) {
-                    (Array::Float(data), Array::Float(lower), Array::Float(upper)) =>
-                        clamp_numeric_float(data, lower, upper)?.into(),
+                    (Array::Float(data), Array::Float(lower), Array::Float(upper)) => {
+                        // nulls are out of range under the error policy, rather than silently passed through
+                        if self.null_policy.to_lowercase() == "error" && data.iter().any(|v| v.is_nan()) {
+                            return Err("data: may not contain nulls when the null policy is `Error`".into())
+                        }
+                        clamp_numeric_float(data, lower, upper)?.into()
+                    },
                     (Array::Int(data), Array::Int(lower), Array::Int(upper)) =>
                         clamp_numeric_integer(data, lower, upper)?.into(),
                     _ => return Err("data, lower, and upper must all have type f64".into())
@@ -1,12 +1,13 @@
 use smartnoise_validator::errors::*;
 
 use crate::NodeArguments;
-use smartnoise_validator::base::{Value, Array, ReleaseNode};
+use smartnoise_validator::base::{Value, Array, Jagged, ReleaseNode};
 use crate::components::Evaluable;
 use ndarray::{ArrayD, Axis, arr0};
 use smartnoise_validator::{proto, Integer};
 use smartnoise_validator::utilities::take_argument;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use itertools::Itertools;
 use crate::utilities::get_num_columns;
 use std::iter::FromIterator;
 use std::hash::Hash;
@@ -15,6 +16,23 @@ use noisy_float::types::n64;
 
 impl Evaluable for proto::Count {
     fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        if self.group_by {
+            let categories = take_argument(&mut arguments, "categories")?.jagged()?;
+            return Ok(ReleaseNode::new(match (take_argument(&mut arguments, "data")?.array()?, categories) {
+                (Array::Bool(data), Jagged::Bool(categories)) => count_by_cells(&data, categories)?.into(),
+                (Array::Int(data), Jagged::Int(categories)) => count_by_cells(&data, categories)?.into(),
+                (Array::Str(data), Jagged::Str(categories)) => count_by_cells(&data, categories)?.into(),
+                _ => return Err("data and categories must share the same type, and must not be float".into())
+            }))
+        }
+
+        if self.count_true {
+            return Ok(ReleaseNode::new(match take_argument(&mut arguments, "data")?.array()? {
+                Array::Bool(data) => count_true(&data)?.into(),
+                _ => return Err("data: atomic type must be bool to count true values".into())
+            }))
+        }
+
         Ok(ReleaseNode::new(if self.distinct {
             match take_argument(&mut arguments, "data")?.array()? {
                 Array::Bool(data) => count_distinct(&data)?.into(),
@@ -60,6 +78,68 @@ pub fn count<T>(data: &ArrayD<T>) -> Result<ArrayD<Integer>> {
     Ok(ndarray::Array::from_shape_vec(vec![], vec![data.len_of(Axis(0)) as Integer])?)
 }
 
+/// Gets number of true values in the data.
+///
+/// # Arguments
+/// * `data` - Boolean data for which you want a count of true values.
+///
+/// # Return
+/// Number of true values in data.
+///
+/// # Example
+/// ```
+/// use ndarray::{arr0, arr1};
+/// use smartnoise_runtime::components::count::count_true;
+/// let data = arr1(&[true, false, true]).into_dyn();
+/// let n = count_true(&data).unwrap();
+/// assert_eq!(n, arr0(2).into_dyn());
+/// ```
+pub fn count_true(data: &ArrayD<bool>) -> Result<ArrayD<Integer>> {
+    Ok(ndarray::Array::from_shape_vec(vec![], vec![data.iter().filter(|v| **v).count() as Integer])?)
+}
+
+/// Gets number of rows in each cell of the cross product of the categories of each column.
+///
+/// Cells are ordered as the cartesian product of the categories, where the first column varies slowest.
+///
+/// # Arguments
+/// * `data` - Data for which you want a count of each cell.
+/// * `categories` - For each column, the categories of the column.
+///
+/// # Return
+/// Number of rows in each cell.
+///
+/// # Example
+/// ```
+/// use ndarray::{arr1, arr2};
+/// use smartnoise_runtime::components::count::count_by_cells;
+/// let data = arr2(&[ [1, 1], [1, 2], [2, 2], [1, 2] ]).into_dyn();
+/// let counts = count_by_cells(&data, vec![vec![1, 2], vec![1, 2]]).unwrap();
+/// assert_eq!(counts, arr1(&[1, 2, 0, 1]).into_dyn());
+/// ```
+pub fn count_by_cells<T: Eq + Hash + Clone>(data: &ArrayD<T>, categories: Vec<Vec<T>>) -> Result<ArrayD<Integer>> {
+    if data.ndim() > 2 {
+        return Err("invalid data shape for Count".into())
+    }
+    if categories.len() as i64 != get_num_columns(&data)? {
+        return Err("categories must be defined for each column of data".into())
+    }
+
+    let cells = categories.into_iter().multi_cartesian_product()
+        .enumerate().map(|(index, cell)| (cell, index))
+        .collect::<HashMap<Vec<T>, usize>>();
+
+    let mut counts = vec![0 as Integer; cells.len()];
+    data.outer_iter().try_for_each(|row| {
+        let index = cells.get(&row.iter().cloned().collect::<Vec<T>>())
+            .ok_or_else(|| Error::from("data contains a row outside of the categories"))?;
+        counts[*index] += 1;
+        Ok::<_, Error>(())
+    })?;
+
+    Ok(ndarray::Array::from(counts).into_dyn())
+}
+
 /// Gets number of unique values in the data.
 ///
 /// # Arguments
@@ -7,7 +7,6 @@ use crate::components::Evaluable;
 use ndarray::{ArrayD, Array};
 
 use smartnoise_validator::{proto, Float};
-use crate::components::mean::mean;
 use ndarray::prelude::*;
 use std::iter::FromIterator;
 
@@ -62,23 +61,15 @@ impl Evaluable for proto::Covariance {
 /// ```
 pub fn matrix_covariance(data: &ArrayD<Float>, delta_degrees_of_freedom: usize) -> Result<Vec<Vec<Float>>> {
 
-    let means: Vec<Float> = mean(&data)?.iter().cloned().collect();
-
-    let mut covariances: Vec<Vec<Float>> = Vec::new();
-    data.gencolumns().into_iter().enumerate()
-        .for_each(|(left_i, left_col)| {
-            let mut col_covariances: Vec<Float> = Vec::new();
-            data.gencolumns().into_iter().enumerate()
-                .filter(|(right_i, _right_col)| &left_i <= right_i)
-                .for_each(|(right_i, right_col)|
-                    col_covariances.push(covariance(
-                        &left_col, &right_col,
-                        means[left_i], means[right_i],
-                        delta_degrees_of_freedom)));
-            covariances.push(col_covariances);
-        });
-
-    Ok(covariances)
+    let columns = data.gencolumns().into_iter()
+        .map(|column| standardize(&column))
+        .collect::<Result<Vec<Standardized>>>()?;
+
+    columns.iter().enumerate()
+        .map(|(left_i, left_col)| columns.iter().skip(left_i)
+            .map(|right_col| standardized_covariance(left_col, right_col, delta_degrees_of_freedom))
+            .collect::<Result<Vec<Float>>>())
+        .collect()
 }
 
 /// Construct cross-covariance matrix from pair of data matrices.
@@ -116,22 +107,20 @@ pub fn matrix_cross_covariance(
     delta_degrees_of_freedom: usize
 ) -> Result<ArrayD<Float>> {
 
-    let left_means: Vec<Float> = mean(&left)?.iter().cloned().collect();
-    let right_means: Vec<Float> = mean(&right)?.iter().cloned().collect();
-
-    let covariances = left.gencolumns().into_iter()
-        .zip(left_means.iter())
-        .flat_map(|(column_left, mean_left)|
-            right.gencolumns().into_iter()
-                .zip(right_means.iter())
-                .map(|(column_right, mean_right)| covariance(
-                    &column_left, &column_right,
-                    *mean_left, *mean_right,
-                delta_degrees_of_freedom))
-                .collect::<Vec<Float>>())
-        .collect::<Vec<Float>>();
-
-    match Array::from_shape_vec((left_means.len(), right_means.len()), covariances) {
+    let left_columns = left.gencolumns().into_iter()
+        .map(|column| standardize(&column))
+        .collect::<Result<Vec<Standardized>>>()?;
+    let right_columns = right.gencolumns().into_iter()
+        .map(|column| standardize(&column))
+        .collect::<Result<Vec<Standardized>>>()?;
+
+    let covariances = left_columns.iter()
+        .flat_map(|column_left| right_columns.iter()
+            .map(move |column_right| standardized_covariance(
+                column_left, column_right, delta_degrees_of_freedom)))
+        .collect::<Result<Vec<Float>>>()?;
+
+    match Array::from_shape_vec((left_columns.len(), right_columns.len()), covariances) {
         Ok(array) => Ok(array.into_dyn()),
         Err(_) => Err("unable to form cross-covariance matrix".into())
     }
@@ -142,8 +131,6 @@ pub fn matrix_cross_covariance(
 /// # Arguments
 /// * `left` - One of the two arrays for which you want the covariance.
 /// * `right` - One of the two arrays for which you want the covariance.
-/// * `mean_left` - Arithmetic mean of the left array.
-/// * `mean_right` - Arithmetic mean of the right array.
 /// * `delta_degrees_of_freedom` - 0 for population, 1 for finite sample correction
 ///
 /// # Return
@@ -156,18 +143,70 @@ pub fn matrix_cross_covariance(
 ///
 /// let left = arr1(&[1.,2.,3.]);
 /// let right = arr1(&[4.,5.,6.]);
-/// let mean_left = 2.;
-/// let mean_right = 5.;
-/// let cov = covariance(&left.view(), &right.view(), mean_left, mean_right, 1);
+/// let cov = covariance(&left.view(), &right.view(), 1).unwrap();
 /// assert_eq!(cov, 1.);
 /// ```
 pub fn covariance(
     left: &ArrayView1<Float>, right: &ArrayView1<Float>,
-    mean_left: Float, mean_right: Float,
     delta_degrees_of_freedom: usize
-) -> Float {
-    left.iter()
-        .zip(right)
-        .fold(0., |sum, (val_left, val_right)|
-            sum + ((val_left - mean_left) * (val_right - mean_right))) / ( (left.len() - delta_degrees_of_freedom) as Float)
-}
\ No newline at end of file
+) -> Result<Float> {
+    standardized_covariance(&standardize(left)?, &standardize(right)?, delta_degrees_of_freedom)
+}
+
+/// Deviations of a column from its mean, in units of the scale of the column.
+pub struct Standardized {
+    deviations: Vec<Float>,
+    scale: Float,
+}
+
+/// Shift a column by the midpoint of its range, and scale it by half of its range.
+///
+/// Every standardized value is within [-1, 1], so the mean does not lose the precision of values
+/// that are small relative to the magnitude of the data, and products of deviations cannot overflow.
+pub fn standardize(column: &ArrayView1<Float>) -> Result<Standardized> {
+    if column.is_empty() {
+        return Err("attempted covariance of an empty column".into())
+    }
+    let (min, max) = column.iter()
+        .fold((Float::INFINITY, Float::NEG_INFINITY), |(min, max), v| (min.min(*v), max.max(*v)));
+
+    // halved before differencing, so that the range of the widest columns does not overflow
+    let midpoint = min / 2. + max / 2.;
+    let scale = match max / 2. - min / 2. {
+        scale if scale > 0. => scale,
+        // every value is the same
+        _ => 1.
+    };
+
+    let values = column.iter().map(|v| (v - midpoint) / scale).collect::<Vec<Float>>();
+    let mean = values.iter().sum::<Float>() / values.len() as Float;
+    Ok(Standardized {
+        deviations: values.into_iter().map(|v| v - mean).collect(),
+        scale,
+    })
+}
+
+/// Covariance of two standardized columns, in the units of the original columns.
+///
+/// Errors if there are no more records than degrees of freedom, or if the covariance is too large to be represented.
+pub fn standardized_covariance(
+    left: &Standardized, right: &Standardized,
+    delta_degrees_of_freedom: usize
+) -> Result<Float> {
+    let num_records = left.deviations.len();
+    if num_records <= delta_degrees_of_freedom {
+        return Err(format!("covariance requires more than {} records, but the data has {}",
+                           delta_degrees_of_freedom, num_records).into())
+    }
+
+    let covariance = left.deviations.iter()
+        .zip(right.deviations.iter())
+        .fold(0., |sum, (val_left, val_right)| sum + val_left * val_right)
+        / (num_records - delta_degrees_of_freedom) as Float
+        * left.scale * right.scale;
+
+    if covariance.is_infinite() {
+        return Err("covariance overflows the range of a float".into())
+    }
+    Ok(covariance)
+}
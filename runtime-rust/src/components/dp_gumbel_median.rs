@@ -8,7 +8,7 @@ use smartnoise_validator::utilities::take_argument;
 
 use crate::components::Evaluable;
 use crate::NodeArguments;
-use crate::utilities::noise;
+use crate::utilities::{argmax_uniform_ties, get_release_precision, noise, round_to_precision};
 
 impl Evaluable for proto::DpGumbelMedian {
     fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
@@ -22,11 +22,16 @@ impl Evaluable for proto::DpGumbelMedian {
         let lower = take_argument(&mut arguments, "lower")?.array()?.first_float()?;
         let upper = take_argument(&mut arguments, "upper")?.array()?.first_float()?;
 
-        let enforce_constant_time = privacy_definition.as_ref()
-            .ok_or_else(|| Error::from("privacy_definition must be known"))?
-            .protect_elapsed_time;
+        let release_precision = get_release_precision(privacy_definition)?;
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| Error::from("privacy_definition must be known"))?;
+        let enforce_constant_time = privacy_definition.protect_elapsed_time;
 
         let median = dp_gumbel_median(data, epsilon, lower, upper, enforce_constant_time)?;
+        // the median is released within the public bounds, and rounding may not move it outside of them
+        let median = num::clamp(
+            round_to_precision(median as f64, release_precision) as Float,
+            lower, upper);
 
         Ok(ReleaseNode {
             value: median.into(),
@@ -55,24 +60,21 @@ fn dp_gumbel_median(
         .collect::<Vec<_>>();
     z_clipped.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
 
-    let mut max_noisy_score = f64::NEG_INFINITY;
-    let mut arg_max_noisy_score: usize = 0;
+    let noisy_scores = (1..z_clipped.len())
+        .map(|i| {
+            let length = z_clipped[i] - z_clipped[i - 1];
+            let dist_from_median = (i as Float - (z_clipped.len() as Float / 2.0)).abs().ceil();
 
-    for i in 1..z_clipped.len() {
-        let length = z_clipped[i] - z_clipped[i - 1];
-        let dist_from_median = (i as Float - (z_clipped.len() as Float / 2.0)).abs().ceil();
+            // This term makes the score *very* sensitive to changes in epsilon
+            let score = length.ln() - (epsilon / 2.0) * dist_from_median;
 
-        // This term makes the score *very* sensitive to changes in epsilon
-        let score = length.ln() - (epsilon / 2.0) * dist_from_median;
-
-        let noise_term = noise::sample_gumbel(0.0, 1.0); // gumbel1(&rng, 0.0, 1.0);
-        let noisy_score: Float = score + noise_term;
+            let noise_term = noise::sample_gumbel(0.0, 1.0); // gumbel1(&rng, 0.0, 1.0);
+            score + noise_term
+        })
+        .collect::<Vec<Float>>();
 
-        if noisy_score > max_noisy_score {
-            max_noisy_score = noisy_score;
-            arg_max_noisy_score = i;
-        }
-    }
+    // intervals of zero length have a score of negative infinity, and may tie
+    let arg_max_noisy_score = argmax_uniform_ties(&noisy_scores)? + 1;
 
     let left = z_clipped[arg_max_noisy_score - 1];
     let right = z_clipped[arg_max_noisy_score];
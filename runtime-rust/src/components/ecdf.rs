@@ -0,0 +1,57 @@
+use smartnoise_validator::errors::*;
+
+use crate::NodeArguments;
+use smartnoise_validator::base::{Array, ReleaseNode};
+use crate::components::Evaluable;
+use ndarray::{ArrayD, Axis};
+
+use smartnoise_validator::{proto, Float};
+use smartnoise_validator::utilities::take_argument;
+
+
+impl Evaluable for proto::Ecdf {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        Ok(ReleaseNode::new(match (take_argument(&mut arguments, "data")?.array()?, take_argument(&mut arguments, "thresholds")?.array()?) {
+            (Array::Float(data), Array::Float(thresholds)) =>
+                ecdf(&data, &thresholds)?.into(),
+            (Array::Int(data), Array::Int(thresholds)) =>
+                ecdf(&data, &thresholds)?.into(),
+            _ => return Err("data and thresholds must be homogeneously typed and numeric".into())
+        }))
+    }
+}
+
+/// Fraction of records strictly below each threshold.
+///
+/// # Arguments
+/// * `data` - Data with a single column.
+/// * `thresholds` - Points at which to evaluate the empirical cdf.
+///
+/// # Return
+/// One fraction for each threshold.
+///
+/// # Example
+/// ```
+/// use ndarray::arr1;
+/// use smartnoise_runtime::components::ecdf::ecdf;
+/// let data = arr1(&[1, 2, 3, 4]).into_dyn();
+/// let fractions = ecdf(&data, &arr1(&[0, 3, 5]).into_dyn()).unwrap();
+/// assert_eq!(fractions, arr1(&[0., 0.5, 1.]).into_dyn());
+/// ```
+pub fn ecdf<T: PartialOrd>(data: &ArrayD<T>, thresholds: &ArrayD<T>) -> Result<ArrayD<Float>> {
+    let num_records = data.len_of(Axis(0));
+    if num_records == 0 {
+        return Err("data: the ecdf of an empty dataset is undefined".into())
+    }
+
+    let fractions = thresholds.iter()
+        .map(|threshold| data.iter().filter(|v| *v < threshold).count() as Float / num_records as Float)
+        .collect::<Vec<Float>>();
+
+    // ensure the ecdf is of correct dimension
+    Ok(match data.ndim() {
+        1 => ndarray::Array::from_shape_vec(vec![fractions.len()], fractions),
+        2 => ndarray::Array::from_shape_vec(vec![fractions.len(), 1], fractions),
+        _ => return Err("invalid data shape for Ecdf".into())
+    }?)
+}
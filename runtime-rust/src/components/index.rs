@@ -17,6 +17,17 @@ impl Evaluable for proto::Index {
     fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
         let data = take_argument(&mut arguments, "data")?;
 
+        // positions of the rows to select, if only some rows are selected
+        let rows = if let Ok(rows) = take_argument(&mut arguments, "rows") {
+            Some(to_name_vec(rows.array()?.int()?)?.into_iter()
+                .map(|row| row as usize).collect::<Vec<usize>>())
+        } else if let Ok(row_mask) = take_argument(&mut arguments, "row_mask") {
+            Some(row_mask.array()?.bool()?.iter().enumerate()
+                .filter(|(_, mask)| **mask)
+                .map(|(idx, _)| idx)
+                .collect::<Vec<usize>>())
+        } else { None };
+
         let dimensionality;
 
         let is_partition = if let Value::Partitions(_) = &data {true} else {false};
@@ -73,6 +84,9 @@ impl Evaluable for proto::Index {
                         .filter(|(_, mask)| *mask)
                         .map(|(idx, _)| idx)
                         .collect::<Vec<usize>>()
+                } else if rows.is_some() {
+                    // only rows are selected, so every column is retained
+                    return select_rows(Value::Array(array), rows)
                 } else {
                     return Err("indices or mask must be supplied when indexing on arrays".into())
                 };
@@ -99,10 +113,31 @@ impl Evaluable for proto::Index {
             }
         };
 
-        Ok(ReleaseNode::new(indexed))
+        select_rows(indexed, rows)
     }
 }
 
+/// Select rows of an indexed array by position.
+fn select_rows(indexed: Value, rows: Option<Vec<usize>>) -> Result<ReleaseNode> {
+    let rows = match rows {
+        Some(rows) => rows,
+        None => return Ok(ReleaseNode::new(indexed))
+    };
+
+    let array = indexed.array()?;
+    let num_records = array.num_records()?;
+    if rows.iter().any(|row| *row >= num_records) {
+        return Err("rows: index out of bounds".into())
+    }
+
+    Ok(ReleaseNode::new(match array {
+        Array::Int(data) => data.select(Axis(0), &rows).into(),
+        Array::Float(data) => data.select(Axis(0), &rows).into(),
+        Array::Bool(data) => data.select(Axis(0), &rows).into(),
+        Array::Str(data) => slow_select(&data, Axis(0), &rows).into(),
+    }))
+}
+
 pub fn mask_columns(column_names: &[IndexKey], mask: &[bool]) -> Result<Vec<IndexKey>> {
     if mask.len() != column_names.len() {
         return Err("boolean mask must be the same length as the column names".into());
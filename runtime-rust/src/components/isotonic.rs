@@ -0,0 +1,93 @@
+use smartnoise_validator::errors::*;
+
+use crate::NodeArguments;
+use smartnoise_validator::base::ReleaseNode;
+use smartnoise_validator::utilities::take_argument;
+use crate::components::Evaluable;
+use ndarray::ArrayD;
+use smartnoise_validator::{proto, Float};
+
+impl Evaluable for proto::Isotonic {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        Ok(ReleaseNode::new(isotonic(
+            take_argument(&mut arguments, "data")?.array()?.cast_float()?
+        )?.into()))
+    }
+}
+
+/// Projects each column of the data onto the non-decreasing vectors.
+///
+/// # Arguments
+/// * `data` - Data to make monotone.
+///
+/// # Return
+/// Least-squares non-decreasing fit of each column.
+///
+/// # Example
+/// ```
+/// use ndarray::arr1;
+/// use smartnoise_runtime::components::isotonic::isotonic;
+/// let data = arr1(&[1., 3., 2., 4.]).into_dyn();
+/// let fit = isotonic(data).unwrap();
+/// assert_eq!(fit, arr1(&[1., 2.5, 2.5, 4.]).into_dyn());
+/// ```
+pub fn isotonic(mut data: ArrayD<Float>) -> Result<ArrayD<Float>> {
+    if data.ndim() > 2 {
+        return Err("invalid data shape for Isotonic".into())
+    }
+    data.gencolumns_mut().into_iter().for_each(|mut column| {
+        let fit = pool_adjacent_violators(&column.to_vec());
+        column.iter_mut().zip(fit.into_iter()).for_each(|(v, fit)| *v = fit);
+    });
+    Ok(data)
+}
+
+/// Least-squares non-decreasing fit of a vector, via the pool-adjacent-violators algorithm.
+///
+/// Values are merged into blocks, each represented by its mean and size.
+/// Whenever the mean of the newest block falls below the mean of the preceding block, the two blocks are pooled.
+pub fn pool_adjacent_violators(values: &[Float]) -> Vec<Float> {
+    let mut blocks: Vec<(Float, usize)> = Vec::with_capacity(values.len());
+
+    for value in values {
+        let (mut mean, mut size) = (*value, 1);
+        while let Some((prior_mean, prior_size)) = blocks.last().cloned() {
+            if prior_mean <= mean { break }
+            mean = (prior_mean * prior_size as Float + mean * size as Float) / (prior_size + size) as Float;
+            size += prior_size;
+            blocks.pop();
+        }
+        blocks.push((mean, size));
+    }
+
+    blocks.into_iter()
+        .flat_map(|(mean, size)| std::iter::repeat(mean).take(size))
+        .collect()
+}
+
+#[cfg(test)]
+mod test_isotonic {
+    use ndarray::arr1;
+    use crate::components::isotonic::isotonic;
+
+    #[test]
+    fn test_isotonic_noisy_cdf() {
+        let cdf = arr1(&[0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8]).into_dyn();
+        let noisy = arr1(&[0.12, 0.18, 0.33, 0.29, 0.52, 0.47, 0.71, 0.83]).into_dyn();
+        assert!(noisy.iter().zip(noisy.iter().skip(1)).any(|(l, r)| l > r));
+
+        let fit = isotonic(noisy.clone()).unwrap();
+
+        // the fit is monotone
+        assert!(fit.iter().zip(fit.iter().skip(1)).all(|(l, r)| l <= r));
+
+        // the projection onto a convex set containing the cdf is no further from the cdf than the input
+        let distance = |l: &ndarray::ArrayD<f64>, r: &ndarray::ArrayD<f64>| l.iter().zip(r.iter())
+            .map(|(l, r)| (l - r).powi(2)).sum::<f64>().sqrt();
+        assert!(distance(&fit, &cdf) <= distance(&noisy, &cdf));
+
+        // the fit stays close to the input, and only moves pooled values
+        assert!(fit.iter().zip(noisy.iter()).all(|(f, n)| (f - n).abs() < 0.03));
+        assert!((fit[[1]] - 0.18).abs() < 1e-10);
+    }
+}
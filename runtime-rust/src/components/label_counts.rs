@@ -0,0 +1,42 @@
+use smartnoise_validator::errors::*;
+
+use indexmap::indexmap;
+
+use crate::NodeArguments;
+use smartnoise_validator::base::{Value, Array, ReleaseNode};
+use crate::components::Evaluable;
+
+use smartnoise_validator::proto;
+use smartnoise_validator::utilities::take_argument;
+use crate::utilities::to_nd;
+
+impl Evaluable for proto::LabelCounts {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let counts = take_argument(&mut arguments, "data")?.array()?;
+        let categories = take_argument(&mut arguments, "categories")?.array()?;
+
+        if counts.num_records()? != categories.num_records()? {
+            return Err("categories must contain one label for each count".into())
+        }
+
+        // the counts of a single column are a vector, aligned with the vector of labels
+        let counts: Value = match counts {
+            Array::Float(counts) => to_nd(counts, 1)?.into(),
+            Array::Int(counts) => to_nd(counts, 1)?.into(),
+            _ => return Err("counts must be numeric".into())
+        };
+
+        // labels estimated by a mechanism may be a column rather than a vector
+        let categories: Value = match categories {
+            Array::Float(categories) => to_nd(categories, 1)?.into(),
+            Array::Int(categories) => to_nd(categories, 1)?.into(),
+            Array::Bool(categories) => to_nd(categories, 1)?.into(),
+            Array::Str(categories) => to_nd(categories, 1)?.into(),
+        };
+
+        Ok(ReleaseNode::new(Value::Dataframe(indexmap![
+            "category".into() => categories,
+            "count".into() => counts
+        ])))
+    }
+}
@@ -1,14 +1,22 @@
-use ndarray::{arr0};
+use std::collections::BTreeMap;
+
+use indexmap::indexmap;
+use itertools::Itertools;
+use ndarray::{arr0, arr1, Array1, ArrayD, Ix2};
 
 use smartnoise_validator::{Float, Integer, proto};
-use smartnoise_validator::base::{Array, ReleaseNode, Value};
+use smartnoise_validator::base::{Array, Jagged, ReleaseNode, Value};
+use smartnoise_validator::components::hadamard_response::hadamard_debias_scale;
+use smartnoise_validator::components::matrix_mechanism::{pseudo_inverse, strategy_sensitivity};
+use smartnoise_validator::components::stability_mechanism::stability_parameters;
 use smartnoise_validator::errors::*;
-use smartnoise_validator::utilities::{array::broadcast_ndarray, privacy::{get_delta, get_epsilon, spread_privacy_usage}, take_argument};
+use smartnoise_validator::utilities::{array::broadcast_ndarray, privacy::{compose_privacy_usages, get_delta, get_epsilon, spread_privacy_usage}, take_argument};
 
 use crate::components::Evaluable;
+use crate::components::cast::cast_index;
 use crate::NodeArguments;
 use crate::utilities;
-use crate::utilities::{get_num_columns, to_nd, get_num_rows};
+use crate::utilities::{get_num_columns, to_nd, get_num_rows, round_to_precision};
 use crate::utilities::mechanisms::exponential_mechanism;
 
 impl Evaluable for proto::LaplaceMechanism {
@@ -17,20 +25,22 @@ impl Evaluable for proto::LaplaceMechanism {
         privacy_definition: &Option<proto::PrivacyDefinition>,
         mut arguments: NodeArguments
     ) -> Result<ReleaseNode> {
+        // a seeded release is reproducible, and is only for debugging and testing
+        let seed = utilities::take_seed(&mut arguments)?;
 
         let enforce_constant_time = privacy_definition.as_ref()
             .map(|v| v.protect_elapsed_time).unwrap_or(false);
+        let release_precision = utilities::get_release_precision(privacy_definition)?;
 
         let mut data = take_argument(&mut arguments, "data")?.array()?.cast_float()?;
         let num_columns = get_num_columns(&data)?;
         let num_rows = get_num_rows(&data)?;
 
-        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.cast_float()?;
-        let sens_num_columns = get_num_columns(&data)?;
-        let sens_num_rows = get_num_rows(&data)?;
-        if num_columns != sens_num_columns {
-            return Err(Error::from(format!("data has {:?} columns, while the expected shape has {:?} columns. This is likely an error from substituting data into the graph.", num_columns, sens_num_columns)))
-        }
+        let sensitivity = take_argument(&mut arguments, "sensitivity")?;
+        sensitivity.assert_num_columns(num_columns as usize)
+            .chain_err(|| "sensitivity must share the shape of data. This is likely an error from substituting data into the graph.")?;
+        let sensitivity = sensitivity.array()?.cast_float()?;
+        let sens_num_rows = get_num_rows(&sensitivity)?;
         if num_rows != sens_num_rows {
             return Err(Error::from(format!("data has {:?} rows, while the expected shape has {:?} rows. This is likely an error from substituting data into the graph.", num_rows, sens_num_rows)))
         }
@@ -42,7 +52,7 @@ impl Evaluable for proto::LaplaceMechanism {
         let usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
         let epsilon = usages.iter().map(get_epsilon).collect::<Result<Vec<f64>>>()?;
 
-        data.gencolumns_mut().into_iter()
+        utilities::with_seed(seed, || data.gencolumns_mut().into_iter()
             .zip(sensitivity.gencolumns().into_iter().zip(epsilon.into_iter()))
             .try_for_each(|(mut data_column, (sensitivity, epsilon))| data_column.iter_mut()
                 .zip(sensitivity.iter())
@@ -50,8 +60,9 @@ impl Evaluable for proto::LaplaceMechanism {
 
                     utilities::mechanisms::laplace_mechanism(
                         *v as Float, epsilon, *sens as f64,
+                        Some(self.max_noise_sigmas).filter(|sigmas| *sigmas > 0.),
                         enforce_constant_time,
-                    ).map(|noise| *v = noise as Float)))?;
+                    ).map(|noise| *v = round_to_precision(noise, release_precision) as Float))))?;
 
         Ok(ReleaseNode {
             value: data.into(),
@@ -67,20 +78,22 @@ impl Evaluable for proto::GaussianMechanism {
         privacy_definition: &Option<proto::PrivacyDefinition>,
         mut arguments: NodeArguments
     ) -> Result<ReleaseNode> {
+        // a seeded release is reproducible, and is only for debugging and testing
+        let seed = utilities::take_seed(&mut arguments)?;
 
         let enforce_constant_time = privacy_definition.as_ref()
             .map(|v| v.protect_elapsed_time).unwrap_or(false);
+        let release_precision = utilities::get_release_precision(privacy_definition)?;
 
         let mut data = take_argument(&mut arguments, "data")?.array()?.cast_float()?;
         let num_columns = get_num_columns(&data)?;
         let num_rows = get_num_rows(&data)?;
 
-        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.cast_float()?;
-        let sens_num_columns = get_num_columns(&data)?;
-        let sens_num_rows = get_num_rows(&data)?;
-        if num_columns != sens_num_columns {
-            return Err(Error::from(format!("data has {:?} columns, while the expected shape has {:?} columns. This is likely an error from substituting data into the graph.", num_columns, sens_num_columns)))
-        }
+        let sensitivity = take_argument(&mut arguments, "sensitivity")?;
+        sensitivity.assert_num_columns(num_columns as usize)
+            .chain_err(|| "sensitivity must share the shape of data. This is likely an error from substituting data into the graph.")?;
+        let sensitivity = sensitivity.array()?.cast_float()?;
+        let sens_num_rows = get_num_rows(&sensitivity)?;
         if num_rows != sens_num_rows {
             return Err(Error::from(format!("data has {:?} rows, while the expected shape has {:?} rows. This is likely an error from substituting data into the graph.", num_rows, sens_num_rows)))
         }
@@ -93,7 +106,7 @@ impl Evaluable for proto::GaussianMechanism {
         let epsilon = usages.iter().map(get_epsilon).collect::<Result<Vec<f64>>>()?;
         let delta = usages.iter().map(get_delta).collect::<Result<Vec<f64>>>()?;
 
-        data.gencolumns_mut().into_iter()
+        utilities::with_seed(seed, || data.gencolumns_mut().into_iter()
             .zip(sensitivity.gencolumns().into_iter())
             .zip(epsilon.into_iter().zip(delta.into_iter()))
             .try_for_each(|((mut data_column, sensitivity), (epsilon, delta))| data_column.iter_mut()
@@ -102,8 +115,9 @@ impl Evaluable for proto::GaussianMechanism {
 
                     utilities::mechanisms::gaussian_mechanism(
                         *v as Float, epsilon, delta, *sens as f64, self.analytic,
+                        Some(self.max_noise_sigmas).filter(|sigmas| *sigmas > 0.),
                         enforce_constant_time,
-                    ).map(|noise| *v = noise as Float)))?;
+                    ).map(|noise| *v = round_to_precision(noise, release_precision) as Float))))?;
 
         Ok(ReleaseNode {
             value: data.into(),
@@ -113,8 +127,63 @@ impl Evaluable for proto::GaussianMechanism {
     }
 }
 
+impl Evaluable for proto::MatrixMechanism {
+    fn evaluate(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        mut arguments: NodeArguments
+    ) -> Result<ReleaseNode> {
+
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+        let release_precision = utilities::get_release_precision(privacy_definition)?;
+
+        let data = take_argument(&mut arguments, "data")?.array()?.cast_float()?;
+        if get_num_columns(&data)? != 1 {
+            return Err("data must be a single column".into())
+        }
+        let num_dimensions = data.ndim();
+        let data = Array1::from(data.iter().copied().collect::<Vec<Float>>());
+
+        let workload = take_argument(&mut arguments, "workload")?.array()?.cast_float()?
+            .into_dimensionality::<Ix2>()?;
+        let strategy = take_argument(&mut arguments, "strategy")?.array()?.cast_float()?
+            .into_dimensionality::<Ix2>()?;
+
+        // the sensitivity of the data, scaled by the largest change of the strategy answers from a change in one element
+        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.cast_float()?
+            .iter().copied().fold(0., Float::max)
+            * strategy_sensitivity(&strategy);
+
+        let usages = spread_privacy_usage(&self.privacy_usage, 1)?;
+        let epsilon = get_epsilon(&usages[0])?;
+
+        // privatize the strategy answers
+        let strategy_answers = strategy.dot(&data).iter()
+            .map(|v| utilities::mechanisms::laplace_mechanism(
+                *v, epsilon, sensitivity, None, enforce_constant_time))
+            .collect::<Result<Vec<Float>>>()?;
+
+        // answer the workload on the least squares reconstruction of the data
+        let estimate = pseudo_inverse(&strategy)?.dot(&Array1::from(strategy_answers));
+        let answers = workload.dot(&estimate).into_dyn()
+            .mapv(|v| round_to_precision(v as f64, release_precision) as Float);
+
+        Ok(ReleaseNode {
+            value: match num_dimensions {
+                1 => answers,
+                _ => answers.into_shape(vec![workload.nrows(), 1])?
+            }.into(),
+            privacy_usages: Some(usages),
+            public: true,
+        })
+    }
+}
+
 impl Evaluable for proto::SimpleGeometricMechanism {
     fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        // a seeded release is reproducible, and is only for debugging and testing
+        let seed = utilities::take_seed(&mut arguments)?;
 
         let enforce_constant_time = privacy_definition.as_ref()
             .map(|v| v.protect_elapsed_time).unwrap_or(false);
@@ -123,12 +192,11 @@ impl Evaluable for proto::SimpleGeometricMechanism {
         let num_columns = get_num_columns(&data)?;
         let num_rows = get_num_rows(&data)?;
 
-        let sensitivity = take_argument(&mut arguments, "sensitivity")?.array()?.int()?;
-        let sens_num_columns = get_num_columns(&data)?;
-        let sens_num_rows = get_num_rows(&data)?;
-        if num_columns != sens_num_columns {
-            return Err(Error::from(format!("data has {:?} columns, while the expected shape has {:?} columns. This is likely an error from substituting data into the graph.", num_columns, sens_num_columns)))
-        }
+        let sensitivity = take_argument(&mut arguments, "sensitivity")?;
+        sensitivity.assert_num_columns(num_columns as usize)
+            .chain_err(|| "sensitivity must share the shape of data. This is likely an error from substituting data into the graph.")?;
+        let sensitivity = sensitivity.array()?.int()?;
+        let sens_num_rows = get_num_rows(&sensitivity)?;
         if num_rows != sens_num_rows {
             return Err(Error::from(format!("data has {:?} rows, while the expected shape has {:?} rows. This is likely an error from substituting data into the graph.", num_rows, sens_num_rows)))
         }
@@ -145,7 +213,7 @@ impl Evaluable for proto::SimpleGeometricMechanism {
         let upper = broadcast_ndarray(
             take_argument(&mut arguments, "upper")?.array()?.int()?, data.shape())?;
 
-        data.gencolumns_mut().into_iter()
+        utilities::with_seed(seed, || data.gencolumns_mut().into_iter()
             .zip(sensitivity.gencolumns().into_iter().zip(epsilon.into_iter()))
             .zip(lower.gencolumns().into_iter().zip(upper.gencolumns().into_iter()))
             .try_for_each(|((mut data_column, (sensitivity, epsilon)), (lower, upper))| data_column.iter_mut()
@@ -157,7 +225,7 @@ impl Evaluable for proto::SimpleGeometricMechanism {
                         *v as Integer, epsilon, *sens as f64,
                         *c_min as i64, *c_max as i64,
                         enforce_constant_time,
-                    ).map(|noise| *v = noise as Integer)))?;
+                    ).map(|noise| *v = noise as Integer))))?;
 
         Ok(ReleaseNode {
             value: data.into(),
@@ -173,6 +241,8 @@ impl Evaluable for proto::ExponentialMechanism {
     ) -> Result<ReleaseNode> {
         let enforce_constant_time = privacy_definition.as_ref()
             .map(|v| v.protect_elapsed_time).unwrap_or(false);
+        // a seeded release is reproducible, and is only for debugging and testing
+        let seed = utilities::take_seed(&mut arguments)?;
 
         let candidates = take_argument(&mut arguments, "candidates")?.array()?;
 
@@ -211,7 +281,7 @@ impl Evaluable for proto::ExponentialMechanism {
         macro_rules! apply_exponential {
             ($candidates:ident) => {
                 {
-                    let mut release_vec = $candidates.gencolumns().into_iter()
+                    let mut release_vec = utilities::with_seed(seed, || $candidates.gencolumns().into_iter()
                         .zip(utilities.gencolumns().into_iter())
                         .zip(sensitivity.iter().zip(epsilon.iter()))
                         .map(|((cands, utils), (sens, eps))| exponential_mechanism(
@@ -219,7 +289,7 @@ impl Evaluable for proto::ExponentialMechanism {
                             &cands.to_vec(),
                             utils.into_iter().map(|v| *v as f64).collect(),
                             enforce_constant_time))
-                        .collect::<Result<Vec<_>>>()?;
+                        .collect::<Result<Vec<_>>>())?;
 
                     Value::from(arr0(release_vec.remove(0)).into_dyn())
                 }
@@ -244,6 +314,7 @@ impl Evaluable for proto::SnappingMechanism {
 
         let enforce_constant_time = privacy_definition.as_ref()
             .map(|v| v.protect_elapsed_time).unwrap_or(false);
+        let release_precision = utilities::get_release_precision(privacy_definition)?;
 
         let mut data = take_argument(&mut arguments, "data")?
             .array()?.cast_float()?;
@@ -304,7 +375,14 @@ impl Evaluable for proto::SnappingMechanism {
                         *v, *eps, *sens as f64,
                         lower, upper, binding_probability,
                         enforce_constant_time
-                    ).map(|privatized| *v = privatized as Float)))?;
+                    ).map(|privatized| {
+                        // the snapped value is already on the mechanism's lattice, so rounding only coarsens it.
+                        // Rounding may not carry a release that lies within the public bounds outside of them
+                        let rounded = round_to_precision(privatized, release_precision);
+                        *v = if lower <= privatized && privatized <= upper {
+                            num::clamp(rounded, lower, upper)
+                        } else { rounded } as Float
+                    })))?;
 
         Ok(ReleaseNode {
             value: data.into(),
@@ -312,4 +390,143 @@ impl Evaluable for proto::SnappingMechanism {
             public: true
         })
     }
-}
\ No newline at end of file
+}
+impl Evaluable for proto::StabilityMechanism {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy_definition must be defined")?;
+        let enforce_constant_time = privacy_definition.protect_elapsed_time;
+
+        let privacy_usage = compose_privacy_usages(&self.privacy_usage)?;
+        let (scale, threshold) = stability_parameters(privacy_definition, &privacy_usage)?;
+
+        let (categories, counts): (Value, Vec<Float>) = match take_argument(&mut arguments, "data")?.array()? {
+            Array::Int(data) => {
+                let (categories, counts) = stable_counts(data, scale, threshold, enforce_constant_time)?;
+                (arr1(&categories).into_dyn().into(), counts)
+            },
+            Array::Str(data) => {
+                let (categories, counts) = stable_counts(data, scale, threshold, enforce_constant_time)?;
+                (arr1(&categories).into_dyn().into(), counts)
+            },
+            Array::Bool(data) => {
+                let (categories, counts) = stable_counts(data, scale, threshold, enforce_constant_time)?;
+                (arr1(&categories).into_dyn().into(), counts)
+            },
+            Array::Float(_) => return Err("data: atomic type must be int, bool or string".into())
+        };
+
+        Ok(ReleaseNode {
+            value: Value::Dataframe(indexmap![
+                "category".into() => categories,
+                "count".into() => arr1(&counts).into_dyn().into()
+            ]),
+            privacy_usages: Some(self.privacy_usage.clone()),
+            public: true
+        })
+    }
+}
+
+impl Evaluable for proto::HadamardResponse {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let enforce_constant_time = privacy_definition.as_ref()
+            .map(|v| v.protect_elapsed_time).unwrap_or(false);
+
+        let debias_scale = hadamard_debias_scale(&compose_privacy_usages(&self.privacy_usage)?)?;
+
+        let data = take_argument(&mut arguments, "data")?.array()?;
+        let categories = take_argument(&mut arguments, "categories")?.jagged()?;
+        let num_categories = categories.num_records().first().copied()
+            .ok_or_else(|| Error::from("categories: must be defined for one column"))?;
+
+        let indices = match (data, categories) {
+            (Array::Str(data), Jagged::Str(categories)) => cast_index(data, categories)?,
+            (Array::Int(data), Jagged::Int(categories)) => cast_index(data, categories)?,
+            (Array::Bool(data), Jagged::Bool(categories)) => cast_index(data, categories)?,
+            _ => return Err("data and categories must be homogeneously typed, and not float".into())
+        };
+
+        Ok(ReleaseNode {
+            value: arr1(&hadamard_response(
+                indices.iter().map(|v| *v as usize),
+                num_categories as usize, debias_scale, enforce_constant_time)?).into_dyn().into(),
+            privacy_usages: Some(self.privacy_usage.clone()),
+            public: true
+        })
+    }
+}
+
+/// Debiased frequency of each category, estimated from one hadamard response per record.
+///
+/// Category `i` is encoded as row `i + 1` of the Sylvester-Hadamard matrix of order `2^m > num_categories`,
+/// skipping the constant first row.
+/// Each record reports a uniformly random column `j` and the sign `H[i + 1][j]`,
+/// flipped with probability `1 / (e^epsilon + 1)`.
+/// Since distinct rows are orthogonal, correlating the reports with row `i + 1` and scaling by `debias_scale`
+/// is an unbiased estimate of the frequency of category `i`.
+/// The correlations with every row are computed at once, with a fast Walsh-Hadamard transform of the summed signs per column.
+fn hadamard_response(
+    indices: impl Iterator<Item=usize>, num_categories: usize,
+    debias_scale: Float, enforce_constant_time: bool
+) -> Result<Vec<Float>> {
+    let order = (num_categories + 1).next_power_of_two();
+    // probability of keeping the sign, as 1 / debias_scale = (e^epsilon - 1) / (e^epsilon + 1)
+    let keep_prob = (1. + 1. / debias_scale) / 2.;
+
+    let mut column_sums = vec![0i64; order];
+    let mut num_records = 0;
+    for index in indices {
+        let column = utilities::noise::sample_uniform_int(0, order as Integer - 1)? as usize;
+        let sign = hadamard_entry(index + 1, column);
+        let keep = utilities::noise::sample_bit_prob(keep_prob, enforce_constant_time)?;
+        column_sums[column] += if keep { sign } else { -sign };
+        num_records += 1;
+    }
+    if num_records == 0 {
+        return Err("data: must contain at least one record".into())
+    }
+
+    fast_walsh_hadamard(&mut column_sums);
+    Ok(column_sums[1..=num_categories].iter()
+        .map(|correlation| *correlation as Float / num_records as Float * debias_scale)
+        .collect())
+}
+
+/// Entry of the Sylvester-Hadamard matrix at the given row and column.
+fn hadamard_entry(row: usize, column: usize) -> i64 {
+    if (row & column).count_ones() % 2 == 0 { 1 } else { -1 }
+}
+
+/// In-place unnormalized Walsh-Hadamard transform, where the length of `values` is a power of two.
+fn fast_walsh_hadamard(values: &mut [i64]) {
+    let mut width = 1;
+    while width < values.len() {
+        for start in (0..values.len()).step_by(2 * width) {
+            for i in start..start + width {
+                let (left, right) = (values[i], values[i + width]);
+                values[i] = left + right;
+                values[i + width] = left - right;
+            }
+        }
+        width *= 2;
+    }
+}
+
+/// Noisy counts of each category observed in the data, omitting the categories whose noisy count falls below the threshold.
+fn stable_counts<T: Ord + Clone>(
+    data: ArrayD<T>, scale: f64, threshold: f64, enforce_constant_time: bool
+) -> Result<(Vec<T>, Vec<Float>)> {
+    let mut counts = BTreeMap::<T, f64>::new();
+    data.iter().for_each(|v| *counts.entry(v.clone()).or_insert(0.) += 1.);
+
+    let mut categories = Vec::new();
+    let mut noisy_counts = Vec::new();
+    for (category, count) in counts {
+        let noisy_count = count + utilities::noise::sample_laplace(0., scale, enforce_constant_time)?;
+        if noisy_count >= threshold {
+            categories.push(category);
+            noisy_counts.push(noisy_count as Float);
+        }
+    }
+    Ok((categories, noisy_counts))
+}
@@ -22,14 +22,18 @@ pub mod covariance;
 pub mod column_bind;
 pub mod digitize;
 pub mod dp_gumbel_median;
+pub mod ecdf;
 pub mod filter;
 pub mod histogram;
 pub mod impute;
 pub mod index;
+pub mod isotonic;
+pub mod label_counts;
 // pub mod linreg_noisy_stats;
 pub mod materialize;
 pub mod mean;
 pub mod mechanisms;
+pub mod moving_average;
 pub mod partition;
 pub mod quantile;
 pub mod raw_moment;
@@ -40,6 +44,7 @@ pub mod theil_sen;
 pub mod to_dataframe;
 pub mod transforms;
 pub mod union;
+pub mod user_sum;
 pub mod variance;
 
 /// Evaluable component trait
@@ -85,13 +90,13 @@ impl Evaluable for proto::component::Variant {
 
         evaluate!(
             // INSERT COMPONENT LIST
-            Cast, Clamp, ColumnBind, Count, Covariance, Digitize, Filter, Histogram, Impute, Index,
-            Materialize, Mean, Partition,
-            Quantile, RawMoment, Reshape, Resize, Sum, ToDataframe, Union, Variance,
+            Cast, Clamp, ColumnBind, Count, Covariance, Digitize, Ecdf, Filter, Histogram, Impute, Index, Isotonic, LabelCounts,
+            Materialize, Mean, MovingAverage, Partition,
+            Quantile, RawMoment, Reshape, Resize, Sum, ToDataframe, Union, UserSum, Variance,
 
             ExponentialMechanism, GaussianMechanism,
-            LaplaceMechanism, SnappingMechanism,
-            SimpleGeometricMechanism,
+            LaplaceMechanism, MatrixMechanism, SnappingMechanism,
+            SimpleGeometricMechanism, StabilityMechanism, HadamardResponse,
 
             Abs, Add, LogicalAnd, Divide, Equal, GreaterThan, LessThan, Log, Modulo, Multiply,
             Negate, Negative, LogicalOr, Power, RowMax, RowMin, Subtract, TheilSen, DpGumbelMedian
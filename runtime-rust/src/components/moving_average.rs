@@ -0,0 +1,77 @@
+use smartnoise_validator::errors::*;
+
+use crate::NodeArguments;
+use smartnoise_validator::base::ReleaseNode;
+use smartnoise_validator::utilities::take_argument;
+use crate::components::Evaluable;
+use ndarray::ArrayD;
+use smartnoise_validator::{proto, Float};
+
+impl Evaluable for proto::MovingAverage {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        Ok(ReleaseNode::new(moving_average(
+            take_argument(&mut arguments, "data")?.array()?.cast_float()?,
+            self.window as usize, self.num_excluded as usize
+        )?.into()))
+    }
+}
+
+/// Replaces each row with the average of the rows in a centered window.
+///
+/// # Arguments
+/// * `data` - Data to smooth.
+/// * `window` - Odd number of rows in each window.
+/// * `num_excluded` - Number of final rows to pass through unsmoothed.
+///
+/// # Return
+/// Moving average of each column, where windows are truncated at the ends of the column.
+///
+/// # Example
+/// ```
+/// use ndarray::arr1;
+/// use smartnoise_runtime::components::moving_average::moving_average;
+/// let data = arr1(&[1., 3., 2., 6., 10.]).into_dyn();
+/// let averaged = moving_average(data, 3, 1).unwrap();
+/// assert_eq!(averaged, arr1(&[2., 2., 11. / 3., 4., 10.]).into_dyn());
+/// ```
+pub fn moving_average(mut data: ArrayD<Float>, window: usize, num_excluded: usize) -> Result<ArrayD<Float>> {
+    if data.ndim() > 2 {
+        return Err("invalid data shape for MovingAverage".into())
+    }
+    if window.is_multiple_of(2) {
+        return Err("window must be odd".into())
+    }
+    let radius = window / 2;
+
+    data.gencolumns_mut().into_iter().try_for_each(|mut column| {
+        let num_smoothed = column.len().checked_sub(num_excluded)
+            .ok_or_else(|| Error::from("num_excluded may not exceed the number of records"))?;
+        let values = column.to_vec();
+        column.iter_mut().take(num_smoothed).enumerate().for_each(|(i, v)| {
+            let window = &values[i.saturating_sub(radius)..(i + radius + 1).min(num_smoothed)];
+            *v = window.iter().sum::<Float>() / window.len() as Float;
+        });
+        Ok::<_, Error>(())
+    })?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod test_moving_average {
+    use ndarray::arr1;
+    use crate::components::moving_average::moving_average;
+
+    #[test]
+    fn test_moving_average_ends() {
+        let data = arr1(&[4., 0., 8., 0.]).into_dyn();
+
+        // a window of one row leaves the data unchanged
+        assert_eq!(moving_average(data.clone(), 1, 0).unwrap(), data);
+
+        // the windows at the ends average over fewer rows
+        assert_eq!(moving_average(data.clone(), 3, 0).unwrap(), arr1(&[2., 4., 8. / 3., 4.]).into_dyn());
+
+        assert!(moving_average(data.clone(), 2, 0).is_err());
+        assert!(moving_average(data, 3, 5).is_err());
+    }
+}
@@ -1,8 +1,11 @@
+use indexmap::indexmap;
+use ndarray::arr0;
+
 use smartnoise_validator::errors::*;
 
 use crate::components::Evaluable;
 use crate::NodeArguments;
-use smartnoise_validator::base::{Value, Array, ReleaseNode};
+use smartnoise_validator::base::{Value, Array, ReleaseNode, IndexKey};
 use smartnoise_validator::utilities::take_argument;
 use smartnoise_validator::{proto, Integer, Float};
 use crate::utilities::broadcast_map;
@@ -54,13 +57,39 @@ impl Evaluable for proto::And {
 }
 
 impl Evaluable for proto::Divide {
+    #[allow(clippy::float_cmp)]
     fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let zero_policy = match arguments.remove(&IndexKey::from("zero_policy")) {
+            Some(zero_policy) => Some(zero_policy.array()?.first_string()?.to_lowercase()),
+            None => None
+        };
+
         match (take_argument(&mut arguments, "left")?, take_argument(&mut arguments, "right")?) {
             (Value::Array(left), Value::Array(right)) => match (left, right) {
-                (Array::Float(x), Array::Float(y)) =>
-                    Ok(broadcast_map(x, y, &|l, r| l / r)?.into()),
-                (Array::Int(x), Array::Int(y)) =>
-                    Ok(broadcast_map(x, y, &|l, r| l / r)?.into()),
+                // floats follow IEEE semantics by default
+                (Array::Float(x), Array::Float(y)) => match zero_policy.as_deref() {
+                    Some("error") => {
+                        if y.iter().any(|r| r == &0.) {
+                            return Err("Divide: division by zero".into())
+                        }
+                        Ok(broadcast_map(x, y, &|l, r| l / r)?.into())
+                    },
+                    Some("null") =>
+                        Ok(broadcast_map(x, y, &|l: &Float, r: &Float| if r == &0. { Float::NAN } else { l / r })?.into()),
+                    Some("nan") | None =>
+                        Ok(broadcast_map(x, y, &|l, r| l / r)?.into()),
+                    _ => Err("Divide: zero_policy must be one of \"error\", \"null\" or \"nan\"".into())
+                },
+                // integers have no null, so division by zero is an error
+                (Array::Int(x), Array::Int(y)) => match zero_policy.as_deref() {
+                    Some("error") | None => {
+                        if y.iter().any(|r| r == &0) {
+                            return Err("Divide: division by zero".into())
+                        }
+                        Ok(broadcast_map(x, y, &|l, r| l / r)?.into())
+                    },
+                    _ => Err("Divide: integer division by zero must error".into())
+                },
                 _ => Err("Divide: Either the argument types are mismatched or non-numeric.".into())
             },
             _ => Err("Divide: Both arguments must be arrays.".into())
@@ -77,8 +106,14 @@ impl Evaluable for proto::Equal {
                     Ok(broadcast_map(x, y, &|l: &bool, r: &bool| l == r)?.into()),
                 (Array::Int(x), Array::Int(y)) =>
                     Ok(broadcast_map(x, y, &|l: &Integer, r: &Integer| l == r)?.into()),
-                (Array::Float(x), Array::Float(y)) =>
-                    Ok(broadcast_map(x, y, &|l: &Float, r: &Float| l == r)?.into()),
+                // exact float equality is the default, but is rarely appropriate for computed values
+                (Array::Float(x), Array::Float(y)) => match arguments.remove(&IndexKey::from("tolerance")) {
+                    Some(tolerance) => {
+                        let tolerance = tolerance.array()?.first_float()?;
+                        Ok(broadcast_map(x, y, &|l: &Float, r: &Float| (l - r).abs() <= tolerance)?.into())
+                    },
+                    None => Ok(broadcast_map(x, y, &|l: &Float, r: &Float| l == r)?.into())
+                },
                 (Array::Str(x), Array::Str(y)) =>
                     Ok(broadcast_map(x, y, &|l: &String, r: &String| l == r)?.into()),
                 _ => Err("Equal: Argument types are mismatched.".into())
@@ -120,7 +155,11 @@ impl Evaluable for proto::LessThan {
 
 impl Evaluable for proto::Log {
     fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
-        let base = take_argument(&mut arguments, "base")?.array()?.float()?;
+        // the base defaults to the natural log
+        let base = match arguments.remove::<IndexKey>(&"base".into()) {
+            Some(base) => base.array()?.float()?,
+            None => arr0(std::f64::consts::E as Float).into_dyn()
+        };
         let data = take_argument(&mut arguments, "data")?.array()?.float()?;
         Ok(ReleaseNode::new(broadcast_map(base, data, &|base, x| x.log(*base))?.into()))
     }
@@ -159,14 +198,17 @@ impl Evaluable for proto::Multiply {
 }
 
 impl Evaluable for proto::Negate {
-    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+    fn evaluate(&self, privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
         match take_argument(&mut arguments, "data")? {
             Value::Array(data) => match data {
                 Array::Bool(data) =>
                     Ok(data.mapv(|v| !v).into()),
-                _ => Err("Or: Either the argument types are mismatched or non-numeric.".into())
+                // numbers are arithmetically negated
+                Array::Float(_) | Array::Int(_) => return proto::Negative {}
+                    .evaluate(privacy_definition, indexmap!["data".into() => Value::Array(data)]),
+                _ => Err("Negate: Argument must be boolean or numeric.".into())
             },
-            _ => Err("Or: Both arguments must be arrays.".into())
+            _ => Err("Negate: Argument must be an array.".into())
         }.map(ReleaseNode::new)
     }
 }
@@ -205,6 +247,9 @@ impl Evaluable for proto::Power {
                     Ok(broadcast_map(x, y, &|l, r| l.powf(*r))?.into()),
                 (Array::Int(x), Array::Int(y)) =>
                     Ok(broadcast_map(x, y, &|l, r| l.pow(*r as u32))?.into()),
+                // integers raised to a float power are promoted to floats
+                (Array::Int(x), Array::Float(y)) =>
+                    Ok(broadcast_map(x.mapv(|v| v as Float), y, &|l, r| l.powf(*r))?.into()),
                 _ => Err("Power: Either the argument types are mismatched or non-numeric.".into())
             },
             _ => Err("Power: Both arguments must be arrays.".into())
@@ -256,3 +301,72 @@ impl Evaluable for proto::Subtract {
         }.map(ReleaseNode::new)
     }
 }
+
+
+#[cfg(test)]
+mod test_transforms {
+    use indexmap::indexmap;
+    use ndarray::arr1;
+    use smartnoise_validator::base::Value;
+    use smartnoise_validator::proto;
+
+    use crate::components::Evaluable;
+
+    #[test]
+    fn test_equal_tolerance() {
+        let evaluate = |tolerance: Option<f64>| {
+            let mut arguments = indexmap![
+                "left".into() => Value::from(arr1(&[0.1 + 0.2]).into_dyn()),
+                "right".into() => Value::from(arr1(&[0.3]).into_dyn())
+            ];
+            if let Some(tolerance) = tolerance {
+                arguments.insert("tolerance".into(), tolerance.into());
+            }
+            proto::Equal {}.evaluate(&None, arguments).unwrap()
+                .value.array().unwrap().first_bool().unwrap()
+        };
+
+        assert!(!evaluate(None));
+        assert!(evaluate(Some(1e-10)));
+    }
+
+    #[test]
+    fn test_divide_zero_policy() {
+        let divide = |left: Value, right: Value, zero_policy: Option<&str>| {
+            let mut arguments = indexmap!["left".into() => left, "right".into() => right];
+            if let Some(zero_policy) = zero_policy {
+                arguments.insert("zero_policy".into(), zero_policy.to_string().into());
+            }
+            proto::Divide {}.evaluate(&None, arguments).map(|release| release.value)
+        };
+        let float_quotient = |zero_policy: Option<&str>| divide(
+            arr1(&[1., 0.]).into(), arr1(&[0., 0.]).into(), zero_policy)
+            .map(|quotient| quotient.array().unwrap().float().unwrap().into_raw_vec());
+
+        assert!(float_quotient(Some("error")).is_err());
+
+        let quotient = float_quotient(Some("null")).unwrap();
+        assert!(quotient[0].is_nan() && quotient[1].is_nan());
+
+        // IEEE semantics, by default
+        for zero_policy in vec![Some("nan"), None] {
+            let quotient = float_quotient(zero_policy).unwrap();
+            assert!(quotient[0].is_infinite() && quotient[1].is_nan());
+        }
+
+        assert!(divide(arr1::<i64>(&[1]).into(), arr1::<i64>(&[0]).into(), None).is_err());
+        assert!(divide(arr1::<i64>(&[1]).into(), arr1::<i64>(&[0]).into(), Some("null")).is_err());
+        assert!(divide(arr1::<i64>(&[4]).into(), arr1::<i64>(&[2]).into(), None).unwrap()
+            == arr1::<i64>(&[2]).into());
+    }
+
+    #[test]
+    fn test_negate() {
+        let negate = |data: Value| proto::Negate {}
+            .evaluate(&None, indexmap!["data".into() => data]).unwrap().value;
+
+        assert!(negate(arr1(&[true, false]).into()) == arr1(&[false, true]).into());
+        assert!(negate(arr1(&[1., -2.]).into()) == arr1(&[-1., 2.]).into());
+        assert!(negate(arr1::<i64>(&[1, -2]).into()) == arr1::<i64>(&[-1, 2]).into());
+    }
+}
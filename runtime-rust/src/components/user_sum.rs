@@ -0,0 +1,63 @@
+use smartnoise_validator::errors::*;
+
+use crate::NodeArguments;
+use smartnoise_validator::base::{Array, ReleaseNode};
+use smartnoise_validator::utilities::take_argument;
+use crate::components::Evaluable;
+use ndarray::ArrayD;
+use indexmap::map::IndexMap;
+use std::hash::Hash;
+use smartnoise_validator::{proto, Float};
+
+impl Evaluable for proto::UserSum {
+    fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
+        let data = take_argument(&mut arguments, "data")?.array()?.cast_float()?;
+        match take_argument(&mut arguments, "user_id")?.array()? {
+            Array::Int(user_id) => user_sum(&data, &user_id, self.cap),
+            Array::Str(user_id) => user_sum(&data, &user_id, self.cap),
+            Array::Bool(user_id) => user_sum(&data, &user_id, self.cap),
+            _ => return Err("user_id must be either i64, string or bool".into())
+        }.map(|sum| ReleaseNode::new(sum.into()))
+    }
+}
+
+/// Calculates the sum of the data, after clamping the total contribution of each user.
+///
+/// # Arguments
+/// * `data` - Single column of contributions.
+/// * `user_id` - Single column identifying the user of each row.
+/// * `cap` - Bound on the magnitude of the total of each user.
+///
+/// # Return
+/// Sum over users of the capped total of each user.
+///
+/// # Example
+/// ```
+/// use ndarray::{arr1, arr2};
+/// use smartnoise_runtime::components::user_sum::user_sum;
+/// let data = arr2(&[ [4.], [4.], [4.], [1.], [-3.] ]).into_dyn();
+/// let user_id = arr2(&[ [0], [0], [0], [1], [2] ]).into_dyn();
+/// let sum = user_sum(&data, &user_id, 5.).unwrap();
+/// assert_eq!(sum, arr2(&[[3.]]).into_dyn());
+/// ```
+pub fn user_sum<T: Eq + Hash + Clone>(data: &ArrayD<Float>, user_id: &ArrayD<T>, cap: Float) -> Result<ArrayD<Float>> {
+    if !cap.is_finite() || cap <= 0. {
+        return Err("cap must be finite and positive".into())
+    }
+    if data.len() != user_id.len() {
+        return Err("data and user_id must have the same number of records".into())
+    }
+
+    // total of each user, before capping
+    let mut totals = IndexMap::<T, Float>::new();
+    data.iter().zip(user_id.iter())
+        .for_each(|(value, user)| *totals.entry(user.clone()).or_insert(0.) += value);
+
+    let sum = totals.values().map(|total| total.max(-cap).min(cap)).sum::<Float>();
+
+    match data.ndim() {
+        1 => ndarray::Array::from_shape_vec(vec![], vec![sum]),
+        2 => ndarray::Array::from_shape_vec(vec![1, 1], vec![sum]),
+        _ => return Err("invalid data shape for UserSum".into())
+    }.map_err(|_| "unable to package UserSum result into an array".into())
+}
@@ -7,7 +7,7 @@ use crate::components::Evaluable;
 use ndarray::{ArrayD, Array};
 use crate::utilities::get_num_columns;
 use smartnoise_validator::{proto, Float};
-use crate::components::mean::mean;
+use crate::components::covariance::{standardize, standardized_covariance};
 
 impl Evaluable for proto::Variance {
     fn evaluate(&self, _privacy_definition: &Option<proto::PrivacyDefinition>, mut arguments: NodeArguments) -> Result<ReleaseNode> {
@@ -28,6 +28,9 @@ impl Evaluable for proto::Variance {
 /// # Return
 /// Variance for each column in the data.
 ///
+/// Each column is standardized before the squared deviations are accumulated,
+/// so data with a large magnitude but a small spread keeps its precision, and squares do not overflow.
+///
 /// # Example
 /// ```
 /// use ndarray::prelude::*;
@@ -38,13 +41,13 @@ impl Evaluable for proto::Variance {
 /// ```
 pub fn variance(data: &ArrayD<Float>, delta_degrees_of_freedom: usize) -> Result<ArrayD<Float>> {
 
-    let means: Vec<Float> = mean(data)?.iter().copied().collect();
-
     // iterate over the generalized columns
-    let variances = data.gencolumns().into_iter().zip(means)
-        .map(|(column, mean)| column.iter()
-                .fold(0., |sum, v| sum + (v - mean).powi(2)) / (column.len() - delta_degrees_of_freedom) as Float)
-        .collect::<Vec<Float>>();
+    let variances = data.gencolumns().into_iter()
+        .map(|column| {
+            let column = standardize(&column)?;
+            standardized_covariance(&column, &column, delta_degrees_of_freedom)
+        })
+        .collect::<Result<Vec<Float>>>()?;
 
     let array = match data.ndim() {
         1 => Array::from_shape_vec(vec![], variances),
@@ -56,4 +59,44 @@ pub fn variance(data: &ArrayD<Float>, delta_degrees_of_freedom: usize) -> Result
         Ok(array) => Ok(array),
         Err(_) => Err("unable to package Variance result into an array".into())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test_variance {
+    use ndarray::{arr1, ArrayD};
+    use smartnoise_validator::Float;
+
+    use crate::components::mean::mean;
+    use crate::components::variance::variance;
+
+    /// The previous implementation, which accumulated squared deviations from the mean of the raw values.
+    fn unstandardized_variance(data: &ArrayD<Float>) -> Float {
+        let mean = mean(data).unwrap().into_raw_vec()[0];
+        data.iter().fold(0., |sum, v| sum + (v - mean).powi(2)) / data.len() as Float
+    }
+
+    #[test]
+    fn test_variance_precision() {
+        // large magnitude, but small spread. The variance of seven steps of 0.5 is exactly 1
+        let data = arr1(&[3e15, 3e15 + 0.5, 3e15 + 1., 3e15 + 1.5, 3e15 + 2., 3e15 + 2.5, 3e15 + 3.]).into_dyn();
+        let estimate = variance(&data, 0).unwrap().into_raw_vec()[0];
+        assert_eq!(estimate, 1.);
+
+        // the mean of the raw values is rounded to the nearest representable value,
+        //    which biases every deviation from it
+        assert!((unstandardized_variance(&data) - 1.).abs() > 0.1);
+    }
+
+    #[test]
+    fn test_variance_overflow() {
+        // the squares of the values overflow, but the variance is representable
+        let data = arr1(&[-1.2e154, 1.2e154]).into_dyn();
+        let estimate = variance(&data, 0).unwrap().into_raw_vec()[0];
+        assert!((estimate / 1.44e308 - 1.).abs() < 1e-12);
+        assert!(data.mapv(|v| v * v).sum().is_infinite());
+
+        // a variance beyond the range of a float is an error, rather than infinite
+        let data = arr1(&[-1e200, 1e200]).into_dyn();
+        assert!(variance(&data, 0).is_err());
+    }
+}
@@ -173,6 +173,11 @@ pub fn release(
         // no nodes were added to the traversal. Begin node execution
         traversal.pop();
 
+        // the expansion may have released the node directly
+        if release.contains_key(&component_id) {
+            continue;
+        }
+
         // the expansion may have overwritten the current component
         let component = computation_graph.get(&component_id).unwrap();
 
@@ -257,3 +262,873 @@ pub fn release(
 
     Ok((release, warnings))
 }
+
+#[cfg(test)]
+mod test_release {
+    use ndarray::arr1;
+
+    use smartnoise_validator::base::{IndexKey, Value};
+    use smartnoise_validator::bindings::Analysis;
+    use smartnoise_validator::Integer;
+    use smartnoise_validator::utilities::serial::serialize_i64_null;
+
+    use crate::proto;
+
+    /// Central privacy usage with the given epsilon and delta.
+    fn privacy_usage(epsilon: f64, delta: f64) -> proto::PrivacyUsage {
+        proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon, delta,
+            })),
+            units: proto::privacy_usage::Units::Central as i32
+        }
+    }
+
+    #[test]
+    fn test_dp_winsorized_mean() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        // the records -90 and 900 are outliers
+        let data = analysis.literal()
+            .value(arr1(&[-90., 1., 2., 3., 4., 5., 6., 7., 8., 900.]).into_dyn().into())
+            .value_public(true).build();
+        let data = analysis.to_float(data).build();
+
+        let lower = analysis.literal().value((-100.).into()).value_public(true).build();
+        let upper = analysis.literal().value(1000.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+        let number_rows = analysis.literal().value(10.into()).value_public(true).build();
+        let resized = analysis.resize(imputed)
+            .number_rows(number_rows).lower(lower).upper(upper)
+            .build();
+
+        let winsor_lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let winsor_upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let usage = privacy_usage(1000., 0.);
+        let winsorized_mean = analysis.dp_winsorized_mean(resized, winsor_lower, winsor_upper, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        // the outliers are replaced by the winsorization bounds, so the true winsorized mean is 4.6.
+        //    The raw mean is 84.6, and the mean clamped to the range [1, 8] of the remaining records is 4.5
+        let released = release.get(&winsorized_mean).unwrap()
+            .value.ref_array().unwrap().first_float().unwrap();
+        assert!(4.5 < released && released < 84.6, "{} is not between the clamped and raw means", released);
+    }
+
+    #[test]
+    fn test_dp_conditional_sum() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let data = analysis.literal()
+            .value(arr1(&[1., 2., 3., 4., 5., 6., 7., 8., 9., 10.]).into_dyn().into())
+            .value_public(true).build();
+        let data = analysis.to_float(data).build();
+
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+
+        let threshold = analysis.literal().value(5.0.into()).value_public(true).build();
+        let mask = analysis.greater_than(imputed, threshold).build();
+
+        let usage = privacy_usage(100., 0.);
+        let conditional_sum = analysis.dp_conditional_sum(imputed, mask, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        // the true sum over the subset is 6 + 7 + 8 + 9 + 10, and the noise scale is 10 / 100
+        let released = release.get(&conditional_sum).unwrap()
+            .value.ref_array().unwrap().first_float().unwrap();
+        assert!((released - 40.).abs() < 5.);
+    }
+
+    #[test]
+    fn test_dp_skewness() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let data = analysis.literal()
+            .value(arr1(&[1., 2., 3., 4., 5., 6., 7., 8., 9., 10.]).into_dyn().into())
+            .value_public(true).build();
+        let data = analysis.to_float(data).build();
+
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(11.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+
+        let usage = privacy_usage(1000., 0.);
+        let skewness = analysis.dp_skewness(imputed, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        // the data is symmetric about its mean, so the true skewness is zero
+        let released = release.get(&skewness).unwrap()
+            .value.ref_array().unwrap().first_float().unwrap();
+        assert!(released.abs() < 0.5, "{} is not near zero", released);
+    }
+
+    #[test]
+    fn test_clamp_null_policy() {
+        let clamp = |null_policy: &str| {
+            let mut analysis = Analysis::new();
+            let data = analysis.literal()
+                .value(arr1(&[1., std::f64::NAN, 20.]).into_dyn().into())
+                .value_public(true).build();
+            let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+            let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+            let fill = analysis.literal().value(5.0.into()).value_public(true).build();
+            let clamped = analysis.clamp(data).lower(lower).upper(upper)
+                .null_policy(null_policy.to_string()).build();
+            let imputed = analysis.impute(clamped).lower(fill).upper(fill).build();
+
+            crate::release(
+                Some(analysis.privacy_definition.clone()),
+                analysis.components.clone(),
+                analysis.release.clone(),
+                proto::FilterLevel::All)
+                .map(|(release, _)| release.get(&imputed).unwrap()
+                    .value.ref_array().unwrap().ref_float().unwrap().clone())
+        };
+
+        // the null is passed through the clamp, and filled by the impute rather than clamped to a bound
+        assert_eq!(clamp("Pass").unwrap(), arr1(&[1., 5., 10.]).into_dyn());
+
+        // the null is out of range
+        assert!(clamp("Error").is_err());
+    }
+
+    #[test]
+    fn test_bool_labels() {
+        let mut analysis = Analysis::new();
+        let data = analysis.literal()
+            .value(arr1(&["yes", "no", "maybe"]).mapv(|v| v.to_string()).into_dyn().into())
+            .value_public(true).build();
+        let true_label = analysis.literal().value("yes".to_string().into()).value_public(true).build();
+        let false_label = analysis.literal().value("no".to_string().into()).value_public(true).build();
+        let cast = analysis.to_bool(data, true_label).false_label(false_label).build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        // the value matching neither label is released as null
+        let released = release.get(&cast).unwrap()
+            .value.ref_array().unwrap().ref_float().unwrap().clone().into_raw_vec();
+        assert_eq!(released[..2], [1., 0.]);
+        assert!(released[2].is_nan());
+    }
+
+    #[test]
+    fn test_dp_rank() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let data = analysis.literal()
+            .value(arr1(&[1., 2., 3., 4., 5., 6., 7., 8., 9., 10.]).into_dyn().into())
+            .value_public(true).build();
+        let data = analysis.to_float(data).build();
+
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+
+        // the median of the data
+        let threshold = analysis.literal().value(5.5.into()).value_public(true).build();
+
+        let usage = privacy_usage(100., 0.);
+        let rank = analysis.dp_rank(imputed, threshold, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        // half of the records are below the median, and the noise scale is 1 / (10 * 100)
+        let released = release.get(&rank).unwrap()
+            .value.ref_array().unwrap().first_float().unwrap();
+        assert!((released - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_dp_iqr() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let data = analysis.literal()
+            .value(arr1(&[1., 2., 3., 4., 5., 6., 7., 8., 9., 10.]).into_dyn().into())
+            .value_public(true).build();
+        let data = analysis.to_float(data).build();
+
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+        let number_rows = analysis.literal().value(10.into()).value_public(true).build();
+        let resized = analysis.resize(imputed)
+            .number_rows(number_rows).lower(lower).upper(upper)
+            .build();
+
+        // with little budget, the noisy upper quartile often falls below the noisy lower quartile
+        let usage = privacy_usage(0.01, 0.);
+        let iqr = analysis.dp_iqr(resized, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        // the released range is clamped to be nonnegative
+        let released = release.get(&iqr).unwrap()
+            .value.ref_array().unwrap().first_float().unwrap();
+        assert!((0. ..=10.).contains(&released));
+    }
+
+    #[test]
+    fn test_dp_ecdf() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let data = analysis.literal()
+            .value(arr1(&[1., 2., 3., 4., 5., 6., 7., 8., 9., 10.]).into_dyn().into())
+            .value_public(true).build();
+        let data = analysis.to_float(data).build();
+
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+
+        let thresholds = analysis.literal()
+            .value(arr1(&[2.5, 5.5, 8.5]).into_dyn().into())
+            .value_public(true).build();
+
+        let usage = privacy_usage(1000., 0.);
+        let ecdf = analysis.dp_ecdf(imputed, thresholds, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        // the noise scale is 3 / (10 * 1000)
+        let tolerance = 0.01;
+        let released = release.get(&ecdf).unwrap()
+            .value.ref_array().unwrap().ref_float().unwrap().clone();
+        assert_eq!(released.len(), 3);
+
+        // the released cdf brackets the true cdf
+        released.iter().zip([0.2, 0.5, 0.8].iter())
+            .for_each(|(released, actual)| assert!((released - actual).abs() < tolerance));
+
+        // the released cdf is approximately monotone
+        released.iter().zip(released.iter().skip(1))
+            .for_each(|(l, r)| assert!(r - l > -tolerance));
+    }
+
+    #[test]
+    fn test_dp_histogram_labeled() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let data = analysis.literal()
+            .value(arr1::<Integer>(&[1, 2, 2, 3, 3, 3, 7]).into_dyn().into())
+            .value_public(true).build();
+        let categories = analysis.literal()
+            .value(Value::Jagged(vec![vec![3i64, 1, 2]].into()))
+            .value_public(true).build();
+        let null_value = analysis.literal()
+            .value(arr1::<Integer>(&[-1]).into_dyn().into())
+            .value_public(true).build();
+        let lower = analysis.literal().value((0 as Integer).into()).value_public(true).build();
+        let inclusive_left = analysis.literal().value(true.into()).value_public(true).build();
+
+        let usage = privacy_usage(1., 0.);
+        let histogram = analysis.dp_histogram(data, lower, inclusive_left, vec![usage])
+            .categories(categories)
+            .null_value(null_value)
+            .labeled(true)
+            .build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        let released = release.get(&histogram).unwrap().value.clone().dataframe().unwrap();
+
+        // the labels are the categories in order, followed by the null value
+        let labels = released.get::<IndexKey>(&"category".into()).unwrap()
+            .ref_array().unwrap().ref_int().unwrap().clone();
+        assert_eq!(labels, arr1::<Integer>(&[3, 1, 2, -1]).into_dyn());
+
+        let counts = released.get::<IndexKey>(&"count".into()).unwrap()
+            .ref_array().unwrap().ref_int().unwrap().clone();
+        assert_eq!(counts.shape(), labels.shape());
+    }
+
+    #[test]
+    fn test_public_dp_count() {
+        let mut analysis = Analysis::new();
+        let data = analysis.literal()
+            .value(arr1(&[1., 2., 3., 4., 5.]).into_dyn().into())
+            .value_public(true).build();
+        let lower = analysis.literal().value((0 as Integer).into()).value_public(true).build();
+
+        let usage = privacy_usage(1., 0.);
+        let count = analysis.dp_count(data, lower, vec![usage]).build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        // the count of public data is released exactly
+        assert_eq!(release.get(&count).unwrap().value.ref_array().unwrap().first_int().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_dp_binned_histogram() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        // uniform over [0, 100)
+        let data = analysis.literal()
+            .value(ndarray::Array1::range(0.5, 100., 1.).into_dyn().into())
+            .value_public(true).build();
+        let edges = analysis.literal()
+            .value(Value::Jagged(vec![vec![0., 25., 50., 75., 100.]].into()))
+            .value_public(true).build();
+
+        let usage = privacy_usage(10., 0.);
+        let histogram = analysis.dp_binned_histogram(data, edges, vec![usage]).build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        let released = release.get(&histogram).unwrap().value.clone().dataframe().unwrap();
+        let labels = released.get::<IndexKey>(&"category".into()).unwrap()
+            .ref_array().unwrap().ref_string().unwrap().clone();
+        assert_eq!(labels.iter().next().unwrap(), "[0, 25)");
+
+        // each bin holds a quarter of the records, and none fall outside of the edges
+        let counts = released.get::<IndexKey>(&"count".into()).unwrap()
+            .ref_array().unwrap().ref_float().unwrap().clone();
+        let counts = counts.iter().copied().collect::<Vec<f64>>();
+        assert_eq!(counts.len(), 5);
+        counts[..4].iter().for_each(|count| assert!((count - 25.).abs() < 5.));
+        assert!(counts[4].abs() < 5.);
+    }
+
+    #[test]
+    fn test_release_precision() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+        analysis.privacy_definition.release_precision = Some(serialize_i64_null(Some(2)));
+
+        let data = analysis.literal()
+            .value(arr1(&[1., 2., 3., 4., 5., 6., 7., 8., 9., 10.]).into_dyn().into())
+            .value_public(true).build();
+        let data = analysis.to_float(data).build();
+
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+
+        let usage = privacy_usage(1., 0.);
+        let sum = analysis.dp_sum(imputed, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        // the release is a whole number of hundredths
+        let released = release.get(&sum).unwrap()
+            .value.ref_array().unwrap().first_float().unwrap();
+        assert!(((released * 100.).round() - released * 100.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_dp_sum_of_squares() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        // the true sum of squares is zero, so the noise is negative about half of the time
+        let data = analysis.literal()
+            .value(arr1(&[0., 0., 0., 0., 0.]).into_dyn().into())
+            .value_public(true).build();
+        let data = analysis.to_float(data).build();
+
+        let lower = analysis.literal().value((-3.0).into()).value_public(true).build();
+        let upper = analysis.literal().value(2.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+
+        let usage = privacy_usage(0.1, 0.);
+        let sum_of_squares = analysis.dp_sum_of_squares(imputed, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        let released = release.get(&sum_of_squares).unwrap()
+            .value.ref_array().unwrap().first_float().unwrap();
+        assert!(released >= 0.);
+    }
+
+    #[test]
+    fn test_dp_trimmed_mean() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        // the clean data has a mean of about 10, and a tenth of the records are outliers
+        let mut values = (0..90).map(|i| 5. + (i % 11) as f64).collect::<Vec<f64>>();
+        values.extend(vec![1000.; 10]);
+        let data = analysis.literal()
+            .value(arr1(&values).into_dyn().into())
+            .value_public(true).build();
+        let data = analysis.to_float(data).build();
+
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(1000.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+
+        let candidates = analysis.literal()
+            .value(arr1(&[0., 2., 4., 6., 8., 10., 12., 14., 16., 18., 20., 100., 500., 1000.]).into_dyn().into())
+            .value_public(true).build();
+
+        let usage = privacy_usage(40., 0.);
+        let trimmed_mean = analysis.dp_trimmed_mean(imputed, 0.15, vec![usage.clone()])
+            .candidates(candidates)
+            .mechanism("Laplace".to_string())
+            .build();
+        let clamped_mean = analysis.dp_mean(imputed, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        let released = |node_id| release.get(&node_id).unwrap()
+            .value.ref_array().unwrap().first_float().unwrap();
+
+        // the outliers are trimmed, but pull the clamped mean towards 109
+        assert!((released(trimmed_mean) - 10.).abs() < (released(clamped_mean) - 10.).abs());
+    }
+
+    #[test]
+    fn test_dp_mean_centered() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        // the data has a mean of 2, away from the midpoint of the bounds
+        let values = (0..100).map(|i| (i % 5) as f64).collect::<Vec<f64>>();
+        let data = analysis.literal()
+            .value(arr1(&values).into_dyn().into())
+            .value_public(true).build();
+        let data = analysis.to_float(data).build();
+
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+
+        let usage = privacy_usage(1., 0.);
+        let mean = analysis.dp_mean(imputed, vec![usage])
+            .implementation("plug-in".to_string())
+            .mechanism("Laplace".to_string())
+            .centered(true)
+            .build();
+
+        // the average of many releases approaches the true mean
+        let num_trials = 200;
+        let average = (0..num_trials)
+            .map(|_| {
+                let (release, _) = crate::release(
+                    Some(analysis.privacy_definition.clone()),
+                    analysis.components.clone(),
+                    analysis.release.clone(),
+                    proto::FilterLevel::All).unwrap();
+                release.get(&mean).unwrap().value.ref_array().unwrap().first_float().unwrap()
+            })
+            .sum::<f64>() / num_trials as f64;
+        assert!((average - 2.).abs() < 0.2, "average: {}", average);
+    }
+
+    #[test]
+    fn test_seeded_release() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let data = analysis.literal()
+            .value(arr1(&[1., 2., 3., 4., 5.]).into_dyn().into())
+            .value_public(true).build();
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let sum = analysis.sum(clamped).build();
+
+        let usage = privacy_usage(1., 0.);
+        let seed = analysis.literal().value(42.into()).value_public(true).build();
+        let noised = analysis.laplace_mechanism(sum, vec![usage]).seed(seed).build();
+
+        let run = || crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap().0;
+
+        // the same seed reproduces the same noise
+        let (first, second) = (run(), run());
+        assert_eq!(
+            first.get(&noised).unwrap().value.ref_array().unwrap().first_float().unwrap(),
+            second.get(&noised).unwrap().value.ref_array().unwrap().first_float().unwrap());
+
+        // the summary warns that the release is not private
+        let report = smartnoise_validator::generate_report(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            first).unwrap();
+        assert!(report.contains(smartnoise_validator::utilities::SEEDED_RELEASE_WARNING));
+    }
+
+    #[test]
+    fn test_dp_correlation() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        // the right column is the left column with a small perturbation, so they are strongly correlated
+        let left_values = (0..1000).map(|i| (i % 100) as f64 / 10.).collect::<Vec<f64>>();
+        let right_values = left_values.iter().enumerate()
+            .map(|(i, v)| v + ((i * 7) % 10) as f64 / 5.)
+            .collect::<Vec<f64>>();
+
+        let mean = |values: &Vec<f64>| values.iter().sum::<f64>() / values.len() as f64;
+        let (left_mean, right_mean) = (mean(&left_values), mean(&right_values));
+        let covariance = left_values.iter().zip(right_values.iter())
+            .map(|(l, r)| (l - left_mean) * (r - right_mean)).sum::<f64>();
+        let left_variance = left_values.iter().map(|l| (l - left_mean).powi(2)).sum::<f64>();
+        let right_variance = right_values.iter().map(|r| (r - right_mean).powi(2)).sum::<f64>();
+        let actual = covariance / (left_variance * right_variance).sqrt();
+
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(12.0.into()).value_public(true).build();
+        let mut preprocess = |values: Vec<f64>| {
+            let data = analysis.literal()
+                .value(arr1(&values).into_dyn().into())
+                .value_public(true).build();
+            let data = analysis.to_float(data).build();
+            let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+            analysis.impute(clamped).lower(lower).upper(upper).build()
+        };
+        let left = preprocess(left_values);
+        let right = preprocess(right_values);
+
+        let usage = privacy_usage(30., 0.);
+        let correlation = analysis.dp_correlation(left, right, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        let released = release.get(&correlation).unwrap()
+            .value.ref_array().unwrap().first_float().unwrap();
+        assert!((-1. ..=1.).contains(&released));
+        assert!((released - actual).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_stability_histogram() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        // one common category, and one category contributed by a single user
+        let mut values = vec![1i64; 1000];
+        values.push(2);
+        let data = analysis.literal()
+            .value(arr1(&values).into_dyn().into())
+            .value_public(true).build();
+        let lower = analysis.literal().value(0.into()).value_public(true).build();
+        let inclusive_left = analysis.literal().value(true.into()).value_public(true).build();
+
+        let usage = privacy_usage(1., 1e-6);
+        let histogram = analysis.dp_histogram(data, lower, inclusive_left, vec![usage])
+            .mechanism("Stability".to_string())
+            .build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        let released = release.get(&histogram).unwrap().value.clone().dataframe().unwrap();
+        let categories = released.get::<IndexKey>(&"category".into()).unwrap()
+            .clone().array().unwrap().int().unwrap();
+        // the rare category is suppressed by the threshold, while the common category survives
+        assert_eq!(categories.into_dimensionality::<ndarray::Ix1>().unwrap().to_vec(), vec![1]);
+    }
+
+    #[test]
+    fn test_quantile_bins() {
+        let mut analysis = Analysis::new();
+
+        // skewed data, where bins of equal width would hold very different counts
+        let values = (0..1000).map(|i| (i * i) as f64 / 1000.).collect::<Vec<f64>>();
+        let data = analysis.literal()
+            .value(arr1(&values).into_dyn().into())
+            .value_public(true).build();
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(1000.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+
+        let candidates = analysis.literal()
+            .value(arr1(&(0..=1000).map(|v| v as f64).collect::<Vec<f64>>()).into_dyn().into())
+            .value_public(true).build();
+        let count_lower = analysis.literal().value(0.into()).value_public(true).build();
+        let inclusive_left = analysis.literal().value(true.into()).value_public(true).build();
+
+        let usage = privacy_usage(100., 0.);
+        let histogram = analysis.dp_histogram(imputed, count_lower, inclusive_left, vec![usage])
+            .candidates(candidates)
+            .quantile_bins(4)
+            .build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        let released = release.get(&histogram).unwrap().value.clone().dataframe().unwrap();
+        let edges = released.get::<IndexKey>(&"category".into()).unwrap()
+            .clone().array().unwrap().float().unwrap();
+        assert_eq!(edges.len(), 4);
+        assert_eq!(edges[0], 0.);
+
+        // each bin holds about a quarter of the records
+        let counts = released.get::<IndexKey>(&"count".into()).unwrap()
+            .clone().array().unwrap().int().unwrap();
+        assert_eq!(counts.len(), 4);
+        assert!(counts.iter().all(|count| (count - 250).abs() < 50));
+    }
+
+    #[test]
+    fn test_histogram_smoothing() {
+        let mut analysis = Analysis::new();
+
+        // the count of bin i is 20 + i, a smooth distribution over 100 bins
+        let values = (0..100).flat_map(|i| vec![i as f64 + 0.5; 20 + i]).collect::<Vec<f64>>();
+        let truth = (0..100).map(|i| (20 + i) as f64).collect::<Vec<f64>>();
+        let num_records = values.len() as f64;
+        let data = analysis.literal()
+            .value(arr1(&values).into_dyn().into())
+            .value_public(false).build();
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(100.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+
+        let edges = analysis.literal()
+            .value(Value::Jagged(vec![(0..=100).map(|v| v as f64).collect::<Vec<f64>>()].into()))
+            .value_public(true).build();
+        let count_lower = analysis.literal().value(0.into()).value_public(true).build();
+        let inclusive_left = analysis.literal().value(true.into()).value_public(true).build();
+
+        let usage = privacy_usage(0.1, 0.);
+        let noisy = analysis.dp_histogram(imputed, count_lower, inclusive_left, vec![usage.clone()])
+            .edges(edges)
+            .build();
+        let smoothed = analysis.dp_histogram(imputed, count_lower, inclusive_left, vec![usage])
+            .edges(edges)
+            .smoothing_window(9)
+            .build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        let noisy = release.get(&noisy).unwrap().value.clone().array().unwrap().cast_float().unwrap();
+        let smoothed = release.get(&smoothed).unwrap().value.clone().array().unwrap().cast_float().unwrap();
+        // one cell per bin, followed by the null cell
+        assert_eq!(smoothed.len(), 101);
+
+        // averaging adjacent bins brings the counts closer to the smooth distribution
+        let distance = |counts: &ndarray::ArrayD<f64>| counts.iter().zip(truth.iter())
+            .map(|(count, truth)| (count - truth).abs()).sum::<f64>();
+        assert!(distance(&smoothed) < distance(&noisy));
+
+        // while approximately preserving the total count
+        let total = smoothed.iter().take(100).sum::<f64>();
+        assert!((total - num_records).abs() < 0.1 * num_records);
+    }
+
+    #[test]
+    fn test_hadamard_response() {
+        let mut analysis = Analysis::new();
+
+        // category c is observed (c + 1) * 100 times
+        let values = (0..20i64).flat_map(|c| vec![c; (c as usize + 1) * 100]).collect::<Vec<i64>>();
+        let num_records = values.len() as f64;
+        let data = analysis.literal()
+            .value(arr1(&values).into_dyn().into())
+            .value_public(true).build();
+        let categories = analysis.literal()
+            .value(Value::Jagged(vec![(0..20i64).collect::<Vec<i64>>()].into()))
+            .value_public(true).build();
+        let null_value = analysis.literal().value((-1).into()).value_public(true).build();
+        let clamped = analysis.clamp(data).categories(categories).null_value(null_value).build();
+
+        let usage = proto::PrivacyUsage {
+            units: proto::privacy_usage::Units::Local as i32,
+            ..privacy_usage(2., 0.)
+        };
+        let frequencies = analysis.hadamard_response(clamped, vec![usage]).build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        let released = release.get(&frequencies).unwrap()
+            .value.clone().array().unwrap().float().unwrap();
+
+        // one estimate per category, followed by the null category, which is never observed
+        assert_eq!(released.len(), 21);
+        released.iter().enumerate().for_each(|(c, estimate)| {
+            let truth = if c < 20 { (c + 1) as f64 * 100. / num_records } else { 0. };
+            assert!((estimate - truth).abs() < 0.04);
+        });
+    }
+
+    #[test]
+    fn test_dp_user_sum() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        // user 0 contributes 1000 rows of 10, while 100 other users each contribute a single row of 1
+        let (values, users): (Vec<f64>, Vec<i64>) = std::iter::repeat((10., 0)).take(1000)
+            .chain((1..=100).map(|user| (1., user)))
+            .unzip();
+        let data = analysis.literal()
+            .value(arr1(&values).into_dyn().into())
+            .value_public(true).build();
+        let user_id = analysis.literal()
+            .value(arr1(&users).into_dyn().into())
+            .value_public(true).build();
+
+        let usage = privacy_usage(1., 0.);
+        let user_sum = analysis.dp_user_sum(data, user_id, 5., vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        // the heavy user is capped at 5, so the sum is near 105 rather than 10100
+        let released = release.get(&user_sum).unwrap()
+            .value.clone().array().unwrap().first_float().unwrap();
+        assert!((released - 105.).abs() < 100.);
+    }
+
+    #[test]
+    fn test_dp_between_group_variance() {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.protect_floating_point = false;
+
+        // two groups of equal size, with means 0 and 10, so the between-group variance is 25
+        let values = (0..1000).map(|i| if i < 500 { 0i64 } else { 10 }).collect::<Vec<i64>>();
+        let data = analysis.literal()
+            .value(arr1(&values).into_dyn().into())
+            .value_public(true).build();
+        let categories = analysis.literal()
+            .value(Value::Jagged(vec![vec![0i64, 10]].into()))
+            .value_public(true).build();
+        let null_value = analysis.literal().value((-1).into()).value_public(true).build();
+        let groups = analysis.clamp(data).categories(categories).null_value(null_value).build();
+
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let data = analysis.to_float(groups).build();
+        let clamped = analysis.clamp(data).lower(lower).upper(upper).build();
+        let imputed = analysis.impute(clamped).lower(lower).upper(upper).build();
+
+        let usage = privacy_usage(100., 0.);
+        let variance = analysis.dp_between_group_variance(imputed, groups, vec![usage]).build();
+
+        let (release, _) = crate::release(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            proto::FilterLevel::All).unwrap();
+
+        let released = release.get(&variance).unwrap()
+            .value.ref_array().unwrap().first_float().unwrap();
+        assert!(released > 0.);
+        assert!((released - 25.).abs() < 2.5);
+    }
+}
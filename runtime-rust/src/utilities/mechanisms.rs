@@ -20,6 +20,7 @@ use std::ops::{Div};
 /// * `value` - Statistic to be privatized.
 /// * `epsilon` - Multiplicative privacy loss parameter.
 /// * `sensitivity` - Upper bound on the L1 sensitivity of the function you want to privatize.
+/// * `max_noise_sigmas` - Optional. Noise beyond this many standard deviations is resampled.
 /// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
 ///
 /// # Return
@@ -28,10 +29,10 @@ use std::ops::{Div};
 /// # Examples
 /// ```
 /// use smartnoise_runtime::utilities::mechanisms::laplace_mechanism;
-/// let n = laplace_mechanism(22.3, 0.1, 2.0, false);
+/// let n = laplace_mechanism(22.3, 0.1, 2.0, None, false);
 /// ```
 pub fn laplace_mechanism(
-    value: f64, epsilon: f64, sensitivity: f64, enforce_constant_time: bool
+    value: f64, epsilon: f64, sensitivity: f64, max_noise_sigmas: Option<f64>, enforce_constant_time: bool
 ) -> Result<f64> {
 
     if sensitivity < 0. {
@@ -41,7 +42,9 @@ pub fn laplace_mechanism(
         return Err(format!("epsilon ({}) must be positive", epsilon).into())
     }
     let scale: f64 = sensitivity / epsilon;
-    noise::sample_laplace(0., scale, enforce_constant_time).map(|n| value + n)
+    // the standard deviation of the laplace distribution is sqrt(2) times the scale
+    let cap = max_noise_sigmas.map(|sigmas| sigmas * 2f64.sqrt() * scale);
+    sample_capped(|| noise::sample_laplace(0., scale, enforce_constant_time), cap).map(|n| value + n)
 }
 
 /// Computes privatized value according to the Snapping mechanism
@@ -139,6 +142,8 @@ pub fn snapping_mechanism(
 /// * `epsilon` - Multiplicative privacy loss parameter.
 /// * `delta` - Additive privacy loss parameter.
 /// * `sensitivity` - Upper bound on the L2 sensitivity of the function you want to privatize.
+/// * `analytic` - Whether to derive the scale with the analytic gaussian.
+/// * `max_noise_sigmas` - Optional. Noise beyond this many standard deviations is resampled.
 /// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
 ///
 /// # Return
@@ -147,12 +152,13 @@ pub fn snapping_mechanism(
 /// # Examples
 /// ```
 /// use smartnoise_runtime::utilities::mechanisms::gaussian_mechanism;
-/// let n = gaussian_mechanism(22.3, 0.1, 0.0001, 2.0, false, false);
+/// let n = gaussian_mechanism(22.3, 0.1, 0.0001, 2.0, false, None, false);
 /// ```
 pub fn gaussian_mechanism(
     value: f64,
     epsilon: f64, delta: f64, sensitivity: f64,
     analytic: bool,
+    max_noise_sigmas: Option<f64>,
     enforce_constant_time: bool
 ) -> Result<f64> {
     if epsilon <= 0. || delta <= 0. || sensitivity <= 0. {
@@ -165,7 +171,20 @@ pub fn gaussian_mechanism(
         sensitivity * (2. * (1.25 / delta).ln()).sqrt() / epsilon
     };
     // this uses mpfr noise if available
-    Ok(value + noise::sample_gaussian(0., scale, enforce_constant_time)?)
+    let cap = max_noise_sigmas.map(|sigmas| sigmas * scale);
+    sample_capped(|| noise::sample_gaussian(0., scale, enforce_constant_time), cap).map(|n| value + n)
+}
+
+/// Resample noise until its magnitude is within the cap, if any.
+///
+/// Resampling conditions the noise on the cap, which the validator charges to the delta of the release.
+fn sample_capped(sample: impl Fn() -> Result<f64>, cap: Option<f64>) -> Result<f64> {
+    loop {
+        let noise = sample()?;
+        if cap.map(|cap| noise.abs() <= cap).unwrap_or(true) {
+            return Ok(noise)
+        }
+    }
 }
 
 /// Returns noise drawn according to the Geometric mechanism.
@@ -217,6 +236,52 @@ pub fn simple_geometric_mechanism(
     Ok(if noised < min {min} else if noised > max { max } else { noised })
 }
 
+/// Returns the candidate with the largest utility after noising, according to the Report Noisy Max mechanism.
+///
+/// Each utility is perturbed with Laplace noise of scale 2 * sensitivity / epsilon, and the candidate with the largest noisy utility is returned.
+/// For more information, see C. Dwork, A. Roth The Algorithmic Foundations of Differential Privacy, Chapter 3.3 Report Noisy Max p.35-36. August 2014.
+///
+/// Tied noisy utilities are broken uniformly at random, as a deterministic rule would make the selection depend on the order of the candidates.
+///
+/// # Arguments
+/// * `epsilon` - Multiplicative privacy loss parameter.
+/// * `sensitivity` - L1 sensitivity of utility function.
+/// * `candidate_set` - Data from which user wants an element returned.
+/// * `utilities` - Utility of each candidate.
+/// * `enforce_constant_time` - Whether or not to enforce the algorithm to run in constant time
+///
+/// # Return
+/// An element of the candidate set.
+///
+/// # Example
+/// ```
+/// use smartnoise_runtime::utilities::mechanisms::report_noisy_max;
+/// let xs: Vec<f64> = vec![1., 2., 3., 4., 5.];
+/// let ans = report_noisy_max(1.0, 1.0, &xs, xs.clone(), false);
+/// # ans.unwrap();
+/// ```
+pub fn report_noisy_max<T>(
+    epsilon: f64,
+    sensitivity: f64,
+    candidate_set: &[T],
+    utilities: Vec<f64>,
+    enforce_constant_time: bool
+) -> Result<T> where T: Clone, {
+    if epsilon <= 0. || sensitivity < 0. {
+        return Err(format!("epsilon ({}) must be positive and sensitivity ({}) must be non-negative", epsilon, sensitivity).into());
+    }
+    if candidate_set.len() != utilities.len() {
+        return Err("candidate_set and utilities must have the same length".into())
+    }
+
+    let scale = 2. * sensitivity / epsilon;
+    let noisy_utilities = utilities.into_iter()
+        .map(|utility| noise::sample_laplace(utility, scale, enforce_constant_time))
+        .collect::<Result<Vec<f64>>>()?;
+
+    Ok(candidate_set[utilities::argmax_uniform_ties(&noisy_utilities)?].clone())
+}
+
 /// Returns data element according to the Exponential mechanism.
 ///
 /// # Arguments
@@ -231,6 +296,8 @@ pub fn simple_geometric_mechanism(
 ///       the real numbers and floating-point numbers. See [Ilvento 2019](https://arxiv.org/abs/1912.04222) for
 ///       more information on the problem and a proposed fix.
 ///
+/// Candidates are sampled by weight, not by ordering, so candidates with tied utilities are selected with equal probability.
+///
 /// # Example
 /// ```
 /// use ndarray::prelude::*;
@@ -261,9 +328,12 @@ pub fn exponential_mechanism<T>(
     // get vector of e^(scaled util), then use to find probabilities
     let scaling = to_rug!(epsilon).div(to_rug!(2. * sensitivity));
 
+    // shift utilities so the largest is zero, which leaves the selection probabilities unchanged
+    let max_utility = utilities.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
     // establish selection probabilities for each element
     let e_util_vec: Vec<rug::Float> = utilities.into_iter()
-        .map(|util| (to_rug!(util) * &scaling).exp())
+        .map(|util| ((to_rug!(util) - max_utility) * &scaling).exp())
         .collect();
     let sum_e_util_vec = to_rug!(rug::Float::sum(e_util_vec.iter()));
     let probability_vec: Vec<Float> = e_util_vec.into_iter()
@@ -283,10 +353,96 @@ pub fn exponential_mechanism<T>(
     enforce_constant_time: bool
 ) -> Result<T> where T: Clone, {
 
+    // compute log-weights, then shift so the largest is zero before exponentiating.
+    // The shift leaves the selection probabilities unchanged, but prevents the weights from overflowing
+    let log_weights: Vec<f64> = utilities.into_iter()
+        .map(|x| epsilon * x / (2. * sensitivity)).collect();
+    let max_log_weight = log_weights.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
     // get vector of e^(util), and sample_from_set accepts weights
-    let weight_vec: Vec<f64> = utilities.into_iter()
-        .map(|x| (epsilon * x / (2. * sensitivity)).exp()).collect();
+    let weight_vec: Vec<f64> = log_weights.into_iter()
+        .map(|x| (x - max_log_weight).exp()).collect();
 
     // sample element relative to probability
     utilities::sample_from_set(candidate_set, &weight_vec, enforce_constant_time)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test_mechanisms {
+    use smartnoise_validator::components::simple_geometric_mechanism::geometric_variance;
+
+    use crate::utilities::argmax_uniform_ties;
+    use crate::utilities::mechanisms::{exponential_mechanism, gaussian_mechanism, laplace_mechanism, report_noisy_max, simple_geometric_mechanism};
+
+    #[test]
+    fn test_exponential_mechanism_large_utilities() {
+        // weights are e^(1e4/2) and e^(1e4/2 + ln 3), which overflow unless shifted
+        let candidates = vec![0, 1];
+        let utilities = vec![1e4, 1e4 + 2. * 3f64.ln()];
+
+        let num_trials = 1000;
+        let num_selected = (0..num_trials)
+            .map(|_| exponential_mechanism(1., 1., &candidates, utilities.clone(), false).unwrap())
+            .filter(|selected| *selected == 1)
+            .count();
+
+        // the second candidate should be chosen with probability 0.75
+        let proportion = num_selected as f64 / num_trials as f64;
+        assert!((proportion - 0.75).abs() < 0.1, "proportion: {}", proportion);
+    }
+
+    #[test]
+    fn test_uniform_tie_breaking() {
+        // candidates 1, 2 and 4 are tied
+        let scores = vec![1., 3., 3., 0., 3.];
+
+        let num_trials = 3000;
+        let mut counts = vec![0; scores.len()];
+        (0..num_trials).for_each(|_| counts[argmax_uniform_ties(&scores).unwrap()] += 1);
+
+        assert_eq!(counts[0] + counts[3], 0);
+        [1, 2, 4].iter().for_each(|index| {
+            let proportion = counts[*index] as f64 / num_trials as f64;
+            assert!((proportion - 1. / 3.).abs() < 0.05, "proportion: {}", proportion);
+        });
+
+        // equal utilities are selected with equal probability by report noisy max
+        let candidates = vec![0, 1];
+        let num_selected = (0..1000)
+            .map(|_| report_noisy_max(1., 1., &candidates, vec![5., 5.], false).unwrap())
+            .filter(|selected| *selected == 1)
+            .count();
+        assert!((num_selected as f64 / 1000. - 0.5).abs() < 0.1, "selected: {}", num_selected);
+    }
+
+    #[test]
+    fn test_max_noise_sigmas() {
+        // laplace noise with scale 1 has a standard deviation of sqrt(2)
+        let laplace_cap = 2. * 2f64.sqrt();
+        (0..1000).for_each(|_| {
+            let noised = laplace_mechanism(0., 1., 1., Some(2.), false).unwrap();
+            assert!(noised.abs() <= laplace_cap, "noised: {}", noised);
+        });
+
+        let sigma = (2. * (1.25 / 1e-6f64).ln()).sqrt();
+        (0..1000).for_each(|_| {
+            let noised = gaussian_mechanism(0., 1., 1e-6, 1., false, Some(2.), false).unwrap();
+            assert!(noised.abs() <= 2. * sigma, "noised: {}", noised);
+        });
+    }
+
+    #[test]
+    fn test_simple_geometric_variance() {
+        // bounds wide enough that censoring is negligible
+        let epsilon = 0.5;
+        let num_trials = 10_000;
+        let samples = (0..num_trials)
+            .map(|_| simple_geometric_mechanism(0, epsilon, 1., -1000, 1000, false).unwrap() as f64)
+            .collect::<Vec<f64>>();
+        let variance = samples.iter().map(|v| v.powi(2)).sum::<f64>() / num_trials as f64;
+
+        // the standard error of the estimate is about 2% of the variance
+        let expected = geometric_variance(1. / epsilon);
+        assert!((variance - expected).abs() < 0.15 * expected, "variance: {}, expected: {}", variance, expected);
+    }
+}
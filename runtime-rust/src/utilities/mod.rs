@@ -1,13 +1,21 @@
+use std::cell::RefCell;
 use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::ops::AddAssign;
 
 use ieee754::Ieee754;
 use ndarray::{ArrayD, Axis, Zip};
 use ndarray::prelude::IxDyn;
 use openssl::rand::rand_bytes;
+use openssl::sha::sha256;
 
+use smartnoise_validator::base::IndexKey;
 use smartnoise_validator::errors::*;
+use smartnoise_validator::{Integer, proto};
 use smartnoise_validator::utilities::array::{slow_select, slow_stack};
+use smartnoise_validator::utilities::serial::parse_i64_null;
+
+use crate::NodeArguments;
 
 pub mod mechanisms;
 pub mod noise;
@@ -208,11 +216,65 @@ pub fn get_bytes(n_bytes: usize) -> Result<String> {
 
 // TODO: substitute implementation with different generators
 pub fn fill_bytes(mut buffer: &mut [u8]) -> Result<()> {
+    // a seeded mechanism draws all of its randomness from the seeded generator
+    if SEEDED_GENERATOR.with(|generator| generator.borrow_mut().as_mut()
+        .map(|generator| generator.fill(buffer)).is_some()) {
+        return Ok(())
+    }
+
     if let Err(e) = rand_bytes(&mut buffer) {
         Err(format!("OpenSSL Error: {}", e).into())
     } else { Ok(()) }
 }
 
+thread_local! {
+    /// The generator of the seeded mechanism currently being evaluated on this thread, if any
+    static SEEDED_GENERATOR: RefCell<Option<SeededGenerator>> = RefCell::new(None);
+}
+
+/// Deterministic generator, whose stream of bytes is the sha256 hashes of the seed and a counter.
+///
+/// The stream is reproducible by anyone who knows the seed, so it must never be used to privatize real data.
+struct SeededGenerator {
+    seed: Integer,
+    counter: u64,
+}
+
+impl SeededGenerator {
+    fn fill(&mut self, buffer: &mut [u8]) {
+        buffer.chunks_mut(32).for_each(|chunk| {
+            let mut block = [0u8; 16];
+            block[..8].copy_from_slice(&self.seed.to_le_bytes());
+            block[8..].copy_from_slice(&self.counter.to_le_bytes());
+            self.counter += 1;
+            chunk.copy_from_slice(&sha256(&block)[..chunk.len()]);
+        })
+    }
+}
+
+/// Remove the seed from the arguments of a mechanism, if one was passed.
+pub fn take_seed(arguments: &mut NodeArguments) -> Result<Option<Integer>> {
+    arguments.remove(&IndexKey::from("seed"))
+        .map(|seed| seed.array()?.first_int())
+        .transpose()
+}
+
+/// Evaluate `function`, drawing all randomness from a generator seeded with `seed`, if set.
+///
+/// Evaluation with the same seed is reproducible, which is only useful for debugging and testing.
+/// Randomness is drawn from OpenSSL as usual when no seed is set.
+pub fn with_seed<T>(seed: Option<Integer>, function: impl FnOnce() -> Result<T>) -> Result<T> {
+    let seed = match seed {
+        Some(seed) => seed,
+        None => return function()
+    };
+    let previous = SEEDED_GENERATOR.with(|generator| generator
+        .replace(Some(SeededGenerator { seed, counter: 0 })));
+    let result = function();
+    SEEDED_GENERATOR.with(|generator| generator.replace(previous));
+    result
+}
+
 
 /// Converts an `f64` to `String` of length 64, yielding the IEEE-754 binary representation of the `f64`.
 ///
@@ -349,6 +411,36 @@ pub fn sample_from_set<T>(
     Ok(candidate_set[return_index].clone())
 }
 
+/// Index of the largest score, with ties broken uniformly at random.
+///
+/// Ties must not be broken deterministically, for example by taking the first index.
+/// The selection would then depend on the order of the candidates, so an individual whose record creates or breaks a tie
+/// could shift the selection from one candidate to another with certainty, rather than with the bounded change in probability
+/// that the privacy analysis of a noisy selection relies on.
+/// Ties are broken with the secure random number generator. NaN scores are never selected.
+///
+/// # Arguments
+/// * `scores` - Score of each candidate.
+///
+/// # Return
+/// Index of a maximal score.
+#[allow(clippy::float_cmp)]
+pub fn argmax_uniform_ties(scores: &[f64]) -> Result<usize> {
+    let max_score = scores.iter().cloned()
+        .filter(|score| !score.is_nan())
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    let tied_indices = scores.iter().enumerate()
+        .filter(|(_, score)| **score == max_score)
+        .map(|(index, _)| index)
+        .collect::<Vec<usize>>();
+
+    if tied_indices.is_empty() {
+        return Err("scores: must contain at least one score that is not NaN".into())
+    }
+    Ok(tied_indices[noise::sample_uniform_int(0, tied_indices.len() as i64 - 1)? as usize])
+}
+
 /// Accepts set and element weights and returns a subset of size k (without replacement).
 ///
 /// Weights are (after being normalized) the probability of drawing each element on the first draw (they sum to 1)
@@ -493,6 +585,61 @@ pub fn get_closest_multiple_of_lambda(x: f64, m: i16) -> Result<f64> {
     Ok(f64::recompose(sign, exponent, mantissa))
 }
 
+/// Number of decimal places released floats are rounded to, if any.
+///
+/// # Arguments
+/// * `privacy_definition` - Definition of privacy, which may set a release precision.
+///
+/// # Returns
+/// The release precision, or None if floats are released at full precision.
+pub fn get_release_precision(privacy_definition: &Option<proto::PrivacyDefinition>) -> Result<Option<u32>> {
+    privacy_definition.as_ref()
+        .and_then(|privacy_definition| privacy_definition.release_precision.clone())
+        .and_then(parse_i64_null)
+        .map(|precision| u32::try_from(precision)
+            .map_err(|_| Error::from("release_precision: must be a non-negative number of decimal places")))
+        .transpose()
+}
+
+/// Rounds x to a number of decimal places.
+///
+/// Rounding a privatized value is post-processing, so it has no effect on the privacy guarantee.
+/// It only removes the low-order bits of the float representation, which may otherwise leak information.
+///
+/// # Arguments
+/// * `x` - Number to be rounded.
+/// * `precision` - Number of decimal places to keep. If None, x is returned unchanged.
+///
+/// # Returns
+/// x, rounded to `precision` decimal places.
+pub fn round_to_precision(x: f64, precision: Option<u32>) -> f64 {
+    let precision = match precision {
+        Some(precision) => precision,
+        None => return x
+    };
+
+    let scale = 10f64.powi(precision.min(i32::MAX as u32) as i32);
+    let scaled = x * scale;
+    // x is already at a coarser precision than the float can represent after scaling
+    if !scaled.is_finite() { return x }
+    scaled.round() / scale
+}
+
+#[cfg(test)]
+mod test_round_to_precision {
+    use crate::utilities::round_to_precision;
+
+    #[test]
+    fn test_round_to_precision() {
+        assert_eq!(round_to_precision(1.23456, None), 1.23456);
+        // zero decimal places rounds to a whole number
+        assert_eq!(round_to_precision(1.5, Some(0)), 2.);
+        assert_eq!(round_to_precision(1.23456, Some(2)), 1.23);
+        assert_eq!(round_to_precision(-1.23556, Some(3)), -1.236);
+        assert_eq!(round_to_precision(1e300, Some(20)), 1e300);
+    }
+}
+
 #[cfg(test)]
 mod test_get_closest_multiple_of_lambda {
     use smartnoise_validator::hashmap;
@@ -6,13 +6,13 @@ use crate::{proto, base, Integer, Float};
 
 use ndarray::prelude::Ix1;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use ndarray::{ArrayD, arr0, Dimension, arr1};
 
 use crate::utilities::{standardize_categorical_argument, deduplicate, get_common_value};
 use indexmap::IndexMap;
 use crate::utilities::serial::{parse_argument_node_ids, serialize_index_key};
-use std::ops::{Add, Div, Mul};
+use std::ops::{Div, Mul};
 use itertools::Itertools;
 
 /// The universal data representation.
@@ -92,6 +92,52 @@ impl Value {
         }
     }
 
+    /// Check that the Value is an array with exactly one element
+    pub fn assert_scalar(&self) -> Result<()> {
+        let shape = self.ref_array()?.shape();
+        if shape.iter().product::<usize>() != 1 {
+            bail!("value must be a scalar, but has shape {:?}", shape)
+        }
+        Ok(())
+    }
+    /// Check that the Value is an array with the given number of columns
+    pub fn assert_num_columns(&self, num_columns: usize) -> Result<()> {
+        let actual_num_columns = self.ref_array()?.num_columns()?;
+        if actual_num_columns != num_columns {
+            bail!("value must have {} column(s), but has {}", num_columns, actual_num_columns)
+        }
+        Ok(())
+    }
+    /// Check that the Value is a matrix with as many rows as columns
+    pub fn assert_square(&self) -> Result<()> {
+        let shape = self.ref_array()?.shape();
+        if shape.len() != 2 || shape[0] != shape[1] {
+            bail!("value must be a square matrix, but has shape {:?}", shape)
+        }
+        Ok(())
+    }
+
+    /// Extract the float from a Value that is an array with exactly one float element
+    pub fn as_scalar_f64(&self) -> Result<Float> {
+        self.assert_scalar()?;
+        self.ref_array()?.ref_float()?.first().copied()
+            .ok_or_else(|| "value must be a scalar, but is empty".into())
+    }
+    /// Extract the integer from a Value that is an array with exactly one integer element
+    pub fn as_scalar_i64(&self) -> Result<Integer> {
+        self.assert_scalar()?;
+        self.ref_array()?.ref_int()?.first().copied()
+            .ok_or_else(|| "value must be a scalar, but is empty".into())
+    }
+    /// Extract the floats from a Value that is an array with at most one axis of length greater than one
+    pub fn as_vec_f64(&self) -> Result<Vec<Float>> {
+        let array = self.ref_array()?.ref_float()?;
+        if array.shape().iter().filter(|length| **length > 1).count() > 1 {
+            bail!("value must be a vector, but has shape {:?}", array.shape())
+        }
+        Ok(array.iter().copied().collect())
+    }
+
     pub fn from_index_key(key: IndexKey) -> Result<Self> {
         Ok(match key {
             IndexKey::Int(key) => key.into(),
@@ -125,22 +171,92 @@ impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Value::Array(lhs), Value::Array(rhs)) => lhs == rhs,
+            (Value::Jagged(lhs), Value::Jagged(rhs)) => lhs == rhs,
+            (Value::Dataframe(lhs), Value::Dataframe(rhs)) => indexmap_eq(lhs, rhs, &|l, r| l == r),
+            (Value::Partitions(lhs), Value::Partitions(rhs)) => indexmap_eq(lhs, rhs, &|l, r| l == r),
+            (Value::Function(lhs), Value::Function(rhs)) => lhs == rhs,
             _ => false
         }
     }
 }
 
+impl Value {
+    /// Check if two values are equal, where floats may differ by at most `tolerance`.
+    ///
+    /// Values of differing variants, data types or shapes are never equal.
+    pub fn approx_eq(&self, other: &Self, tolerance: Float) -> bool {
+        match (self, other) {
+            (Value::Array(lhs), Value::Array(rhs)) => lhs.approx_eq(rhs, tolerance),
+            (Value::Jagged(lhs), Value::Jagged(rhs)) => lhs.approx_eq(rhs, tolerance),
+            (Value::Dataframe(lhs), Value::Dataframe(rhs)) =>
+                indexmap_eq(lhs, rhs, &|l, r| l.approx_eq(r, tolerance)),
+            (Value::Partitions(lhs), Value::Partitions(rhs)) =>
+                indexmap_eq(lhs, rhs, &|l, r| l.approx_eq(r, tolerance)),
+            _ => self == other
+        }
+    }
+}
+
+/// columns and partitions are compared in order
+fn indexmap_eq(
+    lhs: &IndexMap<IndexKey, Value>, rhs: &IndexMap<IndexKey, Value>,
+    value_eq: &dyn Fn(&Value, &Value) -> bool
+) -> bool {
+    lhs.len() == rhs.len() && lhs.iter().zip(rhs.iter())
+        .all(|((l_key, l_value), (r_key, r_value))| l_key == r_key && value_eq(l_value, r_value))
+}
+
+fn float_approx_eq(lhs: &Float, rhs: &Float, tolerance: Float) -> bool {
+    lhs == rhs || (lhs - rhs).abs() <= tolerance
+}
+
 impl PartialEq for Array {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Array::Bool(lhs), Array::Bool(rhs)) => lhs == rhs,
             (Array::Float(lhs), Array::Float(rhs)) => lhs == rhs,
             (Array::Int(lhs), Array::Int(rhs)) => lhs == rhs,
+            (Array::Str(lhs), Array::Str(rhs)) => lhs == rhs,
+            _ => false
+        }
+    }
+}
+
+impl Array {
+    /// Check if two arrays are equal, where floats may differ by at most `tolerance`.
+    pub fn approx_eq(&self, other: &Self, tolerance: Float) -> bool {
+        match (self, other) {
+            (Array::Float(lhs), Array::Float(rhs)) => lhs.shape() == rhs.shape() && lhs.iter().zip(rhs.iter())
+                .all(|(l, r)| float_approx_eq(l, r, tolerance)),
+            _ => self == other
+        }
+    }
+}
+
+impl PartialEq for Jagged {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Jagged::Bool(lhs), Jagged::Bool(rhs)) => lhs == rhs,
+            (Jagged::Float(lhs), Jagged::Float(rhs)) => lhs == rhs,
+            (Jagged::Int(lhs), Jagged::Int(rhs)) => lhs == rhs,
+            (Jagged::Str(lhs), Jagged::Str(rhs)) => lhs == rhs,
             _ => false
         }
     }
 }
 
+impl Jagged {
+    /// Check if two jagged matrices are equal, where floats may differ by at most `tolerance`.
+    pub fn approx_eq(&self, other: &Self, tolerance: Float) -> bool {
+        match (self, other) {
+            (Jagged::Float(lhs), Jagged::Float(rhs)) => lhs.len() == rhs.len() && lhs.iter().zip(rhs.iter())
+                .all(|(l_column, r_column)| l_column.len() == r_column.len() && l_column.iter().zip(r_column.iter())
+                    .all(|(l, r)| float_approx_eq(l, r, tolerance))),
+            _ => self == other
+        }
+    }
+}
+
 // build Value from other types with .into()
 impl From<bool> for Value {
     fn from(value: bool) -> Self {
@@ -717,6 +833,14 @@ pub struct ArrayProperties {
     pub naturally_ordered: bool,
     /// proportion of original data sampled
     pub sample_proportion: Option<f64>,
+    /// true if each column is known to be non-decreasing along axis zero
+    pub monotone: bool,
+    /// true if the value, or the bounds on the value, were derived from private data
+    pub data_derived: bool,
+    /// lower bound on the number of records, when the number of records is not known exactly
+    pub num_records_lower_bound: Option<i64>,
+    /// true if the value was released by a mechanism, or is computed only from such releases
+    pub noised: bool,
 }
 
 
@@ -749,6 +873,43 @@ impl JaggedProperties {
 }
 
 impl ArrayProperties {
+    /// Properties of a differentially private release of one record and one column.
+    ///
+    /// Releases are public, noised and derived from private data.
+    /// Releases of other shapes or with known bounds override fields with struct update syntax.
+    pub fn dp_release(node_id: u32, data_type: DataType, group_id: Vec<GroupId>) -> Self {
+        ArrayProperties {
+            num_records: Some(1),
+            num_columns: Some(1),
+            nullity: false,
+            releasable: true,
+            c_stability: 1,
+            aggregator: None,
+            nature: None,
+            data_type,
+            dataset_id: None,
+            node_id: node_id as i64,
+            is_not_empty: true,
+            dimensionality: Some(1),
+            group_id,
+            naturally_ordered: true,
+            sample_proportion: None,
+            monotone: false,
+            data_derived: true,
+            num_records_lower_bound: None,
+            noised: true,
+        }
+    }
+
+    /// Mark the output of a mechanism as a differentially private release of the aggregate.
+    pub fn mark_released(&mut self) {
+        self.releasable = true;
+        self.noised = true;
+        self.aggregator = None;
+        self.monotone = false;
+        self.data_derived = true;
+    }
+
     pub fn lower(&self) -> Result<Array> {
         Ok(match (self.lower_float(), self.lower_int()) {
             (_, Ok(lower)) => Array::Int(ndarray::arr1(&lower).into_dyn()),
@@ -847,24 +1008,57 @@ impl ArrayProperties {
             None => Err("Categorical nature is not defined. Use a clamp to restrict the data categories.".into())
         }
     }
+    /// Number of distinct rows the data may take, if every column is restricted to a known set of categories
+    pub fn domain_size(&self) -> Option<i64> {
+        self.categories().ok()?.num_records().into_iter()
+            .try_fold(1i64, |size, num_categories| size.checked_mul(num_categories))
+    }
     pub fn assert_non_null(&self) -> Result<()> {
         if self.nullity { Err("Data may contain nullity when non-nullity is required. Use imputation to acquire this property.".into()) } else { Ok(()) }
     }
     pub fn assert_is_not_empty(&self) -> Result<()> {
         if self.is_not_empty { Ok(()) } else { Err("Data may be empty when non-emptiness is required. Use a data resize to acquire this property.".into()) }
     }
+    pub fn assert_bounds_are_public(&self) -> Result<()> {
+        if self.data_derived { Err("Bounds on the data were derived from private data, so they leak information that the sensitivity does not account for. Use bounds from a public source.".into()) } else { Ok(()) }
+    }
     pub fn assert_is_releasable(&self) -> Result<()> {
         if self.releasable { Ok(()) } else { Err("data is not releasable when releasability is required".into()) }
     }
+    /// Check that the data has at least as many columns as the component requires.
+    pub fn assert_minimum_columns(&self, component: &str, minimum: i64) -> Result<()> {
+        let num_columns = self.num_columns()?;
+        if num_columns < minimum {
+            bail!("{} requires at least {} column(s), but the data has {}", component, minimum, num_columns)
+        }
+        Ok(())
+    }
     pub fn num_columns(&self) -> Result<i64> {
         self.num_columns.ok_or_else(|| "Number of columns is not defined. Use a data resize to acquire this property.".into())
     }
     pub fn num_records(&self) -> Result<i64> {
         self.num_records.ok_or_else(|| "Number of records is not defined. Use a data resize to acquire this property.".into())
     }
+    /// The fewest records the data may have: the number of records if known, otherwise the lower bound from a resize
+    pub fn num_records_lower_bound(&self) -> Result<i64> {
+        self.num_records.or(self.num_records_lower_bound)
+            .ok_or_else(|| "Number of records is not defined. Use a data resize with number_rows or minimum_rows to acquire this property.".into())
+    }
     pub fn assert_is_not_aggregated(&self) -> Result<()> {
         if self.aggregator.is_some() { Err("aggregated data may not be manipulated".into()) } else { Ok(()) }
     }
+    /// Aggregators and mechanisms may not take a value that was already released by a mechanism.
+    pub fn assert_is_not_noised(&self) -> Result<()> {
+        if self.noised {
+            Err("data has already been released by a mechanism. Aggregating or noising it again spends budget on a value that is already private, and its sensitivity does not describe the original data. Post-process the release instead".into())
+        } else { Ok(()) }
+    }
+    /// Whether a value computed from this and another value is noised.
+    ///
+    /// Only values computed entirely from releases are releases.
+    pub fn noised_with(&self, other: &ArrayProperties) -> bool {
+        self.noised && other.noised
+    }
     pub fn assert_is_not_sampled(&self) -> Result<()> {
         if self.sample_proportion.unwrap_or(1.) != 1. {
             Err("sampled data may not be manipulated in this way".into())
@@ -1100,6 +1294,37 @@ pub struct ComponentExpansion {
     pub warnings: Vec<Error>
 }
 
+/// Privacy usage and accuracy of a single mechanism, as estimated without executing the analysis.
+#[derive(Clone, Debug)]
+pub struct DryRunNode {
+    pub node_id: u32,
+    pub privacy_usages: Vec<proto::PrivacyUsage>,
+    /// accuracy of each column, if the mechanism has a closed-form accuracy
+    pub accuracies: Option<Vec<proto::Accuracy>>,
+}
+
+/// Summary of the privacy usage and accuracy of an analysis, as estimated without executing the analysis.
+#[derive(Clone, Debug)]
+pub struct DryRun {
+    pub nodes: Vec<DryRunNode>,
+    pub privacy_usage: proto::PrivacyUsage,
+}
+
+/// Outcome of validating every node of an analysis, without stopping at the first invalid node.
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    /// error of each invalid node, keyed by node id
+    pub errors: BTreeMap<u32, Error>,
+    /// warnings from the valid nodes
+    pub warnings: Vec<Error>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
 impl ComponentExpansion {
     pub fn is_valid(&self, component_id: u32) -> Result<()> {
         let offset = if self.computation_graph.contains_key(&component_id) { 1 } else { 0 };
@@ -1132,6 +1357,16 @@ impl proto::Component {
         };
     }
 
+    pub fn remove_argument(&mut self, key: &IndexKey) {
+        let key = serialize_index_key(key.clone());
+        if let Some(arguments) = &mut self.arguments {
+            if let Some(idx) = arguments.keys.iter().position(|idx| idx == &key) {
+                arguments.keys.remove(idx);
+                arguments.values.remove(idx);
+            }
+        }
+    }
+
     pub fn arguments(&self) -> IndexMap<IndexKey, u32> {
         match &self.arguments {
             Some(arguments) => parse_argument_node_ids(arguments.clone()),
@@ -1171,9 +1406,11 @@ impl proto::PrivacyUsage {
                             return Err(Error::from("large epsilon (>100) with privacy amplification by subsampling is numerically unstable")),
                         s => (((epsilon.exp() - 1.) / s) + 1.).ln() / c_stability as f64
                     },
-                    delta: delta / s / ((c_stability as f64 * epsilon).exp() - 1.) / (epsilon.exp() - 1.),
+                    // inverse of the group privacy expansion of delta in effective_to_actual
+                    delta: delta / s / ((c_stability as f64 * epsilon).exp() - 1.) * (epsilon.exp() - 1.),
                 })
-            })
+            }),
+            units: self.units
         })
     }
 
@@ -1195,28 +1432,62 @@ impl proto::PrivacyUsage {
                     },
                     delta: delta * s * ((c_stability as f64 * epsilon).exp() - 1.) / (epsilon.exp() - 1.),
                 })
-            })
+            }),
+            units: self.units
         })
     }
-}
 
+    /// Convert a local (per-record) privacy usage into its central equivalent.
+    ///
+    /// Each record is assumed to be privatized independently with the local usage (for example, via randomized response),
+    /// and the privatized records of the `population_size` individuals are released in a random order.
+    /// The central usage is then computed with amplification by shuffling, introducing `delta`.
+    /// If the amplification bound does not apply, the local epsilon is used, since epsilon-LDP implies central epsilon-DP.
+    ///
+    /// Reference: Feldman, McMillan, Talwar. "Hiding Among the Clones" (2020), Theorem 3.1
+    pub fn local_to_central(&self, population_size: u64, delta: f64) -> Result<Self> {
+        use proto::privacy_usage::{DistanceApproximate, Distance::Approximate, Units};
 
-impl Add<proto::PrivacyUsage> for proto::PrivacyUsage {
-    type Output = Result<proto::PrivacyUsage>;
-
-    fn add(mut self, rhs: proto::PrivacyUsage) -> Self::Output {
-        let left_distance = self.distance.ok_or_else(|| "distance must be defined")?;
-        let right_distance = rhs.distance.ok_or_else(|| "distance must be defined")?;
+        if Units::from_i32(self.units) != Some(Units::Local) {
+            return Err("privacy usage must be in local units to convert to central units".into())
+        }
+        if population_size == 0 {
+            return Err("population size must be greater than zero".into())
+        }
+        if !(0. < delta && delta < 1.) {
+            return Err("delta must be in (0, 1)".into())
+        }
 
-        use proto::privacy_usage::Distance;
+        Ok(proto::PrivacyUsage {
+            distance: Some(match self.distance.as_ref().ok_or_else(|| "distance must be defined")? {
+                Approximate(DistanceApproximate { epsilon: local_epsilon, delta: local_delta }) => {
+                    if *local_delta != 0. {
+                        return Err("conversion to central units is only supported for pure local privacy usages".into())
+                    }
+                    let n = population_size as f64;
+                    let central_epsilon = if *local_epsilon <= (n / (16. * (2. / delta).ln())).ln() {
+                        let exp_eps = local_epsilon.exp();
+                        ((exp_eps - 1.) / (exp_eps + 1.)
+                            * (8. * (exp_eps * (4. / delta).ln()).sqrt() / n.sqrt() + 8. * exp_eps / n)).ln_1p()
+                            .min(*local_epsilon)
+                    } else {
+                        *local_epsilon
+                    };
+                    Approximate(DistanceApproximate { epsilon: central_epsilon, delta })
+                }
+            }),
+            units: Units::Central as i32
+        })
+    }
 
-        self.distance = Some(match (left_distance, right_distance) {
-            (Distance::Approximate(lhs), Distance::Approximate(rhs)) => proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
-                epsilon: lhs.epsilon + rhs.epsilon,
-                delta: lhs.delta + rhs.delta,
-            })
-        });
-        Ok(self)
+    /// A privacy usage with no privacy loss is compatible with any units.
+    #[allow(clippy::float_cmp)]
+    pub(crate) fn is_zero(&self) -> bool {
+        match &self.distance {
+            Some(proto::privacy_usage::Distance::Approximate(approximate)) =>
+                approximate.epsilon == 0. && approximate.delta == 0.,
+            None => false
+        }
     }
 }
 
@@ -1253,6 +1524,25 @@ impl Div<f64> for proto::PrivacyUsage {
 pub mod test_data {
 
     use crate::base::Value;
+    use crate::proto;
+
+    /// Central privacy usage with the given epsilon and delta.
+    pub fn privacy_usage(epsilon: f64, delta: f64) -> proto::PrivacyUsage {
+        proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon, delta,
+            })),
+            units: proto::privacy_usage::Units::Central as i32
+        }
+    }
+
+    /// Local privacy usage with the given epsilon and delta.
+    pub fn local_privacy_usage(epsilon: f64, delta: f64) -> proto::PrivacyUsage {
+        proto::PrivacyUsage {
+            units: proto::privacy_usage::Units::Local as i32,
+            ..privacy_usage(epsilon, delta)
+        }
+    }
 
     pub fn array1d_f64_0() -> Value {
         ndarray::arr1::<f64>(&[]).into()
@@ -1338,3 +1628,98 @@ pub mod test_data {
         ]).into()
     }
 }
+
+#[cfg(test)]
+mod test_base {
+    use ndarray::{arr1, arr2};
+
+    use crate::base::{Jagged, Value};
+
+    #[test]
+    fn test_approx_eq_float_tolerance() {
+        let value: Value = arr1(&[1., 2.]).into_dyn().into();
+        let perturbed: Value = arr1(&[1. + 1e-10, 2.]).into_dyn().into();
+
+        assert!(value != perturbed);
+        assert!(value.approx_eq(&perturbed, 1e-8));
+        assert!(!value.approx_eq(&perturbed, 1e-12));
+    }
+
+    #[test]
+    fn test_approx_eq_shape_mismatch() {
+        let vector: Value = arr1(&[1., 2.]).into_dyn().into();
+        let matrix: Value = arr2(&[[1.], [2.]]).into_dyn().into();
+        let longer: Value = arr1(&[1., 2., 3.]).into_dyn().into();
+
+        // the same elements in a different shape are not equal
+        assert!(!vector.approx_eq(&matrix, 1.));
+        assert!(!vector.approx_eq(&longer, 1.));
+
+        // differing data types are not equal, even when the numbers match
+        let ints: Value = arr1(&[1i64, 2]).into_dyn().into();
+        assert!(!vector.approx_eq(&ints, 1.));
+        assert!(vector != ints);
+    }
+
+    #[test]
+    fn test_approx_eq_jagged() {
+        let jagged = Value::Jagged(Jagged::Float(vec![vec![1., 2.], vec![3.]]));
+        let perturbed = Value::Jagged(Jagged::Float(vec![vec![1., 2. + 1e-10], vec![3.]]));
+        let reshaped = Value::Jagged(Jagged::Float(vec![vec![1.], vec![2., 3.]]));
+
+        assert!(jagged.approx_eq(&perturbed, 1e-8));
+        assert!(!jagged.approx_eq(&reshaped, 1.));
+
+        let categories = Value::Jagged(Jagged::Str(vec![vec!["a".to_string(), "b".to_string()]]));
+        assert!(categories == categories.clone());
+        assert!(categories.approx_eq(&categories.clone(), 0.));
+    }
+
+    #[test]
+    fn test_shape_assertions() {
+        let scalar: Value = 1.0.into();
+        let column: Value = arr1(&[1., 2.]).into_dyn().into();
+        let row: Value = arr2(&[[1., 2.]]).into_dyn().into();
+        let square: Value = arr2(&[[1., 2.], [3., 4.]]).into_dyn().into();
+
+        assert!(scalar.assert_scalar().is_ok());
+        assert!(Value::from(arr1(&[1.]).into_dyn()).assert_scalar().is_ok());
+        assert!(column.assert_scalar().is_err());
+
+        // a value with two columns in a scalar slot names the offending shape
+        let error = row.assert_scalar().unwrap_err().to_string();
+        assert_eq!(error, "value must be a scalar, but has shape [1, 2]");
+
+        assert!(column.assert_num_columns(1).is_ok());
+        assert_eq!(row.assert_num_columns(1).unwrap_err().to_string(), "value must have 1 column(s), but has 2");
+
+        assert!(square.assert_square().is_ok());
+        assert!(row.assert_square().is_err());
+        assert!(column.assert_square().is_err());
+        assert!(Value::Jagged(Jagged::Float(vec![vec![1.]])).assert_scalar().is_err());
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_typed_extraction() {
+        let scalar: Value = 1.5.into();
+        let singleton: Value = arr2(&[[2.5]]).into_dyn().into();
+        let count: Value = 3.into();
+        let column: Value = arr2(&[[1.], [2.]]).into_dyn().into();
+
+        assert_eq!(scalar.as_scalar_f64().unwrap(), 1.5);
+        assert_eq!(singleton.as_scalar_f64().unwrap(), 2.5);
+        assert_eq!(count.as_scalar_i64().unwrap(), 3);
+        assert_eq!(column.as_vec_f64().unwrap(), vec![1., 2.]);
+        assert_eq!(scalar.as_vec_f64().unwrap(), vec![1.5]);
+
+        // mismatched shapes, atomic types and variants each name the mismatch
+        assert_eq!(column.as_scalar_f64().unwrap_err().to_string(), "value must be a scalar, but has shape [2, 1]");
+        assert_eq!(count.as_scalar_f64().unwrap_err().to_string(), "atomic type: expected float, got integer");
+        assert_eq!(scalar.as_scalar_i64().unwrap_err().to_string(), "atomic type: expected integer, got float");
+        assert_eq!(Value::from(arr2(&[[1., 2.], [3., 4.]]).into_dyn()).as_vec_f64().unwrap_err().to_string(),
+                   "value must be a vector, but has shape [2, 2]");
+        assert_eq!(Value::Jagged(Jagged::Float(vec![vec![1.]])).as_scalar_f64().unwrap_err().to_string(),
+                   "value must be an array");
+    }
+}
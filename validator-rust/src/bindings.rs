@@ -50,7 +50,12 @@ impl Analysis {
                 protect_elapsed_time: false,
                 protect_memory_utilization: false,
                 protect_floating_point: true,
-                protect_sensitivity: true
+                protect_sensitivity: true,
+                max_contributions_per_user: 0,
+                release_precision: None,
+                privacy_unit: proto::privacy_definition::PrivacyUnit::Row as i32,
+                user_id: String::new(),
+                allow_unknown_num_records: false,
             },
             components: HashMap::new(),
             component_count: 0,
@@ -3,12 +3,13 @@ use crate::errors::*;
 use std::collections::HashMap;
 
 
-use crate::{proto, base, Warnable, Integer};
+use crate::{proto, base, Warnable, Float, Integer};
 use crate::hashmap;
 use crate::components::{Component, Expandable};
 
 use crate::base::{Value, NodeProperties, ValueProperties, DataType, Nature, NatureCategorical, Jagged, Vector1DNull, NatureContinuous, Array, IndexKey};
-use crate::utilities::{prepend, get_argument};
+use crate::utilities::{prepend, get_argument, get_literal};
+use crate::utilities::inference::infer_property;
 use itertools::Itertools;
 use indexmap::map::IndexMap;
 
@@ -37,6 +38,7 @@ impl Component for proto::Cast {
             "str" => DataType::Str,
             _ => bail!("data type is not recognized. Must be one of \"float\", \"int\", \"bool\" or \"string\"")
         };
+        data_property.monotone = false;
 
         match data_property.data_type {
             DataType::Unknown => unreachable!(),
@@ -44,6 +46,24 @@ impl Component for proto::Cast {
                 // true label must be defined
                 let true_label = get_argument(&public_arguments, "true_label")?.clone().array()?;
 
+                // when both labels are given, values matching neither label are null.
+                //    Bool arrays cannot represent nulls, so the data becomes float indicators in [0, 1], with NAN for nulls
+                if let Some(false_label) = public_arguments.get::<IndexKey>(&"false_label".into()) {
+                    match (false_label.ref_array()?, &true_label) {
+                        (Array::Int(_), Array::Int(_)) | (Array::Float(_), Array::Float(_)) |
+                        (Array::Bool(_), Array::Bool(_)) | (Array::Str(_), Array::Str(_)) => (),
+                        _ => return Err("type of false_label must match the type of true_label".into())
+                    }
+                    let num_columns = data_property.num_columns()?;
+                    data_property.data_type = DataType::Float;
+                    data_property.nature = Some(Nature::Continuous(NatureContinuous {
+                        lower: Vector1DNull::Float((0..num_columns).map(|_| Some(0.)).collect()),
+                        upper: Vector1DNull::Float((0..num_columns).map(|_| Some(1.)).collect()),
+                    }));
+                    data_property.nullity = true;
+                    return Ok(ValueProperties::Array(data_property).into())
+                }
+
                 // check categories for equality with true_label
                 data_property.nature = match data_property.nature {
                     Some(nature) => match nature {
@@ -82,7 +102,26 @@ impl Component for proto::Cast {
 
                 data_property.nullity = false;
             },
+            DataType::Int if self.index => {
+                // each value is replaced by its position in the categories
+                let num_categories = get_index_categories(&public_arguments, &properties, &data_property)?
+                    .iter().map(|categories| categories.len() as Integer).collect::<Vec<Integer>>();
+                if num_categories.contains(&0) {
+                    return Err("categories: must be non-empty for each column".into())
+                }
+
+                data_property.nature = Some(Nature::Continuous(NatureContinuous {
+                    lower: Vector1DNull::Int(num_categories.iter().map(|_| Some(0)).collect()),
+                    upper: Vector1DNull::Int(num_categories.iter().map(|v| Some(v - 1)).collect()),
+                }));
+                data_property.nullity = false;
+            },
             DataType::Int => {
+                let overflow = self.overflow.to_lowercase();
+                if !["error", "saturate", "wrap"].contains(&overflow.as_str()) {
+                    bail!("overflow policy is not recognized. Must be one of \"error\", \"saturate\" or \"wrap\"")
+                }
+
                 // lower must be defined, for imputation of values that won't cast
                 get_argument(&public_arguments, "lower")?.ref_array()?.first_int()
                     .map_err(prepend("type:"))?;
@@ -96,28 +135,45 @@ impl Component for proto::Cast {
                             // properties are lost because floats cannot be categorical
                             Jagged::Float(_) => None,
                             Jagged::Int(_) => Some(nature),
-                            Jagged::Bool(cats) =>
-                                Some(Nature::Categorical(NatureCategorical {
-                                    categories: Jagged::Int(cats.into_iter()
-                                        .map(|cats| cats.into_iter()
-                                            .map(|v| if v { 1 } else { 0 })
-                                            .unique().collect::<Vec<Integer>>())
-                                        .collect())
-                                })),
+                            // indicators are bounded by [0, 1], so that sums of indicators have a tight sensitivity
+                            Jagged::Bool(cats) => {
+                                let cats = cats.into_iter()
+                                    .map(|cats| cats.into_iter()
+                                        .map(|v| if v { 1 } else { 0 })
+                                        .collect::<Vec<Integer>>())
+                                    .collect::<Vec<Vec<Integer>>>();
+                                Some(Nature::Continuous(NatureContinuous {
+                                    lower: Vector1DNull::Int(cats.iter()
+                                        .map(|cats| cats.iter().min().cloned()).collect()),
+                                    upper: Vector1DNull::Int(cats.iter()
+                                        .map(|cats| cats.iter().max().cloned()).collect()),
+                                }))
+                            },
 
                             // properties are lost because of potential imputation
                             Jagged::Str(_) => None
                         },
                         Nature::Continuous(bounds) => match (bounds.lower.clone(), bounds.upper.clone()) {
-                            (Vector1DNull::Float(lower), Vector1DNull::Float(upper)) =>
+                            (Vector1DNull::Float(lower), Vector1DNull::Float(upper)) => {
+                                // values beyond the range of i64 are either rejected or saturated,
+                                // so the bounds are clamped to the range, as `as` does when rounding.
+                                // Wrapped values may land anywhere in the range, so the bounds of a column are lost
+                                let (lower, upper): (Vec<_>, Vec<_>) = lower.into_iter().zip(upper)
+                                    .map(|bounds| match bounds {
+                                        (Some(lower), Some(upper)) if int_contains(lower) && int_contains(upper) =>
+                                            (Some(lower), Some(upper)),
+                                        _ if overflow == "wrap" => (None, None),
+                                        bounds => bounds
+                                    })
+                                    .map(|(lower, upper)| (
+                                        lower.map(|v| v.round() as Integer),
+                                        upper.map(|v| v.round() as Integer)))
+                                    .unzip();
                                 Some(Nature::Continuous(NatureContinuous {
-                                    lower: Vector1DNull::Int(lower.into_iter()
-                                        .map(|v| v.map(|v| v.round() as Integer))
-                                        .collect()),
-                                    upper: Vector1DNull::Int(upper.into_iter()
-                                        .map(|v| v.map(|v| v.round() as Integer))
-                                        .collect())
-                                })),
+                                    lower: Vector1DNull::Int(lower),
+                                    upper: Vector1DNull::Int(upper)
+                                }))
+                            },
                             (Vector1DNull::Int(_), Vector1DNull::Int(_)) =>
                                 Some(Nature::Continuous(NatureContinuous { lower: bounds.lower, upper: bounds.upper })),
                             _ => None
@@ -173,8 +229,84 @@ impl Component for proto::Cast {
 
 }
 
+impl Expandable for proto::Cast {
+    /// Materialize the categories of the data as an argument, so that the runtime encodes against the same categories.
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        if !self.index || properties.contains_key::<IndexKey>(&"categories".into()) {
+            return Ok(expansion)
+        }
+
+        let categories = properties.get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data: missing"))?.array()
+            .map_err(prepend("data:"))?.categories()
+            .map_err(|_| Error::from("data: must be categorical to cast to an index"))?;
+
+        maximum_id += 1;
+        let id_categories = maximum_id;
+        let (patch_node, release) = get_literal(Value::Jagged(categories), component.submission)?;
+        expansion.computation_graph.insert(id_categories, patch_node);
+        expansion.properties.insert(id_categories, infer_property(&release.value, None, id_categories)?);
+        expansion.releases.insert(id_categories, release);
+
+        let mut component = component.clone();
+        component.insert_argument(&"categories".into(), id_categories);
+        expansion.computation_graph.insert(component_id, component);
+
+        Ok(expansion)
+    }
+}
+
+/// Whether a float rounds to a value within the range of i64.
+fn int_contains(value: Float) -> bool {
+    // 2^63, the smallest float beyond the range of i64
+    const LIMIT: Float = 9_223_372_036_854_775_808.;
+    (-LIMIT..LIMIT).contains(&value.round())
+}
+
+/// The categories of each column, in the order they are encoded.
+///
+/// If categories are passed explicitly, every category of the data must be among them,
+/// so that no value may fall outside of the encoding.
+pub(crate) fn get_index_categories(
+    public_arguments: &IndexMap<IndexKey, &Value>,
+    properties: &NodeProperties,
+    data_property: &base::ArrayProperties,
+) -> Result<Vec<Vec<IndexKey>>> {
+    let data_categories = data_property.categories()
+        .map_err(prepend("data:"))?
+        .to_index_keys()?;
+
+    if !properties.contains_key::<IndexKey>(&"categories".into()) {
+        return Ok(data_categories)
+    }
+
+    let categories = public_arguments.get::<IndexKey>(&"categories".into())
+        .ok_or_else(|| Error::from("categories: must be public"))?
+        .ref_jagged().map_err(prepend("categories:"))?
+        .to_index_keys()?;
+
+    if categories.len() != data_categories.len() {
+        return Err("categories: must be defined for each column of data".into())
+    }
+    if !data_categories.iter().zip(categories.iter())
+        .all(|(data_categories, categories)| data_categories.iter().all(|v| categories.contains(v))) {
+        return Err("categories: must contain every category of data".into())
+    }
+    Ok(categories)
+}
+
 macro_rules! make_expandable {
-    ($variant:ident, $var_type:expr) => {
+    ($variant:ident, $var_type:expr, $overflow:expr) => {
         impl Expandable for proto::$variant {
             fn expand_component(
                 &self,
@@ -189,7 +321,9 @@ macro_rules! make_expandable {
                     computation_graph: hashmap![component_id => proto::Component {
                         arguments: component.arguments.clone(),
                         variant: Some(proto::component::Variant::Cast(proto::Cast {
-                            atomic_type: $var_type
+                            atomic_type: $var_type,
+                            index: false,
+                            overflow: $overflow(self)
                         })),
                         omit: component.omit,
                         submission: component.submission,
@@ -205,15 +339,16 @@ macro_rules! make_expandable {
     }
 }
 
-make_expandable!(ToBool, "bool".to_string());
-make_expandable!(ToFloat, "float".to_string());
-make_expandable!(ToInt, "int".to_string());
-make_expandable!(ToString, "string".to_string());
+make_expandable!(ToBool, "bool".to_string(), |_| "error".to_string());
+make_expandable!(ToFloat, "float".to_string(), |_| "error".to_string());
+make_expandable!(ToInt, "int".to_string(), |to_int: &proto::ToInt| to_int.overflow.clone());
+make_expandable!(ToString, "string".to_string(), |_| "error".to_string());
 
 
 #[cfg(test)]
 pub mod test_cast {
-    use crate::base::test_data;
+    use crate::base::{DataType, test_data, Value};
+    use crate::Integer;
 
     pub mod utilities {
         use crate::components::literal::test_literal;
@@ -244,6 +379,14 @@ pub mod test_cast {
             (analysis, cast)
         }
 
+        pub fn analysis_bool_labeled(value: Value, true_label: Value, false_label: Value) -> (Analysis, u32) {
+            let (mut analysis, literal) = test_literal::analysis_literal(value, true);
+            let true_label = analysis.literal().value(true_label).value_public(true).build();
+            let false_label = analysis.literal().value(false_label).value_public(true).build();
+            let cast = analysis.to_bool(literal, true_label).false_label(false_label).build();
+            (analysis, cast)
+        }
+
         pub fn analysis_bool(value: Value, true_label: Value) -> (Analysis, u32) {
             let (mut analysis, literal) = test_literal::analysis_literal(value, true);
             let true_label = analysis.literal().value(true_label).value_public(true).build();
@@ -283,4 +426,88 @@ pub mod test_cast {
         array1d_string_10_uniform: "a".to_string().into(),
         array1d_bool_10_uniform: true.into(),
     );
+    #[test]
+    fn test_bool_labels() {
+        let (analysis, cast) = utilities::analysis_bool_labeled(
+            test_data::array1d_string_10_uniform(), "a".to_string().into(), "b".to_string().into());
+        let cast_property = analysis.properties(cast).unwrap().array().unwrap().clone();
+        // values matching neither label are null, so the labels are cast to nullable indicators
+        assert!(cast_property.nullity);
+        assert_eq!(cast_property.data_type, DataType::Float);
+        assert_eq!(cast_property.lower_float().unwrap(), vec![0.]);
+        assert_eq!(cast_property.upper_float().unwrap(), vec![1.]);
+
+        let (analysis, cast) = utilities::analysis_bool_labeled(
+            test_data::array1d_string_10_uniform(), "a".to_string().into(), 0.into());
+        assert!(analysis.properties(cast).is_err());
+    }
+
+    #[test]
+    fn test_index() {
+        use crate::components::literal::test_literal;
+        use ndarray::arr1;
+
+        let (mut analysis, literal) = test_literal::analysis_literal(
+            arr1(&["low", "high", "med", "low"]).mapv(|v| v.to_string()).into_dyn().into(), true);
+        let categories = analysis.literal()
+            .value(Value::Jagged(vec![vec!["low", "med", "high"].into_iter().map(String::from).collect::<Vec<_>>()].into()))
+            .value_public(true).build();
+        let cast = analysis.cast(literal, "int".to_string())
+            .index(true).categories(categories).build();
+
+        // low, med and high are encoded as 0, 1 and 2
+        let cast_property = analysis.properties(cast).unwrap().array().unwrap().clone();
+        assert!(!cast_property.nullity);
+        assert_eq!(cast_property.lower_int().unwrap(), vec![0]);
+        assert_eq!(cast_property.upper_int().unwrap(), vec![2]);
+
+        // the encoding must cover every category of the data
+        let partial = analysis.literal()
+            .value(Value::Jagged(vec![vec!["low".to_string(), "med".to_string()]].into()))
+            .value_public(true).build();
+        let cast = analysis.cast(literal, "int".to_string())
+            .index(true).categories(partial).build();
+        assert!(analysis.properties(cast).is_err());
+
+        // without explicit categories, the declared categories of the data are used, including the null value
+        let (mut analysis, clamped) = crate::components::clamp::test_clamp::utilities::analysis_i64_cat(
+            test_data::array1d_i64_10_uniform(),
+            Value::Jagged(vec![vec![1i64, 2, 3, 5]].into()), Some((-1).into()));
+        let cast = analysis.cast(clamped, "int".to_string()).index(true).build();
+        assert_eq!(analysis.properties(cast).unwrap().array().unwrap().upper_int().unwrap(), vec![4]);
+
+        // data without categories cannot be encoded
+        let (mut analysis, literal) = test_literal::analysis_literal(test_data::array1d_f64_10_uniform(), false);
+        let cast = analysis.cast(literal, "int".to_string()).index(true).build();
+        assert!(analysis.properties(cast).is_err());
+    }
+
+    #[test]
+    fn test_overflow() {
+        use crate::components::clamp::test_clamp;
+
+        // the upper bound is beyond the range of i64
+        let cast_bounds = |overflow: &str| {
+            let (mut analysis, clamped) = test_clamp::utilities::analysis_f64_cont(
+                test_data::array1d_f64_10_uniform(), Some((-1.).into()), Some(1e19.into()));
+            let lower = analysis.literal().value(0.into()).value_public(true).build();
+            let upper = analysis.literal().value(10.into()).value_public(true).build();
+            let cast = analysis.cast(clamped, "int".to_string())
+                .lower(lower).upper(upper)
+                .overflow(overflow.to_string())
+                .build();
+            analysis.properties(cast).map(|properties| {
+                let properties = properties.array().unwrap().clone();
+                (properties.lower_int().ok(), properties.upper_int().ok())
+            })
+        };
+
+        // values that would overflow are rejected at runtime, so the bounds are clamped to the range
+        assert_eq!(cast_bounds("error").unwrap(), (Some(vec![-1]), Some(vec![Integer::MAX])));
+        // values are replaced by the nearest integer
+        assert_eq!(cast_bounds("saturate").unwrap(), (Some(vec![-1]), Some(vec![Integer::MAX])));
+        // wrapped values may land anywhere in the range
+        assert_eq!(cast_bounds("wrap").unwrap(), (None, None));
+        assert!(cast_bounds("truncate").is_err());
+    }
 }
@@ -1,9 +1,9 @@
 use crate::errors::*;
 
-use crate::base::{Nature, Vector1DNull, Array, ValueProperties, NatureCategorical, Jagged, DataType};
+use crate::base::{Nature, Vector1DNull, Array, ArrayProperties, ValueProperties, NatureCategorical, Jagged, DataType};
 
-use crate::{proto, base, Warnable};
-use crate::utilities::{prepend, get_literal, standardize_null_target_argument};
+use crate::{proto, base, Integer, Warnable};
+use crate::utilities::{prepend, get_array_property, get_literal, standardize_null_target_argument};
 use crate::components::{Component, Expandable};
 
 use crate::base::{IndexKey, Value, NatureContinuous};
@@ -32,47 +32,43 @@ impl Component for proto::Clamp {
 
         // handle categorical clamping
         if let Some(categories) = public_arguments.get::<IndexKey>(&"categories".into()) {
-            let null = public_arguments.get::<IndexKey>(&"null_value".into())
-                .ok_or_else(|| Error::from("null value must be defined when clamping by categories"))?
+            // elements outside of the category set are mapped into the otherwise bucket
+            let null = public_arguments.get::<IndexKey>(&"otherwise".into())
+                .or_else(|| public_arguments.get::<IndexKey>(&"null_value".into()))
+                .ok_or_else(|| Error::from("otherwise must be defined when clamping by categories"))?
                 .ref_array()?;
 
-            let mut categories = categories.ref_jagged()?.clone();
-            match (&mut categories, null) {
-                (Jagged::Float(jagged), Array::Float(null)) => {
-                    let null_target = standardize_null_target_argument(null.clone(), num_columns)?;
-                    jagged.iter_mut().zip(null_target.into_iter())
-                        .for_each(|(cats, null)| cats.push(null))
-                },
-                (Jagged::Int(jagged), Array::Int(null)) => {
-                    let null_target = standardize_null_target_argument(null.clone(), num_columns)?;
-                    jagged.iter_mut().zip(null_target.into_iter())
-                        .for_each(|(cats, null)| cats.push(null))
-                },
-                (Jagged::Str(jagged), Array::Str(null)) => {
-                    let null_target = standardize_null_target_argument(null.clone(), num_columns)?;
-                    jagged.iter_mut().zip(null_target.into_iter())
-                        .for_each(|(cats, null)| cats.push(null))
-                },
-                (Jagged::Bool(jagged), Array::Bool(null)) => {
-                    let null_target = standardize_null_target_argument(null.clone(), num_columns)?;
-                    jagged.iter_mut().zip(null_target.into_iter())
-                        .for_each(|(cats, null)| cats.push(null))
-                },
-                _ => return Err("categories and null_value must be homogeneously typed".into())
-            };
-            categories = categories.standardize(num_columns)?;
+            let categories = categories_with_null(categories.ref_jagged()?.clone(), null, num_columns)?;
             data_property.nature = Some(Nature::Categorical(NatureCategorical { categories }));
+            // every element is mapped into the category set
+            data_property.nullity = false;
 
             return Ok(ValueProperties::Array(data_property).into())
         }
 
+        // bounds derived from private data, like a release of the maximum, leak information about the data
+        if ["lower", "upper"].iter()
+            .filter_map(|name| properties.get::<IndexKey>(&(*name).into()))
+            .filter_map(|bound| bound.array().ok())
+            .any(|bound| bound.data_derived || bound.aggregator.is_some()) {
+            data_property.data_derived = true;
+        }
+
+        // nulls are not clamped, so are either left for a later impute, or rejected when evaluated
+        data_property.nullity = match self.null_policy.to_lowercase().as_str() {
+            "pass" => data_property.nullity,
+            "error" => false,
+            _ => return Err("null_policy: must be one of [`Pass`, `Error`]".into())
+        };
+
         // else handle numerical clamping
         match data_property.data_type {
             DataType::Float => {
 
                 // 1. check public arguments (constant n)
                 let mut clamp_lower = match public_arguments.get::<IndexKey>(&"lower".into()) {
-                    Some(&lower) => lower.ref_array()?.clone().vec_float(Some(num_columns))
+                    Some(&lower) => broadcast_bound(lower.ref_array()?.clone().vec_float(Some(num_columns))
+                        .map_err(prepend("lower:"))?, num_columns)
                         .map_err(prepend("lower:"))?,
 
                     // 2. then private arguments (for example from another clamped column)
@@ -88,7 +84,8 @@ impl Component for proto::Clamp {
 
                 // 1. check public arguments (constant n)
                 let mut clamp_upper = match public_arguments.get::<IndexKey>(&"upper".into()) {
-                    Some(&upper) => upper.ref_array()?.clone().vec_float(Some(num_columns))
+                    Some(&upper) => broadcast_bound(upper.ref_array()?.clone().vec_float(Some(num_columns))
+                        .map_err(prepend("upper:"))?, num_columns)
                         .map_err(prepend("upper:"))?,
 
                     // 2. then private arguments (for example from another clamped column)
@@ -134,7 +131,8 @@ impl Component for proto::Clamp {
             DataType::Int => {
                 // 1. check public arguments (constant n)
                 let mut clamp_lower = match public_arguments.get::<IndexKey>(&"lower".into()) {
-                    Some(&lower) => lower.ref_array()?.clone().vec_int(Some(num_columns))
+                    Some(&lower) => broadcast_bound(lower.ref_array()?.clone().vec_int(Some(num_columns))
+                        .map_err(prepend("lower:"))?, num_columns)
                         .map_err(prepend("lower:"))?,
 
                     // 2. then private arguments (for example from another clamped column)
@@ -150,7 +148,8 @@ impl Component for proto::Clamp {
 
                 // 1. check public arguments (constant n)
                 let mut clamp_upper = match public_arguments.get::<IndexKey>(&"upper".into()) {
-                    Some(&upper) => upper.ref_array()?.clone().vec_int(Some(num_columns))
+                    Some(&upper) => broadcast_bound(upper.ref_array()?.clone().vec_int(Some(num_columns))
+                        .map_err(prepend("upper:"))?, num_columns)
                         .map_err(prepend("upper:"))?,
 
                     // 2. then private arguments (for example from another clamped column)
@@ -200,6 +199,44 @@ impl Component for proto::Clamp {
 
 }
 
+/// Bounds for each column, where a single bound is shared by every column.
+fn broadcast_bound<T: Clone>(bound: Vec<T>, num_columns: i64) -> Result<Vec<T>> {
+    match bound.len() as i64 {
+        1 => Ok((0..num_columns).map(|_| bound[0].clone()).collect()),
+        length if length == num_columns => Ok(bound),
+        length => Err(format!("must be a scalar, or have length 1 or {} (the number of columns), but has length {}",
+                              num_columns, length).into())
+    }
+}
+
+/// Append the otherwise bucket to the categories of each column, so that every element maps into the category set.
+pub fn categories_with_null(mut categories: Jagged, null: &Array, num_columns: i64) -> Result<Jagged> {
+    match (&mut categories, null) {
+        (Jagged::Float(jagged), Array::Float(null)) => {
+            let null_target = standardize_null_target_argument(null.clone(), num_columns)?;
+            jagged.iter_mut().zip(null_target.into_iter())
+                .for_each(|(cats, null)| cats.push(null))
+        },
+        (Jagged::Int(jagged), Array::Int(null)) => {
+            let null_target = standardize_null_target_argument(null.clone(), num_columns)?;
+            jagged.iter_mut().zip(null_target.into_iter())
+                .for_each(|(cats, null)| cats.push(null))
+        },
+        (Jagged::Str(jagged), Array::Str(null)) => {
+            let null_target = standardize_null_target_argument(null.clone(), num_columns)?;
+            jagged.iter_mut().zip(null_target.into_iter())
+                .for_each(|(cats, null)| cats.push(null))
+        },
+        (Jagged::Bool(jagged), Array::Bool(null)) => {
+            let null_target = standardize_null_target_argument(null.clone(), num_columns)?;
+            jagged.iter_mut().zip(null_target.into_iter())
+                .for_each(|(cats, null)| cats.push(null))
+        },
+        _ => return Err("categories and null_value must be homogeneously typed".into())
+    };
+    // the otherwise bucket may already be a member of the category set
+    categories.standardize(num_columns)?.deduplicate()
+}
 
 impl Expandable for proto::Clamp {
     fn expand_component(
@@ -249,6 +286,122 @@ impl Expandable for proto::Clamp {
 }
 
 
+impl Component for proto::ClampCount {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = get_clamp_count_data(&properties)?;
+
+        // the number of clamped records is a nonnegative integer, no larger than the number of records
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_records: Some(1),
+            num_columns: Some(1),
+            nullity: false,
+            releasable: false,
+            c_stability: data_property.c_stability,
+            aggregator: None,
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Int(vec![Some(0)]),
+                upper: Vector1DNull::Int(vec![data_property.num_records.map(|v| v as Integer)]),
+            })),
+            data_type: DataType::Int,
+            dataset_id: Some(node_id as i64),
+            node_id: node_id as i64,
+            is_not_empty: true,
+            dimensionality: data_property.dimensionality,
+            group_id: data_property.group_id,
+            naturally_ordered: true,
+            sample_proportion: data_property.sample_proportion,
+            monotone: false,
+            data_derived: true,
+            num_records_lower_bound: None,
+            noised: false,
+        }).into())
+    }
+}
+
+impl Expandable for proto::ClampCount {
+    /// Expand into a mask of the records outside of the bounds, a filter of the data by the mask, and a count.
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        get_clamp_count_data(properties)?;
+
+        let mut expansion = base::ComponentExpansion::default();
+
+        let argument_ids = component.arguments();
+        let get_id = |name: &str| argument_ids.get::<IndexKey>(&name.into()).copied()
+            .ok_or_else(|| Error::from(format!("{} must be provided as an argument", name)));
+        let data_id = get_id("data")?;
+
+        let mut insert_node = |arguments: IndexMap<IndexKey, u32>, variant: proto::component::Variant| {
+            maximum_id += 1;
+            expansion.computation_graph.insert(maximum_id, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(arguments)),
+                variant: Some(variant),
+                omit: true,
+                submission: component.submission,
+            });
+            expansion.traversal.push(maximum_id);
+            maximum_id
+        };
+
+        // records below the lower bound or above the upper bound are modified by clamping
+        let id_below = insert_node(
+            indexmap!["left".into() => data_id, "right".into() => get_id("lower")?],
+            proto::component::Variant::LessThan(proto::LessThan {}));
+        let id_above = insert_node(
+            indexmap!["left".into() => data_id, "right".into() => get_id("upper")?],
+            proto::component::Variant::GreaterThan(proto::GreaterThan {}));
+        let id_mask = insert_node(
+            indexmap!["left".into() => id_below, "right".into() => id_above],
+            proto::component::Variant::LogicalOr(proto::Or {}));
+        let id_filter = insert_node(
+            indexmap!["data".into() => data_id, "mask".into() => id_mask],
+            proto::component::Variant::Filter(proto::Filter {}));
+
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_filter])),
+            variant: Some(proto::component::Variant::Count(proto::Count {
+                distinct: false,
+                group_by: false,
+                count_true: false,
+            })),
+            omit: component.omit,
+            submission: component.submission,
+        });
+        expansion.traversal.push(component_id);
+
+        Ok(expansion)
+    }
+}
+
+/// Properties of the data to count clamped records of.
+///
+/// Records may only be compared against numeric bounds when there is a single column.
+fn get_clamp_count_data(properties: &base::NodeProperties) -> Result<ArrayProperties> {
+    let data_property = get_array_property(properties, "data")?;
+    data_property.assert_is_not_aggregated()?;
+    if data_property.num_columns()? != 1 {
+        return Err("data: must contain one column".into())
+    }
+    if data_property.data_type != DataType::Int && data_property.data_type != DataType::Float {
+        return Err("data: atomic type must be numeric".into())
+    }
+    Ok(data_property)
+}
+
+
 #[cfg(test)]
 pub mod test_clamp {
     use crate::base::test_data;
@@ -433,4 +586,128 @@ pub mod test_clamp {
         array1d_bool_0,
         array1d_bool_10_uniform,
     );
+
+    #[test]
+    fn test_string_otherwise() {
+        use crate::base::{Jagged, Value};
+        use crate::components::cast::test_cast;
+
+        let (mut analysis, casted) = test_cast::utilities::analysis_string(
+            test_data::array1d_string_10_uniform());
+
+        let categories = analysis.literal()
+            .value(Value::Jagged(vec![vec!["a".to_string(), "b".to_string()]].into()))
+            .value_public(true).build();
+        let otherwise = analysis.literal()
+            .value("other".to_string().into())
+            .value_public(true).build();
+
+        let clamped = analysis.clamp(casted)
+            .categories(categories)
+            .otherwise(otherwise)
+            .build();
+
+        let properties = analysis.properties(clamped).unwrap().array().unwrap().clone();
+        assert!(!properties.nullity);
+        match properties.categories().unwrap() {
+            Jagged::Str(categories) => assert_eq!(categories, vec![vec![
+                "a".to_string(), "b".to_string(), "other".to_string()]]),
+            _ => panic!("categories must be strings")
+        }
+    }
+
+    #[test]
+    fn test_per_column_bounds() {
+        use ndarray::{arr1, arr2};
+        use crate::components::cast::test_cast;
+
+        let (mut analysis, casted) = test_cast::utilities::analysis_f64(
+            arr2(&[[-100., -100., -100.], [100., 100., 100.]]).into_dyn().into());
+
+        let lower = analysis.literal()
+            .value(arr1(&[0., 10., -5.]).into_dyn().into())
+            .value_public(true).build();
+        let upper = analysis.literal()
+            .value(arr1(&[1., 20., 5.]).into_dyn().into())
+            .value_public(true).build();
+        let clamped = analysis.clamp(casted).lower(lower).upper(upper).build();
+
+        let properties = analysis.properties(clamped).unwrap().array().unwrap().clone();
+        assert_eq!(properties.lower_float().unwrap(), vec![0., 10., -5.]);
+        assert_eq!(properties.upper_float().unwrap(), vec![1., 20., 5.]);
+
+        // a single bound is shared by every column
+        let shared_upper = analysis.literal()
+            .value(arr1(&[50.]).into_dyn().into())
+            .value_public(true).build();
+        let clamped = analysis.clamp(casted).lower(lower).upper(shared_upper).build();
+        let properties = analysis.properties(clamped).unwrap().array().unwrap().clone();
+        assert_eq!(properties.upper_float().unwrap(), vec![50., 50., 50.]);
+
+        // bounds must be defined for every column
+        let short_upper = analysis.literal()
+            .value(arr1(&[1., 20.]).into_dyn().into())
+            .value_public(true).build();
+        let clamped = analysis.clamp(casted).lower(lower).upper(short_upper).build();
+        assert!(analysis.properties(clamped).is_err());
+    }
+
+    #[test]
+    fn test_null_policy() {
+        use crate::components::cast::test_cast;
+
+        let (mut analysis, casted) = test_cast::utilities::analysis_f64(
+            ndarray::arr1(&[0.2, f64::NAN, 0.7]).into_dyn().into());
+        assert!(analysis.properties(casted).unwrap().array().unwrap().nullity);
+
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(1.0.into()).value_public(true).build();
+
+        // nulls are passed through to a later impute
+        let passed = analysis.clamp(casted).lower(lower).upper(upper).build();
+        assert!(analysis.properties(passed).unwrap().array().unwrap().nullity);
+        let imputed = analysis.impute(passed).lower(lower).upper(upper).build();
+        let imputed_property = analysis.properties(imputed).unwrap().array().unwrap().clone();
+        assert!(!imputed_property.nullity);
+        assert_eq!(imputed_property.lower_float().unwrap(), vec![0.]);
+        assert_eq!(imputed_property.upper_float().unwrap(), vec![1.]);
+
+        // nulls are rejected when evaluated, so the clamped data is non-null
+        let rejected = analysis.clamp(casted).lower(lower).upper(upper)
+            .null_policy("Error".to_string()).build();
+        assert!(!analysis.properties(rejected).unwrap().array().unwrap().nullity);
+
+        let invalid = analysis.clamp(casted).lower(lower).upper(upper)
+            .null_policy("Drop".to_string()).build();
+        assert!(analysis.properties(invalid).is_err());
+    }
+
+    #[test]
+    fn test_clamp_count() {
+        use crate::proto;
+        use crate::base::IndexKey;
+        use crate::components::cast::test_cast;
+
+        let (mut analysis, casted) = test_cast::utilities::analysis_f64(test_data::array1d_f64_10_uniform());
+        let lower = analysis.literal().value(2.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(8.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(casted).lower(lower).upper(upper).build();
+        let clamp_count = analysis.clamp_count(casted, lower, upper).build();
+
+        let mut computation_graph = analysis.components.clone();
+        let mut release = analysis.release.clone();
+        let (properties, _) = crate::utilities::propagate_properties(
+            &Some(analysis.privacy_definition.clone()),
+            &mut computation_graph, &mut release, None, false).unwrap();
+
+        // the clamped data is unchanged, and the companion node counts the filtered data
+        assert!(matches!(computation_graph[&clamped].variant, Some(proto::component::Variant::Clamp(_))));
+        assert!(matches!(computation_graph[&clamp_count].variant, Some(proto::component::Variant::Count(_))));
+        let id_filter = computation_graph[&clamp_count].arguments()[&IndexKey::from("data")];
+        assert!(matches!(computation_graph[&id_filter].variant, Some(proto::component::Variant::Filter(_))));
+
+        let count_property = properties[&clamp_count].array().unwrap().clone();
+        assert_eq!(count_property.data_type, crate::base::DataType::Int);
+        assert_eq!(count_property.lower_int().unwrap(), vec![0]);
+    }
 }
@@ -65,6 +65,10 @@ impl Component for proto::ColumnBind {
                 .ok_or_else(|| Error::from("natural ordering must be shared among arguments"))?,
             sample_proportion: get_common_value(&array_props.iter().map(|v| v.sample_proportion.map(n64)).collect())
                 .ok_or_else(|| Error::from("sample proportions must be shared among arguments"))?.and_then(|v| v.to_f64()),
+            monotone: array_props.iter().all(|v| v.monotone),
+            data_derived: array_props.iter().any(|v| v.data_derived),
+            num_records_lower_bound: None,
+            noised: array_props.iter().all(|v| v.noised),
         })))
     }
 }
\ No newline at end of file
@@ -1,11 +1,13 @@
 use indexmap::map::IndexMap;
-use ndarray::arr1;
+use ndarray::{arr1, Array};
 
-use crate::{base, Integer, proto, Warnable};
-use crate::base::{AggregatorProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
-use crate::components::{Component, Sensitivity};
+use crate::{base, Float, Integer, proto, Warnable};
+use crate::base::{AggregatorProperties, ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Sensitivity};
+use crate::components::partition::make_dense_partition_keys;
 use crate::errors::*;
-use crate::utilities::get_common_value;
+use crate::utilities::{get_array_property, get_common_value, get_literal, get_property, prepend, privacy::scale_by_contributions};
+use crate::utilities::inference::infer_property;
 
 impl Component for proto::Count {
     fn propagate_property(
@@ -16,7 +18,14 @@ impl Component for proto::Count {
         node_id: u32,
     ) -> Result<Warnable<ValueProperties>> {
 
-        let mut data_property = match properties.get::<IndexKey>(&"data".into()).ok_or("data: missing")?.clone() {
+        if self.group_by {
+            return propagate_group_by(self, properties, node_id)
+        }
+        if self.count_true {
+            return propagate_count_true(self, properties, node_id)
+        }
+
+        let mut data_property = match get_property(&properties, "data")?.clone() {
             ValueProperties::Array(data_property) => data_property,
             ValueProperties::Dataframe(data_property) => {
                 data_property.children.get_index(0)
@@ -33,9 +42,9 @@ impl Component for proto::Count {
         if !data_property.releasable {
             data_property.assert_is_not_aggregated()?;
         }
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
 
-        let c_stability = match properties.get::<IndexKey>(&"data".into())
-            .ok_or("data: missing")? {
+        let c_stability = match get_property(&properties, "data")? {
             ValueProperties::Array(value) => {
                 value.assert_is_not_aggregated()?;
 
@@ -57,9 +66,16 @@ impl Component for proto::Count {
         data_property.c_stability = c_stability;
 
         let data_num_records = data_property.num_records.map(|v| v as Integer);
+        // there can be no more distinct values than there are categories
+        let upper = match (self.distinct, data_num_records, data_property.domain_size()) {
+            (true, Some(num_records), Some(domain_size)) => Some(num_records.min(domain_size)),
+            (true, None, Some(domain_size)) => Some(domain_size),
+            _ => data_num_records
+        };
         data_property.nature = Some(Nature::Continuous(NatureContinuous {
-            lower: Vector1DNull::Int(vec![data_num_records.or(Some(0))]),
-            upper: Vector1DNull::Int(vec![data_num_records]),
+            // a distinct count may be as small as zero, even when the number of records is known
+            lower: Vector1DNull::Int(vec![if self.distinct { Some(0) } else { data_num_records.or(Some(0)) }]),
+            upper: Vector1DNull::Int(vec![upper]),
         }));
         data_property.data_type = DataType::Int;
         data_property.dataset_id = Some(node_id as i64);
@@ -78,14 +94,20 @@ impl Sensitivity for proto::Count {
         properties: &NodeProperties,
         sensitivity_type: &SensitivitySpace
     ) -> Result<Value> {
-        let num_records = match properties.get(&IndexKey::from("data"))
-            .ok_or("data: missing")? {
+        if self.group_by {
+            return group_by_sensitivity(privacy_definition, properties, sensitivity_type)
+        }
+        if self.count_true {
+            return count_true_sensitivity(privacy_definition, properties, sensitivity_type)
+        }
+
+        let (num_records, domain_size) = match get_property(properties, "data")? {
             ValueProperties::Array(value) => {
                 value.assert_is_not_aggregated()?;
-                value.num_records
+                (value.num_records, value.domain_size())
             },
             ValueProperties::Dataframe(value) => {
-                value.num_records()?
+                (value.num_records()?, None)
             },
             _ => return Err("data: must be an array or dataframe".into())
         };
@@ -101,6 +123,10 @@ impl Sensitivity for proto::Count {
 
                 // SENSITIVITY DERIVATIONS
                 let sensitivity = match (neighboring_type, num_records) {
+                    // adding, removing or substituting a record changes the number of distinct values by at most one.
+                    // Unlike row counts, the distinct count is not fixed by a known N
+                    _ if self.distinct => 1,
+
                     // known N. Applies to any neighboring type.
                     (_, Some(_)) => 0,
 
@@ -110,9 +136,364 @@ impl Sensitivity for proto::Count {
                     // unknown N
                     (AddRemove, None) => 1,
                 };
+                let mut sensitivity: Integer = scale_by_contributions(privacy_definition, sensitivity)?;
+
+                // the distinct count is confined to [0, domain_size], so it cannot change by more
+                if let (true, Some(domain_size)) = (self.distinct, domain_size) {
+                    sensitivity = sensitivity.min(domain_size);
+                }
                 Ok((arr1(&[sensitivity]).into_dyn()).into())
             },
             _ => Err("Count sensitivity is only implemented for KNorm".into())
         }
     }
 }
+
+impl Expandable for proto::Count {
+    /// Materialize the categories of the data as an argument, so that the runtime counts the same cells.
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        if !self.group_by || properties.contains_key::<IndexKey>(&"categories".into()) {
+            return Ok(expansion)
+        }
+
+        let categories = get_array_property(properties, "data")?.categories()?;
+
+        maximum_id += 1;
+        let id_categories = maximum_id;
+        let (patch_node, release) = get_literal(Value::Jagged(categories), component.submission)?;
+        expansion.computation_graph.insert(id_categories, patch_node);
+        expansion.properties.insert(id_categories, infer_property(&release.value, None, id_categories)?);
+        expansion.releases.insert(id_categories, release);
+
+        let mut component = component.clone();
+        component.insert_argument(&"categories".into(), id_categories);
+        expansion.computation_graph.insert(component_id, component);
+
+        Ok(expansion)
+    }
+}
+
+/// The cells of a group by count, in the order they are released.
+///
+/// Each cell is a combination of one category from each column of the data,
+/// so every record lands in exactly one cell.
+pub fn get_group_by_cells(data_property: &ArrayProperties) -> Result<Vec<IndexKey>> {
+    let categories = data_property.categories()
+        .map_err(|_| Error::from("data: must be categorical to count by cells"))?;
+    make_dense_partition_keys(categories, data_property.dimensionality)
+}
+
+fn propagate_group_by(
+    count: &proto::Count,
+    properties: NodeProperties,
+    node_id: u32,
+) -> Result<Warnable<ValueProperties>> {
+    if count.distinct {
+        return Err("distinct counts may not be grouped by cells".into())
+    }
+
+    let mut data_property = get_array_property(&properties, "data")?;
+    data_property.assert_is_not_aggregated()?;
+    data_property.assert_is_not_noised().map_err(prepend("data:"))?;
+
+    let num_cells = get_group_by_cells(&data_property)?.len() as i64;
+
+    // save a snapshot of the state when aggregating
+    data_property.aggregator = Some(AggregatorProperties::new(
+        proto::component::Variant::Count(count.clone()), properties, 1));
+
+    // each cell may contain as many as all of the records
+    data_property.nature = Some(Nature::Continuous(NatureContinuous {
+        lower: Vector1DNull::Int(vec![Some(0)]),
+        upper: Vector1DNull::Int(vec![data_property.num_records]),
+    }));
+    data_property.data_type = DataType::Int;
+    data_property.dataset_id = Some(node_id as i64);
+    data_property.num_records = Some(num_cells);
+    data_property.num_columns = Some(1);
+    data_property.dimensionality = Some(1);
+    data_property.monotone = false;
+
+    Ok(ValueProperties::Array(data_property).into())
+}
+
+/// Sensitivity of the count of each cell, where each record lands in exactly one cell.
+fn group_by_sensitivity(
+    privacy_definition: &proto::PrivacyDefinition,
+    properties: &NodeProperties,
+    sensitivity_type: &SensitivitySpace,
+) -> Result<Value> {
+    let data_property = get_array_property(properties, "data")?;
+    data_property.assert_is_not_aggregated()?;
+    let num_cells = get_group_by_cells(&data_property)?.len();
+
+    let k = match sensitivity_type {
+        SensitivitySpace::KNorm(k) => k,
+        _ => return Err("Count sensitivity is only implemented for KNorm".into())
+    };
+
+    use proto::privacy_definition::Neighboring::{self, Substitute, AddRemove};
+    let neighboring_type = Neighboring::from_i32(privacy_definition.neighboring)
+        .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))?;
+
+    // SENSITIVITY DERIVATIONS
+    let sensitivity: Float = match (neighboring_type, num_cells, data_property.num_records) {
+        // one cell, known N. Applies to any neighboring type.
+        (_, 1, Some(_)) => 0.,
+
+        // one cell, unknown N
+        (_, 1, None) => 1.,
+
+        // many cells. A substituted record may leave one cell and enter another.
+        (Substitute, _, _) => match k {
+            1 => 2.,
+            2 => (2. as Float).sqrt(),
+            _ => return Err("KNorm sensitivity is only supported in L1 and L2 spaces".into())
+        },
+
+        // many cells. Only the cell of the added or removed record changes.
+        (AddRemove, _, _) => 1.,
+    };
+    let sensitivity = scale_by_contributions(privacy_definition, sensitivity)?;
+
+    Ok(Array::from_elem(vec![num_cells, 1], sensitivity).into_dyn().into())
+}
+
+fn propagate_count_true(
+    count: &proto::Count,
+    properties: NodeProperties,
+    node_id: u32,
+) -> Result<Warnable<ValueProperties>> {
+    if count.distinct || count.group_by {
+        return Err("count_true may not be combined with distinct or group_by".into())
+    }
+
+    let mut data_property = get_array_property(&properties, "data")?;
+    data_property.assert_is_not_aggregated()?;
+    data_property.assert_is_not_noised().map_err(prepend("data:"))?;
+    if data_property.data_type != DataType::Bool {
+        return Err("data: atomic type must be bool to count true values".into())
+    }
+    if data_property.num_columns()? != 1 {
+        return Err("data: must contain one column to count true values".into())
+    }
+
+    // save a snapshot of the state when aggregating
+    data_property.aggregator = Some(AggregatorProperties::new(
+        proto::component::Variant::Count(count.clone()), properties, 1));
+
+    // any number of the records may be true, even when the number of records is known
+    data_property.nature = Some(Nature::Continuous(NatureContinuous {
+        lower: Vector1DNull::Int(vec![Some(0)]),
+        upper: Vector1DNull::Int(vec![data_property.num_records]),
+    }));
+    data_property.data_type = DataType::Int;
+    data_property.dataset_id = Some(node_id as i64);
+    data_property.num_records = Some(1);
+    data_property.num_columns = Some(1);
+    data_property.nullity = false;
+    data_property.monotone = false;
+
+    Ok(ValueProperties::Array(data_property).into())
+}
+
+/// Sensitivity of the number of true values, which is a sum of records bounded in [0, 1].
+fn count_true_sensitivity(
+    privacy_definition: &proto::PrivacyDefinition,
+    properties: &NodeProperties,
+    sensitivity_type: &SensitivitySpace,
+) -> Result<Value> {
+    get_array_property(properties, "data")?.assert_is_not_aggregated()?;
+
+    if !matches!(sensitivity_type, SensitivitySpace::KNorm(_)) {
+        return Err("Count sensitivity is only implemented for KNorm".into())
+    }
+
+    // SENSITIVITY DERIVATIONS
+    // Adding or removing a record changes the count by at most one.
+    // Substituting a record may flip one value, so the count still changes by at most one,
+    //    and unlike row counts, a known N does not fix the count.
+    let sensitivity: Integer = scale_by_contributions(privacy_definition, 1)?;
+
+    Ok((arr1(&[sensitivity]).into_dyn()).into())
+}
+
+#[cfg(test)]
+mod test_count {
+    use crate::base::{DataType, IndexKey, SensitivitySpace, test_data, Value};
+    use crate::components::clamp::test_clamp;
+    use crate::components::resize::test_resize;
+    use crate::components::Sensitivity;
+    use crate::proto;
+
+    #[test]
+    fn test_distinct_sensitivity() {
+        let (analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        let properties = indexmap![IndexKey::from("data") => analysis.properties(resized).unwrap()];
+
+        let sensitivity = |distinct: bool| proto::Count { distinct, group_by: false, count_true: false }
+            .compute_sensitivity(&analysis.privacy_definition, &properties, &SensitivitySpace::KNorm(1))
+            .unwrap().array().unwrap().first_int().unwrap();
+
+        // the number of rows is known, so a row count has no sensitivity
+        assert_eq!(sensitivity(false), 0);
+        // a single record may still add or remove a distinct value
+        assert_eq!(sensitivity(true), 1);
+    }
+
+    #[test]
+    fn test_max_contributions_sensitivity() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        let properties = indexmap![IndexKey::from("data") => analysis.properties(resized).unwrap()];
+
+        let mut sensitivity = |max_contributions_per_user: u32| {
+            analysis.privacy_definition.max_contributions_per_user = max_contributions_per_user;
+            proto::Count { distinct: true, group_by: false, count_true: false }
+                .compute_sensitivity(&analysis.privacy_definition, &properties, &SensitivitySpace::KNorm(1))
+                .unwrap().array().unwrap().first_int().unwrap()
+        };
+
+        // unset is one row per user
+        assert_eq!(sensitivity(0), 1);
+        // a user contributing three rows may change the count by three
+        assert_eq!(sensitivity(3), 3);
+    }
+
+    #[test]
+    fn test_user_level_sensitivity() {
+        use crate::components::impute::test_impute;
+        use ndarray::arr1;
+
+        // user 1 contributes three rows, and user 2 contributes two
+        let (mut analysis, imputed) = test_impute::utilities::analysis_i64_cont(
+            arr1(&[1i64, 1, 1, 2, 2, 3]).into_dyn().into(), Some(0.into()), Some(10.into()));
+        let mut data_property = analysis.properties(imputed).unwrap().array().unwrap().clone();
+        data_property.num_records = None;
+        let properties = indexmap![IndexKey::from("data") => data_property.into()];
+
+        let mut sensitivity = |privacy_unit: proto::privacy_definition::PrivacyUnit, user_id: &str, max_contributions_per_user: u32| {
+            analysis.privacy_definition.privacy_unit = privacy_unit as i32;
+            analysis.privacy_definition.user_id = user_id.to_string();
+            analysis.privacy_definition.max_contributions_per_user = max_contributions_per_user;
+            proto::Count { distinct: false, group_by: false, count_true: false }
+                .compute_sensitivity(&analysis.privacy_definition, &properties, &SensitivitySpace::KNorm(1))
+                .map(|sensitivity| sensitivity.array().unwrap().first_int().unwrap())
+        };
+
+        use proto::privacy_definition::PrivacyUnit::{Row, User};
+        // each row is protected on its own, so the repeated user ids are ignored
+        assert_eq!(sensitivity(Row, "", 0).unwrap(), 1);
+        assert_eq!(sensitivity(Row, "user", 0).unwrap(), 1);
+        // removing user 1 removes all three of their rows
+        assert_eq!(sensitivity(User, "user", 3).unwrap(), 3);
+        // the column identifying users and the rows per user must be declared under user-level privacy
+        assert_eq!(sensitivity(User, "", 3).unwrap_err().to_string(),
+                   "user-level privacy requires the user id column to be named");
+        assert_eq!(sensitivity(User, "user", 0).unwrap_err().to_string(),
+                   "user-level privacy requires max_contributions_per_user to be declared");
+    }
+
+    #[test]
+    fn test_distinct_domain_size() {
+        // four categories, and the null value that clamping appends
+        let (mut analysis, clamped) = test_clamp::utilities::analysis_i64_cat(
+            test_data::array1d_i64_10_uniform(),
+            Value::Jagged(vec![vec![1i64, 2, 3, 5]].into()), Some((-1).into()));
+        let data_property = analysis.properties(clamped).unwrap();
+        assert_eq!(data_property.array().unwrap().domain_size(), Some(5));
+
+        let count = analysis.count(clamped).distinct(true).build();
+        let count_property = analysis.properties(count).unwrap();
+        assert_eq!(count_property.array().unwrap().upper_int().unwrap(), vec![5]);
+
+        // each user may contribute more rows than there are categories
+        analysis.privacy_definition.max_contributions_per_user = 10;
+        let properties = indexmap![IndexKey::from("data") => data_property];
+        let sensitivity = proto::Count { distinct: true, group_by: false, count_true: false }
+            .compute_sensitivity(&analysis.privacy_definition, &properties, &SensitivitySpace::KNorm(1))
+            .unwrap().array().unwrap().first_int().unwrap();
+        assert_eq!(sensitivity, 5);
+    }
+
+    #[test]
+    fn test_group_by_sensitivity() {
+        use crate::components::count::get_group_by_cells;
+        use ndarray::arr2;
+
+        // two categories in the first column and three in the second, each with the null value that clamping appends
+        let (mut analysis, clamped) = test_clamp::utilities::analysis_i64_cat(
+            arr2(&[[1i64, 1], [1, 2], [2, 1], [2, 3], [1, 3]]).into_dyn().into(),
+            Value::Jagged(vec![vec![1i64, 2], vec![1, 2, 3]].into()), Some((-1).into()));
+        let data_property = analysis.properties(clamped).unwrap();
+
+        // one count for each cell of the cross product
+        let cells = get_group_by_cells(data_property.array().unwrap()).unwrap();
+        assert_eq!(cells.len(), 12);
+        assert_eq!(cells[0], IndexKey::Tuple(vec![1.into(), 1.into()]));
+        assert_eq!(cells[1], IndexKey::Tuple(vec![1.into(), 2.into()]));
+
+        let count = analysis.count(clamped).group_by(true).build();
+        let count_property = analysis.properties(count).unwrap().array().unwrap().clone();
+        assert_eq!(count_property.num_records, Some(12));
+        assert_eq!(count_property.num_columns, Some(1));
+
+        let properties = indexmap![IndexKey::from("data") => data_property];
+        let mut sensitivity = |neighboring: proto::privacy_definition::Neighboring, k: u32| {
+            analysis.privacy_definition.neighboring = neighboring as i32;
+            proto::Count { distinct: false, group_by: true, count_true: false }
+                .compute_sensitivity(&analysis.privacy_definition, &properties, &SensitivitySpace::KNorm(k))
+                .unwrap().array().unwrap().cast_float().unwrap()
+        };
+
+        use proto::privacy_definition::Neighboring::{AddRemove, Substitute};
+        // a record lands in exactly one cell
+        let add_remove = sensitivity(AddRemove, 1);
+        assert_eq!(add_remove.shape(), &[12, 1]);
+        assert!(add_remove.iter().all(|v| *v == 1.));
+        // a substituted record may leave one cell and enter another
+        assert!(sensitivity(Substitute, 1).iter().all(|v| *v == 2.));
+        assert!(sensitivity(Substitute, 2).iter().all(|v| *v == 2f64.sqrt()));
+    }
+
+    #[test]
+    fn test_count_true() {
+        use crate::components::literal::test_literal;
+        use ndarray::arr1;
+
+        let (mut analysis, data) = test_literal::analysis_literal(arr1(&[true, false, true]).into_dyn().into(), true);
+        let data_property = analysis.properties(data).unwrap();
+
+        // the count of trues is a nonnegative integer scalar, even though the number of rows is known
+        let count = analysis.count(data).count_true(true).build();
+        let count_property = analysis.properties(count).unwrap().array().unwrap().clone();
+        assert_eq!(count_property.data_type, DataType::Int);
+        assert_eq!(count_property.num_records, Some(1));
+        assert_eq!(count_property.lower_int().unwrap(), vec![0]);
+        assert_eq!(count_property.upper_int().unwrap(), vec![3]);
+
+        let properties = indexmap![IndexKey::from("data") => data_property];
+        let sensitivity = |count_true: bool| proto::Count { distinct: false, group_by: false, count_true }
+            .compute_sensitivity(&analysis.privacy_definition, &properties, &SensitivitySpace::KNorm(1))
+            .unwrap().array().unwrap().first_int().unwrap();
+        // the number of rows is known, but the number of trues is not
+        assert_eq!(sensitivity(false), 0);
+        assert_eq!(sensitivity(true), 1);
+
+        // only boolean data has true values
+        let numbers = analysis.literal().value(arr1(&[1, 0, 1]).into_dyn().into()).value_public(true).build();
+        let count = analysis.count(numbers).count_true(true).build();
+        assert!(analysis.properties(count).is_err());
+    }
+}
@@ -5,7 +5,7 @@ use crate::{base, Float, proto, Warnable};
 use crate::base::{AggregatorProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
 use crate::components::{Component, Sensitivity};
 use crate::errors::*;
-use crate::utilities::prepend;
+use crate::utilities::{prepend, privacy::scale_by_contributions};
 
 impl Component for proto::Covariance {
     fn propagate_property(
@@ -21,10 +21,13 @@ impl Component for proto::Covariance {
                 .map_err(prepend("data:"))?.clone();
 
             data_property.assert_is_not_empty()?;
+            data_property.assert_minimum_columns("Covariance", 1)
+                .map_err(prepend("data:"))?;
 
             if !data_property.releasable {
                 data_property.assert_is_not_aggregated()?;
             }
+            data_property.assert_is_not_noised().map_err(prepend("data:"))?;
 
             let num_columns = data_property.num_columns()?;
             let num_columns = num_columns * (num_columns + 1) / 2;
@@ -81,14 +84,20 @@ impl Component for proto::Covariance {
             }
             left_property.assert_is_not_empty()?;
             right_property.assert_is_not_empty()?;
+            left_property.assert_minimum_columns("Covariance", 1)
+                .map_err(prepend("left:"))?;
+            right_property.assert_minimum_columns("Covariance", 1)
+                .map_err(prepend("right:"))?;
 
             if !left_property.releasable {
                 left_property.assert_is_not_aggregated()?;
             }
+            left_property.assert_is_not_noised().map_err(prepend("left:"))?;
 
             if !right_property.releasable {
                 right_property.assert_is_not_aggregated()?;
             }
+            right_property.assert_is_not_noised().map_err(prepend("right:"))?;
 
             if !left_property.releasable && !right_property.releasable && left_property.group_id != right_property.group_id {
                 return Err("data from separate partitions may not be mixed".into())
@@ -167,6 +176,7 @@ impl Sensitivity for proto::Covariance {
                             .map_err(prepend("data:"))?.clone();
                         data_property.assert_is_not_aggregated()?;
                         data_property.assert_non_null()?;
+                        data_property.assert_bounds_are_public()?;
                         let data_lower = data_property.lower_float()?;
                         let data_upper = data_property.upper_float()?;
                         data_n = data_property.num_records()? as f64;
@@ -187,6 +197,7 @@ impl Sensitivity for proto::Covariance {
                             .map_err(prepend("left:"))?.clone();
                         left_property.assert_is_not_aggregated()?;
                         left_property.assert_non_null()?;
+                        left_property.assert_bounds_are_public()?;
                         let left_n = left_property.num_records()?;
                         let left_lower = left_property.lower_float()?;
                         let left_upper = left_property.upper_float()?;
@@ -196,6 +207,7 @@ impl Sensitivity for proto::Covariance {
                             .map_err(prepend("right:"))?.clone();
                         right_property.assert_is_not_aggregated()?;
                         right_property.assert_non_null()?;
+                        right_property.assert_bounds_are_public()?;
                         let right_n = right_property.num_records()?;
                         let right_lower = right_property.lower_float()?;
                         let right_upper = right_property.upper_float()?;
@@ -232,9 +244,10 @@ impl Sensitivity for proto::Covariance {
                     },
                     _ => return Err("KNorm sensitivity is only supported in L1 and L2 spaces".into())
                 } as Float;
+                let scaling_constant = scale_by_contributions(privacy_definition, scaling_constant)?;
 
                 let row_sensitivity = differences.iter()
-                    .map(|difference| (difference * scaling_constant))
+                    .map(|difference| difference * scaling_constant)
                     .collect::<Vec<Float>>();
 
                 let mut array_sensitivity = Array::from(row_sensitivity).into_dyn();
@@ -245,4 +258,42 @@ impl Sensitivity for proto::Covariance {
             _ => Err("Covariance sensitivity is only implemented for KNorm".into())
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test_covariance {
+    use ndarray::Array2;
+
+    use crate::base::{IndexKey, SensitivitySpace, test_data};
+    use crate::components::literal::test_literal;
+    use crate::components::resize::test_resize;
+    use crate::components::Sensitivity;
+    use crate::proto;
+
+    #[test]
+    fn test_covariance_no_columns() {
+        let (mut analysis, empty) = test_literal::analysis_literal(
+            Array2::<f64>::zeros((2, 0)).into_dyn().into(), true);
+        let covariance = analysis.covariance().data(empty).build();
+
+        assert!(analysis.properties(covariance).unwrap_err().to_string()
+            .contains("data: Covariance requires at least 1 column(s), but the data has 0"));
+    }
+
+    #[test]
+    fn test_max_contributions_sensitivity() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        let properties = indexmap![IndexKey::from("data") => analysis.properties(resized).unwrap()];
+
+        let mut sensitivity = |max_contributions_per_user: u32| {
+            analysis.privacy_definition.max_contributions_per_user = max_contributions_per_user;
+            proto::Covariance { finite_sample_correction: true }
+                .compute_sensitivity(&analysis.privacy_definition, &properties, &SensitivitySpace::KNorm(1))
+                .unwrap().array().unwrap().first_float().unwrap()
+        };
+
+        // a user contributing three rows may shift the covariance three times as far as a single row
+        assert!((sensitivity(3) - 3. * sensitivity(0)).abs() < 1e-10);
+    }
+}
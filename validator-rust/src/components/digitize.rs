@@ -3,7 +3,7 @@ use crate::errors::*;
 use crate::base::{IndexKey, Nature, NodeProperties, NatureCategorical, Jagged, ValueProperties, DataType, Array};
 
 use crate::{proto, base, Warnable, Integer};
-use crate::utilities::{prepend, standardize_categorical_argument, standardize_null_target_argument, deduplicate, standardize_float_argument, get_literal};
+use crate::utilities::{get_array_property, prepend, require_public_argument, standardize_categorical_argument, standardize_null_target_argument, deduplicate, standardize_float_argument, get_literal};
 use crate::components::{Component, Expandable};
 
 use crate::base::Value;
@@ -15,13 +15,11 @@ impl Component for proto::Digitize {
     fn propagate_property(
         &self,
         _privacy_definition: &Option<proto::PrivacyDefinition>,
-        mut public_arguments: IndexMap<base::IndexKey, &Value>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: NodeProperties,
         _node_id: u32
     ) -> Result<Warnable<ValueProperties>> {
-        let mut data_property = properties.get(&IndexKey::from("data"))
-            .ok_or_else(|| Error::from("data: missing"))?.clone().array()
-            .map_err(prepend("data:"))?.clone();
+        let mut data_property = get_array_property(&properties, "data")?;
 
         if data_property.data_type == DataType::Unknown {
             return Err("data_type must be known".into())
@@ -41,8 +39,7 @@ impl Component for proto::Digitize {
             data_property.assert_is_not_aggregated()?;
         }
 
-        public_arguments.remove::<IndexKey>(&"edges".into())
-            .ok_or_else(|| Error::from("edges: missing, must be public"))
+        require_public_argument(&public_arguments, "edges")
             .and_then(|v| v.clone().jagged())
             .and_then(|v| match v {
                 Jagged::Float(edges) => {
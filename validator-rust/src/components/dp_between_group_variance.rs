@@ -0,0 +1,274 @@
+use indexmap::map::IndexMap;
+use ndarray::arr0;
+
+use crate::{base, proto, Warnable};
+use crate::base::{Array, ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Report};
+use crate::components::dp_stratified_count::get_strata;
+use crate::errors::*;
+use crate::utilities::{get_array_property, get_literal, prepend, privacy::spread_privacy_usage};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json};
+
+impl Component for proto::DpBetweenGroupVariance {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = get_array_property(&properties, "data")?;
+        get_strata(&properties)?;
+
+        if data_property.data_type != DataType::Float {
+            return Err("data: atomic type must be float".into())
+        }
+        if data_property.num_columns()? != 1 {
+            return Err("data: must contain one column".into())
+        }
+        get_bounds(&data_property)?;
+        check_mechanism(&self.mechanism)?;
+
+        Ok(ValueProperties::Array(ArrayProperties {
+            // the variance is floored at zero, but the noisy group means are unbounded
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(vec![Some(0.)]),
+                upper: Vector1DNull::Float(vec![None]),
+            })),
+            dimensionality: Some(0),
+            ..ArrayProperties::dp_release(node_id, DataType::Float, vec![])
+        }).into())
+    }
+}
+
+impl Expandable for proto::DpBetweenGroupVariance {
+    /// Expand into a partition of the data by group, a dp sum and dp count of each partition,
+    /// and the weighted variance of the group means as postprocessing.
+    ///
+    /// The between-group variance is `sum_g(s_g^2 / n_g) / N - (sum_g(s_g) / N)^2`,
+    /// where `s_g` and `n_g` are the sum and count of group `g`, and `N` is the total count.
+    /// Privacy accounting applies parallel composition to the dependents of the partition.
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        use proto::component::Variant;
+        let mut expansion = base::ComponentExpansion::default();
+
+        let strata = get_strata(properties)?;
+        let argument_ids = component.arguments();
+        let id_data = *argument_ids.get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+        let id_by = *argument_ids.get::<IndexKey>(&"by".into())
+            .ok_or_else(|| Error::from("by must be provided as an argument"))?;
+
+        // the budget of each group is split evenly between the sum and the count
+        let privacy_usage = self.privacy_usage.iter().cloned()
+            .map(|usage| usage / 2.)
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+
+        let push_literal = |expansion: &mut base::ComponentExpansion, value: Value, maximum_id: &mut u32| -> Result<u32> {
+            *maximum_id += 1;
+            let id_literal = *maximum_id;
+            expansion.properties.insert(id_literal, infer_property(&value, None, id_literal)?);
+            let (patch_node, release) = get_literal(value, component.submission)?;
+            expansion.computation_graph.insert(id_literal, patch_node);
+            expansion.releases.insert(id_literal, release);
+            Ok(id_literal)
+        };
+        let id_zero = push_literal(&mut expansion, Value::Array(Array::Float(arr0(0.).into_dyn())), &mut maximum_id)?;
+        let id_one = push_literal(&mut expansion, Value::Array(Array::Float(arr0(1.).into_dyn())), &mut maximum_id)?;
+        let name_ids = strata.into_iter()
+            .map(|stratum| push_literal(&mut expansion, Value::from_index_key(stratum)?, &mut maximum_id))
+            .collect::<Result<Vec<u32>>>()?;
+
+        let push_node = |expansion: &mut base::ComponentExpansion, variant: Variant, arguments: IndexMap<IndexKey, u32>, maximum_id: &mut u32| {
+            *maximum_id += 1;
+            expansion.computation_graph.insert(*maximum_id, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(arguments)),
+                variant: Some(variant),
+                omit: true,
+                submission: component.submission,
+            });
+            expansion.traversal.push(*maximum_id);
+            *maximum_id
+        };
+        let binary = |left: u32, right: u32| indexmap!["left".into() => left, "right".into() => right];
+
+        // partition
+        let id_partition = push_node(&mut expansion, Variant::Partition(proto::Partition {}), indexmap![
+            "data".into() => id_data,
+            "by".into() => id_by
+        ], &mut maximum_id);
+
+        // running totals of the sums, counts and squared sums over counts
+        let mut totals: Option<(u32, u32, u32)> = None;
+        for id_name in name_ids {
+            let id_index = push_node(&mut expansion, Variant::Index(proto::Index {}), indexmap![
+                "data".into() => id_partition,
+                "names".into() => id_name
+            ], &mut maximum_id);
+
+            let id_sum = push_node(&mut expansion, Variant::DpSum(proto::DpSum {
+                mechanism: self.mechanism.clone(),
+                privacy_usage: privacy_usage.clone(),
+            }), indexmap!["data".into() => id_index], &mut maximum_id);
+
+            // counts are floored at one, so that every group mean is defined
+            let id_count = push_node(&mut expansion, Variant::DpCount(proto::DpCount {
+                distinct: false,
+                mechanism: self.mechanism.clone(),
+                privacy_usage: privacy_usage.clone(),
+            }), indexmap!["data".into() => id_index], &mut maximum_id);
+            let id_count = push_node(&mut expansion, Variant::ToFloat(proto::ToFloat {}),
+                                     indexmap!["data".into() => id_count], &mut maximum_id);
+            let id_count = push_node(&mut expansion, Variant::RowMax(proto::RowMax {}),
+                                     binary(id_count, id_one), &mut maximum_id);
+
+            let id_squared = push_node(&mut expansion, Variant::Multiply(proto::Multiply {}),
+                                       binary(id_sum, id_sum), &mut maximum_id);
+            let id_squared = push_node(&mut expansion, Variant::Divide(proto::Divide {}),
+                                       binary(id_squared, id_count), &mut maximum_id);
+
+            totals = Some(match totals {
+                Some((id_total_sum, id_total_count, id_total_squared)) => (
+                    push_node(&mut expansion, Variant::Add(proto::Add {}), binary(id_total_sum, id_sum), &mut maximum_id),
+                    push_node(&mut expansion, Variant::Add(proto::Add {}), binary(id_total_count, id_count), &mut maximum_id),
+                    push_node(&mut expansion, Variant::Add(proto::Add {}), binary(id_total_squared, id_squared), &mut maximum_id)),
+                None => (id_sum, id_count, id_squared)
+            });
+        }
+        let (id_total_sum, id_total_count, id_total_squared) = totals
+            .ok_or_else(|| Error::from("by: must have at least one category"))?;
+
+        // weighted mean of the squared group means, less the squared grand mean
+        let id_mean_squared = push_node(&mut expansion, Variant::Divide(proto::Divide {}),
+                                        binary(id_total_squared, id_total_count), &mut maximum_id);
+        let id_grand_mean = push_node(&mut expansion, Variant::Divide(proto::Divide {}),
+                                      binary(id_total_sum, id_total_count), &mut maximum_id);
+        let id_grand_mean = push_node(&mut expansion, Variant::Multiply(proto::Multiply {}),
+                                      binary(id_grand_mean, id_grand_mean), &mut maximum_id);
+        let id_variance = push_node(&mut expansion, Variant::Subtract(proto::Subtract {}),
+                                    binary(id_mean_squared, id_grand_mean), &mut maximum_id);
+
+        // floored at zero, as the noisy statistics may imply a negative variance
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(binary(id_variance, id_zero))),
+            variant: Some(Variant::RowMax(proto::RowMax {})),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpBetweenGroupVariance {
+    fn summarize(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = get_array_property(&properties, "data")?;
+        let strata = get_strata(&properties)?;
+        let (lower, upper) = get_bounds(&data_property)?;
+
+        // the usage of each group is the usage of the entire release
+        let privacy_usage = spread_privacy_usage(&self.privacy_usage, 1)?.remove(0);
+
+        let variable_name = variable_names
+            .and_then(|names| names.first()).cloned()
+            .unwrap_or_else(|| "[Unknown]".into());
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPBetweenGroupVariance".to_string(),
+            variables: serde_json::json!(variable_name.to_string()),
+            release_info: serde_json::json!(release.as_scalar_f64().map_err(prepend("release:"))?),
+            privacy_loss: privacy_usage_to_json(&privacy_usage),
+            accuracy: None,
+            relative_accuracy: None,
+            submission: component.submission,
+            node_id,
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "".to_string(),
+                cite: "".to_string(),
+                mechanism: self.mechanism.clone(),
+                argument: serde_json::json!({
+                    "groups": strata.iter().map(|stratum| stratum.to_string()).collect::<Vec<String>>(),
+                    "constraint": {
+                        "lowerbound": lower[0],
+                        "upperbound": upper[0]
+                    }
+                }),
+            },
+        }]))
+    }
+}
+
+/// Bounds of the data, which the sum of each group requires.
+fn get_bounds(data_property: &ArrayProperties) -> Result<(Vec<f64>, Vec<f64>)> {
+    Ok((data_property.lower_float()
+            .map_err(prepend("data: between-group variance requires clamped data:"))?,
+        data_property.upper_float()
+            .map_err(prepend("data: between-group variance requires clamped data:"))?))
+}
+
+/// The sum and count of each group are privatized by the same mechanism.
+fn check_mechanism(mechanism: &str) -> Result<()> {
+    match mechanism.to_lowercase().as_str() {
+        "laplace" | "gaussian" | "analyticgaussian" => Ok(()),
+        _ => Err("mechanism: must be one of [`Laplace`, `Gaussian`, `AnalyticGaussian`]".into())
+    }
+}
+
+#[cfg(test)]
+mod test_dp_between_group_variance {
+    use crate::base::{test_data, Value};
+    use crate::components::clamp::test_clamp;
+    use crate::components::literal::test_literal;
+    use crate::utilities::privacy::get_epsilon;
+
+    #[test]
+    fn test_parallel_composition() {
+        // each record is its own group mean, so groups are clearly separated
+        let (mut analysis, groups) = test_clamp::utilities::analysis_i64_cat(
+            test_data::array1d_i64_10_uniform(),
+            Value::Jagged(vec![vec![1i64, 2, 3, 5]].into()), Some((-1).into()));
+        let source = *analysis.components.keys().min().unwrap();
+        test_literal::privatize(&mut analysis, source);
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let data = analysis.to_float(groups).build();
+        let lower = analysis.literal().value((-1.0).into()).value_public(true).build();
+        let upper = analysis.literal().value(5.0.into()).value_public(true).build();
+        let data = analysis.clamp(data).lower(lower).upper(upper).build();
+        let data = analysis.impute(data).lower(lower).upper(upper).build();
+
+        let usage = test_data::privacy_usage(0.5, 0.);
+        let variance = analysis.dp_between_group_variance(data, groups, vec![usage]).build();
+
+        let properties = analysis.properties(variance).unwrap().array().unwrap().clone();
+        assert_eq!(properties.num_records, Some(1));
+        assert!(properties.releasable);
+
+        // each record belongs to one group, so the total usage is that of a single group
+        let total_usage = crate::compute_privacy_usage(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap();
+        assert!((get_epsilon(&total_usage).unwrap() - 0.5).abs() < 1e-10);
+    }
+}
@@ -0,0 +1,334 @@
+use indexmap::map::IndexMap;
+use ndarray::{arr0, arr1};
+
+use crate::{base, Integer, proto, Warnable};
+use crate::base::{ArrayProperties, DataframeProperties, DataType, IndexKey, Jagged, NodeProperties, Value, ValueProperties};
+use crate::components::{Accuracy, Component, Expandable, Report};
+use crate::components::dp_histogram::histogram_accuracy;
+use crate::errors::*;
+use crate::utilities::{get_array_property, get_literal, privacy::spread_privacy_usage, require_public_argument};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json, REPORT_ALPHA};
+
+impl Component for proto::DpBinnedHistogram {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let labels = get_bin_labels(&public_arguments, &properties)?;
+
+        // one count per bin, paired with the interval of the bin
+        let column_property = |data_type: DataType| ArrayProperties {
+            num_records: Some(labels.len() as i64),
+            dataset_id: Some(node_id as i64),
+            ..ArrayProperties::dp_release(node_id, data_type, vec![])
+        };
+
+        let count_type = match self.mechanism.to_lowercase().as_str() {
+            "simplegeometric" => DataType::Int,
+            _ => DataType::Float
+        };
+
+        Ok(ValueProperties::Dataframe(DataframeProperties {
+            children: indexmap![
+                IndexKey::from("category") => column_property(DataType::Str).into(),
+                IndexKey::from("count") => column_property(count_type).into()
+            ]
+        }).into())
+    }
+}
+
+impl Expandable for proto::DpBinnedHistogram {
+    /// Expand into a digitization of the data by the edges, a count of each bin, a mechanism,
+    /// and a pairing of each noisy count with the interval of its bin.
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let labels = get_bin_labels(public_arguments, properties)?;
+        let argument_ids = component.arguments();
+
+        let mut insert_node = |arguments: IndexMap<IndexKey, u32>, variant: proto::component::Variant| {
+            maximum_id += 1;
+            expansion.computation_graph.insert(maximum_id, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(arguments)),
+                variant: Some(variant),
+                omit: true,
+                submission: component.submission,
+            });
+            expansion.traversal.push(maximum_id);
+            maximum_id
+        };
+
+        // bin
+        let mut digitize_arguments = IndexMap::new();
+        vec!["data", "edges", "inclusive_left"].into_iter()
+            .for_each(|name| {
+                argument_ids.get::<IndexKey>(&name.into())
+                    .map(|v| digitize_arguments.insert(name.into(), *v));
+            });
+        let id_digitize = insert_node(
+            digitize_arguments,
+            proto::component::Variant::Digitize(proto::Digitize {}));
+
+        // count the records in each bin
+        let id_count = insert_node(
+            indexmap!["data".into() => id_digitize],
+            proto::component::Variant::Count(proto::Count { distinct: false, group_by: true, count_true: false }));
+
+        // noise
+        let mut mechanism_arguments = indexmap!["data".into() => id_count];
+        let variant = match self.mechanism.to_lowercase().as_str() {
+            "laplace" => proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
+            }),
+            "gaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                analytic: false,
+                max_noise_sigmas: 0.
+            }),
+            "analyticgaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                analytic: true,
+                max_noise_sigmas: 0.
+            }),
+            "simplegeometric" => {
+                // each bin may contain as many as all of the records
+                let privacy_definition = privacy_definition.as_ref()
+                    .ok_or_else(|| Error::from("privacy_definition must be known"))?;
+                let count_max = match get_array_property(properties, "data")?.num_records {
+                    Some(num_records) => num_records as Integer,
+                    None => if privacy_definition.protect_elapsed_time {
+                        return Err("data: the number of records must be known when protecting elapsed time".into())
+                    } else { Integer::MAX }
+                };
+
+                for (name, value) in [("lower", 0 as Integer), ("upper", count_max)] {
+                    maximum_id += 1;
+                    let (patch_node, release) = get_literal(arr0(value).into_dyn().into(), component.submission)?;
+                    expansion.computation_graph.insert(maximum_id, patch_node);
+                    expansion.properties.insert(maximum_id, infer_property(&release.value, None, maximum_id)?);
+                    expansion.releases.insert(maximum_id, release);
+                    mechanism_arguments.insert(name.into(), maximum_id);
+                }
+                proto::component::Variant::SimpleGeometricMechanism(proto::SimpleGeometricMechanism {
+                    privacy_usage: self.privacy_usage.clone()
+                })
+            },
+            _ => bail!("Unexpected invalid token {:?}", self.mechanism.as_str()),
+        };
+        maximum_id += 1;
+        let id_noised = maximum_id;
+        expansion.computation_graph.insert(id_noised, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(mechanism_arguments)),
+            variant: Some(variant),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_noised);
+
+        // labels
+        maximum_id += 1;
+        let id_labels = maximum_id;
+        let (patch_node, release) = get_literal(arr1(&labels).into_dyn().into(), component.submission)?;
+        expansion.computation_graph.insert(id_labels, patch_node);
+        expansion.properties.insert(id_labels, infer_property(&release.value, None, id_labels)?);
+        expansion.releases.insert(id_labels, release);
+
+        // pair each noisy count with the interval of its bin
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_noised,
+                "categories".into() => id_labels
+            ])),
+            variant: Some(proto::component::Variant::LabelCounts(proto::LabelCounts {})),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Accuracy for proto::DpBinnedHistogram {
+    /// Each bin is noised with the full usage, so accuracies are those of the mechanism on a single count.
+    fn accuracy_to_privacy_usage(
+        &self,
+        accuracies: &proto::Accuracies,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        match self.mechanism.to_lowercase().as_str() {
+            "laplace" => proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
+            }.accuracy_to_privacy_usage(accuracies, public_arguments),
+            "gaussian" => proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(), analytic: false, max_noise_sigmas: 0.
+            }.accuracy_to_privacy_usage(accuracies, public_arguments),
+            "simplegeometric" => proto::SimpleGeometricMechanism {
+                privacy_usage: self.privacy_usage.clone()
+            }.accuracy_to_privacy_usage(accuracies, public_arguments),
+            _ => Ok(None)
+        }
+    }
+
+    fn privacy_usage_to_accuracy(
+        &self,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        match self.mechanism.to_lowercase().as_str() {
+            "laplace" => proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
+            }.privacy_usage_to_accuracy(public_arguments, alpha),
+            "gaussian" => proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(), analytic: false, max_noise_sigmas: 0.
+            }.privacy_usage_to_accuracy(public_arguments, alpha),
+            "simplegeometric" => proto::SimpleGeometricMechanism {
+                privacy_usage: self.privacy_usage.clone()
+            }.privacy_usage_to_accuracy(public_arguments, alpha),
+            _ => Ok(None)
+        }
+    }
+}
+
+impl Report for proto::DpBinnedHistogram {
+    fn summarize(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| Error::from("privacy_definition must be known"))?;
+        let data_property = get_array_property(&properties, "data")?;
+        let labels = get_bin_labels(&public_arguments, &properties)?;
+
+        let privacy_usage = spread_privacy_usage(&self.privacy_usage, 1)?.remove(0);
+        let effective_usage = privacy_usage.actual_to_effective(
+            data_property.sample_proportion.unwrap_or(1.),
+            data_property.c_stability,
+            privacy_definition.group_size)?;
+        let accuracy = histogram_accuracy(
+            &self.mechanism, privacy_definition, &effective_usage, labels.len(), REPORT_ALPHA, false)?
+            .map(|accuracy_value| crate::utilities::json::Accuracy {
+                accuracy_value,
+                alpha: REPORT_ALPHA,
+                data_dependent: false,
+                simultaneous: false,
+            });
+
+        let variable_name = variable_names
+            .and_then(|names| names.first()).cloned()
+            .unwrap_or_else(|| "[Unknown]".into());
+
+        let counts = match release {
+            Value::Dataframe(release) => release.get::<IndexKey>(&"count".into())
+                .ok_or_else(|| Error::from("release: binned histogram must contain counts"))?,
+            _ => return Err("release: binned histogram must be a dataframe".into())
+        };
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPBinnedHistogram".to_string(),
+            variables: serde_json::json!(variable_name.to_string()),
+            release_info: value_to_json(counts)?,
+            privacy_loss: privacy_usage_to_json(&privacy_usage),
+            accuracy,
+            relative_accuracy: None,
+            submission: component.submission,
+            node_id,
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "".to_string(),
+                cite: "".to_string(),
+                mechanism: self.mechanism.clone(),
+                argument: serde_json::json!({
+                    "bins": labels
+                }),
+            },
+        }]))
+    }
+}
+
+/// Labels of the bins, in the order of the counts.
+///
+/// Each bin is labeled by its interval, and records outside of the edges are counted in a final `outside` bin.
+fn get_bin_labels(
+    public_arguments: &IndexMap<IndexKey, &Value>, properties: &NodeProperties,
+) -> Result<Vec<String>> {
+    let data_property = get_array_property(properties, "data")?;
+    data_property.assert_is_not_aggregated()?;
+    if data_property.num_columns()? != 1 {
+        return Err("data: must contain one column".into())
+    }
+    if data_property.data_type != DataType::Int && data_property.data_type != DataType::Float {
+        return Err("data: atomic type must be numeric".into())
+    }
+
+    let inclusive_left = match public_arguments.get::<IndexKey>(&"inclusive_left".into()) {
+        Some(inclusive_left) => inclusive_left.ref_array()?.first_bool()?,
+        None => true
+    };
+
+    let edges = match require_public_argument(public_arguments, "edges")?.ref_jagged()? {
+        Jagged::Float(edges) => edges.iter().flatten().map(|edge| edge.to_string()).collect::<Vec<String>>(),
+        Jagged::Int(edges) => edges.iter().flatten().map(|edge| edge.to_string()).collect::<Vec<String>>(),
+        _ => return Err("edges: must be numeric".into())
+    };
+    if edges.len() < 2 {
+        return Err("edges: must contain at least two edges".into())
+    }
+
+    Ok(edges.windows(2)
+        .map(|window| if inclusive_left {
+            format!("[{}, {})", window[0], window[1])
+        } else {
+            format!("({}, {}]", window[0], window[1])
+        })
+        .chain(std::iter::once("outside".to_string()))
+        .collect())
+}
+
+#[cfg(test)]
+mod test_dp_binned_histogram {
+    use crate::base::{IndexKey, test_data, Value};
+    use crate::components::resize::test_resize;
+
+    #[test]
+    fn test_binned_histogram_properties() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let edges = analysis.literal()
+            .value(Value::Jagged(vec![vec![0., 5., 10.]].into()))
+            .value_public(true).build();
+        let usage = test_data::privacy_usage(1., 0.);
+        let histogram = analysis.dp_binned_histogram(resized, edges, vec![usage]).build();
+
+        // two bins, and the bin of records outside of the edges
+        let properties = analysis.properties(histogram).unwrap();
+        let children = &properties.dataframe().unwrap().children;
+        let counts = children[&IndexKey::from("count")].array().unwrap();
+        assert_eq!(counts.num_records, Some(3));
+        assert!(counts.releasable && counts.noised && counts.data_derived);
+        assert_eq!(children[&IndexKey::from("category")].array().unwrap().data_type, crate::base::DataType::Str);
+    }
+}
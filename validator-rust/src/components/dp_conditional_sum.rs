@@ -0,0 +1,290 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::{Accuracy, Component, Expandable, Report};
+use crate::errors::*;
+use crate::utilities::{array::get_ith_column, get_array_property, get_literal, prepend, privacy::spread_privacy_usage};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+
+impl Component for proto::DpConditionalSum {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = get_array_property(&properties, "data")?;
+        let mask_property = get_array_property(&properties, "mask")?;
+
+        if !data_property.releasable {
+            data_property.assert_is_not_aggregated()?;
+        }
+        if data_property.data_type != DataType::Float {
+            return Err("data: atomic type must be float".into())
+        }
+        if mask_property.data_type != DataType::Bool {
+            return Err("mask: atomic type must be bool".into())
+        }
+
+        let (lower, upper) = get_masked_bounds(&data_property)?;
+        let num_columns = data_property.num_columns()?;
+
+        // the sum over the mask is bounded when the number of records is known
+        let scale = |bounds: Vec<f64>| data_property.num_records.map(|num_records| bounds.into_iter()
+            .map(|bound| Some(bound * num_records as f64)).collect())
+            .unwrap_or_else(|| vec![None; num_columns as usize]);
+
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_columns: Some(num_columns),
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(scale(lower)),
+                upper: Vector1DNull::Float(scale(upper)),
+            })),
+            ..ArrayProperties::dp_release(node_id, DataType::Float, data_property.group_id)
+        }).into())
+    }
+}
+
+impl Expandable for proto::DpConditionalSum {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        _properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+        let argument_ids = component.arguments();
+
+        let id_data = *argument_ids.get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+        let id_mask = *argument_ids.get::<IndexKey>(&"mask".into())
+            .ok_or_else(|| Error::from("mask must be provided as an argument"))?;
+
+        // cast the mask to float
+        maximum_id += 1;
+        let id_mask_float = maximum_id;
+        expansion.computation_graph.insert(id_mask_float, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_mask])),
+            variant: Some(proto::component::Variant::ToFloat(proto::ToFloat {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_mask_float);
+
+        // mask lower
+        maximum_id += 1;
+        let id_zero = maximum_id;
+        let (patch_node, release) = get_literal(0.0.into(), component.submission)?;
+        expansion.computation_graph.insert(id_zero, patch_node);
+        expansion.properties.insert(id_zero, infer_property(&release.value, None, id_zero)?);
+        expansion.releases.insert(id_zero, release);
+
+        // mask upper
+        maximum_id += 1;
+        let id_one = maximum_id;
+        let (patch_node, release) = get_literal(1.0.into(), component.submission)?;
+        expansion.computation_graph.insert(id_one, patch_node);
+        expansion.properties.insert(id_one, infer_property(&release.value, None, id_one)?);
+        expansion.releases.insert(id_one, release);
+
+        // bound the casted mask to [0, 1]
+        maximum_id += 1;
+        let id_mask_clamped = maximum_id;
+        expansion.computation_graph.insert(id_mask_clamped, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_mask_float,
+                "lower".into() => id_zero,
+                "upper".into() => id_one
+            ])),
+            variant: Some(proto::component::Variant::Clamp(proto::Clamp { null_policy: "Pass".to_string() })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_mask_clamped);
+
+        // records outside of the mask contribute zero, so the number of records is retained
+        maximum_id += 1;
+        let id_masked = maximum_id;
+        expansion.computation_graph.insert(id_masked, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => id_data,
+                "right".into() => id_mask_clamped
+            ])),
+            variant: Some(proto::component::Variant::Multiply(proto::Multiply {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_masked);
+
+        // dp sum of the masked data
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_masked])),
+            variant: Some(proto::component::Variant::DpSum(proto::DpSum {
+                mechanism: self.mechanism.clone(),
+                privacy_usage: self.privacy_usage.clone(),
+            })),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Accuracy for proto::DpConditionalSum {
+    fn accuracy_to_privacy_usage(
+        &self,
+        accuracies: &proto::Accuracies,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        match self.mechanism.to_lowercase().as_str() {
+            "laplace" => proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
+            }.accuracy_to_privacy_usage(accuracies, public_arguments),
+            "gaussian" => proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(), analytic: false, max_noise_sigmas: 0.
+            }.accuracy_to_privacy_usage(accuracies, public_arguments),
+            _ => Ok(None)
+        }
+    }
+
+    fn privacy_usage_to_accuracy(
+        &self,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        match self.mechanism.to_lowercase().as_str() {
+            "laplace" => proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
+            }.privacy_usage_to_accuracy(public_arguments, alpha),
+            "gaussian" => proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(), analytic: false, max_noise_sigmas: 0.
+            }.privacy_usage_to_accuracy(public_arguments, alpha),
+            _ => Ok(None)
+        }
+    }
+}
+
+impl Report for proto::DpConditionalSum {
+    fn summarize(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = get_array_property(&properties, "data")?;
+
+        let mut releases = Vec::new();
+
+        let (lower, upper) = get_masked_bounds(&data_property)?;
+        let num_columns = data_property.num_columns()?;
+        let privacy_usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
+
+        let release = release.ref_array()?.ref_float()?;
+
+        for column_number in 0..(num_columns as usize) {
+            let variable_name = variable_names
+                .and_then(|names| names.get(column_number)).cloned()
+                .unwrap_or_else(|| "[Unknown]".into());
+
+            releases.push(JSONRelease {
+                description: "DP release information".to_string(),
+                statistic: "DPConditionalSum".to_string(),
+                variables: serde_json::json!(variable_name.to_string()),
+                release_info: value_to_json(&get_ith_column(release, column_number)?.into())?,
+                privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
+                accuracy: None,
+                relative_accuracy: None,
+                submission: component.submission,
+                node_id,
+                postprocess: false,
+                algorithm_info: AlgorithmInfo {
+                    name: "".to_string(),
+                    cite: "".to_string(),
+                    mechanism: self.mechanism.clone(),
+                    argument: serde_json::json!({
+                        "constraint": {
+                            "lowerbound": lower[column_number],
+                            "upperbound": upper[column_number]
+                        }
+                    }),
+                },
+            });
+        }
+        Ok(Some(releases))
+    }
+}
+
+/// Bounds on the contribution of each record to the sum.
+///
+/// Records outside of the mask contribute zero, so the bounds of the data are widened to include zero.
+fn get_masked_bounds(data_property: &ArrayProperties) -> Result<(Vec<f64>, Vec<f64>)> {
+    let lower = data_property.lower_float()
+        .map_err(prepend("data: conditional sum requires clamped data:"))?;
+    let upper = data_property.upper_float()
+        .map_err(prepend("data: conditional sum requires clamped data:"))?;
+
+    Ok((lower.into_iter().map(|v| v.min(0.)).collect(),
+        upper.into_iter().map(|v| v.max(0.)).collect()))
+}
+
+#[cfg(test)]
+mod test_dp_conditional_sum {
+    use crate::base::{SensitivitySpace, test_data, ValueProperties};
+    use crate::components::resize::test_resize;
+    use crate::components::Sensitivity;
+    use crate::proto;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_dp_conditional_sum() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let threshold = analysis.literal().value(5.0.into()).value_public(true).build();
+        let mask = analysis.greater_than(resized, threshold).build();
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let conditional_sum = analysis.dp_conditional_sum(resized, mask, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let mut computation_graph = analysis.components.clone();
+        let mut release = analysis.release.clone();
+        let (properties, _) = crate::utilities::propagate_properties(
+            &Some(analysis.privacy_definition.clone()),
+            &mut computation_graph, &mut release, None, true).unwrap();
+
+        // the masked data is summed without filtering, so the number of records is retained
+        assert!(computation_graph.values()
+            .all(|component| !matches!(component.variant, Some(proto::component::Variant::Filter(_)))));
+        let (id_masked, _) = computation_graph.iter()
+            .find(|(_, component)| matches!(component.variant, Some(proto::component::Variant::Multiply(_))))
+            .unwrap();
+        let masked_property = properties.get(id_masked).unwrap().array().unwrap().clone();
+        assert_eq!(masked_property.num_records, Some(10));
+
+        // each record may contribute at most the larger magnitude of its bounds, as in a clamped sum
+        let sensitivity = proto::Sum {}.compute_sensitivity(
+            &analysis.privacy_definition,
+            &indexmap!["data".into() => ValueProperties::Array(masked_property)],
+            &SensitivitySpace::KNorm(1)).unwrap();
+        assert_eq!(sensitivity.ref_array().unwrap().first_float().unwrap(), 10.);
+
+        let conditional_sum_property = analysis.properties(conditional_sum).unwrap().array().unwrap().clone();
+        assert_eq!(conditional_sum_property.num_records, Some(1));
+    }
+}
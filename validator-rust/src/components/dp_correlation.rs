@@ -0,0 +1,432 @@
+use indexmap::map::IndexMap;
+use itertools::Itertools;
+use ndarray::arr0;
+
+use crate::{base, proto, Warnable, Float};
+use crate::base::{Array, ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Report, Sensitivity};
+use crate::errors::*;
+use crate::utilities::{get_array_property, get_literal, prepend, privacy::{get_epsilon, spread_privacy_usage}};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{Accuracy, AlgorithmInfo, JSONRelease, privacy_usage_to_json, REPORT_ALPHA};
+
+impl Component for proto::DpCorrelation {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let (left_property, _) = check_correlation(&properties)?;
+        get_variance_estimates(&public_arguments, &properties)?;
+
+        // the released correlation is clamped to [-1, 1]
+        Ok(ValueProperties::Array(ArrayProperties {
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(vec![Some(-1.)]),
+                upper: Vector1DNull::Float(vec![Some(1.)]),
+            })),
+            ..ArrayProperties::dp_release(node_id, DataType::Float, left_property.group_id)
+        }).into())
+    }
+}
+
+impl Expandable for proto::DpCorrelation {
+    /// Expand into a dp covariance divided by the root of the product of two dp variances.
+    ///
+    /// The noisy variances are floored at zero,
+    /// and the ratio is clamped to `[-1, 1]`, which is postprocessing of the three releases.
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        check_correlation(properties)?;
+
+        let mut expansion = base::ComponentExpansion::default();
+        let argument_ids = component.arguments();
+
+        let id_left = *argument_ids.get::<IndexKey>(&"left".into())
+            .ok_or_else(|| Error::from("left must be provided as an argument"))?;
+        let id_right = *argument_ids.get::<IndexKey>(&"right".into())
+            .ok_or_else(|| Error::from("right must be provided as an argument"))?;
+
+        // the budget is split evenly between the covariance and the two variances
+        let privacy_usage = self.privacy_usage.iter().cloned()
+            .map(|usage| usage / 3.)
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+
+        // dp covariance
+        maximum_id += 1;
+        let id_dp_covariance = maximum_id;
+        expansion.computation_graph.insert(id_dp_covariance, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => id_left,
+                "right".into() => id_right
+            ])),
+            variant: Some(proto::component::Variant::DpCovariance(proto::DpCovariance {
+                mechanism: self.mechanism.clone(),
+                privacy_usage: privacy_usage.clone(),
+                finite_sample_correction: self.finite_sample_correction,
+            })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_dp_covariance);
+
+        // noisy variances may be negative, so they are floored at zero before taking the root
+        maximum_id += 1;
+        let id_zero = maximum_id;
+        let value = Value::Array(Array::Float(arr0(0.).into_dyn()));
+        expansion.properties.insert(id_zero, infer_property(&value, None, id_zero)?);
+        let (patch_node, release) = get_literal(value, component.submission)?;
+        expansion.computation_graph.insert(id_zero, patch_node);
+        expansion.releases.insert(id_zero, release);
+
+        // dp variances
+        let mut id_variances = Vec::new();
+        for id_data in &[id_left, id_right] {
+            maximum_id += 1;
+            let id_dp_variance = maximum_id;
+            expansion.computation_graph.insert(id_dp_variance, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => *id_data])),
+                variant: Some(proto::component::Variant::DpVariance(proto::DpVariance {
+                    mechanism: self.mechanism.clone(),
+                    privacy_usage: privacy_usage.clone(),
+                    finite_sample_correction: self.finite_sample_correction,
+                })),
+                omit: true,
+                submission: component.submission,
+            });
+            expansion.traversal.push(id_dp_variance);
+
+            maximum_id += 1;
+            let id_nonnegative_variance = maximum_id;
+            expansion.computation_graph.insert(id_nonnegative_variance, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                    "left".into() => id_dp_variance,
+                    "right".into() => id_zero
+                ])),
+                variant: Some(proto::component::Variant::RowMax(proto::RowMax {})),
+                omit: true,
+                submission: component.submission,
+            });
+            expansion.traversal.push(id_nonnegative_variance);
+            id_variances.push(id_nonnegative_variance);
+        }
+
+        // product of the standard deviations
+        maximum_id += 1;
+        let id_variance_product = maximum_id;
+        expansion.computation_graph.insert(id_variance_product, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => id_variances[0],
+                "right".into() => id_variances[1]
+            ])),
+            variant: Some(proto::component::Variant::Multiply(proto::Multiply {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_variance_product);
+
+        maximum_id += 1;
+        let id_half = maximum_id;
+        let value = Value::Array(Array::Float(arr0(0.5).into_dyn()));
+        expansion.properties.insert(id_half, infer_property(&value, None, id_half)?);
+        let (patch_node, release) = get_literal(value, component.submission)?;
+        expansion.computation_graph.insert(id_half, patch_node);
+        expansion.releases.insert(id_half, release);
+
+        maximum_id += 1;
+        let id_std_product = maximum_id;
+        expansion.computation_graph.insert(id_std_product, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_variance_product,
+                "radical".into() => id_half
+            ])),
+            variant: Some(proto::component::Variant::Power(proto::Power {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_std_product);
+
+        // divide
+        maximum_id += 1;
+        let id_ratio = maximum_id;
+        expansion.computation_graph.insert(id_ratio, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => id_dp_covariance,
+                "right".into() => id_std_product
+            ])),
+            variant: Some(proto::component::Variant::Divide(proto::Divide {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_ratio);
+
+        // clamp to [-1, 1]
+        let mut id_bounds = Vec::new();
+        for bound in &[-1., 1.] {
+            maximum_id += 1;
+            let id_bound = maximum_id;
+            let value = Value::Array(Array::Float(arr0(*bound).into_dyn()));
+            expansion.properties.insert(id_bound, infer_property(&value, None, id_bound)?);
+            let (patch_node, release) = get_literal(value, component.submission)?;
+            expansion.computation_graph.insert(id_bound, patch_node);
+            expansion.releases.insert(id_bound, release);
+            id_bounds.push(id_bound);
+        }
+
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_ratio,
+                "lower".into() => id_bounds[0],
+                "upper".into() => id_bounds[1]
+            ])),
+            variant: Some(proto::component::Variant::Clamp(proto::Clamp { null_policy: "Pass".to_string() })),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpCorrelation {
+    fn summarize(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let (left_property, right_property) = check_correlation(&properties)?;
+        let correlation = release.as_scalar_f64().map_err(prepend("release:"))?;
+
+        let privacy_usage = spread_privacy_usage(&self.privacy_usage, 1)?.remove(0);
+
+        let component_accuracies = match privacy_definition {
+            Some(privacy_definition) if Accuracy::is_known_for(&self.mechanism) => {
+                // the budget and confidence are each split evenly between the covariance and the two variances
+                let epsilon = get_epsilon(&privacy_usage)? / 3.;
+                let alpha = REPORT_ALPHA / 3.;
+                let accuracy = |sensitivity: Value| -> Result<Float> {
+                    let sensitivity = sensitivity.array()?.cast_float()?.iter().copied()
+                        .fold1(|l, r| l.max(r)).ok_or("sensitivity must not be empty")?;
+                    Ok((1. / alpha).ln() * sensitivity / epsilon)
+                };
+
+                let variance = proto::Variance { finite_sample_correction: self.finite_sample_correction };
+                let covariance = proto::Covariance { finite_sample_correction: self.finite_sample_correction };
+                Some((
+                    accuracy(covariance.compute_sensitivity(
+                        privacy_definition, &properties, &SensitivitySpace::KNorm(1))?)?,
+                    (accuracy(variance.compute_sensitivity(
+                        privacy_definition, &indexmap!["data".into() => ValueProperties::Array(left_property.clone())],
+                        &SensitivitySpace::KNorm(1))?)?,
+                     accuracy(variance.compute_sensitivity(
+                         privacy_definition, &indexmap!["data".into() => ValueProperties::Array(right_property.clone())],
+                         &SensitivitySpace::KNorm(1))?)?)
+                ))
+            },
+            _ => None
+        };
+
+        // the correlation accuracy also depends on the variances, so it is only reported given public estimates of them
+        let accuracy = match (component_accuracies, get_variance_estimates(&public_arguments, &properties)?) {
+            (Some((covariance_accuracy, variance_accuracies)), Some(variances)) => correlation_accuracy(
+                covariance_accuracy, variance_accuracies, variances, correlation)
+                .map(|accuracy_value| Accuracy {
+                    accuracy_value, alpha: REPORT_ALPHA, data_dependent: true, simultaneous: false
+                }),
+            _ => None
+        };
+
+        let get_name = |column_number: usize| variable_names
+            .and_then(|names| names.get(column_number)).cloned()
+            .unwrap_or_else(|| "[Unknown]".into()).to_string();
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPCorrelation".to_string(),
+            variables: serde_json::json!([get_name(0), get_name(1)]),
+            release_info: serde_json::json!(correlation),
+            privacy_loss: privacy_usage_to_json(&privacy_usage),
+            accuracy,
+            relative_accuracy: None,
+            submission: component.submission,
+            node_id,
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "".to_string(),
+                cite: "".to_string(),
+                mechanism: self.mechanism.clone(),
+                argument: serde_json::json!({
+                    "n": left_property.num_records()?,
+                    "component_accuracies": component_accuracies.map(|(covariance, (left, right))| serde_json::json!({
+                        "alpha": REPORT_ALPHA,
+                        "covariance": covariance,
+                        "variance_left": left,
+                        "variance_right": right
+                    })),
+                    "constraint": {
+                        "lowerbound_left": left_property.lower_float()?,
+                        "upperbound_left": left_property.upper_float()?,
+                        "lowerbound_right": right_property.lower_float()?,
+                        "upperbound_right": right_property.upper_float()?
+                    }
+                }),
+            },
+        }]))
+    }
+}
+
+/// Both arguments must be single float columns with the same, known number of records.
+fn check_correlation(properties: &NodeProperties) -> Result<(ArrayProperties, ArrayProperties)> {
+    let left_property = get_array_property(properties, "left")?;
+    let right_property = get_array_property(properties, "right")?;
+
+    for (name, property) in &[("left", &left_property), ("right", &right_property)] {
+        if !property.releasable {
+            property.assert_is_not_aggregated()?;
+        }
+        if property.data_type != DataType::Float {
+            bail!("{}: atomic type must be float", name)
+        }
+        if property.num_columns()? != 1 {
+            bail!("{}: data must contain one column", name)
+        }
+        property.lower_float().map_err(|e| format!("{}: correlation requires clamped data: {}", name, e))?;
+        property.upper_float().map_err(|e| format!("{}: correlation requires clamped data: {}", name, e))?;
+    }
+
+    if left_property.num_records()? != right_property.num_records()? {
+        return Err("left and right must have the same number of records".into())
+    }
+    Ok((left_property, right_property))
+}
+
+/// Public estimates of the variances of `left` and `right`, if both are set.
+fn get_variance_estimates(
+    public_arguments: &IndexMap<IndexKey, &Value>, properties: &NodeProperties
+) -> Result<Option<(Float, Float)>> {
+    let mut estimates = Vec::new();
+    for name in &["variance_left", "variance_right"] {
+        match public_arguments.get::<IndexKey>(&(*name).into()) {
+            Some(estimate) => {
+                let estimate = estimate.ref_array()?.first_float().map_err(prepend(&format!("{}:", name)))?;
+                if !estimate.is_finite() || estimate <= 0. {
+                    bail!("{}: must be finite and positive", name)
+                }
+                estimates.push(estimate)
+            },
+            None if properties.contains_key::<IndexKey>(&(*name).into()) => bail!("{}: must be public", name),
+            None => ()
+        }
+    }
+    Ok(match estimates.as_slice() {
+        [left, right] => Some((*left, *right)),
+        [] => None,
+        _ => bail!("variance_left and variance_right must be set together")
+    })
+}
+
+/// Accuracy of the ratio of a covariance to the root of the product of two variances, combining the accuracy of each by the delta method.
+///
+/// `variances` are estimates of the variance of each column, and `correlation` is an estimate of the correlation.
+/// Returns None when the confidence interval of either variance contains zero, in which case the ratio is unstable.
+/// Since the released correlation is clamped to `[-1, 1]`, the accuracy is at most 2.
+pub fn correlation_accuracy(
+    covariance_accuracy: Float,
+    variance_accuracies: (Float, Float),
+    variances: (Float, Float),
+    correlation: Float,
+) -> Option<Float> {
+    if variance_accuracies.0 >= variances.0 || variance_accuracies.1 >= variances.1 {
+        return None
+    }
+    Some(((covariance_accuracy / (variances.0 * variances.1).sqrt()).powi(2)
+        + (correlation * variance_accuracies.0 / (2. * variances.0)).powi(2)
+        + (correlation * variance_accuracies.1 / (2. * variances.1)).powi(2)).sqrt().min(2.))
+}
+
+#[cfg(test)]
+mod test_dp_correlation {
+    use crate::base::{test_data, Value};
+    use crate::components::dp_correlation::correlation_accuracy;
+    use crate::components::Report;
+    use crate::components::resize::test_resize;
+    use crate::proto;
+    use crate::utilities::inference::infer_property;
+
+    #[test]
+    fn test_dp_correlation() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+        let negated = analysis.negative(resized).build();
+
+        let correlation = analysis.dp_correlation(resized, negated, vec![test_data::privacy_usage(1., 0.)])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let (properties, _) = crate::utilities::propagate_properties(
+            &Some(analysis.privacy_definition.clone()),
+            &mut analysis.components.clone(), &mut analysis.release.clone(), None, false).unwrap();
+
+        // the expanded correlation is clamped to [-1, 1]
+        let properties = properties.get(&correlation).unwrap().array().unwrap().clone();
+        assert_eq!(properties.lower_float().unwrap(), vec![-1.]);
+        assert_eq!(properties.upper_float().unwrap(), vec![1.]);
+
+        let dp_correlation = match &analysis.components.get(&correlation).unwrap().variant {
+            Some(proto::component::Variant::DpCorrelation(dp_correlation)) => dp_correlation.clone(),
+            _ => unreachable!()
+        };
+        let component = proto::Component { arguments: None, variant: None, omit: false, submission: 0 };
+        let summarize = |variance: Option<&Value>| {
+            let mut public_arguments = indexmap![];
+            let mut properties = indexmap![
+                "left".into() => analysis.properties(resized).unwrap(),
+                "right".into() => analysis.properties(negated).unwrap()
+            ];
+            if let Some(variance) = variance {
+                for name in &["variance_left", "variance_right"] {
+                    public_arguments.insert((*name).into(), variance);
+                    properties.insert((*name).into(), infer_property(variance, None, 0).unwrap());
+                }
+            }
+            dp_correlation.summarize(
+                &Some(analysis.privacy_definition.clone()), correlation, &component,
+                public_arguments, properties, &Value::from(-0.9), None).unwrap().unwrap().remove(0)
+        };
+
+        let release = summarize(None);
+        assert!(release.algorithm_info.argument["component_accuracies"]["covariance"].is_number());
+        // the correlation accuracy depends on the variances, which are private
+        assert!(release.accuracy.is_none());
+
+        // given public estimates of the variances, the accuracies of the components are combined
+        let variance = Value::from(1e4);
+        let accuracy = summarize(Some(&variance)).accuracy.unwrap();
+        assert!(accuracy.data_dependent);
+        assert!(accuracy.accuracy_value > 0. && accuracy.accuracy_value <= 2.);
+    }
+
+    #[test]
+    fn test_correlation_accuracy() {
+        // a confidence interval on a variance that contains zero is unstable
+        assert!(correlation_accuracy(0.1, (2., 0.1), (1., 1.), 0.5).is_none());
+        let accuracy = correlation_accuracy(0.1, (0.1, 0.1), (1., 1.), 0.5).unwrap();
+        assert!((accuracy - (0.01f64 + 2. * 0.025f64.powi(2)).sqrt()).abs() < 1e-10);
+        assert_eq!(correlation_accuracy(100., (0.1, 0.1), (1., 1.), 0.5), Some(2.));
+    }
+}
@@ -5,7 +5,7 @@ use crate::{base, Integer, proto};
 use crate::base::{IndexKey, NodeProperties, Value, ValueProperties};
 use crate::components::{Expandable, Report};
 use crate::errors::*;
-use crate::utilities::{get_literal};
+use crate::utilities::{get_literal, prepend};
 use crate::utilities::inference::infer_property;
 use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
 
@@ -14,7 +14,7 @@ impl Expandable for proto::DpCount {
         &self,
         privacy_definition: &Option<proto::PrivacyDefinition>,
         component: &proto::Component,
-        _public_arguments: &IndexMap<IndexKey, &Value>,
+        public_arguments: &IndexMap<IndexKey, &Value>,
         properties: &base::NodeProperties,
         component_id: u32,
         mut maximum_id: u32,
@@ -24,11 +24,35 @@ impl Expandable for proto::DpCount {
         let privacy_definition = privacy_definition.as_ref()
             .ok_or_else(|| Error::from("privacy_definition must be known"))?;
 
+        let argument_ids = component.arguments();
+
+        let num_records = match properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")? {
+            ValueProperties::Array(value) => value.num_records,
+            ValueProperties::Dataframe(value) => value.num_records()?,
+            _ => return Err("data: must be an array or dataframe".into())
+        };
+
         let mechanism = if self.mechanism.to_lowercase().as_str() == "automatic" {
-            if privacy_definition.protect_floating_point
-            { "snapping" } else { "laplace" }.to_string()
+            if privacy_definition.protect_floating_point {
+                // the snapping mechanism requires an upper bound on the count,
+                // which is unknown when the number of records is unknown, like after a filter.
+                // The geometric mechanism is also protected from floating-point attacks, and needs no upper bound
+                if num_records.is_some() || argument_ids.contains_key::<IndexKey>(&"upper".into())
+                { "snapping" } else { "simplegeometric" }
+            } else { "laplace" }.to_string()
         } else { self.mechanism.to_lowercase() };
 
+        // the count of a public value is already public, so it is released exactly, without privacy usage
+        if let Some(count) = get_public_count(self, public_arguments)? {
+            let (mut patch_node, release) = get_literal(arr0(count).into_dyn().into(), component.submission)?;
+            patch_node.omit = component.omit;
+            expansion.computation_graph.insert(component_id, patch_node);
+            expansion.properties.insert(component_id, infer_property(&release.value, None, component_id)?);
+            expansion.releases.insert(component_id, release);
+            return Ok(expansion)
+        }
+
         // count
         maximum_id += 1;
         let id_count = maximum_id;
@@ -38,15 +62,15 @@ impl Expandable for proto::DpCount {
                     .ok_or_else(|| Error::from("data must be provided as an argument"))?
             ])),
             variant: Some(proto::component::Variant::Count(proto::Count {
-                distinct: self.distinct
+                distinct: self.distinct,
+                group_by: false,
+                count_true: false
             })),
             omit: true,
             submission: component.submission,
         });
         expansion.traversal.push(id_count);
 
-        let argument_ids = component.arguments();
-
         if mechanism.as_str() == "simplegeometric" {
 
             let count_min_id = match argument_ids.get::<IndexKey>(&"lower".into()) {
@@ -65,13 +89,6 @@ impl Expandable for proto::DpCount {
 
             let count_max_id = match argument_ids.get::<IndexKey>(&"upper".into()) {
                 None => {
-                    let num_records = match properties.get::<IndexKey>(&"data".into())
-                        .ok_or("data: missing")? {
-                        ValueProperties::Array(value) => value.num_records,
-                        ValueProperties::Dataframe(value) => value.num_records()?,
-                        _ => return Err("data: must be an array or dataframe".into())
-                    };
-
                     let count_max = match num_records {
                         Some(num_records) => arr0(num_records as Integer).into_dyn(),
                         None => if privacy_definition.protect_elapsed_time {
@@ -111,15 +128,18 @@ impl Expandable for proto::DpCount {
 
             let variant = Some(match mechanism.as_str() {
                 "laplace" => proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
-                    privacy_usage: self.privacy_usage.clone()
+                    privacy_usage: self.privacy_usage.clone(),
+                    max_noise_sigmas: 0.
                 }),
                 "gaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
                     privacy_usage: self.privacy_usage.clone(),
-                    analytic: false
+                    analytic: false,
+                    max_noise_sigmas: 0.
                 }),
                 "analyticgaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
                     privacy_usage: self.privacy_usage.clone(),
-                    analytic: true
+                    analytic: true,
+                    max_noise_sigmas: 0.
                 }),
                 "snapping" => {
                     argument_ids.get::<IndexKey>(&"lower".into())
@@ -149,13 +169,21 @@ impl Expandable for proto::DpCount {
 impl Report for proto::DpCount {
     fn summarize(
         &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
         node_id: u32,
         component: &proto::Component,
-        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
         _properties: NodeProperties,
         release: &Value,
         variable_names: Option<&Vec<base::IndexKey>>,
     ) -> Result<Option<Vec<JSONRelease>>> {
+        release.assert_scalar().map_err(prepend("release:"))?;
+
+        // a count of public data is exact, and uses none of the budget
+        let privacy_usage = if get_public_count(self, &public_arguments)?.is_some() {
+            (self.privacy_usage[0].clone() * 0.)?
+        } else { self.privacy_usage[0].clone() };
+
         Ok(Some(vec![JSONRelease {
             description: "DP release information".to_string(),
             statistic: "DPCount".to_string(),
@@ -163,8 +191,9 @@ impl Report for proto::DpCount {
                 .unwrap_or_else(Vec::new).iter()
                 .map(|v| v.to_string()).collect::<Vec<String>>()),
             release_info: value_to_json(&release)?,
-            privacy_loss: privacy_usage_to_json(&self.privacy_usage[0].clone()),
+            privacy_loss: privacy_usage_to_json(&privacy_usage),
             accuracy: None,
+            relative_accuracy: None,
             submission: component.submission,
             node_id,
             postprocess: false,
@@ -179,3 +208,103 @@ impl Report for proto::DpCount {
         }]))
     }
 }
+
+/// The exact count of the data, if the data is a public value already known to the validator.
+///
+/// Public data that has not been evaluated, like a resize of a public value, is counted with a mechanism as usual.
+/// Distinct counts are always counted with a mechanism.
+fn get_public_count(count: &proto::DpCount, public_arguments: &IndexMap<IndexKey, &Value>) -> Result<Option<Integer>> {
+    if count.distinct {
+        return Ok(None)
+    }
+    Ok(match public_arguments.get::<IndexKey>(&"data".into()) {
+        Some(Value::Array(data)) => Some(data.num_records()? as Integer),
+        Some(Value::Dataframe(data)) => Some(data.get_index(0)
+            .ok_or_else(|| Error::from("data: dataframe must have at least one column"))?
+            .1.ref_array()?.num_records()? as Integer),
+        _ => None
+    })
+}
+
+#[cfg(test)]
+mod test_dp_count {
+    use ndarray::arr1;
+
+    use crate::base::test_data;
+    use crate::bindings::Analysis;
+    use crate::proto;
+    use crate::utilities::privacy::get_epsilon;
+
+    #[test]
+    fn test_public_dp_count() {
+        let mut analysis = Analysis::new();
+        let data = analysis.literal()
+            .value(arr1(&[1., 2., 3., 4., 5.]).into_dyn().into())
+            .value_public(true).build();
+        let lower = analysis.literal().value(0.into()).value_public(true).build();
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let count = analysis.dp_count(data, lower, vec![usage]).build();
+
+        // the count is exact, and may be released as-is
+        let mut computation_graph = analysis.components.clone();
+        let mut release = analysis.release.clone();
+        let (properties, _) = crate::utilities::propagate_properties(
+            &Some(analysis.privacy_definition.clone()),
+            &mut computation_graph, &mut release, None, false).unwrap();
+        assert!(matches!(computation_graph[&count].variant, Some(proto::component::Variant::Literal(_))));
+        assert_eq!(release[&count].value.ref_array().unwrap().first_int().unwrap(), 5);
+        let count_property = properties[&count].array().unwrap();
+        assert!(count_property.releasable && count_property.aggregator.is_none());
+
+        let privacy_usage = crate::compute_privacy_usage(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap();
+        assert_eq!(get_epsilon(&privacy_usage).unwrap(), 0.);
+    }
+
+    #[test]
+    fn test_filtered_dp_count() {
+        use crate::base::{IndexKey, SensitivitySpace, test_data};
+        use crate::components::literal::test_literal;
+        use crate::components::resize::test_resize;
+        use crate::components::Sensitivity;
+
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        let source = *analysis.components.keys().min().unwrap();
+        test_literal::privatize(&mut analysis, source);
+
+        // filtering on a private predicate leaves the number of records unknown
+        let threshold = analysis.literal().value(5.0.into()).value_public(true).build();
+        let mask = analysis.greater_than(resized, threshold).build();
+        let filtered = analysis.filter(resized, mask).build();
+        let filtered_property = analysis.properties(filtered).unwrap();
+        assert_eq!(filtered_property.array().unwrap().num_records, None);
+
+        // adding or removing a record still changes the count by one
+        let sensitivity = proto::Count { distinct: false, group_by: false, count_true: false }
+            .compute_sensitivity(
+                &analysis.privacy_definition,
+                &indexmap![IndexKey::from("data") => filtered_property],
+                &SensitivitySpace::KNorm(1))
+            .unwrap().array().unwrap().first_int().unwrap();
+        assert_eq!(sensitivity, 1);
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let lower = analysis.literal().value(0.into()).value_public(true).build();
+
+        // the count is released under floating-point protections, although its upper bound is unknown
+        for mechanism in &["SimpleGeometric", "Automatic"] {
+            let count = analysis.dp_count(filtered, lower, vec![usage.clone()])
+                .mechanism(mechanism.to_string()).build();
+            assert!(analysis.properties(count).unwrap().array().unwrap().releasable);
+        }
+
+        analysis.privacy_definition.protect_floating_point = false;
+        let count = analysis.dp_count(filtered, lower, vec![usage])
+            .mechanism("Laplace".to_string()).build();
+        assert!(analysis.properties(count).unwrap().array().unwrap().releasable);
+    }
+}
@@ -4,7 +4,7 @@ use indexmap::map::IndexMap;
 
 use crate::{base, proto};
 use crate::base::{IndexKey, NodeProperties, Value};
-use crate::components::{Expandable, Report};
+use crate::components::{Expandable, Named, Report};
 use crate::errors::*;
 use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
 use crate::utilities::prepend;
@@ -85,15 +85,18 @@ impl Expandable for proto::DpCovariance {
         let mut arguments = indexmap!["data".into() => id_covariance];
         let variant = Some(match mechanism.as_str() {
             "laplace" => proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
-                privacy_usage: self.privacy_usage.clone()
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
             }),
             "gaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
                 privacy_usage: self.privacy_usage.clone(),
                 analytic: false,
+                max_noise_sigmas: 0.,
             }),
             "analyticgaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
                 privacy_usage: self.privacy_usage.clone(),
                 analytic: true,
+                max_noise_sigmas: 0.,
             }),
             "snapping" => {
                 argument_ids.get::<IndexKey>(&"lower".into())
@@ -135,6 +138,7 @@ impl Expandable for proto::DpCovariance {
 impl Report for proto::DpCovariance {
     fn summarize(
         &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
         node_id: u32,
         component: &proto::Component,
         _public_arguments: IndexMap<base::IndexKey, &Value>,
@@ -145,12 +149,26 @@ impl Report for proto::DpCovariance {
 
         let argument;
         let statistic;
+        let row_names;
+        let column_names;
+
+        // names of the input columns, in the order given by get_names
+        let get_names = |offset: usize, num_columns: i64| (0..num_columns as usize)
+            .map(|column_number| variable_names
+                .and_then(|names| names.get(offset + column_number))
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| "[Unknown]".to_string()))
+            .collect::<Vec<String>>();
 
         if properties.contains_key(&IndexKey::from("data")) {
             let data_property = properties.get::<IndexKey>(&"data".into())
                 .ok_or("data: missing")?.array()
                 .map_err(prepend("data:"))?.clone();
 
+            row_names = get_names(0, data_property.num_columns()?);
+            column_names = row_names.clone();
+            release.assert_square().map_err(prepend("release:"))?;
+
             statistic = "DPCovariance".to_string();
             argument = serde_json::json!({
                 "n": data_property.num_records()?,
@@ -168,6 +186,11 @@ impl Report for proto::DpCovariance {
                 .ok_or("data: missing")?.array()
                 .map_err(prepend("data:"))?.clone();
 
+            // rows correspond to columns of left, and columns to columns of right
+            row_names = get_names(0, left_property.num_columns()?);
+            column_names = get_names(row_names.len(), right_property.num_columns()?);
+            release.assert_num_columns(column_names.len()).map_err(prepend("release:"))?;
+
             statistic = "DPCrossCovariance".to_string();
             argument = serde_json::json!({
                 "n": left_property.num_records()?,
@@ -187,12 +210,14 @@ impl Report for proto::DpCovariance {
         Ok(Some(vec![JSONRelease {
             description: "DP release information".to_string(),
             statistic,
-            variables: serde_json::json!(variable_names.cloned()
-                .unwrap_or_else(Vec::new).iter()
-                .map(|v| v.to_string()).collect::<Vec<String>>()),
+            variables: serde_json::json!({
+                "rows": row_names,
+                "columns": column_names
+            }),
             release_info: value_to_json(&release)?,
             privacy_loss: serde_json::json![privacy_usage],
             accuracy: None,
+            relative_accuracy: None,
             submission: component.submission,
             node_id,
             postprocess: false,
@@ -205,3 +230,65 @@ impl Report for proto::DpCovariance {
         }]))
     }
 }
+
+impl Named for proto::DpCovariance {
+    /// Names of the columns of data, or the names of the columns of left followed by the names of the columns of right.
+    fn get_names(
+        &self,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        argument_variables: IndexMap<base::IndexKey, Vec<IndexKey>>,
+        _release: Option<&Value>,
+    ) -> Result<Vec<IndexKey>> {
+        if let Some(names) = argument_variables.get::<IndexKey>(&"data".into()) {
+            return Ok(names.clone())
+        }
+        let left_names = argument_variables.get::<IndexKey>(&"left".into())
+            .ok_or("left: missing names")?;
+        let right_names = argument_variables.get::<IndexKey>(&"right".into())
+            .ok_or("right: missing names")?;
+        Ok(left_names.iter().chain(right_names.iter()).cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod test_dp_covariance {
+    use ndarray::arr2;
+
+    use crate::base::{IndexKey, test_data};
+    use crate::components::{Named, Report};
+    use crate::components::resize::test_resize;
+    use crate::proto;
+
+    #[test]
+    fn test_summary_labels() {
+        let (analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        let property = analysis.properties(resized).unwrap();
+
+        let covariance = proto::DpCovariance {
+            mechanism: "Laplace".to_string(),
+            privacy_usage: vec![],
+            finite_sample_correction: true,
+        };
+        let component = proto::Component {
+            arguments: None,
+            variant: Some(proto::component::Variant::DpCovariance(covariance.clone())),
+            omit: false,
+            submission: 0,
+        };
+
+        let names = covariance.get_names(indexmap![], indexmap![
+            IndexKey::from("left") => vec![IndexKey::from("age")],
+            IndexKey::from("right") => vec![IndexKey::from("income")]
+        ], None).unwrap();
+
+        let summary = covariance.summarize(
+            &None, 0, &component, indexmap![],
+            indexmap!["left".into() => property.clone(), "right".into() => property],
+            &arr2(&[[1.]]).into_dyn().into(),
+            Some(&names)).unwrap().unwrap();
+
+        assert_eq!(summary[0].variables["rows"], serde_json::json!(["age"]));
+        assert_eq!(summary[0].variables["columns"], serde_json::json!(["income"]));
+    }
+}
@@ -0,0 +1,377 @@
+use indexmap::map::IndexMap;
+use itertools::Itertools;
+use ndarray::arr0;
+
+use crate::{base, proto, Warnable, Float};
+use crate::base::{Array, ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Report, Sensitivity};
+use crate::components::dp_mean::dp_mean_accuracy;
+use crate::errors::*;
+use crate::utilities::{array::get_ith_column, get_literal, prepend, privacy::{get_epsilon, spread_privacy_usage}};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{Accuracy, AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json, REPORT_ALPHA};
+
+impl Component for proto::DpCoefficientOfVariation {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        if !data_property.releasable {
+            data_property.assert_is_not_aggregated()?;
+        }
+
+        if data_property.data_type != DataType::Float {
+            return Err("data: atomic type must be float".into())
+        }
+        check_mean_nonzero(&data_property)?;
+
+        let num_columns = data_property.num_columns()?;
+
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_columns: Some(num_columns),
+            // the noisy mean may be arbitrarily close to zero, so the ratio is unbounded
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float((0..num_columns).map(|_| None).collect()),
+                upper: Vector1DNull::Float((0..num_columns).map(|_| None).collect()),
+            })),
+            ..ArrayProperties::dp_release(node_id, DataType::Float, data_property.group_id)
+        }).into())
+    }
+}
+
+impl Expandable for proto::DpCoefficientOfVariation {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        // the component is replaced by its expansion before properties are propagated over it
+        let data_property: &ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+        check_mean_nonzero(data_property)?;
+
+        let mut expansion = base::ComponentExpansion::default();
+
+        let id_data = *component.arguments().get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+
+        // the budget is split evenly between the variance and the mean
+        let privacy_usage = self.privacy_usage.iter().cloned()
+            .map(|usage| usage / 2.)
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+
+        // dp variance
+        maximum_id += 1;
+        let id_dp_variance = maximum_id;
+        expansion.computation_graph.insert(id_dp_variance, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_data])),
+            variant: Some(proto::component::Variant::DpVariance(proto::DpVariance {
+                mechanism: self.mechanism.clone(),
+                privacy_usage: privacy_usage.clone(),
+                finite_sample_correction: self.finite_sample_correction,
+            })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_dp_variance);
+
+        // noisy variances may be negative, so they are floored at zero before taking the root
+        maximum_id += 1;
+        let id_zero = maximum_id;
+        let value = Value::Array(Array::Float(arr0(0.).into_dyn()));
+        expansion.properties.insert(id_zero, infer_property(&value, None, id_zero)?);
+        let (patch_node, release) = get_literal(value, component.submission)?;
+        expansion.computation_graph.insert(id_zero, patch_node);
+        expansion.releases.insert(id_zero, release);
+
+        maximum_id += 1;
+        let id_nonnegative_variance = maximum_id;
+        expansion.computation_graph.insert(id_nonnegative_variance, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => id_dp_variance,
+                "right".into() => id_zero
+            ])),
+            variant: Some(proto::component::Variant::RowMax(proto::RowMax {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_nonnegative_variance);
+
+        // standard deviation
+        maximum_id += 1;
+        let id_half = maximum_id;
+        let value = Value::Array(Array::Float(arr0(0.5).into_dyn()));
+        expansion.properties.insert(id_half, infer_property(&value, None, id_half)?);
+        let (patch_node, release) = get_literal(value, component.submission)?;
+        expansion.computation_graph.insert(id_half, patch_node);
+        expansion.releases.insert(id_half, release);
+
+        maximum_id += 1;
+        let id_std = maximum_id;
+        expansion.computation_graph.insert(id_std, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_nonnegative_variance,
+                "radical".into() => id_half
+            ])),
+            variant: Some(proto::component::Variant::Power(proto::Power {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_std);
+
+        // dp mean
+        maximum_id += 1;
+        let id_dp_mean = maximum_id;
+        expansion.computation_graph.insert(id_dp_mean, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_data])),
+            variant: Some(proto::component::Variant::DpMean(proto::DpMean {
+                implementation: self.implementation.clone(),
+                centered: false,
+                mechanism: self.mechanism.clone(),
+                privacy_usage,
+            })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_dp_mean);
+
+        // divide
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => id_std,
+                "right".into() => id_dp_mean
+            ])),
+            variant: Some(proto::component::Variant::Divide(proto::Divide {})),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpCoefficientOfVariation {
+    fn summarize(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = properties.get::<base::IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        let mut releases = Vec::new();
+
+        let lower = data_property.lower_float()?;
+        let upper = data_property.upper_float()?;
+        let num_records = data_property.num_records()?;
+
+        let num_columns = data_property.num_columns()?;
+        let privacy_usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
+
+        // the accuracy of the mean is only derived for resized data
+        let accuracy_is_known = Accuracy::is_known_for(&self.mechanism)
+            && self.implementation.to_lowercase() == "resize";
+        let variance_sensitivities = match privacy_definition {
+            Some(privacy_definition) if accuracy_is_known => Some(proto::Variance {
+                finite_sample_correction: self.finite_sample_correction
+            }.compute_sensitivity(privacy_definition, &properties, &SensitivitySpace::KNorm(1))?
+                .array()?.cast_float()?
+                .gencolumns().into_iter()
+                .map(|column| column.into_iter().copied().fold1(|l, r| l.max(r)).unwrap())
+                .collect::<Vec<Float>>()),
+            _ => None
+        };
+
+        for column_number in 0..(num_columns as usize) {
+            let variable_name = variable_names
+                .and_then(|names| names.get(column_number)).cloned()
+                .unwrap_or_else(|| "[Unknown]".into());
+
+            // the budget and confidence are each split evenly between the variance and the mean
+            let (accuracy, unstable) = match &variance_sensitivities {
+                Some(variance_sensitivities) => {
+                    let epsilon = get_epsilon(&privacy_usages[column_number])? / 2.;
+                    let alpha = REPORT_ALPHA / 2.;
+                    let mean_accuracy = dp_mean_accuracy(
+                        lower[column_number], upper[column_number], num_records, epsilon, alpha);
+                    let variance_accuracy = (1. / alpha).ln() * variance_sensitivities[column_number] / epsilon;
+
+                    let accuracy = coefficient_of_variation_accuracy(
+                        // |sqrt(a) - sqrt(b)| <= sqrt(|a - b|)
+                        variance_accuracy.sqrt(),
+                        mean_accuracy,
+                        (upper[column_number] - lower[column_number]) / 2.,
+                        lower[column_number].abs().min(upper[column_number].abs()));
+
+                    (accuracy.map(|accuracy_value| Accuracy {
+                        accuracy_value, alpha: REPORT_ALPHA, data_dependent: false, simultaneous: false
+                    }), accuracy.is_none())
+                },
+                None => (None, false)
+            };
+
+            releases.push(JSONRelease {
+                description: "DP release information".to_string(),
+                statistic: "DPCoefficientOfVariation".to_string(),
+                variables: serde_json::json!(variable_name.to_string()),
+                release_info: match release.ref_array()? {
+                    Array::Float(v) => value_to_json(&get_ith_column(v, column_number)?.into())?,
+                    _ => return Err("coefficient of variation must be float".into())
+                },
+                privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
+                accuracy,
+                relative_accuracy: None,
+                submission: component.submission,
+                node_id,
+                postprocess: false,
+                algorithm_info: AlgorithmInfo {
+                    name: "".to_string(),
+                    cite: "".to_string(),
+                    mechanism: self.mechanism.clone(),
+                    argument: serde_json::json!({
+                        "n": num_records,
+                        "implementation": self.implementation.clone(),
+                        "unstable": unstable,
+                        "constraint": {
+                            "lowerbound": lower[column_number],
+                            "upperbound": upper[column_number]
+                        }
+                    }),
+                },
+            });
+        }
+        Ok(Some(releases))
+    }
+}
+
+/// The coefficient of variation is unstable when the mean may be zero,
+/// so the data bounds of each column must lie strictly to one side of zero.
+fn check_mean_nonzero(data_property: &ArrayProperties) -> Result<()> {
+    let lower = data_property.lower_float()
+        .map_err(prepend("data: coefficient of variation requires clamped data:"))?;
+    let upper = data_property.upper_float()
+        .map_err(prepend("data: coefficient of variation requires clamped data:"))?;
+
+    if !lower.iter().zip(upper.iter()).all(|(lower, upper)| *lower > 0. || *upper < 0.) {
+        return Err("data: bounds may not span zero, because the coefficient of variation is unstable when the mean is near zero".into())
+    }
+    Ok(())
+}
+
+/// Accuracy of the ratio of a standard deviation to a mean, combining the accuracy of each by the delta method.
+///
+/// `std_upper` bounds the true standard deviation, and `mean_magnitude` bounds the magnitude of the true mean from below.
+/// Returns None when the confidence interval of the mean contains zero, in which case the ratio is unstable.
+pub fn coefficient_of_variation_accuracy(
+    std_accuracy: Float, mean_accuracy: Float, std_upper: Float, mean_magnitude: Float
+) -> Option<Float> {
+    if mean_accuracy >= mean_magnitude {
+        return None
+    }
+    Some(((std_accuracy / mean_magnitude).powi(2)
+        + (std_upper * mean_accuracy / mean_magnitude.powi(2)).powi(2)).sqrt())
+}
+
+#[cfg(test)]
+mod test_dp_cv {
+    use crate::base::test_data;
+    use crate::components::dp_cv::coefficient_of_variation_accuracy;
+    use crate::components::resize::test_resize;
+    use crate::proto;
+
+    #[test]
+    fn test_dp_cv() {
+        // clamp and resize within strictly positive bounds
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(1.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let cv = analysis.dp_coefficient_of_variation(resized, vec![test_data::privacy_usage(1., 0.)])
+            .mechanism("Laplace".to_string())
+            .build();
+        assert!(analysis.properties(cv).is_ok());
+
+        let expansion = crate::expand_component(
+            analysis.components.get(&cv).unwrap().clone(),
+            indexmap!["data".into() => analysis.properties(resized).unwrap()],
+            indexmap![],
+            Some(analysis.privacy_definition.clone()),
+            cv, 1000).unwrap();
+
+        assert_eq!(expansion.computation_graph.values()
+            .filter(|component| matches!(component.variant.as_ref(), Some(proto::component::Variant::DpVariance(_))))
+            .count(), 1);
+        assert_eq!(expansion.computation_graph.values()
+            .filter(|component| matches!(component.variant.as_ref(), Some(proto::component::Variant::DpMean(_))))
+            .count(), 1);
+        assert_eq!(expansion.computation_graph.values()
+            .filter(|component| matches!(component.variant.as_ref(), Some(proto::component::Variant::Power(_))))
+            .count(), 1);
+        assert!(matches!(
+            expansion.computation_graph.get(&cv).unwrap().variant,
+            Some(proto::component::Variant::Divide(_))));
+    }
+
+    #[test]
+    fn test_dp_cv_implementation() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(1.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let cv = analysis.dp_coefficient_of_variation(resized, vec![test_data::privacy_usage(1., 0.)])
+            .mechanism("Laplace".to_string())
+            .implementation("plug-in".to_string())
+            .build();
+        analysis.properties(cv).unwrap();
+
+        let expansion = crate::expand_component(
+            analysis.components.get(&cv).unwrap().clone(),
+            indexmap!["data".into() => analysis.properties(resized).unwrap()],
+            indexmap![],
+            Some(analysis.privacy_definition.clone()),
+            cv, 1000).unwrap();
+
+        // the mean is estimated with the requested implementation
+        let implementations = expansion.computation_graph.values()
+            .filter_map(|component| match component.variant.as_ref() {
+                Some(proto::component::Variant::DpMean(mean)) => Some(mean.implementation.clone()),
+                _ => None
+            }).collect::<Vec<_>>();
+        assert_eq!(implementations, vec!["plug-in".to_string()]);
+    }
+
+    #[test]
+    fn test_dp_cv_mean_near_zero() {
+        // data bounds spanning zero admit a zero mean
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let cv = analysis.dp_coefficient_of_variation(resized, vec![test_data::privacy_usage(1., 0.)])
+            .mechanism("Laplace".to_string())
+            .build();
+        assert!(analysis.properties(cv).is_err());
+
+        // a confidence interval on the mean that contains zero is unstable
+        assert!(coefficient_of_variation_accuracy(0.1, 2., 1., 1.).is_none());
+        assert!(coefficient_of_variation_accuracy(0.1, 0.5, 1., 1.).is_some());
+    }
+}
@@ -0,0 +1,296 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Report};
+use crate::components::ecdf::get_num_thresholds;
+use crate::errors::*;
+use crate::utilities::{get_array_property, get_literal, prepend, require_public_argument, privacy::get_epsilon};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{Accuracy, AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json, REPORT_ALPHA};
+
+impl Component for proto::DpEcdf {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = get_array_property(&properties, "data")?;
+
+        if !data_property.releasable {
+            data_property.assert_is_not_aggregated()?;
+        }
+        let num_thresholds = get_num_thresholds(&public_arguments, &data_property)?;
+        if self.monotone {
+            check_thresholds_sorted(&public_arguments)?;
+        }
+
+        // the ecdf is intended to be monotone in [0, 1], but noise is added at each threshold independently
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_records: Some(num_thresholds),
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(vec![Some(0.)]),
+                upper: Vector1DNull::Float(vec![Some(1.)]),
+            })),
+            dataset_id: Some(node_id as i64),
+            dimensionality: data_property.dimensionality,
+            monotone: self.monotone,
+            ..ArrayProperties::dp_release(node_id, DataType::Float, data_property.group_id)
+        }).into())
+    }
+}
+
+impl Expandable for proto::DpEcdf {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+        let argument_ids = component.arguments();
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| Error::from("privacy_definition must be known"))?;
+
+        get_num_thresholds(public_arguments, &get_array_property(properties, "data")?)?;
+        if self.monotone {
+            check_thresholds_sorted(public_arguments)?;
+        }
+
+        let mechanism = if self.mechanism.to_lowercase().as_str() == "automatic" {
+            if privacy_definition.protect_floating_point
+            { "snapping" } else { "laplace" }.to_string()
+        } else { self.mechanism.to_lowercase() };
+
+        let get_id = |name: &str| argument_ids.get::<IndexKey>(&name.into()).cloned()
+            .ok_or_else(|| Error::from(format!("{} must be provided as an argument", name)));
+
+        // ecdf
+        maximum_id += 1;
+        let id_ecdf = maximum_id;
+        expansion.computation_graph.insert(id_ecdf, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => get_id("data")?,
+                "thresholds".into() => get_id("thresholds")?
+            ])),
+            variant: Some(proto::component::Variant::Ecdf(proto::Ecdf {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_ecdf);
+
+        // ecdf lower
+        maximum_id += 1;
+        let id_zero = maximum_id;
+        let (patch_node, release) = get_literal(0.0.into(), component.submission)?;
+        expansion.computation_graph.insert(id_zero, patch_node);
+        expansion.properties.insert(id_zero, infer_property(&release.value, None, id_zero)?);
+        expansion.releases.insert(id_zero, release);
+
+        // ecdf upper
+        maximum_id += 1;
+        let id_one = maximum_id;
+        let (patch_node, release) = get_literal(1.0.into(), component.submission)?;
+        expansion.computation_graph.insert(id_one, patch_node);
+        expansion.properties.insert(id_one, infer_property(&release.value, None, id_one)?);
+        expansion.releases.insert(id_one, release);
+
+        // noising
+        let mut arguments = indexmap!["data".into() => id_ecdf];
+        let variant = match mechanism.as_str() {
+            "laplace" => proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
+            }),
+            "gaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                analytic: false,
+                max_noise_sigmas: 0.
+            }),
+            "analyticgaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                analytic: true,
+                max_noise_sigmas: 0.
+            }),
+            "snapping" => {
+                arguments.insert("lower".into(), id_zero);
+                arguments.insert("upper".into(), id_one);
+                proto::component::Variant::SnappingMechanism(proto::SnappingMechanism {
+                    privacy_usage: self.privacy_usage.clone()
+                })
+            },
+            _ => bail!("Unexpected invalid token {:?}", self.mechanism.as_str())
+        };
+
+        maximum_id += 1;
+        let id_noised = maximum_id;
+        expansion.computation_graph.insert(id_noised, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(arguments)),
+            variant: Some(variant),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_noised);
+
+        // postprocess the noisy fractions back into [0, 1]
+        let clamp = proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_noised,
+                "lower".into() => id_zero,
+                "upper".into() => id_one
+            ])),
+            variant: Some(proto::component::Variant::Clamp(proto::Clamp { null_policy: "Pass".to_string() })),
+            omit: component.omit,
+            submission: component.submission,
+        };
+
+        if !self.monotone {
+            expansion.computation_graph.insert(component_id, clamp);
+            return Ok(expansion)
+        }
+
+        maximum_id += 1;
+        let id_clamp = maximum_id;
+        expansion.computation_graph.insert(id_clamp, proto::Component { omit: true, ..clamp });
+        expansion.traversal.push(id_clamp);
+
+        // postprocess the bounded fractions onto the non-decreasing vectors
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_clamp])),
+            variant: Some(proto::component::Variant::Isotonic(proto::Isotonic {})),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpEcdf {
+    fn summarize(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = get_array_property(&properties, "data")?;
+
+        let num_records = data_property.num_records()?;
+        let num_thresholds = get_num_thresholds(&public_arguments, &data_property)?;
+        let thresholds = require_public_argument(&public_arguments, "thresholds")?;
+
+        let privacy_usage = self.privacy_usage.first()
+            .ok_or_else(|| Error::from("privacy_usage: must be defined"))?;
+
+        // the union bound holds the accuracy at every threshold simultaneously
+        let accuracy = if Accuracy::is_known_for(&self.mechanism) {
+            Some(Accuracy {
+                accuracy_value: (num_thresholds as f64 / REPORT_ALPHA).ln()
+                    * num_thresholds as f64 / num_records as f64 / get_epsilon(privacy_usage)?,
+                alpha: REPORT_ALPHA,
+                data_dependent: false,
+                simultaneous: true,
+            })
+        } else { None };
+
+        let variable_name = variable_names
+            .and_then(|names| names.first()).cloned()
+            .unwrap_or_else(|| "[Unknown]".into());
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPEcdf".to_string(),
+            variables: serde_json::json!(variable_name.to_string()),
+            release_info: serde_json::json!(release.as_vec_f64().map_err(prepend("release:"))?),
+            privacy_loss: privacy_usage_to_json(privacy_usage),
+            accuracy,
+            relative_accuracy: None,
+            submission: component.submission,
+            node_id,
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "".to_string(),
+                cite: "".to_string(),
+                mechanism: self.mechanism.clone(),
+                argument: serde_json::json!({
+                    "n": num_records,
+                    "thresholds": value_to_json(thresholds)?
+                }),
+            },
+        }]))
+    }
+}
+
+/// Check that the thresholds are non-decreasing, so that the ecdf at the thresholds is non-decreasing.
+fn check_thresholds_sorted(public_arguments: &IndexMap<IndexKey, &Value>) -> Result<()> {
+    let sorted = match require_public_argument(public_arguments, "thresholds")?.ref_array()? {
+        base::Array::Float(thresholds) => thresholds.iter().zip(thresholds.iter().skip(1)).all(|(l, r)| l <= r),
+        base::Array::Int(thresholds) => thresholds.iter().zip(thresholds.iter().skip(1)).all(|(l, r)| l <= r),
+        _ => return Err("thresholds: atomic type must be numeric".into())
+    };
+    if !sorted {
+        return Err("thresholds: must be sorted in increasing order when the ecdf is monotone".into())
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_dp_ecdf {
+    use ndarray::arr1;
+
+    use crate::base::test_data;
+    use crate::components::resize::test_resize;
+
+    #[test]
+    fn test_dp_ecdf() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(1.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let thresholds = analysis.literal()
+            .value(arr1(&[0.25, 0.5, 0.75]).into_dyn().into())
+            .value_public(true).build();
+        let usage = test_data::privacy_usage(1., 0.);
+        let ecdf = analysis.dp_ecdf(resized, thresholds, vec![usage.clone()])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let ecdf_property = analysis.properties(ecdf).unwrap().array().unwrap().clone();
+        assert_eq!(ecdf_property.num_records, Some(3));
+        assert_eq!(ecdf_property.lower_float().unwrap(), vec![0.]);
+        assert_eq!(ecdf_property.upper_float().unwrap(), vec![1.]);
+        assert!(!ecdf_property.monotone);
+
+        let ecdf = analysis.dp_ecdf(resized, thresholds, vec![usage.clone()])
+            .mechanism("Laplace".to_string())
+            .monotone(true)
+            .build();
+        assert!(analysis.properties(ecdf).unwrap().array().unwrap().monotone);
+
+        // a monotone ecdf requires sorted thresholds
+        let unsorted = analysis.literal()
+            .value(arr1(&[0.5, 0.25]).into_dyn().into())
+            .value_public(true).build();
+        let ecdf = analysis.dp_ecdf(resized, unsorted, vec![usage])
+            .monotone(true)
+            .build();
+        assert!(analysis.properties(ecdf).is_err());
+
+        // thresholds must be public
+        let private_thresholds = analysis.literal()
+            .value(arr1(&[0.5]).into_dyn().into())
+            .value_public(false).build();
+        let ecdf = analysis.dp_ecdf(resized, private_thresholds, vec![]).build();
+        assert!(analysis.properties(ecdf).is_err());
+    }
+}
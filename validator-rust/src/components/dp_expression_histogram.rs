@@ -0,0 +1,289 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, IndexKey, Jagged, NodeProperties, Value, ValueProperties};
+use crate::components::{Accuracy, Component, Expandable, Report};
+use crate::errors::*;
+use crate::utilities::{get_array_property, get_literal, prepend};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::JSONRelease;
+
+impl Component for proto::DpExpressionHistogram {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let Warnable(expression_property, mut warnings) = self.propagate_expression(
+            privacy_definition, &properties, node_id)?;
+        let edges = self.get_edges(&public_arguments, &expression_property)?;
+
+        let (public_arguments, properties) = binned_arguments(
+            &public_arguments, &properties, &edges, expression_property);
+        let Warnable(histogram_property, histogram_warnings) = self.binned_histogram()
+            .propagate_property(privacy_definition, public_arguments, properties, node_id)?;
+        warnings.extend(histogram_warnings);
+
+        Ok(Warnable(histogram_property, warnings))
+    }
+}
+
+impl Expandable for proto::DpExpressionHistogram {
+    /// Expand into the components of the expression, followed by a binned histogram of the output of the expression.
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+        let argument_ids = component.arguments();
+
+        let mut id_expression = *argument_ids.get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+
+        // each component of the expression is applied to the output of the previous component
+        for expression_component in &self.expression {
+            maximum_id += 1;
+            expansion.computation_graph.insert(maximum_id, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_expression])),
+                variant: expression_component.variant.clone(),
+                omit: true,
+                submission: component.submission,
+            });
+            expansion.traversal.push(maximum_id);
+            id_expression = maximum_id;
+        }
+
+        // edges are derived from the bounds of the output of the expression, if not supplied
+        let id_edges = match argument_ids.get::<IndexKey>(&"edges".into()) {
+            Some(id_edges) => *id_edges,
+            None => {
+                let Warnable(expression_property, _) = self.propagate_expression(
+                    privacy_definition, properties, component_id)?;
+                let edges = self.get_edges(public_arguments, &expression_property)?;
+
+                maximum_id += 1;
+                let id_edges = maximum_id;
+                let (patch_node, release) = get_literal(edges, component.submission)?;
+                expansion.computation_graph.insert(id_edges, patch_node);
+                expansion.properties.insert(id_edges, infer_property(&release.value, None, id_edges)?);
+                expansion.releases.insert(id_edges, release);
+                id_edges
+            }
+        };
+
+        let mut histogram_arguments = indexmap![
+            "data".into() => id_expression,
+            "edges".into() => id_edges
+        ];
+        if let Some(id_inclusive_left) = argument_ids.get::<IndexKey>(&"inclusive_left".into()) {
+            histogram_arguments.insert("inclusive_left".into(), *id_inclusive_left);
+        }
+
+        // binned histogram of the output of the expression
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(histogram_arguments)),
+            variant: Some(proto::component::Variant::DpBinnedHistogram(self.binned_histogram())),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Accuracy for proto::DpExpressionHistogram {
+    fn accuracy_to_privacy_usage(
+        &self,
+        accuracies: &proto::Accuracies,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        self.binned_histogram().accuracy_to_privacy_usage(accuracies, public_arguments)
+    }
+
+    fn privacy_usage_to_accuracy(
+        &self,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        self.binned_histogram().privacy_usage_to_accuracy(public_arguments, alpha)
+    }
+}
+
+impl Report for proto::DpExpressionHistogram {
+    fn summarize(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let Warnable(expression_property, _) = self.propagate_expression(
+            privacy_definition, &properties, node_id)?;
+        let edges = self.get_edges(&public_arguments, &expression_property)?;
+
+        let (public_arguments, properties) = binned_arguments(
+            &public_arguments, &properties, &edges, expression_property);
+        let mut releases = self.binned_histogram().summarize(
+            privacy_definition, node_id, component, public_arguments, properties, release, variable_names)?;
+
+        releases.iter_mut().flatten().for_each(|release| {
+            release.statistic = "DPExpressionHistogram".to_string();
+            release.algorithm_info.argument["expression"] = serde_json::json!(self.expression.iter()
+                .map(|component| component.variant.as_ref()
+                    .map(|variant| format!("{:?}", variant))
+                    .unwrap_or_default())
+                .collect::<Vec<String>>());
+        });
+        Ok(releases)
+    }
+}
+
+impl proto::DpExpressionHistogram {
+    /// The binned histogram that counts the output of the expression.
+    fn binned_histogram(&self) -> proto::DpBinnedHistogram {
+        proto::DpBinnedHistogram {
+            mechanism: self.mechanism.clone(),
+            privacy_usage: self.privacy_usage.clone(),
+        }
+    }
+
+    /// Propagate the properties of the data through each component of the expression.
+    fn propagate_expression(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        properties: &NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ArrayProperties>> {
+        if self.expression.is_empty() {
+            return Err("expression: must contain at least one component".into())
+        }
+
+        let mut expression_property = ValueProperties::Array(get_array_property(properties, "data")?);
+        let mut warnings = Vec::new();
+
+        for (position, component) in self.expression.iter().enumerate() {
+            if !component.arguments().is_empty() {
+                return Err(format!("expression: component {} may not take arguments, as it is applied to the output of the previous component", position).into())
+            }
+            let Warnable(property, component_warnings) = component.propagate_property(
+                privacy_definition, IndexMap::new(), indexmap!["data".into() => expression_property], node_id)
+                .chain_err(|| format!("expression: component {}", position))?;
+            expression_property = property;
+            warnings.extend(component_warnings);
+        }
+
+        Ok(Warnable(expression_property.array()
+            .map_err(prepend("expression:"))?.clone(), warnings))
+    }
+
+    /// Edges of the bins, either supplied publicly or spanning the bounds of the output of the expression.
+    fn get_edges(
+        &self,
+        public_arguments: &IndexMap<IndexKey, &Value>,
+        expression_property: &ArrayProperties,
+    ) -> Result<Value> {
+        if let Some(edges) = public_arguments.get::<IndexKey>(&"edges".into()) {
+            return Ok((*edges).clone())
+        }
+        if self.num_bins == 0 {
+            return Err("num_bins: must be positive".into())
+        }
+
+        let lower = expression_property.lower_float()
+            .map_err(prepend("edges: must be supplied when the output of the expression is not bounded:"))?;
+        let upper = expression_property.upper_float()
+            .map_err(prepend("edges: must be supplied when the output of the expression is not bounded:"))?;
+
+        let width = (upper[0] - lower[0]) / self.num_bins as f64;
+        Ok(Value::Jagged(Jagged::Float(vec![(0..=self.num_bins)
+            .map(|index| lower[0] + index as f64 * width)
+            .collect()])))
+    }
+}
+
+/// Arguments of the binned histogram, over the output of the expression.
+fn binned_arguments<'a>(
+    public_arguments: &IndexMap<IndexKey, &'a Value>,
+    properties: &NodeProperties,
+    edges: &'a Value,
+    expression_property: ArrayProperties,
+) -> (IndexMap<IndexKey, &'a Value>, NodeProperties) {
+    let mut public_arguments = public_arguments.clone();
+    public_arguments.insert("edges".into(), edges);
+    let mut properties = properties.clone();
+    properties.insert("data".into(), ValueProperties::Array(expression_property));
+    (public_arguments, properties)
+}
+
+#[cfg(test)]
+mod test_dp_expression_histogram {
+    use crate::base::{IndexKey, test_data};
+    use crate::components::literal::test_literal;
+    use crate::components::resize::test_resize;
+    use crate::proto;
+    use crate::utilities::privacy::get_epsilon;
+
+    #[test]
+    fn test_log_histogram() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        let source = *analysis.components.keys().min().unwrap();
+        test_literal::privatize(&mut analysis, source);
+        analysis.privacy_definition.protect_floating_point = false;
+
+        // the log is only bounded over positive data
+        let lower = analysis.literal().value(1.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(resized).lower(lower).upper(upper).build();
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let log = proto::Component {
+            arguments: None,
+            variant: Some(proto::component::Variant::Log(proto::Log {})),
+            omit: true,
+            submission: 0,
+        };
+        let histogram = analysis.dp_expression_histogram(clamped, vec![log], vec![usage])
+            .num_bins(4).build();
+
+        // edges over [0, ln 10] are derived from the bounds of the log, with a final bin for records outside of the edges
+        let properties = analysis.properties(histogram).unwrap();
+        let counts = properties.dataframe().unwrap().children[&IndexKey::from("count")].array().unwrap().clone();
+        assert_eq!(counts.num_records, Some(5));
+        assert!(counts.releasable);
+
+        // the expression expands before the histogram, and the budget is only spent by the histogram
+        let mut computation_graph = analysis.components.clone();
+        let mut release = analysis.release.clone();
+        crate::utilities::propagate_properties(
+            &Some(analysis.privacy_definition.clone()),
+            &mut computation_graph, &mut release, None, false).unwrap();
+        let (id_log, _) = computation_graph.iter()
+            .find(|(_, component)| matches!(component.variant, Some(proto::component::Variant::Log(_))))
+            .unwrap();
+        let (_, digitize) = computation_graph.iter()
+            .find(|(_, component)| matches!(component.variant, Some(proto::component::Variant::Digitize(_))))
+            .unwrap();
+        assert_eq!(digitize.arguments()[&IndexKey::from("data")], *id_log);
+
+        let edges = digitize.arguments()[&IndexKey::from("edges")];
+        let edges = release[&edges].value.ref_jagged().unwrap().clone();
+        assert_eq!(edges.num_records(), vec![5]);
+
+        let privacy_usage = crate::compute_privacy_usage(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap();
+        assert!((get_epsilon(&privacy_usage).unwrap() - 1.).abs() < 1e-10);
+    }
+}
@@ -0,0 +1,305 @@
+use indexmap::map::IndexMap;
+use ndarray::arr0;
+
+use crate::{base, proto, Warnable, Float};
+use crate::base::{Array, ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Report};
+use crate::components::dp_mean::dp_mean_accuracy;
+use crate::errors::*;
+use crate::utilities::{array::get_ith_column, get_literal, prepend, privacy::{get_epsilon, spread_privacy_usage}};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{Accuracy, AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json, REPORT_ALPHA};
+
+impl Component for proto::DpGeometricMean {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        if !data_property.releasable {
+            data_property.assert_is_not_aggregated()?;
+        }
+
+        let (lower, upper) = get_positive_bounds(&data_property)?;
+        let num_columns = data_property.num_columns()?;
+
+        // the geometric mean is bounded by the minimum and maximum of the data
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_columns: Some(num_columns),
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(lower.into_iter().map(Some).collect()),
+                upper: Vector1DNull::Float(upper.into_iter().map(Some).collect()),
+            })),
+            ..ArrayProperties::dp_release(node_id, DataType::Float, data_property.group_id)
+        }).into())
+    }
+}
+
+impl Expandable for proto::DpGeometricMean {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+        let (lower, upper) = get_positive_bounds(&data_property)?;
+        let num_columns = data_property.num_columns()?;
+
+        let id_data = *component.arguments().get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+
+        // euler's number, shaped to broadcast against each column of the mean
+        let euler = || Value::Array(Array::Float(if num_columns == 1 {
+            arr0(std::f64::consts::E as Float).into_dyn()
+        } else {
+            ndarray::Array::from_elem((1, num_columns as usize), std::f64::consts::E as Float).into_dyn()
+        }));
+
+        // log base
+        maximum_id += 1;
+        let id_base = maximum_id;
+        let value = euler();
+        expansion.properties.insert(id_base, infer_property(&value, None, id_base)?);
+        let (patch_node, release) = get_literal(value, component.submission)?;
+        expansion.computation_graph.insert(id_base, patch_node);
+        expansion.releases.insert(id_base, release);
+
+        // log
+        maximum_id += 1;
+        let id_log = maximum_id;
+        expansion.computation_graph.insert(id_log, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_data,
+                "base".into() => id_base
+            ])),
+            variant: Some(proto::component::Variant::Log(proto::Log {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_log);
+
+        // dp mean of the logged data
+        maximum_id += 1;
+        let id_dp_mean = maximum_id;
+        expansion.computation_graph.insert(id_dp_mean, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_log
+            ])),
+            variant: Some(proto::component::Variant::DpMean(proto::DpMean {
+                implementation: self.implementation.clone(),
+                centered: false,
+                mechanism: self.mechanism.clone(),
+                privacy_usage: self.privacy_usage.clone(),
+            })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_dp_mean);
+
+        // exponential base
+        maximum_id += 1;
+        let id_exp_base = maximum_id;
+        let value = euler();
+        expansion.properties.insert(id_exp_base, infer_property(&value, None, id_exp_base)?);
+        let (patch_node, release) = get_literal(value, component.submission)?;
+        expansion.computation_graph.insert(id_exp_base, patch_node);
+        expansion.releases.insert(id_exp_base, release);
+
+        // exp
+        maximum_id += 1;
+        let id_power = maximum_id;
+        expansion.computation_graph.insert(id_power, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_exp_base,
+                "radical".into() => id_dp_mean
+            ])),
+            variant: Some(proto::component::Variant::Power(proto::Power {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_power);
+
+        // the noisy mean may leave the bounds of the data, so the geometric mean is clamped into [lower, upper]
+        let mut bound_ids = Vec::new();
+        for bound in [lower, upper] {
+            maximum_id += 1;
+            let id_bound = maximum_id;
+            let value = Value::Array(Array::Float(ndarray::Array::from(bound).into_dyn()));
+            expansion.properties.insert(id_bound, infer_property(&value, None, id_bound)?);
+            let (patch_node, release) = get_literal(value, component.submission)?;
+            expansion.computation_graph.insert(id_bound, patch_node);
+            expansion.releases.insert(id_bound, release);
+            bound_ids.push(id_bound);
+        }
+
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_power,
+                "lower".into() => bound_ids[0],
+                "upper".into() => bound_ids[1]
+            ])),
+            variant: Some(proto::component::Variant::Clamp(proto::Clamp { null_policy: "Pass".to_string() })),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpGeometricMean {
+    fn summarize(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = properties.get::<base::IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        let mut releases = Vec::new();
+
+        let (lower, upper) = get_positive_bounds(&data_property)?;
+        let num_records = data_property.num_records().ok();
+
+        let num_columns = data_property.num_columns()?;
+        let privacy_usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
+
+        let release = release.ref_array()?.ref_float()?;
+
+        for column_number in 0..(num_columns as usize) {
+            let variable_name = variable_names
+                .and_then(|names| names.get(column_number)).cloned()
+                .unwrap_or_else(|| "[Unknown]".into());
+
+            // the accuracy also depends on the number of records
+            let accuracy = match num_records {
+                Some(num_records) if Accuracy::is_known_for(&self.mechanism) => Some(Accuracy {
+                    accuracy_value: geometric_mean_accuracy(
+                        lower[column_number], upper[column_number], num_records,
+                        get_epsilon(&privacy_usages[column_number])?, REPORT_ALPHA),
+                    alpha: REPORT_ALPHA,
+                    data_dependent: false,
+                    simultaneous: false,
+                }),
+                _ => None
+            };
+
+            releases.push(JSONRelease {
+                description: "DP release information".to_string(),
+                statistic: "DPGeometricMean".to_string(),
+                variables: serde_json::json!(variable_name.to_string()),
+                release_info: value_to_json(&get_ith_column(release, column_number)?.into())?,
+                privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
+                accuracy,
+                relative_accuracy: None,
+                submission: component.submission,
+                node_id,
+                postprocess: false,
+                algorithm_info: AlgorithmInfo {
+                    name: "".to_string(),
+                    cite: "".to_string(),
+                    mechanism: self.mechanism.clone(),
+                    argument: serde_json::json!({
+                        "implementation": self.implementation.clone(),
+                        "n": num_records,
+                        "constraint": {
+                            "lowerbound": lower[column_number],
+                            "upperbound": upper[column_number]
+                        }
+                    }),
+                },
+            });
+        }
+        Ok(Some(releases))
+    }
+}
+
+/// Bounds of the data, which must be known and strictly positive for the log transform.
+fn get_positive_bounds(data_property: &ArrayProperties) -> Result<(Vec<Float>, Vec<Float>)> {
+    let lower = data_property.lower_float()
+        .map_err(prepend("data: geometric mean requires clamped data:"))?;
+    let upper = data_property.upper_float()
+        .map_err(prepend("data: geometric mean requires clamped data:"))?;
+
+    if !lower.iter().all(|v| *v > 0.) {
+        return Err("data: lower bound must be strictly positive".into())
+    }
+    Ok((lower, upper))
+}
+
+/// Accuracy of a geometric mean of data in `[lower, upper]`, privatized with the laplace mechanism.
+///
+/// The mean of the logged data is within `a` of its true value with confidence `1 - alpha`.
+/// Exponentiating scales the interval multiplicatively, so the geometric mean is within
+/// `upper * (e^a - 1)` of its true value, with the same confidence.
+pub fn geometric_mean_accuracy(lower: Float, upper: Float, num_records: i64, epsilon: Float, alpha: Float) -> Float {
+    let log_accuracy = dp_mean_accuracy(lower.ln(), upper.ln(), num_records, epsilon, alpha);
+    upper * log_accuracy.exp_m1()
+}
+
+#[cfg(test)]
+mod test_dp_geometric_mean {
+    use crate::base::{IndexKey, test_data};
+    use crate::components::resize::test_resize;
+    use crate::proto;
+
+    #[test]
+    fn test_dp_geometric_mean() {
+        // clamp and resize within strictly positive bounds
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(1.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let geometric_mean = analysis.dp_geometric_mean(resized, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let expansion = crate::expand_component(
+            analysis.components.get(&geometric_mean).unwrap().clone(),
+            indexmap!["data".into() => analysis.properties(resized).unwrap()],
+            indexmap![],
+            Some(analysis.privacy_definition.clone()),
+            geometric_mean, 1000).unwrap();
+
+        assert_eq!(expansion.computation_graph.values()
+            .filter(|component| matches!(component.variant.as_ref(), Some(proto::component::Variant::Log(_))))
+            .count(), 1);
+        assert_eq!(expansion.computation_graph.values()
+            .filter(|component| matches!(component.variant.as_ref(), Some(proto::component::Variant::DpMean(_))))
+            .count(), 1);
+        assert_eq!(expansion.computation_graph.values()
+            .filter(|component| matches!(component.variant.as_ref(), Some(proto::component::Variant::Power(_))))
+            .count(), 1);
+
+        // the released geometric mean is clamped into the bounds of the data
+        let clamp_arguments = expansion.computation_graph.get(&geometric_mean).unwrap().arguments();
+        let bound = |name: &str| expansion.releases.get(clamp_arguments.get::<IndexKey>(&name.into()).unwrap())
+            .unwrap().value.ref_array().unwrap().ref_float().unwrap().iter().cloned().collect::<Vec<f64>>();
+        assert_eq!(bound("lower"), vec![1.]);
+        assert_eq!(bound("upper"), vec![10.]);
+
+        let geometric_mean_property = analysis.properties(geometric_mean).unwrap().array().unwrap().clone();
+        assert!(geometric_mean_property.lower_float().unwrap().iter().all(|lower| *lower > 0.));
+    }
+}
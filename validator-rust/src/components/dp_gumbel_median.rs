@@ -32,21 +32,8 @@ impl Component for proto::DpGumbelMedian {
         }
 
         Ok(ValueProperties::Array(ArrayProperties {
-            num_records: Some(1),
-            num_columns: Some(1),
-            nullity: false,
-            releasable: true,
-            c_stability: 1,
-            aggregator: None,
-            nature: None,
-            data_type: data_property.data_type.clone(),
-            dataset_id: None,
-            node_id: node_id as i64,
-            is_not_empty: true,
             dimensionality: Some(0),
-            group_id: data_property.group_id,
-            naturally_ordered: true,
-            sample_proportion: None,
+            ..ArrayProperties::dp_release(node_id, data_property.data_type.clone(), data_property.group_id)
         }).into())
     }
 }
@@ -92,6 +79,7 @@ impl Expandable for proto::DpGumbelMedian {
 impl Report for proto::DpGumbelMedian {
     fn summarize(
         &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
         node_id: u32,
         component: &proto::Component,
         _public_arguments: IndexMap<base::IndexKey, &Value>,
@@ -126,6 +114,7 @@ impl Report for proto::DpGumbelMedian {
                 },
                 privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
                 accuracy: None,
+                relative_accuracy: None,
                 submission: component.submission,
                 node_id,
                 postprocess: false,
@@ -1,20 +1,22 @@
 use indexmap::map::IndexMap;
-use ndarray::arr0;
+use ndarray::{arr0, arr1};
 
-use crate::{base, Integer, proto};
-use crate::base::{IndexKey, NodeProperties, Value};
+use crate::{base, Float, Integer, proto};
+use crate::base::{Array, ArrayProperties, DataType, IndexKey, Jagged, NodeProperties, Value};
 use crate::components::{Expandable, Report};
+use crate::components::clamp::categories_with_null;
+use crate::components::simple_geometric_mechanism::geometric_accuracy;
 use crate::errors::*;
-use crate::utilities::{array::get_ith_column, get_literal, prepend, privacy::spread_privacy_usage};
+use crate::utilities::{array::get_ith_column, get_literal, prepend, require_public_argument, privacy::{get_epsilon, spread_privacy_usage}};
 use crate::utilities::inference::infer_property;
-use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+use crate::utilities::json::{Accuracy, AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json, REPORT_ALPHA};
 
 impl Expandable for proto::DpHistogram {
     fn expand_component(
         &self,
         privacy_definition: &Option<proto::PrivacyDefinition>,
         component: &proto::Component,
-        _public_arguments: &IndexMap<IndexKey, &Value>,
+        public_arguments: &IndexMap<IndexKey, &Value>,
         properties: &base::NodeProperties,
         component_id: u32,
         mut maximum_id: u32,
@@ -30,19 +32,89 @@ impl Expandable for proto::DpHistogram {
             .ok_or("data: missing")?.array()
             .map_err(prepend("data:"))?;
 
+        // the stability mechanism counts the observed categories, so the category set need not be known
+        if self.mechanism.to_lowercase() == "stability" {
+            if argument_ids.contains_key::<IndexKey>(&"categories".into()) || argument_ids.contains_key::<IndexKey>(&"edges".into()) {
+                return Err("the stability mechanism releases the observed categories, so categories and edges may not be supplied".into())
+            }
+            if self.quantile_bins > 0 {
+                return Err("the stability mechanism releases the observed categories, so quantile_bins may not be set".into())
+            }
+            if self.smoothing_window > 0 {
+                return Err("the stability mechanism releases the observed categories, which are unordered, so smoothing_window may not be set".into())
+            }
+            let stability = proto::StabilityMechanism { privacy_usage: self.privacy_usage.clone() };
+            let stability_component = proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => data_id])),
+                variant: Some(proto::component::Variant::StabilityMechanism(stability.clone())),
+                omit: component.omit,
+                submission: component.submission,
+            };
+            return stability.expand_component(
+                privacy_definition, &stability_component, public_arguments, properties, component_id, maximum_id)
+        }
+
         let privacy_definition = privacy_definition.as_ref()
             .ok_or_else(|| Error::from("privacy_definition must be known"))?;
 
+        // equal-count bins spend half of the budget on the edges, and count the bin index of each record with the rest
+        let quantile_bins = if self.quantile_bins > 0 {
+            Some(expand_quantile_bins(
+                self, component, public_arguments, data_property, &mut expansion, &mut maximum_id)?)
+        } else { None };
+        let privacy_usage = match quantile_bins {
+            Some(_) => self.privacy_usage.iter().cloned()
+                .map(|usage| usage / 2.)
+                .collect::<Result<Vec<proto::PrivacyUsage>>>()?,
+            None => self.privacy_usage.clone()
+        };
+
+        // when labeled, the noisy counts are paired with their labels in a final node
+        let labeled = self.labeled || quantile_bins.is_some();
+        let labels = if self.labeled && quantile_bins.is_none() {
+            Some(get_histogram_labels(public_arguments, data_property)?)
+        } else { None };
+
+        // when smoothed, the noisy counts are averaged over adjacent bins before labeling
+        let smoothing_exclusions = if self.smoothing_window > 0 {
+            Some(get_smoothing_exclusions(public_arguments, data_property, quantile_bins.is_some())?)
+        } else { None };
+        let smooth = |id_counts: u32, num_excluded: u32, omit: bool| proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_counts])),
+            variant: Some(proto::component::Variant::MovingAverage(proto::MovingAverage {
+                window: self.smoothing_window,
+                num_excluded
+            })),
+            omit,
+            submission: component.submission,
+        };
+
+        let id_noised = if labeled || smoothing_exclusions.is_some() {
+            maximum_id += 1;
+            maximum_id
+        } else { component_id };
+        let omit_noised = labeled || smoothing_exclusions.is_some() || component.omit;
+
         // histogram
         maximum_id += 1;
         let id_histogram = maximum_id;
-        let mut histogram_arguments = indexmap!["data".into() => data_id];
-        vec!["categories", "null_value", "edges", "inclusive_left"].into_iter()
-            .map(|name| name.into())
-            .for_each(|name| {
-                argument_ids.get(&name)
-                    .map(|v| histogram_arguments.insert(name, *v));
-            });
+        let histogram_arguments = match &quantile_bins {
+            Some(bins) => indexmap![
+                "data".into() => bins.id_index,
+                "categories".into() => bins.id_categories,
+                "null_value".into() => bins.id_null_value
+            ],
+            None => {
+                let mut histogram_arguments = indexmap!["data".into() => data_id];
+                vec!["categories", "null_value", "edges", "inclusive_left"].into_iter()
+                    .map(|name| name.into())
+                    .for_each(|name| {
+                        argument_ids.get(&name)
+                            .map(|v| histogram_arguments.insert(name, *v));
+                    });
+                histogram_arguments
+            }
+        };
 
         expansion.computation_graph.insert(id_histogram, proto::Component {
             arguments: Some(proto::ArgumentNodeIds::new(histogram_arguments)),
@@ -89,16 +161,16 @@ impl Expandable for proto::DpHistogram {
             };
 
             // noising
-            expansion.computation_graph.insert(component_id, proto::Component {
+            expansion.computation_graph.insert(id_noised, proto::Component {
                 arguments: Some(proto::ArgumentNodeIds::new(indexmap![
                     "data".into() => id_histogram,
                     "lower".into() => count_min_id,
                     "upper".into() => count_max_id
                 ])),
                 variant: Some(proto::component::Variant::SimpleGeometricMechanism(proto::SimpleGeometricMechanism {
-                    privacy_usage: self.privacy_usage.clone()
+                    privacy_usage: privacy_usage.clone()
                 })),
-                omit: component.omit,
+                omit: omit_noised,
                 submission: component.submission,
             });
         } else {
@@ -107,15 +179,18 @@ impl Expandable for proto::DpHistogram {
             let mut arguments = indexmap!["data".into() => id_histogram];
             let variant = Some(match self.mechanism.to_lowercase().as_str() {
                 "laplace" => proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
-                    privacy_usage: self.privacy_usage.clone()
+                    privacy_usage: privacy_usage.clone(),
+                    max_noise_sigmas: 0.
                 }),
                 "gaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
-                    privacy_usage: self.privacy_usage.clone(),
-                    analytic: false
+                    privacy_usage: privacy_usage.clone(),
+                    analytic: false,
+                    max_noise_sigmas: 0.
                 }),
                 "analyticgaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
-                    privacy_usage: self.privacy_usage.clone(),
-                    analytic: true
+                    privacy_usage: privacy_usage.clone(),
+                    analytic: true,
+                    max_noise_sigmas: 0.
                 }),
                 "snapping" => {
                     argument_ids.get::<IndexKey>(&"lower".into())
@@ -124,17 +199,83 @@ impl Expandable for proto::DpHistogram {
                         .map(|upper| arguments.insert("upper".into(), *upper));
 
                     proto::component::Variant::SnappingMechanism(proto::SnappingMechanism {
-                        privacy_usage: self.privacy_usage.clone()
+                        privacy_usage: privacy_usage.clone()
                     })
                 },
                 _ => bail!("Unexpected invalid token {:?}", self.mechanism.as_str()),
             });
-            expansion.computation_graph.insert(component_id, proto::Component {
+            expansion.computation_graph.insert(id_noised, proto::Component {
                 arguments: Some(proto::ArgumentNodeIds::new(arguments)),
                 variant,
+                omit: omit_noised,
+                submission: component.submission,
+            });
+        }
+
+        if labeled {
+            expansion.traversal.push(id_noised);
+
+            let (id_counts, id_labels) = match (quantile_bins, labels) {
+                (Some(bins), _) => {
+                    // every record falls in a bin, so the count of the null category is dropped
+                    maximum_id += 1;
+                    let id_rows = maximum_id;
+                    let (patch_node, release) = get_literal(
+                        arr1(&(0..bins.num_bins as Integer).collect::<Vec<_>>()).into_dyn().into(), component.submission)?;
+                    expansion.computation_graph.insert(id_rows, patch_node);
+                    expansion.properties.insert(id_rows, infer_property(&release.value, None, id_rows)?);
+                    expansion.releases.insert(id_rows, release);
+
+                    maximum_id += 1;
+                    let id_counts = maximum_id;
+                    expansion.computation_graph.insert(id_counts, proto::Component {
+                        arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                            "data".into() => id_noised,
+                            "rows".into() => id_rows
+                        ])),
+                        variant: Some(proto::component::Variant::Index(proto::Index {})),
+                        omit: true,
+                        submission: component.submission,
+                    });
+                    expansion.traversal.push(id_counts);
+                    (id_counts, bins.id_edges)
+                }
+                (None, Some(labels)) => {
+                    maximum_id += 1;
+                    let id_labels = maximum_id;
+                    let (patch_node, release) = get_literal(labels, component.submission)?;
+                    expansion.computation_graph.insert(id_labels, patch_node);
+                    expansion.properties.insert(id_labels, infer_property(&release.value, None, id_labels)?);
+                    expansion.releases.insert(id_labels, release);
+                    (id_noised, id_labels)
+                }
+                (None, None) => return Err("labels must be known for a labeled histogram".into())
+            };
+
+            let id_counts = match smoothing_exclusions {
+                Some(num_excluded) => {
+                    maximum_id += 1;
+                    let id_smoothed = maximum_id;
+                    expansion.computation_graph.insert(id_smoothed, smooth(id_counts, num_excluded, true));
+                    expansion.traversal.push(id_smoothed);
+                    id_smoothed
+                }
+                None => id_counts
+            };
+
+            // pair each noisy count with its label
+            expansion.computation_graph.insert(component_id, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                    "data".into() => id_counts,
+                    "categories".into() => id_labels
+                ])),
+                variant: Some(proto::component::Variant::LabelCounts(proto::LabelCounts {})),
                 omit: component.omit,
                 submission: component.submission,
             });
+        } else if let Some(num_excluded) = smoothing_exclusions {
+            expansion.traversal.push(id_noised);
+            expansion.computation_graph.insert(component_id, smooth(id_noised, num_excluded, component.omit));
         }
 
         Ok(expansion)
@@ -144,39 +285,107 @@ impl Expandable for proto::DpHistogram {
 impl Report for proto::DpHistogram {
     fn summarize(
         &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
         node_id: u32,
         component: &proto::Component,
-        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: NodeProperties,
         release: &Value,
         variable_names: Option<&Vec<base::IndexKey>>,
     ) -> Result<Option<Vec<JSONRelease>>> {
+        if self.mechanism.to_lowercase() == "stability" {
+            let mut releases = proto::StabilityMechanism { privacy_usage: self.privacy_usage.clone() }
+                .summarize(privacy_definition, node_id, component, public_arguments, properties, release, variable_names)?;
+            releases.iter_mut().flatten()
+                .for_each(|release| release.statistic = "DPHistogram".to_string());
+            return Ok(releases)
+        }
+
         let data_property = properties.get::<base::IndexKey>(&"data".into())
             .ok_or("data: missing")?.array()
             .map_err(prepend("data:"))?.clone();
 
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| Error::from("privacy_definition must be known"))?;
+
         let num_columns = data_property.num_columns()?;
         let privacy_usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
 
         let variable_names = variable_names.cloned()
             .unwrap_or_else(|| (0..num_columns).map(|_| "[Unknown]".into()).collect());
 
-        let release = release.ref_array()?.ref_int()?;
+        // the edges of quantile bins are a release, and are reported alongside the counts
+        let edges = match (self.quantile_bins > 0, release) {
+            (true, Value::Dataframe(release)) => Some(release.get::<IndexKey>(&"category".into())
+                .ok_or_else(|| Error::from("release: quantile bins must contain edges"))?
+                .as_vec_f64().map_err(prepend("release:"))?),
+            _ => None
+        };
+
+        // a labeled release pairs the counts with their categories
+        let release = match release {
+            Value::Dataframe(release) => release.get::<IndexKey>(&"count".into())
+                .ok_or_else(|| Error::from("release: labeled histogram must contain counts"))?,
+            release => release
+        }.ref_array()?;
+
+        let mut argument = serde_json::json!({});
+        if let Some(edges) = &edges {
+            argument["edges"] = serde_json::json!(edges);
+        }
+        if self.smoothing_window > 0 {
+            argument["smoothing_window"] = serde_json::json!(self.smoothing_window);
+        }
 
         Ok(Some(privacy_usages.into_iter()
             .zip(variable_names.into_iter()).enumerate()
-            .map(|(column_number, (privacy_usage, variable_name))|
+            .map(|(column_number, (privacy_usage, variable_name))| {
+                // extract ith column of release, which is averaged into floats when smoothed
+                let (counts, num_bins): (Value, usize) = match release {
+                    Array::Int(release) => {
+                        let counts = get_ith_column(release, column_number)?;
+                        let num_bins = counts.len();
+                        (counts.into(), num_bins)
+                    }
+                    Array::Float(release) => {
+                        let counts = get_ith_column(release, column_number)?;
+                        let num_bins = counts.len();
+                        (counts.into(), num_bins)
+                    }
+                    _ => return Err("release: counts must be numeric".into())
+                };
+
+                // half of the budget of quantile bins is spent on the edges
+                let count_usage = match edges {
+                    Some(_) => (privacy_usage.clone() / 2.)?,
+                    None => privacy_usage.clone()
+                };
+                let effective_usage = count_usage.actual_to_effective(
+                    data_property.sample_proportion.unwrap_or(1.),
+                    data_property.c_stability,
+                    privacy_definition.group_size)?;
+                // the bias of smoothed counts depends on the data, so only the accuracy of unsmoothed counts is known
+                let accuracy_value = match self.smoothing_window {
+                    0 => histogram_accuracy(
+                        &self.mechanism, privacy_definition, &effective_usage,
+                        num_bins, REPORT_ALPHA, self.simultaneous_accuracy)?,
+                    _ => None
+                };
+                let accuracy = accuracy_value.map(|accuracy_value| Accuracy {
+                    accuracy_value,
+                    alpha: REPORT_ALPHA,
+                    data_dependent: false,
+                    simultaneous: self.simultaneous_accuracy,
+                });
+
                 Ok(JSONRelease {
                     description: "DP release information".to_string(),
                     statistic: "DPHistogram".to_string(),
                     variables: serde_json::json!(variable_name.to_string()),
-                    // extract ith column of release
-                    release_info: value_to_json(&get_ith_column(
-                        release,
-                        column_number,
-                    )?.into())?,
+                    release_info: value_to_json(&counts)?,
                     privacy_loss: privacy_usage_to_json(&privacy_usage),
-                    accuracy: None,
+                    accuracy,
+                    relative_accuracy: None,
                     submission: component.submission,
                     node_id,
                     postprocess: false,
@@ -184,9 +393,367 @@ impl Report for proto::DpHistogram {
                         name: "".to_string(),
                         cite: "".to_string(),
                         mechanism: self.mechanism.clone(),
-                        argument: serde_json::json!({}),
+                        argument: argument.clone(),
                     },
-                }))
+                })
+            })
             .collect::<Result<Vec<JSONRelease>>>()?))
     }
 }
+
+/// Accuracy of the counts of a histogram column with the given number of bins, at level alpha.
+///
+/// Each bin is noised with the full usage, at the scale of the l1 sensitivity of the histogram.
+/// When simultaneous, the Bonferroni correction splits alpha evenly over the bins,
+/// so that every bin is within the accuracy with probability at least 1 - alpha.
+/// Accuracy is only known in closed form for the laplace and simple geometric mechanisms.
+pub fn histogram_accuracy(
+    mechanism: &str,
+    privacy_definition: &proto::PrivacyDefinition,
+    privacy_usage: &proto::PrivacyUsage,
+    num_bins: usize,
+    alpha: Float,
+    simultaneous: bool,
+) -> Result<Option<Float>> {
+    use proto::privacy_definition::Neighboring;
+
+    // the count of a single bin may not be noised at all when the number of records is known
+    if num_bins < 2 {
+        return Ok(None)
+    }
+
+    // a substituted record may move from one bin to another
+    let l1_sensitivity = match Neighboring::from_i32(privacy_definition.neighboring)
+        .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))? {
+        Neighboring::AddRemove => 1.,
+        Neighboring::Substitute => 2.
+    };
+    let scale = l1_sensitivity / get_epsilon(privacy_usage)?;
+
+    let alpha = if simultaneous { alpha / num_bins as Float } else { alpha };
+
+    Ok(match mechanism.to_lowercase().as_str() {
+        "laplace" => Some(scale * (1. / alpha).ln()),
+        "simplegeometric" => Some(geometric_accuracy(scale, alpha)),
+        _ => None
+    })
+}
+
+/// Nodes added to the expansion to count records into equal-count bins.
+struct QuantileBins {
+    num_bins: usize,
+    /// bin index of each record
+    id_index: u32,
+    id_categories: u32,
+    id_null_value: u32,
+    /// lower edge of each bin, of which all but the lower bound of the data are released by a mechanism
+    id_edges: u32,
+}
+
+/// Estimate the interior edges of `quantile_bins` equal-count bins with DP quantiles,
+/// and compute the index of the bin each record falls in.
+///
+/// Each of the k - 1 interior edges is estimated by the exponential mechanism with an even share of half of the budget.
+/// The bin index is the number of edges at or below the record (above, when `inclusive_left` is false),
+/// so the index is always in `[0, k - 1]` regardless of the order of the estimated edges.
+fn expand_quantile_bins(
+    dp_histogram: &proto::DpHistogram,
+    component: &proto::Component,
+    public_arguments: &IndexMap<IndexKey, &Value>,
+    data_property: &ArrayProperties,
+    expansion: &mut base::ComponentExpansion,
+    maximum_id: &mut u32,
+) -> Result<QuantileBins> {
+    let num_bins = dp_histogram.quantile_bins as usize;
+    if num_bins < 2 {
+        return Err("quantile_bins: must be at least two".into())
+    }
+
+    let argument_ids = component.arguments();
+    if argument_ids.contains_key::<IndexKey>(&"categories".into()) || argument_ids.contains_key::<IndexKey>(&"edges".into()) {
+        return Err("the edges of quantile bins are estimated from the data, so categories and edges may not be supplied".into())
+    }
+    let data_id = *argument_ids.get::<IndexKey>(&"data".into())
+        .ok_or_else(|| Error::from("data is a required argument to DPHistogram"))?;
+    let candidates_id = *argument_ids.get::<IndexKey>(&"candidates".into())
+        .ok_or_else(|| Error::from("candidates is a required argument to DPHistogram when quantile_bins is set"))?;
+
+    if data_property.num_columns()? != 1 {
+        return Err("data: quantile bins may only be estimated over one column".into())
+    }
+    if data_property.data_type != DataType::Float {
+        return Err("data: quantile bins may only be estimated over float data".into())
+    }
+    if data_property.nullity {
+        return Err("data: may not contain nullity when estimating quantile bins".into())
+    }
+    let lower = data_property.lower_float()
+        .map_err(prepend("data: the lower edge of the first quantile bin is the lower bound of the data."))?[0];
+
+    let inclusive_left = match public_arguments.get::<IndexKey>(&"inclusive_left".into()) {
+        Some(inclusive_left) => inclusive_left.ref_array()?.first_bool()?,
+        None => true
+    };
+
+    let quantile_usage = dp_histogram.privacy_usage.iter().cloned()
+        .map(|usage| usage / (2. * (num_bins - 1) as Float))
+        .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+
+    let push_literal = |value: Value, expansion: &mut base::ComponentExpansion, maximum_id: &mut u32| -> Result<u32> {
+        *maximum_id += 1;
+        let id = *maximum_id;
+        let (patch_node, release) = get_literal(value, component.submission)?;
+        expansion.computation_graph.insert(id, patch_node);
+        expansion.properties.insert(id, infer_property(&release.value, None, id)?);
+        expansion.releases.insert(id, release);
+        Ok(id)
+    };
+    let push_node = |arguments: IndexMap<IndexKey, u32>, variant: proto::component::Variant, expansion: &mut base::ComponentExpansion, maximum_id: &mut u32| {
+        *maximum_id += 1;
+        let id = *maximum_id;
+        expansion.computation_graph.insert(id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(arguments)),
+            variant: Some(variant),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id);
+        id
+    };
+
+    let id_lower = push_literal(arr1(&[lower]).into_dyn().into(), expansion, maximum_id)?;
+    let id_int_lower = push_literal(0.into(), expansion, maximum_id)?;
+    let id_int_upper = push_literal(1.into(), expansion, maximum_id)?;
+
+    let mut edge_ids = indexmap![IndexKey::from("lower") => id_lower];
+    let mut id_index = None;
+    for i in 1..num_bins {
+        // interior edge
+        let id_edge = push_node(indexmap![
+            "data".into() => data_id,
+            "candidates".into() => candidates_id
+        ], proto::component::Variant::DpQuantile(proto::DpQuantile {
+            alpha: i as Float / num_bins as Float,
+            mechanism: "Exponential".to_string(),
+            privacy_usage: quantile_usage.clone(),
+            interpolation: "midpoint".to_string(),
+        }), expansion, maximum_id);
+        edge_ids.insert(IndexKey::from(format!("edge_{}", i)), id_edge);
+
+        // indicator that the record falls above the edge
+        let id_above = if inclusive_left {
+            let id_below = push_node(indexmap![
+                "left".into() => data_id,
+                "right".into() => id_edge
+            ], proto::component::Variant::LessThan(proto::LessThan {}), expansion, maximum_id);
+            push_node(indexmap!["data".into() => id_below],
+                      proto::component::Variant::Negate(proto::Negate {}), expansion, maximum_id)
+        } else {
+            push_node(indexmap![
+                "left".into() => data_id,
+                "right".into() => id_edge
+            ], proto::component::Variant::GreaterThan(proto::GreaterThan {}), expansion, maximum_id)
+        };
+        let id_indicator = push_node(indexmap![
+            "data".into() => id_above,
+            "lower".into() => id_int_lower,
+            "upper".into() => id_int_upper
+        ], proto::component::Variant::ToInt(proto::ToInt {
+            overflow: "error".to_string()
+        }), expansion, maximum_id);
+
+        id_index = Some(match id_index {
+            Some(id_index) => push_node(indexmap![
+                "left".into() => id_index,
+                "right".into() => id_indicator
+            ], proto::component::Variant::Add(proto::Add {}), expansion, maximum_id),
+            None => id_indicator
+        });
+    }
+
+    let id_categories = push_literal(
+        Value::Jagged(Jagged::Int(vec![(0..num_bins as Integer).collect()])), expansion, maximum_id)?;
+    let id_null_value = push_literal((-1 as Integer).into(), expansion, maximum_id)?;
+    let id_edges = push_node(edge_ids,
+                             proto::component::Variant::Union(proto::Union { flatten: true }), expansion, maximum_id);
+
+    Ok(QuantileBins {
+        num_bins,
+        id_index: id_index.ok_or_else(|| Error::from("quantile_bins: must be at least two"))?,
+        id_categories,
+        id_null_value,
+        id_edges,
+    })
+}
+
+/// Number of final cells of the histogram that are excluded from smoothing.
+///
+/// Only bins of continuous data are ordered, so smoothing requires edges or quantile bins.
+/// Quantile bins release no null cell, and the null cell of bins from edges is excluded,
+/// unless the null value is one of the bins.
+fn get_smoothing_exclusions(
+    public_arguments: &IndexMap<IndexKey, &Value>, data_property: &ArrayProperties, quantile_bins: bool,
+) -> Result<u32> {
+    if data_property.num_columns()? != 1 {
+        return Err("data: a smoothed histogram must contain one column".into())
+    }
+    if quantile_bins {
+        return Ok(0)
+    }
+    let num_bins = public_arguments.get::<IndexKey>(&"edges".into())
+        .ok_or_else(|| Error::from("smoothing_window: smoothing requires ordered bins, so either edges or quantile_bins must be set"))?
+        .ref_jagged().map_err(prepend("edges:"))?
+        .num_records().first().map(|num_edges| num_edges - 1)
+        .ok_or_else(|| Error::from("edges: must be defined for one column"))?;
+
+    Ok(match public_arguments.get::<IndexKey>(&"null_value".into()) {
+        Some(null_value) if (0..num_bins).contains(&null_value.ref_array()?.first_int()?) => 0,
+        _ => 1
+    })
+}
+
+/// The labels of the categories counted by the histogram, in the order of the counts.
+///
+/// The labels are the public categories followed by the null value, or the categories of the data if not supplied.
+fn get_histogram_labels(
+    public_arguments: &IndexMap<IndexKey, &Value>, data_property: &ArrayProperties,
+) -> Result<Value> {
+    if data_property.num_columns()? != 1 {
+        return Err("data: a labeled histogram must contain one column".into())
+    }
+
+    let categories = match public_arguments.get::<IndexKey>(&"categories".into()) {
+        Some(categories) => categories_with_null(
+            categories.ref_jagged()?.clone(),
+            require_public_argument(public_arguments, "null_value")?.ref_array()?,
+            1)?,
+        None => {
+            if public_arguments.contains_key::<IndexKey>(&"edges".into()) {
+                return Err("categories: a labeled histogram requires categories rather than edges".into())
+            }
+            data_property.categories()?
+        }
+    };
+
+    Ok(match categories {
+        Jagged::Int(jagged) => arr1(&jagged[0]).into_dyn().into(),
+        Jagged::Float(jagged) => arr1(&jagged[0]).into_dyn().into(),
+        Jagged::Bool(jagged) => arr1(&jagged[0]).into_dyn().into(),
+        Jagged::Str(jagged) => arr1(&jagged[0]).into_dyn().into(),
+    })
+}
+
+#[cfg(test)]
+mod test_dp_histogram {
+    use ndarray::arr1;
+
+    use crate::base::{DataType, IndexKey, test_data, Value};
+    use crate::bindings::Analysis;
+    use crate::components::dp_histogram::histogram_accuracy;
+    use crate::components::impute::test_impute;
+
+    #[test]
+    fn test_simultaneous_accuracy() {
+        let analysis = Analysis::new();
+        let usage = test_data::privacy_usage(1., 0.);
+        let (num_bins, alpha) = (10, 0.05);
+
+        let accuracy = |simultaneous: bool| histogram_accuracy(
+            "Laplace", &analysis.privacy_definition, &usage, num_bins, alpha, simultaneous).unwrap().unwrap();
+        let per_bin = accuracy(false);
+        let simultaneous = accuracy(true);
+
+        // the bonferroni correction scales the laplace interval by ln(k / alpha) / ln(1 / alpha)
+        assert!(simultaneous > per_bin);
+        let correction = (num_bins as f64 / alpha).ln() / (1. / alpha).ln();
+        assert!((simultaneous / per_bin - correction).abs() < 1e-10);
+
+        // the geometric interval is also widened, and remains integral
+        let geometric = |simultaneous: bool| histogram_accuracy(
+            "SimpleGeometric", &analysis.privacy_definition, &usage, num_bins, alpha, simultaneous).unwrap().unwrap();
+        assert!(geometric(true) > geometric(false));
+        assert_eq!(geometric(true).fract(), 0.);
+    }
+
+    #[test]
+    fn test_quantile_bins() {
+        let (mut analysis, imputed) = test_impute::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), Some(0.0.into()), Some(10.0.into()));
+        let usage = vec![test_data::privacy_usage(1., 0.)];
+
+        let lower = analysis.literal().value(0.into()).value_public(true).build();
+        let inclusive_left = analysis.literal().value(true.into()).value_public(true).build();
+        let candidates = analysis.literal()
+            .value(arr1(&(0..=10).map(|v| v as f64).collect::<Vec<f64>>()).into_dyn().into())
+            .value_public(true).build();
+        let histogram = analysis.dp_histogram(imputed, lower, inclusive_left, usage.clone())
+            .candidates(candidates)
+            .quantile_bins(4)
+            .build();
+
+        // the estimated lower edge of each bin is paired with the count of the bin
+        let properties = analysis.properties(histogram).unwrap();
+        let children = &properties.dataframe().unwrap().children;
+        let category = children.get::<IndexKey>(&"category".into()).unwrap().array().unwrap();
+        assert_eq!(category.num_records, Some(4));
+        assert!(category.releasable);
+        let count = children.get::<IndexKey>(&"count".into()).unwrap().array().unwrap();
+        assert_eq!(count.num_records, Some(4));
+
+        // the edges are selected from the candidates
+        let histogram = analysis.dp_histogram(imputed, lower, inclusive_left, usage.clone())
+            .quantile_bins(4)
+            .build();
+        assert!(analysis.properties(histogram).is_err());
+
+        // a single bin has no edges to estimate
+        let histogram = analysis.dp_histogram(imputed, lower, inclusive_left, usage)
+            .candidates(candidates)
+            .quantile_bins(1)
+            .build();
+        assert!(analysis.properties(histogram).is_err());
+    }
+
+    #[test]
+    fn test_smoothing() {
+        let (mut analysis, imputed) = test_impute::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), Some(0.0.into()), Some(10.0.into()));
+        let usage = vec![test_data::privacy_usage(1., 0.)];
+
+        let lower = analysis.literal().value(0.into()).value_public(true).build();
+        let inclusive_left = analysis.literal().value(true.into()).value_public(true).build();
+        let edges = analysis.literal()
+            .value(Value::Jagged(vec![(0..=10).map(|v| v as f64).collect::<Vec<f64>>()].into()))
+            .value_public(true).build();
+        let histogram = analysis.dp_histogram(imputed, lower, inclusive_left, usage.clone())
+            .edges(edges)
+            .smoothing_window(3)
+            .build();
+
+        // the smoothed counts keep one cell per bin, followed by the null cell
+        let properties = analysis.properties(histogram).unwrap();
+        let properties = properties.array().unwrap();
+        assert_eq!(properties.num_records, Some(11));
+        assert_eq!(properties.data_type, DataType::Float);
+        assert!(properties.releasable);
+
+        // the window must be centered
+        let histogram = analysis.dp_histogram(imputed, lower, inclusive_left, usage.clone())
+            .edges(edges)
+            .smoothing_window(2)
+            .build();
+        assert!(analysis.properties(histogram).is_err());
+
+        // categories are unordered, so they may not be smoothed
+        let categories = analysis.literal()
+            .value(Value::Jagged(vec![vec![0.0, 5.0]].into()))
+            .value_public(true).build();
+        let null_value = analysis.literal().value((-1.0).into()).value_public(true).build();
+        let histogram = analysis.dp_histogram(imputed, lower, inclusive_left, usage)
+            .categories(categories)
+            .null_value(null_value)
+            .smoothing_window(3)
+            .build();
+        assert!(analysis.properties(histogram).is_err());
+    }
+}
@@ -0,0 +1,279 @@
+use indexmap::map::IndexMap;
+use ndarray::arr0;
+
+use crate::{base, proto, Warnable, Float};
+use crate::base::{Array, ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Report};
+use crate::errors::*;
+use crate::utilities::{array::get_ith_column, get_literal, prepend, privacy::{get_epsilon, spread_privacy_usage}};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{Accuracy, AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json, REPORT_ALPHA};
+
+impl Component for proto::DpIqr {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        if !data_property.releasable {
+            data_property.assert_is_not_aggregated()?;
+        }
+
+        let num_columns = data_property.num_columns()?;
+        let upper = get_iqr_upper_bounds(&data_property)?;
+
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_columns: Some(num_columns),
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float((0..num_columns).map(|_| Some(0.)).collect()),
+                upper: Vector1DNull::Float(upper.into_iter().map(Some).collect()),
+            })),
+            ..ArrayProperties::dp_release(node_id, DataType::Float, data_property.group_id)
+        }).into())
+    }
+}
+
+impl Expandable for proto::DpIqr {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+        let argument_ids = component.arguments();
+
+        let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // the budget is split evenly between the two quartiles
+        let privacy_usage = self.privacy_usage.iter().cloned()
+            .map(|usage| usage / 2.)
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+
+        // dp quartiles
+        let mut quartile_ids = Vec::new();
+        for alpha in &[0.25, 0.75] {
+            maximum_id += 1;
+            let id_quartile = maximum_id;
+            expansion.computation_graph.insert(id_quartile, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(argument_ids.clone())),
+                variant: Some(proto::component::Variant::DpQuantile(proto::DpQuantile {
+                    alpha: *alpha,
+                    mechanism: self.mechanism.clone(),
+                    privacy_usage: privacy_usage.clone(),
+                    interpolation: self.interpolation.clone(),
+                })),
+                omit: true,
+                submission: component.submission,
+            });
+            expansion.traversal.push(id_quartile);
+            quartile_ids.push(id_quartile);
+        }
+
+        // subtract
+        maximum_id += 1;
+        let id_difference = maximum_id;
+        expansion.computation_graph.insert(id_difference, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => quartile_ids[1],
+                "right".into() => quartile_ids[0]
+            ])),
+            variant: Some(proto::component::Variant::Subtract(proto::Subtract {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_difference);
+
+        // noisy quartiles may cross, so the difference is clamped into [0, upper - lower]
+        maximum_id += 1;
+        let id_lower = maximum_id;
+        let value = Value::Array(Array::Float(arr0(0.).into_dyn()));
+        expansion.properties.insert(id_lower, infer_property(&value, None, id_lower)?);
+        let (patch_node, release) = get_literal(value, component.submission)?;
+        expansion.computation_graph.insert(id_lower, patch_node);
+        expansion.releases.insert(id_lower, release);
+
+        maximum_id += 1;
+        let id_upper = maximum_id;
+        let value = Value::Array(Array::Float(
+            ndarray::Array::from(get_iqr_upper_bounds(&data_property)?).into_dyn()));
+        expansion.properties.insert(id_upper, infer_property(&value, None, id_upper)?);
+        let (patch_node, release) = get_literal(value, component.submission)?;
+        expansion.computation_graph.insert(id_upper, patch_node);
+        expansion.releases.insert(id_upper, release);
+
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_difference,
+                "lower".into() => id_lower,
+                "upper".into() => id_upper
+            ])),
+            variant: Some(proto::component::Variant::Clamp(proto::Clamp { null_policy: "Pass".to_string() })),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpIqr {
+    fn summarize(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = properties.get::<base::IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        let mut releases = Vec::new();
+
+        let minimums = data_property.lower_float()?;
+        let maximums = data_property.upper_float()?;
+
+        let num_columns = data_property.num_columns()?;
+        let privacy_usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
+
+        for column_number in 0..(num_columns as usize) {
+            let variable_name = variable_names
+                .and_then(|names| names.get(column_number)).cloned()
+                .unwrap_or_else(|| "[Unknown]".into());
+
+            let accuracy = if Accuracy::is_known_for(&self.mechanism) {
+                let quartile_accuracy = proto::Accuracy {
+                    value: laplace_quartile_accuracy(
+                        maximums[column_number] - minimums[column_number],
+                        get_epsilon(&privacy_usages[column_number])? / 2.,
+                        REPORT_ALPHA / 2.),
+                    alpha: REPORT_ALPHA / 2.
+                };
+                let accuracy = combine_quartile_accuracies(&quartile_accuracy, &quartile_accuracy);
+                Some(Accuracy {
+                    accuracy_value: accuracy.value,
+                    alpha: accuracy.alpha,
+                    data_dependent: false,
+                    simultaneous: false,
+                })
+            } else { None };
+
+            releases.push(JSONRelease {
+                description: "DP release information".to_string(),
+                statistic: "DPIqr".to_string(),
+                variables: serde_json::json!(variable_name.to_string()),
+                release_info: match release.ref_array()? {
+                    Array::Float(v) => value_to_json(&get_ith_column(v, column_number)?.into())?,
+                    _ => return Err("iqr must be float".into())
+                },
+                privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
+                accuracy,
+                relative_accuracy: None,
+                submission: component.submission,
+                node_id,
+                postprocess: false,
+                algorithm_info: AlgorithmInfo {
+                    name: "".to_string(),
+                    cite: "".to_string(),
+                    mechanism: self.mechanism.clone(),
+                    argument: serde_json::json!({
+                        "constraint": {
+                            "lowerbound": minimums[column_number],
+                            "upperbound": maximums[column_number]
+                        }
+                    }),
+                },
+            });
+        }
+        Ok(Some(releases))
+    }
+}
+
+/// The inter-quartile range can be no greater than the width of the data bounds.
+fn get_iqr_upper_bounds(data_property: &ArrayProperties) -> Result<Vec<Float>> {
+    Ok(data_property.lower_float()?.into_iter()
+        .zip(data_property.upper_float()?.into_iter())
+        .map(|(lower, upper)| upper - lower)
+        .collect())
+}
+
+/// Accuracy of a single quartile privatized with the laplace mechanism.
+fn laplace_quartile_accuracy(sensitivity: Float, epsilon: Float, alpha: Float) -> Float {
+    (1. / alpha).ln() * sensitivity / epsilon
+}
+
+/// Combine the confidence intervals of the lower and upper quartiles into an interval on their difference.
+///
+/// By the union bound, the difference is within the sum of the accuracies
+/// with probability at least 1 - (lower.alpha + upper.alpha).
+pub fn combine_quartile_accuracies(lower: &proto::Accuracy, upper: &proto::Accuracy) -> proto::Accuracy {
+    proto::Accuracy {
+        value: lower.value + upper.value,
+        alpha: lower.alpha + upper.alpha,
+    }
+}
+
+#[cfg(test)]
+mod test_dp_iqr {
+    use crate::base::{IndexKey, test_data};
+    use crate::components::resize::test_resize;
+    use crate::proto;
+
+    #[test]
+    fn test_dp_iqr() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let iqr = analysis.dp_iqr(resized, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let expansion = crate::expand_component(
+            analysis.components.get(&iqr).unwrap().clone(),
+            indexmap!["data".into() => analysis.properties(resized).unwrap()],
+            indexmap![],
+            Some(analysis.privacy_definition.clone()),
+            iqr, 1000).unwrap();
+
+        let mut quartile_alphas = expansion.computation_graph.values()
+            .filter_map(|component| match component.variant.as_ref() {
+                Some(proto::component::Variant::DpQuantile(quantile)) => Some(quantile.alpha),
+                _ => None
+            }).collect::<Vec<_>>();
+        quartile_alphas.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        assert_eq!(quartile_alphas, vec![0.25, 0.75]);
+
+        assert_eq!(expansion.computation_graph.values()
+            .filter(|component| matches!(component.variant.as_ref(), Some(proto::component::Variant::Subtract(_))))
+            .count(), 1);
+
+        // the difference is clamped into [0, upper - lower]
+        let clamp_arguments = expansion.computation_graph.get(&iqr).unwrap().arguments();
+        let bound = |name: &str| expansion.releases.get(clamp_arguments.get::<IndexKey>(&name.into()).unwrap())
+            .unwrap().value.ref_array().unwrap().ref_float().unwrap().iter().cloned().collect::<Vec<f64>>();
+        assert_eq!(bound("lower"), vec![0.]);
+        assert_eq!(bound("upper"), vec![10.]);
+
+        // so the released range is nonnegative
+        let properties = analysis.properties(iqr).unwrap().array().unwrap().clone();
+        assert!(properties.releasable);
+        assert_eq!(properties.lower_float().unwrap(), vec![0.]);
+    }
+}
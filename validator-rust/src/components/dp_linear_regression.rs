@@ -177,6 +177,7 @@ impl Expandable for proto::DpLinearRegression {
 impl Report for proto::DpLinearRegression {
     fn summarize(
         &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
         node_id: u32,
         component: &proto::Component,
         _public_arguments: IndexMap<base::IndexKey, &Value>,
@@ -194,6 +195,7 @@ impl Report for proto::DpLinearRegression {
             release_info: value_to_json(release)?,
             privacy_loss: serde_json::json!(privacy_usage.iter().map(privacy_usage_to_json).collect::<Vec<_>>()),
             accuracy: None,
+            relative_accuracy: None,
             submission: component.submission,
             node_id,
             postprocess: false,
@@ -41,6 +41,7 @@ impl Expandable for proto::DpMaximum {
 impl Report for proto::DpMaximum {
     fn summarize(
         &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
         node_id: u32,
         component: &proto::Component,
         _public_arguments: IndexMap<base::IndexKey, &Value>,
@@ -76,6 +77,7 @@ impl Report for proto::DpMaximum {
                 },
                 privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
                 accuracy: None,
+                relative_accuracy: None,
                 submission: component.submission,
                 node_id,
                 postprocess: false,
@@ -1,11 +1,12 @@
 use indexmap::map::IndexMap;
+use ndarray::arr1;
 
-use crate::{base, proto};
-use crate::base::{IndexKey, NodeProperties, Value};
+use crate::{base, proto, Float};
+use crate::base::{DataType, IndexKey, NodeProperties, Value};
 use crate::components::{Expandable, Report};
 use crate::errors::*;
-use crate::utilities::{array::get_ith_column, prepend, privacy::spread_privacy_usage, get_literal};
-use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+use crate::utilities::{array::get_ith_column, prepend, privacy::{get_epsilon, get_max_contributions, spread_privacy_usage}, get_literal, get_minimum_num_records};
+use crate::utilities::json::{Accuracy, AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json, REPORT_ALPHA};
 use crate::utilities::inference::infer_property;
 
 impl Expandable for proto::DpMean {
@@ -46,6 +47,45 @@ impl Expandable for proto::DpMean {
             let id_data = *argument_ids.get::<base::IndexKey>(&"data".into())
                 .ok_or_else(|| Error::from("data must be provided as an argument"))?;
 
+            // when centered, the sum is computed over data shifted to be symmetric about zero
+            let centering = if self.centered {
+                if data_property.data_type != DataType::Float {
+                    return Err("data: centering requires float data".into())
+                }
+                let lower = data_property.lower_float()?;
+                let upper = data_property.upper_float()?;
+                let midpoint = lower.iter().zip(upper.iter())
+                    .map(|(lower, upper)| (lower + upper) / 2.)
+                    .collect::<Vec<Float>>();
+
+                maximum_id += 1;
+                let id_midpoint = maximum_id;
+                let (patch_node, midpoint_release) = get_literal(arr1(&midpoint).into_dyn().into(), component.submission)?;
+                expansion.computation_graph.insert(id_midpoint, patch_node);
+                expansion.properties.insert(id_midpoint, infer_property(&midpoint_release.value, None, id_midpoint)?);
+                expansion.releases.insert(id_midpoint, midpoint_release);
+
+                maximum_id += 1;
+                let id_centered = maximum_id;
+                expansion.computation_graph.insert(id_centered, proto::Component {
+                    arguments: Some(proto::ArgumentNodeIds::new(
+                        indexmap!["left".into() => id_data, "right".into() => id_midpoint])),
+                    variant: Some(proto::component::Variant::Subtract(proto::Subtract {})),
+                    omit: true,
+                    submission: component.submission,
+                });
+                expansion.traversal.push(id_centered);
+
+                // bounds of the centered data are symmetric about zero
+                let radius = lower.iter().zip(upper.iter())
+                    .map(|(lower, upper)| (upper - lower) / 2.)
+                    .collect::<Vec<Float>>();
+                let centered_lower: Value = arr1(&radius.iter().map(|r| -r).collect::<Vec<Float>>()).into_dyn().into();
+                let centered_upper: Value = arr1(&radius).into_dyn().into();
+
+                Some((id_midpoint, id_centered, centered_lower, centered_upper))
+            } else { None };
+
             // dp count
             maximum_id += 1;
             let mut id_dp_count = maximum_id;
@@ -98,7 +138,8 @@ impl Expandable for proto::DpMean {
             expansion.traversal.push(maximum_id);
             id_dp_count = maximum_id;
 
-            let mut dp_sum_arguments = indexmap!["data".into() => id_data];
+            let mut dp_sum_arguments = indexmap!["data".into() => centering.as_ref()
+                .map(|(_, id_centered, _, _)| *id_centered).unwrap_or(id_data)];
 
             // if snapping or geometric, derive lower bound for statistic
             if mechanism == "snapping" || mechanism == "simplegeometric" {
@@ -106,7 +147,10 @@ impl Expandable for proto::DpMean {
                 // data lower
                 maximum_id += 1;
                 let id_data_lower = maximum_id;
-                let (patch_node, data_lower_release) = get_literal(Value::Array(data_property.lower()?), component.submission)?;
+                let (patch_node, data_lower_release) = get_literal(match &centering {
+                    Some((_, _, centered_lower, _)) => centered_lower.clone(),
+                    None => Value::Array(data_property.lower()?)
+                }, component.submission)?;
                 expansion.computation_graph.insert(id_data_lower, patch_node);
                 expansion.properties.insert(id_data_lower, infer_property(&data_lower_release.value, None, id_data_lower)?);
                 expansion.releases.insert(id_data_lower, data_lower_release);
@@ -127,7 +171,10 @@ impl Expandable for proto::DpMean {
                 // data upper
                 maximum_id += 1;
                 let id_data_upper = maximum_id;
-                let (patch_node, data_upper_release) = get_literal(Value::Array(data_property.upper()?), component.submission)?;
+                let (patch_node, data_upper_release) = get_literal(match &centering {
+                    Some((_, _, _, centered_upper)) => centered_upper.clone(),
+                    None => Value::Array(data_property.upper()?)
+                }, component.submission)?;
                 expansion.computation_graph.insert(id_data_upper, patch_node);
                 expansion.properties.insert(id_data_upper, infer_property(&data_upper_release.value, None, id_data_upper)?);
                 expansion.releases.insert(id_data_upper, data_upper_release);
@@ -162,21 +209,50 @@ impl Expandable for proto::DpMean {
             });
             expansion.traversal.push(id_dp_sum);
 
-            // divide
-            expansion.computation_graph.insert(component_id, proto::Component {
+            let divide_component = |omit: bool| proto::Component {
                 arguments: Some(proto::ArgumentNodeIds::new(indexmap![
                     "left".into() => id_dp_sum,
                     "right".into() => id_dp_count
                 ])),
                 variant: Some(proto::component::Variant::Divide(proto::Divide {})),
-                omit: component.omit,
+                omit,
                 submission: component.submission,
-            });
+            };
+
+            match centering {
+                Some((id_midpoint, _, _, _)) => {
+                    // divide
+                    maximum_id += 1;
+                    let id_divide = maximum_id;
+                    expansion.computation_graph.insert(id_divide, divide_component(true));
+                    expansion.traversal.push(id_divide);
+
+                    // shift back to the original location, as postprocessing
+                    expansion.computation_graph.insert(component_id, proto::Component {
+                        arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                            "left".into() => id_divide,
+                            "right".into() => id_midpoint
+                        ])),
+                        variant: Some(proto::component::Variant::Add(proto::Add {})),
+                        omit: component.omit,
+                        submission: component.submission,
+                    });
+                }
+                // divide
+                None => {
+                    expansion.computation_graph.insert(component_id, divide_component(component.omit));
+                }
+            }
 
             Ok(expansion)
         }
 
         else if self.implementation.to_lowercase() == "resize" {
+            // the sensitivity of the mean of resized data depends only on the width of the bounds
+            if self.centered {
+                return Err("centering is only supported by the plug-in implementation".into())
+            }
+
             // mean
             maximum_id += 1;
             let id_mean = maximum_id;
@@ -194,15 +270,18 @@ impl Expandable for proto::DpMean {
             let mut arguments = indexmap!["data".into() => id_mean];
             let variant = Some(match mechanism.as_str() {
                 "laplace" => proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
-                    privacy_usage: self.privacy_usage.clone()
+                    privacy_usage: self.privacy_usage.clone(),
+                    max_noise_sigmas: 0.
                 }),
                 "gaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
                     privacy_usage: self.privacy_usage.clone(),
-                    analytic: false
+                    analytic: false,
+                    max_noise_sigmas: 0.
                 }),
                 "analyticgaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
                     privacy_usage: self.privacy_usage.clone(),
-                    analytic: true
+                    analytic: true,
+                    max_noise_sigmas: 0.
                 }),
                 "snapping" => {
                     argument_ids.get::<IndexKey>(&"lower".into())
@@ -244,6 +323,7 @@ impl Report for proto::DpMean {
     /// * `release` - JSONRelease containing DP release information
     fn summarize(
         &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
         node_id: u32,
         component: &proto::Component,
         _public_arguments: IndexMap<base::IndexKey, &Value>,
@@ -251,6 +331,8 @@ impl Report for proto::DpMean {
         release: &Value,
         variable_names: Option<&Vec<base::IndexKey>>,
     ) -> Result<Option<Vec<JSONRelease>>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or("privacy definition must be defined")?;
 
         let data_property = properties.get::<base::IndexKey>(&"data".into())
             .ok_or("data: missing")?.array()
@@ -261,6 +343,7 @@ impl Report for proto::DpMean {
         let lower = data_property.lower_float()?;
         let upper = data_property.upper_float()?;
         let num_records = data_property.num_records()?;
+        let contributions = get_max_contributions(privacy_definition)? as Float;
 
         let num_columns = data_property.num_columns()?;
         let privacy_usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
@@ -272,16 +355,66 @@ impl Report for proto::DpMean {
                 .and_then(|names| names.get(column_number)).cloned()
                 .unwrap_or_else(|| "[Unknown]".into());
 
+            // the laplace mechanism spends the usage left over after c-stability and group size
+            let epsilon = get_epsilon(&privacy_usages[column_number].actual_to_effective(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition.group_size)?)?;
+            // scale of the laplace noise added to the mean, from the same sensitivity as the mean component
+            let sensitivity = (upper[column_number] - lower[column_number]) * contributions / num_records as Float;
+            let scale = sensitivity / epsilon;
+
+            // the accuracy is only derived for resized data
+            let accuracy = match self.implementation.to_lowercase().as_str() {
+                "resize" if Accuracy::is_known_for(&self.mechanism) => Some(Accuracy {
+                    accuracy_value: (1. / REPORT_ALPHA).ln() * scale,
+                    alpha: REPORT_ALPHA,
+                    // derived from the global sensitivity, so holds for any dataset
+                    data_dependent: false,
+                    simultaneous: false,
+                }),
+                _ => None
+            };
+
+            // a mean far from zero may also be described by its error relative to the estimate
+            let estimate = get_ith_column(release, column_number)?;
+            let relative_accuracy = match (&accuracy, estimate.first()) {
+                (Some(accuracy), Some(estimate)) => accuracy.relative_to(*estimate),
+                _ => None
+            };
+
+            let mut argument = serde_json::json!({
+                // TODO: AlgorithmInfo -> serde_json::Value, move implementation into algorithm_info
+                "implementation": self.implementation.clone(),
+                "centered": self.centered,
+                "n": num_records,
+                "constraint": {
+                    "lowerbound": lower[column_number],
+                    "upperbound": upper[column_number]
+                }
+            });
+
+            // the mean of data in [0, 1] is a proportion, whose interval may be asymmetric, but stays within [0, 1]
+            if let (Some(_), Some(estimate)) = (&accuracy, estimate.first()) {
+                if lower[column_number] == 0. && upper[column_number] == 1. {
+                    let (interval_lower, interval_upper) = dp_proportion_interval(
+                        *estimate, num_records, scale, REPORT_ALPHA)?;
+                    argument["proportion_interval"] = serde_json::json!({
+                        "lower": interval_lower,
+                        "upper": interval_upper,
+                        "alpha": REPORT_ALPHA
+                    });
+                }
+            }
+
             releases.push(JSONRelease {
                 description: "DP release information".to_string(),
                 statistic: "DPMean".to_string(),
                 variables: serde_json::json!(variable_name.to_string()),
-                release_info: value_to_json(&get_ith_column(
-                    release,
-                    column_number as usize
-                )?.into())?,
+                release_info: value_to_json(&estimate.into())?,
                 privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
-                accuracy: None,
+                accuracy,
+                relative_accuracy,
                 submission: component.submission,
                 node_id,
                 postprocess: false,
@@ -289,18 +422,298 @@ impl Report for proto::DpMean {
                     name: "".to_string(),
                     cite: "".to_string(),
                     mechanism: self.mechanism.clone(),
-                    argument: serde_json::json!({
-                        // TODO: AlgorithmInfo -> serde_json::Value, move implementation into algorithm_info
-                        "implementation": self.implementation.clone(),
-                        "n": num_records,
-                        "constraint": {
-                            "lowerbound": lower[column_number],
-                            "upperbound": upper[column_number]
-                        }
-                    })
+                    argument
                 }
             });
         }
         Ok(Some(releases))
     }
 }
+
+/// Accuracy of a dp mean over `num_records` records with data in `[lower, upper]`, privatized with the laplace mechanism.
+pub fn dp_mean_accuracy(lower: Float, upper: Float, num_records: i64, epsilon: Float, alpha: Float) -> Float {
+    (1. / alpha).ln() * (upper - lower) / (num_records as Float * epsilon)
+}
+
+/// Minimum number of records needed for a laplace dp mean of data in `[lower, upper]` to be within `accuracy` of the true mean,
+/// with confidence `1 - alpha`, at the given epsilon.
+///
+/// # Example
+/// ```
+/// use smartnoise_validator::components::dp_mean::dp_mean_minimum_num_records;
+/// // records needed to estimate a mean of data in [0, 100] within +/- 1 at epsilon = 1, with 95% confidence
+/// let num_records = dp_mean_minimum_num_records(0., 100., 1., 1., 0.05).unwrap();
+/// assert_eq!(num_records, 300);
+/// ```
+pub fn dp_mean_minimum_num_records(
+    lower: Float, upper: Float, epsilon: Float, accuracy: Float, alpha: Float
+) -> Result<i64> {
+    if !lower.is_finite() || !upper.is_finite() {
+        return Err("no finite number of records achieves the target accuracy when the bounds are unbounded".into())
+    }
+    if lower > upper {
+        return Err("lower may not be greater than upper".into())
+    }
+    if epsilon.is_nan() || epsilon <= 0. {
+        return Err("epsilon must be positive".into())
+    }
+    if alpha.is_nan() || alpha <= 0. || alpha >= 1. {
+        return Err("alpha must be within (0, 1)".into())
+    }
+    get_minimum_num_records(
+        |num_records| Ok(dp_mean_accuracy(lower, upper, num_records, epsilon, alpha)),
+        accuracy)
+}
+
+/// Confidence interval for a proportion, released as a laplace dp mean of `num_records` records in `[0, 1]`.
+///
+/// `scale` is the scale of the laplace noise added to the mean, that is, its sensitivity over the effective epsilon.
+/// This is an analog of the Clopper-Pearson interval.
+/// The noisy count is widened to an interval on the true count that holds with probability `1 - alpha / 2`,
+/// and the Clopper-Pearson bounds at level `alpha / 2` are taken at its ends.
+/// Unlike the symmetric interval `estimate +/- accuracy`, the bounds are always within `[0, 1]`.
+pub fn dp_proportion_interval(estimate: Float, num_records: i64, scale: Float, alpha: Float) -> Result<(Float, Float)> {
+    if num_records <= 0 {
+        return Err("num_records must be positive".into())
+    }
+    if scale.is_nan() || scale <= 0. {
+        return Err("scale must be positive".into())
+    }
+    if alpha.is_nan() || alpha <= 0. || alpha >= 1. {
+        return Err("alpha must be within (0, 1)".into())
+    }
+    let num_records = num_records as Float;
+
+    // the noise on the count is laplace with scale b = num_records * scale, so exceeds t in magnitude with probability exp(-t / b)
+    let noise_bound = (2. / alpha).ln() * scale * num_records;
+    let count_lower = (estimate * num_records - noise_bound).max(0.).min(num_records);
+    let count_upper = (estimate * num_records + noise_bound).max(0.).min(num_records);
+
+    // the remaining alpha / 2 is split between the tails of the Clopper-Pearson interval
+    let lower = if count_lower == 0. { 0. } else {
+        beta_quantile(alpha / 4., count_lower, num_records - count_lower + 1.)
+    };
+    let upper = if count_upper == num_records { 1. } else {
+        beta_quantile(1. - alpha / 4., count_upper + 1., num_records - count_upper)
+    };
+    Ok((lower, upper))
+}
+
+/// Inverse cdf of the beta distribution with shape parameters `a` and `b`, found by bisection.
+fn beta_quantile(probability: Float, a: Float, b: Float) -> Float {
+    let (mut lower, mut upper) = (0., 1.);
+    (0..64).for_each(|_| {
+        let midpoint = (lower + upper) / 2.;
+        if statrs::function::beta::beta_reg(a, b, midpoint) < probability {
+            lower = midpoint
+        } else {
+            upper = midpoint
+        }
+    });
+    (lower + upper) / 2.
+}
+
+#[cfg(test)]
+mod test_dp_mean {
+    use crate::base::test_data;
+    use crate::components::dp_mean::{dp_mean_accuracy, dp_mean_minimum_num_records, dp_proportion_interval};
+    use crate::components::resize::test_resize;
+    use crate::proto;
+    use crate::utilities::json::{JSONRelease, REPORT_ALPHA};
+    use crate::utilities::privacy::get_epsilon;
+
+    #[test]
+    fn test_minimum_num_records() {
+        let (lower, upper, epsilon, accuracy, alpha) = (0., 100., 1., 1., 0.05);
+        let num_records = dp_mean_minimum_num_records(lower, upper, epsilon, accuracy, alpha).unwrap();
+
+        assert!(dp_mean_accuracy(lower, upper, num_records, epsilon, alpha) <= accuracy);
+        assert!(dp_mean_accuracy(lower, upper, num_records - 1, epsilon, alpha) > accuracy);
+    }
+
+    #[test]
+    fn test_minimum_num_records_unbounded() {
+        assert!(dp_mean_minimum_num_records(0., f64::INFINITY, 1., 1., 0.05).is_err());
+        assert!(dp_mean_minimum_num_records(0., 1e300, 1e-300, 1e-10, 0.05).is_err());
+    }
+
+    #[test]
+    fn test_dry_run_plug_in() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let usage = test_data::privacy_usage(1., 0.);
+        analysis.dp_mean(resized, vec![usage])
+            .implementation("plug-in".to_string())
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let dry_run = crate::dry_run(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone(), 0.05).unwrap();
+
+        // the budget is split between the sum and the count
+        assert_eq!(dry_run.nodes.len(), 2);
+        assert!((get_epsilon(&dry_run.privacy_usage).unwrap() - 1.).abs() < 1e-10);
+        assert!(dry_run.nodes.iter()
+            .flat_map(|node| node.accuracies.clone().unwrap())
+            .all(|accuracy| accuracy.value.is_finite()));
+    }
+
+    #[test]
+    fn test_centered_plug_in() {
+        use crate::base::{SensitivitySpace, ValueProperties};
+        use crate::components::Sensitivity;
+        use crate::utilities::{get_input_properties, propagate_properties};
+
+        // sensitivity of the sum computed by a plug-in dp mean of data in [0, 10]
+        let sum_sensitivity = |centered: bool| {
+            let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+                test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+            analysis.privacy_definition.protect_floating_point = false;
+            analysis.privacy_definition.neighboring = proto::privacy_definition::Neighboring::AddRemove as i32;
+
+            let usage = test_data::privacy_usage(1., 0.);
+            let mean = analysis.dp_mean(resized, vec![usage])
+                .implementation("plug-in".to_string())
+                .mechanism("Laplace".to_string())
+                .centered(centered)
+                .build();
+
+            let mut computation_graph = analysis.components.clone();
+            let mut release = analysis.release.clone();
+            let (properties, _) = propagate_properties(
+                &Some(analysis.privacy_definition.clone()),
+                &mut computation_graph, &mut release, None, false).unwrap();
+
+            // the centered mean is shifted back to the location of the data
+            assert_eq!(
+                matches!(computation_graph[&mean].variant, Some(proto::component::Variant::Add(_))),
+                centered);
+
+            let sum = computation_graph.values()
+                .find(|component| matches!(component.variant, Some(proto::component::Variant::Sum(_))))
+                .unwrap();
+            let sum_properties = get_input_properties::<ValueProperties>(sum, &properties).unwrap();
+            match sum.variant.as_ref().unwrap() {
+                proto::component::Variant::Sum(sum) => sum.compute_sensitivity(
+                    &analysis.privacy_definition, &sum_properties, &SensitivitySpace::KNorm(1)).unwrap()
+                    .array().unwrap().first_float().unwrap(),
+                _ => unreachable!()
+            }
+        };
+
+        assert_eq!(sum_sensitivity(false), 10.);
+        assert_eq!(sum_sensitivity(true), 5.);
+    }
+
+    #[test]
+    fn test_relative_accuracy() {
+        use crate::base::Value;
+        use crate::components::Report;
+
+        // a mean far from zero
+        let (analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(1000.0.into()), Some(1010.0.into()));
+        let usage = test_data::privacy_usage(1., 0.);
+        let dp_mean = proto::DpMean {
+            mechanism: "Laplace".to_string(),
+            privacy_usage: vec![usage],
+            implementation: "resize".to_string(),
+            centered: false,
+        };
+        let component = proto::Component { arguments: None, variant: None, omit: false, submission: 0 };
+        let summarize = |estimate: f64| dp_mean.summarize(
+            &Some(analysis.privacy_definition.clone()), 0, &component,
+            indexmap![],
+            indexmap!["data".into() => analysis.properties(resized).unwrap()],
+            &Value::from(estimate), None).unwrap().unwrap().remove(0);
+
+        // the relative accuracy is reported alongside the additive accuracy, and depends on the estimate
+        let release = summarize(1005.);
+        let accuracy = release.accuracy.unwrap();
+        let relative_accuracy = release.relative_accuracy.unwrap();
+        assert!(!accuracy.data_dependent);
+        assert!(relative_accuracy.data_dependent);
+        assert!((relative_accuracy.accuracy_value - accuracy.accuracy_value / 1005.).abs() < 1e-12);
+
+        // the relative error is unbounded when the interval contains zero
+        assert!(summarize(1e-3).relative_accuracy.is_none());
+    }
+
+    #[test]
+    fn test_proportion_interval() {
+        let (lower, upper) = dp_proportion_interval(0.02, 1000, 1e-3, 0.05).unwrap();
+        assert!(0. < lower && lower < 0.02 && 0.02 < upper && upper < 1.);
+        // a count near zero may grow further than it may shrink
+        assert!(upper - 0.02 > 0.02 - lower);
+
+        // the symmetric interval about an estimate near zero extends below zero, but the proportion interval does not
+        let (lower, upper) = dp_proportion_interval(0.001, 1000, 1e-3, 0.05).unwrap();
+        assert!(0.001 - dp_mean_accuracy(0., 1., 1000, 1., 0.05) < 0.);
+        assert_eq!(lower, 0.);
+        assert!(upper > 0.001);
+
+        // and likewise near one
+        let (lower, upper) = dp_proportion_interval(1.002, 1000, 1e-3, 0.05).unwrap();
+        assert!(lower < 1.);
+        assert_eq!(upper, 1.);
+    }
+
+    #[test]
+    fn test_proportion_report() {
+        use crate::base::Value;
+        use crate::components::Report;
+
+        use crate::base::{Nature, NatureContinuous, Vector1DNull};
+
+        // a proportion is a mean of data in [0, 1]
+        let (analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(1.0.into()));
+        let mut data_property = analysis.properties(resized).unwrap().array().unwrap().clone();
+        data_property.nature = Some(Nature::Continuous(NatureContinuous {
+            lower: Vector1DNull::Float(vec![Some(0.)]),
+            upper: Vector1DNull::Float(vec![Some(1.)]),
+        }));
+        let usage = test_data::privacy_usage(1., 0.);
+        let dp_mean = proto::DpMean {
+            mechanism: "Laplace".to_string(),
+            privacy_usage: vec![usage],
+            implementation: "resize".to_string(),
+            centered: false,
+        };
+        let component = proto::Component { arguments: None, variant: None, omit: false, submission: 0 };
+        let summarize = |privacy_definition: &proto::PrivacyDefinition, data_property: &crate::base::ArrayProperties|
+            dp_mean.summarize(
+                &Some(privacy_definition.clone()), 0, &component,
+                indexmap![],
+                indexmap!["data".into() => data_property.clone().into()],
+                &Value::from(0.02), None).unwrap().unwrap().remove(0);
+        let interval = |release: &JSONRelease| (
+            release.algorithm_info.argument["proportion_interval"]["lower"].as_f64().unwrap(),
+            release.algorithm_info.argument["proportion_interval"]["upper"].as_f64().unwrap());
+
+        // the interval of the proportion is reported alongside the symmetric accuracy
+        let release = summarize(&analysis.privacy_definition, &data_property);
+        assert_eq!(interval(&release), dp_proportion_interval(0.02, 10, 0.1, REPORT_ALPHA).unwrap());
+        assert_eq!(interval(&release).0, 0.);
+        assert!(interval(&release).1 <= 1.);
+        assert!(0.02 - release.accuracy.unwrap().accuracy_value < 0.);
+
+        // the noise scale grows with the contribution bound, as does the sensitivity of the mean
+        let mut privacy_definition = analysis.privacy_definition.clone();
+        privacy_definition.max_contributions_per_user = 2;
+        assert_eq!(
+            interval(&summarize(&privacy_definition, &data_property)),
+            dp_proportion_interval(0.02, 10, 0.2, REPORT_ALPHA).unwrap());
+
+        // and as the effective epsilon shrinks with c-stability
+        let mut stable_property = data_property.clone();
+        stable_property.c_stability = 2;
+        assert_eq!(
+            interval(&summarize(&analysis.privacy_definition, &stable_property)),
+            dp_proportion_interval(0.02, 10, 0.2, REPORT_ALPHA).unwrap());
+    }
+}
@@ -1,12 +1,12 @@
 use crate::errors::*;
 
-use crate::{proto, base};
-use crate::components::{Expandable, Report};
+use crate::{proto, base, Float};
+use crate::components::{Expandable, Report, Sensitivity};
+use crate::components::exponential_mechanism::exponential_utility_accuracy;
 
-
-use crate::base::{NodeProperties, Value, Array, IndexKey};
-use crate::utilities::json::{JSONRelease, value_to_json, privacy_usage_to_json, AlgorithmInfo};
-use crate::utilities::{prepend, privacy::spread_privacy_usage, array::get_ith_column};
+use crate::base::{NodeProperties, Value, Array, IndexKey, SensitivitySpace};
+use crate::utilities::json::{JSONRelease, value_to_json, privacy_usage_to_json, AlgorithmInfo, REPORT_ALPHA};
+use crate::utilities::{get_array_property, prepend, privacy::{get_epsilon, spread_privacy_usage}, array::get_ith_column};
 use indexmap::map::IndexMap;
 
 
@@ -22,12 +22,7 @@ impl Expandable for proto::DpMedian {
     ) -> Result<base::ComponentExpansion> {
         let mut expansion = base::ComponentExpansion::default();
 
-        let mechanism = if self.mechanism.to_lowercase().as_str() == "automatic" {
-            if properties.contains_key::<IndexKey>(&"candidates".into())
-            { "exponential" } else { "laplace" }.to_string()
-        } else {
-            self.mechanism.to_lowercase()
-        };
+        let mechanism = resolve_mechanism(&self.mechanism, properties);
 
         expansion.computation_graph.insert(component_id, proto::Component {
             arguments: component.arguments.clone(),
@@ -58,9 +53,10 @@ impl Expandable for proto::DpMedian {
 impl Report for proto::DpMedian {
     fn summarize(
         &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
         node_id: u32,
         component: &proto::Component,
-        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: NodeProperties,
         release: &Value,
         variable_names: Option<&Vec<base::IndexKey>>,
@@ -77,11 +73,54 @@ impl Report for proto::DpMedian {
         let num_columns = data_property.num_columns()?;
         let privacy_usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
 
+        // the exponential mechanism is only reported on a known set of candidates
+        let num_candidates = match resolve_mechanism(&self.mechanism, &properties).as_str() {
+            "exponential" => Some(get_array_property(&properties, "candidates")?.num_records()?),
+            _ => None
+        };
+
+        let public_float_argument = |name: &str| public_arguments.get::<IndexKey>(&name.into())
+            .map(|value| value.ref_array()?.clone().cast_float()).transpose();
+        let public_data = public_float_argument("data")?;
+        let public_candidates = public_float_argument("candidates")?;
+        let sensitivities = match (num_candidates, &public_data, privacy_definition) {
+            (Some(_), Some(_), Some(privacy_definition)) => Some(proto::Quantile {
+                alpha: 0.5,
+                interpolation: self.interpolation.clone()
+            }.compute_sensitivity(privacy_definition, &properties, &SensitivitySpace::Exponential)?
+                .array()?.float()?.into_raw_vec()),
+            _ => None
+        };
+
         for column_number in 0..(num_columns as usize) {
             let variable_name = variable_names
                 .and_then(|names| names.get(column_number)).cloned()
                 .unwrap_or_else(|| "[Unknown]".into());
 
+            let rank_accuracy = match num_candidates {
+                Some(num_candidates) => Some(match (&public_data, &public_candidates, &sensitivities) {
+                    // the exponential weights are only known when the data is available to the report
+                    (Some(data), Some(candidates), Some(sensitivities)) => exponential_median_weighted_rank_accuracy(
+                        &get_ith_column(candidates, column_number)?.iter().copied().collect::<Vec<Float>>(),
+                        &get_ith_column(data, column_number)?.iter().copied().collect::<Vec<Float>>(),
+                        sensitivities[column_number],
+                        get_epsilon(&privacy_usages[column_number])?, REPORT_ALPHA)?,
+                    _ => exponential_median_rank_accuracy(
+                        num_candidates, get_epsilon(&privacy_usages[column_number])?, REPORT_ALPHA)
+                }),
+                None => None
+            };
+
+            let mut argument = serde_json::json!({
+                "constraint": {
+                    "lowerbound": minimums[column_number],
+                    "upperbound": maximums[column_number]
+                }
+            });
+            if let Some(rank_accuracy) = rank_accuracy {
+                argument["rank_interval"] = serde_json::json!([-rank_accuracy, rank_accuracy]);
+            }
+
             releases.push(JSONRelease {
                 description: "DP release information".to_string(),
                 statistic: "DPMedian".to_string(),
@@ -92,7 +131,14 @@ impl Report for proto::DpMedian {
                     _ => return Err("maximum must be numeric".into())
                 },
                 privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
-                accuracy: None,
+                accuracy: rank_accuracy.map(|accuracy_value| crate::utilities::json::Accuracy {
+                    accuracy_value,
+                    alpha: REPORT_ALPHA,
+                    // the accuracy is in ranks, so the interval of values it spans depends on the data
+                    data_dependent: true,
+                    simultaneous: false,
+                }),
+                relative_accuracy: None,
                 submission: component.submission,
                 node_id,
                 postprocess: false,
@@ -100,15 +146,156 @@ impl Report for proto::DpMedian {
                     name: "".to_string(),
                     cite: "".to_string(),
                     mechanism: self.mechanism.clone(),
-                    argument: serde_json::json!({
-                        "constraint": {
-                            "lowerbound": minimums[column_number],
-                            "upperbound": maximums[column_number]
-                        }
-                    }),
+                    argument,
                 },
             });
         }
         Ok(Some(releases))
     }
 }
+
+/// Resolve the `automatic` mechanism to the exponential mechanism when candidates are provided.
+fn resolve_mechanism(mechanism: &str, properties: &NodeProperties) -> String {
+    if mechanism.to_lowercase().as_str() == "automatic" {
+        if properties.contains_key::<IndexKey>(&"candidates".into())
+        { "exponential" } else { "laplace" }.to_string()
+    } else {
+        mechanism.to_lowercase()
+    }
+}
+
+/// Number of ranks by which an exponential mechanism median may differ from the true median.
+///
+/// The utility of a candidate has sensitivity at most one, so the released candidate is within
+/// `2 ln(num_candidates / alpha) / epsilon` ranks of the median with probability at least `1 - alpha`.
+pub fn exponential_median_rank_accuracy(num_candidates: i64, epsilon: f64, alpha: f64) -> f64 {
+    2. * (num_candidates as f64 / alpha).ln() / epsilon
+}
+
+/// Number of ranks by which an exponential mechanism median may differ from the true median,
+/// derived from the exponential weights of the candidates on the data.
+///
+/// A candidate with `num_lt` records below it and `num_gt` above it is `|num_lt - num_gt| / 2` ranks from the median,
+/// and its utility falls one unit for every rank. The release is therefore within the rank distance of the closest
+/// candidate, plus the loss in utility of the exponential mechanism, of the median.
+pub fn exponential_median_weighted_rank_accuracy(
+    candidates: &[Float], data: &[Float], sensitivity: Float, epsilon: Float, alpha: Float,
+) -> Result<Float> {
+    let rank_distances = candidates.iter()
+        .map(|candidate| {
+            let num_lt = data.iter().filter(|v| *v < candidate).count() as Float;
+            let num_gt = data.iter().filter(|v| *v > candidate).count() as Float;
+            (num_lt - num_gt).abs() / 2.
+        })
+        .collect::<Vec<Float>>();
+
+    let closest = rank_distances.iter().copied().fold(Float::INFINITY, Float::min);
+    let utilities = rank_distances.iter().map(|distance| -distance).collect::<Vec<Float>>();
+    Ok(closest + exponential_utility_accuracy(&utilities, sensitivity, epsilon, alpha)?)
+}
+
+#[cfg(test)]
+mod test_dp_median {
+    use ndarray::arr1;
+
+    use crate::base::{test_data, Value};
+    use crate::components::Report;
+    use crate::components::resize::test_resize;
+    use crate::proto;
+
+    #[test]
+    fn test_accuracy_data_dependence() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        let candidates = analysis.literal()
+            .value(arr1(&[0., 2.5, 5., 7.5, 10.]).into_dyn().into())
+            .value_public(true).build();
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let component = proto::Component { arguments: None, variant: None, omit: false, submission: 0 };
+        let release: Value = arr1(&[5.]).into_dyn().into();
+        let data_property = analysis.properties(resized).unwrap();
+
+        let mean_releases = proto::DpMean {
+            implementation: "resize".to_string(),
+            centered: false,
+            mechanism: "Laplace".to_string(),
+            privacy_usage: vec![usage.clone()],
+        }.summarize(
+            &Some(analysis.privacy_definition.clone()), 0, &component, indexmap![],
+            indexmap!["data".into() => data_property.clone()],
+            &release, None).unwrap().unwrap();
+        assert!(!mean_releases[0].accuracy.as_ref().unwrap().data_dependent);
+
+        let median_releases = proto::DpMedian {
+            mechanism: "Exponential".to_string(),
+            interpolation: "midpoint".to_string(),
+            privacy_usage: vec![usage],
+        }.summarize(
+            &Some(analysis.privacy_definition.clone()), 0, &component, indexmap![],
+            indexmap![
+                "data".into() => data_property,
+                "candidates".into() => analysis.properties(candidates).unwrap()
+            ],
+            &release, None).unwrap().unwrap();
+        assert!(median_releases[0].accuracy.as_ref().unwrap().data_dependent);
+    }
+
+    #[test]
+    fn test_weighted_rank_accuracy_separated() {
+        // records are well-separated from all candidates except the median
+        let data = (1..=101).map(|v| v as f64).collect::<Vec<f64>>();
+        let candidates = [1., 51., 101.];
+
+        // the median candidate holds nearly all of the exponential weight
+        let accuracy = super::exponential_median_weighted_rank_accuracy(
+            &candidates, &data, 0.5, 1., 0.05).unwrap();
+        assert_eq!(accuracy, 0.);
+        assert!(accuracy < super::exponential_median_rank_accuracy(3, 1., 0.05));
+
+        // without a candidate at the median, the closest candidate is 25 ranks away
+        let accuracy = super::exponential_median_weighted_rank_accuracy(
+            &[1., 26., 101.], &data, 0.5, 1., 0.05).unwrap();
+        assert_eq!(accuracy, 25.);
+
+        // with little privacy budget, the far candidates hold enough weight to widen the interval
+        let accuracy = super::exponential_median_weighted_rank_accuracy(
+            &candidates, &data, 0.5, 0.001, 0.05).unwrap();
+        assert_eq!(accuracy, 50.);
+    }
+
+    #[test]
+    fn test_weighted_rank_accuracy_report() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        let candidates = analysis.literal()
+            .value(arr1(&[0., 5., 10.]).into_dyn().into())
+            .value_public(true).build();
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let component = proto::Component { arguments: None, variant: None, omit: false, submission: 0 };
+        let release: Value = arr1(&[5.]).into_dyn().into();
+        let data: Value = arr1(&[0., 1., 2., 3., 4., 5., 6., 7., 8., 9.]).into_dyn().into();
+        let candidates_value: Value = arr1(&[0., 5., 10.]).into_dyn().into();
+
+        let median = proto::DpMedian {
+            mechanism: "Exponential".to_string(),
+            interpolation: "midpoint".to_string(),
+            privacy_usage: vec![usage],
+        };
+        let releases = median.summarize(
+            &Some(analysis.privacy_definition.clone()), 0, &component,
+            indexmap!["data".into() => &data, "candidates".into() => &candidates_value],
+            indexmap![
+                "data".into() => analysis.properties(resized).unwrap(),
+                "candidates".into() => analysis.properties(candidates).unwrap()
+            ],
+            &release, None).unwrap().unwrap();
+
+        // 5 has five records below and four above, and the other candidates are at least four ranks further
+        let accuracy = releases[0].accuracy.as_ref().unwrap();
+        assert_eq!(accuracy.accuracy_value, 0.5);
+        assert!(accuracy.data_dependent);
+        assert_eq!(releases[0].algorithm_info.argument["rank_interval"], serde_json::json!([-0.5, 0.5]));
+    }
+}
@@ -44,6 +44,7 @@ impl Expandable for proto::DpMinimum {
 impl Report for proto::DpMinimum {
     fn summarize(
         &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
         node_id: u32,
         component: &proto::Component,
         _public_arguments: IndexMap<base::IndexKey, &Value>,
@@ -78,6 +79,7 @@ impl Report for proto::DpMinimum {
                 },
                 privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
                 accuracy: None,
+                relative_accuracy: None,
                 submission: component.submission,
                 node_id,
                 postprocess: false,
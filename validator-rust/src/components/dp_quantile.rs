@@ -3,6 +3,7 @@ use indexmap::map::IndexMap;
 use crate::{base, proto};
 use crate::base::{Array, IndexKey, NodeProperties, Value};
 use crate::components::{Expandable, Report};
+use crate::components::exponential_mechanism::get_public_candidates;
 use crate::errors::*;
 use crate::utilities::{array::get_ith_column, prepend, privacy::spread_privacy_usage};
 use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
@@ -36,6 +37,11 @@ impl Expandable for proto::DpQuantile {
             self.mechanism.to_lowercase()
         };
 
+        // the candidates are checked before the utilities are computed over them
+        if mechanism.as_str() == "exponential" {
+            get_public_candidates(properties)?;
+        }
+
         // quantile
         let mut quantile_args = indexmap![IndexKey::from("data") => data_id];
         if mechanism.as_str() == "exponential" {
@@ -57,7 +63,7 @@ impl Expandable for proto::DpQuantile {
 
         // sanitizing
         let mut sanitize_args = IndexMap::new();
-        if self.mechanism.to_lowercase().as_str() == "exponential" {
+        if mechanism.as_str() == "exponential" {
             sanitize_args.insert("utilities".into(), id_quantile);
             sanitize_args.insert("candidates".into(), *argument_ids.get::<IndexKey>(&"candidates".into())
                 .ok_or_else(|| Error::from("candidates is a required argument to DPQuantile when the exponential mechanism is used."))?);
@@ -67,15 +73,18 @@ impl Expandable for proto::DpQuantile {
 
         let variant = Some(match mechanism.as_str() {
             "laplace" => proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
-                privacy_usage: self.privacy_usage.clone()
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
             }),
             "gaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
                 privacy_usage: self.privacy_usage.clone(),
-                analytic: false
+                analytic: false,
+                max_noise_sigmas: 0.
             }),
             "analyticgaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
                 privacy_usage: self.privacy_usage.clone(),
-                analytic: true
+                analytic: true,
+                max_noise_sigmas: 0.
             }),
             "exponential" => proto::component::Variant::ExponentialMechanism(proto::ExponentialMechanism {
                 privacy_usage: self.privacy_usage.clone()
@@ -107,6 +116,7 @@ impl Expandable for proto::DpQuantile {
 impl Report for proto::DpQuantile {
     fn summarize(
         &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
         node_id: u32,
         component: &proto::Component,
         _public_arguments: IndexMap<base::IndexKey, &Value>,
@@ -142,6 +152,7 @@ impl Report for proto::DpQuantile {
                 },
                 privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
                 accuracy: None,
+                relative_accuracy: None,
                 submission: component.submission,
                 node_id,
                 postprocess: false,
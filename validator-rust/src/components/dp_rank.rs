@@ -0,0 +1,289 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{Array, ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::{Accuracy, Component, Expandable, Report};
+use crate::components::dp_mean::dp_mean_accuracy;
+use crate::errors::*;
+use crate::utilities::{array::get_ith_column, get_array_property, get_literal, prepend, require_public_argument, privacy::{get_epsilon, spread_privacy_usage}};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json, REPORT_ALPHA};
+
+impl Component for proto::DpRank {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = get_array_property(&properties, "data")?;
+
+        if !data_property.releasable {
+            data_property.assert_is_not_aggregated()?;
+        }
+        check_rank_arguments(&public_arguments, &data_property)?;
+
+        let num_columns = data_property.num_columns()?;
+
+        // the rank is the fraction of records below the threshold
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_columns: Some(num_columns),
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float((0..num_columns).map(|_| Some(0.)).collect()),
+                upper: Vector1DNull::Float((0..num_columns).map(|_| Some(1.)).collect()),
+            })),
+            ..ArrayProperties::dp_release(node_id, DataType::Float, data_property.group_id)
+        }).into())
+    }
+}
+
+impl Expandable for proto::DpRank {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+        let argument_ids = component.arguments();
+
+        check_rank_arguments(public_arguments, &get_array_property(properties, "data")?)?;
+
+        let get_id = |name: &str| argument_ids.get::<IndexKey>(&name.into()).cloned()
+            .ok_or_else(|| Error::from(format!("{} must be provided as an argument", name)));
+
+        // indicate the records below the threshold
+        maximum_id += 1;
+        let id_below = maximum_id;
+        expansion.computation_graph.insert(id_below, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => get_id("data")?,
+                "right".into() => get_id("threshold")?
+            ])),
+            variant: Some(proto::component::Variant::LessThan(proto::LessThan {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_below);
+
+        maximum_id += 1;
+        let id_below_float = maximum_id;
+        expansion.computation_graph.insert(id_below_float, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_below])),
+            variant: Some(proto::component::Variant::ToFloat(proto::ToFloat {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_below_float);
+
+        // indicator lower
+        maximum_id += 1;
+        let id_zero = maximum_id;
+        let (patch_node, release) = get_literal(0.0.into(), component.submission)?;
+        expansion.computation_graph.insert(id_zero, patch_node);
+        expansion.properties.insert(id_zero, infer_property(&release.value, None, id_zero)?);
+        expansion.releases.insert(id_zero, release);
+
+        // indicator upper
+        maximum_id += 1;
+        let id_one = maximum_id;
+        let (patch_node, release) = get_literal(1.0.into(), component.submission)?;
+        expansion.computation_graph.insert(id_one, patch_node);
+        expansion.properties.insert(id_one, infer_property(&release.value, None, id_one)?);
+        expansion.releases.insert(id_one, release);
+
+        // bound the indicators to [0, 1], so that the sum of indicators has the sensitivity of a count
+        maximum_id += 1;
+        let id_indicator = maximum_id;
+        expansion.computation_graph.insert(id_indicator, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_below_float,
+                "lower".into() => id_zero,
+                "upper".into() => id_one
+            ])),
+            variant: Some(proto::component::Variant::Clamp(proto::Clamp { null_policy: "Pass".to_string() })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_indicator);
+
+        // the mean of the indicators is the count below the threshold, divided by the known number of records
+        maximum_id += 1;
+        let id_rank = maximum_id;
+        expansion.computation_graph.insert(id_rank, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_indicator])),
+            variant: Some(proto::component::Variant::DpMean(proto::DpMean {
+                implementation: "resize".to_string(),
+                centered: false,
+                mechanism: self.mechanism.clone(),
+                privacy_usage: self.privacy_usage.clone(),
+            })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_rank);
+
+        // postprocess the noisy rank back into [0, 1]
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_rank,
+                "lower".into() => id_zero,
+                "upper".into() => id_one
+            ])),
+            variant: Some(proto::component::Variant::Clamp(proto::Clamp { null_policy: "Pass".to_string() })),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Accuracy for proto::DpRank {
+    /// The sensitivity is that of the mean of the indicators, one over the number of records,
+    /// so accuracies from the mechanism are in units of rank.
+    fn accuracy_to_privacy_usage(
+        &self,
+        accuracies: &proto::Accuracies,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        match self.mechanism.to_lowercase().as_str() {
+            "laplace" => proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
+            }.accuracy_to_privacy_usage(accuracies, public_arguments),
+            "gaussian" => proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(), analytic: false, max_noise_sigmas: 0.
+            }.accuracy_to_privacy_usage(accuracies, public_arguments),
+            _ => Ok(None)
+        }
+    }
+
+    fn privacy_usage_to_accuracy(
+        &self,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        match self.mechanism.to_lowercase().as_str() {
+            "laplace" => proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
+            }.privacy_usage_to_accuracy(public_arguments, alpha),
+            "gaussian" => proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(), analytic: false, max_noise_sigmas: 0.
+            }.privacy_usage_to_accuracy(public_arguments, alpha),
+            _ => Ok(None)
+        }
+    }
+}
+
+impl Report for proto::DpRank {
+    fn summarize(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = get_array_property(&properties, "data")?;
+
+        let mut releases = Vec::new();
+
+        let num_records = data_property.num_records()?;
+        let num_columns = data_property.num_columns()?;
+        let privacy_usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
+        let threshold = value_to_json(require_public_argument(&public_arguments, "threshold")?)?;
+
+        let release = release.ref_array()?.ref_float()?;
+
+        for (column_number, privacy_usage) in privacy_usages.iter().enumerate() {
+            let variable_name = variable_names
+                .and_then(|names| names.get(column_number)).cloned()
+                .unwrap_or_else(|| "[Unknown]".into());
+
+            let accuracy = if crate::utilities::json::Accuracy::is_known_for(&self.mechanism) {
+                Some(crate::utilities::json::Accuracy {
+                    accuracy_value: dp_mean_accuracy(
+                        0., 1., num_records,
+                        get_epsilon(privacy_usage)?, REPORT_ALPHA),
+                    alpha: REPORT_ALPHA,
+                    data_dependent: false,
+                    simultaneous: false,
+                })
+            } else { None };
+
+            releases.push(JSONRelease {
+                description: "DP release information".to_string(),
+                statistic: "DPRank".to_string(),
+                variables: serde_json::json!(variable_name.to_string()),
+                release_info: value_to_json(&get_ith_column(release, column_number)?.into())?,
+                privacy_loss: privacy_usage_to_json(privacy_usage),
+                accuracy,
+                relative_accuracy: None,
+                submission: component.submission,
+                node_id,
+                postprocess: false,
+                algorithm_info: AlgorithmInfo {
+                    name: "".to_string(),
+                    cite: "".to_string(),
+                    mechanism: self.mechanism.clone(),
+                    argument: serde_json::json!({
+                        "n": num_records,
+                        "threshold": threshold
+                    }),
+                },
+            });
+        }
+        Ok(Some(releases))
+    }
+}
+
+/// Check that the data has a known number of records, and that the threshold is public and shares the type of the data.
+fn check_rank_arguments(
+    public_arguments: &IndexMap<IndexKey, &Value>, data_property: &ArrayProperties,
+) -> Result<()> {
+    data_property.num_records()
+        .map_err(prepend("data: rank requires a known number of records:"))?;
+
+    match (&data_property.data_type, require_public_argument(public_arguments, "threshold")?.ref_array()?) {
+        (DataType::Float, Array::Float(_)) | (DataType::Int, Array::Int(_)) => Ok(()),
+        (DataType::Float, _) | (DataType::Int, _) => Err("threshold: atomic type must match the data".into()),
+        _ => Err("data: atomic type must be numeric".into())
+    }
+}
+
+#[cfg(test)]
+mod test_dp_rank {
+    use crate::base::test_data;
+    use crate::components::resize::test_resize;
+
+    #[test]
+    fn test_dp_rank() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(1.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let threshold = analysis.literal().value(0.5.into()).value_public(true).build();
+        let usage = test_data::privacy_usage(1., 0.);
+        let rank = analysis.dp_rank(resized, threshold, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let rank_property = analysis.properties(rank).unwrap().array().unwrap().clone();
+        assert_eq!(rank_property.num_records, Some(1));
+        assert_eq!(rank_property.lower_float().unwrap(), vec![0.]);
+        assert_eq!(rank_property.upper_float().unwrap(), vec![1.]);
+
+        // the threshold must share the type of the data
+        let int_threshold = analysis.literal().value(1.into()).value_public(true).build();
+        let rank = analysis.dp_rank(resized, int_threshold, vec![]).build();
+        assert!(analysis.properties(rank).is_err());
+    }
+}
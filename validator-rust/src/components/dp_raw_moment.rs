@@ -49,15 +49,18 @@ impl Expandable for proto::DpRawMoment {
         let mut arguments = indexmap!["data".into() => id_moment];
         let variant = Some(match mechanism.as_str() {
             "laplace" => proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
-                privacy_usage: self.privacy_usage.clone()
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
             }),
             "gaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
                 privacy_usage: self.privacy_usage.clone(),
-                analytic: false
+                analytic: false,
+                max_noise_sigmas: 0.
             }),
             "analyticgaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
                 privacy_usage: self.privacy_usage.clone(),
-                analytic: true
+                analytic: true,
+                max_noise_sigmas: 0.
             }),
             "snapping" => {
                 argument_ids.get::<IndexKey>(&"lower".into())
@@ -86,6 +89,7 @@ impl Expandable for proto::DpRawMoment {
 impl Report for proto::DpRawMoment {
     fn summarize(
         &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
         node_id: u32,
         component: &proto::Component,
         _public_arguments: IndexMap<base::IndexKey, &Value>,
@@ -122,6 +126,7 @@ impl Report for proto::DpRawMoment {
                 },
                 privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
                 accuracy: None,
+                relative_accuracy: None,
                 submission: component.submission,
                 node_id,
                 postprocess: false,
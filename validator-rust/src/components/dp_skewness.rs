@@ -0,0 +1,235 @@
+use indexmap::map::IndexMap;
+use ndarray::arr0;
+
+use crate::{base, proto, Warnable};
+use crate::base::{Array, ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Report};
+use crate::errors::*;
+use crate::utilities::{array::get_ith_column, get_array_property, get_literal, prepend, privacy::spread_privacy_usage};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+
+impl Component for proto::DpSkewness {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = get_array_property(&properties, "data")?;
+
+        if !data_property.releasable {
+            data_property.assert_is_not_aggregated()?;
+        }
+        if data_property.data_type != DataType::Float {
+            return Err("data: atomic type must be float".into())
+        }
+        get_bounds(&data_property)?;
+
+        let num_columns = data_property.num_columns()?;
+
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_columns: Some(num_columns),
+            // the noisy variance may be arbitrarily close to zero, so the ratio is unbounded
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float((0..num_columns).map(|_| None).collect()),
+                upper: Vector1DNull::Float((0..num_columns).map(|_| None).collect()),
+            })),
+            ..ArrayProperties::dp_release(node_id, DataType::Float, data_property.group_id)
+        }).into())
+    }
+}
+
+impl Expandable for proto::DpSkewness {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        _properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        use proto::component::Variant;
+        let mut expansion = base::ComponentExpansion::default();
+
+        let id_data = *component.arguments().get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+
+        // the budget is split evenly between the first three raw moments
+        let privacy_usage = self.privacy_usage.iter().cloned()
+            .map(|usage| usage / 3.)
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+
+        // literals used by the central moment formulas
+        let mut literal_ids = Vec::new();
+        for value in [0., 1.5, 2., 3.] {
+            maximum_id += 1;
+            let id_literal = maximum_id;
+            let value = Value::Array(Array::Float(arr0(value).into_dyn()));
+            expansion.properties.insert(id_literal, infer_property(&value, None, id_literal)?);
+            let (patch_node, release) = get_literal(value, component.submission)?;
+            expansion.computation_graph.insert(id_literal, patch_node);
+            expansion.releases.insert(id_literal, release);
+            literal_ids.push(id_literal);
+        }
+        let (id_zero, id_three_halves, id_two, id_three) = (literal_ids[0], literal_ids[1], literal_ids[2], literal_ids[3]);
+
+        let mut push_node = |expansion: &mut base::ComponentExpansion, variant: Variant, arguments: IndexMap<IndexKey, u32>| {
+            maximum_id += 1;
+            expansion.computation_graph.insert(maximum_id, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(arguments)),
+                variant: Some(variant),
+                omit: true,
+                submission: component.submission,
+            });
+            expansion.traversal.push(maximum_id);
+            maximum_id
+        };
+
+        // dp raw moments
+        let mut moment_ids = Vec::new();
+        for order in 1..=3 {
+            moment_ids.push(push_node(&mut expansion, Variant::DpRawMoment(proto::DpRawMoment {
+                order,
+                mechanism: self.mechanism.clone(),
+                privacy_usage: privacy_usage.clone(),
+            }), indexmap!["data".into() => id_data]));
+        }
+        let (id_m1, id_m2, id_m3) = (moment_ids[0], moment_ids[1], moment_ids[2]);
+
+        let binary = |left: u32, right: u32| indexmap!["left".into() => left, "right".into() => right];
+
+        // variance, floored at zero, as the noisy moments may imply a negative variance
+        let id_m1_squared = push_node(&mut expansion, Variant::Multiply(proto::Multiply {}), binary(id_m1, id_m1));
+        let id_variance = push_node(&mut expansion, Variant::Subtract(proto::Subtract {}), binary(id_m2, id_m1_squared));
+        let id_variance = push_node(&mut expansion, Variant::RowMax(proto::RowMax {}), binary(id_variance, id_zero));
+
+        // third central moment, m3 - 3 m1 m2 + 2 m1^3
+        let id_m1_m2 = push_node(&mut expansion, Variant::Multiply(proto::Multiply {}), binary(id_m1, id_m2));
+        let id_m1_m2 = push_node(&mut expansion, Variant::Multiply(proto::Multiply {}), binary(id_three, id_m1_m2));
+        let id_m1_cubed = push_node(&mut expansion, Variant::Multiply(proto::Multiply {}), binary(id_m1_squared, id_m1));
+        let id_m1_cubed = push_node(&mut expansion, Variant::Multiply(proto::Multiply {}), binary(id_two, id_m1_cubed));
+        let id_central = push_node(&mut expansion, Variant::Subtract(proto::Subtract {}), binary(id_m3, id_m1_m2));
+        let id_central = push_node(&mut expansion, Variant::Add(proto::Add {}), binary(id_central, id_m1_cubed));
+
+        // standardize by the variance to the power of 3/2
+        let id_scale = push_node(&mut expansion, Variant::Power(proto::Power {}), indexmap![
+            "data".into() => id_variance,
+            "radical".into() => id_three_halves
+        ]);
+
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(binary(id_central, id_scale))),
+            variant: Some(Variant::Divide(proto::Divide {})),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpSkewness {
+    fn summarize(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = get_array_property(&properties, "data")?;
+
+        let mut releases = Vec::new();
+
+        let (lower, upper) = get_bounds(&data_property)?;
+        let num_columns = data_property.num_columns()?;
+        let privacy_usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
+
+        let release = release.ref_array()?.ref_float()?;
+
+        for column_number in 0..(num_columns as usize) {
+            let variable_name = variable_names
+                .and_then(|names| names.get(column_number)).cloned()
+                .unwrap_or_else(|| "[Unknown]".into());
+
+            releases.push(JSONRelease {
+                description: "DP release information".to_string(),
+                statistic: "DPSkewness".to_string(),
+                variables: serde_json::json!(variable_name.to_string()),
+                release_info: value_to_json(&get_ith_column(release, column_number)?.into())?,
+                privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
+                accuracy: None,
+                relative_accuracy: None,
+                submission: component.submission,
+                node_id,
+                postprocess: false,
+                algorithm_info: AlgorithmInfo {
+                    name: "".to_string(),
+                    cite: "".to_string(),
+                    mechanism: self.mechanism.clone(),
+                    argument: serde_json::json!({
+                        "constraint": {
+                            "lowerbound": lower[column_number],
+                            "upperbound": upper[column_number]
+                        }
+                    }),
+                },
+            });
+        }
+        Ok(Some(releases))
+    }
+}
+
+/// Bounds of the data, which the raw moments require.
+fn get_bounds(data_property: &ArrayProperties) -> Result<(Vec<f64>, Vec<f64>)> {
+    Ok((data_property.lower_float()
+            .map_err(prepend("data: skewness requires clamped data:"))?,
+        data_property.upper_float()
+            .map_err(prepend("data: skewness requires clamped data:"))?))
+}
+
+#[cfg(test)]
+mod test_dp_skewness {
+    use crate::base::test_data;
+    use crate::components::literal::test_literal;
+    use crate::components::resize::test_resize;
+    use crate::proto;
+    use crate::utilities::privacy::get_epsilon;
+
+    #[test]
+    fn test_dp_skewness() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        let source = *analysis.components.keys().min().unwrap();
+        test_literal::privatize(&mut analysis, source);
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let usage = test_data::privacy_usage(1.5, 0.);
+        let skewness = analysis.dp_skewness(resized, vec![usage]).mechanism("Laplace".to_string()).build();
+
+        let skewness_property = analysis.properties(skewness).unwrap().array().unwrap().clone();
+        assert_eq!(skewness_property.num_records, Some(1));
+        assert!(skewness_property.releasable);
+
+        // the budget is split across the three raw moments, and the combination is postprocessing
+        let mut computation_graph = analysis.components.clone();
+        let mut release = analysis.release.clone();
+        crate::utilities::propagate_properties(
+            &Some(analysis.privacy_definition.clone()),
+            &mut computation_graph, &mut release, None, false).unwrap();
+        assert_eq!(computation_graph.values()
+            .filter(|component| matches!(component.variant, Some(proto::component::Variant::LaplaceMechanism(_))))
+            .count(), 3);
+
+        let privacy_usage = crate::compute_privacy_usage(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap();
+        assert!((get_epsilon(&privacy_usage).unwrap() - 1.5).abs() < 1e-10);
+    }
+}
@@ -0,0 +1,213 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, NodeProperties, Value, ValueProperties};
+use crate::components::{Component, Expandable, Report};
+use crate::components::partition::make_dense_partition_keys;
+use crate::errors::*;
+use crate::utilities::{get_array_property, get_literal, privacy::spread_privacy_usage, prepend};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+
+impl Component for proto::DpStratifiedCount {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let strata = get_strata(&properties)?;
+        let data_type = if self.mechanism.to_lowercase() == "simplegeometric" { DataType::Int } else { DataType::Float };
+
+        // one count per stratum
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_records: Some(strata.len() as i64),
+            dataset_id: Some(node_id as i64),
+            is_not_empty: !strata.is_empty(),
+            ..ArrayProperties::dp_release(node_id, data_type, vec![])
+        }).into())
+    }
+}
+
+impl Expandable for proto::DpStratifiedCount {
+    /// Expand into a partition of the data by the strata, a dp count of each partition, and a union of the counts.
+    ///
+    /// Privacy accounting applies parallel composition to the dependents of the partition,
+    /// so the usage of the release is the usage of a single stratum.
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let strata = get_strata(properties)?;
+        let argument_ids = component.arguments();
+        let data_id = *argument_ids.get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+        let by_id = *argument_ids.get::<IndexKey>(&"by".into())
+            .ok_or_else(|| Error::from("by must be provided as an argument"))?;
+
+        // partition
+        maximum_id += 1;
+        let id_partition = maximum_id;
+        expansion.computation_graph.insert(id_partition, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => data_id,
+                "by".into() => by_id
+            ])),
+            variant: Some(proto::component::Variant::Partition(proto::Partition {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_partition);
+
+        let count_ids = strata.into_iter()
+            .map(|stratum| {
+                // name of the partition
+                maximum_id += 1;
+                let id_name = maximum_id;
+                let (patch_node, release) = get_literal(Value::from_index_key(stratum.clone())?, component.submission)?;
+                expansion.computation_graph.insert(id_name, patch_node);
+                expansion.properties.insert(id_name, infer_property(&release.value, None, id_name)?);
+                expansion.releases.insert(id_name, release);
+
+                // index into the partition
+                maximum_id += 1;
+                let id_index = maximum_id;
+                expansion.computation_graph.insert(id_index, proto::Component {
+                    arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                        "data".into() => id_partition,
+                        "names".into() => id_name
+                    ])),
+                    variant: Some(proto::component::Variant::Index(proto::Index {})),
+                    omit: true,
+                    submission: component.submission,
+                });
+                expansion.traversal.push(id_index);
+
+                // count the partition with the full usage
+                maximum_id += 1;
+                let id_count = maximum_id;
+                expansion.computation_graph.insert(id_count, proto::Component {
+                    arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_index])),
+                    variant: Some(proto::component::Variant::DpCount(proto::DpCount {
+                        distinct: false,
+                        mechanism: self.mechanism.clone(),
+                        privacy_usage: self.privacy_usage.clone(),
+                    })),
+                    omit: true,
+                    submission: component.submission,
+                });
+                expansion.traversal.push(id_count);
+
+                Ok((stratum, id_count))
+            })
+            .collect::<Result<IndexMap<IndexKey, u32>>>()?;
+
+        // stack the counts into a vector
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(count_ids)),
+            variant: Some(proto::component::Variant::Union(proto::Union { flatten: true })),
+            omit: component.omit,
+            submission: component.submission,
+        });
+        expansion.traversal.push(component_id);
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpStratifiedCount {
+    fn summarize(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let strata = get_strata(&properties)?;
+
+        // the usage of each stratum is the usage of the entire release
+        let privacy_usage = spread_privacy_usage(&self.privacy_usage, 1)?.remove(0);
+
+        let variable_name = variable_names
+            .and_then(|names| names.first()).cloned()
+            .unwrap_or_else(|| "[Unknown]".into());
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPStratifiedCount".to_string(),
+            variables: serde_json::json!(variable_name.to_string()),
+            release_info: value_to_json(release)?,
+            privacy_loss: privacy_usage_to_json(&privacy_usage),
+            accuracy: None,
+            relative_accuracy: None,
+            submission: component.submission,
+            node_id,
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "".to_string(),
+                cite: "".to_string(),
+                mechanism: self.mechanism.clone(),
+                argument: serde_json::json!({
+                    "strata": strata.iter().map(|stratum| stratum.to_string()).collect::<Vec<String>>()
+                }),
+            },
+        }]))
+    }
+}
+
+/// The keys of the strata, in the order the counts are released.
+///
+/// The strata must be known ahead of time, so that their number and membership do not depend on the data.
+pub(crate) fn get_strata(properties: &NodeProperties) -> Result<Vec<IndexKey>> {
+    let data_property = get_array_property(properties, "data")?;
+    data_property.assert_is_not_aggregated()?;
+
+    let by_property = get_array_property(properties, "by")?;
+    by_property.assert_is_not_aggregated().map_err(prepend("by:"))?;
+    if by_property.num_columns().map_err(prepend("by:"))? != 1 {
+        return Err("by: must contain one column".into())
+    }
+    let categories = by_property.categories()
+        .map_err(prepend("by:"))?;
+    make_dense_partition_keys(categories, by_property.dimensionality)
+}
+
+#[cfg(test)]
+mod test_dp_stratified_count {
+    use crate::base::{test_data, Value};
+    use crate::components::clamp::test_clamp;
+    use crate::utilities::privacy::get_epsilon;
+
+    #[test]
+    fn test_parallel_composition() {
+        // four strata, and the null stratum that clamping appends
+        let (mut analysis, clamped) = test_clamp::utilities::analysis_i64_cat(
+            test_data::array1d_i64_10_uniform(),
+            Value::Jagged(vec![vec![1i64, 2, 3, 5]].into()), Some((-1).into()));
+
+        let usage = test_data::privacy_usage(0.5, 0.);
+        let counts = analysis.dp_stratified_count(clamped, clamped, vec![usage]).build();
+
+        let properties = analysis.properties(counts).unwrap().array().unwrap().clone();
+        assert_eq!(properties.num_records, Some(5));
+        assert!(properties.releasable && properties.noised && properties.data_derived);
+
+        // each record belongs to one stratum, so the total usage is that of a single stratum
+        let total_usage = crate::compute_privacy_usage(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap();
+        assert_eq!(get_epsilon(&total_usage).unwrap(), 0.5);
+    }
+}
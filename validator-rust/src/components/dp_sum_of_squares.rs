@@ -0,0 +1,276 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::{Accuracy, Component, Expandable, Report};
+use crate::errors::*;
+use crate::utilities::{array::get_ith_column, get_array_property, get_literal, prepend, privacy::spread_privacy_usage};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+
+impl Component for proto::DpSumOfSquares {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = get_array_property(&properties, "data")?;
+
+        if !data_property.releasable {
+            data_property.assert_is_not_aggregated()?;
+        }
+        if data_property.data_type != DataType::Float {
+            return Err("data: atomic type must be float".into())
+        }
+
+        let (_, upper) = get_squared_bounds(&data_property)?;
+        let num_columns = data_property.num_columns()?;
+
+        // the release is postprocessed to be nonnegative, and is bounded above when the number of records is known
+        let upper = data_property.num_records.map(|num_records| upper.into_iter()
+            .map(|bound| Some(bound * num_records as f64)).collect())
+            .unwrap_or_else(|| vec![None; num_columns as usize]);
+
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_columns: Some(num_columns),
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(vec![Some(0.); num_columns as usize]),
+                upper: Vector1DNull::Float(upper),
+            })),
+            ..ArrayProperties::dp_release(node_id, DataType::Float, data_property.group_id)
+        }).into())
+    }
+}
+
+impl Expandable for proto::DpSumOfSquares {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        _properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+        let argument_ids = component.arguments();
+
+        let id_data = *argument_ids.get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+
+        // exponent
+        maximum_id += 1;
+        let id_two = maximum_id;
+        let (patch_node, release) = get_literal(2.0.into(), component.submission)?;
+        expansion.computation_graph.insert(id_two, patch_node);
+        expansion.properties.insert(id_two, infer_property(&release.value, None, id_two)?);
+        expansion.releases.insert(id_two, release);
+
+        // square the data. The bounds of the squares are derived from the bounds of the data
+        maximum_id += 1;
+        let id_squared = maximum_id;
+        expansion.computation_graph.insert(id_squared, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_data,
+                "radical".into() => id_two
+            ])),
+            variant: Some(proto::component::Variant::Power(proto::Power {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_squared);
+
+        // dp sum of the squared data
+        maximum_id += 1;
+        let id_dp_sum = maximum_id;
+        expansion.computation_graph.insert(id_dp_sum, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_squared])),
+            variant: Some(proto::component::Variant::DpSum(proto::DpSum {
+                mechanism: self.mechanism.clone(),
+                privacy_usage: self.privacy_usage.clone(),
+            })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_dp_sum);
+
+        // sum of squares lower
+        maximum_id += 1;
+        let id_zero = maximum_id;
+        let (patch_node, release) = get_literal(0.0.into(), component.submission)?;
+        expansion.computation_graph.insert(id_zero, patch_node);
+        expansion.properties.insert(id_zero, infer_property(&release.value, None, id_zero)?);
+        expansion.releases.insert(id_zero, release);
+
+        // postprocess the noisy sum of squares to be nonnegative
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => id_dp_sum,
+                "right".into() => id_zero
+            ])),
+            variant: Some(proto::component::Variant::RowMax(proto::RowMax {})),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Accuracy for proto::DpSumOfSquares {
+    fn accuracy_to_privacy_usage(
+        &self,
+        accuracies: &proto::Accuracies,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        match self.mechanism.to_lowercase().as_str() {
+            "laplace" => proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
+            }.accuracy_to_privacy_usage(accuracies, public_arguments),
+            "gaussian" => proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(), analytic: false, max_noise_sigmas: 0.
+            }.accuracy_to_privacy_usage(accuracies, public_arguments),
+            _ => Ok(None)
+        }
+    }
+
+    fn privacy_usage_to_accuracy(
+        &self,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        match self.mechanism.to_lowercase().as_str() {
+            "laplace" => proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
+            }.privacy_usage_to_accuracy(public_arguments, alpha),
+            "gaussian" => proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(), analytic: false, max_noise_sigmas: 0.
+            }.privacy_usage_to_accuracy(public_arguments, alpha),
+            _ => Ok(None)
+        }
+    }
+}
+
+impl Report for proto::DpSumOfSquares {
+    fn summarize(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = get_array_property(&properties, "data")?;
+
+        let mut releases = Vec::new();
+
+        let (lower, upper) = get_squared_bounds(&data_property)?;
+        let num_columns = data_property.num_columns()?;
+        let privacy_usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
+
+        let release = release.ref_array()?.ref_float()?;
+
+        for column_number in 0..(num_columns as usize) {
+            let variable_name = variable_names
+                .and_then(|names| names.get(column_number)).cloned()
+                .unwrap_or_else(|| "[Unknown]".into());
+
+            releases.push(JSONRelease {
+                description: "DP release information".to_string(),
+                statistic: "DPSumOfSquares".to_string(),
+                variables: serde_json::json!(variable_name.to_string()),
+                release_info: value_to_json(&get_ith_column(release, column_number)?.into())?,
+                privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
+                accuracy: None,
+                relative_accuracy: None,
+                submission: component.submission,
+                node_id,
+                postprocess: false,
+                algorithm_info: AlgorithmInfo {
+                    name: "".to_string(),
+                    cite: "".to_string(),
+                    mechanism: self.mechanism.clone(),
+                    argument: serde_json::json!({
+                        "constraint": {
+                            "lowerbound": lower[column_number],
+                            "upperbound": upper[column_number]
+                        }
+                    }),
+                },
+            });
+        }
+        Ok(Some(releases))
+    }
+}
+
+/// Bounds on the contribution of each record to the sum of squares.
+///
+/// Squared bounds straddling zero are bounded below by zero.
+fn get_squared_bounds(data_property: &ArrayProperties) -> Result<(Vec<f64>, Vec<f64>)> {
+    let lower = data_property.lower_float()
+        .map_err(prepend("data: sum of squares requires clamped data:"))?;
+    let upper = data_property.upper_float()
+        .map_err(prepend("data: sum of squares requires clamped data:"))?;
+
+    Ok(lower.into_iter().zip(upper.into_iter())
+        .map(|(min, max)| {
+            let (min_squared, max_squared) = (min.powi(2), max.powi(2));
+            if min <= 0. && 0. <= max {
+                (0., min_squared.max(max_squared))
+            } else {
+                (min_squared.min(max_squared), min_squared.max(max_squared))
+            }
+        })
+        .unzip())
+}
+
+#[cfg(test)]
+mod test_dp_sum_of_squares {
+    use crate::base::{SensitivitySpace, test_data, ValueProperties};
+    use crate::components::impute::test_impute;
+    use crate::components::Sensitivity;
+    use crate::proto;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_dp_sum_of_squares() {
+        let (mut analysis, imputed) = test_impute::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), Some((-3.0).into()), Some(2.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+        analysis.privacy_definition.neighboring = proto::privacy_definition::Neighboring::AddRemove as i32;
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let sum_of_squares = analysis.dp_sum_of_squares(imputed, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let mut computation_graph = analysis.components.clone();
+        let mut release = analysis.release.clone();
+        let (properties, _) = crate::utilities::propagate_properties(
+            &Some(analysis.privacy_definition.clone()),
+            &mut computation_graph, &mut release, None, true).unwrap();
+
+        // each record contributes at most max(min^2, max^2)
+        let (id_squared, _) = computation_graph.iter()
+            .find(|(_, component)| matches!(component.variant, Some(proto::component::Variant::Power(_))))
+            .unwrap();
+        let squared_property = properties.get(id_squared).unwrap().array().unwrap().clone();
+        let sensitivity = proto::Sum {}.compute_sensitivity(
+            &analysis.privacy_definition,
+            &indexmap!["data".into() => ValueProperties::Array(squared_property)],
+            &SensitivitySpace::KNorm(1)).unwrap();
+        assert_eq!(sensitivity.ref_array().unwrap().first_float().unwrap(), 9.);
+
+        // the release is postprocessed to be nonnegative
+        let sum_of_squares_property = analysis.properties(sum_of_squares).unwrap().array().unwrap().clone();
+        assert_eq!(sum_of_squares_property.lower_float().unwrap(), vec![0.]);
+        assert!(computation_graph.values()
+            .any(|component| matches!(component.variant, Some(proto::component::Variant::RowMax(_)))));
+    }
+}
@@ -0,0 +1,416 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Report};
+use crate::errors::*;
+use crate::utilities::{get_array_property, get_literal, prepend, privacy::spread_privacy_usage};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json};
+
+impl Component for proto::DpTrimmedMean {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = get_array_property(&properties, "data")?;
+
+        if !data_property.releasable {
+            data_property.assert_is_not_aggregated()?;
+        }
+        check_trimmed_mean(self, &data_property, &properties)?;
+
+        let (lower, upper) = get_trimmed_mean_bounds(&data_property)?;
+
+        // the mean over any subset of the data is within the data bounds
+        Ok(ValueProperties::Array(ArrayProperties {
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(vec![Some(lower)]),
+                upper: Vector1DNull::Float(vec![Some(upper)]),
+            })),
+            ..ArrayProperties::dp_release(node_id, DataType::Float, data_property.group_id)
+        }).into())
+    }
+}
+
+impl Expandable for proto::DpTrimmedMean {
+    /// Expand into dp trimming bounds, and a ratio of a dp sum and a dp count over the records within the bounds.
+    ///
+    /// The trimming bounds are the dp `trim` and `1 - trim` quantiles.
+    /// Since the bounds are releases, a record is masked in or out regardless of the other records.
+    /// Then a neighboring dataset changes the masked sum by at most the sum sensitivity over the data bounds,
+    /// and the masked count by at most one.
+    /// The trimming bounds are not used as bounds on the masked data,
+    /// because bounds derived from private data leak information that the sensitivity does not account for.
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+        let argument_ids = component.arguments();
+
+        let data_property = get_array_property(properties, "data")?;
+        check_trimmed_mean(self, &data_property, properties)?;
+
+        let id_data = *argument_ids.get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+
+        // the budget is split evenly between the two trimming bounds, the sum and the count
+        let privacy_usage = self.privacy_usage.iter().cloned()
+            .map(|usage| usage / 4.)
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+
+        let quantile_mechanism = if argument_ids.contains_key::<IndexKey>(&"candidates".into()) {
+            "Exponential".to_string()
+        } else { self.mechanism.clone() };
+
+        // dp trimming bounds
+        let mut trimming_ids = Vec::new();
+        for alpha in &[self.trim, 1. - self.trim] {
+            maximum_id += 1;
+            let id_quantile = maximum_id;
+            expansion.computation_graph.insert(id_quantile, proto::Component {
+                arguments: Some(proto::ArgumentNodeIds::new(argument_ids.clone())),
+                variant: Some(proto::component::Variant::DpQuantile(proto::DpQuantile {
+                    alpha: *alpha,
+                    mechanism: quantile_mechanism.clone(),
+                    privacy_usage: privacy_usage.clone(),
+                    interpolation: "midpoint".to_string(),
+                })),
+                omit: true,
+                submission: component.submission,
+            });
+            expansion.traversal.push(id_quantile);
+            trimming_ids.push(id_quantile);
+        }
+
+        // records below the lower trimming bound
+        maximum_id += 1;
+        let id_below = maximum_id;
+        expansion.computation_graph.insert(id_below, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => id_data,
+                "right".into() => trimming_ids[0]
+            ])),
+            variant: Some(proto::component::Variant::LessThan(proto::LessThan {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_below);
+
+        maximum_id += 1;
+        let id_not_below = maximum_id;
+        expansion.computation_graph.insert(id_not_below, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_below])),
+            variant: Some(proto::component::Variant::Negate(proto::Negate {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_not_below);
+
+        // records above the upper trimming bound
+        maximum_id += 1;
+        let id_above = maximum_id;
+        expansion.computation_graph.insert(id_above, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => id_data,
+                "right".into() => trimming_ids[1]
+            ])),
+            variant: Some(proto::component::Variant::GreaterThan(proto::GreaterThan {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_above);
+
+        maximum_id += 1;
+        let id_not_above = maximum_id;
+        expansion.computation_graph.insert(id_not_above, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_above])),
+            variant: Some(proto::component::Variant::Negate(proto::Negate {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_not_above);
+
+        // records within the trimming bounds, inclusive
+        maximum_id += 1;
+        let id_mask = maximum_id;
+        expansion.computation_graph.insert(id_mask, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => id_not_below,
+                "right".into() => id_not_above
+            ])),
+            variant: Some(proto::component::Variant::LogicalAnd(proto::And {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_mask);
+
+        // dp sum over the mask
+        maximum_id += 1;
+        let id_dp_sum = maximum_id;
+        expansion.computation_graph.insert(id_dp_sum, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_data,
+                "mask".into() => id_mask
+            ])),
+            variant: Some(proto::component::Variant::DpConditionalSum(proto::DpConditionalSum {
+                mechanism: self.mechanism.clone(),
+                privacy_usage: privacy_usage.clone(),
+            })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_dp_sum);
+
+        // records within the trimming bounds
+        maximum_id += 1;
+        let id_filtered = maximum_id;
+        expansion.computation_graph.insert(id_filtered, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_data,
+                "mask".into() => id_mask
+            ])),
+            variant: Some(proto::component::Variant::Filter(proto::Filter {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_filtered);
+
+        // the filtered count is no greater than the number of records
+        maximum_id += 1;
+        let id_count_upper = maximum_id;
+        let (patch_node, release) = get_literal(data_property.num_records()?.into(), component.submission)?;
+        expansion.computation_graph.insert(id_count_upper, patch_node);
+        expansion.properties.insert(id_count_upper, infer_property(&release.value, None, id_count_upper)?);
+        expansion.releases.insert(id_count_upper, release);
+
+        // dp count over the mask
+        maximum_id += 1;
+        let id_dp_count = maximum_id;
+        expansion.computation_graph.insert(id_dp_count, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_filtered,
+                "upper".into() => id_count_upper
+            ])),
+            variant: Some(proto::component::Variant::DpCount(proto::DpCount {
+                distinct: false,
+                mechanism: "SimpleGeometric".to_string(),
+                privacy_usage,
+            })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_dp_count);
+
+        // to float
+        maximum_id += 1;
+        let id_count_float = maximum_id;
+        expansion.computation_graph.insert(id_count_float, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_dp_count])),
+            variant: Some(proto::component::Variant::ToFloat(proto::ToFloat {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_count_float);
+
+        // one
+        maximum_id += 1;
+        let id_one = maximum_id;
+        let (patch_node, release) = get_literal(1.0.into(), component.submission)?;
+        expansion.computation_graph.insert(id_one, patch_node);
+        expansion.properties.insert(id_one, infer_property(&release.value, None, id_one)?);
+        expansion.releases.insert(id_one, release);
+
+        // set lower bound on dp count
+        maximum_id += 1;
+        let id_count_floor = maximum_id;
+        expansion.computation_graph.insert(id_count_floor, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => id_count_float,
+                "right".into() => id_one
+            ])),
+            variant: Some(proto::component::Variant::RowMax(proto::RowMax {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_count_floor);
+
+        // divide
+        maximum_id += 1;
+        let id_ratio = maximum_id;
+        expansion.computation_graph.insert(id_ratio, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => id_dp_sum,
+                "right".into() => id_count_floor
+            ])),
+            variant: Some(proto::component::Variant::Divide(proto::Divide {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_ratio);
+
+        // the noisy ratio may leave the data bounds, so it is clamped back into them
+        let (lower, upper) = get_trimmed_mean_bounds(&data_property)?;
+
+        maximum_id += 1;
+        let id_lower = maximum_id;
+        let (patch_node, release) = get_literal(lower.into(), component.submission)?;
+        expansion.computation_graph.insert(id_lower, patch_node);
+        expansion.properties.insert(id_lower, infer_property(&release.value, None, id_lower)?);
+        expansion.releases.insert(id_lower, release);
+
+        maximum_id += 1;
+        let id_upper = maximum_id;
+        let (patch_node, release) = get_literal(upper.into(), component.submission)?;
+        expansion.computation_graph.insert(id_upper, patch_node);
+        expansion.properties.insert(id_upper, infer_property(&release.value, None, id_upper)?);
+        expansion.releases.insert(id_upper, release);
+
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_ratio,
+                "lower".into() => id_lower,
+                "upper".into() => id_upper
+            ])),
+            variant: Some(proto::component::Variant::Clamp(proto::Clamp { null_policy: "Pass".to_string() })),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpTrimmedMean {
+    fn summarize(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = get_array_property(&properties, "data")?;
+
+        let (lower, upper) = get_trimmed_mean_bounds(&data_property)?;
+        let privacy_usages = spread_privacy_usage(&self.privacy_usage, 1)?;
+
+        let variable_name = variable_names
+            .and_then(|names| names.first()).cloned()
+            .unwrap_or_else(|| "[Unknown]".into());
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPTrimmedMean".to_string(),
+            variables: serde_json::json!(variable_name.to_string()),
+            release_info: serde_json::json!(release.as_scalar_f64().map_err(prepend("release:"))?),
+            privacy_loss: privacy_usage_to_json(&privacy_usages[0].clone()),
+            accuracy: None,
+            relative_accuracy: None,
+            submission: component.submission,
+            node_id,
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "".to_string(),
+                cite: "".to_string(),
+                mechanism: self.mechanism.clone(),
+                argument: serde_json::json!({
+                    "trim": self.trim,
+                    "constraint": {
+                        "lowerbound": lower,
+                        "upperbound": upper
+                    }
+                }),
+            },
+        }]))
+    }
+}
+
+/// The data must be a single clamped float column with a known number of records,
+/// and at least half of the data must remain after trimming.
+fn check_trimmed_mean(component: &proto::DpTrimmedMean, data_property: &ArrayProperties, properties: &NodeProperties) -> Result<()> {
+    if data_property.data_type != DataType::Float {
+        return Err("data: atomic type must be float".into())
+    }
+    if data_property.num_columns()? != 1 {
+        return Err("data: must contain one column".into())
+    }
+    data_property.num_records()
+        .map_err(prepend("data: trimmed mean requires a known number of records:"))?;
+
+    if !(0. ..0.5).contains(&component.trim) {
+        return Err("trim: must be within [0, 0.5)".into())
+    }
+    if let Ok(candidates_property) = get_array_property(properties, "candidates") {
+        if !candidates_property.releasable {
+            return Err("candidates: must be public".into())
+        }
+    }
+    Ok(())
+}
+
+/// The trimmed mean of a single column is within the bounds of the data.
+fn get_trimmed_mean_bounds(data_property: &ArrayProperties) -> Result<(f64, f64)> {
+    let lower = data_property.lower_float()
+        .map_err(prepend("data: trimmed mean requires clamped data:"))?;
+    let upper = data_property.upper_float()
+        .map_err(prepend("data: trimmed mean requires clamped data:"))?;
+
+    Ok((*lower.first().ok_or("data: must contain one column")?,
+        *upper.first().ok_or("data: must contain one column")?))
+}
+
+#[cfg(test)]
+mod test_dp_trimmed_mean {
+    use crate::base::test_data;
+    use crate::components::resize::test_resize;
+    use crate::proto;
+
+    #[test]
+    fn test_dp_trimmed_mean() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let trimmed_mean = analysis.dp_trimmed_mean(resized, 0.1, vec![usage.clone()])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let expansion = crate::expand_component(
+            analysis.components.get(&trimmed_mean).unwrap().clone(),
+            indexmap!["data".into() => analysis.properties(resized).unwrap()],
+            indexmap![],
+            Some(analysis.privacy_definition.clone()),
+            trimmed_mean, 1000).unwrap();
+
+        let mut trimming_alphas = expansion.computation_graph.values()
+            .filter_map(|component| match component.variant.as_ref() {
+                Some(proto::component::Variant::DpQuantile(quantile)) => Some(quantile.alpha),
+                _ => None
+            }).collect::<Vec<_>>();
+        trimming_alphas.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        assert_eq!(trimming_alphas, vec![0.1, 0.9]);
+
+        let trimmed_mean_property = analysis.properties(trimmed_mean).unwrap().array().unwrap().clone();
+        assert_eq!(trimmed_mean_property.lower_float().unwrap(), vec![0.]);
+        assert_eq!(trimmed_mean_property.upper_float().unwrap(), vec![10.]);
+
+        // at least half of the data must remain after trimming
+        let trimmed_mean = analysis.dp_trimmed_mean(resized, 0.5, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+        assert!(analysis.properties(trimmed_mean).is_err());
+    }
+}
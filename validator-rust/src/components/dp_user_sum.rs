@@ -0,0 +1,202 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, NodeProperties, Value, ValueProperties};
+use crate::components::{Accuracy, Component, Expandable, Report};
+use crate::errors::*;
+use crate::utilities::{get_array_property, privacy::compose_privacy_usages};
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+
+impl Component for proto::DpUserSum {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = get_array_property(&properties, "data")?;
+
+        // the capped sum checks the data and user ids, and bounds the statistic
+        let Warnable(sum_property, warnings) = proto::UserSum { cap: self.cap }
+            .propagate_property(privacy_definition, public_arguments, properties, node_id)?;
+        let sum_property = sum_property.array()?;
+
+        Ok(Warnable(ValueProperties::Array(ArrayProperties {
+            nature: sum_property.nature.clone(),
+            ..ArrayProperties::dp_release(node_id, DataType::Float, data_property.group_id)
+        }), warnings))
+    }
+}
+
+impl Expandable for proto::DpUserSum {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        _properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+        let argument_ids = component.arguments();
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| Error::from("privacy_definition must be known"))?;
+
+        // sum of the capped total of each user
+        maximum_id += 1;
+        let id_sum = maximum_id;
+        expansion.computation_graph.insert(id_sum, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => *argument_ids.get::<IndexKey>(&"data".into())
+                    .ok_or_else(|| Error::from("data must be provided as an argument"))?,
+                "user_id".into() => *argument_ids.get::<IndexKey>(&"user_id".into())
+                    .ok_or_else(|| Error::from("user_id must be provided as an argument"))?
+            ])),
+            variant: Some(proto::component::Variant::UserSum(proto::UserSum { cap: self.cap })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_sum);
+
+        // noising
+        let mechanism = match self.mechanism.to_lowercase().as_str() {
+            "automatic" => if privacy_definition.protect_floating_point { "snapping" } else { "laplace" }.to_string(),
+            mechanism => mechanism.to_string()
+        };
+        let variant = match mechanism.as_str() {
+            "laplace" => proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
+            }),
+            "gaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                analytic: false,
+                max_noise_sigmas: 0.
+            }),
+            "analyticgaussian" => proto::component::Variant::GaussianMechanism(proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                analytic: true,
+                max_noise_sigmas: 0.
+            }),
+            "snapping" => proto::component::Variant::SnappingMechanism(proto::SnappingMechanism {
+                privacy_usage: self.privacy_usage.clone()
+            }),
+            _ => bail!("Unexpected invalid token {:?}", self.mechanism.as_str()),
+        };
+
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_sum])),
+            variant: Some(variant),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Accuracy for proto::DpUserSum {
+    fn accuracy_to_privacy_usage(
+        &self,
+        accuracies: &proto::Accuracies,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        match self.mechanism.to_lowercase().as_str() {
+            "laplace" => proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
+            }.accuracy_to_privacy_usage(accuracies, public_arguments),
+            "gaussian" => proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(), analytic: false, max_noise_sigmas: 0.
+            }.accuracy_to_privacy_usage(accuracies, public_arguments),
+            _ => Ok(None)
+        }
+    }
+
+    fn privacy_usage_to_accuracy(
+        &self,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        match self.mechanism.to_lowercase().as_str() {
+            "laplace" => proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
+            }.privacy_usage_to_accuracy(public_arguments, alpha),
+            "gaussian" => proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(), analytic: false, max_noise_sigmas: 0.
+            }.privacy_usage_to_accuracy(public_arguments, alpha),
+            _ => Ok(None)
+        }
+    }
+}
+
+impl Report for proto::DpUserSum {
+    fn summarize(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        _properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let variable_name = variable_names
+            .and_then(|names| names.first()).cloned()
+            .unwrap_or_else(|| "[Unknown]".into());
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPUserSum".to_string(),
+            variables: serde_json::json!(variable_name.to_string()),
+            release_info: value_to_json(release)?,
+            privacy_loss: privacy_usage_to_json(&compose_privacy_usages(&self.privacy_usage)?),
+            accuracy: None,
+            relative_accuracy: None,
+            submission: component.submission,
+            node_id,
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "".to_string(),
+                cite: "".to_string(),
+                mechanism: self.mechanism.clone(),
+                argument: serde_json::json!({
+                    "cap": self.cap
+                }),
+            },
+        }]))
+    }
+}
+
+#[cfg(test)]
+mod test_dp_user_sum {
+    use crate::base::test_data;
+    use crate::components::impute::test_impute;
+
+    #[test]
+    fn test_dp_user_sum() {
+        let (mut analysis, imputed) = test_impute::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+        let user_id = analysis.literal()
+            .value(ndarray::arr1(&[0i64, 0, 0, 0, 0, 0, 1, 2, 3, 4]).into_dyn().into())
+            .value_public(true).build();
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let user_sum = analysis.dp_user_sum(imputed, user_id, 5., vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        // the release is a noised, continuous scalar
+        let properties = analysis.properties(user_sum).unwrap();
+        let properties = properties.array().unwrap();
+        assert!(properties.releasable && properties.noised);
+        assert_eq!(properties.num_records, Some(1));
+        assert_eq!(properties.num_columns, Some(1));
+        assert!(properties.aggregator.is_none());
+    }
+}
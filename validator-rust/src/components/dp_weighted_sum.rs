@@ -0,0 +1,290 @@
+use indexmap::map::IndexMap;
+use ndarray::Array;
+
+use crate::{base, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::{Accuracy, Component, Expandable, Report};
+use crate::errors::*;
+use crate::utilities::{array::get_ith_column, get_array_property, get_literal, prepend, privacy::spread_privacy_usage};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+
+impl Component for proto::DpWeightedSum {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = get_array_property(&properties, "data")?;
+        let weights_property = get_array_property(&properties, "weights")?;
+
+        if !data_property.releasable {
+            data_property.assert_is_not_aggregated()?;
+        }
+        if data_property.data_type != DataType::Float {
+            return Err("data: atomic type must be float".into())
+        }
+        if !weights_property.releasable {
+            return Err("weights: must be public".into())
+        }
+        if weights_property.data_type != DataType::Float {
+            return Err("weights: atomic type must be float".into())
+        }
+        if weights_property.num_columns()? != 1 {
+            return Err("weights: must be a vector, with one weight per row".into())
+        }
+
+        let (lower, upper) = get_bounds(&data_property)?;
+        let num_columns = data_property.num_columns()?;
+
+        // each record is bounded by the product of its weight with the bounds of the data
+        let (lower, upper) = match get_weights(&public_arguments)? {
+            Some(weights) => lower.into_iter().zip(upper)
+                .map(|(lower, upper)| weights.iter()
+                    .fold((0., 0.), |(sum_lower, sum_upper), weight|
+                        (sum_lower + (weight * lower).min(weight * upper),
+                         sum_upper + (weight * lower).max(weight * upper))))
+                .map(|(lower, upper)| (Some(lower), Some(upper)))
+                .unzip(),
+            None => (vec![None; num_columns as usize], vec![None; num_columns as usize])
+        };
+
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_columns: Some(num_columns),
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(lower),
+                upper: Vector1DNull::Float(upper),
+            })),
+            ..ArrayProperties::dp_release(node_id, DataType::Float, data_property.group_id)
+        }).into())
+    }
+}
+
+impl Expandable for proto::DpWeightedSum {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        public_arguments: &IndexMap<IndexKey, &Value>,
+        _properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+        let argument_ids = component.arguments();
+
+        let id_data = *argument_ids.get::<IndexKey>(&"data".into())
+            .ok_or_else(|| Error::from("data must be provided as an argument"))?;
+        let weights = get_weights(public_arguments)?
+            .ok_or_else(|| Error::from("weights: must be public"))?;
+
+        // weights as a single column, so that their bounds span all records
+        maximum_id += 1;
+        let id_weights = maximum_id;
+        let num_records = weights.len();
+        let weights = Array::from_shape_vec((num_records, 1), weights)?.into_dyn();
+        let (patch_node, release) = get_literal(weights.into(), component.submission)?;
+        expansion.computation_graph.insert(id_weights, patch_node);
+        expansion.properties.insert(id_weights, infer_property(&release.value, None, id_weights)?);
+        expansion.releases.insert(id_weights, release);
+
+        // scale each record by its weight
+        maximum_id += 1;
+        let id_weighted = maximum_id;
+        expansion.computation_graph.insert(id_weighted, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "left".into() => id_data,
+                "right".into() => id_weights
+            ])),
+            variant: Some(proto::component::Variant::Multiply(proto::Multiply {})),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_weighted);
+
+        // dp sum of the weighted data
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap!["data".into() => id_weighted])),
+            variant: Some(proto::component::Variant::DpSum(proto::DpSum {
+                mechanism: self.mechanism.clone(),
+                privacy_usage: self.privacy_usage.clone(),
+            })),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Accuracy for proto::DpWeightedSum {
+    fn accuracy_to_privacy_usage(
+        &self,
+        accuracies: &proto::Accuracies,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        match self.mechanism.to_lowercase().as_str() {
+            "laplace" => proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
+            }.accuracy_to_privacy_usage(accuracies, public_arguments),
+            "gaussian" => proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(), analytic: false, max_noise_sigmas: 0.
+            }.accuracy_to_privacy_usage(accuracies, public_arguments),
+            _ => Ok(None)
+        }
+    }
+
+    fn privacy_usage_to_accuracy(
+        &self,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        alpha: f64,
+    ) -> Result<Option<Vec<proto::Accuracy>>> {
+        match self.mechanism.to_lowercase().as_str() {
+            "laplace" => proto::LaplaceMechanism {
+                privacy_usage: self.privacy_usage.clone(),
+                max_noise_sigmas: 0.
+            }.privacy_usage_to_accuracy(public_arguments, alpha),
+            "gaussian" => proto::GaussianMechanism {
+                privacy_usage: self.privacy_usage.clone(), analytic: false, max_noise_sigmas: 0.
+            }.privacy_usage_to_accuracy(public_arguments, alpha),
+            _ => Ok(None)
+        }
+    }
+}
+
+impl Report for proto::DpWeightedSum {
+    fn summarize(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = get_array_property(&properties, "data")?;
+
+        let mut releases = Vec::new();
+
+        let (lower, upper) = get_bounds(&data_property)?;
+        let num_columns = data_property.num_columns()?;
+        let privacy_usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
+
+        let max_weight = get_weights(&public_arguments)?
+            .map(|weights| weights.into_iter().fold(0., |max: f64, weight| max.max(weight.abs())));
+
+        let release = release.ref_array()?.ref_float()?;
+
+        for column_number in 0..(num_columns as usize) {
+            let variable_name = variable_names
+                .and_then(|names| names.get(column_number)).cloned()
+                .unwrap_or_else(|| "[Unknown]".into());
+
+            releases.push(JSONRelease {
+                description: "DP release information".to_string(),
+                statistic: "DPWeightedSum".to_string(),
+                variables: serde_json::json!(variable_name.to_string()),
+                release_info: value_to_json(&get_ith_column(release, column_number)?.into())?,
+                privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
+                accuracy: None,
+                relative_accuracy: None,
+                submission: component.submission,
+                node_id,
+                postprocess: false,
+                algorithm_info: AlgorithmInfo {
+                    name: "".to_string(),
+                    cite: "".to_string(),
+                    mechanism: self.mechanism.clone(),
+                    argument: serde_json::json!({
+                        "max_weight": max_weight,
+                        "constraint": {
+                            "lowerbound": lower[column_number],
+                            "upperbound": upper[column_number]
+                        }
+                    }),
+                },
+            });
+        }
+        Ok(Some(releases))
+    }
+}
+
+/// Bounds on each record of the data, before weighting.
+fn get_bounds(data_property: &ArrayProperties) -> Result<(Vec<f64>, Vec<f64>)> {
+    Ok((data_property.lower_float()
+            .map_err(prepend("data: weighted sum requires clamped data:"))?,
+        data_property.upper_float()
+            .map_err(prepend("data: weighted sum requires clamped data:"))?))
+}
+
+/// Retrieve the weights, if they are known.
+fn get_weights(public_arguments: &IndexMap<IndexKey, &Value>) -> Result<Option<Vec<f64>>> {
+    public_arguments.get::<IndexKey>(&"weights".into())
+        .map(|weights| Ok(weights.ref_array()?.ref_float()?.iter().cloned().collect()))
+        .transpose()
+}
+
+#[cfg(test)]
+mod test_dp_weighted_sum {
+    use ndarray::arr1;
+
+    use crate::base::{SensitivitySpace, test_data, ValueProperties};
+    use crate::bindings::Analysis;
+    use crate::components::resize::test_resize;
+    use crate::components::Sensitivity;
+    use crate::proto;
+
+    /// Sensitivity of the sum of the weighted data, after expansion.
+    fn weighted_sensitivity(weights: Vec<f64>) -> f64 {
+        let (mut analysis, resized): (Analysis, u32) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let weights = analysis.literal()
+            .value(arr1(&weights).into_dyn().into())
+            .value_public(true).build();
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let weighted_sum = analysis.dp_weighted_sum(resized, weights, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let mut computation_graph = analysis.components.clone();
+        let mut release = analysis.release.clone();
+        let (properties, _) = crate::utilities::propagate_properties(
+            &Some(analysis.privacy_definition.clone()),
+            &mut computation_graph, &mut release, None, true).unwrap();
+
+        let (id_weighted, _) = computation_graph.iter()
+            .find(|(_, component)| matches!(component.variant, Some(proto::component::Variant::Multiply(_))))
+            .unwrap();
+        let weighted_property = properties.get(id_weighted).unwrap().array().unwrap().clone();
+
+        let weighted_sum_property = analysis.properties(weighted_sum).unwrap().array().unwrap().clone();
+        assert_eq!(weighted_sum_property.num_records, Some(1));
+
+        let sensitivity = proto::Sum {}.compute_sensitivity(
+            &analysis.privacy_definition,
+            &indexmap!["data".into() => ValueProperties::Array(weighted_property)],
+            &SensitivitySpace::KNorm(1)).unwrap();
+        sensitivity.ref_array().unwrap().first_float().unwrap()
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_dp_weighted_sum() {
+        // unit weights reduce to a clamped sum
+        assert_eq!(weighted_sensitivity(vec![1.; 10]), 10.);
+
+        // each record may contribute at most the largest weight magnitude times the clamp range
+        let mut weights = vec![0.5; 10];
+        weights[3] = 3.;
+        assert_eq!(weighted_sensitivity(weights.clone()), 30.);
+        weights[7] = -4.;
+        assert_eq!(weighted_sensitivity(weights), 40.);
+    }
+}
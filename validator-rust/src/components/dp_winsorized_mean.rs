@@ -0,0 +1,253 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable, Float};
+use crate::base::{ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Report};
+use crate::components::dp_mean::dp_mean_accuracy;
+use crate::errors::*;
+use crate::utilities::{array::get_ith_column, get_array_property, prepend, require_public_argument, privacy::{get_epsilon, spread_privacy_usage}};
+use crate::utilities::json::{Accuracy, AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json, REPORT_ALPHA};
+
+impl Component for proto::DpWinsorizedMean {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = get_array_property(&properties, "data")?;
+
+        if !data_property.releasable {
+            data_property.assert_is_not_aggregated()?;
+        }
+        if data_property.data_type != DataType::Float {
+            return Err("data: atomic type must be float".into())
+        }
+        data_property.num_records()
+            .map_err(prepend("data: winsorized mean requires a known number of records:"))?;
+
+        let num_columns = data_property.num_columns()?;
+        let (lower, upper) = get_winsorization_bounds(&public_arguments, num_columns)?;
+
+        // the winsorized mean is bounded by the winsorization bounds
+        Ok(ValueProperties::Array(ArrayProperties {
+            num_columns: Some(num_columns),
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(lower.into_iter().map(Some).collect()),
+                upper: Vector1DNull::Float(upper.into_iter().map(Some).collect()),
+            })),
+            ..ArrayProperties::dp_release(node_id, DataType::Float, data_property.group_id)
+        }).into())
+    }
+}
+
+impl Expandable for proto::DpWinsorizedMean {
+    fn expand_component(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+        let argument_ids = component.arguments();
+
+        // the sensitivity of the mean may only depend on public bounds
+        let num_columns = get_array_property(properties, "data")?.num_columns()?;
+        get_winsorization_bounds(public_arguments, num_columns)?;
+
+        let get_id = |name: &str| argument_ids.get::<IndexKey>(&name.into()).cloned()
+            .ok_or_else(|| Error::from(format!("{} must be provided as an argument", name)));
+
+        // winsorize: elements outside of the bounds are replaced with the closer bound
+        maximum_id += 1;
+        let id_clamp = maximum_id;
+        expansion.computation_graph.insert(id_clamp, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => get_id("data")?,
+                "lower".into() => get_id("lower")?,
+                "upper".into() => get_id("upper")?
+            ])),
+            variant: Some(proto::component::Variant::Clamp(proto::Clamp { null_policy: "Pass".to_string() })),
+            omit: true,
+            submission: component.submission,
+        });
+        expansion.traversal.push(id_clamp);
+
+        // dp mean of the winsorized data, with the sensitivity of a clamped mean
+        expansion.computation_graph.insert(component_id, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => id_clamp
+            ])),
+            variant: Some(proto::component::Variant::DpMean(proto::DpMean {
+                implementation: "resize".to_string(),
+                centered: false,
+                mechanism: self.mechanism.clone(),
+                privacy_usage: self.privacy_usage.clone(),
+            })),
+            omit: component.omit,
+            submission: component.submission,
+        });
+
+        Ok(expansion)
+    }
+}
+
+impl Report for proto::DpWinsorizedMean {
+    fn summarize(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let data_property = get_array_property(&properties, "data")?;
+
+        let mut releases = Vec::new();
+
+        let num_records = data_property.num_records()?;
+        let num_columns = data_property.num_columns()?;
+        let (lower, upper) = get_winsorization_bounds(&public_arguments, num_columns)?;
+        let privacy_usages = spread_privacy_usage(&self.privacy_usage, num_columns as usize)?;
+
+        let release = release.ref_array()?.ref_float()?;
+
+        for column_number in 0..(num_columns as usize) {
+            let variable_name = variable_names
+                .and_then(|names| names.get(column_number)).cloned()
+                .unwrap_or_else(|| "[Unknown]".into());
+
+            let accuracy = if Accuracy::is_known_for(&self.mechanism) {
+                Some(Accuracy {
+                    accuracy_value: dp_mean_accuracy(
+                        lower[column_number], upper[column_number], num_records,
+                        get_epsilon(&privacy_usages[column_number])?, REPORT_ALPHA),
+                    alpha: REPORT_ALPHA,
+                    data_dependent: false,
+                    simultaneous: false,
+                })
+            } else { None };
+
+            releases.push(JSONRelease {
+                description: "DP release information".to_string(),
+                statistic: "DPWinsorizedMean".to_string(),
+                variables: serde_json::json!(variable_name.to_string()),
+                release_info: value_to_json(&get_ith_column(release, column_number)?.into())?,
+                privacy_loss: privacy_usage_to_json(&privacy_usages[column_number].clone()),
+                accuracy,
+                relative_accuracy: None,
+                submission: component.submission,
+                node_id,
+                postprocess: false,
+                algorithm_info: AlgorithmInfo {
+                    name: "".to_string(),
+                    cite: "".to_string(),
+                    mechanism: self.mechanism.clone(),
+                    argument: serde_json::json!({
+                        "n": num_records,
+                        "constraint": {
+                            "lowerbound": lower[column_number],
+                            "upperbound": upper[column_number]
+                        }
+                    }),
+                },
+            });
+        }
+        Ok(Some(releases))
+    }
+}
+
+/// Public winsorization bounds for each column of the data.
+fn get_winsorization_bounds(
+    public_arguments: &IndexMap<IndexKey, &Value>, num_columns: i64,
+) -> Result<(Vec<Float>, Vec<Float>)> {
+    let lower = require_public_argument(public_arguments, "lower")?
+        .ref_array()?.clone().vec_float(Some(num_columns))
+        .map_err(prepend("lower:"))?;
+    let upper = require_public_argument(public_arguments, "upper")?
+        .ref_array()?.clone().vec_float(Some(num_columns))
+        .map_err(prepend("upper:"))?;
+
+    if !lower.iter().zip(upper.iter()).all(|(lower, upper)| lower < upper) {
+        return Err("lower is greater than upper".into())
+    }
+    Ok((lower, upper))
+}
+
+#[cfg(test)]
+mod test_dp_winsorized_mean {
+    use ndarray::arr1;
+
+    use crate::base::{ReleaseNode, test_data, Value};
+    use crate::components::resize::test_resize;
+    use crate::proto;
+
+    #[test]
+    fn test_dp_winsorized_mean() {
+        // data with outliers far outside of the winsorization bounds
+        let data: Value = arr1(&[-90., 1., 2., 3., 4., 5., 6., 7., 8., 900.]).into_dyn().into();
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            data, 10.into(), Some((-100.).into()), Some(1000.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let (winsor_lower_value, winsor_upper_value): (Value, Value) = (0.0.into(), 10.0.into());
+        let winsor_lower = analysis.literal().value(winsor_lower_value.clone()).value_public(true).build();
+        let winsor_upper = analysis.literal().value(winsor_upper_value.clone()).value_public(true).build();
+        let winsorized_mean = analysis.dp_winsorized_mean(resized, winsor_lower, winsor_upper, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+
+        let expansion = crate::expand_component(
+            analysis.components.get(&winsorized_mean).unwrap().clone(),
+            indexmap![
+                "data".into() => analysis.properties(resized).unwrap(),
+                "lower".into() => analysis.properties(winsor_lower).unwrap(),
+                "upper".into() => analysis.properties(winsor_upper).unwrap()
+            ],
+            indexmap![
+                "lower".into() => ReleaseNode {
+                    value: winsor_lower_value, privacy_usages: None, public: true
+                },
+                "upper".into() => ReleaseNode {
+                    value: winsor_upper_value, privacy_usages: None, public: true
+                }
+            ],
+            Some(analysis.privacy_definition.clone()),
+            winsorized_mean, 1000).unwrap();
+
+        assert_eq!(expansion.computation_graph.values()
+            .filter(|component| matches!(component.variant.as_ref(), Some(proto::component::Variant::Clamp(_))))
+            .count(), 1);
+        assert_eq!(expansion.computation_graph.values()
+            .filter(|component| matches!(component.variant.as_ref(), Some(proto::component::Variant::DpMean(_))))
+            .count(), 1);
+
+        // the raw data spans [-90, 900], but the winsorized mean is within the winsorization bounds
+        let data_property = analysis.properties(resized).unwrap().array().unwrap().clone();
+        assert!(data_property.lower_float().unwrap()[0] < 0.);
+        assert!(data_property.upper_float().unwrap()[0] > 10.);
+
+        let winsorized_mean_property = analysis.properties(winsorized_mean).unwrap().array().unwrap().clone();
+        assert_eq!(winsorized_mean_property.lower_float().unwrap(), vec![0.]);
+        assert_eq!(winsorized_mean_property.upper_float().unwrap(), vec![10.]);
+    }
+
+    #[test]
+    fn test_dp_winsorized_mean_requires_public_bounds() {
+        let data: Value = arr1(&[1., 2., 3.]).into_dyn().into();
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            data, 3.into(), Some(0.0.into()), Some(10.0.into()));
+        let winsor_lower = analysis.literal().value(0.0.into()).value_public(false).build();
+        let winsor_upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let winsorized_mean = analysis.dp_winsorized_mean(resized, winsor_lower, winsor_upper, vec![]).build();
+
+        assert!(analysis.properties(winsorized_mean).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use indexmap::map::IndexMap;
+use ndarray::Array;
+
+use crate::{base, Float, proto, Warnable};
+use crate::base::{AggregatorProperties, ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Sensitivity};
+use crate::errors::*;
+use crate::utilities::{get_array_property, prepend, require_public_argument, privacy::scale_by_contributions};
+
+impl Component for proto::Ecdf {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let mut data_property = get_array_property(&properties, "data")?;
+
+        if !data_property.releasable {
+            data_property.assert_is_not_aggregated()?;
+        }
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
+        let num_thresholds = get_num_thresholds(&public_arguments, &data_property)?;
+
+        // save a snapshot of the state when aggregating
+        data_property.aggregator = Some(AggregatorProperties::new(
+            proto::component::Variant::Ecdf(self.clone()), properties, 1));
+        data_property.monotone = false;
+
+        data_property.nature = Some(Nature::Continuous(NatureContinuous {
+            lower: Vector1DNull::Float(vec![Some(0.)]),
+            upper: Vector1DNull::Float(vec![Some(1.)]),
+        }));
+        data_property.data_type = DataType::Float;
+        data_property.num_records = Some(num_thresholds);
+        data_property.dataset_id = Some(node_id as i64);
+
+        Ok(ValueProperties::Array(data_property).into())
+    }
+}
+
+impl Sensitivity for proto::Ecdf {
+    fn compute_sensitivity(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        properties: &NodeProperties,
+        sensitivity_type: &SensitivitySpace,
+    ) -> Result<Value> {
+        let data_property = get_array_property(properties, "data")?;
+        data_property.assert_is_not_aggregated()?;
+
+        let num_records = data_property.num_records()? as Float;
+        let num_thresholds = get_array_property(properties, "thresholds")?.num_records()?;
+
+        match sensitivity_type {
+            SensitivitySpace::KNorm(k) => {
+                // SENSITIVITY DERIVATIONS
+                // A record is counted below every threshold above it.
+                // Substituting a record may move it across every threshold, changing each fraction by 1 / n.
+                // When n is known, adding or removing a record is equivalent to a substitution.
+                let sensitivity = scale_by_contributions(privacy_definition, match k {
+                    1 => num_thresholds as Float,
+                    2 => (num_thresholds as Float).sqrt(),
+                    _ => return Err("KNorm sensitivity is only supported in L1 and L2 spaces".into())
+                } / num_records)?;
+
+                Ok(Array::from_elem(vec![num_thresholds as usize, 1], sensitivity).into())
+            }
+            _ => Err("Ecdf sensitivity is only implemented for KNorm".into())
+        }
+    }
+}
+
+/// Check the data and thresholds of an ecdf, and retrieve the number of thresholds.
+pub fn get_num_thresholds(
+    public_arguments: &IndexMap<IndexKey, &Value>, data_property: &ArrayProperties,
+) -> Result<i64> {
+    data_property.assert_non_null()?;
+    if data_property.num_columns()? != 1 {
+        return Err("data: must contain one column".into())
+    }
+    data_property.num_records()
+        .map_err(prepend("data: ecdf requires a known number of records:"))?;
+
+    let thresholds = require_public_argument(public_arguments, "thresholds")?.ref_array()?;
+    if thresholds.shape().len() > 1 {
+        return Err("thresholds: must be a vector".into())
+    }
+    match (&data_property.data_type, thresholds) {
+        (DataType::Float, base::Array::Float(_)) | (DataType::Int, base::Array::Int(_)) => (),
+        (DataType::Float, _) | (DataType::Int, _) => return Err("thresholds: atomic type must match the data".into()),
+        _ => return Err("data: atomic type must be numeric".into())
+    }
+
+    let num_thresholds = thresholds.shape().iter().product::<usize>() as i64;
+    if num_thresholds == 0 {
+        return Err("thresholds: must contain at least one threshold".into())
+    }
+    Ok(num_thresholds)
+}
+
+#[cfg(test)]
+mod test_ecdf {
+    use ndarray::arr1;
+
+    use crate::base::{IndexKey, SensitivitySpace, test_data};
+    use crate::components::resize::test_resize;
+    use crate::components::Sensitivity;
+    use crate::proto;
+
+    #[test]
+    fn test_ecdf_sensitivity() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(1.0.into()));
+        let thresholds = analysis.literal()
+            .value(arr1(&[0.25, 0.5, 0.75, 1.]).into_dyn().into())
+            .value_public(true).build();
+
+        let properties = indexmap![
+            IndexKey::from("data") => analysis.properties(resized).unwrap(),
+            IndexKey::from("thresholds") => analysis.properties(thresholds).unwrap()
+        ];
+        let sensitivity = |k| proto::Ecdf {}
+            .compute_sensitivity(&analysis.privacy_definition, &properties, &SensitivitySpace::KNorm(k))
+            .unwrap().array().unwrap().float().unwrap();
+
+        // one record may change the fraction at each of the four thresholds by 1 / 10
+        assert_eq!(sensitivity(1).shape(), &[4, 1]);
+        assert!(sensitivity(1).iter().all(|v| (v - 0.4).abs() < 1e-10));
+        assert!(sensitivity(2).iter().all(|v| (v - 0.2).abs() < 1e-10));
+    }
+}
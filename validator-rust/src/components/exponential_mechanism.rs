@@ -1,19 +1,20 @@
 use indexmap::map::IndexMap;
 use itertools::Itertools;
 
-use crate::{base, proto, Warnable};
+use crate::{base, Float, proto, Warnable};
 use crate::base::{ArrayProperties, DataType, IndexKey, NodeProperties, SensitivitySpace, Value, ValueProperties};
-use crate::components::{Component, Expandable, Mechanism, Sensitivity};
+use crate::components::{Component, Expandable, Mechanism, Report};
 use crate::errors::*;
-use crate::utilities::{get_literal, prepend, check_sensitivity_properties};
+use crate::utilities::{compute_mechanism_sensitivity, get_array_property, get_literal, prepend, get_seed, is_derived_sensitivity, summarize_mechanism, SEEDED_RELEASE_WARNING};
 use crate::utilities::inference::infer_property;
-use crate::utilities::privacy::privacy_usage_check;
+use crate::utilities::json::JSONRelease;
+use crate::utilities::privacy::{compose_privacy_usages, get_aggregated_num_records, privacy_usage_check};
 
 impl Component for proto::ExponentialMechanism {
     fn propagate_property(
         &self,
         privacy_definition: &Option<proto::PrivacyDefinition>,
-        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: base::NodeProperties,
         node_id: u32,
     ) -> Result<Warnable<ValueProperties>> {
@@ -28,18 +29,13 @@ impl Component for proto::ExponentialMechanism {
             .get(&IndexKey::from("utilities"))
             .ok_or("utilities: missing")?.array()
             .map_err(prepend("utilities:"))?.clone();
+        utilities_property.assert_is_not_noised().map_err(prepend("utilities:"))?;
 
         if utilities_property.data_type != DataType::Float {
             return Err("utilities: data_type must be float".into());
         }
 
-        let candidates_property: ArrayProperties = properties
-            .get(&IndexKey::from("candidates"))
-            .ok_or_else(|| Error::from("candidates: missing"))?.array()?.clone();
-
-        if !candidates_property.releasable {
-            return Err(Error::from("candidates: must be public"))
-        }
+        let candidates_property = get_public_candidates(&properties)?;
 
         if utilities_property.num_records()? != candidates_property.num_records()? {
             return Err("utilities and candidates must share the same number of records".into());
@@ -56,40 +52,29 @@ impl Component for proto::ExponentialMechanism {
             .ok_or_else(|| Error::from("aggregator: missing"))?;
 
         // sensitivity must be computable
-        let sensitivity_values = aggregator.component.compute_sensitivity(
-            privacy_definition,
-            &aggregator.properties,
-            &SensitivitySpace::Exponential)?;
+        let sensitivity_values = compute_mechanism_sensitivity(
+            self, privacy_definition, &aggregator)?;
 
         // make sure sensitivities are an f64 array
         sensitivity_values.array()?.float()?;
 
         let output_property = ArrayProperties {
-            num_records: Some(1),
-            num_columns: Some(1),
-            nullity: false,
-            releasable: true,
-            c_stability: 1,
-            aggregator: None,
-            nature: None,
-            data_type: candidates_property.data_type.clone(),
-            dataset_id: None,
-            node_id: node_id as i64,
-            is_not_empty: true,
             dimensionality: Some(0),
-            group_id: utilities_property.group_id,
-            naturally_ordered: true,
-            sample_proportion: None
+            ..ArrayProperties::dp_release(node_id, candidates_property.data_type.clone(), utilities_property.group_id)
         };
 
-        let privacy_usage = self.privacy_usage.iter().cloned().map(Ok)
-            .fold1(|l, r| l? + r?)
-            .ok_or_else(|| "privacy_usage: must be defined")??;
+        let privacy_usage = compose_privacy_usages(&self.privacy_usage)?;
 
-        let warnings = privacy_usage_check(
+        let mut warnings = privacy_usage_check(
             &privacy_usage,
-            output_property.num_records,
-            privacy_definition.strict_parameter_checks)?;
+            get_aggregated_num_records(&aggregator),
+            privacy_definition.strict_parameter_checks,
+            privacy_definition.allow_unknown_num_records)?;
+
+        // seeded noise is reproducible, and offers no privacy
+        if get_seed(&public_arguments, &properties)?.is_some() {
+            warnings.push(SEEDED_RELEASE_WARNING.into());
+        }
 
         Ok(Warnable(output_property.into(), warnings))
     }
@@ -110,6 +95,7 @@ impl Expandable for proto::ExponentialMechanism {
         let utilities_property: ArrayProperties = properties.get::<IndexKey>(&"utilities".into())
             .ok_or("utilities: missing")?.array()
             .map_err(prepend("utilities:"))?.clone();
+        get_public_candidates(properties)?;
 
         let privacy_definition = privacy_definition.as_ref()
             .ok_or_else(|| "privacy definition must be defined")?;
@@ -130,19 +116,23 @@ impl Expandable for proto::ExponentialMechanism {
             // this case should never happen
         } else { return Err(Error::from("Variant must be defined")) }
 
+        let derive_sensitivity = || compute_mechanism_sensitivity(
+            self, privacy_definition, utilities_property.aggregator.as_ref()
+                .ok_or_else(|| Error::from("aggregator: missing"))?);
+
         if let Some(sensitivity_property) = properties.get(&IndexKey::from("sensitivity")) {
-            if privacy_definition.protect_sensitivity {
+            let sensitivity_property = sensitivity_property.array()?;
+            // the expansion of a previous propagation inserts the derived sensitivity,
+            //    so a sensitivity equal to the derived sensitivity is not custom
+            if privacy_definition.protect_sensitivity && !is_derived_sensitivity(sensitivity_property, &derive_sensitivity()?)? {
                 return Err(Error::from("custom sensitivities may only be passed if protect_sensitivity is disabled"))
             }
-            check_sensitivity_properties(sensitivity_property.array()?, &utilities_property)?;
+            // the utilities of all candidates share one sensitivity per column
+            if sensitivity_property.num_columns()? != utilities_property.num_columns()? {
+                return Err("sensitivity must have one column for each column of the utilities".into())
+            }
         } else {
-            let aggregator = utilities_property.aggregator
-                .ok_or_else(|| Error::from("aggregator: missing"))?;
-
-            let sensitivity = aggregator.component.compute_sensitivity(
-                privacy_definition,
-                &aggregator.properties,
-                &SensitivitySpace::Exponential)?;
+            let sensitivity = derive_sensitivity()?;
 
             // exponential sensitivity cannot currently be modified by lipschitz constants
 
@@ -161,6 +151,31 @@ impl Expandable for proto::ExponentialMechanism {
     }
 }
 
+impl Report for proto::ExponentialMechanism {
+    fn summarize(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        summarize_mechanism(
+            "Exponential",
+            privacy_definition,
+            &self.privacy_usage,
+            node_id,
+            component,
+            &public_arguments,
+            &properties,
+            release,
+            variable_names,
+            |_sensitivity, _usage| Ok(None))
+    }
+}
+
 impl Mechanism for proto::ExponentialMechanism {
     fn get_privacy_usage(
         &self,
@@ -179,4 +194,108 @@ impl Mechanism for proto::ExponentialMechanism {
                 privacy_definition.group_size))
             .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
     }
+
+    fn sensitivity_space(&self) -> Result<SensitivitySpace> {
+        Ok(SensitivitySpace::Exponential)
+    }
+}
+
+/// Retrieve the properties of the candidate set, which must be public.
+///
+/// The exponential mechanism releases one of the candidates as-is.
+/// A candidate set derived from private data, like the observed values of the data,
+/// would reveal a private value whenever one of its candidates is selected.
+/// Candidates are public if every source they are derived from is public, or was released by a mechanism.
+pub fn get_public_candidates(properties: &NodeProperties) -> Result<ArrayProperties> {
+    let candidates_property = get_array_property(properties, "candidates")?;
+    if !candidates_property.releasable {
+        return Err("candidates: must be public, but are derived from private data".into())
+    }
+    Ok(candidates_property)
+}
+
+/// Loss in utility, relative to the best candidate, within which the exponential mechanism release falls
+/// with probability at least `1 - alpha`.
+///
+/// Candidate `i` is selected with probability proportional to `exp(epsilon * u_i / (2 * sensitivity))`.
+/// Because the candidates are discrete, the loss is the smallest utility gap whose candidates
+/// hold at least `1 - alpha` of the total weight.
+pub fn exponential_utility_accuracy(
+    utilities: &[Float], sensitivity: Float, epsilon: Float, alpha: Float,
+) -> Result<Float> {
+    if utilities.is_empty() {
+        return Err("utilities: must be non-empty".into())
+    }
+    if sensitivity <= 0. || epsilon <= 0. {
+        return Err("sensitivity and epsilon must be positive".into())
+    }
+    let max_utility = utilities.iter().copied().fold(Float::NEG_INFINITY, Float::max);
+
+    // weights are relative to the best candidate, so they cannot underflow to all zeros
+    let losses = utilities.iter()
+        .map(|utility| max_utility - utility)
+        .sorted_by(|l, r| l.partial_cmp(r).unwrap())
+        .collect::<Vec<Float>>();
+    let weights = losses.iter()
+        .map(|loss| (-epsilon * loss / (2. * sensitivity)).exp())
+        .collect::<Vec<Float>>();
+    let total: Float = weights.iter().sum();
+
+    let mut cumulative = 0.;
+    for (loss, weight) in losses.iter().zip(weights.iter()) {
+        cumulative += weight;
+        if cumulative >= (1. - alpha) * total {
+            return Ok(*loss)
+        }
+    }
+    Ok(*losses.last().unwrap())
+}
+
+#[cfg(test)]
+mod test_exponential_mechanism {
+    use ndarray::arr1;
+
+    use crate::base::test_data;
+    use crate::bindings::Analysis;
+    use crate::components::literal::test_literal;
+    use crate::components::resize::test_resize;
+
+    /// A private analysis, and the dp median of its data over the given candidates.
+    fn dp_median(candidates: impl Fn(&mut Analysis, u32) -> u32) -> (Analysis, u32) {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        let source = *analysis.components.keys().min().unwrap();
+        test_literal::privatize(&mut analysis, source);
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let candidates = candidates(&mut analysis, resized);
+        let median = analysis.dp_median(resized, vec![usage])
+            .mechanism("Exponential".to_string())
+            .candidates(candidates).build();
+        (analysis, median)
+    }
+
+    #[test]
+    fn test_public_candidates() {
+        // a fixed grid is public
+        let (analysis, median) = dp_median(|analysis, _| analysis.literal()
+            .value(arr1(&[0., 2.5, 5., 7.5, 10.]).into_dyn().into())
+            .value_public(true).build());
+        assert!(analysis.properties(median).unwrap().array().unwrap().releasable);
+
+        // a grid of the observed values of the data would release one of the private records
+        let (analysis, median) = dp_median(|_, resized| resized);
+        let error = analysis.properties(median).unwrap_err();
+        assert!(error.iter().any(|cause| cause.to_string().contains("candidates: must be public")), "{}", error);
+
+        // a grid shifted by a statistic of the private data is also derived from the private data
+        let (analysis, median) = dp_median(|analysis, resized| {
+            let grid = analysis.literal()
+                .value(arr1(&[-1., 0., 1.]).into_dyn().into())
+                .value_public(true).build();
+            let mean = analysis.mean(resized).build();
+            analysis.add(grid, mean).build()
+        });
+        assert!(analysis.properties(median).is_err());
+    }
 }
@@ -40,10 +40,16 @@ impl Component for proto::Filter {
             return Err("mask: number of columns must be one".into())
         }
 
+        // a null mask neither keeps nor drops its row
+        if mask_property.nullity {
+            return Err("mask: may not contain nulls".into())
+        }
+
         propagate_binary_shape(&data_property, &mask_property)?;
 
-        // the number of records is not known after filtering rows
+        // the number of records is not known after filtering rows, and any lower bound no longer holds
         data_property.num_records = None;
+        data_property.num_records_lower_bound = None;
 
         // This exists to prevent binary ops on non-conformable arrays from being approved
         data_property.dataset_id = Some(node_id as i64);
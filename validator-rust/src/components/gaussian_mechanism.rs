@@ -3,24 +3,26 @@ use indexmap::map::IndexMap;
 use itertools::Itertools;
 use statrs::function::erf;
 
-use crate::{base, proto, Warnable};
+use crate::{base, Float, proto, Warnable};
 use crate::base::{DataType, IndexKey, NodeProperties, SensitivitySpace, Value, ValueProperties};
-use crate::components::{Accuracy, Mechanism, Sensitivity};
+use crate::components::{Accuracy, Mechanism, Report};
 use crate::components::{Component, Expandable};
 use crate::errors::*;
-use crate::utilities::{expand_mechanism, prepend};
-use crate::utilities::privacy::{get_delta, get_epsilon, privacy_usage_check, spread_privacy_usage};
+use crate::utilities::{compute_mechanism_sensitivity, expand_mechanism, prepend, get_seed, get_sensitivity_override, summarize_mechanism, SEEDED_RELEASE_WARNING, SENSITIVITY_OVERRIDE_WARNING};
+use crate::utilities::json::JSONRelease;
+use crate::utilities::privacy::{compose_privacy_usages, get_delta, get_epsilon, get_aggregated_num_records, privacy_usage_check, spread_privacy_usage, get_mechanism_privacy_definition};
 
 impl Component for proto::GaussianMechanism {
     fn propagate_property(
         &self,
         privacy_definition: &Option<proto::PrivacyDefinition>,
-        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: base::NodeProperties,
         _node_id: u32,
     ) -> Result<Warnable<ValueProperties>> {
-        let privacy_definition = privacy_definition.as_ref()
-            .ok_or_else(|| "privacy_definition must be defined")?;
+        let privacy_definition = &get_mechanism_privacy_definition(
+            privacy_definition.as_ref().ok_or_else(|| "privacy_definition must be defined")?,
+            &public_arguments, &properties)?;
 
         if privacy_definition.protect_floating_point {
             return Err("Floating-point protections are enabled. The gaussian mechanism is susceptible to floating-point attacks.".into())
@@ -33,6 +35,7 @@ impl Component for proto::GaussianMechanism {
         let mut data_property = properties.get::<IndexKey>(&"data".into())
             .ok_or("data: missing")?.array()
             .map_err(prepend("data:"))?.clone();
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
 
         if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
             return Err("data: atomic type must be numeric".into());
@@ -40,22 +43,33 @@ impl Component for proto::GaussianMechanism {
         let aggregator = data_property.aggregator.clone()
             .ok_or_else(|| Error::from("aggregator: missing"))?;
 
-        // sensitivity must be computable
-        aggregator.component.compute_sensitivity(
-            privacy_definition,
-            &aggregator.properties,
-            &SensitivitySpace::KNorm(2))?.array()?.cast_float()?;
+        // sensitivity must be computable, unless certified by the user
+        let sensitivity_override = get_sensitivity_override(privacy_definition, &public_arguments, &properties)?;
+        if sensitivity_override.is_none() {
+            compute_mechanism_sensitivity(
+                self, privacy_definition, &aggregator)?.array()?.cast_float()?;
+        }
+
+        let num_records = get_aggregated_num_records(&aggregator);
 
         // make sure lipschitz constants are available as float arrays
         aggregator.lipschitz_constants.array()?.cast_float()?;
 
-        let privacy_usage = self.privacy_usage.iter().cloned().map(Ok)
-            .fold1(|l, r| l? + r?).ok_or_else(|| "privacy_usage: must be defined")??;
+        let privacy_usage = compose_privacy_usages(&self.privacy_usage)?;
+
+        if get_delta(&privacy_usage)? == 0.0 {
+            return Err("delta: may not be zero".into())
+        }
 
-        let warnings = privacy_usage_check(
-            &privacy_usage,
-            data_property.num_records,
-            privacy_definition.strict_parameter_checks)?;
+        // resampling noise beyond the cap adds to the delta of the release
+        let truncated_privacy_usage = truncated_usage(
+            &privacy_usage, self.analytic, self.max_noise_sigmas, data_property.num_records)?;
+
+        let mut warnings = privacy_usage_check(
+            &truncated_privacy_usage,
+            num_records,
+            privacy_definition.strict_parameter_checks,
+            privacy_definition.allow_unknown_num_records)?;
 
         let epsilon = get_epsilon(&privacy_usage)?;
         if !self.analytic && epsilon > 1.0 {
@@ -67,12 +81,15 @@ impl Component for proto::GaussianMechanism {
             return Err(message)
         }
 
-        if get_delta(&privacy_usage)? == 0.0 {
-            return Err("delta: may not be zero".into())
-        }
+        data_property.mark_released();
 
-        data_property.releasable = true;
-        data_property.aggregator = None;
+        // seeded noise is reproducible, and offers no privacy
+        if get_seed(&public_arguments, &properties)?.is_some() {
+            warnings.push(SEEDED_RELEASE_WARNING.into());
+        }
+        if sensitivity_override.is_some() {
+            warnings.push(SENSITIVITY_OVERRIDE_WARNING.into());
+        }
 
         Ok(Warnable(data_property.into(), warnings))
     }
@@ -83,14 +100,18 @@ impl Expandable for proto::GaussianMechanism {
         &self,
         privacy_definition: &Option<proto::PrivacyDefinition>,
         component: &proto::Component,
-        _public_arguments: &IndexMap<IndexKey, &Value>,
+        public_arguments: &IndexMap<IndexKey, &Value>,
         properties: &base::NodeProperties,
         component_id: u32,
         maximum_id: u32,
     ) -> Result<base::ComponentExpansion> {
+        let privacy_definition = get_mechanism_privacy_definition(
+            privacy_definition.as_ref().ok_or_else(|| "privacy_definition must be defined")?,
+            public_arguments, properties)?;
+        // an override must be certified before it replaces the derived sensitivity
+        get_sensitivity_override(&privacy_definition, public_arguments, properties)?;
         expand_mechanism(
-            &SensitivitySpace::KNorm(2),
-            privacy_definition,
+            &Some(privacy_definition),
             self.privacy_usage.as_ref(),
             component,
             properties,
@@ -100,6 +121,38 @@ impl Expandable for proto::GaussianMechanism {
     }
 }
 
+impl Report for proto::GaussianMechanism {
+    fn summarize(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        summarize_mechanism(
+            "Gaussian",
+            privacy_definition,
+            &self.privacy_usage,
+            node_id,
+            component,
+            &public_arguments,
+            &properties,
+            release,
+            variable_names,
+            |sensitivity, usage| {
+                let (epsilon, delta) = (get_epsilon(usage)?, get_delta(usage)?);
+                Ok(Some(if self.analytic {
+                    get_analytic_gaussian_sigma(epsilon, delta, sensitivity)
+                } else {
+                    sensitivity * (2.0 * (1.25 / delta).ln()).sqrt() / epsilon
+                }))
+            })
+    }
+}
+
 impl Mechanism for proto::GaussianMechanism {
     fn get_privacy_usage(
         &self,
@@ -113,12 +166,17 @@ impl Mechanism for proto::GaussianMechanism {
             .map_err(prepend("data:"))?;
 
         Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
-            .map(|usage| usage.effective_to_actual(
-                data_property.sample_proportion.unwrap_or(1.),
-                data_property.c_stability,
-                privacy_definition.group_size))
+            .map(|usage| truncated_usage(usage, self.analytic, self.max_noise_sigmas, data_property.num_records)?
+                .effective_to_actual(
+                    data_property.sample_proportion.unwrap_or(1.),
+                    data_property.c_stability,
+                    privacy_definition.group_size))
             .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
     }
+
+    fn sensitivity_space(&self) -> Result<SensitivitySpace> {
+        Ok(SensitivitySpace::KNorm(2))
+    }
 }
 
 
@@ -153,7 +211,8 @@ impl Accuracy for proto::GaussianMechanism {
                 distance: Some(Distance::Approximate(DistanceApproximate {
                     epsilon: sigma * 2.0_f64.sqrt() * erf::erf_inv(1.0_f64 - accuracy.alpha),
                     delta,
-                }))
+                })),
+                units: proto::privacy_usage::Units::Central as i32
             })
         }).collect()).transpose()
     }
@@ -191,6 +250,52 @@ impl Accuracy for proto::GaussianMechanism {
     }
 }
 
+/// Privacy usage of the gaussian mechanism when noise beyond `max_noise_sigmas` standard deviations is resampled.
+///
+/// Neighboring releases share the same normalizing constant `Z = 1 - 2 P(N(0,1) > m)` in each of the `num_records`
+/// noised values of a column, so the privacy loss where their supports overlap is that of the untruncated mechanism,
+/// with delta inflated by `1 / Z^num_records`.
+/// Where the supports differ, an interval of width `sensitivity = r sigma` at the edge of the cap,
+/// each noised value carries mass `P(m - r < N(0,1) <= m) / Z`, which is charged to delta.
+pub fn truncated_usage(
+    usage: &proto::PrivacyUsage,
+    analytic: bool,
+    max_noise_sigmas: Float,
+    num_records: Option<i64>,
+) -> Result<proto::PrivacyUsage> {
+    if max_noise_sigmas == 0. {
+        return Ok(usage.clone())
+    }
+    if max_noise_sigmas < 0. {
+        return Err("max_noise_sigmas: must be non-negative".into())
+    }
+    let num_records = num_records
+        .ok_or_else(|| Error::from("max_noise_sigmas: the number of records must be known to account for truncation"))?;
+
+    let (epsilon, delta) = (get_epsilon(usage)?, get_delta(usage)?);
+    // sensitivity in units of the standard deviation of the noise
+    let r = if analytic {
+        1. / get_analytic_gaussian_sigma(epsilon, delta, 1.)
+    } else {
+        epsilon / (2.0 * (1.25 / delta).ln()).sqrt()
+    };
+    if max_noise_sigmas <= r {
+        return Err(format!("max_noise_sigmas: the cap of {} must exceed the sensitivity of {} standard deviations, so that neighboring releases overlap", max_noise_sigmas, r).into())
+    }
+
+    let normalizer = 1. - 2. * phi(-max_noise_sigmas);
+    let edge_mass = phi(max_noise_sigmas) - phi(max_noise_sigmas - r);
+    let delta = delta / normalizer.powi(num_records as i32)
+        + num_records as Float * edge_mass / normalizer;
+
+    Ok(proto::PrivacyUsage {
+        distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+            epsilon, delta,
+        })),
+        units: usage.units
+    })
+}
+
 /// Integrate gaussian from -inf to t
 /// P(N(0,1)≤t)
 ///
@@ -326,10 +431,57 @@ pub fn get_analytic_gaussian_sigma(epsilon: f64, delta: f64, sensitivity: f64) -
 
 #[cfg(test)]
 mod test_analytic_gaussian {
-    use crate::components::gaussian_mechanism::get_analytic_gaussian_sigma;
+    use ndarray::arr2;
+
+    use crate::components::Mechanism;
+    use crate::components::clamp::test_clamp;
+    use crate::components::gaussian_mechanism::{get_analytic_gaussian_sigma, truncated_usage};
+    use crate::base::{test_data, Value};
+    use crate::proto;
+    use crate::utilities::compute_mechanism_sensitivity;
+    use crate::utilities::privacy::{get_delta, get_epsilon};
 
     #[test]
     fn test_analytic_gaussian_sigma() {
         println!("{:?}", get_analytic_gaussian_sigma(0.5, 1E-10, 1.))
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_truncated_usage() {
+        let usage = test_data::privacy_usage(0.5, 1e-6);
+
+        // without a cap, the usage is unchanged
+        assert_eq!(truncated_usage(&usage, true, 0., None).unwrap(), usage);
+
+        // truncation leaves epsilon unchanged, and only adds to delta
+        let truncated = truncated_usage(&usage, true, 4., Some(1)).unwrap();
+        assert_eq!(get_epsilon(&truncated).unwrap(), 0.5);
+        assert!(get_delta(&truncated).unwrap() > 1e-6);
+
+        // the delta of each noised value is charged
+        let vector = truncated_usage(&usage, true, 4., Some(10)).unwrap();
+        assert!(get_delta(&vector).unwrap() > get_delta(&truncated).unwrap());
+
+        // the number of noised values must be known
+        assert!(truncated_usage(&usage, true, 4., None).is_err());
+    }
+
+    #[test]
+    fn test_gaussian_mechanism_sensitivity() {
+        let (mut analysis, clamped) = test_clamp::utilities::analysis_i64_cat(
+            arr2(&[[1i64], [2], [1]]).into_dyn().into(),
+            Value::Jagged(vec![vec![1i64, 2]].into()), Some((-1).into()));
+        analysis.privacy_definition.neighboring = proto::privacy_definition::Neighboring::Substitute as i32;
+        let count = analysis.count(clamped).group_by(true).build();
+        let aggregator = analysis.properties(count).unwrap()
+            .array().unwrap().aggregator.clone().unwrap();
+
+        let sensitivity = |mechanism: &dyn Mechanism| compute_mechanism_sensitivity(
+            mechanism, &analysis.privacy_definition, &aggregator)
+            .unwrap().array().unwrap().cast_float().unwrap();
+
+        // a substituted record changes two cells, so the L1 sensitivity is not reused by the gaussian
+        assert!(sensitivity(&proto::LaplaceMechanism::default()).iter().all(|v| *v == 2.));
+        assert!(sensitivity(&proto::GaussianMechanism::default()).iter().all(|v| *v == 2f64.sqrt()));
+    }
+}
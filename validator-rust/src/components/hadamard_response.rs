@@ -0,0 +1,219 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, Float, proto, Warnable};
+use crate::base::{ArrayProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Mechanism, Report};
+use crate::components::cast::get_index_categories;
+use crate::errors::*;
+use crate::utilities::{get_array_property, get_literal, prepend};
+use crate::utilities::inference::infer_property;
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+use crate::utilities::privacy::{compose_privacy_usages, get_delta, get_epsilon, privacy_usage_check};
+
+impl Component for proto::HadamardResponse {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy_definition must be defined")?;
+
+        let data_property = get_array_property(&properties, "data")?;
+        data_property.assert_is_not_aggregated()?;
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
+        if data_property.num_columns()? != 1 {
+            return Err("data: must contain one column".into())
+        }
+
+        let num_categories = get_index_categories(&public_arguments, &properties, &data_property)?
+            .first().map(Vec::len)
+            .ok_or_else(|| Error::from("categories: must be defined for one column"))?;
+        if num_categories == 0 {
+            return Err("categories: must be non-empty".into())
+        }
+
+        let privacy_usage = compose_privacy_usages(&self.privacy_usage)?;
+        let debias_scale = hadamard_debias_scale(&privacy_usage)?;
+
+        // the number of records is not checked against delta, because delta is zero
+        let warnings = privacy_usage_check(
+            &privacy_usage,
+            None,
+            privacy_definition.strict_parameter_checks,
+            privacy_definition.allow_unknown_num_records)?;
+
+        // each estimate is the debiased mean of reports in {-1, 1}
+        Ok(Warnable(ArrayProperties {
+            num_records: Some(num_categories as i64),
+            nature: Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(vec![Some(-debias_scale)]),
+                upper: Vector1DNull::Float(vec![Some(debias_scale)]),
+            })),
+            dataset_id: Some(node_id as i64),
+            ..ArrayProperties::dp_release(node_id, DataType::Float, data_property.group_id.clone())
+        }.into(), warnings))
+    }
+}
+
+impl Expandable for proto::HadamardResponse {
+    /// Materialize the categories of the data as an argument, and convert the per-record usage to an effective usage.
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        mut maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let data_property = get_array_property(properties, "data")?;
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy definition must be defined")?;
+
+        let mut noise_component = component.clone();
+
+        if !properties.contains_key::<IndexKey>(&"categories".into()) {
+            let categories = data_property.categories().map_err(prepend("data:"))?;
+
+            maximum_id += 1;
+            let id_categories = maximum_id;
+            let (patch_node, release) = get_literal(Value::Jagged(categories), component.submission)?;
+            expansion.computation_graph.insert(id_categories, patch_node);
+            expansion.properties.insert(id_categories, infer_property(&release.value, None, id_categories)?);
+            expansion.releases.insert(id_categories, release);
+            noise_component.insert_argument(&"categories".into(), id_categories);
+        }
+
+        // each record is privatized independently, so subsampling does not amplify the local usage
+        if let Some(proto::component::Variant::HadamardResponse(variant)) = &mut noise_component.variant {
+            variant.privacy_usage = self.privacy_usage.iter()
+                .map(|usage| usage.actual_to_effective(
+                    1., data_property.c_stability, privacy_definition.group_size))
+                .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+        } else { return Err(Error::from("Variant must be defined")) }
+
+        expansion.computation_graph.insert(component_id, noise_component);
+        Ok(expansion)
+    }
+}
+
+impl Mechanism for proto::HadamardResponse {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = get_array_property(properties, "data")?;
+
+        Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                1., data_property.c_stability, privacy_definition.group_size))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    }
+
+    fn sensitivity_space(&self) -> Result<SensitivitySpace> {
+        Ok(SensitivitySpace::KNorm(1))
+    }
+}
+
+impl Report for proto::HadamardResponse {
+    fn summarize(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        _properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let privacy_usage = compose_privacy_usages(&self.privacy_usage)?;
+
+        let variable_name = variable_names
+            .and_then(|names| names.first()).cloned()
+            .unwrap_or_else(|| "[Unknown]".into());
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "HadamardResponse".to_string(),
+            variables: serde_json::json!(variable_name.to_string()),
+            release_info: value_to_json(release)?,
+            privacy_loss: privacy_usage_to_json(&privacy_usage),
+            accuracy: None,
+            relative_accuracy: None,
+            submission: component.submission,
+            node_id,
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "".to_string(),
+                cite: "https://arxiv.org/abs/1802.04705".to_string(),
+                mechanism: "HadamardResponse".to_string(),
+                argument: serde_json::json!({
+                    "debias_scale": hadamard_debias_scale(&privacy_usage)?
+                }),
+            },
+        }]))
+    }
+}
+
+/// Factor by which the mean of the reported signs is scaled to debias the frequency estimates.
+///
+/// Each sign is kept with probability `e^epsilon / (e^epsilon + 1)`,
+/// so the expected reported sign is the true sign scaled by `tanh(epsilon / 2)`.
+/// The usage must be in local units, as it is spent on each record independently.
+pub fn hadamard_debias_scale(privacy_usage: &proto::PrivacyUsage) -> Result<Float> {
+    use proto::privacy_usage::Units;
+
+    if Units::from_i32(privacy_usage.units) != Some(Units::Local) {
+        return Err("privacy_usage: units must be local, as each record is privatized independently".into())
+    }
+    let epsilon = get_epsilon(privacy_usage)?;
+    if epsilon <= 0. {
+        return Err("epsilon: must be greater than zero".into())
+    }
+    if get_delta(privacy_usage)? != 0. {
+        return Err("delta: hadamard response is pure, so delta must be zero".into())
+    }
+    Ok(1. / (epsilon / 2.).tanh())
+}
+
+#[cfg(test)]
+mod test_hadamard_response {
+    use crate::base::{test_data, Value};
+    use crate::components::impute::test_impute;
+
+    #[test]
+    fn test_hadamard_response() {
+        let (mut analysis, imputed) = test_impute::utilities::analysis_string_cat(
+            test_data::array1d_string_10_uniform(), None, None);
+        let num_categories = analysis.properties(imputed).unwrap()
+            .array().unwrap().categories().unwrap().num_records()[0];
+
+        let estimates = analysis.hadamard_response(imputed, vec![test_data::local_privacy_usage(1., 0.)]).build();
+        let properties = analysis.properties(estimates).unwrap();
+        let properties = properties.array().unwrap();
+
+        // one releasable estimate per category, marked as privatized
+        assert!(properties.releasable && properties.noised);
+        assert_eq!(properties.num_records, Some(num_categories));
+        assert_eq!(properties.upper_float().unwrap()[0], 1. / 0.5f64.tanh());
+
+        // the usage is spent on each record, so it must be local
+        let central = analysis.hadamard_response(imputed, vec![test_data::privacy_usage(1., 0.)]).build();
+        assert!(analysis.properties(central).is_err());
+
+        // explicit categories must contain every category of the data
+        let categories = analysis.literal()
+            .value(Value::Jagged(vec![vec!["a".to_string()]].into()))
+            .value_public(true).build();
+        let subset = analysis.hadamard_response(imputed, vec![test_data::local_privacy_usage(1., 0.)])
+            .categories(categories).build();
+        assert!(analysis.properties(subset).is_err());
+    }
+}
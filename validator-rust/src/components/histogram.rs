@@ -1,10 +1,10 @@
 use crate::errors::*;
 
-use crate::{proto, Warnable, base};
+use crate::{proto, Warnable, base, Integer};
 
 use crate::components::{Component, Sensitivity, Expandable};
 use crate::base::{IndexKey, Value, NodeProperties, AggregatorProperties, SensitivitySpace, ValueProperties, DataType, NatureContinuous, Nature, Vector1DNull, Jagged};
-use crate::utilities::{prepend, get_literal};
+use crate::utilities::{prepend, get_literal, privacy::scale_by_contributions};
 use ndarray::{arr1, Array};
 use indexmap::map::IndexMap;
 use crate::utilities::inference::infer_property;
@@ -25,6 +25,7 @@ impl Component for proto::Histogram {
         if !data_property.releasable {
             data_property.assert_is_not_aggregated()?;
         }
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
 
         // this check is already guaranteed by the state space, but still included for safety
         if data_property.data_type == DataType::Unknown {
@@ -42,6 +43,7 @@ impl Component for proto::Histogram {
         data_property.aggregator = Some(AggregatorProperties::new(
             proto::component::Variant::Histogram(self.clone()),
             properties, num_columns));
+        data_property.monotone = false;
 
         data_property.nature = Some(Nature::Continuous(NatureContinuous {
             lower: Vector1DNull::Int((0..num_columns).map(|_| Some(0)).collect()),
@@ -118,7 +120,7 @@ impl Expandable for proto::Histogram {
                         "categories".into() => *categories_id,
                         "null_value".into() => *null_id
                     ])),
-                    variant: Some(proto::component::Variant::Clamp(proto::Clamp {})),
+                    variant: Some(proto::component::Variant::Clamp(proto::Clamp { null_policy: "Pass".to_string() })),
                     omit: true,
                     submission: component.submission,
                 });
@@ -192,43 +194,44 @@ impl Sensitivity for proto::Histogram {
                 let num_columns = data_property.num_columns()?;
 
                 macro_rules! wrap {
-                    ($sensitivity:expr) => {
+                    ($sensitivity:expr) => {{
+                        let sensitivity = scale_by_contributions(privacy_definition, $sensitivity)?;
                         Ok(Array::from_shape_vec(
                             vec![categories_length as usize, num_columns as usize],
                             (0..categories_length)
                                 .map(|_| (0..num_columns)
-                                    .map(|_| $sensitivity)
+                                    .map(|_| sensitivity)
                                     .collect::<Vec<_>>())
                                 .flatten()
                                 .collect::<Vec<_>>())?.into())
-                    }
+                    }}
                 }
 
                 // SENSITIVITY DERIVATIONS
                 match (neighboring_type, categories_length, num_records) {
                     // one category, known N. Applies to any neighboring type.
-                    (_, 1, Some(_)) => wrap!(0),
+                    (_, 1, Some(_)) => wrap!(0 as Integer),
 
                     // one category, unknown N. The sensitivity here is really zero-- artificially raised
-                    (Substitute, 1, None) => wrap!(1),
+                    (Substitute, 1, None) => wrap!(1 as Integer),
                     // two categories, known N. Knowing N determines the second category
                     // uncomment if Evaluable is updated to produce [#cat1, n - #cat1]
                     // (Substitute, 2, Some(_)) => wrap!(1),
 
                     // one category, unknown N
-                    (AddRemove, 1, None) => wrap!(1),
+                    (AddRemove, 1, None) => wrap!(1 as Integer),
                     // two categories, known N
                     // uncomment if Evaluable is updated to produce [#cat1, n - #cat1]
                     // (AddRemove, 2, Some(_)) => wrap!(1),
 
                     // over two categories, N either known or unknown. Record may switch from one bin to another.
                     (Substitute, _, _) => match k {
-                        1 => wrap!(2),
+                        1 => wrap!(2 as Integer),
                         2 => wrap!(2.0_f64.sqrt()),
                         _ => Err("KNorm sensitivity is only supported in L1 and L2 spaces".into())
                     } ,
                     // over two categories, N either known or unknown. Only one bin may be edited.
-                    (AddRemove, _, _) => wrap!(1),
+                    (AddRemove, _, _) => wrap!(1 as Integer),
                 }
 
             },
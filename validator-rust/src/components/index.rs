@@ -1,13 +1,14 @@
 use crate::errors::*;
 
-use crate::base::{Array, Value, ValueProperties, IndexKey};
+use crate::base::{Array, ArrayProperties, DataType, Value, ValueProperties, IndexKey};
 
 use crate::{proto, base, Warnable};
 use crate::components::{Component, Named};
 
 use ndarray::ArrayD;
 use ndarray::prelude::*;
-use crate::utilities::{get_argument};
+use crate::utilities::{get_argument, prepend};
+use crate::components::transforms::propagate_binary_shape;
 use indexmap::map::IndexMap;
 use crate::utilities::properties::{select_properties, stack_properties};
 
@@ -21,6 +22,10 @@ impl Component for proto::Index {
     ) -> Result<Warnable<ValueProperties>> {
         let data_property = properties.get::<IndexKey>(&"data".into())
             .ok_or("data: missing")?.clone();
+        let row_mask_property = properties.get::<IndexKey>(&"row_mask".into())
+            .map(|property| property.array().map_err(prepend("row_mask:")).cloned())
+            .transpose()?;
+        let is_row_index = public_arguments.contains_key::<IndexKey>(&"rows".into()) || row_mask_property.is_some();
 
         let mut dimensionality = None;
 
@@ -105,6 +110,9 @@ impl Component for proto::Index {
                     mask.into_iter().enumerate().filter(|(_, mask)| *mask)
                         .map(|(idx, _)| select_properties(&data_property, idx))
                         .collect::<Result<Vec<ValueProperties>>>()
+                } else if is_row_index {
+                    // only rows are selected, so every column is retained
+                    return select_rows(data_property, &public_arguments, row_mask_property, node_id).map(Warnable::new)
                 } else {
                     return Err("either indices or mask must be supplied".into())
                 }
@@ -113,10 +121,84 @@ impl Component for proto::Index {
             ValueProperties::Function(_) => Err("indexing is not suppported on functions".into())
         }?;
 
-        stack_properties(&properties, dimensionality, node_id).map(Warnable::new)
+        let property = stack_properties(&properties, dimensionality, node_id)?;
+        if !is_row_index {
+            return Ok(Warnable::new(property))
+        }
+        select_rows(property.array()?.clone(), &public_arguments, row_mask_property, node_id).map(Warnable::new)
     }
 }
 
+/// Select rows from indexed data, either by public position or by a boolean mask.
+///
+/// A public positional slice, or a public mask, of data with a known number of records retains a known number of records.
+/// Masking by a private mask, like filtering, leaves the number of records unknown.
+fn select_rows(
+    mut data_property: ArrayProperties,
+    public_arguments: &IndexMap<IndexKey, &Value>,
+    row_mask_property: Option<ArrayProperties>,
+    node_id: u32
+) -> Result<ValueProperties> {
+    if !data_property.releasable {
+        data_property.assert_is_not_aggregated()?;
+    }
+
+    if let Some(rows) = public_arguments.get::<IndexKey>(&"rows".into()) {
+        let rows = to_name_vec(rows.ref_array()?.ref_int()?.clone())?;
+        if rows.iter().any(|row| *row < 0) {
+            return Err("rows: indices must be non-negative".into())
+        }
+        // a record selected more than once would influence the output more than once
+        if rows.iter().collect::<std::collections::HashSet<_>>().len() != rows.len() {
+            return Err("rows: indices must be distinct".into())
+        }
+        if let Some(num_records) = data_property.num_records {
+            if rows.iter().any(|row| *row >= num_records) {
+                return Err("rows: index out of bounds".into())
+            }
+        }
+
+        // when the number of records is unknown, the selected rows may not exist
+        data_property.num_records = data_property.num_records.map(|_| rows.len() as i64);
+        data_property.num_records_lower_bound = None;
+        data_property.is_not_empty = data_property.num_records.map(|n| n > 0).unwrap_or(false);
+
+    } else if let Some(row_mask_property) = row_mask_property {
+        if !row_mask_property.releasable {
+            row_mask_property.assert_is_not_aggregated()?;
+        }
+        if row_mask_property.data_type != DataType::Bool {
+            return Err("row_mask: must be boolean".into())
+        }
+        if row_mask_property.num_columns()? != 1 {
+            return Err("row_mask: number of columns must be one".into())
+        }
+        if row_mask_property.nullity {
+            return Err("row_mask: may not contain nulls".into())
+        }
+        propagate_binary_shape(&data_property, &row_mask_property)?;
+
+        match public_arguments.get::<IndexKey>(&"row_mask".into()) {
+            Some(row_mask) if data_property.num_records.is_some() => {
+                let num_records = row_mask.ref_array()?.ref_bool()?.iter().filter(|v| **v).count() as i64;
+                data_property.num_records = Some(num_records);
+                data_property.is_not_empty = num_records > 0;
+            }
+            // the number of selected rows depends on the private mask
+            _ => {
+                data_property.num_records = None;
+                data_property.num_records_lower_bound = None;
+                data_property.is_not_empty = false;
+            }
+        }
+    }
+
+    // a subset of rows is not conformable with the rows of the data it was selected from
+    data_property.dataset_id = Some(node_id as i64);
+
+    Ok(ValueProperties::Array(data_property))
+}
+
 impl Named for proto::Index {
     fn get_names(
         &self,
@@ -169,3 +251,59 @@ pub fn to_name_vec<T: Clone>(columns: ArrayD<T>) -> Result<Vec<T>> {
         _ => Err("dimensionality of column names must be less than 2".into())
     }
 }
+
+#[cfg(test)]
+mod test_index {
+    use ndarray::arr1;
+
+    use crate::base::test_data;
+    use crate::bindings::Analysis;
+    use crate::components::literal::test_literal;
+    use crate::components::resize::test_resize;
+    use crate::proto;
+
+    /// Select the rows of the data with the given row selector.
+    fn index_rows(analysis: &mut Analysis, data: u32, selector: &str, selector_id: u32) -> u32 {
+        analysis.component_count += 1;
+        analysis.components.insert(analysis.component_count, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => data, selector.into() => selector_id])),
+            variant: Some(proto::component::Variant::Index(proto::Index {})),
+            omit: true,
+            submission: 0,
+        });
+        analysis.component_count
+    }
+
+    #[test]
+    fn test_index_rows() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        let source = *analysis.components.keys().min().unwrap();
+        test_literal::privatize(&mut analysis, source);
+
+        // a public positional slice retains a known number of records
+        let rows = analysis.literal().value(arr1(&[0i64, 2, 4]).into_dyn().into())
+            .value_public(true).build();
+        let sliced = index_rows(&mut analysis, resized, "rows", rows);
+        assert_eq!(analysis.properties(sliced).unwrap().array().unwrap().num_records, Some(3));
+
+        // positions beyond the number of records are rejected
+        let rows = analysis.literal().value(arr1(&[0i64, 10]).into_dyn().into())
+            .value_public(true).build();
+        let out_of_bounds = index_rows(&mut analysis, resized, "rows", rows);
+        assert!(analysis.properties(out_of_bounds).is_err());
+
+        // a private boolean mask leaves the number of records unknown
+        let threshold = analysis.literal().value(5.0.into()).value_public(true).build();
+        let mask = analysis.greater_than(resized, threshold).build();
+        let masked = index_rows(&mut analysis, resized, "row_mask", mask);
+        let masked_property = analysis.properties(masked).unwrap().array().unwrap().clone();
+        assert_eq!(masked_property.num_records, None);
+        assert!(!masked_property.is_not_empty);
+
+        // the masked data may no longer be combined with data of a known number of records
+        let combined = analysis.multiply(masked, resized).build();
+        assert!(analysis.properties(combined).is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{DataType, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::Component;
+use crate::errors::*;
+use crate::utilities::get_array_property;
+
+impl Component for proto::Isotonic {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        _node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let mut data_property = get_array_property(&properties, "data")?;
+
+        // the projection couples records, so it may only be applied as post-processing
+        data_property.assert_is_releasable()?;
+        data_property.assert_non_null()?;
+
+        data_property.nature = match data_property.data_type {
+            // averages of values in a column stay within the bounds of the column
+            DataType::Float | DataType::Int => match (data_property.lower_float_option(), data_property.upper_float_option()) {
+                (Ok(lower), Ok(upper)) => Some(Nature::Continuous(NatureContinuous {
+                    lower: Vector1DNull::Float(lower),
+                    upper: Vector1DNull::Float(upper),
+                })),
+                _ => None
+            },
+            _ => return Err("data: atomic type must be numeric".into())
+        };
+        data_property.data_type = DataType::Float;
+        data_property.monotone = true;
+
+        Ok(ValueProperties::Array(data_property).into())
+    }
+}
+
+#[cfg(test)]
+mod test_isotonic {
+    use ndarray::{arr1, arr2};
+
+    use crate::bindings::Analysis;
+
+    #[test]
+    fn test_isotonic_properties() {
+        let mut analysis = Analysis::new();
+        let data = analysis.literal()
+            .value(arr2(&[[0.1], [0.3], [0.2], [0.6]]).into_dyn().into())
+            .value_public(true).build();
+        let isotonic = analysis.isotonic(data).build();
+
+        // the bounds of public data are inferred from the data itself
+        let isotonic_property = analysis.properties(isotonic).unwrap().array().unwrap().clone();
+        assert!(isotonic_property.monotone);
+        assert_eq!(isotonic_property.lower_float().unwrap(), vec![0.1]);
+        assert_eq!(isotonic_property.upper_float().unwrap(), vec![0.6]);
+
+        // transformations that may reorder values invalidate monotonicity
+        let negated = analysis.negative(isotonic).build();
+        assert!(!analysis.properties(negated).unwrap().array().unwrap().monotone);
+
+        // private data may not be projected
+        let private = analysis.literal()
+            .value(arr1(&[0.1, 0.3, 0.2, 0.6]).into_dyn().into())
+            .value_public(false).build();
+        let isotonic = analysis.isotonic(private).build();
+        assert!(analysis.properties(isotonic).is_err());
+    }
+}
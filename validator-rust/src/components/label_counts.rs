@@ -0,0 +1,74 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{DataframeProperties, IndexKey, NodeProperties, Value, ValueProperties};
+use crate::components::Component;
+use crate::errors::*;
+use crate::utilities::{get_array_property, prepend};
+
+impl Component for proto::LabelCounts {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        _node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let data_property = get_array_property(&properties, "data")?;
+        data_property.assert_is_releasable()?;
+        if data_property.num_columns()? != 1 {
+            return Err("data: must contain one column".into())
+        }
+
+        // labels may be public, or released by a mechanism, as with estimated bin edges
+        let categories_property = get_array_property(&properties, "categories")?;
+        categories_property.assert_is_releasable().map_err(prepend("categories:"))?;
+        if categories_property.num_columns()? != 1 {
+            return Err("categories: must be a vector".into())
+        }
+        if data_property.num_records()? != categories_property.num_records()? {
+            return Err("categories: must contain one label for each count".into())
+        }
+
+        // the labels and counts are paired row by row
+        Ok(ValueProperties::Dataframe(DataframeProperties {
+            children: indexmap![
+                IndexKey::from("category") => categories_property.into(),
+                IndexKey::from("count") => data_property.into()
+            ]
+        }).into())
+    }
+}
+
+#[cfg(test)]
+mod test_label_counts {
+    use ndarray::arr1;
+
+    use crate::base::IndexKey;
+    use crate::bindings::Analysis;
+    use crate::Integer;
+
+    #[test]
+    fn test_label_counts_properties() {
+        let mut analysis = Analysis::new();
+        let counts = analysis.literal()
+            .value(arr1::<Integer>(&[3, 4, 5]).into_dyn().into())
+            .value_public(true).build();
+        let labels = analysis.literal()
+            .value(arr1(&["a".to_string(), "b".to_string(), "c".to_string()]).into_dyn().into())
+            .value_public(true).build();
+        let labeled = analysis.label_counts(counts, labels).build();
+
+        let properties = analysis.properties(labeled).unwrap();
+        let children = &properties.dataframe().unwrap().children;
+        assert_eq!(children.keys().cloned().collect::<Vec<IndexKey>>(), vec!["category".into(), "count".into()]);
+        assert_eq!(children[&IndexKey::from("category")].array().unwrap().num_records, Some(3));
+
+        // each count must have a label
+        let short_labels = analysis.literal()
+            .value(arr1(&["a".to_string()]).into_dyn().into())
+            .value_public(true).build();
+        let labeled = analysis.label_counts(counts, short_labels).build();
+        assert!(analysis.properties(labeled).is_err());
+    }
+}
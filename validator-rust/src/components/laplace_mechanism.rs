@@ -1,24 +1,26 @@
 use indexmap::map::IndexMap;
 use itertools::Itertools;
 
-use crate::{base, proto, Warnable};
+use crate::{base, Float, proto, Warnable};
 use crate::base::{DataType, IndexKey, NodeProperties, SensitivitySpace, Value, ValueProperties, ArrayProperties};
-use crate::components::{Accuracy, Component, Expandable, Mechanism, Sensitivity};
+use crate::components::{Accuracy, Component, Expandable, Mechanism, Report};
 use crate::errors::*;
-use crate::utilities::{expand_mechanism, prepend};
-use crate::utilities::privacy::{get_epsilon, privacy_usage_check, spread_privacy_usage};
+use crate::utilities::{compute_mechanism_sensitivity, expand_mechanism, prepend, get_seed, get_sensitivity_override, summarize_mechanism, SEEDED_RELEASE_WARNING, SENSITIVITY_OVERRIDE_WARNING};
+use crate::utilities::json::JSONRelease;
+use crate::utilities::privacy::{compose_privacy_usages, get_delta, get_epsilon, get_aggregated_num_records, privacy_usage_check, spread_privacy_usage, get_mechanism_privacy_definition};
 
 impl Component for proto::LaplaceMechanism {
     fn propagate_property(
         &self,
         privacy_definition: &Option<proto::PrivacyDefinition>,
-        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: base::NodeProperties,
         _node_id: u32,
     ) -> Result<Warnable<ValueProperties>> {
 
-        let privacy_definition = privacy_definition.as_ref()
-            .ok_or_else(|| "privacy_definition must be defined")?;
+        let privacy_definition = &get_mechanism_privacy_definition(
+            privacy_definition.as_ref().ok_or_else(|| "privacy_definition must be defined")?,
+            &public_arguments, &properties)?;
 
         if privacy_definition.protect_floating_point {
             return Err("Floating-point protections are enabled. The laplace mechanism is susceptible to floating-point attacks.".into())
@@ -27,6 +29,7 @@ impl Component for proto::LaplaceMechanism {
         let mut data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
             .ok_or("data: missing")?.array()
             .map_err(prepend("data:"))?.clone();
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
 
         if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
             return Err("data: atomic type must be numeric".into());
@@ -35,25 +38,38 @@ impl Component for proto::LaplaceMechanism {
         let aggregator = data_property.aggregator.clone()
             .ok_or_else(|| Error::from("aggregator: missing"))?;
 
-        // sensitivity must be computable
-        aggregator.component.compute_sensitivity(
-            privacy_definition,
-            &aggregator.properties,
-            &SensitivitySpace::KNorm(1))?.array()?.cast_float()?;
+        // sensitivity must be computable, unless certified by the user
+        let sensitivity_override = get_sensitivity_override(privacy_definition, &public_arguments, &properties)?;
+        if sensitivity_override.is_none() {
+            compute_mechanism_sensitivity(
+                self, privacy_definition, &aggregator)?.array()?.cast_float()?;
+        }
+
+        let num_records = get_aggregated_num_records(&aggregator);
 
         // make sure lipschitz constants are available as a float array
         aggregator.lipschitz_constants.array()?.cast_float()?;
 
-        let privacy_usage = self.privacy_usage.iter().cloned().map(Ok)
-            .fold1(|l, r| l? + r?).ok_or_else(|| "privacy_usage: must be defined")??;
+        let privacy_usage = compose_privacy_usages(&self.privacy_usage)?;
 
-        let warnings = privacy_usage_check(
+        // resampling noise beyond the cap adds to the delta of the release
+        let privacy_usage = truncated_usage(&privacy_usage, self.max_noise_sigmas, data_property.num_records)?;
+
+        let mut warnings = privacy_usage_check(
             &privacy_usage,
-            data_property.num_records,
-            privacy_definition.strict_parameter_checks)?;
+            num_records,
+            privacy_definition.strict_parameter_checks,
+            privacy_definition.allow_unknown_num_records)?;
 
-        data_property.releasable = true;
-        data_property.aggregator = None;
+        data_property.mark_released();
+
+        // seeded noise is reproducible, and offers no privacy
+        if get_seed(&public_arguments, &properties)?.is_some() {
+            warnings.push(SEEDED_RELEASE_WARNING.into());
+        }
+        if sensitivity_override.is_some() {
+            warnings.push(SENSITIVITY_OVERRIDE_WARNING.into());
+        }
 
         Ok(Warnable(data_property.into(), warnings))
     }
@@ -65,14 +81,18 @@ impl Expandable for proto::LaplaceMechanism {
         &self,
         privacy_definition: &Option<proto::PrivacyDefinition>,
         component: &proto::Component,
-        _public_arguments: &IndexMap<IndexKey, &Value>,
+        public_arguments: &IndexMap<IndexKey, &Value>,
         properties: &base::NodeProperties,
         component_id: u32,
         maximum_id: u32,
     ) -> Result<base::ComponentExpansion> {
+        let privacy_definition = get_mechanism_privacy_definition(
+            privacy_definition.as_ref().ok_or_else(|| "privacy_definition must be defined")?,
+            public_arguments, properties)?;
+        // an override must be certified before it replaces the derived sensitivity
+        get_sensitivity_override(&privacy_definition, public_arguments, properties)?;
         expand_mechanism(
-            &SensitivitySpace::KNorm(1),
-            privacy_definition,
+            &Some(privacy_definition),
             self.privacy_usage.as_ref(),
             component,
             properties,
@@ -82,6 +102,31 @@ impl Expandable for proto::LaplaceMechanism {
     }
 }
 
+impl Report for proto::LaplaceMechanism {
+    fn summarize(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        summarize_mechanism(
+            "Laplace",
+            privacy_definition,
+            &self.privacy_usage,
+            node_id,
+            component,
+            &public_arguments,
+            &properties,
+            release,
+            variable_names,
+            |sensitivity, usage| Ok(Some(sensitivity / get_epsilon(usage)?)))
+    }
+}
+
 impl Mechanism for proto::LaplaceMechanism {
     fn get_privacy_usage(
         &self,
@@ -94,12 +139,55 @@ impl Mechanism for proto::LaplaceMechanism {
             .map_err(prepend("data:"))?;
 
         Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
-            .map(|usage| usage.effective_to_actual(
-                data_property.sample_proportion.unwrap_or(1.),
-                data_property.c_stability,
-                privacy_definition.group_size))
+            .map(|usage| truncated_usage(usage, self.max_noise_sigmas, data_property.num_records)?
+                .effective_to_actual(
+                    data_property.sample_proportion.unwrap_or(1.),
+                    data_property.c_stability,
+                    privacy_definition.group_size))
             .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
     }
+
+    fn sensitivity_space(&self) -> Result<SensitivitySpace> {
+        Ok(SensitivitySpace::KNorm(1))
+    }
+}
+
+/// Privacy usage of the laplace mechanism when noise beyond `max_noise_sigmas` standard deviations is resampled.
+///
+/// Noise with scale `b = sensitivity / epsilon` is truncated to `[-t b, t b]`, where `t = max_noise_sigmas * sqrt(2)`.
+/// Epsilon is unchanged, because neighboring releases share the same normalizing constant `1 - e^-t`.
+/// However, their supports differ on an interval of width `epsilon b` at the edge of the cap,
+/// which carries mass `(e^-(t - epsilon) - e^-t) / 2` before renormalizing.
+/// Each of the `num_records` noised values in a column may fall into this interval, which is charged to delta.
+pub fn truncated_usage(
+    usage: &proto::PrivacyUsage,
+    max_noise_sigmas: Float,
+    num_records: Option<i64>,
+) -> Result<proto::PrivacyUsage> {
+    if max_noise_sigmas == 0. {
+        return Ok(usage.clone())
+    }
+    if max_noise_sigmas < 0. {
+        return Err("max_noise_sigmas: must be non-negative".into())
+    }
+    let num_records = num_records
+        .ok_or_else(|| Error::from("max_noise_sigmas: the number of records must be known to account for truncation"))?;
+
+    let epsilon = get_epsilon(usage)?;
+    let t = max_noise_sigmas * (2. as Float).sqrt();
+    if t <= epsilon {
+        return Err(format!("max_noise_sigmas: the cap of {} scales must exceed epsilon ({}), so that neighboring releases overlap", t, epsilon).into())
+    }
+
+    let edge_mass = ((epsilon - t).exp() - (-t).exp()) / 2.;
+    let delta = get_delta(usage)? + num_records as Float * edge_mass / (1. - (-t).exp());
+
+    Ok(proto::PrivacyUsage {
+        distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+            epsilon, delta,
+        })),
+        units: usage.units
+    })
 }
 
 
@@ -122,7 +210,8 @@ impl Accuracy for proto::LaplaceMechanism {
                 distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
                     epsilon: (1. / accuracy.alpha).ln() * (sensitivity as f64 / accuracy.value),
                     delta: 0.,
-                }))
+                })),
+                units: proto::privacy_usage::Units::Central as i32
             })
             .collect()))
     }
@@ -151,4 +240,230 @@ impl Accuracy for proto::LaplaceMechanism {
             })
             .collect()))
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test_laplace_mechanism {
+    use ndarray::arr1;
+
+    use crate::base::{IndexKey, ReleaseNode, test_data};
+    use crate::components::resize::test_resize;
+    use crate::utilities::{SEEDED_RELEASE_WARNING, SENSITIVITY_OVERRIDE_WARNING};
+
+    #[test]
+    fn test_bare_mechanism_report() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let sum = analysis.sum(resized).build();
+        let usage = test_data::privacy_usage(1., 0.);
+        let noised = analysis.laplace_mechanism(sum, vec![usage]).build();
+        analysis.release.insert(noised, ReleaseNode::new(arr1(&[50.]).into_dyn().into()));
+
+        let report: serde_json::Value = serde_json::from_str(&crate::generate_report(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap()).unwrap();
+
+        let entries = report.as_array().unwrap().iter()
+            .filter(|entry| entry["nodeID"] == noised)
+            .collect::<Vec<_>>();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["statistic"], "Laplace");
+        assert_eq!(entries[0]["algorithmInfo"]["argument"]["sensitivity"], 10.);
+        assert_eq!(entries[0]["algorithmInfo"]["argument"]["noise_scale"], 10.);
+    }
+
+    #[test]
+    fn test_seeded_mechanism_warning() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let sum = analysis.sum(resized).build();
+        let usage = test_data::privacy_usage(1., 0.);
+        let seed = analysis.literal().value(42.into()).value_public(true).build();
+        let noised = analysis.laplace_mechanism(sum, vec![usage]).seed(seed).build();
+
+        let mut computation_graph = analysis.components.clone();
+        let mut release = analysis.release.clone();
+        let (_, warnings) = crate::utilities::propagate_properties(
+            &Some(analysis.privacy_definition.clone()),
+            &mut computation_graph, &mut release, None, false).unwrap();
+        assert!(warnings.iter().flat_map(|warning| warning.iter())
+            .any(|cause| cause.to_string() == SEEDED_RELEASE_WARNING));
+
+        // the warning is carried into the summary of the release
+        analysis.release.insert(noised, ReleaseNode::new(arr1(&[50.]).into_dyn().into()));
+        let report: serde_json::Value = serde_json::from_str(&crate::generate_report(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap()).unwrap();
+        let entry = report.as_array().unwrap().iter()
+            .find(|entry| entry["nodeID"] == noised).unwrap();
+        assert_eq!(entry["algorithmInfo"]["argument"]["seed"], 42);
+        assert_eq!(entry["algorithmInfo"]["argument"]["warning"], SEEDED_RELEASE_WARNING);
+    }
+
+    #[test]
+    fn test_conflicting_contributions() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let mut noised_sum = |contributions: i64| {
+            let sum = analysis.sum(resized).build();
+            let contributions = analysis.literal().value(contributions.into()).value_public(true).build();
+            analysis.laplace_mechanism(sum, vec![usage.clone()])
+                .max_contributions_per_user(contributions).build()
+        };
+        let one = noised_sum(1);
+        let three = noised_sum(3);
+
+        // the sensitivity of each branch is scaled by its own contribution bound
+        let validate = |analysis: &crate::bindings::Analysis| crate::validate_analysis(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone());
+        assert!(validate(&analysis).unwrap_err().to_string().starts_with(&format!(
+            "mechanisms at node_ids {} and {} assume 1 and 3 contributions per user", one, three)));
+
+        // both branches agree with the contribution bound of the analysis
+        analysis.privacy_definition.max_contributions_per_user = 3;
+        analysis.components.get_mut(&one).unwrap().remove_argument(&"max_contributions_per_user".into());
+        assert!(validate(&analysis).is_ok());
+
+        // an override may not tighten the bound of the analysis, and must fit in 32 bits
+        for contributions in &[2, -1, i64::from(u32::MAX) + 1] {
+            let sum = analysis.sum(resized).build();
+            let contributions = analysis.literal().value((*contributions).into()).value_public(true).build();
+            let noised = analysis.laplace_mechanism(sum, vec![usage.clone()])
+                .max_contributions_per_user(contributions).build();
+            assert!(analysis.properties(noised).is_err());
+        }
+    }
+
+    #[test]
+    fn test_max_noise_sigmas() {
+        use crate::components::laplace_mechanism::truncated_usage;
+        use crate::utilities::privacy::{get_delta, get_epsilon};
+
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let sum = analysis.sum(resized).build();
+        analysis.laplace_mechanism(sum, vec![usage.clone()]).max_noise_sigmas(5.).build();
+
+        // truncation leaves epsilon unchanged, but is charged to delta
+        let total_usage = crate::compute_privacy_usage(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap();
+        let t = 5. * 2f64.sqrt();
+        let expected_delta = ((1. - t).exp() - (-t).exp()) / 2. / (1. - (-t).exp());
+        assert_eq!(get_epsilon(&total_usage).unwrap(), 1.);
+        assert!((get_delta(&total_usage).unwrap() - expected_delta).abs() < 1e-15);
+
+        // a tighter cap costs more delta
+        let loose = get_delta(&truncated_usage(&usage, 5., Some(1)).unwrap()).unwrap();
+        let tight = get_delta(&truncated_usage(&usage, 2., Some(1)).unwrap()).unwrap();
+        assert!(tight > loose);
+
+        // the cap must leave the supports of neighboring releases overlapping
+        let noised = analysis.laplace_mechanism(sum, vec![usage]).max_noise_sigmas(0.5).build();
+        assert!(analysis.properties(noised).is_err());
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_sensitivity_override() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+        analysis.privacy_definition.protect_sensitivity = false;
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let overridden_sum = |analysis: &mut crate::bindings::Analysis, sensitivity: f64, certified: bool| {
+            let sum = analysis.sum(resized).build();
+            let sensitivity = analysis.literal().value(sensitivity.into()).value_public(true).build();
+            let certified = analysis.literal().value(certified.into()).value_public(true).build();
+            analysis.laplace_mechanism(sum, vec![usage.clone()])
+                .sensitivity_override(sensitivity)
+                .sensitivity_certified(certified).build()
+        };
+        let noised = overridden_sum(&mut analysis, 2., true);
+
+        // the certified override calibrates the noise in place of the derived sensitivity of 10
+        let mut computation_graph = analysis.components.clone();
+        let mut release = analysis.release.clone();
+        let (_, warnings) = crate::utilities::propagate_properties(
+            &Some(analysis.privacy_definition.clone()),
+            &mut computation_graph, &mut release, None, false).unwrap();
+        assert!(warnings.iter().flat_map(|warning| warning.iter())
+            .any(|cause| cause.to_string() == SENSITIVITY_OVERRIDE_WARNING));
+        let id_sensitivity = computation_graph[&noised].arguments()[&IndexKey::from("sensitivity")];
+        assert_eq!(release[&id_sensitivity].value.ref_array().unwrap().first_float().unwrap(), 2.);
+
+        // the override and its warning are recorded in the summary
+        analysis.release.insert(noised, ReleaseNode::new(arr1(&[50.]).into_dyn().into()));
+        let report: serde_json::Value = serde_json::from_str(&crate::generate_report(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap()).unwrap();
+        let entry = report.as_array().unwrap().iter()
+            .find(|entry| entry["nodeID"] == noised).unwrap();
+        assert_eq!(entry["algorithmInfo"]["argument"]["sensitivity"], 2.);
+        assert_eq!(entry["algorithmInfo"]["argument"]["noise_scale"], 2.);
+        assert_eq!(entry["algorithmInfo"]["argument"]["sensitivity_warning"], SENSITIVITY_OVERRIDE_WARNING);
+
+        // overrides must be certified, finite and positive
+        let uncertified = overridden_sum(&mut analysis, 2., false);
+        assert!(analysis.properties(uncertified).is_err());
+        let nonpositive = overridden_sum(&mut analysis, 0., true);
+        assert!(analysis.properties(nonpositive).is_err());
+        let infinite = overridden_sum(&mut analysis, f64::INFINITY, true);
+        assert!(analysis.properties(infinite).is_err());
+
+        // overrides are rejected while sensitivities are protected
+        analysis.privacy_definition.protect_sensitivity = true;
+        let protected = overridden_sum(&mut analysis, 2., true);
+        assert!(analysis.properties(protected).is_err());
+    }
+
+    #[test]
+    fn test_noised_data_rejected() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let sum = analysis.sum(resized).build();
+        let usage = test_data::privacy_usage(1., 0.);
+        let noised = analysis.laplace_mechanism(sum, vec![usage.clone()]).build();
+        assert!(analysis.properties(noised).unwrap().array().unwrap().noised);
+
+        let is_rejected = |analysis: &mut crate::bindings::Analysis, node_id: u32| analysis
+            .properties(node_id).unwrap_err().to_string()
+            .contains("data: data has already been released by a mechanism");
+
+        // noising a release again is rejected
+        let renoised = analysis.laplace_mechanism(noised, vec![usage.clone()]).build();
+        assert!(is_rejected(&mut analysis, renoised));
+
+        // as is aggregating a release, before any mechanism is applied
+        let noised_sum = analysis.sum(noised).build();
+        assert!(is_rejected(&mut analysis, noised_sum));
+        let noised_mean = analysis.mean(noised).build();
+        assert!(is_rejected(&mut analysis, noised_mean));
+        let noised_count = analysis.count(noised).build();
+        assert!(is_rejected(&mut analysis, noised_count));
+        let renoised_sum = analysis.laplace_mechanism(noised_sum, vec![usage]).build();
+        assert!(is_rejected(&mut analysis, renoised_sum));
+
+        // post-processing a release needs no mechanism
+        let doubled = analysis.add(noised, noised).build();
+        assert!(analysis.properties(doubled).unwrap().array().unwrap().releasable);
+    }
+}
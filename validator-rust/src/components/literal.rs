@@ -31,7 +31,11 @@ impl Component for proto::Literal {
             dimensionality: None,
             group_id: vec![],
             naturally_ordered: true,
-            sample_proportion: None
+            sample_proportion: None,
+            monotone: false,
+            data_derived: false,
+            num_records_lower_bound: None,
+            noised: false
         }).into())
     }
 }
@@ -102,6 +106,7 @@ impl Named for proto::Literal {
 #[cfg(test)]
 pub mod test_literal {
     use crate::base::Value;
+    use crate::proto;
     use crate::bindings::Analysis;
     use crate::base::test_data::array1d_f64_10_uniform;
 
@@ -113,6 +118,26 @@ pub mod test_literal {
         (analysis, literal)
     }
 
+    /// Replace a literal with a column of a private, materialized dataset,
+    /// so that nothing derived from it is public.
+    pub fn privatize(analysis: &mut Analysis, literal: u32) {
+        let column_names = analysis.literal()
+            .value(ndarray::arr1(&["A".to_string()]).into_dyn().into())
+            .value_public(true).build();
+        let dataset = analysis.materialize(column_names, "data.csv".to_string()).build();
+        let name = analysis.literal()
+            .value("A".to_string().into()).value_public(true).build();
+
+        analysis.release.remove(&literal);
+        analysis.components.insert(literal, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => dataset, "names".into() => name])),
+            variant: Some(proto::component::Variant::Index(proto::Index {})),
+            omit: true,
+            submission: 0,
+        });
+    }
+
     #[test]
     fn test_literal() {
         let (analysis, literal) = analysis_literal(array1d_f64_10_uniform(), true);
@@ -2,9 +2,10 @@ use crate::errors::*;
 
 use crate::{proto, base};
 
-use crate::components::{Expandable};
-use crate::base::{ValueProperties, IndexKey, Value, ReleaseNode};
+use crate::components::{Expandable, Report};
+use crate::base::{ValueProperties, IndexKey, Value, ReleaseNode, NodeProperties};
 use crate::utilities::{get_literal};
+use crate::utilities::json::{Accuracy, JSONRelease};
 use crate::utilities::inference::infer_property;
 use indexmap::set::IndexSet;
 use indexmap::map::IndexMap;
@@ -120,3 +121,122 @@ impl Expandable for proto::Map {
         Ok(expansion)
     }
 }
+
+/// Summarize a component that was mapped over partitions.
+///
+/// Each group is summarized on its own properties and release.
+/// The summaries of the groups are merged into one summary per statistic, where the release info is keyed by partition.
+/// The reported accuracy is the loosest accuracy among the groups.
+pub fn summarize_partitions(
+    component: &proto::Component,
+    privacy_definition: &Option<proto::PrivacyDefinition>,
+    node_id: u32,
+    public_arguments: &IndexMap<IndexKey, &Value>,
+    properties: &NodeProperties,
+    partition_releases: &IndexMap<IndexKey, Value>,
+    variable_names: Option<&Vec<IndexKey>>,
+) -> Result<Option<Vec<JSONRelease>>> {
+    let mut summaries: Option<Vec<JSONRelease>> = None;
+    let mut release_infos = Vec::<serde_json::Map<String, serde_json::Value>>::new();
+
+    for (partition_idx, partition_release) in partition_releases {
+        let partition_properties = properties.iter()
+            .map(|(name, property)| Ok((name.clone(), match property.partitions() {
+                Ok(partitions) => partitions.children.get(partition_idx)
+                    .ok_or_else(|| Error::from(format!("{:?}: missing partition {:?}", name, partition_idx)))?.clone(),
+                Err(_) => property.clone()
+            })))
+            .collect::<Result<NodeProperties>>()?;
+
+        let partition_arguments = public_arguments.iter()
+            .map(|(name, value)| Ok((name.clone(), match value {
+                Value::Partitions(partitions) => partitions.get(partition_idx)
+                    .ok_or_else(|| Error::from(format!("{:?}: missing partition {:?}", name, partition_idx)))?,
+                _ => *value
+            })))
+            .collect::<Result<IndexMap<IndexKey, &Value>>>()?;
+
+        let partition_summaries = match component.summarize(
+            privacy_definition, node_id, component,
+            partition_arguments, partition_properties, partition_release, variable_names)? {
+            Some(partition_summaries) => partition_summaries,
+            None => return Ok(None)
+        };
+
+        match summaries.as_mut() {
+            Some(summaries) => {
+                if summaries.len() != partition_summaries.len() {
+                    return Err("every partition must have the same number of summaries".into())
+                }
+                summaries.iter_mut().zip(partition_summaries.into_iter())
+                    .zip(release_infos.iter_mut())
+                    .for_each(|((summary, partition_summary), release_info)| {
+                        release_info.insert(partition_idx.to_string(), partition_summary.release_info);
+                        summary.accuracy = match (summary.accuracy.take(), partition_summary.accuracy) {
+                            (Some(l), Some(r)) => Some(Accuracy {
+                                accuracy_value: l.accuracy_value.max(r.accuracy_value),
+                                alpha: l.alpha,
+                                data_dependent: l.data_dependent || r.data_dependent,
+                                simultaneous: l.simultaneous && r.simultaneous,
+                            }),
+                            _ => None
+                        };
+                    });
+            }
+            None => {
+                release_infos = partition_summaries.iter()
+                    .map(|summary| vec![(partition_idx.to_string(), summary.release_info.clone())]
+                        .into_iter().collect())
+                    .collect();
+                summaries = Some(partition_summaries);
+            }
+        }
+    }
+
+    Ok(summaries.map(|summaries| summaries.into_iter().zip(release_infos.into_iter())
+        .map(|(mut summary, release_info)| {
+            summary.release_info = serde_json::Value::Object(release_info);
+            summary
+        })
+        .collect()))
+}
+
+#[cfg(test)]
+mod test_map {
+    use ndarray::arr1;
+
+    use crate::base::{IndexKey, ReleaseNode, test_data, Value};
+    use crate::components::resize::test_resize;
+
+    #[test]
+    fn test_partitioned_count_report() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+
+        // ten records split into groups of four, three and three
+        let num_partitions = analysis.literal().value(3.into()).value_public(true).build();
+        let partitioned = analysis.partition(resized).num_partitions(num_partitions).build();
+
+        let lower = analysis.literal().value(0.into()).value_public(true).build();
+        let usage = test_data::privacy_usage(1., 0.);
+        let count = analysis.dp_count(partitioned, lower, vec![usage]).build();
+
+        let group_counts = (0..3i64)
+            .zip(vec![4i64, 3, 3])
+            .map(|(idx, count)| (IndexKey::from(idx), arr1(&[count]).into_dyn().into()))
+            .collect();
+        analysis.release.insert(count, ReleaseNode::new(Value::Partitions(group_counts)));
+
+        let report: serde_json::Value = serde_json::from_str(&crate::generate_report(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap()).unwrap();
+
+        let entries = report.as_array().unwrap().iter()
+            .filter(|entry| entry["nodeID"] == count)
+            .collect::<Vec<_>>();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["statistic"], "DPCount");
+        assert_eq!(entries[0]["releaseInfo"], serde_json::json!({"0": [4], "1": [3], "2": [3]}));
+    }
+}
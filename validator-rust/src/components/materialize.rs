@@ -15,15 +15,22 @@ impl Component for proto::Materialize {
         node_id: u32
     ) -> Result<Warnable<ValueProperties>> {
 
+        let public_columns = get_public_columns(&public_arguments)?;
         let column_names = self.get_names(public_arguments, IndexMap::new(), None)?;
 
+        if let Some(name) = public_columns.iter().find(|name| !column_names.contains(name)) {
+            return Err(format!("public_columns: {:?} is not a column name", name).into())
+        }
+
         Ok(ValueProperties::Dataframe(DataframeProperties {
             children: column_names.into_iter()
-                .map(|name| (name, ValueProperties::Array(ArrayProperties {
+                .map(|name| (name.clone(), ValueProperties::Array(ArrayProperties {
                     num_records: None,
                     num_columns: Some(1),
                     nullity: true,
-                    releasable: self.public,
+                    // columns tagged as public are public knowledge in full, including their number of rows,
+                    //    so neighboring datasets differ only in the private columns, and public columns are released exactly
+                    releasable: self.public || public_columns.contains(&name),
                     c_stability: 1,
                     aggregator: None,
                     nature: None,
@@ -35,12 +42,34 @@ impl Component for proto::Materialize {
                     dimensionality: Some(1),
                     group_id: vec![],
                     naturally_ordered: true,
-                    sample_proportion: None
+                    sample_proportion: None,
+                    monotone: false,
+                    data_derived: false,
+                    num_records_lower_bound: None,
+                    noised: false
                 }))).collect(),
         }).into())
     }
 }
 
+/// Retrieve the names of the columns tagged as public.
+fn get_public_columns(public_arguments: &IndexMap<base::IndexKey, &Value>) -> Result<Vec<IndexKey>> {
+    let public_columns = match public_arguments.get::<base::IndexKey>(&"public_columns".into()) {
+        Some(public_columns) => public_columns.ref_array()?,
+        None => return Ok(Vec::new())
+    };
+
+    if public_columns.shape().len() > 1 {
+        return Err("public_columns: dimensionality may not be greater than one".into())
+    }
+    Ok(match public_columns {
+        base::Array::Int(keys) => keys.iter().copied().map(IndexKey::from).collect(),
+        base::Array::Bool(keys) => keys.iter().copied().map(IndexKey::from).collect(),
+        base::Array::Str(keys) => keys.iter().map(|v| v.as_str().into()).collect(),
+        _ => return Err("public_columns: unhashable type".into())
+    })
+}
+
 impl Named for proto::Materialize {
     fn get_names(
         &self,
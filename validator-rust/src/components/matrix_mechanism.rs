@@ -0,0 +1,275 @@
+use indexmap::map::IndexMap;
+use ndarray::{Array2, Axis, Ix2, Slice};
+
+use crate::{base, proto, Warnable, Float};
+use crate::base::{DataType, IndexKey, NodeProperties, SensitivitySpace, Value, ValueProperties};
+use crate::components::{Component, Expandable, Mechanism, Report};
+use crate::errors::*;
+use crate::utilities::{compute_mechanism_sensitivity, expand_mechanism, get_array_property, prepend, require_public_argument, summarize_mechanism};
+use crate::utilities::json::JSONRelease;
+use crate::utilities::privacy::{compose_privacy_usages, get_epsilon, get_aggregated_num_records, privacy_usage_check};
+
+impl Component for proto::MatrixMechanism {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        _node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy_definition must be defined")?;
+
+        if privacy_definition.protect_floating_point {
+            return Err("Floating-point protections are enabled. The matrix mechanism is susceptible to floating-point attacks.".into())
+        }
+
+        let mut data_property = get_array_property(&properties, "data")?;
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
+
+        if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
+            return Err("data: atomic type must be numeric".into());
+        }
+        if data_property.num_columns()? != 1 {
+            return Err("data: must be a single column".into())
+        }
+        let num_records = data_property.num_records()?;
+
+        let workload = get_matrix(&public_arguments, "workload", num_records)?;
+        let strategy = get_matrix(&public_arguments, "strategy", num_records)?;
+
+        // the data must be recoverable from the strategy answers
+        pseudo_inverse(&strategy).map_err(prepend("strategy:"))?;
+
+        let aggregator = data_property.aggregator.clone()
+            .ok_or_else(|| Error::from("aggregator: missing"))?;
+
+        // sensitivity must be computable
+        compute_mechanism_sensitivity(
+            self, privacy_definition, &aggregator)?.array()?.cast_float()?;
+
+        let privacy_usage = compose_privacy_usages(&self.privacy_usage)?;
+
+        let warnings = privacy_usage_check(
+            &privacy_usage,
+            get_aggregated_num_records(&aggregator),
+            privacy_definition.strict_parameter_checks,
+            privacy_definition.allow_unknown_num_records)?;
+
+        // one answer for each query in the workload
+        data_property.num_records = Some(workload.nrows() as i64);
+        data_property.data_type = DataType::Float;
+        data_property.nature = None;
+        data_property.mark_released();
+
+        Ok(Warnable(data_property.into(), warnings))
+    }
+}
+
+impl Expandable for proto::MatrixMechanism {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        expand_mechanism(
+            privacy_definition,
+            self.privacy_usage.as_ref(),
+            component,
+            properties,
+            component_id,
+            maximum_id
+        )
+    }
+}
+
+impl Report for proto::MatrixMechanism {
+    fn summarize(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let num_records = get_array_property(&properties, "data")?.num_records()?;
+        let strategy_norm = strategy_sensitivity(
+            &get_matrix(&public_arguments, "strategy", num_records)?);
+
+        summarize_mechanism(
+            "Matrix",
+            privacy_definition,
+            &self.privacy_usage,
+            node_id,
+            component,
+            &public_arguments,
+            &properties,
+            release,
+            variable_names,
+            // noise is added to the answers of the strategy queries
+            |sensitivity, usage| Ok(Some(sensitivity * strategy_norm / get_epsilon(usage)?)))
+    }
+}
+
+impl Mechanism for proto::MatrixMechanism {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = get_array_property(properties, "data")?;
+
+        Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition.group_size))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    }
+
+    fn sensitivity_space(&self) -> Result<SensitivitySpace> {
+        Ok(SensitivitySpace::KNorm(1))
+    }
+}
+
+/// Retrieve a public matrix of linear queries over `num_elements` elements.
+fn get_matrix(
+    public_arguments: &IndexMap<IndexKey, &Value>, name: &str, num_elements: i64,
+) -> Result<Array2<Float>> {
+    let matrix = require_public_argument(public_arguments, name)?
+        .ref_array()?.clone().cast_float()
+        .and_then(|matrix| Ok(matrix.into_dimensionality::<Ix2>()?))
+        .map_err(prepend(&format!("{}: must be a numeric matrix:", name)))?;
+
+    if matrix.ncols() as i64 != num_elements {
+        bail!("{}: must have one column for each of the {} elements of data", name, num_elements)
+    }
+    if matrix.nrows() == 0 {
+        bail!("{}: must contain at least one query", name)
+    }
+    Ok(matrix)
+}
+
+/// Factor by which the strategy scales the L1 sensitivity of the data.
+///
+/// A change of the data in one element changes the strategy answers by the corresponding column of the strategy,
+/// so the L1 sensitivity of the strategy answers is the sensitivity of the data times the largest L1 norm of a column.
+pub fn strategy_sensitivity(strategy: &Array2<Float>) -> Float {
+    strategy.gencolumns().into_iter()
+        .map(|column| column.iter().map(|v| v.abs()).sum::<Float>())
+        .fold(0., Float::max)
+}
+
+/// Moore-Penrose pseudo-inverse `(A^T A)^-1 A^T` of a matrix `A` with full column rank.
+///
+/// Multiplying noisy strategy answers by the pseudo-inverse gives the least squares estimate of the data.
+pub fn pseudo_inverse(matrix: &Array2<Float>) -> Result<Array2<Float>> {
+    let gram = matrix.t().dot(matrix);
+    let size = gram.nrows();
+
+    // gauss-jordan elimination with partial pivoting, on [gram | identity]
+    let mut reduced = Array2::<Float>::zeros((size, 2 * size));
+    reduced.slice_axis_mut(Axis(1), Slice::from(..size)).assign(&gram);
+    (0..size).for_each(|i| reduced[[i, size + i]] = 1.);
+
+    for column in 0..size {
+        let pivot = (column..size)
+            .max_by(|l, r| reduced[[*l, column]].abs().partial_cmp(&reduced[[*r, column]].abs())
+                .unwrap_or(std::cmp::Ordering::Equal))
+            .unwrap();
+        if reduced[[pivot, column]].abs() < 1e-10 {
+            bail!("matrix must have full column rank")
+        }
+        if pivot != column {
+            for j in 0..2 * size {
+                reduced.swap([pivot, j], [column, j]);
+            }
+        }
+        let divisor = reduced[[column, column]];
+        reduced.row_mut(column).mapv_inplace(|v| v / divisor);
+
+        let pivot_row = reduced.row(column).to_owned();
+        for row in (0..size).filter(|row| *row != column) {
+            let factor = reduced[[row, column]];
+            reduced.row_mut(row).zip_mut_with(&pivot_row, |v, p| *v -= factor * p);
+        }
+    }
+
+    Ok(reduced.slice_axis(Axis(1), Slice::from(size..)).dot(&matrix.t()))
+}
+
+/// Expected total squared error of the workload answers from the matrix mechanism.
+///
+/// Each strategy answer is perturbed with laplace noise of variance `2b^2`, where `b` is the noise scale.
+/// The noise on the workload answers is the strategy noise multiplied by `W A^+`,
+/// so the total variance is `2b^2` times the squared frobenius norm of `W A^+`.
+pub fn matrix_mechanism_total_error(
+    workload: &Array2<Float>, strategy: &Array2<Float>, sensitivity: Float, epsilon: Float,
+) -> Result<Float> {
+    let scale = sensitivity * strategy_sensitivity(strategy) / epsilon;
+    let reconstruction = workload.dot(&pseudo_inverse(strategy)?);
+    Ok(2. * scale.powi(2) * reconstruction.iter().map(|v| v.powi(2)).sum::<Float>())
+}
+
+#[cfg(test)]
+mod test_matrix_mechanism {
+    use ndarray::{arr2, Array2};
+
+    use crate::components::matrix_mechanism::{matrix_mechanism_total_error, pseudo_inverse, strategy_sensitivity};
+
+    /// each query sums a prefix of the elements
+    fn prefix_workload(size: usize) -> Array2<f64> {
+        Array2::from_shape_fn((size, size), |(i, j)| if j <= i { 1. } else { 0. })
+    }
+
+    /// each query sums a dyadic interval of the elements, from the full range down to single elements
+    fn hierarchical_strategy(size: usize) -> Array2<f64> {
+        let mut rows = Vec::new();
+        let mut width = size;
+        while width >= 1 {
+            (0..size / width).for_each(|block| rows.push((0..size)
+                .map(|j| if j / width == block { 1. } else { 0. })
+                .collect::<Vec<f64>>()));
+            width /= 2;
+        }
+        Array2::from_shape_vec((rows.len(), size), rows.concat()).unwrap()
+    }
+
+    #[test]
+    fn test_pseudo_inverse() {
+        let strategy = hierarchical_strategy(4);
+        let reconstruction = pseudo_inverse(&strategy).unwrap().dot(&strategy);
+        assert!(reconstruction.indexed_iter()
+            .all(|((i, j), v)| (v - if i == j { 1. } else { 0. }).abs() < 1e-8));
+
+        // a strategy without full column rank cannot reconstruct the data
+        assert!(pseudo_inverse(&arr2(&[[1., 1.], [2., 2.]])).is_err());
+    }
+
+    #[test]
+    fn test_prefix_workload_error() {
+        let size = 16;
+        let (sensitivity, epsilon) = (1., 1.);
+        let workload = prefix_workload(size);
+        let strategy = hierarchical_strategy(size);
+
+        // each element is counted by one query on each level of the hierarchy
+        assert!((strategy_sensitivity(&strategy) - 5.).abs() < 1e-8);
+
+        // answering each prefix query independently with laplace noise
+        let independent_error = matrix_mechanism_total_error(
+            &workload, &workload, sensitivity, epsilon).unwrap();
+        let matrix_error = matrix_mechanism_total_error(
+            &workload, &strategy, sensitivity, epsilon).unwrap();
+
+        assert!(matrix_error < independent_error);
+    }
+}
@@ -3,8 +3,8 @@ use crate::errors::*;
 use crate::{proto, base, Warnable, Float};
 
 use crate::components::{Component, Sensitivity};
-use crate::base::{Value, NodeProperties, AggregatorProperties, SensitivitySpace, ValueProperties, DataType, IndexKey};
-use crate::utilities::prepend;
+use crate::base::{Value, NodeProperties, AggregatorProperties, SensitivitySpace, ValueProperties, DataType};
+use crate::utilities::{get_array_property, prepend, privacy::scale_by_contributions};
 use ndarray::prelude::*;
 use indexmap::map::IndexMap;
 
@@ -16,13 +16,12 @@ impl Component for proto::Mean {
         properties: base::NodeProperties,
         node_id: u32
     ) -> Result<Warnable<ValueProperties>> {
-        let mut data_property = properties.get::<IndexKey>(&"data".into())
-            .ok_or("data: missing")?.array()
-            .map_err(prepend("data:"))?.clone();
+        let mut data_property = get_array_property(&properties, "data")?;
 
         if !data_property.releasable {
             data_property.assert_is_not_aggregated()?;
         }
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
         data_property.assert_is_not_empty()?;
 
         let num_columns = data_property.num_columns()?;
@@ -43,31 +42,36 @@ impl Component for proto::Mean {
 
 impl Sensitivity for proto::Mean {
     /// Mean sensitivities [are backed by the the proofs here](https://github.com/opendp/smartnoise-core/blob/master/whitepapers/sensitivities/mean/mean.pdf).
+    ///
+    /// The sensitivity decreases in n, so when n is unknown but bounded below (by a resize with minimum_rows),
+    /// the sensitivity at the lower bound holds for every dataset. This applies under both neighboring definitions:
+    /// the resize pads an add/remove neighbor to the same size, which substitutes a record, and substitution shares the bound.
+    /// An upper bound on n alone does not bound the sensitivity.
     fn compute_sensitivity(
         &self,
-        _privacy_definition: &proto::PrivacyDefinition,
+        privacy_definition: &proto::PrivacyDefinition,
         properties: &NodeProperties,
         sensitivity_type: &SensitivitySpace,
     ) -> Result<Value> {
         match sensitivity_type {
             SensitivitySpace::KNorm(k) => {
-                let data_property = properties.get::<IndexKey>(&"data".into())
-                    .ok_or("data: missing")?.array()
-                    .map_err(prepend("data:"))?.clone();
+                let data_property = get_array_property(properties, "data")?;
 
                 data_property.assert_non_null()?;
                 data_property.assert_is_not_aggregated()?;
+                data_property.assert_bounds_are_public()?;
                 let data_lower = data_property.lower_float()?;
                 let data_upper = data_property.upper_float()?;
-                let data_n = data_property.num_records()? as Float;
+                // the exact number of records if known, otherwise the fewest records the data may have
+                let data_n = data_property.num_records_lower_bound()? as Float;
 
                 // AddRemove vs. Substitute share the same bounds
 
                 let row_sensitivity = match k {
                     1 | 2 => data_lower.iter()
                         .zip(data_upper.iter())
-                        .map(|(min, max)| (max - min) / data_n)
-                        .collect::<Vec<Float>>(),
+                        .map(|(min, max)| scale_by_contributions(privacy_definition, (max - min) / data_n))
+                        .collect::<Result<Vec<Float>>>()?,
                     _ => return Err("KNorm sensitivity is only supported in L1 and L2 spaces".into())
                 };
 
@@ -80,3 +84,41 @@ impl Sensitivity for proto::Mean {
         }
     }
 }
+
+#[cfg(test)]
+mod test_mean {
+    use crate::base::{IndexKey, SensitivitySpace, test_data};
+    use crate::components::impute::test_impute;
+    use crate::components::Sensitivity;
+    use crate::proto;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_num_records_lower_bound_sensitivity() {
+        let (mut analysis, imputed) = test_impute::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), Some(0.0.into()), Some(10.0.into()));
+
+        // padding to a minimum number of rows bounds the number of records from below
+        let minimum_rows = analysis.literal().value(20.into()).value_public(true).build();
+        let resized = analysis.resize(imputed).minimum_rows(minimum_rows).build();
+        assert_eq!(analysis.properties(resized).unwrap().array().unwrap()
+                       .num_records_lower_bound().unwrap(), 20);
+
+        let mut data_property = analysis.properties(imputed).unwrap().array().unwrap().clone();
+        data_property.num_records = None;
+
+        let sensitivity = |data_property: &crate::base::ArrayProperties| proto::Mean {}
+            .compute_sensitivity(
+                &analysis.privacy_definition,
+                &indexmap![IndexKey::from("data") => data_property.clone().into()],
+                &SensitivitySpace::KNorm(1))
+            .map(|sensitivity| sensitivity.array().unwrap().first_float().unwrap());
+
+        // the sensitivity is unbounded when nothing is known about the number of records
+        assert!(sensitivity(&data_property).is_err());
+
+        // at least five records bounds the sensitivity by (10 - 0) / 5
+        data_property.num_records_lower_bound = Some(5);
+        assert_eq!(sensitivity(&data_property).unwrap(), 2.);
+    }
+}
@@ -18,27 +18,50 @@ mod count;
 mod covariance;
 mod column_bind;
 mod digitize;
+mod ecdf;
+mod dp_between_group_variance;
+mod dp_binned_histogram;
+mod dp_conditional_sum;
+mod dp_sum_of_squares;
 mod dp_count;
 mod dp_variance;
+mod dp_correlation;
 mod dp_covariance;
+mod dp_cv;
+mod dp_ecdf;
+mod dp_expression_histogram;
 mod dp_gumbel_median;
+mod dp_geometric_mean;
+mod dp_winsorized_mean;
+mod dp_trimmed_mean;
 mod dp_histogram;
+mod dp_iqr;
 mod dp_linear_regression;
 mod dp_maximum;
 mod dp_median;
 mod dp_minimum;
-mod dp_mean;
+pub mod dp_mean;
 mod dp_quantile;
+mod dp_rank;
 mod dp_raw_moment;
+mod dp_stratified_count;
+mod dp_skewness;
 mod dp_sum;
+mod dp_user_sum;
+mod dp_weighted_sum;
 mod filter;
+pub mod hadamard_response;
 mod histogram;
 mod impute;
+mod isotonic;
+mod label_counts;
 pub mod index;
 mod raw_moment;
-mod literal;
+pub mod literal;
 mod map;
 mod materialize;
+mod moving_average;
+pub mod matrix_mechanism;
 pub mod partition;
 mod quantile;
 mod reshape;
@@ -46,13 +69,15 @@ mod mean;
 mod exponential_mechanism;
 pub mod gaussian_mechanism;
 mod laplace_mechanism;
-mod simple_geometric_mechanism;
+pub mod simple_geometric_mechanism;
 pub mod snapping_mechanism;
-mod resize;
+pub mod stability_mechanism;
+pub mod resize;
 mod theil_sen;
 mod to_dataframe;
 mod sum;
 mod union;
+mod user_sum;
 mod variance;
 
 use crate::base::{IndexKey, Value, NodeProperties, SensitivitySpace, ValueProperties};
@@ -147,6 +172,11 @@ pub trait Mechanism {
         release_usage: Option<&Vec<proto::PrivacyUsage>>,
         properties: &NodeProperties
     ) -> Result<Option<Vec<proto::PrivacyUsage>>>;
+
+    /// Space in which the sensitivity of the aggregator must be computed for the mechanism to be valid.
+    ///
+    /// For example, the laplace mechanism requires L1 sensitivity, and the gaussian mechanism requires L2 sensitivity.
+    fn sensitivity_space(&self) -> Result<SensitivitySpace>;
 }
 
 /// Sensitivity component trait
@@ -200,6 +230,7 @@ pub trait Report {
     /// Summarize the relevant metadata around a computation in a readable, JSON-serializable format.
     fn summarize(
         &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
         node_id: u32,
         component: &proto::Component,
         public_arguments: IndexMap<base::IndexKey, &Value>,
@@ -257,18 +288,18 @@ impl Component for proto::Component {
 
         propagate_property!(
             // INSERT COMPONENT LIST
-            Cast, Clamp, ColumnBind, Count, Covariance, Digitize,
-            Filter, Histogram, Impute, Index, Literal, Materialize, Mean,
-            Partition, Quantile, RawMoment, Reshape, Resize, Sum, ToDataframe, Union, Variance,
+            Cast, Clamp, ClampCount, ColumnBind, Count, Covariance, Digitize, Ecdf,
+            Filter, Histogram, Impute, Index, Isotonic, LabelCounts, Literal, Materialize, Mean, MovingAverage,
+            Partition, Quantile, RawMoment, Reshape, Resize, Sum, ToDataframe, Union, UserSum, Variance,
 
-            ExponentialMechanism, GaussianMechanism, LaplaceMechanism,
-            SimpleGeometricMechanism, SnappingMechanism,
+            ExponentialMechanism, GaussianMechanism, LaplaceMechanism, MatrixMechanism,
+            SimpleGeometricMechanism, SnappingMechanism, StabilityMechanism, HadamardResponse,
 
             Abs, Add, LogicalAnd, Divide, Equal, GreaterThan, LessThan, Log, Modulo, Multiply,
-            Negate, Negative, LogicalOr, Power, RowMax, RowMin, Subtract, TheilSen, DpGumbelMedian
+            Negate, Negative, LogicalOr, Power, RowMax, RowMin, Subtract, TheilSen, DpCoefficientOfVariation, DpCorrelation, DpGeometricMean, DpGumbelMedian, DpIqr, DpWinsorizedMean, DpBinnedHistogram, DpConditionalSum, DpRank, DpEcdf, DpStratifiedCount, DpSkewness, DpSumOfSquares, DpTrimmedMean, DpUserSum, DpWeightedSum, DpExpressionHistogram, DpBetweenGroupVariance
         );
 
-        Err(format!("proto component {:?} is missing its Component trait", variant).into())
+        Err(unsupported_operation("propagate_property", variant))
     }
 }
 
@@ -333,13 +364,13 @@ impl Expandable for proto::Component {
 
         expand_component!(
             // INSERT COMPONENT LIST
-            Clamp, Digitize, Histogram, Impute, Map, Maximum, Median, Minimum, Partition, Resize,
+            Cast, Clamp, ClampCount, Count, Digitize, Histogram, Impute, Map, Maximum, Median, Minimum, Partition, Resize,
 
-            DpCount, DpCovariance, DpHistogram, DpLinearRegression, DpMaximum, DpMean, DpMedian,
-            DpMinimum, DpQuantile, DpRawMoment, DpSum, DpVariance,
+            DpBetweenGroupVariance, DpBinnedHistogram, DpCoefficientOfVariation, DpConditionalSum, DpCorrelation, DpCount, DpCovariance, DpEcdf, DpExpressionHistogram, DpGeometricMean, DpHistogram, DpIqr, DpLinearRegression, DpMaximum, DpMean, DpMedian,
+            DpMinimum, DpQuantile, DpRank, DpRawMoment, DpSkewness, DpStratifiedCount, DpSum, DpSumOfSquares, DpTrimmedMean, DpUserSum, DpVariance, DpWeightedSum, DpWinsorizedMean,
 
-            ExponentialMechanism, GaussianMechanism, LaplaceMechanism,
-            SimpleGeometricMechanism, SnappingMechanism, DpGumbelMedian,
+            ExponentialMechanism, GaussianMechanism, LaplaceMechanism, MatrixMechanism,
+            SimpleGeometricMechanism, SnappingMechanism, StabilityMechanism, HadamardResponse, DpGumbelMedian,
 
             ToBool, ToFloat, ToInt, ToString
         );
@@ -375,12 +406,42 @@ impl Mechanism for proto::Component {
 
         get_privacy_usage!(
             // INSERT COMPONENT LIST
-            ExponentialMechanism, GaussianMechanism, LaplaceMechanism,
-            SimpleGeometricMechanism, SnappingMechanism
+            ExponentialMechanism, GaussianMechanism, LaplaceMechanism, MatrixMechanism,
+            SimpleGeometricMechanism, SnappingMechanism, StabilityMechanism, HadamardResponse
         );
 
+        // privacy usage is only accounted on mechanisms, so an unexpanded dp component would go uncounted
+        if is_dp_component(variant) {
+            return Err(unsupported_operation("get_privacy_usage", variant))
+        }
+
         Ok(None)
     }
+
+    fn sensitivity_space(&self) -> Result<SensitivitySpace> {
+        let variant = self.variant.as_ref()
+            .ok_or_else(|| "variant: must be defined")?;
+
+        macro_rules! sensitivity_space {
+            ($( $variant:ident ),*) => {
+                {
+                    $(
+                       if let proto::component::Variant::$variant(x) = variant {
+                            return x.sensitivity_space()
+                       }
+                    )*
+                }
+            }
+        }
+
+        sensitivity_space!(
+            // INSERT COMPONENT LIST
+            ExponentialMechanism, GaussianMechanism, LaplaceMechanism, MatrixMechanism,
+            SimpleGeometricMechanism, SnappingMechanism, StabilityMechanism, HadamardResponse
+        );
+
+        Err(unsupported_operation("sensitivity_space", variant))
+    }
 }
 
 
@@ -400,6 +461,7 @@ impl Sensitivity for proto::component::Variant {
                     $(
                        if let proto::component::Variant::$variant(x) = self {
                             return x.compute_sensitivity(privacy_definition, properties, sensitivity_type)
+                                .and_then(|sensitivity| check_sensitivity(sensitivity, properties))
                                 .chain_err(|| format!("node specification {:?}:", self))
                        }
                     )*
@@ -409,10 +471,10 @@ impl Sensitivity for proto::component::Variant {
 
         compute_sensitivity!(
             // INSERT COMPONENT LIST
-            Count, Covariance, Histogram, Mean, Quantile, RawMoment, Sum, Union, Variance
+            Count, Covariance, Ecdf, Histogram, Mean, Quantile, RawMoment, Sum, Union, UserSum, Variance
         );
 
-        Err(format!("sensitivity is not implemented for proto component {:?}", self).into())
+        Err(unsupported_operation("compute_sensitivity", self))
     }
 }
 
@@ -442,12 +504,24 @@ impl Accuracy for proto::Component {
         }
 
         accuracy_to_privacy_usage!(
+             DpBinnedHistogram,
+             DpConditionalSum,
+             DpExpressionHistogram,
+             DpRank,
+             DpSumOfSquares,
+             DpUserSum,
+             DpWeightedSum,
              LaplaceMechanism,
              GaussianMechanism,
              SimpleGeometricMechanism,
              SnappingMechanism
         );
 
+        // a mechanism without an accuracy cannot be budgeted from one, and must not be skipped
+        if self.sensitivity_space().is_ok() || is_dp_component(variant) {
+            return Err(unsupported_operation("accuracy_to_privacy_usage", variant))
+        }
+
         Ok(None)
     }
 
@@ -476,6 +550,13 @@ impl Accuracy for proto::Component {
         }
 
         privacy_usage_to_accuracy!(
+            DpBinnedHistogram,
+            DpConditionalSum,
+            DpExpressionHistogram,
+            DpRank,
+            DpSumOfSquares,
+            DpUserSum,
+            DpWeightedSum,
             LaplaceMechanism,
             GaussianMechanism,
             SimpleGeometricMechanism,
@@ -486,12 +567,53 @@ impl Accuracy for proto::Component {
     }
 }
 
+/// Error for a component dispatched to a trait method it does not implement.
+fn unsupported_operation(operation: &str, variant: &proto::component::Variant) -> Error {
+    let variant = format!("{:?}", variant);
+    ErrorKind::UnsupportedOperation(
+        operation.to_string(),
+        variant.split('(').next().unwrap_or(&variant).to_string()).into()
+}
+
+/// Reject a derived sensitivity that is not finite and non-negative.
+///
+/// Noise calibrated to a NaN, infinite or negative sensitivity does not privatize the release,
+/// so a faulty derivation must fail before it reaches a mechanism.
+fn check_sensitivity(sensitivity: Value, properties: &NodeProperties) -> Result<Value> {
+    let is_valid = match sensitivity.ref_array()? {
+        base::Array::Float(sensitivity) => sensitivity.iter().all(|v| v.is_finite() && *v >= 0.),
+        base::Array::Int(sensitivity) => sensitivity.iter().all(|v| *v >= 0),
+        _ => return Err("sensitivity must be numeric".into())
+    };
+    if !is_valid {
+        let node_ids = properties.values()
+            .filter_map(|property| property.array().ok())
+            .map(|property| property.node_id)
+            .collect::<Vec<i64>>();
+        return Err(format!(
+            "sensitivity of the aggregation over nodes {:?} must be finite and non-negative, but is {:?}",
+            node_ids, sensitivity).into())
+    }
+    Ok(sensitivity)
+}
+
+/// True if the component is a differentially private algorithm that expands into mechanisms.
+pub(crate) fn is_dp_component(variant: &proto::component::Variant) -> bool {
+    use proto::component::Variant::*;
+    matches!(variant,
+        DpBetweenGroupVariance(_) | DpBinnedHistogram(_) | DpCoefficientOfVariation(_) | DpConditionalSum(_) | DpCorrelation(_) | DpCount(_) | DpCovariance(_) | DpEcdf(_) | DpExpressionHistogram(_) | DpGeometricMean(_) |
+        DpGumbelMedian(_) | DpHistogram(_) | DpIqr(_) | DpLinearRegression(_) | DpMaximum(_) | DpMean(_) | DpMedian(_) |
+        DpMinimum(_) | DpQuantile(_) | DpRank(_) | DpRawMoment(_) | DpSkewness(_) | DpStratifiedCount(_) | DpSum(_) | DpSumOfSquares(_) | DpTrimmedMean(_) |
+        DpUserSum(_) | DpVariance(_) | DpWeightedSum(_) | DpWinsorizedMean(_))
+}
+
 impl Report for proto::Component {
     /// Utility implementation on the component.
     ///
     /// This utility delegates evaluation to the concrete implementation of each component variant.
     fn summarize(
         &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
         node_id: u32,
         component: &proto::Component,
         public_arguments: IndexMap<base::IndexKey, &Value>,
@@ -502,12 +624,21 @@ impl Report for proto::Component {
         let variant = self.variant.as_ref()
             .ok_or_else(|| "variant: must be defined")?;
 
+        // components applied over partitions release one value per group
+        if let Value::Partitions(partition_releases) = release {
+            if properties.values().any(|props| props.partitions().is_ok()) {
+                return map::summarize_partitions(
+                    self, privacy_definition, node_id, &public_arguments,
+                    &properties, partition_releases, variable_names)
+            }
+        }
+
         macro_rules! summarize {
             ($( $variant:ident ),*) => {
                 {
                     $(
                        if let proto::component::Variant::$variant(x) = variant {
-                            return x.summarize(node_id, component, public_arguments,
+                            return x.summarize(privacy_definition, node_id, component, public_arguments,
                                  properties, release, variable_names)
                                 .chain_err(|| format!("node specification: {:?}:", variant))
                        }
@@ -518,8 +649,11 @@ impl Report for proto::Component {
 
         summarize!(
             // INSERT COMPONENT LIST
-            DpCount, DpCovariance, DpHistogram, DpMaximum, DpMean, DpMinimum, DpQuantile,
-            DpRawMoment, DpSum, DpVariance
+            DpBetweenGroupVariance, DpBinnedHistogram, DpCoefficientOfVariation, DpConditionalSum, DpCorrelation, DpCount, DpCovariance, DpEcdf, DpExpressionHistogram, DpGeometricMean, DpHistogram, DpIqr, DpMaximum, DpMean, DpMinimum, DpQuantile,
+            DpRank, DpRawMoment, DpSkewness, DpStratifiedCount, DpSum, DpSumOfSquares, DpTrimmedMean, DpUserSum, DpVariance, DpWeightedSum, DpWinsorizedMean,
+
+            ExponentialMechanism, GaussianMechanism, LaplaceMechanism, MatrixMechanism,
+            SimpleGeometricMechanism, SnappingMechanism, StabilityMechanism, HadamardResponse
         );
 
         Ok(None)
@@ -552,10 +686,10 @@ impl Named for proto::Component {
             }
         }
 
-        // TODO: transforms, covariance/cross-covariance, extended indexing, columnbind
+        // TODO: transforms, extended indexing, columnbind
         get_names!(
             // INSERT COMPONENT LIST
-            ToDataframe, Index, Literal, Materialize
+            DpCovariance, ToDataframe, Index, Literal, Materialize
         );
 
         // default implementation
@@ -0,0 +1,82 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, proto, Warnable};
+use crate::base::{DataType, Nature, NatureContinuous, NodeProperties, Value, ValueProperties, Vector1DNull};
+use crate::components::Component;
+use crate::errors::*;
+use crate::utilities::get_array_property;
+
+impl Component for proto::MovingAverage {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        _node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let mut data_property = get_array_property(&properties, "data")?;
+
+        // the average couples records, so it may only be applied as post-processing
+        data_property.assert_is_releasable()?;
+        data_property.assert_non_null()?;
+
+        if self.window.is_multiple_of(2) {
+            return Err("window: must be odd".into())
+        }
+        if let Some(num_records) = data_property.num_records {
+            if self.num_excluded as i64 > num_records {
+                return Err("num_excluded: may not exceed the number of records".into())
+            }
+        }
+
+        data_property.nature = match data_property.data_type {
+            // averages of values in a column stay within the bounds of the column
+            DataType::Float | DataType::Int => match (data_property.lower_float_option(), data_property.upper_float_option()) {
+                (Ok(lower), Ok(upper)) => Some(Nature::Continuous(NatureContinuous {
+                    lower: Vector1DNull::Float(lower),
+                    upper: Vector1DNull::Float(upper),
+                })),
+                _ => None
+            },
+            _ => return Err("data: atomic type must be numeric".into())
+        };
+        data_property.data_type = DataType::Float;
+        data_property.monotone = false;
+
+        Ok(ValueProperties::Array(data_property).into())
+    }
+}
+
+#[cfg(test)]
+mod test_moving_average {
+    use ndarray::{arr1, arr2};
+
+    use crate::bindings::Analysis;
+
+    #[test]
+    fn test_moving_average_properties() {
+        let mut analysis = Analysis::new();
+        let data = analysis.literal()
+            .value(arr2(&[[1], [3], [2], [6]]).into_dyn().into())
+            .value_public(true).build();
+        let averaged = analysis.moving_average(data).build();
+
+        // the shape and bounds of the data are preserved
+        let averaged_property = analysis.properties(averaged).unwrap().array().unwrap().clone();
+        assert_eq!(averaged_property.num_records, Some(4));
+        assert_eq!(averaged_property.data_type, crate::base::DataType::Float);
+        assert_eq!(averaged_property.lower_float().unwrap(), vec![1.]);
+        assert_eq!(averaged_property.upper_float().unwrap(), vec![6.]);
+
+        // the window must be centered
+        let even = analysis.moving_average(data).window(2).build();
+        assert!(analysis.properties(even).is_err());
+
+        // private data may not be averaged
+        let private = analysis.literal()
+            .value(arr1(&[0.1, 0.3, 0.2, 0.6]).into_dyn().into())
+            .value_public(false).build();
+        let averaged = analysis.moving_average(private).build();
+        assert!(analysis.properties(averaged).is_err());
+    }
+}
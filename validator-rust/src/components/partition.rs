@@ -34,13 +34,13 @@ impl Component for proto::Partition {
                     .map_err(prepend("by:"))?.clone();
                 by_property.num_columns
                     .ok_or_else(|| Error::from("number of columns must be known on by"))?;
-                let categories = by_property.categories()
-                    .map_err(prepend("by:"))?;
+                let categories = get_by_categories(&public_arguments, &properties, &by_property)?;
 
                 let partition_keys = make_dense_partition_keys(categories, by_property.dimensionality)?;
 
                 PartitionsProperties {
-                    children: broadcast_partitions(partition_keys, &data_property, node_id, neighboring)?,
+                    children: broadcast_partitions(
+                        partition_keys, &data_property, node_id, neighboring, by_property.releasable)?,
                 }
             }
 
@@ -71,7 +71,8 @@ impl Component for proto::Partition {
                                 IndexKey::from(index as Integer),
                                 *partition_num_records,
                                 node_id,
-                                neighboring)?
+                                neighboring,
+                                false)?
                         )))
                         .collect::<Result<IndexMap<IndexKey, ValueProperties>>>()?,
                 }
@@ -85,7 +86,7 @@ impl Expandable for proto::Partition {
         &self,
         _privacy_definition: &Option<proto::PrivacyDefinition>,
         component: &proto::Component,
-        _public_arguments: &IndexMap<IndexKey, &Value>,
+        public_arguments: &IndexMap<IndexKey, &Value>,
         properties: &NodeProperties,
         component_id: u32,
         mut maximum_id: u32
@@ -94,8 +95,10 @@ impl Expandable for proto::Partition {
         let mut expansion = base::ComponentExpansion::default();
 
         if let Some(by) = properties.get::<IndexKey>(&"by".into()) {
+            // categories that were already materialized must match those used to build the partition keys
+            let categories = get_by_categories(public_arguments, properties, by.array()?)?;
+
             if !properties.contains_key::<IndexKey>(&"categories".into()) {
-                let categories = by.array()?.categories()?;
                 maximum_id += 1;
                 let id_categories = maximum_id;
                 let (patch_node, release) = get_literal(Value::Jagged(categories), component.submission)?;
@@ -113,9 +116,54 @@ impl Expandable for proto::Partition {
     }
 }
 
+/// Check that the categories argument, if present, matches the categories of `by`.
+///
+/// Partition keys are derived from the categories of `by` when propagating properties,
+/// but the runtime partitions on the categories argument materialized during expansion.
+/// If the two differ, the partitions would be misaligned with their properties.
+fn check_partition_categories(
+    public_arguments: &IndexMap<IndexKey, &Value>,
+    properties: &NodeProperties,
+    by_categories: &Jagged,
+) -> Result<()> {
+    if !properties.contains_key::<IndexKey>(&"categories".into()) {
+        return Ok(())
+    }
+    let categories = public_arguments.get::<IndexKey>(&"categories".into())
+        .ok_or_else(|| Error::from("categories: must be public"))?
+        .ref_jagged().map_err(prepend("categories:"))?;
+
+    if categories.to_index_keys()? != by_categories.to_index_keys()? {
+        return Err("categories: must match the categories of by".into())
+    }
+    Ok(())
+}
+
+/// Retrieve the categories of `by`, which become the partition keys.
+///
+/// Public `by` columns, like region codes, need not be clamped.
+/// If the categories of a public `by` are unknown, they are taken from the public categories argument.
+fn get_by_categories(
+    public_arguments: &IndexMap<IndexKey, &Value>,
+    properties: &NodeProperties,
+    by_property: &ArrayProperties,
+) -> Result<Jagged> {
+    match by_property.categories() {
+        Ok(categories) => {
+            check_partition_categories(public_arguments, properties, &categories)?;
+            Ok(categories)
+        }
+        Err(_) if by_property.releasable => public_arguments.get::<IndexKey>(&"categories".into())
+            .ok_or_else(|| Error::from("categories: must be supplied when the categories of a public by are unknown"))?
+            .ref_jagged().map_err(prepend("categories:")).cloned(),
+        Err(err) => Err(prepend("by:")(err))
+    }
+}
+
 pub fn broadcast_partitions(
     partition_keys: Vec<IndexKey>, properties: &ValueProperties, node_id: u32,
-    neighboring_definition: proto::privacy_definition::Neighboring
+    neighboring_definition: proto::privacy_definition::Neighboring,
+    public_keys: bool
 ) -> Result<IndexMap<IndexKey, ValueProperties>> {
     // create dense partitioning
     partition_keys.into_iter()
@@ -124,14 +172,16 @@ pub fn broadcast_partitions(
             v,
             None,
             node_id,
-            neighboring_definition)?)))
+            neighboring_definition,
+            public_keys)?)))
         .collect()
 }
 
 fn get_partition_properties(
     properties: &ValueProperties,
     index: IndexKey, num_records: Option<i64>, node_id: u32,
-    neighboring_definition: proto::privacy_definition::Neighboring
+    neighboring_definition: proto::privacy_definition::Neighboring,
+    public_keys: bool
 ) -> Result<ValueProperties> {
 
     let update_array_properties = |mut properties: ArrayProperties| -> ArrayProperties {
@@ -141,10 +191,13 @@ fn get_partition_properties(
             index: index.clone()
         });
         properties.num_records = num_records;
+        properties.num_records_lower_bound = None;
         properties.dataset_id = Some(node_id as i64);
         properties.is_not_empty = num_records.unwrap_or(0) != 0;
 
-        if neighboring_definition == proto::privacy_definition::Neighboring::Substitute {
+        // substituting a record may move it to another partition, unless the partition keys are public.
+        //    Neighboring datasets differ only in private columns, so a substituted record keeps its public key
+        if neighboring_definition == proto::privacy_definition::Neighboring::Substitute && !public_keys {
             properties.c_stability = properties.c_stability * 2
         }
 
@@ -192,7 +245,15 @@ pub fn even_split_lengths(num_records: i64, num_partitions: i64) -> Vec<i64> {
 
 #[cfg(test)]
 mod test_partition {
+    use ndarray::arr1;
+
+    use crate::base::{Jagged, ReleaseNode, Value};
+    use crate::base::test_data;
+    use crate::bindings::Analysis;
+    use crate::components::clamp::test_clamp;
     use crate::components::partition::even_split_lengths;
+    use crate::proto;
+    use crate::utilities::privacy::get_epsilon;
 
     fn vec_eq(left: &Vec<i64>, right: &Vec<i64>) -> bool {
         (left.len() == right.len()) && left.iter().zip(right)
@@ -217,4 +278,143 @@ mod test_partition {
             &even_split_lengths(2, 0),
             &vec![]));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_partition_categories_consistent() {
+        let (mut analysis, by) = test_clamp::utilities::analysis_bool_cat(
+            test_data::array1d_bool_10_uniform());
+        let partition = analysis.partition(by).by(by).build();
+
+        let by_properties = analysis.properties(by).unwrap();
+        let expand = |categories: Jagged| {
+            // the categories argument, as if materialized by a prior expansion
+            let mut component = analysis.components.get(&partition).unwrap().clone();
+            component.insert_argument(&"categories".into(), 1001);
+            crate::expand_component(
+                component,
+                indexmap![
+                    "data".into() => by_properties.clone(),
+                    "by".into() => by_properties.clone()
+                ],
+                indexmap!["categories".into() => ReleaseNode {
+                    value: Value::Jagged(categories),
+                    privacy_usages: None,
+                    public: true
+                }],
+                Some(analysis.privacy_definition.clone()),
+                partition, 1001)
+        };
+
+        // the categories materialized at expansion agree with the categories at propagation
+        assert!(expand(vec![vec![false, true]].into()).is_ok());
+
+        // perturbing the categories between phases is caught
+        assert!(expand(vec![vec![true, false]].into()).is_err());
+        assert!(expand(vec![vec![true]].into()).is_err());
+    }
+
+    /// Select a column of the dataset by name.
+    fn index_column(analysis: &mut Analysis, data: u32, name: &str) -> u32 {
+        let names = analysis.literal().value(name.to_string().into()).value_public(true).build();
+        analysis.component_count += 1;
+        analysis.components.insert(analysis.component_count, proto::Component {
+            arguments: Some(proto::ArgumentNodeIds::new(indexmap![
+                "data".into() => data, "names".into() => names])),
+            variant: Some(proto::component::Variant::Index(proto::Index {})),
+            omit: true,
+            submission: 0,
+        });
+        analysis.component_count
+    }
+
+    /// Materialize a dataset of regions and incomes, where the region column may be tagged as public.
+    fn materialize_regions(analysis: &mut Analysis, public_columns: &[&str]) -> (u32, u32) {
+        let column_names = analysis.literal()
+            .value(arr1(&["region".to_string(), "income".to_string()]).into_dyn().into())
+            .value_public(true).build();
+        let public_columns = analysis.literal()
+            .value(arr1(&public_columns.iter().map(|name| name.to_string()).collect::<Vec<_>>()).into_dyn().into())
+            .value_public(true).build();
+        let dataset = analysis.materialize(column_names, "data.csv".to_string())
+            .public_columns(public_columns).build();
+
+        (index_column(analysis, dataset, "region"), index_column(analysis, dataset, "income"))
+    }
+
+    #[test]
+    fn test_public_columns() {
+        let mut analysis = Analysis::new();
+        let (region, income) = materialize_regions(&mut analysis, &["region"]);
+
+        // the public column is known exactly, so its aggregates are released without privacy usage
+        let region_count = analysis.count(region).build();
+        assert!(analysis.properties(region_count).unwrap().array().unwrap().releasable);
+        assert!(crate::compute_privacy_usage(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap().is_zero());
+
+        // while the aggregates of private columns are not releasable
+        let income_count = analysis.count(income).build();
+        assert!(!analysis.properties(income_count).unwrap().array().unwrap().releasable);
+
+        // only columns of the dataset may be tagged
+        let (region, _) = materialize_regions(&mut analysis, &["zip"]);
+        assert!(analysis.properties(region).is_err());
+    }
+
+    /// Stability of the partitions and total epsilon of a dp sum of a private measure grouped by region.
+    fn group_by_usage(public_region: bool) -> (Vec<u32>, f64) {
+        let mut analysis = Analysis::new();
+        analysis.privacy_definition.neighboring = proto::privacy_definition::Neighboring::Substitute as i32;
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let (region, income) = materialize_regions(
+            &mut analysis, if public_region { &["region"] } else { &[] });
+        assert_eq!(analysis.properties(region).unwrap().array().unwrap().releasable, public_region);
+        assert!(!analysis.properties(income).unwrap().array().unwrap().releasable);
+
+        let income = analysis.to_float(income).build();
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(100.0.into()).value_public(true).build();
+        let income = analysis.clamp(income).lower(lower).upper(upper).build();
+        let income = analysis.impute(income).lower(lower).upper(upper).build();
+
+        let categories = analysis.literal()
+            .value(Value::Jagged(vec![vec!["east".to_string(), "west".to_string()]].into()))
+            .value_public(true).build();
+        let partitioned = if public_region {
+            // the public region needs no clamping, only the categories to partition by
+            analysis.partition(income).by(region).categories(categories).build()
+        } else {
+            let null_value = analysis.literal().value("east".to_string().into()).value_public(true).build();
+            let region = analysis.clamp(region).categories(categories).null_value(null_value).build();
+            analysis.partition(income).by(region).build()
+        };
+        let stabilities = analysis.properties(partitioned).unwrap().partitions().unwrap()
+            .children.values().map(|part| part.array().unwrap().c_stability).collect();
+
+        analysis.dp_sum(partitioned, vec![test_data::privacy_usage(1., 0.)])
+            .mechanism("Laplace".to_string()).build();
+
+        let epsilon = get_epsilon(&crate::compute_privacy_usage(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap()).unwrap();
+        (stabilities, epsilon)
+    }
+
+    #[test]
+    fn test_public_partition_keys() {
+        // only the measure is charged, and a substituted record keeps its public region,
+        //    so the partitions need not be doubly stable
+        let (stabilities, epsilon) = group_by_usage(true);
+        assert_eq!(stabilities, vec![1, 1]);
+        assert!((epsilon - 1.).abs() < 1e-10);
+
+        // a substituted record may move to another private region, so the noise on the measure is doubled
+        let (stabilities, epsilon) = group_by_usage(false);
+        assert_eq!(stabilities, vec![2, 2]);
+        assert!((epsilon - 1.).abs() < 1e-10);
+    }
+}
@@ -9,7 +9,7 @@ use crate::base::{
 };
 use crate::components::{Component, Expandable, Sensitivity};
 use crate::errors::*;
-use crate::utilities::prepend;
+use crate::utilities::{get_array_property, prepend, privacy::scale_by_contributions};
 
 impl Component for proto::Quantile {
     fn propagate_property(
@@ -19,13 +19,12 @@ impl Component for proto::Quantile {
         mut properties: base::NodeProperties,
         node_id: u32
     ) -> Result<Warnable<ValueProperties>> {
-        let mut data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
-            .ok_or("data: missing")?.array()
-            .map_err(prepend("data:"))?.clone();
+        let mut data_property: ArrayProperties = get_array_property(&properties, "data")?;
 
         if !data_property.releasable {
             data_property.assert_is_not_aggregated()?;
         }
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
 
         if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
             return Err("data: atomic type must be numeric".into());
@@ -81,7 +80,11 @@ impl Component for proto::Quantile {
                     dimensionality: candidates_property.dimensionality,
                     group_id: data_property.group_id,
                     naturally_ordered: data_property.naturally_ordered,
-                    sample_proportion: None
+                    sample_proportion: None,
+                    monotone: false,
+                    data_derived: false,
+                    num_records_lower_bound: None,
+                    noised: data_property.noised
                 }).into()
             },
             None => {
@@ -109,11 +112,10 @@ impl Sensitivity for proto::Quantile {
         properties: &NodeProperties,
         sensitivity_type: &SensitivitySpace,
     ) -> Result<Value> {
-        let data_property = properties.get::<IndexKey>(&"data".into())
-            .ok_or("data: missing")?.array()
-            .map_err(prepend("data:"))?.clone();
+        let data_property = get_array_property(properties, "data")?;
 
         data_property.assert_is_not_aggregated()?;
+        data_property.assert_bounds_are_public()?;
 
         match sensitivity_type {
             SensitivitySpace::KNorm(_k) => {
@@ -138,10 +140,11 @@ impl Sensitivity for proto::Quantile {
                 let neighboring_type = Neighboring::from_i32(privacy_definition.neighboring)
                     .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))?;
                 use proto::privacy_definition::Neighboring;
-                let cell_sensitivity = match neighboring_type {
+                // a user may shift the rank of a candidate once per row they contribute
+                let cell_sensitivity = scale_by_contributions(privacy_definition, match neighboring_type {
                     Neighboring::AddRemove => self.alpha.max(1. - self.alpha),
                     Neighboring::Substitute => 1.
-                } as Float;
+                } as Float)?;
 
                 let row_sensitivity = (0..data_property.num_columns()?)
                     .map(|_| cell_sensitivity)
@@ -4,7 +4,7 @@ use crate::{proto, base, Warnable, Float};
 
 use crate::components::{Component, Sensitivity};
 use crate::base::{Value, NodeProperties, AggregatorProperties, SensitivitySpace, ValueProperties, DataType};
-use crate::utilities::prepend;
+use crate::utilities::{prepend, privacy::scale_by_contributions};
 use ndarray::prelude::*;
 use std::convert::TryFrom;
 use indexmap::map::IndexMap;
@@ -28,6 +28,7 @@ impl Component for proto::RawMoment {
         if !data_property.releasable {
             data_property.assert_is_not_aggregated()?;
         }
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
         data_property.assert_is_not_empty()?;
 
         let num_columns = data_property.num_columns()?;
@@ -43,13 +44,14 @@ impl Component for proto::RawMoment {
 impl Sensitivity for proto::RawMoment {
     fn compute_sensitivity(
         &self,
-        _privacy_definition: &proto::PrivacyDefinition,
+        privacy_definition: &proto::PrivacyDefinition,
         properties: &NodeProperties,
         sensitivity_type: &SensitivitySpace
     ) -> Result<Value> {
         let data_property = properties.get::<base::IndexKey>(&"data".into())
             .ok_or("data: missing")?.array()
             .map_err(prepend("data:"))?.clone();
+        data_property.assert_bounds_are_public()?;
 
         match sensitivity_type {
             SensitivitySpace::KNorm(k) => {
@@ -60,9 +62,10 @@ impl Sensitivity for proto::RawMoment {
 
                 let row_sensitivity = lower.iter()
                     .zip(upper.iter())
-                    .map(|(min, max)|
-                        ((max - min).powi(self.order as i32) / (num_records as Float)).powi(k))
-                    .collect::<Vec<Float>>();
+                    .map(|(min, max)| scale_by_contributions(
+                        privacy_definition, (max - min).powi(self.order as i32) / (num_records as Float))
+                        .map(|sensitivity| sensitivity.powi(k)))
+                    .collect::<Result<Vec<Float>>>()?;
 
                 let mut array_sensitivity = Array::from(row_sensitivity).into_dyn();
                 array_sensitivity.insert_axis_inplace(Axis(0));
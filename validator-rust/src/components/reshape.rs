@@ -55,6 +55,7 @@ impl Component for proto::Reshape {
         // Treat this as a new dataset, because number of rows is not necessarily the same anymore
         // This exists to prevent binary ops on non-conformable arrays from being approved
         data_property.dataset_id = Some(node_id as i64);
+        data_property.monotone = false;
 
         let matrix_properties = ValueProperties::Array(data_property);
 
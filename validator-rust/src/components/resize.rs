@@ -53,15 +53,22 @@ impl Component for proto::Resize {
             }
 
             data_property.num_records = Some(num_records as i64);
+            data_property.num_records_lower_bound = None;
             data_property.is_not_empty = num_records > 0;
         }
 
         if let Some(minimum_rows) = public_arguments.get::<IndexKey>(&"minimum_rows".into()) {
-            if minimum_rows.ref_array()?.first_int()? > 0 {
-                data_property.is_not_empty = true;
-            } else {
+            let minimum_rows = minimum_rows.ref_array()?.first_int()
+                .map_err(prepend("minimum_rows:"))?;
+            if minimum_rows < 1 {
                 return Err("minimum_rows must be greater than zero".into())
             }
+            // records are only ever added, so the data has at least minimum_rows records,
+            //    even when the exact number of records is unknown
+            data_property.num_records = data_property.num_records.map(|n| n.max(minimum_rows));
+            data_property.num_records_lower_bound = Some(data_property.num_records_lower_bound
+                .map_or(minimum_rows, |bound| bound.max(minimum_rows)));
+            data_property.is_not_empty = true;
         }
 
         let num_columns = data_property.num_columns()?;
@@ -249,6 +256,8 @@ impl Component for proto::Resize {
         if data_property.sample_proportion.is_some() {
             data_property.naturally_ordered = false;
         }
+        // records may be sampled or imputed
+        data_property.monotone = false;
 
         Ok(ValueProperties::Array(data_property).into())
     }
@@ -339,7 +348,7 @@ pub mod test_resize {
         pub fn analysis_f64_cont(value: Value, number_rows: Value, lower: Option<Value>, upper: Option<Value>) -> (Analysis, u32) {
 
             let (mut analysis, imputed) = test_impute::utilities::analysis_f64_cont(
-                value,  None, None);
+                value, lower.clone(), upper.clone());
 
             let lower = analysis.literal().value(match lower {
                 Some(lower) => lower, None => 0.0.into()
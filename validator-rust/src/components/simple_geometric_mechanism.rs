@@ -1,28 +1,30 @@
 use crate::errors::*;
 
-use crate::components::{Sensitivity, Accuracy, Mechanism};
-use crate::{proto, base, Warnable};
+use crate::components::{Accuracy, Mechanism, Report};
+use crate::{proto, base, Float, Warnable};
 
 use crate::components::{Component, Expandable};
 use crate::base::{Value, SensitivitySpace, ValueProperties, DataType, NodeProperties, IndexKey};
-use crate::utilities::{prepend, expand_mechanism, get_literal};
-use crate::utilities::privacy::{spread_privacy_usage, get_epsilon, privacy_usage_check};
+use crate::utilities::{compute_mechanism_sensitivity, prepend, expand_mechanism, get_literal, get_seed, summarize_mechanism, SEEDED_RELEASE_WARNING};
+use crate::utilities::privacy::{compose_privacy_usages, spread_privacy_usage, get_epsilon, get_aggregated_num_records, privacy_usage_check, get_mechanism_privacy_definition};
 use itertools::Itertools;
 use indexmap::map::IndexMap;
 use crate::utilities::inference::infer_property;
+use crate::utilities::json::JSONRelease;
 
 
 impl Component for proto::SimpleGeometricMechanism {
     fn propagate_property(
         &self,
         privacy_definition: &Option<proto::PrivacyDefinition>,
-        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: base::NodeProperties,
         _node_id: u32
     ) -> Result<Warnable<ValueProperties>> {
 
-        let privacy_definition = privacy_definition.as_ref()
-            .ok_or_else(|| "privacy_definition must be defined")?;
+        let privacy_definition = &get_mechanism_privacy_definition(
+            privacy_definition.as_ref().ok_or_else(|| "privacy_definition must be defined")?,
+            &public_arguments, &properties)?;
 
         if privacy_definition.group_size == 0 {
             return Err("group size must be greater than zero".into())
@@ -31,6 +33,7 @@ impl Component for proto::SimpleGeometricMechanism {
         let mut data_property = properties.get::<IndexKey>(&"data".into())
             .ok_or("data: missing")?.array()
             .map_err(prepend("data:"))?.clone();
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
 
         if data_property.data_type != DataType::Int {
             return Err("data: atomic type must be integer".into())
@@ -40,21 +43,23 @@ impl Component for proto::SimpleGeometricMechanism {
             .ok_or_else(|| Error::from("aggregator: missing"))?;
 
         // sensitivity must be computable
-        aggregator.component.compute_sensitivity(
-            privacy_definition,
-            &aggregator.properties,
-            &SensitivitySpace::KNorm(1))?;
+        compute_mechanism_sensitivity(
+            self, privacy_definition, &aggregator)?;
 
-        let privacy_usage = self.privacy_usage.iter().cloned().map(Ok)
-            .fold1(|l, r| l? + r?).ok_or_else(|| "privacy_usage: must be defined")??;
+        let privacy_usage = compose_privacy_usages(&self.privacy_usage)?;
 
-        let warnings = privacy_usage_check(
+        let mut warnings = privacy_usage_check(
             &privacy_usage,
-            data_property.num_records,
-            privacy_definition.strict_parameter_checks)?;
+            get_aggregated_num_records(&aggregator),
+            privacy_definition.strict_parameter_checks,
+            privacy_definition.allow_unknown_num_records)?;
+
+        data_property.mark_released();
 
-        data_property.releasable = true;
-        data_property.aggregator = None;
+        // seeded noise is reproducible, and offers no privacy
+        if get_seed(&public_arguments, &properties)?.is_some() {
+            warnings.push(SEEDED_RELEASE_WARNING.into());
+        }
 
         Ok(Warnable(data_property.into(), warnings))
     }
@@ -86,8 +91,9 @@ impl Expandable for proto::SimpleGeometricMechanism {
         };
 
         let mut expansion = expand_mechanism(
-            &SensitivitySpace::KNorm(1),
-            privacy_definition,
+            &Some(get_mechanism_privacy_definition(
+                privacy_definition.as_ref().ok_or_else(|| "privacy_definition must be defined")?,
+                public_arguments, properties)?),
             self.privacy_usage.as_ref(),
             component,
             properties,
@@ -124,6 +130,41 @@ impl Expandable for proto::SimpleGeometricMechanism {
     }
 }
 
+impl Report for proto::SimpleGeometricMechanism {
+    fn summarize(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        summarize_mechanism(
+            "SimpleGeometric",
+            privacy_definition,
+            &self.privacy_usage,
+            node_id,
+            component,
+            &public_arguments,
+            &properties,
+            release,
+            variable_names,
+            |sensitivity, usage| Ok(Some(sensitivity / get_epsilon(usage)?)))
+            .map(|releases| releases.map(|releases| releases.into_iter()
+                .map(|mut release| {
+                    // the variance of the discrete noise, as the continuous approximation overstates it
+                    let variance = release.algorithm_info.argument.get("noise_scale")
+                        .and_then(|noise_scale| noise_scale.as_f64())
+                        .map(geometric_variance);
+                    release.algorithm_info.argument["variance"] = serde_json::json!(variance);
+                    release
+                })
+                .collect()))
+    }
+}
+
 impl Mechanism for proto::SimpleGeometricMechanism {
     fn get_privacy_usage(
         &self,
@@ -143,6 +184,10 @@ impl Mechanism for proto::SimpleGeometricMechanism {
                 privacy_definition.group_size))
             .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
     }
+
+    fn sensitivity_space(&self) -> Result<SensitivitySpace> {
+        Ok(SensitivitySpace::KNorm(1))
+    }
 }
 
 
@@ -163,9 +208,10 @@ impl Accuracy for proto::SimpleGeometricMechanism {
         Ok(Some(sensitivities.into_iter().zip(accuracies.values.iter())
             .map(|(sensitivity, accuracy)| proto::PrivacyUsage {
                 distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
-                    epsilon: (1. / accuracy.alpha).ln() * (sensitivity as f64 / accuracy.value),
+                    epsilon: sensitivity / geometric_accuracy_to_scale(accuracy.value, accuracy.alpha),
                     delta: 0.,
-                }))
+                })),
+                units: proto::privacy_usage::Units::Central as i32
             })
             .collect()))
     }
@@ -188,9 +234,83 @@ impl Accuracy for proto::SimpleGeometricMechanism {
 
         Ok(Some(sensitivities.into_iter().zip(epsilon.into_iter())
             .map(|(sensitivity, epsilon)| proto::Accuracy {
-                value: ((1. / alpha).ln() * (sensitivity / epsilon) as f64).ceil(),
+                value: geometric_accuracy(sensitivity / epsilon, alpha),
                 alpha
             })
             .collect()))
     }
 }
+
+/// Variance of the two-sided geometric noise with the given scale.
+///
+/// Noise is distributed with probability proportional to `q^|z|`, where `q = exp(-1 / scale)`,
+/// which has variance `2q / (1 - q)^2`.
+pub fn geometric_variance(scale: Float) -> Float {
+    let q = (-1. / scale).exp();
+    2. * q / (1. - q).powi(2)
+}
+
+/// Smallest integer accuracy `a` at which two-sided geometric noise with the given scale exceeds `a` in magnitude with probability at most alpha.
+///
+/// The noise exceeds `a` with probability exactly `2q^(a + 1) / (1 + q)`, where `q = exp(-1 / scale)`.
+pub fn geometric_accuracy(scale: Float, alpha: Float) -> Float {
+    let q = (-1. / scale).exp();
+    ((alpha * (1. + q) / 2.).ln() / q.ln() - 1.).ceil().max(0.)
+}
+
+/// Largest scale of two-sided geometric noise that exceeds the accuracy in magnitude with probability at most alpha.
+///
+/// The tail probability `2q^(a + 1) / (1 + q)` is increasing in `q`, so `q` is found by bisection.
+fn geometric_accuracy_to_scale(accuracy: Float, alpha: Float) -> Float {
+    // the noise is integral, so an error of at most the accuracy is an error of at most its floor
+    let exponent = accuracy.max(0.).floor() + 1.;
+    let tail = |q: Float| 2. * q.powf(exponent) / (1. + q);
+
+    let (mut q_inf, mut q_sup): (Float, Float) = (0., 1.);
+    (0..100).for_each(|_| {
+        let q_mid = q_inf + (q_sup - q_inf) / 2.;
+        if tail(q_mid) > alpha { q_sup = q_mid } else { q_inf = q_mid }
+    });
+    -1. / q_inf.ln()
+}
+
+#[cfg(test)]
+mod test_simple_geometric_mechanism {
+    use crate::components::simple_geometric_mechanism::{geometric_accuracy, geometric_accuracy_to_scale, geometric_variance};
+
+    /// Probability mass of two-sided geometric noise with the given scale.
+    fn probability(scale: f64, z: i64) -> f64 {
+        let q = (-1. / scale).exp();
+        (1. - q) / (1. + q) * q.powi(z.abs() as i32)
+    }
+
+    #[test]
+    fn test_geometric_variance() {
+        // epsilon = 1 and sensitivity = 1
+        let scale = 1.;
+        let q = (-1f64).exp();
+        assert!((geometric_variance(scale) - 2. * q / (1. - q).powi(2)).abs() < 1e-12);
+
+        // the variance of the mass function
+        let variance: f64 = (-1000..=1000).map(|z| (z as f64).powi(2) * probability(scale, z)).sum();
+        assert!((geometric_variance(scale) - variance).abs() < 1e-10);
+
+        // the continuous approximation overstates the variance
+        assert!(geometric_variance(scale) < 2. * scale.powi(2));
+    }
+
+    #[test]
+    fn test_geometric_accuracy() {
+        let (scale, alpha) = (2., 0.05);
+        let accuracy = geometric_accuracy(scale, alpha);
+
+        // the accuracy is the smallest integer that the noise exceeds with probability at most alpha
+        let exceeds = |a: f64| 1. - (-(a as i64)..=(a as i64)).map(|z| probability(scale, z)).sum::<f64>();
+        assert!(exceeds(accuracy) <= alpha);
+        assert!(exceeds(accuracy - 1.) > alpha);
+
+        // the smallest usage that attains the accuracy is at most the given usage
+        assert!(geometric_accuracy_to_scale(accuracy, alpha) >= scale);
+        assert_eq!(geometric_accuracy(geometric_accuracy_to_scale(accuracy, alpha), alpha), accuracy);
+    }
+}
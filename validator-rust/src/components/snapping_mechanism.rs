@@ -7,24 +7,26 @@ use ndarray;
 
 use crate::{base, proto, Warnable};
 use crate::base::{DataType, IndexKey, NodeProperties, SensitivitySpace, Value, ValueProperties};
-use crate::components::{Accuracy, Mechanism, Sensitivity};
+use crate::components::{Accuracy, Mechanism, Report};
 use crate::components::{Component, Expandable};
 use crate::errors::*;
-use crate::utilities::{expand_mechanism, get_literal, prepend, standardize_numeric_argument};
+use crate::utilities::{compute_mechanism_sensitivity, expand_mechanism, get_literal, prepend, standardize_numeric_argument, summarize_mechanism};
 use crate::utilities::inference::infer_property;
-use crate::utilities::privacy::{get_epsilon, privacy_usage_check, spread_privacy_usage};
+use crate::utilities::json::JSONRelease;
+use crate::utilities::privacy::{compose_privacy_usages, get_epsilon, get_aggregated_num_records, privacy_usage_check, spread_privacy_usage, get_mechanism_privacy_definition};
 
 impl Component for proto::SnappingMechanism {
     fn propagate_property(
         &self,
         privacy_definition: &Option<proto::PrivacyDefinition>,
-        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: base::NodeProperties,
         _node_id: u32
     ) -> Result<Warnable<ValueProperties>> {
 
-        let privacy_definition = privacy_definition.as_ref()
-            .ok_or_else(|| "privacy_definition must be defined")?;
+        let privacy_definition = &get_mechanism_privacy_definition(
+            privacy_definition.as_ref().ok_or_else(|| "privacy_definition must be defined")?,
+            &public_arguments, &properties)?;
 
         if privacy_definition.group_size == 0 {
             return Err("group size must be greater than zero".into())
@@ -38,6 +40,7 @@ impl Component for proto::SnappingMechanism {
         let mut data_property = properties.get::<IndexKey>(&"data".into())
             .ok_or("data: missing")?.array()
             .map_err(prepend("data:"))?.clone();
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
 
         if data_property.data_type != DataType::Float && data_property.data_type != DataType::Int {
             return Err("data: atomic type must be numeric".into());
@@ -51,25 +54,23 @@ impl Component for proto::SnappingMechanism {
             .ok_or_else(|| Error::from("aggregator: missing"))?;
 
         // sensitivity must be computable
-        aggregator.component.compute_sensitivity(
-            privacy_definition,
-            &aggregator.properties,
-            &SensitivitySpace::KNorm(1))?.array()?.cast_float()?;
+        compute_mechanism_sensitivity(
+            self, privacy_definition, &aggregator)?.array()?.cast_float()?;
+
+        let num_records = get_aggregated_num_records(&aggregator);
 
         // make sure lipschitz constants is available as a float array
         aggregator.lipschitz_constants.array()?.cast_float()?;
 
-        let privacy_usage = self.privacy_usage.iter().cloned().map(Ok)
-            .fold1(|l, r| l? + r?)
-            .ok_or_else(|| "privacy_usage: must be defined")??;
+        let privacy_usage = compose_privacy_usages(&self.privacy_usage)?;
 
         let warnings = privacy_usage_check(
             &privacy_usage,
-            data_property.num_records,
-            privacy_definition.strict_parameter_checks)?;
+            num_records,
+            privacy_definition.strict_parameter_checks,
+            privacy_definition.allow_unknown_num_records)?;
 
-        data_property.releasable = true;
-        data_property.aggregator = None;
+        data_property.mark_released();
 
         Ok(Warnable(data_property.into(), warnings))
     }
@@ -81,7 +82,7 @@ impl Expandable for proto::SnappingMechanism {
         &self,
         privacy_definition: &Option<proto::PrivacyDefinition>,
         component: &proto::Component,
-        _public_arguments: &IndexMap<IndexKey, &Value>,
+        public_arguments: &IndexMap<IndexKey, &Value>,
         properties: &base::NodeProperties,
         component_id: u32,
         mut maximum_id: u32,
@@ -104,8 +105,9 @@ impl Expandable for proto::SnappingMechanism {
         };
 
         let mut expansion = expand_mechanism(
-            &SensitivitySpace::KNorm(1),
-            privacy_definition,
+            &Some(get_mechanism_privacy_definition(
+                privacy_definition.as_ref().ok_or_else(|| "privacy_definition must be defined")?,
+                public_arguments, properties)?),
             self.privacy_usage.as_ref(),
             component,
             properties,
@@ -142,6 +144,31 @@ impl Expandable for proto::SnappingMechanism {
     }
 }
 
+impl Report for proto::SnappingMechanism {
+    fn summarize(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        summarize_mechanism(
+            "Snapping",
+            privacy_definition,
+            &self.privacy_usage,
+            node_id,
+            component,
+            &public_arguments,
+            &properties,
+            release,
+            variable_names,
+            |sensitivity, usage| Ok(Some(sensitivity / get_epsilon(usage)?)))
+    }
+}
+
 impl Mechanism for proto::SnappingMechanism {
     fn get_privacy_usage(
         &self,
@@ -160,6 +187,10 @@ impl Mechanism for proto::SnappingMechanism {
                 privacy_definition.group_size))
             .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
     }
+
+    fn sensitivity_space(&self) -> Result<SensitivitySpace> {
+        Ok(SensitivitySpace::KNorm(1))
+    }
 }
 
 
@@ -195,7 +226,8 @@ impl Accuracy for proto::SnappingMechanism {
                 distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
                     epsilon: accuracy_to_epsilon(accuracy.value, accuracy.alpha, sensitivity as f64, (upper - lower) as f64 / 2.)?,
                     delta: 0.,
-                }))
+                })),
+                units: proto::privacy_usage::Units::Central as i32
             }))
             .collect::<Result<Vec<_>>>()).transpose()
     }
@@ -0,0 +1,262 @@
+use indexmap::map::IndexMap;
+
+use crate::{base, Float, proto, Warnable};
+use crate::base::{ArrayProperties, DataframeProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Expandable, Mechanism, Report};
+use crate::errors::*;
+use crate::utilities::{get_array_property, prepend};
+use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json, value_to_json};
+use crate::utilities::privacy::{compose_privacy_usages, get_delta, get_epsilon, get_max_contributions, privacy_usage_check};
+
+impl Component for proto::StabilityMechanism {
+    fn propagate_property(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32,
+    ) -> Result<Warnable<ValueProperties>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy_definition must be defined")?;
+
+        if privacy_definition.protect_floating_point {
+            return Err("Floating-point protections are enabled. The stability mechanism adds laplace noise, which is susceptible to floating-point attacks.".into())
+        }
+
+        let data_property = get_array_property(&properties, "data")?;
+        data_property.assert_is_not_aggregated()?;
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
+        if data_property.num_columns()? != 1 {
+            return Err("data: must contain one column".into())
+        }
+        if data_property.data_type == DataType::Float || data_property.data_type == DataType::Unknown {
+            return Err("data: atomic type must be int, bool or string".into())
+        }
+
+        let privacy_usage = compose_privacy_usages(&self.privacy_usage)?;
+
+        // the threshold must be derivable from the usage
+        let (_, threshold) = stability_parameters(privacy_definition, &privacy_usage)?;
+
+        let warnings = privacy_usage_check(
+            &privacy_usage,
+            data_property.num_records,
+            privacy_definition.strict_parameter_checks,
+            privacy_definition.allow_unknown_num_records)?;
+
+        // the released categories are a subset of the observed categories, selected by the noisy counts
+        let column_property = |data_type: DataType, nature: Option<Nature>| ArrayProperties {
+            num_records: None,
+            nature,
+            dataset_id: Some(node_id as i64),
+            is_not_empty: false,
+            ..ArrayProperties::dp_release(node_id, data_type, data_property.group_id.clone())
+        };
+
+        Ok(Warnable(ValueProperties::Dataframe(DataframeProperties {
+            children: indexmap![
+                IndexKey::from("category") => column_property(data_property.data_type.clone(), None).into(),
+                IndexKey::from("count") => column_property(DataType::Float, Some(Nature::Continuous(NatureContinuous {
+                    lower: Vector1DNull::Float(vec![Some(threshold)]),
+                    upper: Vector1DNull::Float(vec![None]),
+                }))).into()
+            ]
+        }), warnings))
+    }
+}
+
+impl Expandable for proto::StabilityMechanism {
+    fn expand_component(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        component: &proto::Component,
+        _public_arguments: &IndexMap<IndexKey, &Value>,
+        properties: &base::NodeProperties,
+        component_id: u32,
+        _maximum_id: u32,
+    ) -> Result<base::ComponentExpansion> {
+        let mut expansion = base::ComponentExpansion::default();
+
+        let data_property = get_array_property(properties, "data")?;
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy definition must be defined")?;
+
+        // update the privacy usage
+        let mut noise_component = component.clone();
+        if let Some(proto::component::Variant::StabilityMechanism(variant)) = &mut noise_component.variant {
+            variant.privacy_usage = self.privacy_usage.iter()
+                .map(|usage| usage.actual_to_effective(
+                    data_property.sample_proportion.unwrap_or(1.),
+                    data_property.c_stability,
+                    privacy_definition.group_size))
+                .collect::<Result<Vec<proto::PrivacyUsage>>>()?;
+        } else { return Err(Error::from("Variant must be defined")) }
+
+        expansion.computation_graph.insert(component_id, noise_component);
+        Ok(expansion)
+    }
+}
+
+impl Mechanism for proto::StabilityMechanism {
+    fn get_privacy_usage(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        release_usage: Option<&Vec<proto::PrivacyUsage>>,
+        properties: &NodeProperties
+    ) -> Result<Option<Vec<proto::PrivacyUsage>>> {
+        let data_property = properties.get::<IndexKey>(&"data".into())
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?;
+
+        Some(release_usage.unwrap_or_else(|| &self.privacy_usage).iter()
+            .map(|usage| usage.effective_to_actual(
+                data_property.sample_proportion.unwrap_or(1.),
+                data_property.c_stability,
+                privacy_definition.group_size))
+            .collect::<Result<Vec<proto::PrivacyUsage>>>()).transpose()
+    }
+
+    fn sensitivity_space(&self) -> Result<SensitivitySpace> {
+        Ok(SensitivitySpace::KNorm(1))
+    }
+}
+
+impl Report for proto::StabilityMechanism {
+    fn summarize(
+        &self,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        node_id: u32,
+        component: &proto::Component,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        _properties: NodeProperties,
+        release: &Value,
+        variable_names: Option<&Vec<base::IndexKey>>,
+    ) -> Result<Option<Vec<JSONRelease>>> {
+        let privacy_definition = privacy_definition.as_ref()
+            .ok_or_else(|| "privacy definition must be defined")?;
+        let privacy_usage = compose_privacy_usages(&self.privacy_usage)?;
+        let (scale, threshold) = stability_parameters(privacy_definition, &privacy_usage)?;
+
+        let variable_name = variable_names
+            .and_then(|names| names.first()).cloned()
+            .unwrap_or_else(|| "[Unknown]".into());
+
+        Ok(Some(vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "StabilityHistogram".to_string(),
+            variables: serde_json::json!(variable_name.to_string()),
+            release_info: value_to_json(release)?,
+            privacy_loss: privacy_usage_to_json(&privacy_usage),
+            accuracy: None,
+            relative_accuracy: None,
+            submission: component.submission,
+            node_id,
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "".to_string(),
+                cite: "".to_string(),
+                mechanism: "Stability".to_string(),
+                argument: serde_json::json!({
+                    "scale": scale,
+                    "threshold": threshold
+                }),
+            },
+        }]))
+    }
+}
+
+/// Scale of the laplace noise added to each count, and the threshold below which noisy counts are suppressed.
+///
+/// A user contributing up to k rows changes the counts by k in L1 norm, and may add up to k categories,
+/// each with a count of at most k.
+/// The count of a category that only exists in one of the neighboring datasets exceeds the threshold
+/// `k + scale * ln(k / (2 delta))` with probability at most `delta / k`,
+/// so the release of any new category has probability at most delta.
+pub fn stability_parameters(
+    privacy_definition: &proto::PrivacyDefinition,
+    privacy_usage: &proto::PrivacyUsage,
+) -> Result<(Float, Float)> {
+    use proto::privacy_definition::Neighboring;
+
+    let epsilon = get_epsilon(privacy_usage)?;
+    let delta = get_delta(privacy_usage)?;
+    if epsilon <= 0. {
+        return Err("epsilon: must be greater than zero".into())
+    }
+    if delta <= 0. || delta >= 1. {
+        return Err("delta: the stability mechanism requires delta in (0, 1), to bound the probability of releasing a category contributed by one user".into())
+    }
+
+    let contributions = get_max_contributions(privacy_definition)? as Float;
+
+    // substituting rows moves them from one category to another, which changes twice as many counts
+    let l1_sensitivity = match Neighboring::from_i32(privacy_definition.neighboring)
+        .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))? {
+        Neighboring::AddRemove => contributions,
+        Neighboring::Substitute => 2. * contributions
+    };
+
+    let scale = l1_sensitivity / epsilon;
+    let threshold = contributions + scale * (contributions / (2. * delta)).ln();
+    Ok((scale, threshold))
+}
+
+#[cfg(test)]
+mod test_stability_mechanism {
+    use crate::base::{IndexKey, test_data};
+    use crate::bindings::Analysis;
+    use crate::components::impute::test_impute;
+    use crate::components::stability_mechanism::stability_parameters;
+
+    #[test]
+    fn test_stability_parameters() {
+        let mut analysis = Analysis::new();
+        let (scale, threshold) = stability_parameters(&analysis.privacy_definition, &test_data::privacy_usage(1., 1e-6)).unwrap();
+        assert_eq!(scale, 1.);
+        assert!((threshold - (1. + (0.5e6f64).ln())).abs() < 1e-10);
+
+        // the category set may only be private with a nonzero delta
+        assert!(stability_parameters(&analysis.privacy_definition, &test_data::privacy_usage(1., 0.)).is_err());
+
+        // a larger delta admits a lower threshold
+        let (_, loose_threshold) = stability_parameters(&analysis.privacy_definition, &test_data::privacy_usage(1., 1e-2)).unwrap();
+        assert!(loose_threshold < threshold);
+
+        // a user contributing more rows raises the threshold
+        analysis.privacy_definition.max_contributions_per_user = 2;
+        let (_, group_threshold) = stability_parameters(&analysis.privacy_definition, &test_data::privacy_usage(1., 1e-6)).unwrap();
+        assert!(group_threshold > threshold);
+    }
+
+    #[test]
+    fn test_stability_histogram() {
+        let (mut analysis, imputed) = test_impute::utilities::analysis_i64_cont(
+            test_data::array1d_i64_10_uniform(), Some(0.into()), Some(10.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let lower = analysis.literal().value(0.into()).value_public(true).build();
+        let inclusive_left = analysis.literal().value(true.into()).value_public(true).build();
+        let histogram = analysis.dp_histogram(imputed, lower, inclusive_left, vec![test_data::privacy_usage(1., 1e-6)])
+            .mechanism("Stability".to_string())
+            .build();
+
+        let properties = analysis.properties(histogram).unwrap();
+        let properties = properties.dataframe().unwrap();
+
+        // the released categories depend on the data, so they may not be used as public categories downstream
+        let category = properties.children.get::<IndexKey>(&"category".into()).unwrap().array().unwrap();
+        assert!(category.data_derived);
+        assert_eq!(category.data_type, crate::base::DataType::Int);
+
+        // every released count is at least the threshold, which the conversion to an effective delta may only raise
+        let (_, threshold) = stability_parameters(&analysis.privacy_definition, &test_data::privacy_usage(1., 1e-6)).unwrap();
+        let count = properties.children.get::<IndexKey>(&"count".into()).unwrap().array().unwrap();
+        assert!(count.lower_float().unwrap()[0] >= threshold);
+
+        // the category set is not private without a nonzero delta
+        let histogram = analysis.dp_histogram(imputed, lower, inclusive_left, vec![test_data::privacy_usage(1., 0.)])
+            .mechanism("Stability".to_string())
+            .build();
+        assert!(analysis.properties(histogram).is_err());
+    }
+}
@@ -2,10 +2,10 @@ use indexmap::map::IndexMap;
 use ndarray::prelude::*;
 
 use crate::{base, Float, proto, Warnable};
-use crate::base::{AggregatorProperties, DataType, IndexKey, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
+use crate::base::{AggregatorProperties, DataType, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
 use crate::components::{Component, Sensitivity};
 use crate::errors::*;
-use crate::utilities::prepend;
+use crate::utilities::{get_array_property, prepend, privacy::scale_by_contributions};
 
 impl Component for proto::Sum {
     fn propagate_property(
@@ -15,13 +15,12 @@ impl Component for proto::Sum {
         properties: base::NodeProperties,
         node_id: u32
     ) -> Result<Warnable<ValueProperties>> {
-        let mut data_property = properties.get::<IndexKey>(&"data".into())
-            .ok_or("data: missing")?.array()
-            .map_err(prepend("data:"))?.clone();
+        let mut data_property = get_array_property(&properties, "data")?;
 
         if !data_property.releasable {
             data_property.assert_is_not_aggregated()?;
         }
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
 
         let num_columns = data_property.num_columns()?;
         // save a snapshot of the state when aggregating
@@ -67,12 +66,14 @@ impl Sensitivity for proto::Sum {
 
             SensitivitySpace::KNorm(k) => {
 
-                let data_property = properties.get::<IndexKey>(&"data".into())
-                    .ok_or("data: missing")?.array()
-                    .map_err(prepend("data:"))?.clone();
+                let data_property = get_array_property(properties, "data")?;
 
                 data_property.assert_is_not_aggregated()?;
                 data_property.assert_non_null()?;
+                data_property.assert_bounds_are_public()?;
+                assert_is_bounded(
+                    &data_property.lower_float_option()?,
+                    &data_property.upper_float_option()?)?;
 
                 use proto::privacy_definition::Neighboring;
                 let neighboring_type = Neighboring::from_i32(privacy_definition.neighboring)
@@ -85,12 +86,12 @@ impl Sensitivity for proto::Sum {
                                 1 | 2 => match neighboring_type {
                                     Neighboring::AddRemove => $lower.iter()
                                         .zip($upper.iter())
-                                        .map(|(min, max)| min.abs().max(max.abs()))
-                                        .collect::<Vec<_>>(),
+                                        .map(|(min, max)| scale_by_contributions(privacy_definition, min.abs().max(max.abs())))
+                                        .collect::<Result<Vec<_>>>()?,
                                     Neighboring::Substitute => $lower.iter()
                                         .zip($upper.iter())
-                                        .map(|(min, max)| (max - min))
-                                        .collect::<Vec<_>>()
+                                        .map(|(min, max)| scale_by_contributions(privacy_definition, max - min))
+                                        .collect::<Result<Vec<_>>>()?
                                 }
                                 _ => return Err("KNorm sensitivity is only supported in L1 and L2 spaces".into())
                             };
@@ -104,12 +105,112 @@ impl Sensitivity for proto::Sum {
                 }
 
                 match data_property.data_type {
-                    DataType::Int => compute_sensitivity!(data_property.lower_int()?, data_property.upper_int()?),
-                    DataType::Float => compute_sensitivity!(data_property.lower_float()?, data_property.upper_float()?),
+                    DataType::Int => compute_sensitivity!(
+                        data_property.lower_int()?, data_property.upper_int()?),
+                    DataType::Float => compute_sensitivity!(
+                        data_property.lower_float()?, data_property.upper_float()?),
                     _ => return Err(Error::from("sum data must be numeric"))
                 }
             }
             _ => Err("Sum sensitivity is only implemented for KNorm".into())
         }
     }
-}
\ No newline at end of file
+}
+/// Sum sensitivity is only derivable when each column is bounded from both sides.
+///
+/// Knowing only one bound is not sufficient, even under add/remove neighboring.
+/// For example, if data is known to be nonnegative but unbounded above,
+/// a single added, removed or substituted record may change the sum by an arbitrarily large amount.
+fn assert_is_bounded(lower: &[Option<Float>], upper: &[Option<Float>]) -> Result<()> {
+    lower.iter().zip(upper.iter()).enumerate()
+        .try_for_each(|(column_number, bounds)| match bounds {
+            (Some(_), Some(_)) => Ok(()),
+            (Some(lower), None) => Err(format!(
+                "column {}: data is bounded below by {}, but not above. A sum over data bounded from one side has unbounded sensitivity. Use a clamp to set an upper bound.",
+                column_number, lower).into()),
+            (None, Some(upper)) => Err(format!(
+                "column {}: data is bounded above by {}, but not below. A sum over data bounded from one side has unbounded sensitivity. Use a clamp to set a lower bound.",
+                column_number, upper).into()),
+            (None, None) => Err(format!(
+                "column {}: data bounds are unknown. Use a clamp to set data bounds.",
+                column_number).into())
+        })
+}
+
+#[cfg(test)]
+mod test_sum {
+    use crate::base::{ArrayProperties, IndexKey, Nature, NatureContinuous, SensitivitySpace, test_data, ValueProperties, Vector1DNull};
+    use crate::components::impute::test_impute;
+    use crate::components::Sensitivity;
+    use crate::proto;
+    use crate::proto::privacy_definition::Neighboring;
+
+    fn data_property(lower: Option<f64>, upper: Option<f64>) -> ArrayProperties {
+        let (analysis, imputed) = test_impute::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), Some(0.0.into()), Some(10.0.into()));
+        let mut data_property = analysis.properties(imputed).unwrap().array().unwrap().clone();
+        data_property.nature = Some(Nature::Continuous(NatureContinuous {
+            lower: Vector1DNull::Float(vec![lower]),
+            upper: Vector1DNull::Float(vec![upper]),
+        }));
+        data_property
+    }
+
+    fn sum_sensitivity(data_property: ArrayProperties, neighboring: Neighboring) -> crate::errors::Result<Vec<f64>> {
+        let mut privacy_definition = crate::bindings::Analysis::new().privacy_definition;
+        privacy_definition.neighboring = neighboring as i32;
+
+        let sensitivity = proto::Sum {}.compute_sensitivity(
+            &privacy_definition,
+            &indexmap![IndexKey::from("data") => ValueProperties::Array(data_property)],
+            &SensitivitySpace::KNorm(1))?;
+        Ok(sensitivity.array()?.float()?.iter().cloned().collect())
+    }
+
+    #[test]
+    fn test_nonnegative_unbounded_above() {
+        for neighboring in [Neighboring::AddRemove, Neighboring::Substitute] {
+            assert!(sum_sensitivity(data_property(Some(0.), None), neighboring).is_err());
+        }
+    }
+
+    #[test]
+    fn test_nonpositive_unbounded_below() {
+        for neighboring in [Neighboring::AddRemove, Neighboring::Substitute] {
+            assert!(sum_sensitivity(data_property(None, Some(0.)), neighboring).is_err());
+        }
+    }
+
+    #[test]
+    fn test_bounded_below_and_above() {
+        let data_property = data_property(Some(2.), Some(10.));
+        assert_eq!(sum_sensitivity(data_property.clone(), Neighboring::AddRemove).unwrap(), vec![10.]);
+        assert_eq!(sum_sensitivity(data_property, Neighboring::Substitute).unwrap(), vec![8.]);
+    }
+
+    #[test]
+    fn test_data_derived_bounds() {
+        let (mut analysis, imputed) = test_impute::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+
+        // the upper bound is a release of the maximum of the private data
+        let maximum = analysis.dp_maximum(imputed, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+        let clamped = analysis.clamp(imputed).lower(lower).upper(maximum).build();
+        let clamped_property = analysis.properties(clamped).unwrap().array().unwrap().clone();
+        assert!(clamped_property.data_derived);
+        assert!(sum_sensitivity(clamped_property, Neighboring::AddRemove).is_err());
+
+        // bounds from a public source remain valid
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(imputed).lower(lower).upper(upper).build();
+        let clamped_property = analysis.properties(clamped).unwrap().array().unwrap().clone();
+        assert!(!clamped_property.data_derived);
+        assert!(sum_sensitivity(clamped_property, Neighboring::AddRemove).is_ok());
+    }
+}
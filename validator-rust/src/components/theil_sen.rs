@@ -83,7 +83,11 @@ impl Component for proto::TheilSen {
             dimensionality: Some(1),
             group_id: propagate_binary_group_id(&data_property_x, &data_property_y)?,
             naturally_ordered: false,
-            sample_proportion: None
+            sample_proportion: None,
+            monotone: false,
+            data_derived: false,
+            num_records_lower_bound: None,
+            noised: data_property_x.noised && data_property_y.noised
         };
 
         Ok(ValueProperties::Dataframe(DataframeProperties {
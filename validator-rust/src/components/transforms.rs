@@ -1,18 +1,19 @@
 use crate::errors::*;
 
-use crate::base::{Nature, NatureCategorical, Vector1DNull, Jagged, ArrayProperties, ValueProperties, DataType, GroupId};
+use crate::base::{Array, Nature, NatureCategorical, Vector1DNull, Jagged, ArrayProperties, ValueProperties, DataType, GroupId};
 
 use crate::{proto, base, Warnable, Integer, Float};
 
-use crate::utilities::{prepend};
+use crate::utilities::{prepend, interval};
+use crate::utilities::inference::infer_property;
+use crate::utilities::interval::Interval;
 
 use crate::components::{Component};
 
 use crate::base::{IndexKey, Value, NatureContinuous};
-use num::{CheckedAdd, CheckedSub, Zero};
+use num::{CheckedAdd, CheckedSub};
 use indexmap::map::IndexMap;
-use std::ops::{Mul, Div};
-use std::cmp::Ordering;
+use ndarray::arr0;
 use itertools::Itertools;
 
 
@@ -54,6 +55,8 @@ impl Component for proto::Abs {
                 })),
             }, data_property.num_columns()?)?;
 
+        // the transformation may not preserve the order of values
+        data_property.monotone = false;
         Ok(ValueProperties::Array(data_property).into())
     }
 }
@@ -94,6 +97,7 @@ impl Component for proto::Add {
             return Err("left and right arguments must share the same data types".into())
         }
 
+        let noised = left_property.noised_with(&right_property);
         Ok(ValueProperties::Array(ArrayProperties {
             nullity: left_property.nullity || right_property.nullity,
             releasable: left_property.releasable && right_property.releasable,
@@ -105,23 +109,8 @@ impl Component for proto::Add {
                 str: Some(Box::new(|l: &String, r: &String| Ok(format!("{}{}", l, r)))),
                 bool: None,
             }, &OptimizeBinaryOperators {
-                float: Some(&|bounds| Ok((
-                    bounds.left_lower.and_then(|lmin| bounds.right_lower.and_then(|rmin|
-                        Some(lmin + rmin))),
-                    bounds.left_upper.and_then(|lmax| bounds.right_upper.and_then(|rmax|
-                        Some(lmax + rmax))),
-                ))),
-                int: Some(&|bounds| Ok((
-                    match (bounds.left_lower, bounds.right_lower) {
-                        (Some(lmin), Some(rmin)) => Some(lmin.checked_add(rmin)
-                            .ok_or_else(|| Error::from("addition may result in underflow or overflow"))?),
-                        _ => None
-                    },
-                    match (bounds.left_upper, bounds.right_upper) {
-                        (Some(lmax), Some(rmax)) => Some(lmax.checked_add(rmax)
-                            .ok_or_else(|| Error::from("addition may result in underflow or overflow"))?),
-                        _ => None
-                    })))
+                float: Some(&|bounds| interval::add(bounds.left(), bounds.right())),
+                int: Some(&|bounds| interval::add(bounds.left(), bounds.right())),
             }, num_columns)?,
             // checks to ensure this is correct are made in propagate_binary_shape
             c_stability: left_property.c_stability
@@ -138,7 +127,12 @@ impl Component for proto::Add {
                 .max(right_property.dimensionality),
             naturally_ordered: true,
             // checks are made within propagate_binary_shape that sampling proportion is equal and permissible
-            sample_proportion: left_property.sample_proportion
+            sample_proportion: left_property.sample_proportion,
+            monotone: false,
+            data_derived: left_property.data_derived || right_property.data_derived,
+            num_records_lower_bound: left_property.num_records_lower_bound
+                .max(right_property.num_records_lower_bound),
+            noised
         }).into())
     }
 }
@@ -173,6 +167,8 @@ impl Component for proto::And {
         }
 
         left_property.releasable = left_property.releasable && right_property.releasable;
+        // a false operand determines the conjunction, even when the other operand is null
+        left_property.nullity = propagate_kleene_nullity(&left_property, &right_property, kleene_and);
         left_property.nature = propagate_binary_nature(
             &left_property, &right_property,
             &BinaryOperators {
@@ -198,12 +194,86 @@ impl Component for proto::And {
     }
 }
 
+/// Kleene three-valued conjunction, where `None` is null.
+///
+/// The conjunction is false if either operand is false, and otherwise null if either operand is null.
+pub fn kleene_and(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None
+    }
+}
+
+/// Kleene three-valued disjunction, where `None` is null.
+///
+/// The disjunction is true if either operand is true, and otherwise null if either operand is null.
+pub fn kleene_or(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None
+    }
+}
+
+/// Whether a three-valued boolean operator may produce a null, over every pair of values the operands may take.
+fn propagate_kleene_nullity(
+    left_property: &ArrayProperties, right_property: &ArrayProperties,
+    operator: fn(Option<bool>, Option<bool>) -> Option<bool>,
+) -> bool {
+    // values a boolean operand may take, where None is null
+    let get_values = |property: &ArrayProperties| {
+        let mut values = match &property.nature {
+            Some(Nature::Categorical(NatureCategorical { categories: Jagged::Bool(categories) })) =>
+                categories.iter().flatten().copied().unique().map(Some).collect::<Vec<Option<bool>>>(),
+            _ => vec![Some(true), Some(false)]
+        };
+        if property.nullity {
+            values.push(None)
+        }
+        values
+    };
+
+    get_values(left_property).into_iter()
+        .cartesian_product(get_values(right_property))
+        .any(|(left, right)| operator(left, right).is_none())
+}
+
+/// Result of a division by zero
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ZeroPolicy {
+    /// evaluation fails
+    Error,
+    /// the quotient is null
+    Null,
+    /// IEEE semantics. A nonzero numerator produces infinity, and a zero numerator produces NaN
+    NaN,
+}
+
+fn get_zero_policy(public_arguments: &IndexMap<base::IndexKey, &Value>, data_type: &DataType) -> Result<ZeroPolicy> {
+    let zero_policy = match public_arguments.get::<IndexKey>(&"zero_policy".into()) {
+        Some(policy) => match policy.ref_array()?.first_string()?.to_lowercase().as_str() {
+            "error" => ZeroPolicy::Error,
+            "null" => ZeroPolicy::Null,
+            "nan" => ZeroPolicy::NaN,
+            _ => return Err("zero_policy: must be one of \"error\", \"null\" or \"nan\"".into())
+        },
+        // floats follow IEEE semantics by default, while integer division by zero is undefined
+        None => if data_type == &DataType::Float { ZeroPolicy::NaN } else { ZeroPolicy::Error }
+    };
+
+    if data_type == &DataType::Int && zero_policy != ZeroPolicy::Error {
+        return Err("zero_policy: integers have no null or NaN, so integer division by zero must error".into())
+    }
+    Ok(zero_policy)
+}
+
 impl Component for proto::Divide {
     #[allow(clippy::float_cmp)]
     fn propagate_property(
         &self,
         _privacy_definition: &Option<proto::PrivacyDefinition>,
-        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: base::NodeProperties,
         node_id: u32
     ) -> Result<Warnable<ValueProperties>> {
@@ -228,91 +298,37 @@ impl Component for proto::Divide {
             return Err("left and right arguments must share the same data types".into())
         }
 
-        let float_denominator_may_span_zero = match right_property.clone().nature {
+        let zero_policy = get_zero_policy(&public_arguments, &left_property.data_type)?;
+
+        let denominator_may_be_zero = match right_property.clone().nature {
             Some(nature) => match nature {
-                Nature::Continuous(nature) => nature.lower.float()
-                    .map(|min| nature.upper.float()
-                        .map(|max| min.iter().zip(max.iter())
-                            .any(|(min, max)| min
-                                .map(|min| max
-                                    .map(|max| min < 0. && max > 0.)
-                                    // if max is not known
-                                    .unwrap_or(min > 0.))
-                                // if min is not known
-                                .unwrap_or_else(|| max.map(|max| max < 0.)
-                                    .unwrap_or(true))))
-                        // if max is not float
-                        .unwrap_or(false))
-                    // if min is not float
-                    .unwrap_or(false),
-                Nature::Categorical(nature) => nature.categories.float()
-                    .map(|categories| categories.iter()
+                Nature::Continuous(nature) => match (nature.lower, nature.upper) {
+                    (Vector1DNull::Float(min), Vector1DNull::Float(max)) => min.iter().zip(max.iter())
+                        // if a bound is not known, the data could span zero
+                        .any(|(min, max)| min.map(|min| min <= 0.).unwrap_or(true)
+                            && max.map(|max| max >= 0.).unwrap_or(true)),
+                    (Vector1DNull::Int(min), Vector1DNull::Int(max)) => min.iter().zip(max.iter())
+                        .any(|(min, max)| min.map(|min| min <= 0).unwrap_or(true)
+                            && max.map(|max| max >= 0).unwrap_or(true)),
+                    _ => true
+                },
+                Nature::Categorical(nature) => match nature.categories {
+                    Jagged::Float(categories) => categories.iter()
                         .any(|column| column.iter()
-                            .any(|category| category.is_nan() || category == &0.)))
-                    // if categories are not known, a category could be zero or NAN
-                    .unwrap_or(false)
+                            .any(|category| category.is_nan() || category == &0.)),
+                    Jagged::Int(categories) => categories.iter()
+                        .any(|column| column.contains(&0)),
+                    _ => true
+                }
             },
             // if nature is not known, data could span zero
             _ => true
         };
 
-        // minimize and maximize b / e when a <= b <= c and d <= e <= f
-        fn optimize<T: PartialOrd + Div<Output=T> + Zero + Copy>(
-            a: T, c: T, d: T, f: T
-        ) -> Result<(Option<T>, Option<T>)> {
-            let compare = |x: &T, y: &T| x.partial_cmp(y).unwrap_or(Ordering::Equal);
-            // if denominator interval does not contain zero
-            if T::zero() < d || f < T::zero() {
-                let (min, max) = vec![a / f, a / d, c / f, c / d].into_iter()
-                    .minmax_by(compare).into_option().unwrap();
-                return Ok((Some(min), Some(max)))
-            }
-
-            // if one arm of denominator is zero
-            if d.is_zero() && !f.is_zero() {
-                return Ok((None, vec![a / f, c / f].into_iter().max_by(compare)))
-            }
-            if !d.is_zero() && f.is_zero() {
-                return Ok((vec![a / d, c / d].into_iter().min_by(compare), None))
-            }
-            Ok((None, None))
-        }
-
-        fn optimize_wrapper<T: PartialOrd + Div<Output=T> + Zero + Copy>(
-            bounds: BinaryBounds<T>
-        ) -> Result<(Option<T>, Option<T>)> {
-
-            let a = match bounds.left_lower {
-                Some(v) => *v,
-                None => return Ok((None, None))
-            };
-            let c = match bounds.left_upper {
-                Some(v) => *v,
-                None => return Ok((None, None))
-            };
-            let d = match bounds.right_lower {
-                Some(v) => *v,
-                None => {
-                    if bounds.right_upper.map(|v| v >= T::zero()).unwrap_or(true) {
-                        return Err("potential division by zero".into())
-                    }
-                    return Ok((None, None))
-                }
-            };
-            let f = match bounds.right_upper {
-                Some(v) => *v,
-                None => {
-                    if bounds.right_lower.map(|v| v <= T::zero()).unwrap_or(true) {
-                        return Err("potential division by zero".into())
-                    }
-                    return Ok((None, None))
-                }
-            };
-            optimize(a, c, d, f)
-        }
-
+        let noised = left_property.noised_with(&right_property);
         Ok(ValueProperties::Array(ArrayProperties {
-            nullity: left_property.nullity || right_property.nullity || float_denominator_may_span_zero,
+            nullity: left_property.nullity || right_property.nullity
+                || (zero_policy != ZeroPolicy::Error && denominator_may_be_zero),
             releasable: left_property.releasable && right_property.releasable,
             nature: propagate_binary_nature(&left_property, &right_property, &BinaryOperators {
                 float: Some(Box::new(|l: &Float, r: &Float| {
@@ -327,8 +343,14 @@ impl Component for proto::Divide {
                 str: None,
                 bool: None,
             }, &OptimizeBinaryOperators {
-                float: Some(&optimize_wrapper),
-                int: Some(&optimize_wrapper)
+                // bounds on the quotient exclude division by zero, which either errors or is null.
+                //    Under IEEE semantics, the quotient is already unbounded as the denominator approaches zero
+                float: Some(&|bounds| match zero_policy {
+                    ZeroPolicy::Error => interval::div(bounds.left(), bounds.right()),
+                    // a denominator unbounded toward zero leaves the quotient unbounded, instead of erroring
+                    _ => Ok(interval::div(bounds.left(), bounds.right()).unwrap_or((None, None)))
+                }),
+                int: Some(&|bounds| interval::div(bounds.left(), bounds.right())),
             }, num_columns)?,
             // checks to ensure this is correct are made in propagate_binary_shape
             c_stability: left_property.c_stability
@@ -345,7 +367,12 @@ impl Component for proto::Divide {
                 .max(right_property.dimensionality),
             naturally_ordered: true,
             // checks are made within propagate_binary_shape that sampling proportion is equal and permissible
-            sample_proportion: left_property.sample_proportion
+            sample_proportion: left_property.sample_proportion,
+            monotone: false,
+            data_derived: left_property.data_derived || right_property.data_derived,
+            num_records_lower_bound: left_property.num_records_lower_bound
+                .max(right_property.num_records_lower_bound),
+            noised
         }).into())
     }
 }
@@ -354,7 +381,7 @@ impl Component for proto::Equal {
     fn propagate_property(
         &self,
         _privacy_definition: &Option<proto::PrivacyDefinition>,
-        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: base::NodeProperties,
         node_id: u32
     ) -> Result<Warnable<ValueProperties>> {
@@ -378,8 +405,18 @@ impl Component for proto::Equal {
             return Err("left and right must be homogeneously typed".into())
         }
 
+        if let Some(tolerance) = public_arguments.get::<IndexKey>(&"tolerance".into()) {
+            if left_property.data_type != DataType::Float {
+                return Err("tolerance may only be set when comparing floats".into())
+            }
+            if tolerance.ref_array()?.first_float()? < 0. {
+                return Err("tolerance must be non-negative".into())
+            }
+        }
+
         let (num_columns, num_records) = propagate_binary_shape(&left_property, &right_property)?;
 
+        let noised = left_property.noised_with(&right_property);
         Ok(ValueProperties::Array(ArrayProperties {
             nullity: false,
             releasable: left_property.releasable && right_property.releasable,
@@ -400,7 +437,13 @@ impl Component for proto::Equal {
             group_id: propagate_binary_group_id(&left_property, &right_property)?,
             naturally_ordered: true,
             // checks are made within propagate_binary_shape that sampling proportion is equal and permissible
-            sample_proportion: left_property.sample_proportion
+            sample_proportion: left_property.sample_proportion,
+            monotone: false,
+            // comparisons are always bounded by {true, false}, even when compared against a release
+            data_derived: false,
+            num_records_lower_bound: left_property.num_records_lower_bound
+                .max(right_property.num_records_lower_bound),
+            noised
         }).into())
     }
 }
@@ -442,6 +485,7 @@ impl Component for proto::GreaterThan {
 
         let (num_columns, num_records) = propagate_binary_shape(&left_property, &right_property)?;
 
+        let noised = left_property.noised_with(&right_property);
         Ok(ValueProperties::Array(ArrayProperties {
             nullity: false,
             releasable: left_property.releasable && right_property.releasable,
@@ -463,7 +507,13 @@ impl Component for proto::GreaterThan {
             group_id: propagate_binary_group_id(&left_property, &right_property)?,
             naturally_ordered: true,
             // checks are made within propagate_binary_shape that sampling proportion is equal and permissible
-            sample_proportion: left_property.sample_proportion
+            sample_proportion: left_property.sample_proportion,
+            monotone: false,
+            // comparisons are always bounded by {true, false}, even when compared against a release
+            data_derived: false,
+            num_records_lower_bound: left_property.num_records_lower_bound
+                .max(right_property.num_records_lower_bound),
+            noised
         }).into())
     }
 }
@@ -505,6 +555,7 @@ impl Component for proto::LessThan {
 
         let (num_columns, num_records) = propagate_binary_shape(&left_property, &right_property)?;
 
+        let noised = left_property.noised_with(&right_property);
         Ok(ValueProperties::Array(ArrayProperties {
             nullity: false,
             releasable: left_property.releasable && right_property.releasable,
@@ -526,7 +577,13 @@ impl Component for proto::LessThan {
             group_id: propagate_binary_group_id(&left_property, &right_property)?,
             naturally_ordered: true,
             // checks are made within propagate_binary_shape that sampling proportion is equal and permissible
-            sample_proportion: left_property.sample_proportion
+            sample_proportion: left_property.sample_proportion,
+            monotone: false,
+            // comparisons are always bounded by {true, false}, even when compared against a release
+            data_derived: false,
+            num_records_lower_bound: left_property.num_records_lower_bound
+                .max(right_property.num_records_lower_bound),
+            noised
         }).into())
     }
 }
@@ -536,25 +593,26 @@ impl Component for proto::Log {
     fn propagate_property(
         &self,
         _privacy_definition: &Option<proto::PrivacyDefinition>,
-        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: base::NodeProperties,
-        _node_id: u32
+        node_id: u32
     ) -> Result<Warnable<ValueProperties>> {
         let mut data_property: ArrayProperties = properties.get(&IndexKey::from("data"))
-            .ok_or("left: missing")?.array()
-            .map_err(prepend("left:"))?.clone();
-        let base_property: ArrayProperties = properties.get::<IndexKey>(&"base".into())
-            .ok_or("base: missing")?.array()
-            .map_err(prepend("base:"))?.clone();
+            .ok_or("data: missing")?.array()
+            .map_err(prepend("data:"))?.clone();
+
+        // the base defaults to the natural log
+        let base_property: ArrayProperties = match public_arguments.get::<IndexKey>(&"base".into()) {
+            Some(base) => infer_property(base, None, node_id)?,
+            None if properties.contains_key::<IndexKey>(&"base".into()) =>
+                return Err("base: must be public".into()),
+            None => infer_property(&Value::Array(Array::Float(arr0(std::f64::consts::E as Float).into_dyn())), None, node_id)?
+        }.array().map_err(prepend("base:"))?.clone();
 
         if !data_property.releasable {
             data_property.assert_is_not_aggregated()?;
             data_property.assert_is_not_sampled()?;
         }
-        if !base_property.releasable {
-            base_property.assert_is_not_aggregated()?;
-            base_property.assert_is_not_sampled()?;
-        }
 
         if data_property.data_type != DataType::Float {
             return Err("arguments for log must be float and homogeneously typed".into());
@@ -566,9 +624,9 @@ impl Component for proto::Log {
             return Err("base must be in [0, 1) U (1, inf) and not span zero".into())
         }
 
-        if !data_property.lower_float()?.iter()
+        if !data_property.lower_float().map_err(prepend("data:"))?.iter()
             .all(|min| min > &0.) {
-            return Err("data may potentially be less than zero".into())
+            return Err("data: log requires positive inputs, but data may be zero or less".into())
         }
 
         data_property.nature = propagate_binary_nature(
@@ -580,10 +638,7 @@ impl Component for proto::Log {
                 str: None,
             },
             &OptimizeBinaryOperators {
-                float: Some(&|_bounds| {
-                    // TODO: derive data bounds for log transform
-                    Ok((None, None))
-                }),
+                float: Some(&|bounds| interval::log(bounds.left(), bounds.right())),
                 int: None
             }, data_property.num_columns()?)?;
 
@@ -597,6 +652,12 @@ impl Component for proto::Log {
 }
 
 
+/// Modulo is the mathematical (euclidean) modulo, so results share the sign of the divisor.
+///
+/// This differs from the remainder (`%` in Rust, `fmod` in C), which shares the sign of the dividend:
+/// `-90.0 mod 360.0` is `270.0`, while the remainder is `-90.0`.
+/// Since divisors must be positive, the output lies in `[0, divisor)` regardless of the bounds of the dividend,
+/// which makes the float modulo suitable for cyclic features like angles.
 impl Component for proto::Modulo {
     fn propagate_property(
         &self,
@@ -628,19 +689,18 @@ impl Component for proto::Modulo {
                     return Err("divisor must be greater than zero".into())
                 }
 
-                left_property.nature = propagate_binary_nature(
-                    &left_property, &right_property,
-                    &BinaryOperators {
-                        float: Some(Box::new(|l, r| Ok(l.rem_euclid(*r)))),
-                        int: None,
-                        bool: None,
-                        str: None,
-                    },
-                    &OptimizeBinaryOperators {
-                        // TODO: this could be tighter
-                        float: Some(&|bounds| Ok((Some(0.), *bounds.right_upper))),
-                        int: None
-                    }, left_property.num_columns()?)?;
+                // the output is bounded by the divisor, even when the dividend is unbounded
+                // TODO: this could be tighter
+                let num_columns = left_property.num_columns()?;
+                left_property.nature = Some(Nature::Continuous(NatureContinuous {
+                    lower: Vector1DNull::Float(vec![Some(0.); num_columns as usize]),
+                    upper: Vector1DNull::Float(broadcast(&right_property.upper_float_option()?, num_columns)?),
+                }));
+
+                // infinite dividends have no modulo
+                if left_property.lower_float().is_err() || left_property.upper_float().is_err() {
+                    left_property.nullity = true;
+                }
             },
             (DataType::Int, DataType::Int) => {
                 if !right_property.lower_int()?.iter().all(|v| v > &0) {
@@ -659,7 +719,7 @@ impl Component for proto::Modulo {
                         int: Some(&|bounds| Ok((Some(0), bounds.right_upper.map(|v| v - 1)))),
                     }, left_property.num_columns()?)?;
             },
-            _ => return Err("arguments for power must be numeric and homogeneously typed".into())
+            _ => return Err("arguments for modulo must be numeric and homogeneously typed".into())
         };
 
         left_property.is_not_empty = left_property.is_not_empty && right_property.is_not_empty;
@@ -700,41 +760,7 @@ impl Component for proto::Multiply {
             return Err("left and right arguments must share the same data types".into())
         }
 
-        /// compute minimum and maximum of b * e when a <= b <= c and d <= e <= f
-        fn optimize<T: PartialOrd + Mul<Output=T> + Zero + Copy>(
-            a: T, c: T, d: T, f: T
-        ) -> Result<(Option<T>, Option<T>)> {
-            let corners = vec![a * d, a * f, c * d, c * f];
-            Ok((
-                corners.iter().min_by(|x, y|
-                    x.partial_cmp(y).unwrap_or(Ordering::Equal)).cloned(),
-                corners.iter().max_by(|x, y|
-                    x.partial_cmp(y).unwrap_or(Ordering::Equal)).cloned()
-            ))
-        }
-
-        fn optimize_wrapper<T: PartialOrd + Mul<Output=T> + Zero + Copy>(
-            bounds: BinaryBounds<T>
-        ) -> Result<(Option<T>, Option<T>)> {
-            let a = match bounds.left_lower {
-                Some(v) => *v,
-                None => return Ok((None, None))
-            };
-            let c = match bounds.left_upper {
-                Some(v) => *v,
-                None => return Ok((None, None))
-            };
-            let d = match bounds.right_lower {
-                Some(v) => *v,
-                None => return Ok((None, None))
-            };
-            let f = match bounds.right_upper {
-                Some(v) => *v,
-                None => return Ok((None, None))
-            };
-            optimize(a, c, d, f)
-        }
-
+        let noised = left_property.noised_with(&right_property);
         Ok(ValueProperties::Array(ArrayProperties {
             nullity: left_property.nullity || right_property.nullity,
             releasable: left_property.releasable && right_property.releasable,
@@ -751,8 +777,8 @@ impl Component for proto::Multiply {
                 str: None,
                 bool: None,
             }, &OptimizeBinaryOperators {
-                float: Some(&optimize_wrapper),
-                int: Some(&optimize_wrapper),
+                float: Some(&|bounds| interval::mul(bounds.left(), bounds.right())),
+                int: Some(&|bounds| interval::mul(bounds.left(), bounds.right())),
             }, num_columns)?,
             // checks to ensure this is correct are made in propagate_binary_shape
             c_stability: left_property.c_stability
@@ -769,7 +795,12 @@ impl Component for proto::Multiply {
                 .max(right_property.dimensionality),
             naturally_ordered: true,
             // checks are made within propagate_binary_shape that sampling proportion is equal and permissible
-            sample_proportion: left_property.sample_proportion
+            sample_proportion: left_property.sample_proportion,
+            monotone: false,
+            data_derived: left_property.data_derived || right_property.data_derived,
+            num_records_lower_bound: left_property.num_records_lower_bound
+                .max(right_property.num_records_lower_bound),
+            noised
         }).into())
     }
 }
@@ -778,29 +809,47 @@ impl Component for proto::Multiply {
 impl Component for proto::Negate {
     fn propagate_property(
         &self,
-        _privacy_definition: &Option<proto::PrivacyDefinition>,
-        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        privacy_definition: &Option<proto::PrivacyDefinition>,
+        public_arguments: IndexMap<base::IndexKey, &Value>,
         properties: base::NodeProperties,
-        _node_id: u32
+        node_id: u32
     ) -> Result<Warnable<ValueProperties>> {
         let mut data_property: ArrayProperties = properties.get(&IndexKey::from("data"))
             .ok_or("data: missing")?.array()
             .map_err(prepend("data:"))?.clone();
 
+        // numbers are arithmetically negated
+        if data_property.data_type == DataType::Float || data_property.data_type == DataType::Int {
+            return proto::Negative {}.propagate_property(privacy_definition, public_arguments, properties, node_id)
+        }
+
         if !data_property.releasable {
             data_property.assert_is_not_aggregated()?;
         }
 
-        data_property.nature = propagate_unary_nature(
-            &data_property,
-            &UnaryOperators {
-                float: None,
-                int: None,
-                str: None,
-                bool: Some(Box::new(|v| Ok(!*v))),
-            }, &OptimizeUnaryOperators { float: None, int: None },
-            data_property.num_columns()?)?;
+        let num_columns = data_property.num_columns()?;
+
+        // nullity is preserved, because the negation of a null is null
+        data_property.nature = match data_property.data_type {
+            // logical complement
+            DataType::Bool => Some(propagate_unary_nature(
+                &data_property,
+                &UnaryOperators {
+                    float: None,
+                    int: None,
+                    str: None,
+                    bool: Some(Box::new(|v| Ok(!*v))),
+                }, &OptimizeUnaryOperators { float: None, int: None },
+                num_columns)?
+                .unwrap_or_else(|| Nature::Categorical(NatureCategorical {
+                    categories: Jagged::Bool((0..num_columns).map(|_| vec![false, true]).collect())
+                }))),
+            DataType::Str => return Err("data: atomic type may not be string".into()),
+            _ => return Err("data: atomic type must be known".into()),
+        };
 
+        // the transformation may not preserve the order of values
+        data_property.monotone = false;
         Ok(ValueProperties::Array(data_property).into())
     }
 }
@@ -825,17 +874,18 @@ impl Component for proto::Negative {
             &data_property,
             &UnaryOperators {
                 float: Some(Box::new(|v| Ok(-*v))),
-                int: Some(Box::new(|v| Ok(-*v))),
+                int: Some(Box::new(|v| v.checked_neg()
+                    .ok_or_else(|| Error::from("negation may result in overflow")))),
                 bool: None,
                 str: None,
             },
             &OptimizeUnaryOperators {
-                float: Some(Box::new(|bounds|
-                    Ok((bounds.upper.map(|v| -v), bounds.lower.map(|v| -v))))),
-                int: Some(Box::new(|bounds|
-                    Ok((bounds.upper.map(|v| -v), bounds.lower.map(|v| -v))))),
+                float: Some(Box::new(|bounds| interval::neg(bounds.interval()))),
+                int: Some(Box::new(|bounds| interval::neg(bounds.interval()))),
             }, data_property.num_columns()?)?;
 
+        // the transformation may not preserve the order of values
+        data_property.monotone = false;
         Ok(ValueProperties::Array(data_property).into())
     }
 }
@@ -871,6 +921,8 @@ impl Component for proto::Or {
         }
 
         left_property.releasable = left_property.releasable && right_property.releasable;
+        // a true operand determines the disjunction, even when the other operand is null
+        left_property.nullity = propagate_kleene_nullity(&left_property, &right_property, kleene_or);
         left_property.nature = propagate_binary_nature(
             &left_property, &right_property,
             &BinaryOperators {
@@ -882,7 +934,7 @@ impl Component for proto::Or {
             num_columns)?;
         // checks to ensure this is correct are made in propagate_binary_shape
         left_property.c_stability = left_property.c_stability
-            .max(left_property.c_stability);
+            .max(right_property.c_stability);
         left_property.num_columns = Some(num_columns);
         left_property.num_records = num_records;
 
@@ -921,6 +973,21 @@ impl Component for proto::Power {
             radical_property.assert_is_not_sampled()?;
         }
 
+        // a non-integer power is only defined for non-negative bases, and always results in floats
+        let fractional = !is_integral(&radical_property)?;
+        if fractional {
+            if !data_property.lower_float().map_err(prepend("data:"))?.iter().all(|min| min >= &0.) {
+                return Err("data: a non-integer power requires non-negative inputs, but data may be negative".into())
+            }
+            if data_property.data_type == DataType::Int {
+                data_property.nature = Some(Nature::Continuous(NatureContinuous {
+                    lower: Vector1DNull::Float(data_property.lower_float_option()?),
+                    upper: Vector1DNull::Float(data_property.upper_float_option()?),
+                }));
+                data_property.data_type = DataType::Float;
+            }
+        }
+
         match (data_property.data_type.clone(), radical_property.data_type.clone()) {
             (DataType::Float, DataType::Float) => {
 
@@ -932,9 +999,8 @@ impl Component for proto::Power {
                         bool: None,
                         str: None,
                     },
-                    // TODO: derive bounds
                     &OptimizeBinaryOperators {
-                        float: Some(&|_bounds| Ok((None, None))),
+                        float: Some(&|bounds| interval::pow(bounds.left(), bounds.right())),
                         int: None
                     }, data_property.num_columns()?)?;
             },
@@ -952,23 +1018,48 @@ impl Component for proto::Power {
                         bool: None,
                         str: None,
                     },
-                    // TODO: derive bounds and throw error if potential overflow
                     &OptimizeBinaryOperators {
                         float: None,
-                        int: Some(&|_bounds| Ok((None, None))),
+                        int: Some(&|bounds| interval::pow(bounds.left(), bounds.right())),
                     }, data_property.num_columns()?)?;
             },
             _ => return Err("arguments for power must be numeric and homogeneously typed".into())
         }
 
+        // powers of non-negative bases, like square roots, are non-negative
+        if fractional {
+            if let Some(Nature::Continuous(NatureContinuous { lower: Vector1DNull::Float(lower), .. })) = &mut data_property.nature {
+                lower.iter_mut().flatten().for_each(|lower| *lower = lower.max(0.));
+            }
+        }
+
         data_property.is_not_empty = data_property.is_not_empty && radical_property.is_not_empty;
         data_property.dimensionality = data_property.dimensionality
             .max(radical_property.dimensionality);
         data_property.group_id = propagate_binary_group_id(&data_property, &radical_property)?;
+        // the transformation may not preserve the order of values
+        data_property.monotone = false;
         Ok(ValueProperties::Array(data_property).into())
     }
 }
 
+/// True if every exponent is known to be an integer.
+///
+/// Float exponents are only known to be integers when they are public constants without a fractional part.
+fn is_integral(radical_property: &ArrayProperties) -> Result<bool> {
+    Ok(match radical_property.data_type {
+        DataType::Int => true,
+        DataType::Float => match (radical_property.lower_float_option(), radical_property.upper_float_option()) {
+            (Ok(lower), Ok(upper)) => lower.iter().zip(upper.iter())
+                .all(|(lower, upper)| match (lower, upper) {
+                    (Some(lower), Some(upper)) => lower == upper && lower.fract() == 0.,
+                    _ => false
+                }),
+            _ => false
+        },
+        _ => return Err("radical: must be numeric".into())
+    })
+}
 
 impl Component for proto::RowMax {
     fn propagate_property(
@@ -994,11 +1085,18 @@ impl Component for proto::RowMax {
             right_property.assert_is_not_sampled()?;
         }
 
+        // the maximum is taken between elements of left and right, so neither may be empty
+        left_property.assert_minimum_columns("RowMax", 1)
+            .map_err(prepend("left:"))?;
+        right_property.assert_minimum_columns("RowMax", 1)
+            .map_err(prepend("right:"))?;
+
         let (num_columns, num_records) = propagate_binary_shape(&left_property, &right_property)?;
         if left_property.data_type != right_property.data_type {
             return Err("left and right arguments must share the same data types".into())
         }
 
+        let noised = left_property.noised_with(&right_property);
         Ok(ValueProperties::Array(ArrayProperties {
             nullity: left_property.nullity || right_property.nullity,
             releasable: left_property.releasable && right_property.releasable,
@@ -1050,7 +1148,12 @@ impl Component for proto::RowMax {
                 .max(right_property.dimensionality),
             naturally_ordered: true,
             // checks are made within propagate_binary_shape that sampling proportion is equal and permissible
-            sample_proportion: left_property.sample_proportion
+            sample_proportion: left_property.sample_proportion,
+            monotone: false,
+            data_derived: left_property.data_derived || right_property.data_derived,
+            num_records_lower_bound: left_property.num_records_lower_bound
+                .max(right_property.num_records_lower_bound),
+            noised
         }).into())
     }
 }
@@ -1079,11 +1182,18 @@ impl Component for proto::RowMin {
             right_property.assert_is_not_sampled()?;
         }
 
+        // the minimum is taken between elements of left and right, so neither may be empty
+        left_property.assert_minimum_columns("RowMin", 1)
+            .map_err(prepend("left:"))?;
+        right_property.assert_minimum_columns("RowMin", 1)
+            .map_err(prepend("right:"))?;
+
         let (num_columns, num_records) = propagate_binary_shape(&left_property, &right_property)?;
         if left_property.data_type != right_property.data_type {
             return Err("left and right arguments must share the same data types".into())
         }
 
+        let noised = left_property.noised_with(&right_property);
         Ok(ValueProperties::Array(ArrayProperties {
             nullity: left_property.nullity || right_property.nullity,
             releasable: left_property.releasable && right_property.releasable,
@@ -1135,7 +1245,12 @@ impl Component for proto::RowMin {
                 .max(right_property.dimensionality),
             naturally_ordered: true,
             // checks are made within propagate_binary_shape that sampling proportion is equal and permissible
-            sample_proportion: left_property.sample_proportion
+            sample_proportion: left_property.sample_proportion,
+            monotone: false,
+            data_derived: left_property.data_derived || right_property.data_derived,
+            num_records_lower_bound: left_property.num_records_lower_bound
+                .max(right_property.num_records_lower_bound),
+            noised
         }).into())
     }
 }
@@ -1169,6 +1284,7 @@ impl Component for proto::Subtract {
             return Err("left and right arguments must share the same data types".into())
         }
 
+        let noised = left_property.noised_with(&right_property);
         Ok(ValueProperties::Array(ArrayProperties {
             nullity: left_property.nullity || right_property.nullity,
             releasable: left_property.releasable && right_property.releasable,
@@ -1180,23 +1296,8 @@ impl Component for proto::Subtract {
                 str: None,
                 bool: None,
             }, &OptimizeBinaryOperators {
-                float: Some(&|bounds| Ok((
-                    bounds.left_lower.and_then(|lmin| bounds.right_lower.and_then(|rmin|
-                        Some(lmin - rmin))),
-                    bounds.left_upper.and_then(|lmax| bounds.right_upper.and_then(|rmax|
-                        Some(lmax - rmax))),
-                ))),
-                int: Some(&|bounds| Ok((
-                    match (bounds.left_lower, bounds.right_lower) {
-                        (Some(lmin), Some(rmin)) => Some(lmin.checked_sub(rmin)
-                            .ok_or_else(|| Error::from("subtraction may result in underflow or overflow"))?),
-                        _ => None
-                    },
-                    match (bounds.left_upper, bounds.right_upper) {
-                        (Some(lmax), Some(rmax)) => Some(lmax.checked_sub(rmax)
-                            .ok_or_else(|| Error::from("subtraction may result in underflow or overflow"))?),
-                        _ => None
-                    })))
+                float: Some(&|bounds| interval::sub(bounds.left(), bounds.right())),
+                int: Some(&|bounds| interval::sub(bounds.left(), bounds.right())),
             }, num_columns)?,
             // checks to ensure this is correct are made in propagate_binary_shape
             c_stability: left_property.c_stability
@@ -1213,7 +1314,12 @@ impl Component for proto::Subtract {
                 .max(right_property.dimensionality),
             naturally_ordered: true,
             // checks are made within propagate_binary_shape that sampling proportion is equal and permissible
-            sample_proportion: left_property.sample_proportion
+            sample_proportion: left_property.sample_proportion,
+            monotone: false,
+            data_derived: left_property.data_derived || right_property.data_derived,
+            num_records_lower_bound: left_property.num_records_lower_bound
+                .max(right_property.num_records_lower_bound),
+            noised
         }).into())
     }
 }
@@ -1229,6 +1335,11 @@ pub struct UnaryBounds<'a, T> {
     pub lower: &'a Option<T>,
     pub upper: &'a Option<T>,
 }
+impl<'a, T: Copy> UnaryBounds<'a, T> {
+    pub fn interval(&self) -> Interval<T> {
+        (*self.lower, *self.upper)
+    }
+}
 
 type UnaryOptimizer<T> = Option<Box<dyn Fn(UnaryBounds<T>) -> Result<(Option<T>, Option<T>)>>>;
 pub struct OptimizeUnaryOperators {
@@ -1249,6 +1360,14 @@ pub struct BinaryBounds<'a, T> {
     pub right_lower: &'a Option<T>,
     pub right_upper: &'a Option<T>,
 }
+impl<'a, T: Copy> BinaryBounds<'a, T> {
+    pub fn left(&self) -> Interval<T> {
+        (*self.left_lower, *self.left_upper)
+    }
+    pub fn right(&self) -> Interval<T> {
+        (*self.right_lower, *self.right_upper)
+    }
+}
 
 type BinaryOptimizer<'a, T> = Option<&'a dyn Fn(BinaryBounds<T>) -> Result<(Option<T>, Option<T>)>>;
 pub struct OptimizeBinaryOperators<'a> {
@@ -1280,7 +1399,9 @@ pub fn propagate_binary_shape(
     let right_is_column_broadcastable = right_num_columns == 1;
 
     if !(left_is_column_broadcastable || right_is_column_broadcastable) && left_num_columns != right_num_columns {
-        return Err("number of columns must be the same for left and right arguments, or one column must be broadcastable".into());
+        return Err(format!(
+            "number of columns must be the same for left and right arguments, or one column must be broadcastable. left has {} columns and right has {} columns",
+            left_num_columns, right_num_columns).into());
     }
 
     let output_num_columns = left_num_columns.max(right_num_columns);
@@ -1565,3 +1686,334 @@ fn broadcast<T: Clone>(data: &[T], length: i64) -> Result<Vec<T>> {
 
     Ok((0..length).map(|_| data[0].clone()).collect())
 }
+
+#[cfg(test)]
+mod test_transforms {
+    use ndarray::{arr1, arr2, Array2};
+
+    use crate::base::{Jagged, Nature};
+    use crate::components::literal::test_literal;
+
+    #[test]
+    fn test_negate_bool() {
+        let (mut analysis, literal) = test_literal::analysis_literal(
+            arr1(&[true, true]).into(), true);
+        let negated = analysis.negate(literal).build();
+
+        match analysis.properties(negated).unwrap().array().unwrap().nature.clone() {
+            Some(Nature::Categorical(nature)) => match nature.categories {
+                Jagged::Bool(categories) => assert_eq!(categories, vec![vec![false]]),
+                _ => panic!("categories must be boolean")
+            },
+            _ => panic!("nature must be categorical")
+        }
+    }
+
+    #[test]
+    fn test_negate_numeric() {
+        let (mut analysis, literal) = test_literal::analysis_literal(
+            arr2(&[[1.], [3.]]).into_dyn().into(), true);
+        let negated = analysis.negate(literal).build();
+
+        let negated_property = analysis.properties(negated).unwrap().array().unwrap().clone();
+        assert_eq!(negated_property.lower_float().unwrap(), vec![-3.]);
+        assert_eq!(negated_property.upper_float().unwrap(), vec![-1.]);
+    }
+
+    #[test]
+    fn test_negate_string() {
+        let (mut analysis, literal) = test_literal::analysis_literal(
+            arr1(&["a".to_string()]).into(), true);
+        let negated = analysis.negate(literal).build();
+        assert!(analysis.properties(negated).is_err());
+    }
+
+    #[test]
+    fn test_subtract_scalar() {
+        let (mut analysis, matrix) = test_literal::analysis_literal(
+            arr2(&[[1., 2., 3.], [4., 5., 6.]]).into_dyn().into(), true);
+        let scalar = analysis.literal().value(1.0.into()).value_public(true).build();
+        let difference = analysis.subtract(matrix, scalar).build();
+
+        let difference_property = analysis.properties(difference).unwrap().array().unwrap().clone();
+        assert_eq!(difference_property.num_columns, Some(3));
+        assert_eq!(difference_property.num_records, Some(2));
+        assert_eq!(difference_property.lower_float().unwrap(), vec![0., 1., 2.]);
+        assert_eq!(difference_property.upper_float().unwrap(), vec![3., 4., 5.]);
+    }
+
+    #[test]
+    fn test_subtract_row_vector() {
+        let (mut analysis, matrix) = test_literal::analysis_literal(
+            arr2(&[[1., 2., 3.], [4., 5., 6.]]).into_dyn().into(), true);
+        let row = analysis.literal()
+            .value(arr2(&[[1., 2., 3.]]).into_dyn().into())
+            .value_public(true).build();
+        let difference = analysis.subtract(matrix, row).build();
+
+        let difference_property = analysis.properties(difference).unwrap().array().unwrap().clone();
+        assert_eq!(difference_property.num_columns, Some(3));
+        assert_eq!(difference_property.num_records, Some(2));
+        assert_eq!(difference_property.lower_float().unwrap(), vec![0., 0., 0.]);
+        assert_eq!(difference_property.upper_float().unwrap(), vec![3., 3., 3.]);
+    }
+
+    #[test]
+    fn test_subtract_incompatible_columns() {
+        let (mut analysis, matrix) = test_literal::analysis_literal(
+            arr2(&[[1., 2., 3.], [4., 5., 6.]]).into_dyn().into(), true);
+        let other = analysis.literal()
+            .value(arr2(&[[1., 2.], [3., 4.]]).into_dyn().into())
+            .value_public(true).build();
+        let difference = analysis.subtract(matrix, other).build();
+
+        assert!(analysis.properties(difference).is_err());
+    }
+
+    #[test]
+    fn test_divide_zero_policy() {
+        let divide = |denominator: Vec<f64>, zero_policy: Option<&str>| {
+            let (mut analysis, numerator) = test_literal::analysis_literal(
+                arr2(&[[1.], [2.]]).into_dyn().into(), true);
+            let denominator = analysis.literal()
+                .value(Array2::from_shape_vec((denominator.len(), 1), denominator).unwrap().into_dyn().into())
+                .value_public(true).build();
+            let zero_policy = zero_policy.map(|zero_policy| analysis.literal()
+                .value(zero_policy.to_string().into())
+                .value_public(true).build());
+
+            let mut quotient = analysis.divide(numerator, denominator);
+            if let Some(zero_policy) = zero_policy {
+                quotient = quotient.zero_policy(zero_policy);
+            }
+            let quotient = quotient.build();
+            analysis.properties(quotient).map(|property| property.array().unwrap().clone())
+        };
+
+        // a denominator bounded away from zero never divides by zero
+        let quotient = divide(vec![2., 4.], Some("null")).unwrap();
+        assert!(!quotient.nullity);
+        assert_eq!(quotient.lower_float().unwrap(), vec![0.25]);
+        assert_eq!(quotient.upper_float().unwrap(), vec![1.]);
+
+        // errors are raised on evaluation instead of producing nulls
+        assert!(!divide(vec![0., 4.], Some("error")).unwrap().nullity);
+        assert!(divide(vec![0., 4.], Some("null")).unwrap().nullity);
+        assert!(divide(vec![0., 4.], Some("nan")).unwrap().nullity);
+        // floats follow IEEE semantics by default
+        assert!(divide(vec![0., 4.], None).unwrap().nullity);
+        assert!(divide(vec![0., 4.], Some("skip")).is_err());
+
+        // the quotient diverges as the denominator approaches zero
+        let quotient = divide(vec![0., 4.], Some("null")).unwrap();
+        assert_eq!(quotient.lower_float_option().unwrap(), vec![Some(0.25)]);
+        assert_eq!(quotient.upper_float_option().unwrap(), vec![None]);
+    }
+
+    #[test]
+    fn test_divide_straddling_zero() {
+        let (mut analysis, numerator) = test_literal::analysis_literal(
+            arr2(&[[1.], [2.]]).into_dyn().into(), true);
+        let denominator = analysis.literal()
+            .value(arr2(&[[-1.], [4.]]).into_dyn().into())
+            .value_public(true).build();
+        let quotient = analysis.divide(numerator, denominator).build();
+
+        let quotient = analysis.properties(quotient).unwrap().array().unwrap().clone();
+        assert_eq!(quotient.lower_float_option().unwrap(), vec![None]);
+        assert_eq!(quotient.upper_float_option().unwrap(), vec![None]);
+    }
+
+    #[test]
+    fn test_divide_int_zero_policy() {
+        let (mut analysis, numerator) = test_literal::analysis_literal(
+            arr2::<i64, _>(&[[1], [2]]).into_dyn().into(), true);
+        let denominator = analysis.literal()
+            .value(arr2::<i64, _>(&[[0], [4]]).into_dyn().into())
+            .value_public(true).build();
+        let zero_policy = analysis.literal()
+            .value("null".to_string().into())
+            .value_public(true).build();
+        let quotient = analysis.divide(numerator, denominator).zero_policy(zero_policy).build();
+
+        // integers have no null to produce
+        assert!(analysis.properties(quotient).is_err());
+    }
+
+    #[test]
+    fn test_greater_than_indicator_sum() {
+        use crate::base::{SensitivitySpace, test_data, ValueProperties};
+        use crate::components::resize::test_resize;
+        use crate::components::Sensitivity;
+        use crate::proto;
+
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let threshold = analysis.literal().value(5.0.into()).value_public(true).build();
+        let exceeds = analysis.greater_than(resized, threshold).build();
+
+        let lower = analysis.literal().value(0.into()).value_public(true).build();
+        let upper = analysis.literal().value(1.into()).value_public(true).build();
+        let indicator = analysis.to_int(exceeds, lower, upper).build();
+
+        let indicator_property = analysis.properties(indicator).unwrap().array().unwrap().clone();
+        assert_eq!(indicator_property.lower_int().unwrap(), vec![0]);
+        assert_eq!(indicator_property.upper_int().unwrap(), vec![1]);
+
+        // each record contributes at most one to the number of records exceeding the threshold
+        let sensitivity = proto::Sum {}.compute_sensitivity(
+            &analysis.privacy_definition,
+            &indexmap!["data".into() => ValueProperties::Array(indicator_property)],
+            &SensitivitySpace::KNorm(1)).unwrap();
+        assert_eq!(sensitivity.ref_array().unwrap().first_int().unwrap(), 1);
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let sum = analysis.dp_sum(indicator, vec![usage])
+            .mechanism("Laplace".to_string())
+            .build();
+        assert!(analysis.properties(sum).is_ok());
+    }
+
+    #[test]
+    fn test_log_base() {
+        let (mut analysis, literal) = test_literal::analysis_literal(
+            arr2(&[[1.], [10.], [1000.]]).into_dyn().into(), true);
+        let base = analysis.literal().value(10.0.into()).value_public(true).build();
+        let log10 = analysis.log(literal).base(base).build();
+
+        let log10_property = analysis.properties(log10).unwrap().array().unwrap().clone();
+        assert_eq!(log10_property.lower_float().unwrap(), vec![0.]);
+        assert!((log10_property.upper_float().unwrap()[0] - 3.).abs() < 1e-10);
+
+        // the base defaults to the natural log
+        let ln = analysis.log(literal).build();
+        let ln_property = analysis.properties(ln).unwrap().array().unwrap().clone();
+        assert!((ln_property.upper_float().unwrap()[0] - 1000f64.ln()).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_log_positivity() {
+        let (mut analysis, literal) = test_literal::analysis_literal(
+            arr2(&[[0.], [10.]]).into_dyn().into(), true);
+        let log = analysis.log(literal).build();
+        assert!(analysis.properties(log).unwrap_err().to_string()
+            .contains("data: log requires positive inputs, but data may be zero or less"));
+    }
+
+    #[test]
+    fn test_fractional_power() {
+        let (mut analysis, literal) = test_literal::analysis_literal(
+            arr2(&[[0.], [16.]]).into_dyn().into(), true);
+        let radical = analysis.literal().value(0.5.into()).value_public(true).build();
+        let root = analysis.power(literal, radical).build();
+
+        let root_property = analysis.properties(root).unwrap().array().unwrap().clone();
+        assert_eq!(root_property.lower_float().unwrap(), vec![0.]);
+        assert_eq!(root_property.upper_float().unwrap(), vec![4.]);
+
+        // integer data is promoted to floats
+        let (mut analysis, literal) = test_literal::analysis_literal(
+            arr2(&[[0], [16]]).into_dyn().into(), true);
+        let radical = analysis.literal().value(0.5.into()).value_public(true).build();
+        let root = analysis.power(literal, radical).build();
+
+        let root_property = analysis.properties(root).unwrap().array().unwrap().clone();
+        assert_eq!(root_property.data_type, crate::base::DataType::Float);
+        assert_eq!(root_property.upper_float().unwrap(), vec![4.]);
+    }
+
+    #[test]
+    fn test_fractional_power_negative_base() {
+        let (mut analysis, literal) = test_literal::analysis_literal(
+            arr2(&[[-1.], [16.]]).into_dyn().into(), true);
+        let radical = analysis.literal().value(0.5.into()).value_public(true).build();
+        let root = analysis.power(literal, radical).build();
+
+        assert!(analysis.properties(root).unwrap_err().to_string()
+            .contains("data: a non-integer power requires non-negative inputs, but data may be negative"));
+    }
+
+    #[test]
+    fn test_row_min_single_column() {
+        let (mut analysis, column) = test_literal::analysis_literal(
+            arr2(&[[1.], [3.]]).into_dyn().into(), true);
+        let empty = analysis.literal()
+            .value(Array2::<f64>::zeros((2, 0)).into_dyn().into())
+            .value_public(true).build();
+        let minimum = analysis.row_min(column, empty).build();
+
+        assert!(analysis.properties(minimum).unwrap_err().to_string()
+            .contains("right: RowMin requires at least 1 column(s), but the data has 0"));
+    }
+
+    #[test]
+    fn test_float_modulo() {
+        use crate::base::{DataType, test_data};
+        use crate::components::resize::test_resize;
+
+        let (mut analysis, angle) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some((-720.).into()), Some(720.0.into()));
+        let degrees = analysis.literal().value(360.0.into()).value_public(true).build();
+        let modulo = analysis.modulo(angle, degrees).build();
+
+        let properties = analysis.properties(modulo).unwrap().array().unwrap().clone();
+        assert_eq!(properties.data_type, DataType::Float);
+        assert_eq!(properties.lower_float().unwrap(), vec![0.]);
+        assert_eq!(properties.upper_float().unwrap(), vec![360.]);
+    }
+
+    #[test]
+    fn test_kleene_truth_tables() {
+        use crate::components::transforms::{kleene_and, kleene_or};
+
+        let (t, f, n) = (Some(true), Some(false), None);
+        // (left, right, and, or)
+        let truth_table = vec![
+            (t, t, t, t), (t, f, f, t), (t, n, n, t),
+            (f, t, f, t), (f, f, f, f), (f, n, f, n),
+            (n, t, n, t), (n, f, f, n), (n, n, n, n),
+        ];
+        truth_table.into_iter().for_each(|(left, right, and, or)| {
+            assert_eq!(kleene_and(left, right), and, "{:?} and {:?}", left, right);
+            assert_eq!(kleene_or(left, right), or, "{:?} or {:?}", left, right);
+        });
+    }
+
+    #[test]
+    fn test_kleene_nullity() {
+        use indexmap::IndexMap;
+        use crate::base::{IndexKey, ValueProperties};
+        use crate::components::Component;
+        use crate::proto;
+
+        let (mut analysis, nullable) = test_literal::analysis_literal(arr1(&[true, false]).into(), true);
+        let always_true = analysis.literal().value(true.into()).value_public(true).build();
+        let always_false = analysis.literal().value(false.into()).value_public(true).build();
+
+        let property = |node_id: u32, nullity: bool| {
+            let mut property = analysis.properties(node_id).unwrap().array().unwrap().clone();
+            property.nullity = nullity;
+            ValueProperties::Array(property)
+        };
+        let nullable = property(nullable, true);
+        let always_true = property(always_true, false);
+        let always_false = property(always_false, false);
+
+        let propagate = |component: &dyn Component, left: &ValueProperties, right: &ValueProperties| component
+            .propagate_property(
+                &None, IndexMap::new(),
+                indexmap![IndexKey::from("left") => left.clone(), IndexKey::from("right") => right.clone()], 0)
+            .unwrap().0.array().unwrap().nullity;
+
+        // the result is provably non-null when the other operand determines it
+        assert!(!propagate(&proto::Or {}, &nullable, &always_true));
+        assert!(!propagate(&proto::And {}, &always_false, &nullable));
+
+        // otherwise the null propagates
+        assert!(propagate(&proto::Or {}, &nullable, &always_false));
+        assert!(propagate(&proto::And {}, &always_true, &nullable));
+        assert!(propagate(&proto::And {}, &nullable, &nullable));
+    }
+}
@@ -80,6 +80,10 @@ impl Component for proto::Union {
                 },
                 naturally_ordered: false,
                 sample_proportion: None,
+                monotone: false,
+                data_derived: array_props.iter().any(|v| v.data_derived),
+                num_records_lower_bound: None,
+                noised: array_props.iter().all(|v| v.noised),
             })
         } else {
             ValueProperties::Partitions(PartitionsProperties { children: properties })
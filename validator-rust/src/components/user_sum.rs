@@ -0,0 +1,144 @@
+use indexmap::map::IndexMap;
+use ndarray::prelude::*;
+
+use crate::{base, Float, proto, Warnable};
+use crate::base::{AggregatorProperties, DataType, Nature, NatureContinuous, NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull};
+use crate::components::{Component, Sensitivity};
+use crate::components::transforms::propagate_binary_shape;
+use crate::errors::*;
+use crate::utilities::{get_array_property, prepend};
+
+impl Component for proto::UserSum {
+    fn propagate_property(
+        &self,
+        _privacy_definition: &Option<proto::PrivacyDefinition>,
+        _public_arguments: IndexMap<base::IndexKey, &Value>,
+        properties: base::NodeProperties,
+        node_id: u32
+    ) -> Result<Warnable<ValueProperties>> {
+        let mut data_property = get_array_property(&properties, "data")?;
+        let user_id_property = get_array_property(&properties, "user_id")?;
+
+        data_property.assert_is_not_aggregated()?;
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
+        data_property.assert_non_null().map_err(prepend("data:"))?;
+        if data_property.data_type != DataType::Float {
+            return Err("data: atomic type must be float".into())
+        }
+        if data_property.num_columns()? != 1 {
+            return Err("data: must contain one column".into())
+        }
+
+        user_id_property.assert_is_not_aggregated().map_err(prepend("user_id:"))?;
+        user_id_property.assert_non_null().map_err(prepend("user_id:"))?;
+        if user_id_property.data_type == DataType::Float || user_id_property.data_type == DataType::Unknown {
+            return Err("user_id: atomic type must be int, bool or string".into())
+        }
+        if user_id_property.num_columns()? != 1 {
+            return Err("user_id: must contain one column".into())
+        }
+
+        // each user id must label a row of the data
+        propagate_binary_shape(&data_property, &user_id_property)?;
+
+        let cap = get_cap(self)?;
+
+        // save a snapshot of the state when aggregating
+        data_property.aggregator = Some(AggregatorProperties::new(
+            proto::component::Variant::UserSum(self.clone()), properties, 1));
+
+        // there are at most as many users as rows
+        data_property.nature = Some(Nature::Continuous(NatureContinuous {
+            lower: Vector1DNull::Float(vec![data_property.num_records.map(|n| -cap * n as Float)]),
+            upper: Vector1DNull::Float(vec![data_property.num_records.map(|n| cap * n as Float)]),
+        }));
+        data_property.num_records = Some(1);
+        data_property.dataset_id = Some(node_id as i64);
+
+        Ok(ValueProperties::Array(data_property).into())
+    }
+}
+
+impl Sensitivity for proto::UserSum {
+    /// Each user changes the sum by at most their capped total, no matter how many rows they contribute.
+    fn compute_sensitivity(
+        &self,
+        privacy_definition: &proto::PrivacyDefinition,
+        properties: &NodeProperties,
+        sensitivity_type: &SensitivitySpace,
+    ) -> Result<Value> {
+        match sensitivity_type {
+            SensitivitySpace::KNorm(k) => {
+                let data_property = get_array_property(properties, "data")?;
+                data_property.assert_is_not_aggregated()?;
+                data_property.assert_non_null()?;
+
+                let cap = get_cap(self)?;
+
+                use proto::privacy_definition::Neighboring;
+                let neighboring_type = Neighboring::from_i32(privacy_definition.neighboring)
+                    .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))?;
+
+                // all rows of a user are grouped before capping, so the contribution bound of the analysis does not apply
+                let sensitivity = match k {
+                    1 | 2 => match neighboring_type {
+                        Neighboring::AddRemove => cap,
+                        Neighboring::Substitute => 2. * cap
+                    },
+                    _ => return Err("KNorm sensitivity is only supported in L1 and L2 spaces".into())
+                };
+
+                Ok(arr2(&[[sensitivity]]).into_dyn().into())
+            }
+            _ => Err("UserSum sensitivity is only implemented for KNorm".into())
+        }
+    }
+}
+
+/// Retrieve the per-user cap, which must be finite and positive.
+pub fn get_cap(component: &proto::UserSum) -> Result<Float> {
+    if !component.cap.is_finite() || component.cap <= 0. {
+        return Err("cap: must be finite and positive".into())
+    }
+    Ok(component.cap)
+}
+
+#[cfg(test)]
+mod test_user_sum {
+    use crate::base::{SensitivitySpace, test_data};
+    use crate::components::impute::test_impute;
+    use crate::components::Sensitivity;
+    use crate::proto;
+    use crate::proto::privacy_definition::Neighboring;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_user_sum() {
+        let (mut analysis, imputed) = test_impute::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), Some(0.0.into()), Some(10.0.into()));
+        let user_id = analysis.literal()
+            .value(ndarray::arr1(&[1i64; 10]).into_dyn().into())
+            .value_public(true).build();
+
+        let user_sum = analysis.user_sum(imputed, user_id, 2.).build();
+        let properties = analysis.properties(user_sum).unwrap();
+        let properties = properties.array().unwrap();
+        assert!(properties.aggregator.is_some());
+        assert_eq!(properties.upper_float().unwrap()[0], 20.);
+
+        // a user contributing many rows is capped, regardless of the contribution bound of the analysis
+        let aggregator = properties.aggregator.clone().unwrap();
+        analysis.privacy_definition.max_contributions_per_user = 5;
+        let sensitivity = |privacy_definition: &proto::PrivacyDefinition| proto::UserSum { cap: 2. }
+            .compute_sensitivity(privacy_definition, &aggregator.properties, &SensitivitySpace::KNorm(1))
+            .unwrap().ref_array().unwrap().first_float().unwrap();
+        assert_eq!(sensitivity(&analysis.privacy_definition), 2.);
+
+        analysis.privacy_definition.neighboring = Neighboring::Substitute as i32;
+        assert_eq!(sensitivity(&analysis.privacy_definition), 4.);
+
+        // the cap must be positive
+        let invalid = analysis.user_sum(imputed, user_id, 0.).build();
+        assert!(analysis.properties(invalid).is_err());
+    }
+}
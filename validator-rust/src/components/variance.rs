@@ -3,12 +3,12 @@ use ndarray::prelude::*;
 
 use crate::{base, Float, proto, Warnable};
 use crate::base::{
-    AggregatorProperties, DataType, IndexKey, Nature, NatureContinuous,
+    AggregatorProperties, DataType, Nature, NatureContinuous,
     NodeProperties, SensitivitySpace, Value, ValueProperties, Vector1DNull,
 };
 use crate::components::{Component, Sensitivity};
 use crate::errors::*;
-use crate::utilities::prepend;
+use crate::utilities::{get_array_property, prepend, privacy::scale_by_contributions};
 
 impl Component for proto::Variance {
     fn propagate_property(
@@ -18,13 +18,12 @@ impl Component for proto::Variance {
         properties: base::NodeProperties,
         node_id: u32
     ) -> Result<Warnable<ValueProperties>> {
-        let mut data_property = properties.get::<IndexKey>(&"data".into())
-            .ok_or("data: missing")?.array()
-            .map_err(prepend("data:"))?.clone();
+        let mut data_property = get_array_property(&properties, "data")?;
 
         if !data_property.releasable {
             data_property.assert_is_not_aggregated()?;
         }
+        data_property.assert_is_not_noised().map_err(prepend("data:"))?;
 
         data_property.assert_is_not_empty()?;
 
@@ -55,6 +54,10 @@ impl Component for proto::Variance {
 
 impl Sensitivity for proto::Variance {
     /// Variance sensitivities [are backed by the the proofs here](https://github.com/opendp/smartnoise-core/blob/master/whitepapers/sensitivities/variance/variance.pdf)
+    ///
+    /// When n is unknown but bounded below (by a resize with minimum_rows), a resized add/remove neighbor has the same size,
+    /// and differs by a substitution. The substitute bound is then used under either neighboring definition,
+    /// evaluated at the least n of at least two, where the bound is largest.
     fn compute_sensitivity(
         &self,
         privacy_definition: &proto::PrivacyDefinition,
@@ -65,34 +68,47 @@ impl Sensitivity for proto::Variance {
         match sensitivity_type {
             SensitivitySpace::KNorm(k) => {
 
-                let data_property = properties.get::<IndexKey>(&"data".into())
-                    .ok_or("data: missing")?.array()
-                    .map_err(prepend("data:"))?.clone();
+                let data_property = get_array_property(properties, "data")?;
 
                 data_property.assert_non_null()?;
                 data_property.assert_is_not_aggregated()?;
+                data_property.assert_bounds_are_public()?;
                 let data_min = data_property.lower_float()?;
                 let data_max = data_property.upper_float()?;
-                let data_n = data_property.num_records()? as f64;
 
                 let delta_degrees_of_freedom = if self.finite_sample_correction { 1 } else { 0 } as f64;
-                let normalization = data_n - delta_degrees_of_freedom;
 
                 use proto::privacy_definition::Neighboring;
                 let neighboring_type = Neighboring::from_i32(privacy_definition.neighboring)
                     .ok_or_else(|| Error::from("neighboring definition must be either \"AddRemove\" or \"Substitute\""))?;
 
-                let scaling_constant = match k {
-                    1 | 2 => match neighboring_type {
-                        Neighboring::AddRemove => data_n / (data_n + 1.) / normalization,
-                        Neighboring::Substitute => (data_n - 1.) / data_n / normalization
-                    },
-                    _ => return Err("KNorm sensitivity is only supported in L1 and L2 spaces".into())
+                if ![1, 2].contains(k) {
+                    return Err("KNorm sensitivity is only supported in L1 and L2 spaces".into())
+                }
+
+                let scaling_constant = match data_property.num_records {
+                    Some(data_n) => {
+                        let data_n = data_n as f64;
+                        let normalization = data_n - delta_degrees_of_freedom;
+                        match neighboring_type {
+                            Neighboring::AddRemove => data_n / (data_n + 1.) / normalization,
+                            Neighboring::Substitute => (data_n - 1.) / data_n / normalization
+                        }
+                    }
+                    None => {
+                        let data_n_lower = data_property.num_records_lower_bound()? as f64;
+                        if self.finite_sample_correction && data_n_lower < 2. {
+                            return Err("minimum_rows must be at least two when applying the finite sample correction".into())
+                        }
+                        let data_n = data_n_lower.max(2.);
+                        (data_n - 1.) / data_n / (data_n - delta_degrees_of_freedom)
+                    }
                 } as Float;
+                let scaling_constant = scale_by_contributions(privacy_definition, scaling_constant)?;
 
                 let row_sensitivity = data_min.iter()
                     .zip(data_max.iter())
-                    .map(|(min, max)| ((max - min).powi(2) * scaling_constant))
+                    .map(|(min, max)| (max - min).powi(2) * scaling_constant)
                     .collect::<Vec<Float>>();
 
                 let mut array_sensitivity = Array::from(row_sensitivity).into_dyn();
@@ -100,7 +116,7 @@ impl Sensitivity for proto::Variance {
 
                 Ok(array_sensitivity.into())
             },
-            _ => Err("Variance sensitivity is only implemented for KNorm of 1".into())
+            _ => Err("Variance sensitivity is only implemented for KNorm".into())
         }
     }
 }
@@ -37,7 +37,15 @@ use crate::utilities::privacy::compute_graph_privacy_usage;
 #[doc(hidden)]
 pub mod errors {
     // Create the Error, ErrorKind, ResultExt, and Result types
-    error_chain! {}
+    error_chain! {
+        errors {
+            /// A component was dispatched to a trait method it does not implement
+            UnsupportedOperation(operation: String, component: String) {
+                description("unsupported operation for component")
+                display("unsupported operation {} for component {}", operation, component)
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -105,6 +113,21 @@ pub fn validate_analysis(
     Ok(())
 }
 
+/// Checks that static properties are met on all components, reporting every invalid component at once.
+///
+/// Unlike `validate_analysis`, validation continues past an invalid component,
+/// so that all errors in a large analysis may be fixed together.
+/// Components that depend on an invalid component are not validated, and are not reported.
+pub fn validate_graph(
+    privacy_definition: Option<proto::PrivacyDefinition>,
+    mut computation_graph: HashMap<u32, proto::Component>,
+    mut release: base::Release
+) -> Result<base::ValidationReport> {
+    Ok(utilities::propagate_properties_collecting_errors(
+        &privacy_definition,
+        &mut computation_graph,
+        &mut release)?.1)
+}
 
 /// Compute overall privacy usage of an analysis.
 ///
@@ -124,21 +147,213 @@ pub fn compute_privacy_usage(
     let privacy_usage = compute_graph_privacy_usage(
         &computation_graph, &privacy_definition, &properties, &release)?;
 
-    utilities::privacy::privacy_usage_check(&privacy_usage, None, false)?;
+    // each mechanism has already checked delta against the number of records it was run on
+    //    an analysis of public data uses no privacy, which is valid
+    if !privacy_usage.is_zero() {
+        utilities::privacy::privacy_usage_check(&privacy_usage, None, false, true)?;
+    }
 
     Ok(privacy_usage)
 }
 
 
+/// Compute the remaining privacy budget of each data source in an analysis.
+///
+/// Each source, identified by its node id, is allotted its own budget.
+/// The usages of the mechanisms downstream of a source are subtracted from the budget of that source.
+/// An error is returned if any source is overspent.
+pub fn compute_remaining_budgets(
+    privacy_definition: proto::PrivacyDefinition,
+    mut computation_graph: HashMap<u32, proto::Component>,
+    mut release: base::Release,
+    budgets: utilities::privacy::SourceBudgets,
+) -> Result<utilities::privacy::SourceBudgets> {
+
+    let properties = utilities::propagate_properties(
+        &Some(privacy_definition.clone()),
+        &mut computation_graph,
+        &mut release, None, false)?.0;
+
+    utilities::privacy::compute_remaining_budgets(
+        &computation_graph, &privacy_definition, &properties, &release, &budgets)
+}
+
+/// Compute the privacy usage and accuracy of every mechanism in an analysis, without executing it.
+///
+/// The graph is validated and expanded as in `compute_privacy_usage`,
+/// but no data is touched and no noise is sampled, so the analysis may be inspected before anything is released.
+/// Accuracies are reported at the `alpha` confidence level for mechanisms with closed-form accuracies.
+pub fn dry_run(
+    privacy_definition: proto::PrivacyDefinition,
+    mut computation_graph: HashMap<u32, proto::Component>,
+    mut release: base::Release,
+    alpha: f64
+) -> Result<base::DryRun> {
+
+    let properties = utilities::propagate_properties(
+        &Some(privacy_definition.clone()),
+        &mut computation_graph,
+        &mut release, None, false)?.0;
+
+    let mut nodes = computation_graph.iter()
+        .map(|(node_id, component)| {
+            let privacy_usages = match component.get_privacy_usage(
+                &privacy_definition,
+                release.get(node_id).and_then(|v| v.privacy_usages.as_ref()),
+                &utilities::get_input_properties(component, &properties)?)? {
+                Some(privacy_usages) => privacy_usages,
+                None => return Ok(None)
+            };
+
+            Ok(Some(base::DryRunNode {
+                node_id: *node_id,
+                privacy_usages,
+                accuracies: component.privacy_usage_to_accuracy(
+                    get_public_arguments(component, &release)?, alpha)?
+            }))
+        })
+        .collect::<Result<Vec<Option<base::DryRunNode>>>>()?
+        .into_iter().flatten()
+        .collect::<Vec<base::DryRunNode>>();
+    nodes.sort_by_key(|node| node.node_id);
+
+    let privacy_usage = compute_graph_privacy_usage(
+        &computation_graph, &privacy_definition, &properties, &release)?;
+
+    Ok(base::DryRun { nodes, privacy_usage })
+}
+
+
 /// Generate a json string with a summary/report of the Analysis and Release
 pub fn generate_report(
     privacy_definition: proto::PrivacyDefinition,
     computation_graph: HashMap<u32, proto::Component>,
-    mut release: base::Release
+    release: base::Release
 ) -> Result<String> {
+    let release_schemas = generate_release_schemas(privacy_definition, computation_graph, release)?;
+
+    match serde_json::to_string(&release_schemas) {
+        Ok(serialized) => Ok(serialized),
+        Err(_) => Err("unable to parse report into json".into())
+    }
+}
+
+/// Summarize the expected absolute error of each noised release in the Analysis and Release, and their total.
+///
+/// The errors are derived from the noise scales in the report, so designs with different budget allocations may be compared.
+pub fn generate_error_summary(
+    privacy_definition: proto::PrivacyDefinition,
+    computation_graph: HashMap<u32, proto::Component>,
+    release: base::Release
+) -> Result<utilities::json::ErrorSummary> {
+    let release_schemas = generate_release_schemas(privacy_definition, computation_graph, release)?;
+    Ok(utilities::json::summarize_error(&release_schemas))
+}
+
+/// Reconcile the releases of related statistics, so that they satisfy their known relationships.
+///
+/// When a dp sum, dp count and dp mean are released on the same data, the sum and mean are adjusted so that `mean = sum / count`.
+/// The adjustment is weighted by the variance of the noise recorded by the mechanism each statistic expands into.
+/// Only released values are used, so reconciliation is postprocessing, and consumes no privacy budget.
+pub fn reconcile_release(
+    privacy_definition: proto::PrivacyDefinition,
+    computation_graph: HashMap<u32, proto::Component>,
+    mut release: base::Release
+) -> Result<base::Release> {
+    use crate::utilities::consistency::{find_mean_relationships, reconcile_mean};
+
+    let relationships = find_mean_relationships(&computation_graph);
+    if relationships.is_empty() {
+        return Ok(release)
+    }
+
+    // omit the reconciled releases, so that their components are expanded into mechanisms
+    let mut expanded_graph = computation_graph;
+    let mut expanded_release = release.clone();
+    relationships.iter().for_each(|relationship| {
+        expanded_release.remove(&relationship.sum_id);
+        expanded_release.remove(&relationship.mean_id);
+    });
+    let privacy_definition = Some(privacy_definition);
+    let graph_properties = utilities::propagate_properties(
+        &privacy_definition,
+        &mut expanded_graph,
+        &mut expanded_release, None, false)?.0;
+
+    // variance of the noise in each column of a release
+    let noise_variances = |node_id: u32, value: &Value| -> Result<Vec<Float>> {
+        let component = expanded_graph.get(&node_id)
+            .ok_or_else(|| Error::from(format!("node {}: missing from the computation graph", node_id)))?;
+        component.summarize(
+            &privacy_definition,
+            node_id,
+            component,
+            get_public_arguments(component, &expanded_release)?,
+            utilities::get_input_properties(component, &graph_properties)?,
+            value,
+            None)?
+            .unwrap_or_default().iter()
+            .map(|summary| summary.algorithm_info.argument.get("noise_scale")
+                .and_then(|noise_scale| noise_scale.as_f64())
+                .and_then(|noise_scale| utilities::json::noise_variance(&summary.algorithm_info.mechanism, noise_scale))
+                .ok_or_else(|| Error::from(format!("node {}: the variance of the noise is unknown", node_id))))
+            .collect()
+    };
+
+    for relationship in relationships {
+        let released = |node_id: u32| release.get(&node_id)
+            .map(|node| node.value.clone())
+            .ok_or_else(|| Error::from(format!("node {}: must be released to be reconciled", node_id)));
+        let (sum_value, count_value, mean_value) = (
+            released(relationship.sum_id)?, released(relationship.count_id)?, released(relationship.mean_id)?);
+
+        let sum_variances = noise_variances(relationship.sum_id, &sum_value)?;
+        let mean_variances = noise_variances(relationship.mean_id, &mean_value)?;
+
+        let count = count_value.array()?.cast_float()?;
+        if count.len() != 1 {
+            return Err(format!("node {}: count must be a scalar to be reconciled", relationship.count_id).into())
+        }
+        let count = count[0];
+
+        let sum_array = sum_value.array()?;
+        let is_int = matches!(sum_array, base::Array::Int(_));
+        let mut sums = sum_array.cast_float()?;
+        let mut means = mean_value.array()?.cast_float()?;
+        if sums.len() != means.len() || sums.len() != sum_variances.len() || means.len() != mean_variances.len() {
+            return Err(format!(
+                "nodes {} and {}: sum and mean must have one value per column to be reconciled",
+                relationship.sum_id, relationship.mean_id).into())
+        }
+
+        sums.iter_mut().zip(means.iter_mut())
+            .zip(sum_variances.iter().zip(mean_variances.iter()))
+            .for_each(|((sum, mean), (sum_variance, mean_variance))| {
+                let (reconciled_sum, reconciled_mean) = reconcile_mean(*sum, count, *mean, *sum_variance, *mean_variance);
+                *sum = reconciled_sum;
+                *mean = reconciled_mean;
+            });
+
+        if let Some(node) = release.get_mut(&relationship.sum_id) {
+            node.value = if is_int { sums.mapv(|v| v.round() as Integer).into() } else { sums.into() };
+        }
+        if let Some(node) = release.get_mut(&relationship.mean_id) {
+            node.value = means.into();
+        }
+    }
+
+    Ok(release)
+}
 
+fn generate_release_schemas(
+    privacy_definition: proto::PrivacyDefinition,
+    computation_graph: HashMap<u32, proto::Component>,
+    mut release: base::Release
+) -> Result<Vec<utilities::json::JSONRelease>> {
+
+    let privacy_definition = Some(privacy_definition);
     let graph_properties = utilities::propagate_properties(
-        &Some(privacy_definition),
+        &privacy_definition,
         &mut computation_graph.clone(),
         &mut release, None, false)?.0;
 
@@ -174,7 +389,7 @@ pub fn generate_report(
     });
 
     // generate summaries for any component that has a release, and has summarize implemented on it
-    let release_schemas = computation_graph.iter()
+    computation_graph.iter()
         .map(|(node_id, component)| {
             let public_arguments = utilities::get_public_arguments(&component, &release)?;
             let input_properties = utilities::get_input_properties(&component, &graph_properties)?;
@@ -185,6 +400,7 @@ pub fn generate_report(
                 None => return Ok(None)
             };
             component.summarize(
+                &privacy_definition,
                 *node_id,
                 &component,
                 public_arguments,
@@ -193,14 +409,10 @@ pub fn generate_report(
                 variable_names,
             )
         })
-        .collect::<Result<Vec<Option<Vec<utilities::json::JSONRelease>>>>>()?.into_iter()
-        .filter_map(|v| v).flat_map(|v| v)
-        .collect::<Vec<utilities::json::JSONRelease>>();
-
-    match serde_json::to_string(&release_schemas) {
-        Ok(serialized) => Ok(serialized),
-        Err(_) => Err("unable to parse report into json".into())
-    }
+        .collect::<Result<Vec<Option<Vec<utilities::json::JSONRelease>>>>>()
+        .map(|releases| releases.into_iter()
+            .flatten().flatten()
+            .collect())
 }
 
 
@@ -357,9 +569,15 @@ pub fn expand_component(
         .collect::<IndexMap<IndexKey, &Value>>();
 
     if result.traversal.is_empty() {
-        let Warnable(propagated_property, propagation_warnings) = component
-            .propagate_property(&privacy_definition, public_values, properties, component_id)
-            .chain_err(|| format!("at node_id {:?}", component_id))?;
+        // if the expansion released the node publicly, infer properties directly from the public data
+        let Warnable(propagated_property, propagation_warnings) = match result.releases.get(&component_id)
+            .filter(|release_node| release_node.public) {
+            Some(release_node) => Warnable(utilities::inference::infer_property(
+                &release_node.value, result.properties.get(&component_id), component_id)?, vec![]),
+            None => component
+                .propagate_property(&privacy_definition, public_values, properties, component_id)
+                .chain_err(|| format!("at node_id {:?}", component_id))?
+        };
 
         result.warnings.extend(propagation_warnings.into_iter()
             .map(|err| err.chain_err(|| format!("at node_id {:?}", component_id))));
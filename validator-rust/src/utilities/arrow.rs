@@ -0,0 +1,198 @@
+//! Export of releases to Apache Arrow
+
+use std::sync::Arc;
+
+use ::arrow::array::{ArrayRef, Float64Array, StringArray, UInt32Array};
+use ::arrow::datatypes::{DataType, Field, Schema};
+use ::arrow::record_batch::RecordBatch;
+
+use crate::base::{IndexKey, Release, Value};
+use crate::errors::*;
+use crate::utilities::json::JSONRelease;
+
+/// Schema of a record batch of releases, with one row per released element.
+///
+/// The category is the label of the element, like the bin of a histogram count, if the release is labeled.
+/// Epsilon and delta are the privacy usage of the release the element belongs to,
+/// and are missing for releases that did not use any privacy budget, like postprocessing.
+pub fn release_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("node_id", DataType::UInt32, false),
+        Field::new("statistic", DataType::Utf8, false),
+        Field::new("variable", DataType::Utf8, true),
+        Field::new("category", DataType::Utf8, true),
+        Field::new("value", DataType::Float64, false),
+        Field::new("mechanism", DataType::Utf8, false),
+        Field::new("epsilon", DataType::Float64, true),
+        Field::new("delta", DataType::Float64, true),
+    ])
+}
+
+/// Serialize a report into an Arrow record batch.
+///
+/// Each numeric element of the release info of each JSONRelease becomes a row.
+/// Category labels are retrieved from the released value of the node,
+/// which is a dataframe with one string column of labels when the release is labeled, like those of LabelCounts.
+///
+/// # Arguments
+/// * `releases` - the JSONReleases of a report
+/// * `release` - the released values, keyed by node id
+pub fn releases_to_record_batch(releases: &[JSONRelease], release: &Release) -> Result<RecordBatch> {
+    let mut node_ids = Vec::new();
+    let mut statistics = Vec::new();
+    let mut variables = Vec::new();
+    let mut categories = Vec::new();
+    let mut values = Vec::new();
+    let mut mechanisms = Vec::new();
+    let mut epsilons = Vec::new();
+    let mut deltas = Vec::new();
+
+    for json_release in releases {
+        let mut release_values = Vec::new();
+        flatten_numeric(&json_release.release_info, &mut release_values)
+            .chain_err(|| format!("node {}: release info", json_release.node_id))?;
+
+        let labels = release.get(&json_release.node_id)
+            .map(|release_node| get_labels(&release_node.value))
+            .transpose()?.flatten();
+        if let Some(labels) = &labels {
+            if labels.len() != release_values.len() {
+                bail!("node {}: the number of labels must match the number of released values", json_release.node_id)
+            }
+        }
+
+        let variable_names = get_variable_names(&json_release.variables, release_values.len());
+        let epsilon = json_release.privacy_loss.get("epsilon").and_then(serde_json::Value::as_f64);
+        let delta = json_release.privacy_loss.get("delta").and_then(serde_json::Value::as_f64);
+
+        release_values.into_iter().enumerate().for_each(|(idx, value)| {
+            node_ids.push(json_release.node_id);
+            statistics.push(json_release.statistic.clone());
+            variables.push(variable_names.as_ref().map(|names| names[idx].clone()));
+            categories.push(labels.as_ref().map(|labels| labels[idx].clone()));
+            values.push(value);
+            mechanisms.push(json_release.algorithm_info.mechanism.clone());
+            epsilons.push(epsilon);
+            deltas.push(delta);
+        });
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(UInt32Array::from(node_ids)),
+        Arc::new(StringArray::from(statistics.iter().map(String::as_str).collect::<Vec<_>>())),
+        Arc::new(StringArray::from(variables.iter().map(Option::as_deref).collect::<Vec<_>>())),
+        Arc::new(StringArray::from(categories.iter().map(Option::as_deref).collect::<Vec<_>>())),
+        Arc::new(Float64Array::from(values)),
+        Arc::new(StringArray::from(mechanisms.iter().map(String::as_str).collect::<Vec<_>>())),
+        Arc::new(Float64Array::from(epsilons)),
+        Arc::new(Float64Array::from(deltas)),
+    ];
+
+    RecordBatch::try_new(Arc::new(release_schema()), columns)
+        .map_err(|err| Error::from(format!("failed to build record batch: {}", err)))
+}
+
+/// Collect the numbers of a (possibly nested) json release, in row-major order.
+fn flatten_numeric(value: &serde_json::Value, buffer: &mut Vec<f64>) -> Result<()> {
+    match value {
+        serde_json::Value::Number(number) => buffer.push(number.as_f64()
+            .ok_or_else(|| Error::from("numbers must be representable as floats"))?),
+        serde_json::Value::Bool(boolean) => buffer.push(if *boolean { 1. } else { 0. }),
+        serde_json::Value::Array(array) => array.iter()
+            .try_for_each(|value| flatten_numeric(value, buffer))?,
+        _ => bail!("only numeric releases may be exported")
+    };
+    Ok(())
+}
+
+/// Retrieve the labels of a labeled release, a dataframe with exactly one string column.
+fn get_labels(value: &Value) -> Result<Option<Vec<String>>> {
+    let dataframe = match value {
+        Value::Dataframe(dataframe) => dataframe,
+        _ => return Ok(None)
+    };
+
+    let mut labels = dataframe.iter()
+        .filter_map(|(_, column)| column.ref_array().ok()?.ref_string().ok());
+    let category: Option<&Value> = dataframe.get::<IndexKey>(&"category".into());
+
+    Ok(match (category, labels.next(), labels.next()) {
+        (Some(category), _, _) => Some(category.ref_array()?.ref_string()?.iter().cloned().collect()),
+        (None, Some(labels), None) => Some(labels.iter().cloned().collect()),
+        _ => None
+    })
+}
+
+/// Assign a variable name to every released value, if the names are known.
+///
+/// A single variable name applies to all values, otherwise there must be one name per value.
+fn get_variable_names(variables: &serde_json::Value, num_values: usize) -> Option<Vec<String>> {
+    match variables {
+        serde_json::Value::String(name) => Some(vec![name.clone(); num_values]),
+        serde_json::Value::Array(names) => {
+            let names = names.iter()
+                .map(|name| name.as_str().map(String::from))
+                .collect::<Option<Vec<String>>>()?;
+            match names.len() {
+                1 => Some(vec![names[0].clone(); num_values]),
+                len if len == num_values => Some(names),
+                _ => None
+            }
+        },
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod test_arrow {
+    use ::arrow::array::{Float64Array, StringArray};
+    use ndarray::arr1;
+
+    use crate::base::{ReleaseNode, Value, test_data};
+    use crate::utilities::json::{AlgorithmInfo, JSONRelease, privacy_usage_to_json};
+
+    #[test]
+    fn test_histogram_record_batch() {
+        let labels: Value = arr1(&["a".to_string(), "b".to_string(), "c".to_string()]).into_dyn().into();
+        let counts: Value = arr1(&[3i64, 1, 4]).into_dyn().into();
+        let release = vec![(3, ReleaseNode::new(Value::Dataframe(indexmap![
+            "category".into() => labels,
+            "count".into() => counts
+        ])))].into_iter().collect();
+
+        let report = vec![JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: "DPHistogram".to_string(),
+            variables: serde_json::json!("letters"),
+            release_info: serde_json::json!([3, 1, 4]),
+            privacy_loss: privacy_usage_to_json(&test_data::privacy_usage(0.5, 0.)),
+            accuracy: None,
+            relative_accuracy: None,
+            submission: 0,
+            node_id: 3,
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                name: "".to_string(),
+                cite: "".to_string(),
+                mechanism: "SimpleGeometric".to_string(),
+                argument: serde_json::json!({}),
+            },
+        }];
+
+        let batch = super::releases_to_record_batch(&report, &release).unwrap();
+        assert_eq!(batch.num_rows(), 3);
+        assert_eq!(batch.schema().as_ref(), &super::release_schema());
+
+        let categories = batch.column(3).as_any().downcast_ref::<StringArray>().unwrap();
+        let values = batch.column(4).as_any().downcast_ref::<Float64Array>().unwrap();
+        let epsilons = batch.column(6).as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!((0..3).map(|idx| categories.value(idx)).collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        assert_eq!(values.values(), &[3., 1., 4.]);
+        assert!((0..3).all(|idx| epsilons.value(idx) == 0.5));
+
+        // a release that is not numeric may not be exported
+        let mut report = report;
+        report[0].release_info = serde_json::json!(["a"]);
+        assert!(super::releases_to_record_batch(&report, &release).is_err());
+    }
+}
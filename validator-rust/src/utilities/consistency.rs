@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use indexmap::map::IndexMap;
+
+use crate::{Float, proto};
+use crate::base::IndexKey;
+
+type MeanCandidates = (Vec<u32>, Vec<u32>, Vec<u32>);
+
+/// A dp sum, dp count and dp mean of the same data, whose releases should satisfy `mean = sum / count`.
+#[derive(Debug, PartialEq)]
+pub struct MeanRelationship {
+    pub sum_id: u32,
+    pub count_id: u32,
+    pub mean_id: u32,
+}
+
+/// Identify the dp sums, dp counts and dp means that are computed on the same data.
+///
+/// A relationship is only formed when the data has exactly one of each,
+/// as there is otherwise no single release to reconcile with.
+pub fn find_mean_relationships(
+    computation_graph: &HashMap<u32, proto::Component>
+) -> Vec<MeanRelationship> {
+    use proto::component::Variant;
+
+    // for each data node, the ids of the sums, counts and means computed on it
+    let mut candidates: IndexMap<u32, MeanCandidates> = IndexMap::new();
+
+    let mut node_ids = computation_graph.keys().copied().collect::<Vec<u32>>();
+    node_ids.sort_unstable();
+    node_ids.into_iter().for_each(|node_id| {
+        let component = &computation_graph[&node_id];
+        let data_id = match component.arguments().get::<IndexKey>(&"data".into()) {
+            Some(data_id) => *data_id,
+            None => return
+        };
+        let (sums, counts, means) = candidates.entry(data_id).or_default();
+        match component.variant.as_ref() {
+            Some(Variant::DpSum(_)) => sums.push(node_id),
+            Some(Variant::DpCount(count)) if !count.distinct => counts.push(node_id),
+            Some(Variant::DpMean(_)) => means.push(node_id),
+            _ => ()
+        }
+    });
+
+    candidates.into_iter()
+        .filter_map(|(_, (sums, counts, means))| match (sums.as_slice(), counts.as_slice(), means.as_slice()) {
+            ([sum_id], [count_id], [mean_id]) => Some(MeanRelationship {
+                sum_id: *sum_id, count_id: *count_id, mean_id: *mean_id,
+            }),
+            _ => None
+        })
+        .collect()
+}
+
+/// Reconcile a released sum and mean with a released count, so that `sum = count * mean`.
+///
+/// The count is held fixed, and the sum and mean are projected onto the constraint by least squares,
+/// weighted by the inverse variance of their noise, so the noisier release moves further.
+/// A count that is not positive cannot relate the sum to the mean, so the releases are left unchanged.
+///
+/// # Return
+/// The reconciled sum and mean.
+pub fn reconcile_mean(
+    sum: Float, count: Float, mean: Float,
+    sum_variance: Float, mean_variance: Float,
+) -> (Float, Float) {
+    if count <= 0. {
+        return (sum, mean)
+    }
+
+    // minimize (count * m - sum)^2 / sum_variance + (m - mean)^2 / mean_variance over m
+    let reconciled_mean = (count * sum / sum_variance + mean / mean_variance)
+        / (count.powi(2) / sum_variance + 1. / mean_variance);
+    (count * reconciled_mean, reconciled_mean)
+}
+
+#[cfg(test)]
+mod test_consistency {
+    use ndarray::arr1;
+
+    use crate::base::{ReleaseNode, test_data};
+    use crate::components::resize::test_resize;
+    use crate::utilities::consistency::{find_mean_relationships, MeanRelationship, reconcile_mean};
+
+    #[test]
+    fn test_reconcile_mean() {
+        let (sum, mean) = reconcile_mean(110., 10., 10., 1., 1.);
+        assert!((sum - 10. * mean).abs() < 1e-10);
+
+        // an exact release does not move
+        let (sum, mean) = reconcile_mean(110., 10., 10., 1., 1e-12);
+        assert!((mean - 10.).abs() < 1e-8);
+        assert!((sum - 100.).abs() < 1e-6);
+
+        // consistent releases are unchanged
+        assert_eq!(reconcile_mean(100., 10., 10., 1., 1.), (100., 10.));
+    }
+
+    #[test]
+    fn test_reconcile_release() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let sum = analysis.dp_sum(resized, vec![usage.clone()]).mechanism("Laplace".to_string()).build();
+        let lower = analysis.literal().value(0.into()).value_public(true).build();
+        let count = analysis.dp_count(resized, lower, vec![usage.clone()]).mechanism("Laplace".to_string()).build();
+        let mean = analysis.dp_mean(resized, vec![usage]).mechanism("Laplace".to_string()).build();
+
+        assert_eq!(find_mean_relationships(&analysis.components),
+                   vec![MeanRelationship { sum_id: sum, count_id: count, mean_id: mean }]);
+
+        // noise leaves the releases inconsistent
+        analysis.release.insert(sum, ReleaseNode::new(arr1(&[62.]).into_dyn().into()));
+        analysis.release.insert(count, ReleaseNode::new(arr1(&[10.]).into_dyn().into()));
+        analysis.release.insert(mean, ReleaseNode::new(arr1(&[5.]).into_dyn().into()));
+
+        let release = crate::reconcile_release(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap();
+        let released = |node_id: u32| release[&node_id].value.ref_array().unwrap().first_float().unwrap();
+
+        assert_eq!(released(count), 10.);
+        assert!((released(mean) - released(sum) / released(count)).abs() < 1e-10);
+
+        // the sum has ten times the sensitivity of the mean, so absorbs most of the adjustment
+        assert!((released(mean) - 5.).abs() < (released(sum) - 62.).abs() / 10.);
+    }
+}
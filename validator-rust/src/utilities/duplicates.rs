@@ -0,0 +1,173 @@
+//! Detect repeated queries in a computation graph
+//!
+//! Each release of a query consumes privacy budget, even when the same query has already been released on the same data.
+//! Identical queries carry no more information than a single release, so they should share one release.
+
+use std::collections::HashMap;
+
+use indexmap::map::IndexMap;
+
+use crate::{base, proto};
+use crate::components::{is_dp_component, Mechanism};
+use crate::errors::*;
+
+/// Group the queries that are structurally identical, and computed on the same data.
+///
+/// A query is a differentially private component or a mechanism that has not yet been released.
+/// Queries are identical when they share the same variant and options, and their arguments are computed by identical subgraphs.
+/// Public values are compared by value, and data sources without arguments by node id.
+///
+/// # Returns
+/// The node ids of each group of identical queries, in ascending order. Queries without duplicates are not returned.
+pub fn find_duplicate_queries(
+    computation_graph: &HashMap<u32, proto::Component>,
+    release: &base::Release,
+) -> Result<Vec<Vec<u32>>> {
+    let mut signatures = HashMap::new();
+    let mut groups: IndexMap<String, Vec<u32>> = IndexMap::new();
+
+    let mut node_ids = computation_graph.keys().copied().collect::<Vec<u32>>();
+    node_ids.sort_unstable();
+    for node_id in node_ids {
+        let component = &computation_graph[&node_id];
+        let is_query = component.variant.as_ref().map(is_dp_component).unwrap_or(false)
+            || component.sensitivity_space().is_ok();
+        if !is_query || release.contains_key(&node_id) {
+            continue
+        }
+
+        let signature = get_signature(computation_graph, release, node_id, &mut signatures)?;
+        groups.entry(signature).or_default().push(node_id);
+    }
+
+    Ok(groups.into_iter()
+        .map(|(_, node_ids)| node_ids)
+        .filter(|node_ids| node_ids.len() > 1)
+        .collect())
+}
+
+/// Fuse each group of identical queries into the first query of the group.
+///
+/// Dependents of the duplicates read the retained query instead, and the duplicates are removed from the graph.
+/// The retained query is released if any query in its group was to be released.
+///
+/// # Returns
+/// The id of the retained query, keyed by the id of each removed duplicate.
+pub fn fuse_duplicate_queries(
+    computation_graph: &mut HashMap<u32, proto::Component>,
+    groups: &[Vec<u32>],
+) -> HashMap<u32, u32> {
+    let mut retained_ids = HashMap::new();
+
+    for group in groups {
+        let (retained_id, duplicate_ids) = match group.split_first() {
+            Some(split) => split,
+            None => continue
+        };
+        let is_omitted = duplicate_ids.iter()
+            .filter_map(|node_id| computation_graph.remove(node_id))
+            .all(|duplicate| duplicate.omit);
+        if let Some(retained) = computation_graph.get_mut(retained_id) {
+            retained.omit &= is_omitted;
+        }
+        duplicate_ids.iter().for_each(|node_id| {
+            retained_ids.insert(*node_id, *retained_id);
+        });
+    }
+
+    computation_graph.values_mut()
+        .filter_map(|component| component.arguments.as_mut())
+        .for_each(|arguments| arguments.values.iter_mut()
+            .for_each(|argument_id| if let Some(retained_id) = retained_ids.get(argument_id) {
+                *argument_id = *retained_id
+            }));
+
+    retained_ids
+}
+
+/// A description of the subgraph that computes a node, shared by nodes that always evaluate to the same value.
+fn get_signature(
+    computation_graph: &HashMap<u32, proto::Component>,
+    release: &base::Release,
+    node_id: u32,
+    signatures: &mut HashMap<u32, String>,
+) -> Result<String> {
+    if let Some(signature) = signatures.get(&node_id) {
+        return Ok(signature.clone())
+    }
+
+    let signature = match (release.get(&node_id), computation_graph.get(&node_id)) {
+        (Some(release_node), _) if release_node.public =>
+            format!("public {:?}", release_node.value),
+        (_, Some(component)) if component.arguments().is_empty() =>
+            format!("source {}", node_id),
+        (_, Some(component)) => {
+            let arguments = component.arguments().into_iter()
+                .map(|(name, argument_id)| Ok(format!("{:?}: {}",
+                    name, get_signature(computation_graph, release, argument_id, signatures)?)))
+                .collect::<Result<Vec<String>>>()?;
+            format!("{:?}({})", component.variant, arguments.join(", "))
+        }
+        (_, None) => bail!("node {}: missing from the computation graph", node_id)
+    };
+
+    signatures.insert(node_id, signature.clone());
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod test_duplicates {
+    use crate::base::test_data;
+    use crate::components::literal::test_literal;
+    use crate::components::resize::test_resize;
+    use crate::proto;
+    use crate::utilities::duplicates::{find_duplicate_queries, fuse_duplicate_queries};
+    use crate::utilities::privacy::get_epsilon;
+
+    #[test]
+    fn test_duplicate_queries() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        let source = *analysis.components.keys().min().unwrap();
+        test_literal::privatize(&mut analysis, source);
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let usage = test_data::privacy_usage(1., 0.);
+        let dp_count = |analysis: &mut crate::bindings::Analysis| {
+            let lower = analysis.literal().value(0.into()).value_public(true).build();
+            analysis.dp_count(resized, lower, vec![usage.clone()])
+                .mechanism("Laplace".to_string()).build()
+        };
+        let count = dp_count(&mut analysis);
+        let duplicate = dp_count(&mut analysis);
+        let distinct = analysis.dp_sum(resized, vec![usage.clone()]).mechanism("Laplace".to_string()).build();
+
+        assert_eq!(find_duplicate_queries(&analysis.components, &analysis.release).unwrap(),
+                   vec![vec![count, duplicate]]);
+
+        // the duplicate is flagged when validating
+        let mut computation_graph = analysis.components.clone();
+        let mut release = analysis.release.clone();
+        let (_, warnings) = crate::utilities::propagate_properties(
+            &Some(analysis.privacy_definition.clone()),
+            &mut computation_graph, &mut release, None, false).unwrap();
+        assert!(warnings.iter().flat_map(|warning| warning.iter())
+            .any(|cause| cause.to_string().contains("should share a single release")));
+
+        let epsilon = |computation_graph: &std::collections::HashMap<u32, proto::Component>| get_epsilon(
+            &crate::compute_privacy_usage(
+                analysis.privacy_definition.clone(),
+                computation_graph.clone(),
+                analysis.release.clone()).unwrap()).unwrap();
+        assert_eq!(epsilon(&analysis.components), 3.);
+
+        // once fused, the count is only charged once
+        let mut computation_graph = analysis.components.clone();
+        let groups = find_duplicate_queries(&computation_graph, &analysis.release).unwrap();
+        let retained_ids = fuse_duplicate_queries(&mut computation_graph, &groups);
+        assert_eq!(retained_ids, vec![(duplicate, count)].into_iter().collect());
+        assert!(!computation_graph.contains_key(&duplicate));
+        assert!(computation_graph.contains_key(&distinct));
+        assert_eq!(epsilon(&computation_graph), 2.);
+    }
+}
@@ -246,7 +246,11 @@ pub fn infer_property(
                     .map(|v| v.group_id.clone())
                     .unwrap_or_else(Vec::new),
                 naturally_ordered: true,
-                sample_proportion: prior_prop_arr.and_then(|p| p.sample_proportion)
+                sample_proportion: prior_prop_arr.and_then(|p| p.sample_proportion),
+                monotone: false,
+                data_derived: prior_prop_arr.map(|p| p.data_derived).unwrap_or(false),
+                num_records_lower_bound: None,
+                noised: prior_prop_arr.map(|p| p.noised).unwrap_or(false)
             }.into()
         },
         Value::Dataframe(dataframe) => match prior_property {
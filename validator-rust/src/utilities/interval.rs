@@ -0,0 +1,290 @@
+//! Interval arithmetic over continuous bounds
+//!
+//! Each interval is a (lower, upper) pair, where `None` denotes an unbounded endpoint.
+//! Operations return the tightest interval containing every result of the operation
+//! applied to members of the argument intervals.
+
+use crate::errors::*;
+
+use crate::{Float, Integer};
+use num::Zero;
+use std::cmp::Ordering;
+
+/// A (lower, upper) pair of optional bounds.
+pub type Interval<T> = (Option<T>, Option<T>);
+
+/// Numeric types that may be used as interval endpoints.
+///
+/// Operations return `Ok(None)` if the result is unbounded, and an error if the result cannot be represented.
+pub trait Endpoint: Copy + PartialOrd + Zero {
+    fn add_endpoint(self, other: Self) -> Result<Option<Self>>;
+    fn sub_endpoint(self, other: Self) -> Result<Option<Self>>;
+    fn mul_endpoint(self, other: Self) -> Result<Option<Self>>;
+    fn div_endpoint(self, other: Self) -> Result<Option<Self>>;
+    fn neg_endpoint(self) -> Result<Option<Self>>;
+    fn pow_endpoint(self, exponent: Self) -> Result<Option<Self>>;
+    /// candidate exponents at which the extrema of x^y may occur when y is within [lower, upper]
+    fn exponent_candidates(lower: Self, upper: Self) -> Vec<Self>;
+    /// true if x^y is defined for negative x and all y within [lower, upper]
+    fn negative_base_defined(lower: Self, upper: Self) -> bool;
+}
+
+/// floating-point endpoints that overflow to infinity are unbounded
+fn finite(value: Float) -> Option<Float> {
+    if value.is_finite() { Some(value) } else { None }
+}
+
+impl Endpoint for Float {
+    fn add_endpoint(self, other: Self) -> Result<Option<Self>> { Ok(finite(self + other)) }
+    fn sub_endpoint(self, other: Self) -> Result<Option<Self>> { Ok(finite(self - other)) }
+    fn mul_endpoint(self, other: Self) -> Result<Option<Self>> { Ok(finite(self * other)) }
+    fn div_endpoint(self, other: Self) -> Result<Option<Self>> { Ok(finite(self / other)) }
+    fn neg_endpoint(self) -> Result<Option<Self>> { Ok(Some(-self)) }
+    fn pow_endpoint(self, exponent: Self) -> Result<Option<Self>> { Ok(finite(self.powf(exponent))) }
+    fn exponent_candidates(lower: Self, upper: Self) -> Vec<Self> { vec![lower, upper] }
+    #[allow(clippy::float_cmp)]
+    fn negative_base_defined(lower: Self, upper: Self) -> bool { lower == upper && lower.fract() == 0. }
+}
+
+impl Endpoint for Integer {
+    fn add_endpoint(self, other: Self) -> Result<Option<Self>> {
+        self.checked_add(other).map(Some)
+            .ok_or_else(|| "addition may result in underflow or overflow".into())
+    }
+    fn sub_endpoint(self, other: Self) -> Result<Option<Self>> {
+        self.checked_sub(other).map(Some)
+            .ok_or_else(|| "subtraction may result in underflow or overflow".into())
+    }
+    fn mul_endpoint(self, other: Self) -> Result<Option<Self>> {
+        self.checked_mul(other).map(Some)
+            .ok_or_else(|| "multiplication may result in underflow or overflow".into())
+    }
+    fn div_endpoint(self, other: Self) -> Result<Option<Self>> {
+        self.checked_div(other).map(Some)
+            .ok_or_else(|| "either division by zero, or underflow or overflow".into())
+    }
+    fn neg_endpoint(self) -> Result<Option<Self>> {
+        self.checked_neg().map(Some)
+            .ok_or_else(|| "negation may result in overflow".into())
+    }
+    fn pow_endpoint(self, exponent: Self) -> Result<Option<Self>> {
+        if exponent < 0 || exponent > u32::MAX as Integer {
+            return Err("integer power must be non-negative and fit in 32 bits".into())
+        }
+        self.checked_pow(exponent as u32).map(Some)
+            .ok_or_else(|| "power may result in overflow".into())
+    }
+    fn exponent_candidates(lower: Self, upper: Self) -> Vec<Self> {
+        // the sign of a negative base alternates with the parity of the exponent,
+        //     so the exponents adjacent to each bound are considered as well
+        vec![lower, lower.saturating_add(1), upper.saturating_sub(1), upper].into_iter()
+            .filter(|exponent| lower <= *exponent && *exponent <= upper)
+            .collect()
+    }
+    fn negative_base_defined(_lower: Self, _upper: Self) -> bool { true }
+}
+
+/// Interval containing all values of `left + right`.
+pub fn add<T: Endpoint>(left: Interval<T>, right: Interval<T>) -> Result<Interval<T>> {
+    Ok((
+        apply_bounded(left.0, right.0, T::add_endpoint)?,
+        apply_bounded(left.1, right.1, T::add_endpoint)?))
+}
+
+/// Interval containing all values of `left - right`.
+pub fn sub<T: Endpoint>(left: Interval<T>, right: Interval<T>) -> Result<Interval<T>> {
+    Ok((
+        apply_bounded(left.0, right.1, T::sub_endpoint)?,
+        apply_bounded(left.1, right.0, T::sub_endpoint)?))
+}
+
+/// Interval containing all values of `left * right`.
+pub fn mul<T: Endpoint>(left: Interval<T>, right: Interval<T>) -> Result<Interval<T>> {
+    let (a, c, d, f) = match (left, right) {
+        ((Some(a), Some(c)), (Some(d), Some(f))) => (a, c, d, f),
+        _ => return Ok((None, None))
+    };
+    extrema(vec![a.mul_endpoint(d)?, a.mul_endpoint(f)?, c.mul_endpoint(d)?, c.mul_endpoint(f)?])
+}
+
+/// Interval containing all values of `left / right`.
+///
+/// Errors if the denominator may be zero, unless the denominator is bounded to one side of zero.
+pub fn div<T: Endpoint>(left: Interval<T>, right: Interval<T>) -> Result<Interval<T>> {
+    let (a, c) = match left {
+        (Some(a), Some(c)) => (a, c),
+        _ => return Ok((None, None))
+    };
+    let (d, f) = match right {
+        (Some(d), Some(f)) => (d, f),
+        (None, upper) => {
+            if upper.map(|v| v >= T::zero()).unwrap_or(true) {
+                return Err("potential division by zero".into())
+            }
+            return Ok((None, None))
+        }
+        (lower, None) => {
+            if lower.map(|v| v <= T::zero()).unwrap_or(true) {
+                return Err("potential division by zero".into())
+            }
+            return Ok((None, None))
+        }
+    };
+
+    // if denominator interval does not contain zero
+    if T::zero() < d || f < T::zero() {
+        return extrema(vec![a.div_endpoint(f)?, a.div_endpoint(d)?, c.div_endpoint(f)?, c.div_endpoint(d)?])
+    }
+
+    // if one arm of denominator is zero, the denominator is within (0, f] or [d, 0).
+    //     The quotient diverges as the denominator approaches zero, and division by zero itself is excluded
+    if d.is_zero() && !f.is_zero() {
+        if T::zero() <= a { return Ok((a.div_endpoint(f)?, None)) }
+        if c <= T::zero() { return Ok((None, c.div_endpoint(f)?)) }
+    }
+    if !d.is_zero() && f.is_zero() {
+        if T::zero() <= a { return Ok((None, a.div_endpoint(d)?)) }
+        if c <= T::zero() { return Ok((c.div_endpoint(d)?, None)) }
+    }
+
+    // the denominator straddles zero, or the numerator and denominator both straddle zero
+    Ok((None, None))
+}
+
+/// Interval containing all values of `-data`.
+pub fn neg<T: Endpoint>(data: Interval<T>) -> Result<Interval<T>> {
+    Ok((
+        data.1.map(T::neg_endpoint).transpose()?.flatten(),
+        data.0.map(T::neg_endpoint).transpose()?.flatten()))
+}
+
+/// Interval containing all values of `data ^ radical`.
+///
+/// Bounds are only derived when the base is non-negative,
+/// or when the endpoints are integers and negative bases are well-defined.
+pub fn pow<T: Endpoint>(data: Interval<T>, radical: Interval<T>) -> Result<Interval<T>> {
+    let (a, c, d, f) = match (data, radical) {
+        ((Some(a), Some(c)), (Some(d), Some(f))) => (a, c, d, f),
+        _ => return Ok((None, None))
+    };
+
+    // zero to a negative power is undefined
+    if a <= T::zero() && d < T::zero() {
+        return Ok((None, None))
+    }
+
+    // non-integer powers of negative floats are undefined
+    if a < T::zero() && !T::negative_base_defined(d, f) {
+        return Ok((None, None))
+    }
+
+    let mut bases = vec![a, c];
+    if a < T::zero() && T::zero() < c {
+        bases.push(T::zero())
+    }
+
+    let mut candidates = Vec::new();
+    for exponent in T::exponent_candidates(d, f) {
+        for base in &bases {
+            candidates.push(base.pow_endpoint(exponent)?);
+        }
+    }
+    extrema(candidates)
+}
+
+/// Interval containing all values of `log_base(data)`.
+///
+/// Bounds are only derived for strictly positive data and bases.
+pub fn log(data: Interval<Float>, base: Interval<Float>) -> Result<Interval<Float>> {
+    let ln = |(lower, upper): Interval<Float>| (
+        lower.filter(|v| *v > 0.).and_then(|v| finite(v.ln())),
+        upper.filter(|v| *v > 0.).and_then(|v| finite(v.ln())));
+    div(ln(data), ln(base))
+}
+
+/// Apply `operator` if both endpoints are bounded.
+fn apply_bounded<T: Endpoint>(
+    left: Option<T>, right: Option<T>,
+    operator: impl Fn(T, T) -> Result<Option<T>>
+) -> Result<Option<T>> {
+    match (left, right) {
+        (Some(left), Some(right)) => operator(left, right),
+        _ => Ok(None)
+    }
+}
+
+/// The smallest interval containing all candidates. If any candidate is unbounded, so is the interval.
+fn extrema<T: Endpoint>(candidates: Vec<Option<T>>) -> Result<Interval<T>> {
+    let candidates = match candidates.into_iter().collect::<Option<Vec<T>>>() {
+        Some(candidates) => candidates,
+        None => return Ok((None, None))
+    };
+    let compare = |x: &&T, y: &&T| x.partial_cmp(y).unwrap_or(Ordering::Equal);
+    Ok((
+        candidates.iter().min_by(compare).cloned(),
+        candidates.iter().max_by(compare).cloned()))
+}
+
+#[cfg(test)]
+mod test_interval {
+    use crate::utilities::interval::*;
+
+    #[test]
+    fn test_add() {
+        assert_eq!(add((Some(1.), Some(2.)), (Some(-3.), Some(4.))).unwrap(), (Some(-2.), Some(6.)));
+        assert_eq!(add((None, Some(2)), (Some(-3), Some(4))).unwrap(), (None, Some(6)));
+        assert!(add((Some(Integer::MAX), None), (Some(1), None)).is_err());
+    }
+
+    #[test]
+    fn test_sub() {
+        assert_eq!(sub((Some(1.), Some(2.)), (Some(-3.), Some(4.))).unwrap(), (Some(-3.), Some(5.)));
+        assert_eq!(sub((Some(1), Some(2)), (None, Some(4))).unwrap(), (Some(-3), None));
+    }
+
+    #[test]
+    fn test_mul() {
+        assert_eq!(mul((Some(-1.), Some(2.)), (Some(-3.), Some(4.))).unwrap(), (Some(-6.), Some(8.)));
+        assert_eq!(mul((Some(-1), Some(2)), (Some(-3), None)).unwrap(), (None, None));
+    }
+
+    #[test]
+    fn test_div() {
+        assert_eq!(div((Some(1.), Some(2.)), (Some(2.), Some(4.))).unwrap(), (Some(0.25), Some(1.)));
+        assert_eq!(div((Some(1.), Some(2.)), (Some(0.), Some(4.))).unwrap(), (Some(0.25), None));
+        assert_eq!(div((Some(-2.), Some(-1.)), (Some(0.), Some(4.))).unwrap(), (None, Some(-0.25)));
+        assert_eq!(div((Some(1.), Some(2.)), (Some(-4.), Some(0.))).unwrap(), (None, Some(-0.25)));
+        assert_eq!(div((Some(-1.), Some(2.)), (Some(0.), Some(4.))).unwrap(), (None, None));
+        // a denominator straddling zero leaves the quotient unbounded
+        assert_eq!(div((Some(1.), Some(2.)), (Some(-1.), Some(4.))).unwrap(), (None, None));
+        assert_eq!(div((Some(1.), None), (Some(2.), Some(4.))).unwrap(), (None, None));
+        assert!(div((Some(1.), Some(2.)), (None, Some(4.))).is_err());
+    }
+
+    #[test]
+    fn test_neg() {
+        assert_eq!(neg((Some(-1.), Some(2.))).unwrap(), (Some(-2.), Some(1.)));
+        assert_eq!(neg((None, Some(2))).unwrap(), (Some(-2), None));
+    }
+
+    #[test]
+    fn test_pow() {
+        assert_eq!(pow((Some(1.), Some(2.)), (Some(2.), Some(3.))).unwrap(), (Some(1.), Some(8.)));
+        assert_eq!(pow((Some(-2), Some(3)), (Some(2), Some(3))).unwrap(), (Some(-8), Some(27)));
+        assert_eq!(pow((Some(-2.), Some(3.)), (Some(2.), Some(3.))).unwrap(), (None, None));
+        assert_eq!(pow((Some(1.), None), (Some(2.), Some(3.))).unwrap(), (None, None));
+    }
+
+    #[test]
+    fn test_log() {
+        let (lower, upper) = log((Some(1.), Some(100.)), (Some(10.), Some(10.))).unwrap();
+        assert_eq!(lower, Some(0.));
+        assert!((upper.unwrap() - 2.).abs() < 1e-12);
+
+        // a base within (0, 1) reverses the bounds
+        let (lower, upper) = log((Some(1.), Some(4.)), (Some(0.5), Some(0.5))).unwrap();
+        assert!((lower.unwrap() + 2.).abs() < 1e-12);
+        assert_eq!(upper, Some(0.));
+        assert_eq!(log((Some(0.), Some(4.)), (Some(2.), Some(2.))).unwrap(), (None, None));
+    }
+}
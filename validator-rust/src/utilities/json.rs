@@ -27,6 +27,9 @@ pub struct JSONRelease {
     pub privacy_loss: Value,
     /// optional parameter. It is a combination of the accuracy and alpha value
     pub accuracy: Option<Accuracy>,
+    /// optional parameter. The accuracy as a proportion of the magnitude of the released value
+    #[serde(rename(serialize = "relativeAccuracy", deserialize = "relativeAccuracy"), default, skip_serializing_if = "Option::is_none")]
+    pub relative_accuracy: Option<Accuracy>,
     /// which release the implemented statistic is originating from. This provides a tool to keep track of overall privacyLoss.
     pub submission: u32,
     /// For advanced users. Corresponds to the node of the graph this release originated from
@@ -49,6 +52,48 @@ pub struct Accuracy {
     pub accuracy_value: f64,
     /// 100(1 - alpha)% confidence that the actual value is within the interval spanned by the accuracyValue.
     pub alpha: f64,
+    /// Whether the accuracy depends on the private data.
+    ///
+    /// A data-independent accuracy, like a laplace interval from the global sensitivity, holds for any dataset.
+    /// A data-dependent accuracy, like the accuracy of an exponential mechanism median,
+    /// bounds the error in ranks, and the width of the corresponding interval of values depends on the data.
+    #[serde(rename(serialize = "dataDependent", deserialize = "dataDependent"))]
+    pub data_dependent: bool,
+    /// Whether the accuracy holds for every element of the release at once.
+    ///
+    /// A per-element accuracy bounds the error of each element with probability 1 - alpha,
+    /// while a simultaneous accuracy bounds the error of all elements together with probability 1 - alpha.
+    #[serde(default)]
+    pub simultaneous: bool,
+}
+
+/// Confidence level used when reporting the accuracy of a release
+pub const REPORT_ALPHA: f64 = 0.05;
+
+impl Accuracy {
+    /// Whether the accuracy of releases from the given mechanism is known.
+    ///
+    /// Accuracy is only known in closed form for the laplace mechanism, so releases from other mechanisms report no accuracy.
+    pub fn is_known_for(mechanism: &str) -> bool {
+        mechanism.to_lowercase() == "laplace"
+    }
+
+    /// The accuracy as a proportion of the magnitude of the estimate.
+    ///
+    /// The relative accuracy is always data-dependent, because it is scaled by the noisy estimate.
+    /// When the interval contains zero, the actual value may be arbitrarily small relative to its error,
+    /// so no relative accuracy is reported.
+    pub fn relative_to(&self, estimate: f64) -> Option<Accuracy> {
+        if !estimate.is_finite() || estimate.abs() <= self.accuracy_value {
+            return None
+        }
+        Some(Accuracy {
+            accuracy_value: self.accuracy_value / estimate.abs(),
+            alpha: self.alpha,
+            data_dependent: true,
+            simultaneous: self.simultaneous,
+        })
+    }
 }
 
 /// Algorithm summary
@@ -65,6 +110,82 @@ pub struct AlgorithmInfo {
     pub argument: Value,
 }
 
+/// Expected error summary of a release
+///
+/// Each noised column of the report contributes its expected absolute error.
+/// Comparing totals across designs shows which allocation of the privacy budget yields the least noise.
+#[derive(Serialize, Deserialize)]
+pub struct ErrorSummary {
+    /// Sum of the expected absolute errors of every column with a known error
+    #[serde(rename(serialize = "totalExpectedError", deserialize = "totalExpectedError"))]
+    pub total_expected_error: f64,
+    /// The expected absolute error of each noised column
+    pub releases: Vec<ReleaseError>,
+}
+
+/// Expected error of one column of a release
+#[derive(Serialize, Deserialize)]
+pub struct ReleaseError {
+    #[serde(rename(serialize = "nodeID", deserialize = "nodeID"))]
+    pub node_id: u32,
+    pub variables: Value,
+    pub mechanism: String,
+    /// Expected absolute difference between the noised and non-noised value.
+    /// None when the error of the mechanism is not derivable from its noise scale, like for the exponential mechanism.
+    #[serde(rename(serialize = "expectedError", deserialize = "expectedError"))]
+    pub expected_error: Option<f64>,
+}
+
+/// Sum the expected absolute error of each release in a report, from the noise scales recorded by the mechanisms.
+///
+/// Releases without a noise scale, like those of postprocessing or of DP components that have not been expanded, are omitted.
+pub fn summarize_error(releases: &[JSONRelease]) -> ErrorSummary {
+    let releases = releases.iter()
+        .filter_map(|release| {
+            let noise_scale = release.algorithm_info.argument.get("noise_scale")?;
+            Some(ReleaseError {
+                node_id: release.node_id,
+                variables: release.variables.clone(),
+                mechanism: release.algorithm_info.mechanism.clone(),
+                expected_error: noise_scale.as_f64().and_then(|noise_scale|
+                    expected_absolute_error(&release.algorithm_info.mechanism, noise_scale)),
+            })
+        })
+        .collect::<Vec<ReleaseError>>();
+
+    ErrorSummary {
+        total_expected_error: releases.iter().filter_map(|release| release.expected_error).sum(),
+        releases,
+    }
+}
+
+/// Expected absolute value of the noise added by a mechanism with the given noise scale.
+pub fn expected_absolute_error(mechanism: &str, noise_scale: f64) -> Option<f64> {
+    match mechanism.to_lowercase().as_str() {
+        // the mean absolute deviation of a laplace distribution is its scale
+        "laplace" | "snapping" => Some(noise_scale),
+        // the mean absolute deviation of a gaussian distribution is sigma * sqrt(2 / pi)
+        "gaussian" => Some(noise_scale * (2. / std::f64::consts::PI).sqrt()),
+        // a two-sided geometric with parameter alpha = exp(-1 / scale) has mean absolute deviation 2 alpha / (1 - alpha^2)
+        "simplegeometric" => {
+            let alpha = (-1. / noise_scale).exp();
+            Some(2. * alpha / (1. - alpha.powi(2)))
+        },
+        _ => None
+    }
+}
+
+/// Variance of the noise added by a mechanism with the given noise scale.
+pub fn noise_variance(mechanism: &str, noise_scale: f64) -> Option<f64> {
+    match mechanism.to_lowercase().as_str() {
+        // a laplace distribution with scale b has variance 2 b^2
+        "laplace" | "snapping" => Some(2. * noise_scale.powi(2)),
+        "gaussian" => Some(noise_scale.powi(2)),
+        "simplegeometric" => Some(crate::components::simple_geometric_mechanism::geometric_variance(noise_scale)),
+        _ => None
+    }
+}
+
 /// converts an ArrayND (which can take any of types (float, integer, string, and Boolean) to JSON
 pub fn value_to_json(value: &base::Value) -> Result<serde_json::Value> {
     match value {
@@ -74,7 +195,21 @@ pub fn value_to_json(value: &base::Value) -> Result<serde_json::Value> {
             base::Array::Str(value) => arraynd_to_json(value),
             base::Array::Bool(value) => arraynd_to_json(value)
         },
-        _ => Err("only arrayND to json is implemented".into())
+        // each column of a jagged value may have a different length, like per-group results
+        base::Value::Jagged(jagged) => Ok(match jagged {
+            base::Jagged::Float(value) => serde_json::json!(value),
+            base::Jagged::Int(value) => serde_json::json!(value),
+            base::Jagged::Str(value) => serde_json::json!(value),
+            base::Jagged::Bool(value) => serde_json::json!(value)
+        }),
+        base::Value::Partitions(partitions) => Ok(Value::Object(partitions.iter()
+            .map(|(idx, value)| Ok((idx.to_string(), value_to_json(value)?)))
+            .collect::<Result<serde_json::Map<String, Value>>>()?)),
+        // each column of a dataframe is keyed by its name
+        base::Value::Dataframe(dataframe) => Ok(Value::Object(dataframe.iter()
+            .map(|(name, value)| Ok((name.to_string(), value_to_json(value)?)))
+            .collect::<Result<serde_json::Map<String, Value>>>()?)),
+        _ => Err("only arrayND, jagged, partitioned and dataframe values to json are implemented".into())
     }
 }
 
@@ -97,3 +232,43 @@ pub fn privacy_usage_to_json(privacy_usage: &proto::PrivacyUsage) -> serde_json:
             serde_json::json!({"name": "approximate", "epsilon": distance.epsilon, "delta": distance.delta})
     }
 }
+
+#[cfg(test)]
+mod test_json {
+    use ndarray::arr1;
+
+    use crate::base::{ReleaseNode, test_data};
+    use crate::components::resize::test_resize;
+
+    /// total expected error of a noised sum and mean over ten records in [0, 10], with the given epsilons
+    fn total_error(sum_epsilon: f64, mean_epsilon: f64) -> f64 {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let sum = analysis.sum(resized).build();
+        let noised_sum = analysis.laplace_mechanism(sum, vec![test_data::privacy_usage(sum_epsilon, 0.)]).build();
+        let mean = analysis.mean(resized).build();
+        let noised_mean = analysis.laplace_mechanism(mean, vec![test_data::privacy_usage(mean_epsilon, 0.)]).build();
+        analysis.release.insert(noised_sum, ReleaseNode::new(arr1(&[50.]).into_dyn().into()));
+        analysis.release.insert(noised_mean, ReleaseNode::new(arr1(&[5.]).into_dyn().into()));
+
+        let summary = crate::generate_error_summary(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap();
+        assert_eq!(summary.releases.len(), 2);
+        summary.total_expected_error
+    }
+
+    #[test]
+    fn test_error_summary() {
+        // the sum has sensitivity 10, and the mean has sensitivity 1
+        let sum_favored = total_error(1.5, 0.5);
+        let mean_favored = total_error(0.5, 1.5);
+        assert!((sum_favored - (10. / 1.5 + 1. / 0.5)).abs() < 1e-10);
+
+        // spending more of the same budget on the high-sensitivity query leaves less total error
+        assert!(sum_favored < mean_favored);
+    }
+}
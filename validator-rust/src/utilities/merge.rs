@@ -0,0 +1,219 @@
+//! Combine releases computed on disjoint datasets
+//!
+//! When each shard of a dataset is released independently, the privacy usage of the combined release
+//! is governed by parallel composition, so the combined usage is the largest usage of any shard.
+
+use crate::errors::*;
+use crate::utilities::json::{Accuracy, AlgorithmInfo, JSONRelease};
+
+use serde_json::Value;
+
+/// Statistics whose releases on disjoint data may be added together.
+const ADDITIVE_STATISTICS: &[&str] = &["DPCount", "DPSum", "DPHistogram"];
+
+/// Merge sets of releases, where each set was computed on a disjoint dataset.
+///
+/// Releases are matched across shards by statistic and variables.
+/// Counts, sums and histograms are added, and means are recombined as the mean of the shard means weighted by the shard sizes.
+/// Accuracies are combined by the union bound, so the combined alpha is the sum of the shard alphas.
+///
+/// Errors if a statistic does not compose across disjoint datasets, such as a median,
+/// or if a statistic is not released in every shard.
+pub fn merge_disjoint_releases(shards: &[Vec<JSONRelease>]) -> Result<Vec<JSONRelease>> {
+    let (first, rest) = shards.split_first()
+        .ok_or_else(|| Error::from("at least one set of releases must be provided"))?;
+
+    first.iter().map(|release| {
+        // collect the matching release from every shard
+        let matches = std::iter::once(Ok(release))
+            .chain(rest.iter().map(|shard| {
+                let mut candidates = shard.iter().filter(|candidate|
+                    candidate.statistic == release.statistic && candidate.variables == release.variables);
+                let candidate = candidates.next().ok_or_else(|| Error::from(format!(
+                    "{} on {} is not released in every shard", release.statistic, release.variables)))?;
+                if candidates.next().is_some() {
+                    bail!("{} on {} is released more than once in a shard", release.statistic, release.variables)
+                }
+                Ok(candidate)
+            }))
+            .collect::<Result<Vec<&JSONRelease>>>()?;
+
+        merge_releases(&matches)
+            .chain_err(|| format!("failed to merge {} on {}", release.statistic, release.variables))
+    }).collect()
+}
+
+/// Merge matching releases of one statistic, each computed on a disjoint dataset.
+fn merge_releases(releases: &[&JSONRelease]) -> Result<JSONRelease> {
+    let statistic = releases[0].statistic.as_str();
+
+    // weights applied to the release and accuracy of each shard
+    let (weights, argument) = if ADDITIVE_STATISTICS.contains(&statistic) {
+        (vec![1.; releases.len()], serde_json::json!({"shards": releases.len()}))
+    } else if statistic == "DPMean" {
+        let sizes = releases.iter()
+            .map(|release| release.algorithm_info.argument["n"].as_f64()
+                .ok_or_else(|| Error::from("means may only be merged if the number of records in each shard is known")))
+            .collect::<Result<Vec<f64>>>()?;
+        let total: f64 = sizes.iter().sum();
+        if total <= 0. {
+            bail!("means may only be merged over a positive number of records")
+        }
+        (sizes.iter().map(|size| size / total).collect(),
+         serde_json::json!({"shards": releases.len(), "n": total}))
+    } else {
+        bail!("{} does not compose across disjoint datasets", statistic)
+    };
+
+    let release_info = weighted_sum(
+        &releases.iter().map(|release| &release.release_info).collect::<Vec<_>>(), &weights)?;
+
+    // union bound: the combined error is within the (weighted) sum of accuracies,
+    // with probability at least 1 - the sum of alphas
+    let accuracy = releases.iter().zip(weights.iter())
+        .map(|(release, weight)| release.accuracy.as_ref()
+            .map(|accuracy| (accuracy.accuracy_value * weight, accuracy.alpha)))
+        .collect::<Option<Vec<(f64, f64)>>>()
+        .map(|accuracies| Accuracy {
+            accuracy_value: accuracies.iter().map(|(value, _)| value).sum(),
+            alpha: accuracies.iter().map(|(_, alpha)| alpha).sum(),
+            // the combined accuracy depends on the data if any shard accuracy does
+            data_dependent: releases.iter().any(|release| release.accuracy.as_ref()
+                .map(|accuracy| accuracy.data_dependent).unwrap_or(false)),
+            // the combined accuracy holds for every element if every shard accuracy does
+            simultaneous: releases.iter().all(|release| release.accuracy.as_ref()
+                .map(|accuracy| accuracy.simultaneous).unwrap_or(false)),
+        });
+
+    Ok(JSONRelease {
+        description: "DP release information".to_string(),
+        variables: releases[0].variables.clone(),
+        statistic: statistic.to_string(),
+        release_info,
+        privacy_loss: parallel_composition(
+            &releases.iter().map(|release| &release.privacy_loss).collect::<Vec<_>>())?,
+        accuracy,
+        relative_accuracy: None,
+        submission: releases[0].submission,
+        node_id: releases[0].node_id,
+        postprocess: releases.iter().all(|release| release.postprocess),
+        algorithm_info: AlgorithmInfo {
+            mechanism: releases[0].algorithm_info.mechanism.clone(),
+            name: "".to_string(),
+            cite: "".to_string(),
+            argument,
+        },
+    })
+}
+
+/// Privacy usage of releases on disjoint data is the maximum usage of any release.
+fn parallel_composition(privacy_losses: &[&Value]) -> Result<Value> {
+    let mut combined = privacy_losses[0].clone();
+    for field in &["epsilon", "delta"] {
+        let maximum = privacy_losses.iter()
+            .map(|loss| loss[field].as_f64()
+                .ok_or_else(|| Error::from(format!("privacy loss: {} must be numeric", field))))
+            .collect::<Result<Vec<f64>>>()?.into_iter()
+            .fold(0., f64::max);
+        combined[field] = serde_json::json!(maximum);
+    }
+    Ok(combined)
+}
+
+/// Elementwise weighted sum of numeric json values, or nested arrays of numeric values.
+///
+/// Integer values summed with unit weights remain integers.
+fn weighted_sum(values: &[&Value], weights: &[f64]) -> Result<Value> {
+    if values.iter().all(|value| value.is_array()) {
+        let arrays = values.iter().map(|value| value.as_array().unwrap()).collect::<Vec<_>>();
+        let length = arrays[0].len();
+        if arrays.iter().any(|array| array.len() != length) {
+            bail!("releases must share the same shape")
+        }
+        return Ok(Value::Array((0..length)
+            .map(|index| weighted_sum(
+                &arrays.iter().map(|array| &array[index]).collect::<Vec<_>>(), weights))
+            .collect::<Result<Vec<Value>>>()?))
+    }
+
+    #[allow(clippy::float_cmp)]
+    let unweighted = weights.iter().all(|weight| *weight == 1.);
+    if unweighted && values.iter().all(|value| value.is_i64()) {
+        return values.iter()
+            .try_fold(0i64, |sum, value| sum.checked_add(value.as_i64().unwrap()))
+            .map(|sum| serde_json::json!(sum))
+            .ok_or_else(|| "sum of releases may result in overflow".into())
+    }
+
+    Ok(serde_json::json!(values.iter().zip(weights.iter())
+        .map(|(value, weight)| value.as_f64()
+            .map(|value| value * weight)
+            .ok_or_else(|| Error::from("releases must be numeric")))
+        .collect::<Result<Vec<f64>>>()?.into_iter().sum::<f64>()))
+}
+
+#[cfg(test)]
+mod test_merge {
+    use crate::utilities::json::{Accuracy, AlgorithmInfo, JSONRelease};
+    use crate::utilities::merge::merge_disjoint_releases;
+
+    fn release(statistic: &str, value: serde_json::Value, accuracy: f64, n: i64) -> JSONRelease {
+        JSONRelease {
+            description: "DP release information".to_string(),
+            variables: serde_json::json!("age"),
+            statistic: statistic.to_string(),
+            release_info: value,
+            privacy_loss: serde_json::json!({"name": "approximate", "epsilon": 1., "delta": 0.}),
+            accuracy: Some(Accuracy { accuracy_value: accuracy, alpha: 0.025, data_dependent: false, simultaneous: false }),
+            relative_accuracy: None,
+            submission: 0,
+            node_id: 0,
+            postprocess: false,
+            algorithm_info: AlgorithmInfo {
+                mechanism: "Laplace".to_string(),
+                name: "".to_string(),
+                cite: "".to_string(),
+                argument: serde_json::json!({"n": n}),
+            },
+        }
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_merge_counts() {
+        let merged = merge_disjoint_releases(&[
+            vec![release("DPCount", serde_json::json!(10), 3., 10)],
+            vec![release("DPCount", serde_json::json!(20), 4., 20)],
+        ]).unwrap();
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].release_info, serde_json::json!(30));
+        let accuracy = merged[0].accuracy.as_ref().unwrap();
+        assert_eq!(accuracy.accuracy_value, 7.);
+        assert_eq!(accuracy.alpha, 0.05);
+        // disjoint data composes in parallel
+        assert_eq!(merged[0].privacy_loss["epsilon"], 1.);
+    }
+
+    #[test]
+    fn test_merge_means() {
+        let merged = merge_disjoint_releases(&[
+            vec![release("DPMean", serde_json::json!([1.]), 1., 10)],
+            vec![release("DPMean", serde_json::json!([4.]), 1., 30)],
+        ]).unwrap();
+        assert_eq!(merged[0].release_info, serde_json::json!([3.25]));
+    }
+
+    #[test]
+    fn test_merge_incompatible() {
+        assert!(merge_disjoint_releases(&[
+            vec![release("DPMedian", serde_json::json!(1.), 1., 10)],
+            vec![release("DPMedian", serde_json::json!(2.), 1., 10)],
+        ]).is_err());
+
+        assert!(merge_disjoint_releases(&[
+            vec![release("DPCount", serde_json::json!(10), 3., 10)],
+            vec![],
+        ]).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::hash::Hash;
 use std::iter::FromIterator;
 
@@ -7,21 +7,29 @@ use itertools::Itertools;
 use ndarray::prelude::*;
 use noisy_float::prelude::n64;
 
-use crate::{base, Float, proto, Warnable};
+use crate::{base, Float, Integer, proto, Warnable};
 use crate::base::{IndexKey, NodeProperties, Release, SensitivitySpace, Value, ValueProperties, ArrayProperties, Array};
 // import all trait implementations
 use crate::components::*;
 use crate::errors::*;
 use crate::utilities::inference::infer_property;
-use crate::utilities::privacy::spread_privacy_usage;
+use crate::utilities::privacy::{check_contribution_consistency, get_mechanism_privacy_definition, spread_privacy_usage};
 use std::ops::MulAssign;
 
 pub mod json;
+pub mod consistency;
+pub mod duplicates;
 pub mod inference;
+pub mod interval;
+pub mod merge;
 pub mod serial;
+pub mod simplify;
+pub mod typecheck;
 pub mod array;
 pub mod privacy;
 pub mod properties;
+#[cfg(feature = "arrow-export")]
+pub mod arrow;
 
 /// Retrieve the specified Value from the arguments to a component.
 pub fn take_argument(
@@ -40,6 +48,38 @@ pub fn get_argument<'a>(
         .ok_or_else(|| Error::from(name.to_string() + " must be defined"))
 }
 
+/// Retrieve the specified public Value from the arguments to a component.
+///
+/// Errors if the argument is missing or private.
+pub fn require_public_argument<'a>(
+    public_arguments: &IndexMap<base::IndexKey, &'a Value>,
+    name: &str,
+) -> Result<&'a Value> {
+    public_arguments.get::<base::IndexKey>(&name.into()).cloned()
+        .ok_or_else(|| Error::from(format!("{}: missing, must be public", name)))
+}
+
+/// Retrieve the properties of the specified argument to a component.
+pub fn get_property<'a>(
+    properties: &'a NodeProperties,
+    name: &str,
+) -> Result<&'a ValueProperties> {
+    properties.get::<base::IndexKey>(&name.into())
+        .ok_or_else(|| Error::from(format!("{}: missing", name)))
+}
+
+/// Retrieve the array properties of the specified argument to a component.
+///
+/// Errors are prefixed with the name of the argument.
+pub fn get_array_property(
+    properties: &NodeProperties,
+    name: &str,
+) -> Result<ArrayProperties> {
+    get_property(properties, name)?.array()
+        .map_err(prepend(&format!("{}:", name)))
+        .cloned()
+}
+
 /// Retrieve the Values for each of the arguments of a component from the Release.
 pub fn get_public_arguments<'a>(
     component: &proto::Component,
@@ -86,6 +126,41 @@ pub fn propagate_properties(
     release: &mut base::Release,
     properties: Option<HashMap<u32, base::ValueProperties>>,
     dynamic: bool
+) -> Result<(HashMap<u32, ValueProperties>, Vec<Error>)> {
+    // report every type error in the graph at once, rather than the first encountered while expanding
+    if !dynamic {
+        typecheck::check_types(computation_graph, release)?;
+    }
+
+    propagate_graph(privacy_definition, computation_graph, release, properties, dynamic, None)
+}
+
+/// Propagate properties over the whole graph, collecting the error of every invalid node rather than failing on the first.
+///
+/// Nodes that depend on an invalid node are skipped, since their errors would only repeat the error of the invalid node.
+/// Errors that are not specific to one node, like a cycle in the graph, still fail the propagation.
+///
+/// # Returns
+/// * `0` - Properties of every valid node
+/// * `1` - The error of each invalid node, and the warnings from the valid nodes
+pub fn propagate_properties_collecting_errors(
+    privacy_definition: &Option<proto::PrivacyDefinition>,
+    computation_graph: &mut HashMap<u32, proto::Component>,
+    release: &mut base::Release,
+) -> Result<(HashMap<u32, ValueProperties>, base::ValidationReport)> {
+    let mut errors = BTreeMap::new();
+    let (properties, warnings) = propagate_graph(
+        privacy_definition, computation_graph, release, None, false, Some(&mut errors))?;
+    Ok((properties, base::ValidationReport { errors, warnings }))
+}
+
+fn propagate_graph(
+    privacy_definition: &Option<proto::PrivacyDefinition>,
+    computation_graph: &mut HashMap<u32, proto::Component>,
+    release: &mut base::Release,
+    properties: Option<HashMap<u32, base::ValueProperties>>,
+    dynamic: bool,
+    errors: Option<&mut BTreeMap<u32, Error>>
 ) -> Result<(HashMap<u32, ValueProperties>, Vec<Error>)> {
     let mut traversal: Vec<u32> = get_traversal(&computation_graph)?;
     // extend and pop from the end of the traversal
@@ -93,13 +168,6 @@ pub fn propagate_properties(
 
     let mut properties = properties.unwrap_or_else(HashMap::new);
 
-    let mut maximum_id = computation_graph.keys().max().cloned().unwrap_or(0);
-    // println!("maximum node id: {:?}", maximum_id);
-    // let maximum_property_id = graph_properties.keys().max().cloned().unwrap_or(0);
-    // println!("maximum property id: {:?}", maximum_property_id);
-    // let maximum_release_id = release.keys().max().cloned().unwrap_or(0);
-    // println!("maximum release id: {:?}", maximum_release_id);
-
     // infer properties on public evaluations
     properties.extend(release.iter()
         .filter(|(_, release_node)| release_node.public)
@@ -109,6 +177,85 @@ pub fn propagate_properties(
                 properties.get(node_id), *node_id)?)))
         .collect::<Result<HashMap<u32, ValueProperties>>>()?);
 
+    // identical queries are each charged against the budget, although one release carries the same information
+    let duplicate_warnings = duplicates::find_duplicate_queries(computation_graph, release)?.into_iter()
+        .map(|node_ids| Error::from(format!(
+            "nodes {:?}: identical queries on the same data each consume privacy budget, and should share a single release",
+            node_ids)))
+        .collect::<Vec<Error>>();
+
+    let (properties, mut warnings) = propagate_traversal(
+        privacy_definition, computation_graph, release, properties, traversal, dynamic, errors)?;
+    warnings.extend(duplicate_warnings);
+    Ok((properties, warnings))
+}
+
+/// Resume property propagation from the properties of a previous propagation.
+///
+/// Properties of the `changed` nodes and of every node downstream of them are discarded and re-propagated,
+/// along with any nodes missing from the checkpoint. All other properties are reused from the checkpoint.
+/// The computation graph and release should be the expanded graph and release from the previous propagation.
+///
+/// # Returns
+/// * `0` - Properties for every node in the expanded graph
+/// * `1` - Warnings from the nodes that were re-propagated
+pub fn resume_propagation(
+    privacy_definition: &Option<proto::PrivacyDefinition>,
+    computation_graph: &mut HashMap<u32, proto::Component>,
+    release: &mut base::Release,
+    mut properties: HashMap<u32, base::ValueProperties>,
+    changed: &HashSet<u32>,
+    dynamic: bool
+) -> Result<(HashMap<u32, ValueProperties>, Vec<Error>)> {
+    // arguments are visited before the nodes that depend on them
+    let traversal: Vec<u32> = get_traversal(&computation_graph)?;
+
+    let mut stale = changed.clone();
+    traversal.iter().for_each(|node_id| {
+        if computation_graph.get(node_id).unwrap().arguments().values()
+            .any(|argument_node_id| stale.contains(argument_node_id)) {
+            stale.insert(*node_id);
+        }
+    });
+    properties.retain(|node_id, _| !stale.contains(node_id));
+
+    // when sensitivities are protected, only expansions insert them,
+    //    so the sensitivities of stale mechanisms are dropped to be derived again
+    if privacy_definition.as_ref().map(|v| v.protect_sensitivity).unwrap_or(true) {
+        computation_graph.iter_mut()
+            .filter(|(node_id, component)| stale.contains(node_id) && component.sensitivity_space().is_ok())
+            .for_each(|(_, component)| component.remove_argument(&"sensitivity".into()));
+    }
+
+    let mut traversal = traversal.into_iter()
+        .filter(|node_id| !properties.contains_key(node_id))
+        .collect::<Vec<u32>>();
+    // extend and pop from the end of the traversal
+    traversal.reverse();
+
+    propagate_traversal(privacy_definition, computation_graph, release, properties, traversal, dynamic, None)
+}
+
+/// Propagate properties over each node in the traversal, expanding components along the way.
+///
+/// The traversal is popped from the end.
+/// If `errors` is provided, the error of each invalid node is collected into it, and propagation continues.
+/// Otherwise errors are returned as warnings when `dynamic`, or fail the propagation.
+fn propagate_traversal(
+    privacy_definition: &Option<proto::PrivacyDefinition>,
+    computation_graph: &mut HashMap<u32, proto::Component>,
+    release: &mut base::Release,
+    mut properties: HashMap<u32, base::ValueProperties>,
+    mut traversal: Vec<u32>,
+    dynamic: bool,
+    mut errors: Option<&mut BTreeMap<u32, Error>>
+) -> Result<(HashMap<u32, ValueProperties>, Vec<Error>)> {
+    let mut maximum_id = computation_graph.keys().max().cloned().unwrap_or(0);
+    // println!("maximum node id: {:?}", maximum_id);
+    // let maximum_property_id = graph_properties.keys().max().cloned().unwrap_or(0);
+    // println!("maximum property id: {:?}", maximum_property_id);
+    // let maximum_release_id = release.keys().max().cloned().unwrap_or(0);
+    // println!("maximum release id: {:?}", maximum_release_id);
 
     let mut failed_ids = HashSet::new();
 
@@ -136,18 +283,29 @@ pub fn propagate_properties(
                 maximum_id,
             ) {
             Ok(expansion) => expansion,
-            Err(err) => if dynamic {
-                failed_ids.insert(traversal.pop().unwrap());
-                warnings.push(err);
+            Err(err) => {
+                traversal.pop();
+                failed_ids.insert(node_id);
+                match errors.as_mut() {
+                    Some(errors) => { errors.insert(node_id, err); },
+                    None if dynamic => warnings.push(err),
+                    None => return Err(err)
+                }
                 continue;
-            } else { return Err(err) }
+            }
         };
 
         maximum_id = expansion.computation_graph.keys().max().cloned()
             .unwrap_or(0).max(maximum_id);
 
+        // nodes patched by the expansion must not depend on themselves, or on the node being expanded
+        let patched_ids = expansion.computation_graph.keys().copied()
+            .chain(expansion.traversal.iter().copied())
+            .collect::<HashSet<u32>>();
+
         // patch the computation graph
         computation_graph.extend(expansion.computation_graph);
+        check_expansion_acyclic(computation_graph, &patched_ids, &expansion.traversal, node_id)?;
         properties.extend(expansion.properties);
         release.extend(expansion.releases);
         warnings.extend(expansion.warnings);
@@ -210,13 +368,21 @@ pub fn propagate_properties(
 
                 properties.insert(node_id, component_properties);
             },
-            Err(err) => if dynamic {
+            Err(err) => {
                 failed_ids.insert(node_id);
-                warnings.push(err);
-            } else { return Err(err) }
+                match errors.as_mut() {
+                    Some(errors) => { errors.insert(node_id, err); },
+                    None if dynamic => warnings.push(err),
+                    None => return Err(err)
+                }
+            }
         };
     }
     // println!("done propagating");
+
+    if let Some(privacy_definition) = privacy_definition {
+        check_contribution_consistency(computation_graph, privacy_definition, release)?;
+    }
     Ok((properties, warnings))
 }
 
@@ -279,9 +445,113 @@ pub fn get_traversal(
             return Err("Graph is cyclic.".into());
         }
     }
+
+    // nodes on a cycle never have all of their arguments visited
+    if traversal.len() < graph.len() {
+        let unvisited = graph.keys()
+            .filter(|node_id| !traversal.contains(node_id))
+            .copied().collect::<HashSet<u32>>();
+        return Err(match find_cycle(graph, &unvisited) {
+            Some(cycle) => format!("Graph is cyclic through node_ids {:?}", cycle),
+            None => "Graph is cyclic.".to_string()
+        }.into())
+    }
     Ok(traversal)
 }
 
+/// Check that an expansion patched into the computation graph did not introduce a cycle.
+///
+/// Patched nodes may not depend on themselves through their arguments.
+/// Nodes in the expansion traversal are propagated before the expanded node,
+/// so they also may not depend on the expanded node.
+fn check_expansion_acyclic(
+    computation_graph: &HashMap<u32, proto::Component>,
+    patched_ids: &HashSet<u32>,
+    traversal: &[u32],
+    node_id: u32
+) -> Result<()> {
+    if let Some(cycle) = find_cycle(computation_graph, patched_ids) {
+        bail!("expansion of node_id {} is cyclic through node_ids {:?}", node_id, cycle)
+    }
+
+    for traversal_id in traversal.iter().filter(|traversal_id| **traversal_id != node_id) {
+        if let Some(path) = find_path(computation_graph, *traversal_id, node_id) {
+            bail!("expansion of node_id {} is cyclic through node_ids {:?}", node_id, path)
+        }
+    }
+    Ok(())
+}
+
+/// Find a cycle of argument edges reachable from any of the start nodes.
+///
+/// # Returns
+/// The node ids along the cycle, or None if no cycle is reachable.
+fn find_cycle(
+    graph: &HashMap<u32, proto::Component>,
+    starts: &HashSet<u32>
+) -> Option<Vec<u32>> {
+    // nodes whose arguments have all been searched
+    let mut finished = HashSet::new();
+
+    fn search(
+        graph: &HashMap<u32, proto::Component>, node_id: u32,
+        path: &mut Vec<u32>, finished: &mut HashSet<u32>
+    ) -> Option<Vec<u32>> {
+        if let Some(position) = path.iter().position(|path_id| *path_id == node_id) {
+            return Some(path[position..].to_vec())
+        }
+        if finished.contains(&node_id) { return None }
+
+        path.push(node_id);
+        let arguments = graph.get(&node_id)
+            .map(|component| component.arguments().values().copied().collect::<Vec<u32>>())
+            .unwrap_or_else(Vec::new);
+        for argument_id in arguments {
+            if let Some(cycle) = search(graph, argument_id, path, finished) {
+                return Some(cycle)
+            }
+        }
+        path.pop();
+        finished.insert(node_id);
+        None
+    }
+
+    let mut starts = starts.iter().copied().collect::<Vec<u32>>();
+    starts.sort_unstable();
+    starts.into_iter()
+        .find_map(|start| search(graph, start, &mut Vec::new(), &mut finished))
+}
+
+/// Find a path of argument edges from the `source` node to the `target` node it depends on.
+fn find_path(
+    graph: &HashMap<u32, proto::Component>,
+    source: u32, target: u32
+) -> Option<Vec<u32>> {
+    // each visited node, mapped to the node that reached it
+    let mut reached_from = HashMap::new();
+    let mut queue = vec![source];
+
+    while let Some(node_id) = queue.pop() {
+        if node_id == target {
+            let mut path = vec![target];
+            while let Some(previous) = reached_from.get(path.last().unwrap()) {
+                path.push(*previous);
+            }
+            path.reverse();
+            return Some(path)
+        }
+        if let Some(component) = graph.get(&node_id) {
+            for argument_id in component.arguments().values() {
+                if *argument_id != source && !reached_from.contains_key(argument_id) {
+                    reached_from.insert(*argument_id, node_id);
+                    queue.push(*argument_id);
+                }
+            }
+        }
+    }
+    None
+}
+
 /// Retrieve the set of node ids in a graph that have no dependent nodes.
 ///
 /// # Arguments
@@ -509,7 +779,6 @@ pub fn prepend(text: &str) -> impl Fn(Error) -> Error + '_ {
 /// Utility function for building component expansions for dp mechanisms
 #[allow(clippy::float_cmp)]
 pub fn expand_mechanism(
-    sensitivity_type: &SensitivitySpace,
     privacy_definition: &Option<proto::PrivacyDefinition>,
     privacy_usage: &[proto::PrivacyUsage],
     component: &proto::Component,
@@ -526,6 +795,7 @@ pub fn expand_mechanism(
     let data_property: ArrayProperties = properties.get::<IndexKey>(&"data".into())
         .ok_or("data: missing")?.array()
         .map_err(prepend("data:"))?.clone();
+    data_property.assert_is_not_noised().map_err(prepend("data:"))?;
 
     // spread privacy usage over each column
     let spread_usages = spread_privacy_usage(
@@ -553,40 +823,30 @@ pub fn expand_mechanism(
             }
         }
     }
-    assign_usage!(LaplaceMechanism, GaussianMechanism, SimpleGeometricMechanism, SnappingMechanism);
+    assign_usage!(LaplaceMechanism, GaussianMechanism, MatrixMechanism, SimpleGeometricMechanism, SnappingMechanism);
 
-    if let Some(sensitivity_property) = properties.get(&IndexKey::from("sensitivity")) {
+    if let Some(id_override) = component.arguments().get::<IndexKey>(&"sensitivity_override".into()) {
+        // a user-certified sensitivity bypasses the sensitivity of the aggregator
         if privacy_definition.protect_sensitivity {
+            return Err(Error::from("sensitivity_override: may only be passed if protect_sensitivity is disabled"))
+        }
+        let override_property = properties.get::<IndexKey>(&"sensitivity_override".into())
+            .ok_or("sensitivity_override: missing")?.array()
+            .map_err(prepend("sensitivity_override:"))?;
+        check_sensitivity_properties(override_property, &data_property)
+            .map_err(prepend("sensitivity_override:"))?;
+        noise_component.insert_argument(&"sensitivity".into(), *id_override);
+    } else if let Some(sensitivity_property) = properties.get(&IndexKey::from("sensitivity")) {
+        let sensitivity_property = sensitivity_property.array()?;
+        // the expansion of a previous propagation inserts the derived sensitivity,
+        //    so a sensitivity equal to the derived sensitivity is not custom
+        if privacy_definition.protect_sensitivity && !is_derived_sensitivity(
+            sensitivity_property, &derive_mechanism_sensitivity(component, privacy_definition, &data_property)?)? {
             return Err(Error::from("custom sensitivities may only be passed if protect_sensitivity is disabled"))
         }
-        check_sensitivity_properties(sensitivity_property.array()?, &data_property)?;
+        check_sensitivity_properties(sensitivity_property, &data_property)?;
     } else {
-        let aggregator = data_property.aggregator.as_ref()
-            .ok_or_else(|| Error::from("aggregator: missing"))?;
-
-        // sensitivity scaling
-        let mut sensitivity_value = aggregator.component.compute_sensitivity(
-            privacy_definition,
-            &aggregator.properties,
-            &sensitivity_type)?;
-
-        match aggregator.lipschitz_constants.clone().array()? {
-            Array::Float(lipschitz) => {
-                if lipschitz.iter().any(|v| v != &1.) {
-                    let mut sensitivity = sensitivity_value.array()?.float()?;
-                    sensitivity.mul_assign(&lipschitz);
-                    sensitivity_value = sensitivity.into();
-                }
-            },
-            Array::Int(lipschitz) => {
-                if lipschitz.iter().any(|v| v != &1) {
-                    let mut sensitivity = sensitivity_value.array()?.int()?;
-                    sensitivity.mul_assign(&lipschitz);
-                    sensitivity_value = sensitivity.into();
-                }
-            },
-            _ => return Err(Error::from("lipschitz constants must be numeric"))
-        };
+        let sensitivity_value = derive_mechanism_sensitivity(component, privacy_definition, &data_property)?;
 
         maximum_id += 1;
         let id_sensitivity = maximum_id;
@@ -602,6 +862,228 @@ pub fn expand_mechanism(
     Ok(expansion)
 }
 
+/// Sensitivity of the aggregator feeding a mechanism, scaled by the lipschitz constants of any postprocessing.
+#[allow(clippy::float_cmp)]
+fn derive_mechanism_sensitivity(
+    component: &proto::Component,
+    privacy_definition: &proto::PrivacyDefinition,
+    data_property: &ArrayProperties,
+) -> Result<Value> {
+    let aggregator = data_property.aggregator.as_ref()
+        .ok_or_else(|| Error::from("aggregator: missing"))?;
+
+    // sensitivity scaling
+    let mut sensitivity_value = compute_mechanism_sensitivity(
+        component, privacy_definition, aggregator)?;
+
+    match aggregator.lipschitz_constants.clone().array()? {
+        Array::Float(lipschitz) => {
+            if lipschitz.iter().any(|v| v != &1.) {
+                let mut sensitivity = sensitivity_value.array()?.float()?;
+                sensitivity.mul_assign(&lipschitz);
+                sensitivity_value = sensitivity.into();
+            }
+        },
+        Array::Int(lipschitz) => {
+            if lipschitz.iter().any(|v| v != &1) {
+                let mut sensitivity = sensitivity_value.array()?.int()?;
+                sensitivity.mul_assign(&lipschitz);
+                sensitivity_value = sensitivity.into();
+            }
+        },
+        _ => return Err(Error::from("lipschitz constants must be numeric"))
+    };
+
+    Ok(sensitivity_value)
+}
+
+/// Check if the property of a public sensitivity was inferred from the derived sensitivity.
+pub(crate) fn is_derived_sensitivity(sensitivity_property: &ArrayProperties, derived: &Value) -> Result<bool> {
+    let derived_property = infer_property(derived, None, 0)?.array()?.clone();
+    Ok(sensitivity_property.lower()? == derived_property.lower()?
+        && sensitivity_property.upper()? == derived_property.upper()?)
+}
+
+/// Compute the sensitivity of an aggregator in the space required by a mechanism.
+///
+/// Errors if the aggregator cannot provide sensitivity in that space,
+/// for example when an aggregator that only supports L1 sensitivity is privatized with the gaussian mechanism.
+pub fn compute_mechanism_sensitivity(
+    mechanism: &dyn Mechanism,
+    privacy_definition: &proto::PrivacyDefinition,
+    aggregator: &base::AggregatorProperties,
+) -> Result<Value> {
+    let sensitivity_space = mechanism.sensitivity_space()?;
+    aggregator.component.compute_sensitivity(
+        privacy_definition,
+        &aggregator.properties,
+        &sensitivity_space)
+        .chain_err(|| format!(
+            "the aggregator is unable to compute sensitivity in the {:?} space required by the mechanism",
+            sensitivity_space))
+}
+
+/// Summarize a bare mechanism node for the report.
+///
+/// One JSONRelease is emitted per column, recording the sensitivity and noise scale the mechanism was run with.
+/// `noise_scale` maps the sensitivity and effective privacy usage of a column to the scale of the noise, if any.
+#[allow(clippy::too_many_arguments)]
+pub fn summarize_mechanism(
+    mechanism: &str,
+    privacy_definition: &Option<proto::PrivacyDefinition>,
+    privacy_usage: &[proto::PrivacyUsage],
+    node_id: u32,
+    component: &proto::Component,
+    public_arguments: &IndexMap<IndexKey, &Value>,
+    properties: &NodeProperties,
+    release: &Value,
+    variable_names: Option<&Vec<IndexKey>>,
+    noise_scale: impl Fn(Float, &proto::PrivacyUsage) -> Result<Option<Float>>,
+) -> Result<Option<Vec<json::JSONRelease>>> {
+    let privacy_definition = &get_mechanism_privacy_definition(
+        privacy_definition.as_ref().ok_or_else(|| "privacy definition must be defined")?,
+        public_arguments, properties)?;
+
+    // the exponential mechanism scores candidates with utilities, all others noise data
+    let sensitivity_type = component.sensitivity_space()?;
+    let data_name = match sensitivity_type {
+        SensitivitySpace::Exponential => "utilities",
+        _ => "data"
+    };
+    let data_property: &ArrayProperties = properties.get::<IndexKey>(&data_name.into())
+        .ok_or_else(|| Error::from(format!("{}: missing", data_name)))?.array()
+        .map_err(prepend(&format!("{}:", data_name)))?;
+
+    let sensitivity_override = get_sensitivity_override(privacy_definition, public_arguments, properties)?;
+    let sensitivity_value = match (&sensitivity_override, public_arguments.get(&IndexKey::from("sensitivity"))) {
+        (Some(sensitivity), _) => sensitivity.clone(),
+        (None, Some(sensitivity)) => (*sensitivity).clone(),
+        (None, None) => {
+            let aggregator = data_property.aggregator.as_ref()
+                .ok_or_else(|| Error::from("aggregator: missing"))?;
+            let sensitivity = compute_mechanism_sensitivity(
+                component, privacy_definition, aggregator)?.array()?.cast_float()?;
+
+            match sensitivity_type {
+                SensitivitySpace::Exponential => sensitivity.into(),
+                _ => (sensitivity * aggregator.lipschitz_constants.ref_array()?.clone().cast_float()?).into()
+            }
+        }
+    };
+
+    // take max sensitivity of each column
+    let sensitivities: Vec<Float> = sensitivity_value.array()?.cast_float()?
+        .gencolumns().into_iter()
+        .map(|sensitivity_col| sensitivity_col.into_iter().copied().fold1(|l, r| l.max(r)).unwrap())
+        .collect();
+
+    let privacy_usages = spread_privacy_usage(privacy_usage, sensitivities.len())?;
+    let seed = get_seed(public_arguments, properties)?;
+
+    let mut releases = Vec::new();
+    for (column_number, (sensitivity, usage)) in sensitivities.into_iter().zip(privacy_usages.into_iter()).enumerate() {
+        let variable_name = variable_names
+            .and_then(|names| names.get(column_number)).cloned()
+            .unwrap_or_else(|| "[Unknown]".into());
+
+        let effective_usage = usage.actual_to_effective(
+            data_property.sample_proportion.unwrap_or(1.),
+            data_property.c_stability,
+            privacy_definition.group_size)?;
+
+        releases.push(json::JSONRelease {
+            description: "DP release information".to_string(),
+            statistic: mechanism.to_string(),
+            variables: serde_json::json!(variable_name.to_string()),
+            release_info: match release.ref_array()? {
+                Array::Float(v) => json::value_to_json(&array::get_ith_column(v, column_number)?.into())?,
+                Array::Int(v) => json::value_to_json(&array::get_ith_column(v, column_number)?.into())?,
+                Array::Bool(v) => json::value_to_json(&array::get_ith_column(v, column_number)?.into())?,
+                Array::Str(v) => json::value_to_json(&array::get_ith_column(v, column_number)?.into())?,
+            },
+            privacy_loss: json::privacy_usage_to_json(&usage),
+            accuracy: None,
+            relative_accuracy: None,
+            submission: component.submission,
+            node_id,
+            postprocess: false,
+            algorithm_info: json::AlgorithmInfo {
+                name: "".to_string(),
+                cite: "".to_string(),
+                mechanism: mechanism.to_string(),
+                argument: {
+                    let mut argument = serde_json::json!({
+                        "sensitivity": sensitivity,
+                        "noise_scale": noise_scale(sensitivity, &effective_usage)?
+                    });
+                    if let Some(seed) = seed {
+                        argument["seed"] = serde_json::json!(seed);
+                        argument["warning"] = serde_json::json!(SEEDED_RELEASE_WARNING);
+                    }
+                    if sensitivity_override.is_some() {
+                        argument["sensitivity_certified"] = serde_json::json!(true);
+                        argument["sensitivity_warning"] = serde_json::json!(SENSITIVITY_OVERRIDE_WARNING);
+                    }
+                    argument
+                },
+            },
+        });
+    }
+    Ok(Some(releases))
+}
+
+/// Warning attached to every release of a seeded mechanism.
+pub const SEEDED_RELEASE_WARNING: &str = "seed: a seeded release is NOT differentially private, and must never be used on real data";
+
+/// Retrieve the seed of a mechanism, if one is set.
+///
+/// A seeded mechanism reproduces the same noise on every run, which is only useful for debugging and testing.
+pub fn get_seed(
+    public_arguments: &IndexMap<IndexKey, &Value>, properties: &NodeProperties
+) -> Result<Option<Integer>> {
+    match public_arguments.get::<IndexKey>(&"seed".into()) {
+        Some(seed) => Ok(Some(seed.ref_array()?.first_int().map_err(prepend("seed:"))?)),
+        None if properties.contains_key::<IndexKey>(&"seed".into()) => Err("seed: must be public".into()),
+        None => Ok(None)
+    }
+}
+
+/// Warning attached to every release of a mechanism calibrated to a user-certified sensitivity.
+pub const SENSITIVITY_OVERRIDE_WARNING: &str = "sensitivity_override: the sensitivity was certified by the user instead of derived by the library. The user is responsible for its correctness, and the release is NOT differentially private if it understates the sensitivity";
+
+/// Retrieve the user-certified sensitivity override of a mechanism, if one is set.
+///
+/// The override is only honored when `protect_sensitivity` is disabled and `sensitivity_certified` is true,
+/// and must be public, finite and positive.
+pub fn get_sensitivity_override(
+    privacy_definition: &proto::PrivacyDefinition,
+    public_arguments: &IndexMap<IndexKey, &Value>,
+    properties: &NodeProperties
+) -> Result<Option<Value>> {
+    if !properties.contains_key::<IndexKey>(&"sensitivity_override".into()) {
+        return Ok(None)
+    }
+    if privacy_definition.protect_sensitivity {
+        return Err("sensitivity_override: may only be passed if protect_sensitivity is disabled".into())
+    }
+    let sensitivity = public_arguments.get::<IndexKey>(&"sensitivity_override".into())
+        .ok_or_else(|| Error::from("sensitivity_override: must be public"))?;
+    let sensitivity = (*sensitivity).clone().array()?.cast_float()
+        .map_err(prepend("sensitivity_override:"))?;
+
+    let certified = match public_arguments.get::<IndexKey>(&"sensitivity_certified".into()) {
+        Some(certified) => certified.ref_array()?.first_bool().map_err(prepend("sensitivity_certified:"))?,
+        None => false
+    };
+    if !certified {
+        return Err("sensitivity_override: must be certified by setting `sensitivity_certified` to true".into())
+    }
+    if sensitivity.iter().any(|sensitivity| !sensitivity.is_finite() || *sensitivity <= 0.) {
+        return Err("sensitivity_override: must be finite and positive".into())
+    }
+    Ok(Some(sensitivity.into()))
+}
+
 pub fn check_sensitivity_properties(
     sensitivity_property: &ArrayProperties, data_property: &ArrayProperties
 ) -> Result<()> {
@@ -618,6 +1100,40 @@ pub fn check_sensitivity_properties(
     Ok(())
 }
 
+/// Find the smallest number of records for which a release meets the target accuracy.
+///
+/// `accuracy` maps a number of records to the accuracy of the release, and must be non-increasing in the number of records.
+/// Errors if no number of records representable in an i64 achieves the target.
+pub fn get_minimum_num_records(
+    accuracy: impl Fn(i64) -> Result<Float>, target_accuracy: Float
+) -> Result<i64> {
+    if target_accuracy.is_nan() || target_accuracy <= 0. {
+        return Err("target accuracy must be positive".into())
+    }
+    let meets_target = |num_records: i64| -> Result<bool> {
+        let accuracy = accuracy(num_records)?;
+        if accuracy.is_nan() {
+            return Err("accuracy is not a number".into())
+        }
+        Ok(accuracy <= target_accuracy)
+    };
+
+    // exponential search for an upper bound on the number of records
+    let mut upper = 1;
+    while !meets_target(upper)? {
+        upper = upper.checked_mul(2).ok_or_else(|| Error::from(
+            "no finite number of records achieves the target accuracy. Consider narrower bounds, a larger privacy usage, or a looser target accuracy"))?;
+    }
+
+    // binary search for the smallest number of records that meets the target
+    let mut lower = upper / 2;
+    while upper - lower > 1 {
+        let middle = lower + (upper - lower) / 2;
+        if meets_target(middle)? { upper = middle } else { lower = middle }
+    }
+    Ok(upper)
+}
+
 /// given a vector of items, return the shared item, or None, if no item is shared
 #[allow(clippy::ptr_arg)]
 pub fn get_common_value<T: Clone + Eq>(values: &Vec<T>) -> Option<T> {
@@ -648,6 +1164,11 @@ pub fn deduplicate<T: Eq + Hash + Clone>(values: Vec<T>) -> Vec<T> {
 
 #[cfg(test)]
 mod test_utilities {
+    use crate::base::{DataframeProperties, IndexKey, Nature, NatureContinuous, SensitivitySpace, test_data, Value, ValueProperties, Vector1DNull};
+    use crate::components::Mechanism;
+    use crate::errors::*;
+    use crate::components::resize::test_resize;
+    use crate::proto;
     use crate::utilities;
 
     #[test]
@@ -656,4 +1177,245 @@ mod test_utilities {
         let deduplicated = utilities::deduplicate(values.clone());
         assert!(deduplicated == vec![2, 0, 1]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_get_array_property() {
+        let properties = indexmap![
+            IndexKey::from("data") => ValueProperties::Dataframe(DataframeProperties { children: indexmap![] })
+        ];
+
+        // the helpers produce the same errors as retrieving properties manually
+        let manual = |name: &str| properties.get::<IndexKey>(&name.into())
+            .ok_or_else(|| Error::from(format!("{}: missing", name)))
+            .and_then(|property| property.array().map_err(utilities::prepend(&format!("{}:", name))).cloned())
+            .unwrap_err().to_string();
+
+        let helper = |name: &str| utilities::get_array_property(&properties, name)
+            .unwrap_err().to_string();
+
+        assert_eq!(helper("data"), manual("data"));
+        assert_eq!(helper("data"), "data: value must be an array");
+        assert_eq!(helper("left"), manual("left"));
+        assert_eq!(helper("left"), "left: missing");
+    }
+
+    #[test]
+    fn test_require_public_argument() {
+        let edges = Value::from(1.);
+        let public_arguments = indexmap![IndexKey::from("edges") => &edges];
+        assert!(utilities::require_public_argument(&public_arguments, "edges").is_ok());
+        assert_eq!(utilities::require_public_argument(&public_arguments, "lower").unwrap_err().to_string(),
+                   "lower: missing, must be public");
+    }
+
+    #[test]
+    fn test_mechanism_sensitivity_space() {
+        assert_eq!(proto::LaplaceMechanism::default().sensitivity_space().unwrap(), SensitivitySpace::KNorm(1));
+        assert_eq!(proto::GaussianMechanism::default().sensitivity_space().unwrap(), SensitivitySpace::KNorm(2));
+    }
+
+    #[test]
+    fn test_mechanism_sensitivity_mismatch() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        let mean = analysis.mean(resized).build();
+        let aggregator = analysis.properties(mean).unwrap()
+            .array().unwrap().aggregator.clone().unwrap();
+
+        // the mean supports both L1 and L2 sensitivities
+        assert!(utilities::compute_mechanism_sensitivity(
+            &proto::GaussianMechanism::default(), &analysis.privacy_definition, &aggregator).is_ok());
+
+        // but cannot score candidates for the exponential mechanism
+        let error = utilities::compute_mechanism_sensitivity(
+            &proto::ExponentialMechanism::default(), &analysis.privacy_definition, &aggregator).unwrap_err();
+        assert!(error.to_string().contains("Exponential space required by the mechanism"));
+    }
+
+    #[test]
+    fn test_mechanism_fails_closed() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+        let usage = test_data::privacy_usage(1., 0.);
+
+        // the data is not aggregated, so there is no sensitivity to scale the noise to
+        let noised = analysis.laplace_mechanism(resized, vec![usage.clone()]).build();
+        assert!(analysis.properties(noised).is_err());
+
+        // an aggregator without a sensitivity is not treated as having zero sensitivity
+        let mean = analysis.mean(resized).build();
+        let mut aggregator = analysis.properties(mean).unwrap()
+            .array().unwrap().aggregator.clone().unwrap();
+        aggregator.component = proto::component::Variant::Clamp(proto::Clamp { null_policy: "Pass".to_string() });
+        let error = utilities::compute_mechanism_sensitivity(
+            &proto::LaplaceMechanism::default(), &analysis.privacy_definition, &aggregator).unwrap_err();
+        assert!(error.iter().any(|cause| cause.to_string() == "unsupported operation compute_sensitivity for component Clamp"));
+
+        // an unexpanded dp component would otherwise not be counted against the budget
+        let dp_mean = proto::Component {
+            arguments: None,
+            variant: Some(proto::component::Variant::DpMean(proto::DpMean {
+                implementation: "resize".to_string(),
+                centered: false,
+                mechanism: "Laplace".to_string(),
+                privacy_usage: vec![usage],
+            })),
+            omit: false,
+            submission: 0,
+        };
+        let error = dp_mean.get_privacy_usage(&analysis.privacy_definition, None, &indexmap![]).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::UnsupportedOperation(operation, component)
+            if operation == "get_privacy_usage" && component == "DpMean"));
+    }
+
+    #[test]
+    fn test_invalid_sensitivity() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.neighboring = proto::privacy_definition::Neighboring::Substitute as i32;
+        let sum = analysis.sum(resized).build();
+        let mut aggregator = analysis.properties(sum).unwrap()
+            .array().unwrap().aggregator.clone().unwrap();
+        assert!(utilities::compute_mechanism_sensitivity(
+            &proto::LaplaceMechanism::default(), &analysis.privacy_definition, &aggregator).is_ok());
+
+        // a faulty derivation from corrupted bounds yields a NaN sensitivity, which must not calibrate noise
+        match aggregator.properties.get_mut::<IndexKey>(&"data".into()).unwrap() {
+            ValueProperties::Array(data_property) => data_property.nature = Some(Nature::Continuous(NatureContinuous {
+                lower: Vector1DNull::Float(vec![Some(0.)]),
+                upper: Vector1DNull::Float(vec![Some(f64::NAN)]),
+            })),
+            _ => unreachable!()
+        }
+        let error = utilities::compute_mechanism_sensitivity(
+            &proto::LaplaceMechanism::default(), &analysis.privacy_definition, &aggregator).unwrap_err();
+        assert!(error.iter().any(|cause| cause.to_string().starts_with(&format!(
+            "sensitivity of the aggregation over nodes {:?} must be finite and non-negative", vec![resized]))));
+    }
+
+    #[test]
+    fn test_cyclic_graph() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        let mean = analysis.mean(resized).build();
+        let abs = analysis.abs(mean).build();
+
+        // an expansion whose traversal depends on the expanded node can never be propagated
+        let error = utilities::check_expansion_acyclic(
+            &analysis.components, &[abs].iter().copied().collect(), &[abs], mean).unwrap_err();
+        assert_eq!(error.to_string(), format!(
+            "expansion of node_id {} is cyclic through node_ids {:?}", mean, vec![abs, mean]));
+
+        // the mean and abs depend on each other, so neither has all of its arguments evaluated first
+        analysis.components.get_mut(&mean).unwrap().insert_argument(&"data".into(), abs);
+        let error = crate::validate_analysis(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap_err();
+        assert!(error.to_string() == format!("Graph is cyclic through node_ids {:?}", vec![mean, abs]));
+    }
+
+    #[test]
+    fn test_validate_graph() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+        let usage = test_data::privacy_usage(1., 0.);
+
+        // a valid mean
+        let mean = analysis.mean(resized).build();
+        analysis.laplace_mechanism(mean, vec![usage.clone()]).build();
+
+        // three independent errors: noise on unaggregated data, a mean of strings, and a clamp of booleans
+        let unaggregated = analysis.laplace_mechanism(resized, vec![usage.clone()]).build();
+        let words = analysis.literal()
+            .value(test_data::array1d_string_10_uniform())
+            .value_public(true).build();
+        let string_mean = analysis.mean(words).build();
+        let flags = analysis.literal()
+            .value(ndarray::arr1(&[true, false, true]).into_dyn().into())
+            .value_public(true).build();
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(1.0.into()).value_public(true).build();
+        let bool_clamp = analysis.clamp(flags).lower(lower).upper(upper).build();
+
+        // the error of the mean of strings is not repeated downstream
+        analysis.laplace_mechanism(string_mean, vec![usage]).build();
+
+        // validation stops at the first error
+        assert!(crate::validate_analysis(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone()).is_err());
+
+        let report = crate::validate_graph(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap();
+        assert!(!report.is_valid());
+        assert_eq!(report.errors.keys().copied().collect::<Vec<u32>>(), {
+            let mut expected = vec![unaggregated, string_mean, bool_clamp];
+            expected.sort_unstable();
+            expected
+        });
+    }
+
+    #[test]
+    fn test_resume_propagation() {
+        use prost::Message;
+        use std::collections::HashSet;
+        use crate::utilities::serial::{parse_graph_properties, serialize_graph_properties};
+
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+
+        let lower = analysis.literal().value(2.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(9.0.into()).value_public(true).build();
+        let clamped = analysis.clamp(resized).lower(lower).upper(upper).build();
+
+        let usage = test_data::privacy_usage(1., 0.);
+        analysis.dp_mean(clamped, vec![usage.clone()]).mechanism("Laplace".to_string()).build();
+        analysis.dp_sum(clamped, vec![usage.clone()]).mechanism("Laplace".to_string()).build();
+        analysis.dp_variance(resized, vec![usage]).mechanism("Laplace".to_string()).build();
+
+        let privacy_definition = Some(analysis.privacy_definition.clone());
+        let mut computation_graph = analysis.components.clone();
+        let mut release = analysis.release.clone();
+        let (properties, _) = utilities::propagate_properties(
+            &privacy_definition, &mut computation_graph, &mut release, None, true).unwrap();
+
+        // save and reload the propagation state
+        let mut buffer = Vec::new();
+        serialize_graph_properties(properties, vec![]).encode(&mut buffer).unwrap();
+        let checkpoint = parse_graph_properties(proto::GraphProperties::decode(&*buffer).unwrap());
+
+        // edit the graph by tightening the clamp
+        release.get_mut(&upper).unwrap().value = 8.0.into();
+        let changed = [upper].iter().cloned().collect::<HashSet<u32>>();
+
+        let (resumed, _) = utilities::resume_propagation(
+            &privacy_definition, &mut computation_graph, &mut release,
+            checkpoint, &changed, true).unwrap();
+
+        // propagate the edited graph from scratch
+        analysis.release.get_mut(&upper).unwrap().value = 8.0.into();
+        let (expected, _) = utilities::propagate_properties(
+            &privacy_definition, &mut analysis.components.clone(), &mut analysis.release.clone(), None, true).unwrap();
+
+        // the ids of expanded nodes differ between the two propagations
+        let describe = |property: &ValueProperties| {
+            let mut property = property.clone();
+            if let ValueProperties::Array(array) = &mut property {
+                array.dataset_id = None;
+            }
+            format!("{:?}", property)
+        };
+
+        assert_eq!(resumed.get(&clamped).unwrap().array().unwrap().upper_float().unwrap(), vec![8.]);
+        analysis.components.keys().for_each(|node_id| assert_eq!(
+            describe(resumed.get(node_id).unwrap()),
+            describe(expected.get(node_id).unwrap())));
+    }
+}
@@ -1,30 +1,169 @@
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::ops::Mul;
 
+use indexmap::map::IndexMap;
 use itertools::Itertools;
 
 use crate::proto;
-use crate::base::{GroupId, IndexKey, Release, ValueProperties};
-use crate::components::Mechanism;
+use crate::base::{AggregatorProperties, GroupId, IndexKey, NodeProperties, Release, Value, ValueProperties};
+use crate::components::{Accuracy, Mechanism};
 use crate::errors::*;
-use crate::utilities::{get_common_value, get_dependents, get_input_properties};
+use crate::utilities::{get_common_value, get_dependents, get_input_properties, prepend};
 
 type BatchIdentifier = (u32, u32);
 type PartitionIds = Vec<u32>;
 
+/// An (epsilon, delta) privacy budget, in central or local units.
+///
+/// Budgets only support operations that are meaningful under differential privacy:
+/// sequential composition with [`PrivacyBudget::compose`], parallel composition with [`PrivacyBudget::compose_parallel`],
+/// and the remainder of a budget after a usage with [`PrivacyBudget::remaining`].
+/// The arithmetic operators are not implemented, so that epsilons are not mixed by accident.
+///
+/// ```compile_fail
+/// use smartnoise_validator::utilities::privacy::PrivacyBudget;
+/// let budget = PrivacyBudget::new(1., 0.).unwrap();
+/// let total = budget + budget;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PrivacyBudget {
+    epsilon: f64,
+    delta: f64,
+    units: proto::privacy_usage::Units,
+}
+
+impl PrivacyBudget {
+    /// A budget in central units.
+    pub fn new(epsilon: f64, delta: f64) -> Result<Self> {
+        PrivacyBudget::with_units(epsilon, delta, proto::privacy_usage::Units::Central)
+    }
+
+    /// A budget with a finite, non-negative epsilon, and a delta in `[0, 1]`.
+    pub fn with_units(epsilon: f64, delta: f64, units: proto::privacy_usage::Units) -> Result<Self> {
+        if !epsilon.is_finite() || epsilon < 0. {
+            return Err("epsilon: must be finite and non-negative".into())
+        }
+        if !(0. ..=1.).contains(&delta) {
+            return Err("delta: must be within [0, 1]".into())
+        }
+        Ok(PrivacyBudget { epsilon, delta, units })
+    }
+
+    /// The budget of releasing nothing, which composes with budgets in any units.
+    pub fn zero() -> Self {
+        PrivacyBudget { epsilon: 0., delta: 0., units: proto::privacy_usage::Units::Central }
+    }
+
+    pub fn epsilon(&self) -> f64 { self.epsilon }
+    pub fn delta(&self) -> f64 { self.delta }
+    pub fn units(&self) -> proto::privacy_usage::Units { self.units }
+
+    #[allow(clippy::float_cmp)]
+    fn is_zero(&self) -> bool {
+        self.epsilon == 0. && self.delta == 0.
+    }
+
+    fn common_units(&self, other: &PrivacyBudget) -> Result<proto::privacy_usage::Units> {
+        match (self.units == other.units, self.is_zero(), other.is_zero()) {
+            (true, _, _) | (false, false, true) => Ok(self.units),
+            (false, true, _) => Ok(other.units),
+            _ => Err("privacy budgets in central and local units may not be composed without an explicit conversion".into())
+        }
+    }
+
+    /// Budget spent by both releases over the same data.
+    ///
+    /// Under basic composition, the epsilons and deltas add.
+    pub fn compose(self, other: PrivacyBudget) -> Result<PrivacyBudget> {
+        Ok(PrivacyBudget {
+            units: self.common_units(&other)?,
+            epsilon: self.epsilon + other.epsilon,
+            delta: (self.delta + other.delta).min(1.),
+        })
+    }
+
+    /// Budget spent by sequentially composing every budget, or the zero budget if there are none.
+    pub fn compose_all(budgets: impl IntoIterator<Item=PrivacyBudget>) -> Result<PrivacyBudget> {
+        budgets.into_iter().try_fold(PrivacyBudget::zero(), PrivacyBudget::compose)
+    }
+
+    /// Budget spent by both releases over disjoint partitions of the data.
+    ///
+    /// Each individual is only in one partition, so the larger epsilon and delta are spent.
+    pub fn compose_parallel(self, other: PrivacyBudget) -> Result<PrivacyBudget> {
+        Ok(PrivacyBudget {
+            units: self.common_units(&other)?,
+            epsilon: self.epsilon.max(other.epsilon),
+            delta: self.delta.max(other.delta),
+        })
+    }
+
+    /// Budget left after spending `spent`, or an error if `spent` exceeds the budget.
+    pub fn remaining(self, spent: PrivacyBudget) -> Result<PrivacyBudget> {
+        if self.units != spent.units && !spent.is_zero() {
+            return Err("privacy usages in central and local units may not be subtracted without an explicit conversion".into())
+        }
+        if !self.covers(&spent) {
+            return Err(format!(
+                "privacy usage of (epsilon: {}, delta: {}) exceeds the budget of (epsilon: {}, delta: {})",
+                spent.epsilon, spent.delta, self.epsilon, self.delta).into())
+        }
+        Ok(PrivacyBudget {
+            units: self.units,
+            epsilon: self.epsilon - spent.epsilon,
+            delta: self.delta - spent.delta,
+        })
+    }
+
+    /// True if `spent` fits within the budget.
+    pub fn covers(&self, spent: &PrivacyBudget) -> bool {
+        spent.epsilon <= self.epsilon && spent.delta <= self.delta
+    }
+}
+
+impl TryFrom<&proto::PrivacyUsage> for PrivacyBudget {
+    type Error = Error;
+
+    fn try_from(usage: &proto::PrivacyUsage) -> Result<Self> {
+        PrivacyBudget::with_units(
+            get_epsilon(usage)?, get_delta(usage)?,
+            proto::privacy_usage::Units::from_i32(usage.units)
+                .ok_or_else(|| Error::from("units must be either \"Central\" or \"Local\""))?)
+    }
+}
+
+impl From<PrivacyBudget> for proto::PrivacyUsage {
+    fn from(budget: PrivacyBudget) -> Self {
+        proto::PrivacyUsage {
+            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
+                epsilon: budget.epsilon,
+                delta: budget.delta,
+            })),
+            units: budget.units as i32
+        }
+    }
+}
+
+/// Sequential composition of the privacy usages of a mechanism, of which there must be at least one.
+pub fn compose_privacy_usages(usages: &[proto::PrivacyUsage]) -> Result<proto::PrivacyUsage> {
+    if usages.is_empty() {
+        return Err("privacy_usage: must be defined".into())
+    }
+    Ok(PrivacyBudget::compose_all(usages.iter()
+        .map(PrivacyBudget::try_from)
+        .collect::<Result<Vec<PrivacyBudget>>>()?)?.into())
+}
+
 fn compute_batch_privacy_usage(
     privacy_usages: Vec<&proto::PrivacyUsage>
-) -> Result<proto::PrivacyUsage> {
+) -> Result<PrivacyBudget> {
     // TODO: insert advanced composition here
     //    This is just linear composition
-    privacy_usages.into_iter().cloned().map(Ok)
-        .fold1(|l, r| l? + r?)
-        .unwrap_or_else(|| Ok(proto::PrivacyUsage {
-            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
-                epsilon: 0.,
-                delta: 0.,
-            }))
-        }))
+    PrivacyBudget::compose_all(privacy_usages.into_iter()
+        .map(PrivacyBudget::try_from)
+        .collect::<Result<Vec<PrivacyBudget>>>()?)
 }
 
 /// Use a computation graph to partition privacy usages into batches.
@@ -202,13 +341,6 @@ pub fn compute_graph_privacy_usage(
     //     also return the node ids of partitions, as parallel composition needs to be applied to its dependents
     let (batches, partition_ids) = batch_partition(graph, &release_privacy_usages)?;
 
-    let zero_usage = || proto::PrivacyUsage {
-        distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
-            epsilon: 0.0,
-            delta: 0.0,
-        }))
-    };
-
     // get all node ids that are indexed by a specific category
     let get_category_indexes = |
         category: IndexKey, partition_id: u32,
@@ -286,43 +418,23 @@ pub fn compute_graph_privacy_usage(
     };
 
     // return the max of the left and right privacy usages
-    let max_usage = |l: Result<proto::PrivacyUsage>, r: Result<proto::PrivacyUsage>| -> Result<proto::PrivacyUsage> {
-        let proto::privacy_usage::DistanceApproximate {
-            epsilon: eps_l, delta: del_l
-        } = match l?.distance {
-            Some(proto::privacy_usage::Distance::Approximate(x)) => Ok(x),
-            _ => Err("expected approximate privacy")
-        }?;
-        let proto::privacy_usage::DistanceApproximate {
-            epsilon: eps_r, delta: del_r
-        } = match r?.distance {
-            Some(proto::privacy_usage::Distance::Approximate(x)) => Ok(x),
-            _ => Err("expected approximate privacy")
-        }?;
-
-        Ok(proto::PrivacyUsage {
-            distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
-                epsilon: eps_l.max(eps_r),
-                delta: del_l.max(del_r),
-            }))
-        })
-    };
+    let max_usage = |l: Result<PrivacyBudget>, r: Result<PrivacyBudget>| l?.compose_parallel(r?);
 
     // compute privacy usage of a subset of the graph,
     //     where the subset is indicated by a collection of node ids
     let compute_all_partitions_usage = |
         partition_ids: Vec<u32>
-    | -> Result<proto::PrivacyUsage> {
+    | -> Result<PrivacyBudget> {
         partition_ids.iter()
-            .map(|partition_id| compute_graph_privacy_usage(
+            .map(|partition_id| PrivacyBudget::try_from(&compute_graph_privacy_usage(
                 &get_downstream_graph(None, *partition_id)?,
-                privacy_definition, properties, release))
+                privacy_definition, properties, release)?))
             .fold1(max_usage)
-            .unwrap_or_else(|| Ok(zero_usage()))
+            .unwrap_or_else(|| Ok(PrivacyBudget::zero()))
     };
 
     // compute the overall privacy usage
-    let partitions_usage: proto::PrivacyUsage = partition_ids.into_iter()
+    let partitions_usage: PrivacyBudget = partition_ids.into_iter()
         // for each partition component...
         .map(|partition_node_id| {
             let partition_properties = properties.get(&partition_node_id)
@@ -339,25 +451,71 @@ pub fn compute_graph_privacy_usage(
 
                     let (batches, partition_ids) = batch_partition(
                         &unioned_downstream_graph, &release_privacy_usages)?;
-                    let batch_usages = batches.into_iter()
-                        .map(|(_, batch)| compute_batch_privacy_usage(batch))
-                        .fold1(|l, r| l? + r?)
-                        .unwrap_or_else(|| Ok(zero_usage()))?;
+                    let batch_usages = PrivacyBudget::compose_all(batches.into_values()
+                        .map(compute_batch_privacy_usage)
+                        .collect::<Result<Vec<PrivacyBudget>>>()?)?;
 
-                    batch_usages + compute_all_partitions_usage(partition_ids)?
+                    batch_usages.compose(compute_all_partitions_usage(partition_ids)?)
                 })
                 .fold1(max_usage)
-                .unwrap_or_else(|| Ok(zero_usage()))
+                .unwrap_or_else(|| Ok(PrivacyBudget::zero()))
         })
-        .fold1(|l, r| l? + r?)
-        .unwrap_or_else(|| Ok(zero_usage()))?;
+        .collect::<Result<Vec<PrivacyBudget>>>()
+        .and_then(PrivacyBudget::compose_all)?;
 
-    let batch_usages = batches.into_iter()
-        .map(|(_, batch)| compute_batch_privacy_usage(batch))
-        .fold1(|l, r| l? + r?)
-        .unwrap_or_else(|| Ok(zero_usage()))?;
+    let batch_usages = PrivacyBudget::compose_all(batches.into_values()
+        .map(compute_batch_privacy_usage)
+        .collect::<Result<Vec<PrivacyBudget>>>()?)?;
 
-    batch_usages + partitions_usage
+    Ok(batch_usages.compose(partitions_usage)?.into())
+}
+
+/// Privacy budget allotted to each data source, keyed by the node id of the source (typically a materialize node).
+pub type SourceBudgets = HashMap<u32, proto::PrivacyUsage>;
+
+/// Subtract the privacy usage of every mechanism from the budget of each data source it reads from.
+///
+/// Each source is accounted for independently, by composing the usages of the mechanisms downstream of the source.
+/// A mechanism that combines several sources is charged against each of them.
+///
+/// # Returns
+/// The remaining budget of each source, or an error if the usage of any source exceeds its budget.
+pub fn compute_remaining_budgets(
+    graph: &HashMap<u32, proto::Component>,
+    privacy_definition: &proto::PrivacyDefinition,
+    properties: &HashMap<u32, ValueProperties>,
+    release: &Release,
+    budgets: &SourceBudgets,
+) -> Result<SourceBudgets> {
+    let dependents = get_dependents(graph);
+
+    budgets.iter()
+        .map(|(source_id, budget)| {
+            if !graph.contains_key(source_id) {
+                return Err(format!("source {}: not found in the computation graph", source_id).into())
+            }
+
+            // trace every mechanism that reads from the source
+            let mut downstream_graph = HashMap::new();
+            let mut traversal = vec![*source_id];
+            while let Some(node_id) = traversal.pop() {
+                if downstream_graph.insert(node_id, graph[&node_id].clone()).is_some() {
+                    continue
+                }
+                if let Some(node_dependents) = dependents.get(&node_id) {
+                    traversal.extend(node_dependents);
+                }
+            }
+
+            let usage = PrivacyBudget::try_from(&compute_graph_privacy_usage(
+                &downstream_graph, privacy_definition, properties, release)?)?;
+            let remaining = PrivacyBudget::try_from(budget)
+                .and_then(|budget| budget.remaining(usage))
+                .chain_err(|| format!("source {}:", source_id))?;
+
+            Ok((*source_id, remaining.into()))
+        })
+        .collect()
 }
 
 // pub fn privacy_usage_reducer(
@@ -378,10 +536,26 @@ pub fn compute_graph_privacy_usage(
 // }
 
 
+/// Number of records in the data that an aggregator summarizes, if known.
+///
+/// The aggregate itself has one record, so delta must be checked against the number of records of its input.
+pub fn get_aggregated_num_records(aggregator: &AggregatorProperties) -> Option<i64> {
+    aggregator.properties.values().find_map(|property| match property {
+        ValueProperties::Array(property) => property.num_records,
+        ValueProperties::Dataframe(property) => property.num_records().ok().flatten(),
+        _ => None
+    })
+}
+
+/// Check that a privacy usage is valid, and collect warnings for usages that are valid but risky.
+///
+/// A nonzero delta of at least `1 / num_records` permits releasing individual records.
+/// When the number of records is unknown, a nonzero delta is only accepted if `allow_unknown_num_records` is set.
 pub fn privacy_usage_check(
     privacy_usage: &proto::PrivacyUsage,
     num_records: Option<i64>,
     strict_parameter_check: bool,
+    allow_unknown_num_records: bool,
 ) -> Result<Vec<Error>> {
     let mut warnings = Vec::new();
 
@@ -406,16 +580,18 @@ pub fn privacy_usage_check(
                     }
                     match num_records {
                         Some(num_records) => {
-                            if usage.delta * num_records as f64 > 1.0 {
-                                return Err("delta: a value greater than 1 / num_records is not differentially private".into());
+                            if usage.delta * num_records as f64 >= 1.0 {
+                                return Err(format!(
+                                    "delta: a value of {} is at least 1 / num_records = 1 / {}, which permits releasing individual records",
+                                    usage.delta, num_records).into());
                             }
 
                             if strict_parameter_check && usage.delta * num_records.pow(2) as f64 > 1.0 {
                                 warnings.push("delta: a value greater than 1 / num_records^2 exposes individuals to significant risk".into());
                             }
                         }
-                        None => if strict_parameter_check {
-                            return Err("delta: the number of records must be known to check if delta is a value that satisfies differential privacy".into());
+                        None => if strict_parameter_check || !allow_unknown_num_records {
+                            return Err("delta: the number of records must be known to check if delta is a value that satisfies differential privacy. Set allow_unknown_num_records in the privacy definition to accept delta anyways".into());
                         }
                     }
                 }
@@ -461,11 +637,143 @@ pub fn spread_privacy_usage(usages: &[proto::PrivacyUsage], length: usize) -> Re
                 distance: Some(proto::privacy_usage::Distance::Approximate(proto::privacy_usage::DistanceApproximate {
                     epsilon: approx.epsilon / (length as f64),
                     delta: approx.delta / (length as f64),
-                }))
+                })),
+                units: usages[0].units
             }).collect()
     })
 }
 
+/// Number of rows a single user may change in a neighboring dataset.
+///
+/// When a user contributes up to k rows, the sensitivities of row-level aggregations are scaled by k.
+/// Under user-level privacy, the contribution bound must be declared,
+/// because the number of rows sharing a user id is not known without inspecting the data.
+pub fn get_max_contributions(privacy_definition: &proto::PrivacyDefinition) -> Result<u32> {
+    use proto::privacy_definition::PrivacyUnit;
+
+    match PrivacyUnit::from_i32(privacy_definition.privacy_unit)
+        .ok_or_else(|| Error::from("privacy unit must be either \"Row\" or \"User\""))? {
+        PrivacyUnit::Row => Ok(privacy_definition.max_contributions_per_user.max(1)),
+        PrivacyUnit::User => {
+            if privacy_definition.user_id.is_empty() {
+                bail!("user-level privacy requires the user id column to be named")
+            }
+            if privacy_definition.max_contributions_per_user == 0 {
+                bail!("user-level privacy requires max_contributions_per_user to be declared")
+            }
+            Ok(privacy_definition.max_contributions_per_user)
+        }
+    }
+}
+
+/// Scale the sensitivity of a row-level aggregation to the number of rows a single user may change.
+///
+/// Sensitivities are derived for neighboring datasets that differ in one row.
+/// A user may change as many rows as they contribute, so the sensitivity is multiplied by the contribution bound.
+pub fn scale_by_contributions<T: Mul<Output = T> + From<u32>>(
+    privacy_definition: &proto::PrivacyDefinition, sensitivity: T,
+) -> Result<T> {
+    Ok(sensitivity * T::from(get_max_contributions(privacy_definition)?))
+}
+
+/// Privacy definition seen by a mechanism.
+///
+/// A public `max_contributions_per_user` argument on the mechanism overrides the contribution bound of the analysis.
+/// The override may only loosen the bound, because a tighter bound would understate the sensitivity.
+pub fn get_mechanism_privacy_definition(
+    privacy_definition: &proto::PrivacyDefinition,
+    public_arguments: &IndexMap<IndexKey, &Value>,
+    properties: &NodeProperties,
+) -> Result<proto::PrivacyDefinition> {
+    let key = IndexKey::from("max_contributions_per_user");
+    let mut privacy_definition = privacy_definition.clone();
+    match public_arguments.get(&key) {
+        Some(contributions) => privacy_definition.max_contributions_per_user = parse_contributions(
+            contributions, &privacy_definition)?,
+        None => if properties.contains_key(&key) {
+            bail!("max_contributions_per_user: must be public")
+        }
+    }
+    Ok(privacy_definition)
+}
+
+fn parse_contributions(value: &Value, privacy_definition: &proto::PrivacyDefinition) -> Result<u32> {
+    value.assert_scalar().map_err(prepend("max_contributions_per_user:"))?;
+    let contributions = value.ref_array()?.first_int()
+        .map_err(prepend("max_contributions_per_user:"))?;
+    let contributions = u32::try_from(contributions)
+        .map_err(|_| Error::from(format!("max_contributions_per_user: {} must be a positive 32-bit integer", contributions)))?;
+    if contributions < 1 {
+        bail!("max_contributions_per_user: must be positive")
+    }
+    if contributions < privacy_definition.max_contributions_per_user {
+        bail!("max_contributions_per_user: {} may not be less than the {} contributions declared in the privacy definition",
+            contributions, privacy_definition.max_contributions_per_user)
+    }
+    Ok(contributions)
+}
+
+/// Check that every mechanism downstream of a data source assumes the same number of contributions per user.
+///
+/// Sensitivities are derived under the contribution bound of each mechanism,
+/// so composing releases of one data source under different bounds is unsound.
+/// Data sources are the private nodes whose arguments are all public.
+pub fn check_contribution_consistency(
+    graph: &HashMap<u32, proto::Component>,
+    privacy_definition: &proto::PrivacyDefinition,
+    release: &Release,
+) -> Result<()> {
+    let is_public = |node_id: &u32| release.get(node_id)
+        .map(|release_node| release_node.public).unwrap_or(false);
+
+    // data source id -> (contribution bound, mechanism id)
+    let mut assumptions = HashMap::<u32, (u32, u32)>::new();
+
+    for (mechanism_id, component) in graph.iter().sorted_by_key(|(node_id, _)| *node_id) {
+        if component.sensitivity_space().is_err() {
+            continue
+        }
+        let arguments = component.arguments();
+
+        let contributions = match arguments.get(&IndexKey::from("max_contributions_per_user")) {
+            Some(argument_id) => parse_contributions(&release.get(argument_id)
+                .filter(|release_node| release_node.public)
+                .ok_or_else(|| "max_contributions_per_user: must be public")?.value, privacy_definition)?,
+            None => get_max_contributions(privacy_definition)?
+        };
+
+        let mut traversal = arguments.values()
+            .filter(|node_id| !is_public(node_id))
+            .copied().collect::<Vec<u32>>();
+        let mut visited = HashSet::new();
+
+        while let Some(node_id) = traversal.pop() {
+            if !visited.insert(node_id) {
+                continue
+            }
+            let private_arguments = graph.get(&node_id)
+                .ok_or_else(|| format!("node {} is missing from the graph", node_id))?
+                .arguments().into_iter()
+                .map(|(_, argument_id)| argument_id)
+                .filter(|argument_id| !is_public(argument_id))
+                .collect::<Vec<u32>>();
+
+            if !private_arguments.is_empty() {
+                traversal.extend(private_arguments);
+                continue
+            }
+
+            let (expected, other_id) = *assumptions.entry(node_id)
+                .or_insert((contributions, *mechanism_id));
+            if expected != contributions {
+                bail!("mechanisms at node_ids {} and {} assume {} and {} contributions per user from the data source at node_id {}",
+                    other_id, mechanism_id, expected, contributions, node_id)
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn get_group_id_path(arguments: Vec<Vec<GroupId>>) -> Result<Vec<GroupId>> {
     let partition_depth = get_common_value(&arguments.iter()
         .map(|group_ids| group_ids.len())
@@ -519,4 +827,302 @@ pub fn get_c_stability_multiplier(arguments: Vec<Vec<GroupId>>) -> Result<u32> {
         *counts.entry(group_id.index).or_insert(0) += 1);
 
     Ok(*counts.values().max().unwrap())
-}
\ No newline at end of file
+}
+/// A statistic to be released, and the accuracy it should be released with.
+pub struct AccuracyTarget<'a> {
+    /// Component of the statistic, which must implement `accuracy_to_privacy_usage`.
+    pub component: proto::Component,
+    /// Public arguments of the component, like the sensitivity of a mechanism.
+    pub public_arguments: IndexMap<IndexKey, &'a Value>,
+    /// Target accuracy of each column of the statistic.
+    pub accuracies: proto::Accuracies,
+}
+
+/// Privacy usage allotted to each statistic of a release, by [`calibrate_release`].
+#[derive(Debug)]
+pub struct BudgetAllocation {
+    /// Privacy usage of each column of each statistic, in the order of the targets.
+    pub privacy_usages: Vec<Vec<proto::PrivacyUsage>>,
+    /// Accuracy of each column of each statistic under the allotted usage.
+    pub accuracies: Vec<proto::Accuracies>,
+    /// True if every statistic meets its target accuracy.
+    pub feasible: bool,
+}
+
+/// Allot a total budget across the statistics of a release, so that each statistic meets its target accuracy.
+///
+/// Rather than splitting the budget evenly, like [`spread_privacy_usage`], every target is relaxed (or tightened)
+/// by a common factor, and the smallest factor whose usages compose within the budget is found by bisection.
+/// This minimizes the largest ratio of achieved to target accuracy, and spends the entire budget.
+/// Usages compose by summing epsilons and deltas.
+///
+/// # Returns
+/// The allotted usages. If the targets cannot all be met, `feasible` is false,
+/// and the accuracies are the best that may be achieved uniformly across the statistics.
+pub fn calibrate_release(
+    targets: &[AccuracyTarget],
+    budget: &proto::PrivacyUsage,
+) -> Result<BudgetAllocation> {
+    if targets.is_empty() {
+        return Err("targets: at least one statistic must be calibrated".into())
+    }
+    let budget = PrivacyBudget::try_from(budget).map_err(prepend("budget:"))?;
+    if budget.epsilon() <= 0. {
+        return Err("budget: epsilon must be greater than 0".into())
+    }
+
+    // accuracies of every target, scaled by a common factor
+    let scale = |factor: f64| targets.iter()
+        .map(|target| proto::Accuracies {
+            values: target.accuracies.values.iter()
+                .map(|accuracy| proto::Accuracy { value: accuracy.value * factor, alpha: accuracy.alpha })
+                .collect()
+        })
+        .collect::<Vec<proto::Accuracies>>();
+
+    let allocate = |factor: f64| -> Result<(Vec<Vec<proto::PrivacyUsage>>, bool)> {
+        let usages = targets.iter().zip(scale(factor)).enumerate()
+            .map(|(index, (target, accuracies))| target.component
+                .accuracy_to_privacy_usage(&accuracies, target.public_arguments.clone())
+                .chain_err(|| format!("target {}:", index))?
+                .ok_or_else(|| Error::from(format!("target {}: accuracy may not be converted to a privacy usage", index))))
+            .collect::<Result<Vec<Vec<proto::PrivacyUsage>>>>()?;
+
+        let total = PrivacyBudget::compose_all(usages.iter().flatten()
+            .map(PrivacyBudget::try_from)
+            .collect::<Result<Vec<PrivacyBudget>>>()?)?;
+        Ok((usages, budget.covers(&total)))
+    };
+
+    // bracket the smallest factor that fits in the budget
+    let feasible = allocate(1.)?.1;
+    let (mut lower, mut upper) = (0., 1.);
+    if !feasible {
+        while !allocate(upper)?.1 {
+            lower = upper;
+            upper *= 2.;
+            if upper > 1e12 {
+                return Err("budget: no accuracy is achievable within the budget".into())
+            }
+        }
+    }
+    for _ in 0..64 {
+        let midpoint = (lower + upper) / 2.;
+        if allocate(midpoint)?.1 { upper = midpoint } else { lower = midpoint }
+    }
+
+    Ok(BudgetAllocation {
+        privacy_usages: allocate(upper)?.0,
+        accuracies: scale(upper),
+        feasible,
+    })
+}
+
+#[cfg(test)]
+mod test_privacy {
+    use std::convert::TryFrom;
+
+    use crate::base::test_data;
+    use crate::proto;
+    use crate::proto::privacy_usage::Units;
+
+    #[test]
+    fn test_sum_across_units() {
+        let laplace_usage = test_data::privacy_usage(1., 0.);
+        let randomized_response_usage = test_data::local_privacy_usage(0.5, 0.);
+
+        assert!(super::compose_privacy_usages(&[laplace_usage.clone(), randomized_response_usage.clone()]).is_err());
+
+        let central_usage = randomized_response_usage.local_to_central(100_000, 1e-6).unwrap();
+        assert_eq!(central_usage.units, Units::Central as i32);
+        assert!(super::get_epsilon(&central_usage).unwrap() <= 0.5);
+
+        let total_usage = super::compose_privacy_usages(&[laplace_usage, central_usage]).unwrap();
+        assert_eq!(total_usage.units, Units::Central as i32);
+    }
+
+    #[test]
+    fn test_budget_composition() {
+        use super::PrivacyBudget;
+
+        let budgets = vec![
+            PrivacyBudget::new(0.5, 1e-6).unwrap(),
+            PrivacyBudget::new(0.25, 0.).unwrap(),
+            PrivacyBudget::new(0.25, 1e-6).unwrap(),
+        ];
+
+        // sequential composition sums the epsilons and deltas
+        let total = PrivacyBudget::compose_all(budgets.clone()).unwrap();
+        assert!((total.epsilon() - 1.).abs() < 1e-12);
+        assert!((total.delta() - 2e-6).abs() < 1e-18);
+        assert_eq!(budgets[0].compose(budgets[1]).unwrap().compose(budgets[2]).unwrap(), total);
+
+        // parallel composition spends the largest of the budgets
+        let parallel = budgets[0].compose_parallel(budgets[1]).unwrap();
+        assert_eq!((parallel.epsilon(), parallel.delta()), (0.5, 1e-6));
+
+        // the remainder of a budget may not be negative
+        let budget = PrivacyBudget::new(1., 1e-5).unwrap();
+        let remaining = budget.remaining(total).unwrap();
+        assert!(remaining.epsilon().abs() < 1e-12);
+        assert!(remaining.remaining(budgets[0]).is_err());
+
+        // budgets are only constructed with a valid epsilon and delta
+        assert!(PrivacyBudget::new(-1., 0.).is_err());
+        assert!(PrivacyBudget::new(f64::INFINITY, 0.).is_err());
+        assert!(PrivacyBudget::new(1., 2.).is_err());
+        assert!(PrivacyBudget::try_from(&test_data::privacy_usage(-1., 0.)).is_err());
+
+        // budgets are only composed in the same units
+        let local = PrivacyBudget::with_units(0.5, 0., Units::Local).unwrap();
+        assert!(budget.compose(local).is_err());
+        assert_eq!(PrivacyBudget::zero().compose(local).unwrap().units(), Units::Local);
+    }
+
+    #[test]
+    fn test_effective_delta() {
+        let usage = test_data::privacy_usage(1., 1e-6);
+
+        // without subsampling or group privacy, the effective usage is the actual usage
+        let effective = usage.actual_to_effective(1., 1, 1).unwrap();
+        assert!((super::get_delta(&effective).unwrap() - 1e-6).abs() < 1e-12);
+
+        // converting an effective usage back recovers the actual delta
+        let actual = usage.actual_to_effective(0.5, 1, 1).unwrap()
+            .effective_to_actual(0.5, 1, 1).unwrap();
+        assert!((super::get_delta(&actual).unwrap() - 1e-6).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_delta_relative_to_num_records() {
+        use crate::base::test_data;
+        use crate::components::resize::test_resize;
+
+        let approximate_usage = |delta: f64| test_data::privacy_usage(1., delta);
+
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 100.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+        let sum = analysis.sum(resized).build();
+
+        // a delta of 1 / 100 or more permits releasing individual records
+        let noised = analysis.gaussian_mechanism(sum, vec![approximate_usage(0.01)]).build();
+        assert!(analysis.properties(noised).is_err());
+
+        let noised = analysis.gaussian_mechanism(sum, vec![approximate_usage(1e-6)]).build();
+        assert!(analysis.properties(noised).is_ok());
+
+        // when the number of records is unknown, delta is only accepted with an acknowledgment
+        assert!(super::privacy_usage_check(&approximate_usage(1e-6), None, false, false).is_err());
+        assert!(super::privacy_usage_check(&approximate_usage(1e-6), None, false, true).is_ok());
+    }
+
+    #[test]
+    fn test_remaining_budgets_per_source() {
+        use crate::base::test_data;
+        use crate::components::resize::test_resize;
+
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+        // the private data the resized data is derived from
+        let source_a = *analysis.components.keys().min().unwrap();
+        let source_b = analysis.literal()
+            .value(test_data::array1d_f64_10_uniform())
+            .value_public(false).build();
+
+        analysis.dp_sum(resized, vec![test_data::privacy_usage(0.5, 0.)]).mechanism("Laplace".to_string()).build();
+        let lower = analysis.literal().value(0.into()).value_public(true).build();
+        analysis.dp_count(source_b, lower, vec![test_data::privacy_usage(0.25, 0.)]).build();
+
+        let budgets = vec![(source_a, test_data::privacy_usage(1., 0.)), (source_b, test_data::privacy_usage(1., 0.))]
+            .into_iter().collect::<super::SourceBudgets>();
+        let remaining = crate::compute_remaining_budgets(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            budgets).unwrap();
+
+        // each source is only charged for the mechanism that reads from it
+        assert!((super::get_epsilon(&remaining[&source_a]).unwrap() - 0.5).abs() < 1e-10);
+        assert!((super::get_epsilon(&remaining[&source_b]).unwrap() - 0.75).abs() < 1e-10);
+
+        // overspending either source is an error
+        let budgets = vec![(source_a, test_data::privacy_usage(1., 0.)), (source_b, test_data::privacy_usage(0.1, 0.))]
+            .into_iter().collect::<super::SourceBudgets>();
+        assert!(crate::compute_remaining_budgets(
+            analysis.privacy_definition.clone(),
+            analysis.components.clone(),
+            analysis.release.clone(),
+            budgets).is_err());
+    }
+
+    #[test]
+    fn test_contributions_must_be_scalar() {
+        use ndarray::arr2;
+        use crate::base::test_data;
+        use crate::components::resize::test_resize;
+
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+        analysis.privacy_definition.protect_floating_point = false;
+        let sum = analysis.sum(resized).build();
+
+        let contributions = analysis.literal()
+            .value(arr2(&[[1i64, 2]]).into_dyn().into())
+            .value_public(true).build();
+        let noised = analysis.laplace_mechanism(sum, vec![test_data::privacy_usage(1., 0.)])
+            .max_contributions_per_user(contributions)
+            .build();
+
+        // a two-column value in a scalar slot is rejected, rather than silently taking the first element
+        let error = analysis.properties(noised).unwrap_err();
+        assert!(error.iter().any(|error| error.to_string()
+            == "max_contributions_per_user: value must be a scalar, but has shape [1, 2]"), "{:?}", error);
+    }
+
+    #[test]
+    fn test_calibrate_release() {
+        use ndarray::arr1;
+        use crate::base::{IndexKey, Value};
+        use crate::utilities::privacy::{AccuracyTarget, calibrate_release, get_epsilon};
+
+        // two noised statistics, where the second is four times as sensitive
+        let sensitivities: Vec<Value> = vec![arr1(&[1.]).into_dyn().into(), arr1(&[4.]).into_dyn().into()];
+        let targets = sensitivities.iter()
+            .map(|sensitivity| AccuracyTarget {
+                component: proto::Component {
+                    arguments: None,
+                    variant: Some(proto::component::Variant::LaplaceMechanism(proto::LaplaceMechanism {
+                        privacy_usage: vec![], max_noise_sigmas: 0.
+                    })),
+                    omit: false,
+                    submission: 0,
+                },
+                public_arguments: indexmap![IndexKey::from("sensitivity") => sensitivity],
+                accuracies: proto::Accuracies { values: vec![proto::Accuracy { value: 10., alpha: 0.05 }] },
+            })
+            .collect::<Vec<AccuracyTarget>>();
+        // epsilon needed to meet the targets, ln(1 / alpha) * sensitivity / accuracy
+        let required = (1. / 0.05_f64).ln() * 5. / 10.;
+
+        let total = |allocation: &super::BudgetAllocation| allocation.privacy_usages.iter().flatten()
+            .map(|usage| get_epsilon(usage).unwrap()).sum::<f64>();
+
+        // the targets fit within the budget, and the slack tightens both statistics
+        let allocation = calibrate_release(&targets, &test_data::privacy_usage(2., 0.)).unwrap();
+        assert!(allocation.feasible);
+        assert!(total(&allocation) <= 2. && total(&allocation) > 2. - 1e-6);
+        let epsilons = allocation.privacy_usages.iter()
+            .map(|usages| get_epsilon(&usages[0]).unwrap()).collect::<Vec<f64>>();
+        assert!((epsilons[1] / epsilons[0] - 4.).abs() < 1e-6);
+        assert!(allocation.accuracies.iter().all(|accuracies| accuracies.values[0].value < 10.));
+
+        // the targets exceed the budget, and both statistics fall short by the same factor
+        let allocation = calibrate_release(&targets, &test_data::privacy_usage(1., 0.)).unwrap();
+        assert!(!allocation.feasible);
+        assert!(total(&allocation) <= 1.);
+        assert!(allocation.accuracies.iter()
+            .all(|accuracies| (accuracies.values[0].value - 10. * required).abs() < 1e-6));
+    }
+}
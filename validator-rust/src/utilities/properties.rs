@@ -109,7 +109,12 @@ pub fn stack_properties(
         dimensionality,
         group_id,
         naturally_ordered: true,
-        sample_proportion
+        sample_proportion,
+        monotone: false,
+        data_derived: all_properties.iter().any(|prop| prop.data_derived),
+        num_records_lower_bound: all_properties.iter()
+            .map(|prop| prop.num_records_lower_bound).max().flatten(),
+        noised: all_properties.iter().all(|prop| prop.noised)
     }))
 }
 
@@ -190,6 +190,12 @@ pub fn parse_value_properties(value: proto::ValueProperties) -> ValueProperties
     }
 }
 
+pub fn parse_graph_properties(value: proto::GraphProperties) -> HashMap<u32, ValueProperties> {
+    value.properties.into_iter()
+        .map(|(node_id, properties)| (node_id, parse_value_properties(properties)))
+        .collect()
+}
+
 pub fn parse_argument_node_ids(value: proto::ArgumentNodeIds) -> IndexMap<IndexKey, u32> {
     value.values.iter().zip(value.keys.into_iter())
         .map(|(v, k)| (parse_index_key(k), *v))
@@ -274,7 +280,11 @@ pub fn parse_array_properties(value: proto::ArrayProperties) -> ArrayProperties
         dimensionality: value.dimensionality.and_then(parse_i64_null),
         group_id: value.group_id.into_iter().map(parse_group_id).collect(),
         naturally_ordered: value.naturally_ordered,
-        sample_proportion: parse_f64_null(value.sample_proportion.unwrap()).map(Float::from)
+        sample_proportion: parse_f64_null(value.sample_proportion.unwrap()),
+        monotone: value.monotone,
+        data_derived: value.data_derived,
+        num_records_lower_bound: value.num_records_lower_bound.and_then(parse_i64_null),
+        noised: value.noised
     }
 }
 
@@ -542,7 +552,8 @@ pub fn serialize_array_properties(value: ArrayProperties) -> proto::ArrayPropert
         c_stability, aggregator, nature,
         data_type, dataset_id, is_not_empty,
         dimensionality, group_id,
-        naturally_ordered, sample_proportion, node_id
+        naturally_ordered, sample_proportion, node_id, monotone, data_derived,
+        num_records_lower_bound, noised
     } = value;
 
     proto::ArrayProperties {
@@ -578,7 +589,11 @@ pub fn serialize_array_properties(value: ArrayProperties) -> proto::ArrayPropert
         group_id: group_id.into_iter().map(serialize_group_id).collect(),
         naturally_ordered,
         sample_proportion: Some(serialize_f64_null(sample_proportion.map(f64::from))),
-        node_id: node_id as u32
+        node_id: node_id as u32,
+        monotone,
+        data_derived,
+        num_records_lower_bound: Some(serialize_i64_null(num_records_lower_bound)),
+        noised
     }
 }
 
@@ -630,6 +645,17 @@ pub fn serialize_value_properties(value: ValueProperties) -> proto::ValuePropert
     }
 }
 
+pub fn serialize_graph_properties(
+    properties: HashMap<u32, ValueProperties>, warnings: Vec<crate::Error>,
+) -> proto::GraphProperties {
+    proto::GraphProperties {
+        properties: properties.into_iter()
+            .map(|(node_id, properties)| (node_id, serialize_value_properties(properties)))
+            .collect(),
+        warnings: warnings.into_iter().map(serialize_error).collect(),
+    }
+}
+
 pub fn serialize_component_expansion(value: ComponentExpansion) -> proto::ComponentExpansion {
     proto::ComponentExpansion {
         computation_graph: value.computation_graph,
@@ -0,0 +1,240 @@
+//! Simplify an expanded computation graph
+//!
+//! Expansions may clamp or impute the same data more than once.
+//! Redundant nodes are fused or bypassed without changing any value the graph releases.
+
+use std::collections::{HashMap, HashSet};
+
+use ndarray::ArrayD;
+
+use crate::{base, proto};
+use crate::base::{Array, IndexKey, Value, ValueProperties};
+use crate::errors::*;
+use crate::utilities::{get_dependents, get_literal, get_traversal, standardize_numeric_argument};
+use crate::utilities::inference::infer_property;
+
+/// Fuse consecutive numeric clamps, and bypass imputations of data that is already non-null.
+///
+/// Bypassed nodes that are omitted from the release, and no longer have dependents, are removed from the graph,
+/// along with their omitted arguments that are no longer used.
+/// Removed nodes are also removed from the traversal.
+///
+/// # Arguments
+/// * `computation_graph` - expanded graph, as returned by property propagation
+/// * `release` - release of the expanded graph, where literals for fused bounds are added
+/// * `properties` - properties of every node in the expanded graph
+/// * `traversal` - order in which the graph is to be evaluated
+pub fn simplify_graph(
+    computation_graph: &mut HashMap<u32, proto::Component>,
+    release: &mut base::Release,
+    properties: &mut HashMap<u32, ValueProperties>,
+    traversal: &mut Vec<u32>,
+) -> Result<()> {
+    let mut maximum_id = computation_graph.keys().max().cloned().unwrap_or(0);
+    let mut bypassed_ids = Vec::new();
+
+    // visit dependencies first, so that a chain of clamps fuses into a single clamp
+    for node_id in get_traversal(computation_graph)? {
+        let component = computation_graph[&node_id].clone();
+        let arguments = component.arguments();
+        let data_id = match arguments.get::<IndexKey>(&"data".into()) {
+            Some(data_id) => *data_id,
+            None => continue
+        };
+
+        match component.variant.as_ref() {
+            Some(proto::component::Variant::Clamp(_)) => {
+                let (prior_id, lower, upper) = match fuse_clamp(computation_graph, release, properties, &arguments)? {
+                    Some(fused) => fused,
+                    None => continue
+                };
+
+                let mut component = component;
+                for (name, value) in [("lower", lower), ("upper", upper)] {
+                    maximum_id += 1;
+                    let (patch_node, release_node) = get_literal(value, component.submission)?;
+                    properties.insert(maximum_id, infer_property(&release_node.value, None, maximum_id)?);
+                    computation_graph.insert(maximum_id, patch_node);
+                    release.insert(maximum_id, release_node);
+                    component.insert_argument(&name.into(), maximum_id);
+                }
+                component.insert_argument(&"data".into(), prior_id);
+                computation_graph.insert(node_id, component);
+                bypassed_ids.push(data_id);
+            }
+            Some(proto::component::Variant::Impute(_)) => {
+                // imputing by categories may replace non-null values
+                if arguments.contains_key::<IndexKey>(&"categories".into()) {
+                    continue
+                }
+                let is_nullable = properties.get(&data_id)
+                    .and_then(|property| property.array().ok())
+                    .map(|property| property.nullity)
+                    .unwrap_or(true);
+                if is_nullable {
+                    continue
+                }
+
+                // dependents of the imputation read the data directly
+                computation_graph.values_mut()
+                    .filter_map(|component| component.arguments.as_mut())
+                    .for_each(|arguments| arguments.values.iter_mut()
+                        .filter(|argument_id| **argument_id == node_id)
+                        .for_each(|argument_id| *argument_id = data_id));
+                bypassed_ids.push(node_id);
+            }
+            _ => ()
+        }
+    }
+
+    // remove nodes that no longer contribute to the release
+    let mut dependents = get_dependents(computation_graph);
+    let mut removed_ids = HashSet::new();
+    while let Some(node_id) = bypassed_ids.pop() {
+        let is_unused = dependents.get(&node_id).map(HashSet::is_empty).unwrap_or(true);
+        let is_omitted = computation_graph.get(&node_id).map(|component| component.omit).unwrap_or(false);
+        if !is_unused || !is_omitted {
+            continue
+        }
+
+        let component = computation_graph.remove(&node_id).unwrap();
+        release.remove(&node_id);
+        properties.remove(&node_id);
+        removed_ids.insert(node_id);
+
+        component.arguments().values().for_each(|argument_id| {
+            if let Some(argument_dependents) = dependents.get_mut(argument_id) {
+                argument_dependents.remove(&node_id);
+            }
+            bypassed_ids.push(*argument_id);
+        });
+    }
+    traversal.retain(|node_id| !removed_ids.contains(node_id));
+
+    Ok(())
+}
+
+/// When a numeric clamp is applied to the output of another numeric clamp, intersect the bounds of the two clamps.
+///
+/// Clamping to overlapping intervals in sequence is equivalent to clamping to their intersection.
+/// Clamps with disjoint intervals, or without public bounds, are not fused.
+///
+/// # Returns
+/// The id of the data the prior clamp was applied to, and the intersected lower and upper bounds.
+fn fuse_clamp(
+    computation_graph: &HashMap<u32, proto::Component>,
+    release: &base::Release,
+    properties: &HashMap<u32, ValueProperties>,
+    arguments: &indexmap::IndexMap<IndexKey, u32>,
+) -> Result<Option<(u32, Value, Value)>> {
+    let prior = match computation_graph.get(&arguments[&IndexKey::from("data")]) {
+        Some(prior) if matches!(prior.variant, Some(proto::component::Variant::Clamp(_))) => prior,
+        _ => return Ok(None)
+    };
+    let prior_arguments = prior.arguments();
+
+    let get_bound = |arguments: &indexmap::IndexMap<IndexKey, u32>, name: &str| arguments
+        .get::<IndexKey>(&name.into())
+        .and_then(|node_id| release.get(node_id))
+        .filter(|release_node| release_node.public)
+        .and_then(|release_node| release_node.value.ref_array().ok());
+
+    let bounds = match (
+        get_bound(arguments, "lower"), get_bound(arguments, "upper"),
+        get_bound(&prior_arguments, "lower"), get_bound(&prior_arguments, "upper")) {
+        // categorical clamps are not fused
+        _ if arguments.contains_key::<IndexKey>(&"categories".into())
+            || prior_arguments.contains_key::<IndexKey>(&"categories".into()) => return Ok(None),
+        (Some(lower), Some(upper), Some(prior_lower), Some(prior_upper)) =>
+            (lower, upper, prior_lower, prior_upper),
+        _ => return Ok(None)
+    };
+
+    let prior_id = match prior_arguments.get::<IndexKey>(&"data".into()) {
+        Some(prior_id) => *prior_id,
+        None => return Ok(None)
+    };
+    let num_columns = match properties.get(&prior_id).and_then(|property| property.array().ok()) {
+        Some(property) => property.num_columns()?,
+        None => return Ok(None)
+    };
+
+    Ok(match bounds {
+        (Array::Int(lower), Array::Int(upper), Array::Int(prior_lower), Array::Int(prior_upper)) =>
+            intersect_bounds(
+                (lower.clone(), upper.clone()), (prior_lower.clone(), prior_upper.clone()), num_columns)?
+                .map(|(lower, upper)| (prior_id, lower.into(), upper.into())),
+        (lower, upper, prior_lower, prior_upper) =>
+            intersect_bounds(
+                (lower.clone().cast_float()?, upper.clone().cast_float()?),
+                (prior_lower.clone().cast_float()?, prior_upper.clone().cast_float()?), num_columns)?
+                .map(|(lower, upper)| (prior_id, lower.into(), upper.into())),
+    })
+}
+
+/// Intersect two sets of per-column intervals, or None if the intervals of any column are disjoint.
+fn intersect_bounds<T: Clone + PartialOrd>(
+    (lower, upper): (ArrayD<T>, ArrayD<T>),
+    (prior_lower, prior_upper): (ArrayD<T>, ArrayD<T>),
+    num_columns: i64,
+) -> Result<Option<(ArrayD<T>, ArrayD<T>)>> {
+    let mut lower = standardize_numeric_argument(lower, num_columns)?;
+    let mut upper = standardize_numeric_argument(upper, num_columns)?;
+
+    lower.iter_mut().zip(standardize_numeric_argument(prior_lower, num_columns)?.iter())
+        .for_each(|(lower, prior_lower)| if *prior_lower > *lower { *lower = prior_lower.clone() });
+    upper.iter_mut().zip(standardize_numeric_argument(prior_upper, num_columns)?.iter())
+        .for_each(|(upper, prior_upper)| if *prior_upper < *upper { *upper = prior_upper.clone() });
+
+    Ok(if lower.iter().zip(upper.iter()).all(|(lower, upper)| lower <= upper) {
+        Some((lower, upper))
+    } else { None })
+}
+
+#[cfg(test)]
+mod test_simplify {
+    use crate::base::{Array, IndexKey, test_data};
+    use crate::components::resize::test_resize;
+    use crate::proto;
+    use crate::utilities::{get_traversal, propagate_properties};
+    use crate::utilities::simplify::simplify_graph;
+
+    #[test]
+    fn test_fuse_clamps() {
+        let (mut analysis, resized) = test_resize::utilities::analysis_f64_cont(
+            test_data::array1d_f64_10_uniform(), 10.into(), Some(0.0.into()), Some(10.0.into()));
+
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(10.0.into()).value_public(true).build();
+        let outer = analysis.clamp(resized).lower(lower).upper(upper).build();
+        // as if the outer clamp were introduced by an expansion
+        analysis.components.get_mut(&outer).unwrap().omit = true;
+
+        let lower = analysis.literal().value(2.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(8.0.into()).value_public(true).build();
+        let inner = analysis.clamp(outer).lower(lower).upper(upper).build();
+
+        let mut computation_graph = analysis.components.clone();
+        let mut release = analysis.release.clone();
+        let (mut properties, _) = propagate_properties(
+            &Some(analysis.privacy_definition.clone()),
+            &mut computation_graph, &mut release, None, false).unwrap();
+        let mut traversal = get_traversal(&computation_graph).unwrap();
+
+        simplify_graph(&mut computation_graph, &mut release, &mut properties, &mut traversal).unwrap();
+
+        // only the inner clamp remains, applied directly to the data
+        assert!(!computation_graph.contains_key(&outer));
+        assert!(!traversal.contains(&outer));
+        assert!(matches!(computation_graph[&inner].variant, Some(proto::component::Variant::Clamp(_))));
+
+        let arguments = computation_graph[&inner].arguments();
+        assert_eq!(arguments[&IndexKey::from("data")], resized);
+        let bound = |name: &str| match release[&arguments[&IndexKey::from(name)]].value.ref_array().unwrap() {
+            Array::Float(bound) => bound.iter().copied().collect::<Vec<f64>>(),
+            _ => panic!("bounds must be float")
+        };
+        assert_eq!(bound("lower"), vec![2.]);
+        assert_eq!(bound("upper"), vec![8.]);
+    }
+}
@@ -0,0 +1,193 @@
+//! Check the atomic types of arguments before expansion
+//!
+//! Property propagation stops at the first invalid component, deep within the expanded graph.
+//! Atomic types are instead inferred over the graph as submitted, so that every mismatch may be reported at once.
+
+use std::collections::HashMap;
+
+use indexmap::map::IndexMap;
+
+use crate::{base, proto};
+use crate::base::{Array, DataType, IndexKey, Value};
+use crate::errors::*;
+use crate::utilities::get_traversal;
+
+/// Check that the atomic type of every argument matches the expectations of the component it is passed to.
+///
+/// Atomic types are known for literals and released values, and are inferred forward through the graph.
+/// Arguments of unknown atomic type, like columns of a materialized dataset, are not checked.
+/// An error lists every mismatch, along with the node id of the component the mismatch was found at.
+pub fn check_types(
+    computation_graph: &HashMap<u32, proto::Component>,
+    release: &base::Release,
+) -> Result<()> {
+    let mut data_types: HashMap<u32, DataType> = HashMap::new();
+    let mut mismatches = Vec::new();
+
+    for node_id in get_traversal(computation_graph)? {
+        let component = &computation_graph[&node_id];
+        let variant = match component.variant.as_ref() {
+            Some(variant) => variant,
+            None => continue
+        };
+
+        let argument_types = component.arguments().into_iter()
+            .map(|(name, argument_id)| (name, data_types.get(&argument_id).cloned().unwrap_or(DataType::Unknown)))
+            .collect::<IndexMap<IndexKey, DataType>>();
+
+        mismatches.extend(check_arguments(variant, &argument_types).into_iter()
+            .map(|mismatch| format!("node {}: {}", node_id, mismatch)));
+
+        let data_type = match release.get(&node_id) {
+            Some(release_node) => get_data_type(&release_node.value),
+            None => infer_data_type(variant, &argument_types)
+        };
+        data_types.insert(node_id, data_type);
+    }
+
+    if mismatches.is_empty() {
+        return Ok(())
+    }
+    bail!("{} type mismatches:\n{}", mismatches.len(), mismatches.join("\n"))
+}
+
+/// Atomic type of a value, if it is an array.
+fn get_data_type(value: &Value) -> DataType {
+    match value {
+        Value::Array(Array::Bool(_)) => DataType::Bool,
+        Value::Array(Array::Float(_)) => DataType::Float,
+        Value::Array(Array::Int(_)) => DataType::Int,
+        Value::Array(Array::Str(_)) => DataType::Str,
+        _ => DataType::Unknown
+    }
+}
+
+/// Describe each argument whose atomic type does not match the expectations of the component.
+fn check_arguments(variant: &proto::component::Variant, argument_types: &IndexMap<IndexKey, DataType>) -> Vec<String> {
+    use proto::component::Variant;
+
+    let get = |name: &str| argument_types.get::<IndexKey>(&name.into())
+        .cloned().unwrap_or(DataType::Unknown);
+
+    let is_type = |name: &str, expected: &[DataType]| match get(name) {
+        DataType::Unknown => None,
+        data_type if expected.contains(&data_type) => None,
+        data_type => Some(format!("{}: atomic type must be one of {:?}, but is {:?}", name, expected, data_type))
+    };
+    let numeric = |name: &str| is_type(name, &[DataType::Float, DataType::Int]);
+    let same = |left: &str, right: &str| match (get(left), get(right)) {
+        (DataType::Unknown, _) | (_, DataType::Unknown) => None,
+        (left_type, right_type) if left_type == right_type => None,
+        (left_type, right_type) => Some(format!(
+            "{} and {} must share the same atomic type, but are {:?} and {:?}", left, right, left_type, right_type))
+    };
+    let has = |name: &str| argument_types.contains_key::<IndexKey>(&name.into());
+
+    let checks = match variant {
+        Variant::Add(_) | Variant::Subtract(_) | Variant::Multiply(_) | Variant::Divide(_) | Variant::Modulo(_) |
+        Variant::GreaterThan(_) | Variant::LessThan(_) =>
+            vec![numeric("left"), numeric("right"), same("left", "right")],
+        Variant::RowMax(_) | Variant::RowMin(_) | Variant::Equal(_) =>
+            vec![same("left", "right")],
+        Variant::LogicalAnd(_) | Variant::LogicalOr(_) =>
+            vec![is_type("left", &[DataType::Bool]), is_type("right", &[DataType::Bool])],
+        Variant::Abs(_) | Variant::Negative(_) | Variant::Sum(_) =>
+            vec![numeric("data")],
+        Variant::Negate(_) =>
+            vec![is_type("data", &[DataType::Bool, DataType::Float, DataType::Int])],
+        Variant::Power(_) =>
+            vec![numeric("data"), numeric("radical")],
+        Variant::Log(_) =>
+            vec![is_type("data", &[DataType::Float])],
+        Variant::Filter(_) =>
+            vec![is_type("mask", &[DataType::Bool])],
+        // clamping to an interval requires numeric data, while clamping to categories does not
+        Variant::Clamp(_) if !has("categories") =>
+            vec![numeric("data"), same("data", "lower"), same("data", "upper")],
+        Variant::Impute(_) if !has("categories") =>
+            vec![numeric("data"), same("data", "lower"), same("data", "upper")],
+        Variant::Mean(_) | Variant::Variance(_) | Variant::DpMean(_) | Variant::DpVariance(_) |
+        Variant::DpRawMoment(_) | Variant::DpSkewness(_) | Variant::DpConditionalSum(_) | Variant::DpWeightedSum(_) =>
+            vec![is_type("data", &[DataType::Float])],
+        Variant::DpSum(_) | Variant::DpMedian(_) | Variant::DpQuantile(_) | Variant::DpMinimum(_) | Variant::DpMaximum(_) =>
+            vec![numeric("data")],
+        _ => vec![]
+    };
+    checks.into_iter().flatten().collect()
+}
+
+/// Atomic type of the output of a component, given the atomic types of its arguments.
+fn infer_data_type(variant: &proto::component::Variant, argument_types: &IndexMap<IndexKey, DataType>) -> DataType {
+    use proto::component::Variant;
+
+    let get = |name: &str| argument_types.get::<IndexKey>(&name.into())
+        .cloned().unwrap_or(DataType::Unknown);
+
+    match variant {
+        Variant::Cast(cast) => match cast.atomic_type.to_lowercase().as_str() {
+            "float" | "real" => DataType::Float,
+            "int" | "integer" => DataType::Int,
+            "bool" => DataType::Bool,
+            "string" | "str" => DataType::Str,
+            _ => DataType::Unknown
+        },
+        Variant::ToFloat(_) | Variant::Log(_) | Variant::Mean(_) | Variant::Variance(_) | Variant::DpMean(_) |
+        Variant::DpVariance(_) | Variant::DpRawMoment(_) | Variant::DpSkewness(_) | Variant::DpConditionalSum(_) |
+        Variant::DpWeightedSum(_) => DataType::Float,
+        Variant::ToInt(_) | Variant::Count(_) | Variant::DpCount(_) | Variant::DpHistogram(_) => DataType::Int,
+        Variant::ToBool(_) | Variant::LogicalAnd(_) | Variant::LogicalOr(_) | Variant::Equal(_) |
+        Variant::GreaterThan(_) | Variant::LessThan(_) => DataType::Bool,
+        Variant::ToString(_) => DataType::Str,
+        Variant::Add(_) | Variant::Subtract(_) | Variant::Multiply(_) | Variant::Divide(_) | Variant::Modulo(_) |
+        Variant::RowMax(_) | Variant::RowMin(_) => get("left"),
+        Variant::Abs(_) | Variant::Negate(_) | Variant::Negative(_) | Variant::Clamp(_) | Variant::Impute(_) |
+        Variant::Filter(_) | Variant::Resize(_) | Variant::Sum(_) | Variant::DpSum(_) | Variant::DpMedian(_) |
+        Variant::DpQuantile(_) | Variant::DpMinimum(_) | Variant::DpMaximum(_) => get("data"),
+        _ => DataType::Unknown
+    }
+}
+
+#[cfg(test)]
+mod test_typecheck {
+    use ndarray::arr1;
+
+    use crate::bindings::Analysis;
+    use crate::utilities::typecheck::check_types;
+
+    #[test]
+    fn test_multiple_type_errors() {
+        let mut analysis = Analysis::new();
+        let numbers = analysis.literal()
+            .value(arr1(&[1., 2., 3.]).into_dyn().into())
+            .value_public(true).build();
+        let words = analysis.literal()
+            .value(arr1(&["a".to_string(), "b".to_string(), "c".to_string()]).into_dyn().into())
+            .value_public(true).build();
+        let flags = analysis.literal()
+            .value(arr1(&[true, false, true]).into_dyn().into())
+            .value_public(true).build();
+        let lower = analysis.literal().value(0.0.into()).value_public(true).build();
+        let upper = analysis.literal().value(1.0.into()).value_public(true).build();
+
+        // a well-typed comparison passes
+        let comparison = analysis.greater_than(numbers, lower).build();
+        check_types(&analysis.components, &analysis.release).unwrap();
+
+        // comparing strings to floats, and clamping booleans, are both reported
+        let invalid_comparison = analysis.greater_than(words, numbers).build();
+        let invalid_clamp = analysis.clamp(flags).lower(lower).upper(upper).build();
+        // the output of a comparison is boolean, so it may be combined by a conjunction
+        analysis.logical_and(comparison, flags).build();
+
+        let error = check_types(&analysis.components, &analysis.release).unwrap_err().to_string();
+        assert!(error.contains(&format!("node {}: left", invalid_comparison)), "{}", error);
+        assert!(error.contains(&format!("node {}: data", invalid_clamp)), "{}", error);
+
+        // the pass runs before expansion when validating, and reports every mismatch
+        let error = crate::validate_analysis(
+            Some(analysis.privacy_definition.clone()),
+            analysis.components.clone(),
+            analysis.release.clone()).unwrap_err().to_string();
+        assert!(error.contains(&format!("node {}", invalid_comparison)) && error.contains(&format!("node {}", invalid_clamp)));
+    }
+}